@@ -1,3 +1,5 @@
 fn main() {
+    // 未启用 gui 特性时不依赖 tauri，无需生成应用上下文与权限文件
+    #[cfg(feature = "gui")]
     tauri_build::build()
 }
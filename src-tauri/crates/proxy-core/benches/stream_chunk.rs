@@ -0,0 +1,73 @@
+// 流式分片解析基准
+//
+// `cargo bench -p antigravity-proxy-core --bench stream_chunk`
+//
+// 对比 OpenAI Chat 流式转换在大参数工具调用分片上的两种解析方式：
+// - value: 整个分片先解析为 `Value`，工具调用参数再序列化为字符串 (原实现)
+// - borrowed: `ChatChunkConverter` 借用原始 JSON，参数原样输出
+
+use antigravity_proxy_core::mappers::openai::streaming::ChatChunkConverter;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Value};
+
+/// 构造带 v1internal 包装、参数约为 `kb` KB 的工具调用分片
+fn tool_call_chunk(kb: usize) -> String {
+    let lines: Vec<Value> = (0..kb * 8)
+        .map(|i| json!({ "line": i, "text": "let value = compute(input, \"quoted\");", "tags": ["a", "b"] }))
+        .collect();
+    let chunk = json!({
+        "response": {
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [{
+                        "functionCall": {
+                            "name": "apply_patch",
+                            "args": { "path": "src/main.rs", "edits": lines }
+                        },
+                        "thoughtSignature": "c2lnbmF0dXJl"
+                    }]
+                }
+            }],
+            "modelVersion": "gemini-3-pro"
+        }
+    });
+    serde_json::to_string(&chunk).unwrap()
+}
+
+/// 原实现的解析路径: 构建 Value 树，工具调用去重键、ID 与参数各序列化一次
+fn convert_with_value(payload: &str) -> usize {
+    let mut json: Value = serde_json::from_str(payload).unwrap();
+    let data = json.get_mut("response").map(|v| v.take()).unwrap_or(json);
+    let mut written = 0;
+    for candidate in data["candidates"].as_array().into_iter().flatten() {
+        for part in candidate["content"]["parts"].as_array().into_iter().flatten() {
+            if let Some(func_call) = part.get("functionCall") {
+                let call_key = serde_json::to_string(func_call).unwrap();
+                let id_source = serde_json::to_string(func_call).unwrap();
+                let args = func_call.get("args").unwrap_or(&json!({})).to_string();
+                written += call_key.len() + id_source.len() + args.len();
+            }
+        }
+    }
+    written
+}
+
+fn bench_tool_call_chunks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("openai_stream_tool_call");
+    for kb in [4, 64, 512] {
+        let payload = tool_call_chunk(kb);
+        group.throughput(Throughput::Bytes(payload.len() as u64));
+        group.bench_with_input(BenchmarkId::new("value", kb), &payload, |b, payload| {
+            b.iter(|| convert_with_value(black_box(payload)))
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", kb), &payload, |b, payload| {
+            // 每次迭代使用新的转换器，避免工具调用去重跳过输出
+            b.iter(|| ChatChunkConverter::new("gemini-3-pro".to_string()).convert(black_box(payload)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tool_call_chunks);
+criterion_main!(benches);
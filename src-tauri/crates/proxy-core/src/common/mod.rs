@@ -1,7 +1,5 @@
 // Common 模块 - 公共工具
 
-// pub mod error;
-// pub mod rate_limiter;
 pub mod model_mapping;
 pub mod utils;
 pub mod json_schema;
@@ -70,6 +70,11 @@ pub fn map_claude_model_to_gemini(input: &str) -> String {
     "claude-sonnet-4-5".to_string()
 }
 
+/// 是否为已知可直接发往上游的模型 (内置映射的键与目标，以及 gemini- 前缀的模型)
+pub fn is_known_model(model: &str) -> bool {
+    model.starts_with("gemini-") || CLAUDE_TO_GEMINI.contains_key(model) || CLAUDE_TO_GEMINI.values().any(|m| *m == model)
+}
+
 /// 获取所有内置支持的模型列表关键字
 pub fn get_supported_models() -> Vec<String> {
     CLAUDE_TO_GEMINI.keys().map(|s| s.to_string()).collect()
@@ -101,7 +106,7 @@ pub async fn get_all_dynamic_models(
     // [NEW] Issue #247: Dynamically generate all Image Gen Combinations
     let base = "gemini-3-pro-image";
     let resolutions = vec!["", "-2k", "-4k"];
-    let ratios = vec!["", "-1x1", "-4x3", "-3x4", "-16x9", "-9x16", "-21x9"];
+    let ratios = ["", "-1x1", "-4x3", "-3x4", "-16x9", "-9x16", "-21x9"];
     
     for res in resolutions {
         for ratio in ratios.iter() {
@@ -132,7 +137,7 @@ pub async fn get_all_dynamic_models(
 /// - `gpt-4*` 匹配 `gpt-4`, `gpt-4-turbo`, `gpt-4-0613` 等
 /// - `claude-3-5-sonnet-*` 匹配所有 3.5 sonnet 版本
 /// - `*-thinking` 匹配所有以 `-thinking` 结尾的模型
-fn wildcard_match(pattern: &str, text: &str) -> bool {
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
     if let Some(star_pos) = pattern.find('*') {
         let prefix = &pattern[..star_pos];
         let suffix = &pattern[star_pos + 1..];
@@ -157,14 +162,14 @@ pub fn resolve_model_route(
 ) -> String {
     // 1. 精确匹配 (最高优先级)
     if let Some(target) = custom_mapping.get(original_model) {
-        crate::modules::logger::log_info(&format!("[Router] 精确映射: {} -> {}", original_model, target));
+        tracing::info!("[Router] 精确映射: {} -> {}", original_model, target);
         return target.clone();
     }
     
     // 2. 通配符匹配
     for (pattern, target) in custom_mapping.iter() {
         if pattern.contains('*') && wildcard_match(pattern, original_model) {
-            crate::modules::logger::log_info(&format!("[Router] 通配符映射: {} -> {} (规则: {})", original_model, target, pattern));
+            tracing::info!("[Router] 通配符映射: {} -> {} (规则: {})", original_model, target, pattern);
             return target.clone();
         }
     }
@@ -172,7 +177,7 @@ pub fn resolve_model_route(
     // 3. 系统默认映射
     let result = map_claude_model_to_gemini(original_model);
     if result != original_model {
-        crate::modules::logger::log_info(&format!("[Router] 系统默认映射: {} -> {}", original_model, result));
+        tracing::info!("[Router] 系统默认映射: {} -> {}", original_model, result);
     }
     result
 }
@@ -0,0 +1,24 @@
+//! Antigravity 反代核心
+//!
+//! OpenAI / Claude / Gemini 协议与 v1internal 之间的请求 / 响应 / 流式转换、模型映射、
+//! JSON Schema 清洗、会话指纹、thinking 签名缓存、限流跟踪与调度配置、账号池，
+//! 以及 v1internal 上游客户端 (连接池、重试、流式续传、TLS 校验)。
+//! 不依赖 Tauri 和管理器的账号 / 配置模块，可以嵌入其他程序复用。
+//!
+//! 账号池 [`token_pool::TokenPool`] 负责为每个请求选出账号: 粘性会话、选择策略与优先级、
+//! 账号池 / 标签 / 可用时段过滤、并发槽位、限流冷却，以及请求路径和启动时的 Token 刷新。
+//! 账号的持久化、OAuth 刷新与 project_id 解析由宿主程序实现 [`token_pool::AccountStore`] 提供。
+//! HTTP 服务仍在 antigravity_tools 中，它组合了认证、监控等依赖应用数据目录的中间件。
+
+pub mod common;            // 公共工具
+pub mod mappers;           // 协议转换器
+pub mod rate_limit;        // 限流跟踪 (账号冷却)
+pub mod rotation;          // 账号轮换规则
+pub mod session_manager;   // 会话指纹管理
+pub mod signature_cache;   // Signature Cache (v3.3.16)
+pub mod sticky_config;     // 调度模式与粘性会话配置
+pub mod token_pool;        // 账号池 (选择账号与 Token 刷新)
+pub mod upstream;          // 上游客户端
+
+pub use signature_cache::SignatureCache;
+pub use token_pool::{AccountStore, TokenPool};
@@ -18,6 +18,8 @@ pub use collector::collect_stream_to_json;
 
 use bytes::Bytes;
 use futures::Stream;
+use serde::Deserialize;
+use serde_json::value::RawValue;
 use std::pin::Pin;
 
 /// 创建从 Gemini SSE 流到 Claude SSE 流的转换
@@ -89,6 +91,38 @@ pub fn create_claude_sse_stream(
     })
 }
 
+/// 流式分片中用到的字段
+///
+/// 直接反序列化，避免先构建完整的 `Value` 再逐个 `from_value(clone)`；
+/// parts 保留为借用的原始 JSON，单个 part 解析失败时只跳过该 part。
+#[derive(Deserialize)]
+struct StreamChunk<'a> {
+    #[serde(rename = "responseId", default)]
+    response_id: Option<String>,
+    #[serde(rename = "modelVersion", default)]
+    model_version: Option<String>,
+    #[serde(rename = "usageMetadata", default)]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(borrow, default)]
+    candidates: Option<Vec<StreamCandidate<'a>>>,
+}
+
+#[derive(Deserialize)]
+struct StreamCandidate<'a> {
+    #[serde(borrow, default)]
+    content: Option<StreamContent<'a>>,
+    #[serde(rename = "finishReason", default)]
+    finish_reason: Option<String>,
+    #[serde(rename = "groundingMetadata", default)]
+    grounding_metadata: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StreamContent<'a> {
+    #[serde(borrow, default)]
+    parts: Option<Vec<&'a RawValue>>,
+}
+
 /// 处理单行 SSE 数据
 fn process_sse_line(line: &str, state: &mut StreamingState, trace_id: &str, email: &str) -> Option<Vec<Bytes>> {
     if !line.starts_with("data: ") {
@@ -108,53 +142,56 @@ fn process_sse_line(line: &str, state: &mut StreamingState, trace_id: &str, emai
         return Some(chunks);
     }
 
-    // 解析 JSON
-    let json_value: serde_json::Value = match serde_json::from_str(data_str) {
-        Ok(v) => v,
+    // 解包 response 字段 (如果存在)：只定位字节范围，不构建 Value
+    let payload = match crate::mappers::gemini::response_span(data_str.as_bytes()) {
+        Ok(Some(span)) => &data_str[span],
+        Ok(None) => data_str,
         Err(_) => return None,
     };
+    // 直接反序列化为流式分片结构，parts 借用原始 JSON 逐个解析
+    let raw_json: StreamChunk = match serde_json::from_str(payload) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::debug!("[{}] Skipping malformed stream chunk: {}", trace_id, e);
+            return None;
+        }
+    };
+    let candidate = raw_json.candidates.as_ref().and_then(|c| c.first());
 
     let mut chunks = Vec::new();
 
-    // 解包 response 字段 (如果存在)
-    let raw_json = json_value.get("response").unwrap_or(&json_value);
-
     // 发送 message_start
     if !state.message_start_sent {
-        chunks.push(state.emit_message_start(raw_json));
+        chunks.push(state.emit_message_start(
+            raw_json.response_id.as_deref(),
+            raw_json.model_version.as_deref(),
+            raw_json.usage_metadata.as_ref(),
+        ));
     }
 
     // 捕获 groundingMetadata (Web Search)
-    if let Some(candidate) = raw_json.get("candidates").and_then(|c| c.get(0)) {
-        if let Some(grounding) = candidate.get("groundingMetadata") {
-            // 提取搜索词
-            if let Some(query) = grounding.get("webSearchQueries")
-                .and_then(|v| v.as_array())
-                .and_then(|arr| arr.get(0))
-                .and_then(|v| v.as_str())
-            {
-                state.web_search_query = Some(query.to_string());
-            }
+    if let Some(grounding) = candidate.and_then(|c| c.grounding_metadata.as_ref()) {
+        // 提取搜索词
+        if let Some(query) = grounding.get("webSearchQueries")
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|v| v.as_str())
+        {
+            state.web_search_query = Some(query.to_string());
+        }
 
-            // 提取结果块
-            if let Some(chunks_arr) = grounding.get("groundingChunks").and_then(|v| v.as_array()) {
-                state.grounding_chunks = Some(chunks_arr.clone());
-            } else if let Some(chunks_arr) = grounding.get("grounding_metadata").and_then(|m| m.get("groundingChunks")).and_then(|v| v.as_array()) {
-                state.grounding_chunks = Some(chunks_arr.clone());
-            }
+        // 提取结果块
+        if let Some(chunks_arr) = grounding.get("groundingChunks").and_then(|v| v.as_array()) {
+            state.grounding_chunks = Some(chunks_arr.clone());
+        } else if let Some(chunks_arr) = grounding.get("grounding_metadata").and_then(|m| m.get("groundingChunks")).and_then(|v| v.as_array()) {
+            state.grounding_chunks = Some(chunks_arr.clone());
         }
     }
 
     // 处理所有 parts
-    if let Some(parts) = raw_json
-        .get("candidates")
-        .and_then(|c| c.get(0))
-        .and_then(|cand| cand.get("content"))
-        .and_then(|content| content.get("parts"))
-        .and_then(|p| p.as_array())
-    {
+    if let Some(parts) = candidate.and_then(|c| c.content.as_ref()).and_then(|content| content.parts.as_ref()) {
         for part_value in parts {
-            if let Ok(part) = serde_json::from_value::<GeminiPart>(part_value.clone()) {
+            if let Ok(part) = serde_json::from_str::<GeminiPart>(part_value.get()) {
                 let mut processor = PartProcessor::new(state);
                 chunks.extend(processor.process(&part));
             }
@@ -179,17 +216,10 @@ fn process_sse_line(line: &str, state: &mut StreamingState, trace_id: &str, emai
     */
 
     // 检查是否结束
-    if let Some(finish_reason) = raw_json
-        .get("candidates")
-        .and_then(|c| c.get(0))
-        .and_then(|cand| cand.get("finishReason"))
-        .and_then(|f| f.as_str())
-    {
-        let usage = raw_json
-            .get("usageMetadata")
-            .and_then(|u| serde_json::from_value::<UsageMetadata>(u.clone()).ok());
+    if let Some(finish_reason) = candidate.and_then(|c| c.finish_reason.as_deref()) {
+        let usage = raw_json.usage_metadata.as_ref();
 
-        if let Some(ref u) = usage {
+        if let Some(u) = usage {
             let cached_tokens = u.cached_content_token_count.unwrap_or(0);
             let cache_info = if cached_tokens > 0 {
                 format!(", Cached: {}", cached_tokens)
@@ -207,7 +237,7 @@ fn process_sse_line(line: &str, state: &mut StreamingState, trace_id: &str, emai
              );
         }
 
-        chunks.extend(state.emit_finish(Some(finish_reason), usage.as_ref()));
+        chunks.extend(state.emit_finish(Some(finish_reason), usage));
     }
 
     if chunks.is_empty() {
@@ -256,7 +286,7 @@ fn process_grounding_metadata(
     // Generate a unique tool_use_id
     let tool_use_id = format!(
         "srvtoolu_{}",
-        crate::proxy::common::utils::generate_random_id()
+        crate::common::utils::generate_random_id()
     );
 
     // Build search results array
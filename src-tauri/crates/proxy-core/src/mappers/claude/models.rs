@@ -48,6 +48,7 @@ pub enum SystemPrompt {
     Array(Vec<SystemBlock>),
 }
 
+/// System Prompt 文本块
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemBlock {
     #[serde(rename = "type")]
@@ -62,6 +63,7 @@ pub struct Message {
     pub content: MessageContent,
 }
 
+/// 消息内容: 纯文本或内容块数组
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum MessageContent {
@@ -137,6 +139,7 @@ pub enum ContentBlock {
     },
 }
 
+/// 图片来源 (base64)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageSource {
     #[serde(rename = "type")]
@@ -145,6 +148,7 @@ pub struct ImageSource {
     pub data: String,
 }
 
+/// 文档来源 (如 base64 编码的 PDF)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentSource {
     #[serde(rename = "type")]
@@ -282,6 +286,7 @@ pub struct GeminiPart {
     pub inline_data: Option<InlineData>,
 }
 
+/// Gemini 函数调用
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionCall {
     pub name: String,
@@ -291,6 +296,7 @@ pub struct FunctionCall {
     pub args: Option<serde_json::Value>,
 }
 
+/// Gemini 函数调用结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionResponse {
     pub name: String,
@@ -299,6 +305,7 @@ pub struct FunctionResponse {
     pub id: Option<String>,
 }
 
+/// Gemini 内联数据 (图片、文档等)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InlineData {
     #[serde(rename = "mimeType")]
@@ -322,6 +329,7 @@ pub struct GeminiResponse {
     pub response_id: Option<String>,
 }
 
+/// Gemini 响应候选
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candidate {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -336,6 +344,7 @@ pub struct Candidate {
     pub grounding_metadata: Option<GroundingMetadata>,
 }
 
+/// Gemini Token 用量
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -374,12 +383,14 @@ pub struct GroundingMetadata {
     pub search_entry_point: Option<SearchEntryPoint>,
 }
 
+/// Grounding 来源条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroundingChunk {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub web: Option<WebSource>,
 }
 
+/// 网页来源
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSource {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -388,6 +399,7 @@ pub struct WebSource {
     pub title: Option<String>,
 }
 
+/// 回答片段与来源条目的对应关系
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroundingSupport {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -400,6 +412,7 @@ pub struct GroundingSupport {
     pub confidence_scores: Option<Vec<f64>>,
 }
 
+/// 回答中的文本片段
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextSegment {
     #[serde(rename = "startIndex")]
@@ -412,6 +425,7 @@ pub struct TextSegment {
     pub text: Option<String>,
 }
 
+/// 搜索入口 (渲染后的 HTML)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchEntryPoint {
     #[serde(rename = "renderedContent")]
@@ -2,9 +2,9 @@
 // 对应 transformClaudeRequestIn
 
 use super::models::*;
-use crate::proxy::mappers::signature_store::get_thought_signature; // Deprecated, kept for fallback
-use crate::proxy::mappers::tool_result_compressor;
-use crate::proxy::session_manager::SessionManager;
+use crate::mappers::signature_store::get_thought_signature; // Deprecated, kept for fallback
+use crate::mappers::tool_result_compressor;
+use crate::session_manager::SessionManager;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
@@ -86,49 +86,41 @@ fn clean_cache_control_from_messages(messages: &mut [Message]) {
         if let MessageContent::Array(blocks) = &mut msg.content {
             for (block_idx, block) in blocks.iter_mut().enumerate() {
                 match block {
-                    ContentBlock::Thinking { cache_control, .. } => {
-                        if cache_control.is_some() {
-                            tracing::warn!(
-                                "[DEBUG-593] Found cache_control in Thinking block at message[{}].content[{}]",
-                                idx,
-                                block_idx
-                            );
-                            *cache_control = None;
-                            total_cleaned += 1;
-                        }
+                    ContentBlock::Thinking { cache_control, .. } if cache_control.is_some() => {
+                        tracing::warn!(
+                            "[DEBUG-593] Found cache_control in Thinking block at message[{}].content[{}]",
+                            idx,
+                            block_idx
+                        );
+                        *cache_control = None;
+                        total_cleaned += 1;
                     }
-                    ContentBlock::Image { cache_control, .. } => {
-                        if cache_control.is_some() {
-                            tracing::debug!(
-                                "[Cache-Control-Cleaner] Removed cache_control from Image block at message[{}].content[{}]",
-                                idx,
-                                block_idx
-                            );
-                            *cache_control = None;
-                            total_cleaned += 1;
-                        }
+                    ContentBlock::Image { cache_control, .. } if cache_control.is_some() => {
+                        tracing::debug!(
+                            "[Cache-Control-Cleaner] Removed cache_control from Image block at message[{}].content[{}]",
+                            idx,
+                            block_idx
+                        );
+                        *cache_control = None;
+                        total_cleaned += 1;
                     }
-                    ContentBlock::Document { cache_control, .. } => {
-                        if cache_control.is_some() {
-                            tracing::debug!(
-                                "[Cache-Control-Cleaner] Removed cache_control from Document block at message[{}].content[{}]",
-                                idx,
-                                block_idx
-                            );
-                            *cache_control = None;
-                            total_cleaned += 1;
-                        }
+                    ContentBlock::Document { cache_control, .. } if cache_control.is_some() => {
+                        tracing::debug!(
+                            "[Cache-Control-Cleaner] Removed cache_control from Document block at message[{}].content[{}]",
+                            idx,
+                            block_idx
+                        );
+                        *cache_control = None;
+                        total_cleaned += 1;
                     }
-                    ContentBlock::ToolUse { cache_control, .. } => {
-                        if cache_control.is_some() {
-                            tracing::debug!(
-                                "[Cache-Control-Cleaner] Removed cache_control from ToolUse block at message[{}].content[{}]",
-                                idx,
-                                block_idx
-                            );
-                            *cache_control = None;
-                            total_cleaned += 1;
-                        }
+                    ContentBlock::ToolUse { cache_control, .. } if cache_control.is_some() => {
+                        tracing::debug!(
+                            "[Cache-Control-Cleaner] Removed cache_control from ToolUse block at message[{}].content[{}]",
+                            idx,
+                            block_idx
+                        );
+                        *cache_control = None;
+                        total_cleaned += 1;
                     }
                     _ => {}
                 }
@@ -227,7 +219,7 @@ fn sort_thinking_blocks_first(messages: &mut [Message]) {
 }
 
 /// 转换 Claude 请求为 Gemini v1internal 格式
-
+#[tracing::instrument(name = "convert.claude_request", skip_all, fields(model = %claude_req.model))]
 pub fn transform_claude_request_in(
     claude_req: &ClaudeRequest,
     project_id: &str,
@@ -290,7 +282,7 @@ pub fn transform_claude_request_in(
         );
         WEB_SEARCH_FALLBACK_MODEL.to_string()
     } else {
-        crate::proxy::common::model_mapping::map_claude_model_to_gemini(&claude_req.model)
+        crate::common::model_mapping::map_claude_model_to_gemini(&claude_req.model)
     };
     
     // 将 Claude 工具转为 Value 数组以便探测联网
@@ -300,7 +292,7 @@ pub fn transform_claude_request_in(
 
 
     // Resolve grounding config
-    let config = crate::proxy::mappers::common_utils::resolve_request_config(&claude_req.model, &mapped_model, &tools_val);
+    let config = crate::mappers::common_utils::resolve_request_config(&claude_req.model, &mapped_model, &tools_val);
     
     // [CRITICAL FIX] Disable dummy thought injection for Vertex AI
     // [CRITICAL FIX] Disable dummy thought injection for Vertex AI
@@ -418,7 +410,7 @@ pub fn transform_claude_request_in(
     });
 
     // 深度清理 [undefined] 字符串 (Cherry Studio 等客户端常见注入)
-    crate::proxy::mappers::common_utils::deep_clean_undefined(&mut inner_request);
+    crate::mappers::common_utils::deep_clean_undefined(&mut inner_request);
 
     if let Some(sys_inst) = system_instruction {
         inner_request["systemInstruction"] = sys_inst;
@@ -440,7 +432,7 @@ pub fn transform_claude_request_in(
 
     // Inject googleSearch tool if needed (and not already done by build_tools)
     if config.inject_google_search && !has_web_search_tool {
-        crate::proxy::mappers::common_utils::inject_google_search_tool(&mut inner_request);
+        crate::mappers::common_utils::inject_google_search_tool(&mut inner_request);
     }
 
     // Inject imageConfig if present (for image generation models)
@@ -699,7 +691,7 @@ fn build_contents(
         }
     }
 
-    for (_i, msg) in messages.iter().enumerate() {
+    for msg in messages.iter() {
         let role = if msg.role == "assistant" {
             // Proactive Tool Chain Repair:
             // If we are about to process an Assistant message, but we still have pending tool_use_ids,
@@ -744,10 +736,8 @@ fn build_contents(
 
         match &msg.content {
             MessageContent::String(text) => {
-                if text != "(no content)" {
-                    if !text.trim().is_empty() {
-                        parts.push(json!({"text": text.trim()}));
-                    }
+                if text != "(no content)" && !text.trim().is_empty() {
+                    parts.push(json!({"text": text.trim()}));
                 }
             }
             MessageContent::Array(blocks) => {
@@ -818,7 +808,7 @@ fn build_contents(
                                 "thought": true, // [CRITICAL FIX] Vertex AI v1internal requires thought: true to distinguish from text
                             });
                             // [New] 递归清理黑名单字段（如 cache_control）
-                            crate::proxy::common::json_schema::clean_json_schema(&mut part);
+                            crate::common::json_schema::clean_json_schema(&mut part);
 
                             // [CRITICAL FIX] Do NOT add skip_thought_signature_validator for Vertex AI
                             // If no signature, the block should have been filtered out
@@ -829,9 +819,9 @@ fn build_contents(
                             if let Some(sig) = signature {
                                 // [NEW] Cross-Model Compatibility Check
                                 // Verify if the signature belongs to a compatible model family
-                                let cached_family = crate::proxy::SignatureCache::global().get_signature_family(sig);
+                                let cached_family = crate::SignatureCache::global().get_signature_family(sig);
                                 if let Some(family) = cached_family {
-                                    if !is_model_compatible(&family, &mapped_model) {
+                                    if !is_model_compatible(&family, mapped_model) {
                                         tracing::warn!(
                                             "[Thinking-Compatibility] Incompatible signature detected (Family: {}, Target: {}). Dropping signature.",
                                             family, mapped_model
@@ -894,7 +884,7 @@ fn build_contents(
                             }
                             
                             // [New] 递归清理参数中可能存在的非法校验字段
-                            crate::proxy::common::json_schema::clean_json_schema(&mut part);
+                            crate::common::json_schema::clean_json_schema(&mut part);
 
                             // 存储 id -> name 映射
                             tool_id_to_name.insert(id.clone(), name.clone());
@@ -909,31 +899,29 @@ fn build_contents(
                                 .or_else(|| {
                                     // [NEW v3.3.17] Try session-based signature cache first (Layer 3)
                                     // This provides conversation-level isolation
-                                    crate::proxy::SignatureCache::global().get_session_signature(&session_id)
-                                        .map(|s| {
+                                    crate::SignatureCache::global().get_session_signature(session_id)
+                                        .inspect(|s| {
                                             tracing::info!(
                                                 "[Claude-Request] Recovered signature from SESSION cache (session: {}, len: {})", 
                                                 session_id, s.len()
                                             );
-                                            s
                                         })
                                 })
                                 .or_else(|| {
                                     // Try tool-specific signature cache (Layer 1)
-                                    crate::proxy::SignatureCache::global().get_tool_signature(id)
-                                        .map(|s| {
+                                    crate::SignatureCache::global().get_tool_signature(id)
+                                        .inspect(|_s| {
                                             tracing::info!("[Claude-Request] Recovered signature from TOOL cache for tool_id: {}", id);
-                                            s
                                         })
                                 })
                                 .or_else(|| {
                                     // [DEPRECATED] Global store fallback - kept for backward compatibility
                                     let global_sig = get_thought_signature();
-                                    if global_sig.is_some() {
+                                    if let Some(sig) = &global_sig {
                                         tracing::warn!(
                                             "[Claude-Request] Using deprecated GLOBAL thought_signature fallback (length: {}). \
                                              This indicates session cache miss.", 
-                                            global_sig.as_ref().unwrap().len()
+                                            sig.len()
                                         );
                                     }
                                     global_sig
@@ -1099,7 +1087,7 @@ fn build_contents(
             } else {
                 // [Crucial Check] 即使有 thought 块，也必须保证它位于 parts 的首位 (Index 0)
                 // 且必须包含 thought: true 标记
-                let first_is_thought = parts.get(0).map_or(false, |p| {
+                let first_is_thought = parts.first().is_some_and(|p| {
                     (p.get("thought").is_some() || p.get("thoughtSignature").is_some())
                     && p.get("text").is_some() // 对于 v1internal，通常 text + thought: true 才是合规的思维块
                 });
@@ -1218,7 +1206,7 @@ fn build_tools(tools: &Option<Vec<Tool>>, has_web_search: bool) -> Result<Option
                     "type": "object",
                     "properties": {}
                 }));
-                crate::proxy::common::json_schema::clean_json_schema(&mut input_schema);
+                crate::common::json_schema::clean_json_schema(&mut input_schema);
 
                 function_declarations.push(json!({
                     "name": name,
@@ -1382,7 +1370,7 @@ fn is_model_compatible(cached: &str, target: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::proxy::common::json_schema::clean_json_schema;
+    use crate::common::json_schema::clean_json_schema;
 
     #[test]
     fn test_simple_request() {
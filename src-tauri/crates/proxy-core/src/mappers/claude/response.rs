@@ -58,7 +58,7 @@ fn remap_function_call_args(tool_name: &str, args: &mut serde_json::Value) {
                 if !obj.contains_key("path") {
                     if let Some(paths) = obj.remove("paths") {
                         let path_str = if let Some(arr) = paths.as_array() {
-                            arr.get(0)
+                            arr.first()
                                 .and_then(|v| v.as_str())
                                 .unwrap_or(".")
                                 .to_string()
@@ -152,7 +152,7 @@ fn remap_function_call_args(tool_name: &str, args: &mut serde_json::Value) {
                 if !obj.contains_key("path") {
                     if let Some(paths) = obj.remove("paths") {
                         let path_str = if let Some(arr) = paths.as_array() {
-                            arr.get(0)
+                            arr.first()
                                 .and_then(|v| v.as_str())
                                 .unwrap_or(".")
                                 .to_string()
@@ -204,6 +204,12 @@ pub struct NonStreamingProcessor {
     pub context_limit: u32,
 }
 
+impl Default for NonStreamingProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NonStreamingProcessor {
     pub fn new() -> Self {
         Self {
@@ -227,7 +233,7 @@ impl NonStreamingProcessor {
         let parts = gemini_response
             .candidates
             .as_ref()
-            .and_then(|c| c.get(0))
+            .and_then(|c| c.first())
             .and_then(|candidate| candidate.content.as_ref())
             .map(|content| &content.parts)
             .unwrap_or(&empty_parts);
@@ -238,7 +244,7 @@ impl NonStreamingProcessor {
         }
 
         // 处理 grounding(web search) -> 转换为 server_tool_use / web_search_tool_result
-        if let Some(candidate) = gemini_response.candidates.as_ref().and_then(|c| c.get(0)) {
+        if let Some(candidate) = gemini_response.candidates.as_ref().and_then(|c| c.first()) {
             if let Some(grounding) = &candidate.grounding_metadata {
                 self.process_grounding(grounding);
             }
@@ -302,7 +308,7 @@ impl NonStreamingProcessor {
                 format!(
                     "{}-{}",
                     fc.name,
-                    crate::proxy::common::utils::generate_random_id()
+                    crate::common::utils::generate_random_id()
                 )
             });
 
@@ -512,7 +518,7 @@ impl NonStreamingProcessor {
         let finish_reason = gemini_response
             .candidates
             .as_ref()
-            .and_then(|c| c.get(0))
+            .and_then(|c| c.first())
             .and_then(|candidate| candidate.finish_reason.as_deref());
 
         let stop_reason = if self.has_tool_call {
@@ -537,7 +543,7 @@ impl NonStreamingProcessor {
 
         ClaudeResponse {
             id: gemini_response.response_id.clone().unwrap_or_else(|| {
-                format!("msg_{}", crate::proxy::common::utils::generate_random_id())
+                format!("msg_{}", crate::common::utils::generate_random_id())
             }),
             type_: "message".to_string(),
             role: "assistant".to_string(),
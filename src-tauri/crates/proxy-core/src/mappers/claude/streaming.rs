@@ -3,9 +3,11 @@
 
 use super::models::*;
 use super::utils::to_claude_usage;
-// use crate::proxy::mappers::signature_store::store_thought_signature; // Deprecated
-use crate::proxy::SignatureCache;
-use bytes::Bytes;
+// use crate::mappers::signature_store::store_thought_signature; // Deprecated
+use crate::SignatureCache;
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::ser::SerializeMap;
+use serde::Serialize;
 use serde_json::json;
 
 /// [FIX #547] Helper function to coerce string values to boolean
@@ -64,7 +66,7 @@ fn remap_function_call_args(tool_name: &str, args: &mut serde_json::Value) {
                     if let Some(paths) = obj.remove("paths") {
                         let path_str = if let Some(arr) = paths.as_array() {
                             // Take first element if array
-                            arr.get(0)
+                            arr.first()
                                 .and_then(|v| v.as_str())
                                 .unwrap_or(".")
                                 .to_string()
@@ -129,7 +131,7 @@ fn remap_function_call_args(tool_name: &str, args: &mut serde_json::Value) {
                 if !obj.contains_key("path") {
                     if let Some(paths) = obj.remove("paths") {
                         let path_str = if let Some(arr) = paths.as_array() {
-                            arr.get(0)
+                            arr.first()
                                 .and_then(|v| v.as_str())
                                 .unwrap_or(".")
                                 .to_string()
@@ -169,6 +171,44 @@ fn remap_function_call_args(tool_name: &str, args: &mut serde_json::Value) {
     }
 }
 
+/// 输出缓冲区的初始容量
+const SSE_BUFFER_CAPACITY: usize = 8 * 1024;
+/// 每个事件写入前预留的容量 (大多数 delta 事件在此范围内，一次写入不触发扩容)
+const SSE_EVENT_CAPACITY: usize = 512;
+
+/// content_block_delta 事件体
+#[derive(Serialize)]
+struct BlockDelta<'a, D: Serialize> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    index: usize,
+    delta: D,
+}
+
+/// 单个字符串字段的 delta，直接借用文本序列化
+struct StringDelta<'a> {
+    delta_type: &'a str,
+    field: &'a str,
+    value: &'a str,
+}
+
+impl Serialize for StringDelta<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("type", self.delta_type)?;
+        map.serialize_entry(self.field, self.value)?;
+        map.end()
+    }
+}
+
+/// content_block_stop 事件体
+#[derive(Serialize)]
+struct BlockStop<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    index: usize,
+}
+
 /// 块类型枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlockType {
@@ -183,6 +223,12 @@ pub struct SignatureManager {
     pending: Option<String>,
 }
 
+impl Default for SignatureManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SignatureManager {
     pub fn new() -> Self {
         Self { pending: None }
@@ -230,6 +276,14 @@ pub struct StreamingState {
     // [NEW] MCP XML Bridge 缓冲区
     pub mcp_xml_buffer: String,
     pub in_mcp_xml: bool,
+    /// SSE 输出缓冲区 (各事件从中切出，复用同一块分配)
+    out: BytesMut,
+}
+
+impl Default for StreamingState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StreamingState {
@@ -253,46 +307,57 @@ impl StreamingState {
             context_limit: 1_048_576, // Default to 1M
             mcp_xml_buffer: String::new(),
             in_mcp_xml: false,
+            out: BytesMut::with_capacity(SSE_BUFFER_CAPACITY),
         }
     }
 
     /// 发送 SSE 事件
-    pub fn emit(&self, event_type: &str, data: serde_json::Value) -> Bytes {
-        let sse = format!(
-            "event: {}\ndata: {}\n\n",
-            event_type,
-            serde_json::to_string(&data).unwrap_or_default()
-        );
-        Bytes::from(sse)
+    pub fn emit(&mut self, event_type: &str, data: serde_json::Value) -> Bytes {
+        self.write_event(event_type, &data)
+    }
+
+    /// 直接把事件序列化进复用的输出缓冲区，再切出本事件的字节
+    ///
+    /// 不构建中间 String / Value；`split` 后剩余容量留给下一个事件，
+    /// 只有容量用尽时才重新分配。
+    fn write_event<T: Serialize + ?Sized>(&mut self, event_type: &str, data: &T) -> Bytes {
+        self.out.reserve(SSE_EVENT_CAPACITY);
+        self.out.extend_from_slice(b"event: ");
+        self.out.extend_from_slice(event_type.as_bytes());
+        self.out.extend_from_slice(b"\ndata: ");
+        let data_start = self.out.len();
+        if serde_json::to_writer((&mut self.out).writer(), data).is_err() {
+            self.out.truncate(data_start);
+        }
+        self.out.extend_from_slice(b"\n\n");
+        self.out.split().freeze()
     }
 
     /// 发送 message_start 事件
-    pub fn emit_message_start(&mut self, raw_json: &serde_json::Value) -> Bytes {
+    pub fn emit_message_start(
+        &mut self,
+        response_id: Option<&str>,
+        model_version: Option<&str>,
+        usage_metadata: Option<&UsageMetadata>,
+    ) -> Bytes {
         if self.message_start_sent {
             return Bytes::new();
         }
 
-        let usage = raw_json
-            .get("usageMetadata")
-            .and_then(|u| serde_json::from_value::<UsageMetadata>(u.clone()).ok())
-            .map(|u| to_claude_usage(&u, self.scaling_enabled, self.context_limit));
+        let usage = usage_metadata.map(|u| to_claude_usage(u, self.scaling_enabled, self.context_limit));
 
         let mut message = json!({
-            "id": raw_json.get("responseId")
-                .and_then(|v| v.as_str())
-                .unwrap_or_else(|| "msg_unknown"),
+            "id": response_id.unwrap_or("msg_unknown"),
             "type": "message",
             "role": "assistant",
             "content": [],
-            "model": raw_json.get("modelVersion")
-                .and_then(|v| v.as_str())
-                .unwrap_or(""),
+            "model": model_version.unwrap_or(""),
             "stop_reason": null,
             "stop_sequence": null,
         });
 
         // Capture model name for signature cache
-        if let Some(m) = raw_json.get("modelVersion").and_then(|v| v.as_str()) {
+        if let Some(m) = model_version {
             self.model_name = Some(m.to_string());
         }
 
@@ -347,17 +412,11 @@ impl StreamingState {
         // Thinking 块结束时发送暂存的签名
         if self.block_type == BlockType::Thinking && self.signatures.has_pending() {
             if let Some(signature) = self.signatures.consume() {
-                chunks.push(self.emit_delta("signature_delta", json!({ "signature": signature })));
+                chunks.push(self.emit_string_delta("signature_delta", "signature", &signature));
             }
         }
 
-        chunks.push(self.emit(
-            "content_block_stop",
-            json!({
-                "type": "content_block_stop",
-                "index": self.block_index
-            }),
-        ));
+        chunks.push(self.emit_block_stop());
 
         self.block_index += 1;
         self.block_type = BlockType::None;
@@ -366,7 +425,7 @@ impl StreamingState {
     }
 
     /// 发送 delta 事件
-    pub fn emit_delta(&self, delta_type: &str, delta_content: serde_json::Value) -> Bytes {
+    pub fn emit_delta(&mut self, delta_type: &str, delta_content: serde_json::Value) -> Bytes {
         let mut delta = json!({ "type": delta_type });
         if let serde_json::Value::Object(map) = delta_content {
             for (k, v) in map {
@@ -374,14 +433,24 @@ impl StreamingState {
             }
         }
 
-        self.emit(
-            "content_block_delta",
-            json!({
-                "type": "content_block_delta",
-                "index": self.block_index,
-                "delta": delta
-            }),
-        )
+        let event = BlockDelta { kind: "content_block_delta", index: self.block_index, delta };
+        self.write_event("content_block_delta", &event)
+    }
+
+    /// 发送 `{"type": delta_type, field: value}` 形式的 delta 事件 (文本 / 思考 / 签名 / 工具参数)
+    pub fn emit_string_delta(&mut self, delta_type: &str, field: &str, value: &str) -> Bytes {
+        let event = BlockDelta {
+            kind: "content_block_delta",
+            index: self.block_index,
+            delta: StringDelta { delta_type, field, value },
+        };
+        self.write_event("content_block_delta", &event)
+    }
+
+    /// 发送 content_block_stop 事件
+    fn emit_block_stop(&mut self) -> Bytes {
+        let event = BlockStop { kind: "content_block_stop", index: self.block_index };
+        self.write_event("content_block_stop", &event)
     }
 
     /// 发送结束事件
@@ -405,15 +474,9 @@ impl StreamingState {
                     "content_block": { "type": "thinking", "thinking": "" }
                 }),
             ));
-            chunks.push(self.emit_delta("thinking_delta", json!({ "thinking": "" })));
-            chunks.push(self.emit_delta("signature_delta", json!({ "signature": signature })));
-            chunks.push(self.emit(
-                "content_block_stop",
-                json!({
-                    "type": "content_block_stop",
-                    "index": self.block_index
-                }),
-            ));
+            chunks.push(self.emit_string_delta("thinking_delta", "thinking", ""));
+            chunks.push(self.emit_string_delta("signature_delta", "signature", &signature));
+            chunks.push(self.emit_block_stop());
             self.block_index += 1;
         }
 
@@ -453,8 +516,8 @@ impl StreamingState {
                     "index": self.block_index,
                     "content_block": { "type": "text", "text": "" }
                 })));
-                chunks.push(self.emit_delta("text_delta", json!({ "text": grounding_text })));
-                chunks.push(self.emit("content_block_stop", json!({ "type": "content_block_stop", "index": self.block_index })));
+                chunks.push(self.emit_string_delta("text_delta", "text", &grounding_text));
+                chunks.push(self.emit_block_stop());
                 self.block_index += 1;
             }
         }
@@ -647,13 +710,10 @@ impl<'a> PartProcessor<'a> {
                             "content_block": { "type": "thinking", "thinking": "" }
                         }),
                     ));
+                    chunks.push(self.state.emit_string_delta("thinking_delta", "thinking", ""));
                     chunks.push(
                         self.state
-                            .emit_delta("thinking_delta", json!({ "thinking": "" })),
-                    );
-                    chunks.push(
-                        self.state
-                            .emit_delta("signature_delta", json!({ "signature": trailing_sig })),
+                            .emit_string_delta("signature_delta", "signature", &trailing_sig),
                     );
                     chunks.extend(self.state.end_block());
                 }
@@ -703,13 +763,10 @@ impl<'a> PartProcessor<'a> {
                         "content_block": { "type": "thinking", "thinking": "" }
                     }),
                 ));
+                chunks.push(self.state.emit_string_delta("thinking_delta", "thinking", ""));
                 chunks.push(
                     self.state
-                        .emit_delta("thinking_delta", json!({ "thinking": "" })),
-                );
-                chunks.push(
-                    self.state
-                        .emit_delta("signature_delta", json!({ "signature": trailing_sig })),
+                        .emit_string_delta("signature_delta", "signature", &trailing_sig),
                 );
                 chunks.extend(self.state.end_block());
             }
@@ -724,10 +781,7 @@ impl<'a> PartProcessor<'a> {
         }
 
         if !text.is_empty() {
-            chunks.push(
-                self.state
-                    .emit_delta("thinking_delta", json!({ "thinking": text })),
-            );
+            chunks.push(self.state.emit_string_delta("thinking_delta", "thinking", text));
         }
 
         // [IMPROVED] Store signature to global cache
@@ -783,26 +837,23 @@ impl<'a> PartProcessor<'a> {
                         "content_block": { "type": "thinking", "thinking": "" }
                     }),
                 ));
+                chunks.push(self.state.emit_string_delta("thinking_delta", "thinking", ""));
                 chunks.push(
                     self.state
-                        .emit_delta("thinking_delta", json!({ "thinking": "" })),
-                );
-                chunks.push(
-                    self.state
-                        .emit_delta("signature_delta", json!({ "signature": trailing_sig })),
+                        .emit_string_delta("signature_delta", "signature", &trailing_sig),
                 );
                 chunks.extend(self.state.end_block());
             }
         }
 
         // 非空 text 带签名 - 立即处理
-        if signature.is_some() {
+        if let Some(signature) = signature {
             // 2. 开始新 text 块并发送内容
             chunks.extend(
                 self.state
                     .start_block(BlockType::Text, json!({ "type": "text", "text": "" })),
             );
-            chunks.push(self.state.emit_delta("text_delta", json!({ "text": text })));
+            chunks.push(self.state.emit_string_delta("text_delta", "text", text));
             chunks.extend(self.state.end_block());
 
             // 输出空 thinking 块承载签名
@@ -814,14 +865,8 @@ impl<'a> PartProcessor<'a> {
                     "content_block": { "type": "thinking", "thinking": "" }
                 }),
             ));
-            chunks.push(
-                self.state
-                    .emit_delta("thinking_delta", json!({ "thinking": "" })),
-            );
-            chunks.push(self.state.emit_delta(
-                "signature_delta",
-                json!({ "signature": signature.unwrap() }),
-            ));
+            chunks.push(self.state.emit_string_delta("thinking_delta", "thinking", ""));
+            chunks.push(self.state.emit_string_delta("signature_delta", "signature", &signature));
             chunks.extend(self.state.end_block());
 
             return chunks;
@@ -868,7 +913,7 @@ impl<'a> PartProcessor<'a> {
                                 if self.state.current_block_type() != BlockType::Text {
                                     chunks.extend(self.state.start_block(BlockType::Text, json!({ "type": "text", "text": "" })));
                                 }
-                                chunks.push(self.state.emit_delta("text_delta", json!({ "text": prefix_text })));
+                                chunks.push(self.state.emit_string_delta("text_delta", "text", prefix_text));
                             }
                             
                             chunks.extend(tool_chunks);
@@ -896,7 +941,7 @@ impl<'a> PartProcessor<'a> {
             );
         }
 
-        chunks.push(self.state.emit_delta("text_delta", json!({ "text": text })));
+        chunks.push(self.state.emit_string_delta("text_delta", "text", text));
 
         chunks
     }
@@ -915,7 +960,7 @@ impl<'a> PartProcessor<'a> {
             format!(
                 "{}-{}",
                 fc.name,
-                crate::proxy::common::utils::generate_random_id()
+                crate::common::utils::generate_random_id()
             )
         });
 
@@ -955,7 +1000,7 @@ impl<'a> PartProcessor<'a> {
                 serde_json::to_string(&remapped_args).unwrap_or_else(|_| "{}".to_string());
             chunks.push(
                 self.state
-                    .emit_delta("input_json_delta", json!({ "partial_json": json_str })),
+                    .emit_string_delta("input_json_delta", "partial_json", &json_str),
             );
         }
 
@@ -985,7 +1030,7 @@ mod tests {
 
     #[test]
     fn test_streaming_state_emit() {
-        let state = StreamingState::new();
+        let mut state = StreamingState::new();
         let chunk = state.emit("test_event", json!({"foo": "bar"}));
 
         let s = String::from_utf8(chunk.to_vec()).unwrap();
@@ -993,6 +1038,28 @@ mod tests {
         assert!(s.contains("\"foo\":\"bar\""));
     }
 
+    #[test]
+    fn test_string_delta_matches_value_delta() {
+        fn event_data(chunk: &Bytes) -> serde_json::Value {
+            let s = std::str::from_utf8(chunk).unwrap();
+            let data = s.strip_prefix("event: content_block_delta\ndata: ").unwrap();
+            assert!(data.ends_with("\n\n"));
+            serde_json::from_str(data.trim_end()).unwrap()
+        }
+
+        let mut state = StreamingState::new();
+        let text = "line \"quoted\"\n\u{4f60}\u{597d}";
+        let typed = state.emit_string_delta("text_delta", "text", text);
+        let generic = state.emit_delta("text_delta", json!({ "text": text }));
+        assert_eq!(event_data(&typed), event_data(&generic));
+        assert_eq!(event_data(&typed)["delta"], json!({ "type": "text_delta", "text": text }));
+
+        // 连续事件从同一缓冲区切出，互不覆盖
+        let stop = state.emit_block_stop();
+        assert_eq!(&stop[..], b"event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n");
+        assert_eq!(event_data(&typed)["index"], 0);
+    }
+
     #[test]
     fn test_process_function_call_deltas() {
         let mut state = StreamingState::new();
@@ -1,6 +1,7 @@
 use super::models::{Message, MessageContent, ContentBlock};
 use tracing::info;
 
+/// 对话状态 (是否处于工具调用循环)
 #[derive(Debug, Default)]
 pub struct ConversationState {
     pub in_tool_loop: bool,
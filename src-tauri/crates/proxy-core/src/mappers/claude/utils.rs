@@ -3,18 +3,14 @@
 
 // 已移除未使用的 Value 导入
 
-/// 将 JSON Schema 中的类型名称转为大写 (Gemini 要求)
-/// 例如: "string" -> "STRING", "integer" -> "INTEGER"
-// 已移除未使用的 uppercase_schema_types 函数
+// 已移除未使用的 uppercase_schema_types 函数 (将 JSON Schema 中的类型名称转为大写)
 
 /// 根据模型名称获取上下文 Token 限制
 pub fn get_context_limit_for_model(model: &str) -> u32 {
     if model.contains("pro") {
         2_097_152 // 2M for Pro
-    } else if model.contains("flash") {
-        1_048_576 // 1M for Flash
     } else {
-        1_048_576 // Default 1M
+        1_048_576 // 1M for Flash and others
     }
 }
 
@@ -44,7 +40,7 @@ pub fn to_claude_usage(usage_metadata: &super::models::UsageMetadata, scaling_en
             } else {
                 // 第二阶段：回归区 - 从 70% 到 100% 线性回归到 195k
                 // 计算当前处于 70% - 100% 的比例
-                let range = (context_limit as f64 * 0.3) as f64;
+                let range = context_limit as f64 * 0.3;
                 let progress = (total_raw - perception_start) as f64 / range;
                 
                 // 计算第一阶段末端的数值作为起点
@@ -86,8 +82,7 @@ pub fn to_claude_usage(usage_metadata: &super::models::UsageMetadata, scaling_en
     }
 }
 
-/// 提取 thoughtSignature
-// 已移除未使用的 extract_thought_signature 函数
+// 已移除未使用的 extract_thought_signature 函数 (提取 thoughtSignature)
 
 #[cfg(test)]
 mod tests {
@@ -1,6 +1,7 @@
 // Gemini v1internal 数据模型
 use serde::{Deserialize, Serialize};
 
+/// v1internal 请求包装 (project、requestId 与 Gemini 请求体)
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct V1InternalRequest {
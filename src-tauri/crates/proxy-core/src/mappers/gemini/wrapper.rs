@@ -1,7 +1,11 @@
 // Gemini v1internal 包装/解包
+use serde::Deserialize;
+use serde_json::value::RawValue;
 use serde_json::{json, Value};
+use std::ops::Range;
 
 /// 包装请求体为 v1internal 格式
+#[tracing::instrument(name = "convert.gemini_request", skip_all, fields(model = %mapped_model))]
 pub fn wrap_request(body: &Value, project_id: &str, mapped_model: &str) -> Value {
     // 优先使用传入的 mapped_model，其次尝试从 body 获取
     let original_model = body.get("model").and_then(|v| v.as_str()).unwrap_or(mapped_model);
@@ -17,19 +21,17 @@ pub fn wrap_request(body: &Value, project_id: &str, mapped_model: &str) -> Value
     let mut inner_request = body.clone();
 
     // 深度清理 [undefined] 字符串 (Cherry Studio 等客户端常见注入)
-    crate::proxy::mappers::common_utils::deep_clean_undefined(&mut inner_request);
+    crate::mappers::common_utils::deep_clean_undefined(&mut inner_request);
 
     // [FIX] Removed forced maxOutputTokens (64000) as it exceeds limits for Gemini 1.5 Flash/Pro standard models (8192).
     // This caused upstream to return empty/invalid responses, leading to 'NoneType' object has no attribute 'strip' in Python clients.
     // relying on upstream defaults or user provided values is safer.
 
     // 提取 tools 列表以进行联网探测 (Gemini 风格可能是嵌套的)
-    let tools_val: Option<Vec<Value>> = inner_request.get("tools").and_then(|t| t.as_array()).map(|arr| {
-        arr.clone()
-    });
+    let tools_val: Option<Vec<Value>> = inner_request.get("tools").and_then(|t| t.as_array()).cloned();
 
     // Use shared grounding/config logic
-    let config = crate::proxy::mappers::common_utils::resolve_request_config(original_model, final_model_name, &tools_val);
+    let config = crate::mappers::common_utils::resolve_request_config(original_model, final_model_name, &tools_val);
     
     // Clean tool declarations (remove forbidden Schema fields like multipleOf, and remove redundant search decls)
     if let Some(tools) = inner_request.get_mut("tools") {
@@ -50,7 +52,7 @@ pub fn wrap_request(body: &Value, project_id: &str, mapped_model: &str) -> Value
                         // 2. 清洗剩余 Schema
                         for decl in decls_arr {
                             if let Some(params) = decl.get_mut("parameters") {
-                                crate::proxy::common::json_schema::clean_json_schema(params);
+                                crate::common::json_schema::clean_json_schema(params);
                             }
                         }
                     }
@@ -64,7 +66,7 @@ pub fn wrap_request(body: &Value, project_id: &str, mapped_model: &str) -> Value
     
     // Inject googleSearch tool if needed
     if config.inject_google_search {
-        crate::proxy::mappers::common_utils::inject_google_search_tool(&mut inner_request);
+        crate::mappers::common_utils::inject_google_search_tool(&mut inner_request);
     }
 
     // Inject imageConfig if present (for image generation models)
@@ -104,7 +106,7 @@ pub fn wrap_request(body: &Value, project_id: &str, mapped_model: &str) -> Value
             if let Some(parts) = system_instruction.get_mut("parts") {
                 if let Some(parts_array) = parts.as_array_mut() {
                     // 检查第一个 part 是否已包含 Antigravity 身份
-                    let has_antigravity = parts_array.get(0)
+                    let has_antigravity = parts_array.first()
                         .and_then(|p| p.get("text"))
                         .and_then(|t| t.as_str())
                         .map(|s| s.contains("You are Antigravity"))
@@ -142,6 +144,24 @@ pub fn unwrap_response(response: &Value) -> Value {
     response.get("response").unwrap_or(response).clone()
 }
 
+/// 定位 v1internal 响应中 `response` 字段的原始字节范围 (零拷贝解包)
+///
+/// 只做借用解析，不构建 `Value` 也不重新序列化，调用方直接切片转发上游字节。
+/// 没有 `response` 字段时返回 `Ok(None)`，此时整个负载原样转发。
+pub fn response_span(payload: &[u8]) -> Result<Option<Range<usize>>, serde_json::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper<'a> {
+        #[serde(borrow, default)]
+        response: Option<&'a RawValue>,
+    }
+
+    let wrapper: Wrapper = serde_json::from_slice(payload)?;
+    Ok(wrapper.response.map(|raw| {
+        let start = raw.get().as_ptr() as usize - payload.as_ptr() as usize;
+        start..start + raw.get().len()
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,4 +251,15 @@ mod tests {
         // Should NOT inject duplicate, so only 1 part remains
         assert_eq!(parts.len(), 1);
     }
+
+    #[test]
+    fn test_response_span() {
+        let payload = br#"{"response": {"candidates": [{"content": {"parts": [{"text": "hi"}]}}]}, "traceId": "abc"}"#;
+        let span = response_span(payload).unwrap().unwrap();
+        let inner: Value = serde_json::from_slice(&payload[span]).unwrap();
+        assert_eq!(inner, unwrap_response(&serde_json::from_slice(payload).unwrap()));
+
+        assert_eq!(response_span(br#"{"candidates": []}"#).unwrap(), None);
+        assert!(response_span(b"{not json").is_err());
+    }
 }
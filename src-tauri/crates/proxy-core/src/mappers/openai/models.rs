@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// OpenAI Chat / Completions / Responses 请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIRequest {
     pub model: String,
@@ -32,11 +33,13 @@ pub struct OpenAIRequest {
     pub input: Option<Value>,
 }
 
+/// 响应格式 (`response_format`)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseFormat {
     pub r#type: String,
 }
 
+/// 消息内容: 纯文本或内容块数组
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum OpenAIContent {
@@ -44,6 +47,7 @@ pub enum OpenAIContent {
     Array(Vec<OpenAIContentBlock>),
 }
 
+/// 消息内容块
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum OpenAIContentBlock {
@@ -61,6 +65,7 @@ pub enum OpenAIContentBlock {
     },
 }
 
+/// 图片 URL (支持 data URI)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct OpenAIImageUrl {
     pub url: String,
@@ -68,11 +73,13 @@ pub struct OpenAIImageUrl {
     pub detail: Option<String>,
 }
 
+/// 音频 URL
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AudioUrlContent {
     pub url: String,
 }
 
+/// 对话消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIMessage {
     pub role: String,
@@ -88,6 +95,7 @@ pub struct OpenAIMessage {
     pub name: Option<String>,
 }
 
+/// 助手发起的工具调用
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
@@ -95,12 +103,14 @@ pub struct ToolCall {
     pub function: ToolFunction,
 }
 
+/// 工具调用的函数名与参数 (JSON 字符串)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolFunction {
     pub name: String,
     pub arguments: String,
 }
 
+/// Chat Completions 非流式响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIResponse {
     pub id: String,
@@ -110,6 +120,7 @@ pub struct OpenAIResponse {
     pub choices: Vec<Choice>,
 }
 
+/// 响应候选
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Choice {
     pub index: u32,
@@ -3,14 +3,15 @@ use super::models::*;
 use serde_json::{json, Value};
 use super::streaming::get_thought_signature;
 
+#[tracing::instrument(name = "convert.openai_request", skip_all, fields(model = %mapped_model))]
 pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mapped_model: &str) -> Value {
     // 将 OpenAI 工具转为 Value 数组以便探测
     let tools_val = request.tools.as_ref().map(|list| {
-        list.iter().map(|v| v.clone()).collect::<Vec<_>>()
+        list.to_vec()
     });
 
     // Resolve grounding config
-    let config = crate::proxy::mappers::common_utils::resolve_request_config(&request.model, mapped_model, &tools_val);
+    let config = crate::mappers::common_utils::resolve_request_config(&request.model, mapped_model, &tools_val);
 
     tracing::debug!("[Debug] OpenAI Request: original='{}', mapped='{}', type='{}', has_image_config={}", 
         request.model, mapped_model, config.request_type, config.image_config.is_some());
@@ -50,8 +51,8 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
 
     // 从全局存储获取 thoughtSignature (PR #93 支持)
     let global_thought_sig = get_thought_signature();
-    if global_thought_sig.is_some() {
-        tracing::debug!("从全局存储获取到 thoughtSignature (长度: {})", global_thought_sig.as_ref().unwrap().len());
+    if let Some(sig) = &global_thought_sig {
+        tracing::debug!("从全局存储获取到 thoughtSignature (长度: {})", sig.len());
     }
 
     // 2. 构建 Gemini contents (过滤掉 system)
@@ -150,7 +151,7 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
 
             // Handle tool calls (assistant message)
             if let Some(tool_calls) = &msg.tool_calls {
-                for (_index, tc) in tool_calls.iter().enumerate() {
+                for tc in tool_calls.iter() {
                     /* 暂时移除：防止 Codex CLI 界面碎片化
                     if index == 0 && parts.is_empty() {
                          if mapped_model.contains("gemini-3") {
@@ -270,7 +271,7 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
     });
 
     // 深度清理 [undefined] 字符串 (Cherry Studio 等客户端常见注入)
-    crate::proxy::mappers::common_utils::deep_clean_undefined(&mut inner_request);
+    crate::mappers::common_utils::deep_clean_undefined(&mut inner_request);
 
     // 4. Handle Tools (Merged Cleaning)
     if let Some(tools) = &request.tools {
@@ -311,7 +312,7 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
 
             if let Some(params) = gemini_func.get_mut("parameters") {
                 // [DEEP FIX] 统一调用公共库清洗：展开 $ref 并剔除所有层级的 format/definitions
-                crate::proxy::common::json_schema::clean_json_schema(params);
+                crate::common::json_schema::clean_json_schema(params);
 
                 // Gemini v1internal 要求：
                 // 1. type 必须是大写 (OBJECT, STRING 等)
@@ -361,7 +362,7 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
     });
     
     if config.inject_google_search {
-        crate::proxy::mappers::common_utils::inject_google_search_tool(&mut inner_request);
+        crate::mappers::common_utils::inject_google_search_tool(&mut inner_request);
     }
 
     if let Some(image_config) = config.image_config {
@@ -390,16 +391,12 @@ pub fn transform_openai_request(request: &OpenAIRequest, project_id: &str, mappe
 
 fn enforce_uppercase_types(value: &mut Value) {
     if let Value::Object(map) = value {
-        if let Some(type_val) = map.get_mut("type") {
-            if let Value::String(ref mut s) = type_val {
-                *s = s.to_uppercase();
-            }
+        if let Some(Value::String(s)) = map.get_mut("type") {
+            *s = s.to_uppercase();
         }
-        if let Some(properties) = map.get_mut("properties") {
-            if let Value::Object(ref mut props) = properties {
-                for v in props.values_mut() {
-                    enforce_uppercase_types(v);
-                }
+        if let Some(Value::Object(props)) = map.get_mut("properties") {
+            for v in props.values_mut() {
+                enforce_uppercase_types(v);
             }
         }
         if let Some(items) = map.get_mut("items") {
@@ -1,7 +1,11 @@
 // OpenAI 流式转换
 use bytes::{Bytes, BytesMut};
 use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::value::RawValue;
 use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::sync::{Mutex, OnceLock};
 use chrono::Utc;
@@ -50,18 +54,283 @@ pub fn get_thought_signature() -> Option<String> {
     }
 }
 
+/// Chat Completions 流式分片中用到的字段
+///
+/// 借用原始 JSON 直接反序列化：parts 逐个解析 (单个 part 解析失败只跳过该 part)，
+/// functionCall 保留原始文本，参数直接作为 `arguments` 输出，不构建 `Value` 再重复序列化。
+#[derive(Deserialize)]
+struct ChatStreamChunk<'a> {
+    #[serde(borrow, default)]
+    candidates: Option<Vec<ChatStreamCandidate<'a>>>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamCandidate<'a> {
+    #[serde(borrow, default)]
+    content: Option<ChatStreamContent<'a>>,
+    #[serde(rename = "finishReason", borrow, default)]
+    finish_reason: Option<Cow<'a, str>>,
+    #[serde(rename = "groundingMetadata", default)]
+    grounding_metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamContent<'a> {
+    #[serde(borrow, default)]
+    parts: Option<Vec<&'a RawValue>>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamPart<'a> {
+    #[serde(borrow, default)]
+    text: Option<Cow<'a, str>>,
+    #[serde(default)]
+    thought: Option<bool>,
+    #[serde(rename = "thoughtSignature", borrow, default)]
+    thought_signature: Option<Cow<'a, str>>,
+    #[serde(rename = "thought_signature", borrow, default)]
+    thought_signature_snake: Option<Cow<'a, str>>,
+    #[serde(rename = "inlineData", borrow, default)]
+    inline_data: Option<ChatStreamInlineData<'a>>,
+    #[serde(rename = "functionCall", borrow, default)]
+    function_call: Option<&'a RawValue>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamInlineData<'a> {
+    #[serde(rename = "mimeType", borrow, default)]
+    mime_type: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    data: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamFunctionCall<'a> {
+    #[serde(borrow, default)]
+    name: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    args: Option<&'a RawValue>,
+}
+
+/// 联网搜索引文 (Grounding Metadata) 转为追加在正文后的 Markdown
+fn grounding_text(grounding: &Value) -> String {
+    let mut grounding_text = String::new();
+
+    // 1. 处理搜索词
+    if let Some(queries) = grounding.get("webSearchQueries").and_then(|q| q.as_array()) {
+        let query_list: Vec<&str> = queries.iter().filter_map(|v| v.as_str()).collect();
+        if !query_list.is_empty() {
+            grounding_text.push_str("\n\n---\n**🔍 已为您搜索：** ");
+            grounding_text.push_str(&query_list.join(", "));
+        }
+    }
+
+    // 2. 处理来源链接 (Chunks)
+    if let Some(chunks) = grounding.get("groundingChunks").and_then(|c| c.as_array()) {
+        let mut links = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            if let Some(web) = chunk.get("web") {
+                let title = web.get("title").and_then(|v| v.as_str()).unwrap_or("网页来源");
+                let uri = web.get("uri").and_then(|v| v.as_str()).unwrap_or("#");
+                links.push(format!("[{}] [{}]({})", i + 1, title, uri));
+            }
+        }
+        if !links.is_empty() {
+            grounding_text.push_str("\n\n**🌐 来源引文：**\n");
+            grounding_text.push_str(&links.join("\n"));
+        }
+    }
+
+    grounding_text
+}
+
+/// Gemini 流式分片 → OpenAI `chat.completion.chunk` 转换器
+///
+/// 同一个流的所有 chunk 共用 ID 与 created 时间戳，并按原始 functionCall 文本去重工具调用
+pub struct ChatChunkConverter {
+    stream_id: String,
+    created_ts: i64,
+    model: String,
+    emitted_tool_calls: HashSet<String>,
+}
+
+impl ChatChunkConverter {
+    pub fn new(model: String) -> Self {
+        Self {
+            stream_id: format!("chatcmpl-{}", Uuid::new_v4()),
+            created_ts: Utc::now().timestamp(),
+            model,
+            emitted_tool_calls: HashSet::new(),
+        }
+    }
+
+    /// 转换一行 `data:` 的负载 (可带 v1internal 的 response 包装)，无法解析时返回空
+    pub fn convert(&mut self, payload: &str) -> Vec<Bytes> {
+        // 解包 response 字段 (如果存在)：只定位字节范围，不构建 Value
+        let payload = match crate::mappers::gemini::response_span(payload.as_bytes()) {
+            Ok(Some(span)) => &payload[span],
+            Ok(None) => payload,
+            Err(_) => return Vec::new(),
+        };
+        let chunk: ChatStreamChunk = match serde_json::from_str(payload) {
+            Ok(chunk) => chunk,
+            Err(_) => return Vec::new(),
+        };
+        // Log raw chunk for debugging gemini-3 thoughts
+        tracing::debug!("Gemini SSE Chunk: {}", payload);
+
+        let mut out = Vec::new();
+        for (idx, candidate) in chunk.candidates.iter().flatten().enumerate() {
+            let mut content_out = String::new();
+            let mut thought_out = String::new();
+
+            let parts = candidate.content.as_ref().and_then(|c| c.parts.as_deref()).unwrap_or(&[]);
+            for raw in parts {
+                let Ok(part) = serde_json::from_str::<ChatStreamPart>(raw.get()) else { continue };
+
+                if let Some(text) = &part.text {
+                    if part.thought.unwrap_or(false) {
+                        thought_out.push_str(text);
+                    } else {
+                        content_out.push_str(text);
+                    }
+                }
+                // 捕获 thoughtSignature (Gemini 3 工具调用必需)
+                if let Some(sig) = part.thought_signature.as_deref().or(part.thought_signature_snake.as_deref()) {
+                    store_thought_signature(sig);
+                }
+
+                if let Some(img) = &part.inline_data {
+                    let mime_type = img.mime_type.as_deref().unwrap_or("image/png");
+                    let data = img.data.as_deref().unwrap_or("");
+                    if !data.is_empty() {
+                        content_out.push_str(&format!("![image](data:{};base64,{})", mime_type, data));
+                    }
+                }
+
+                // Handle function call
+                if let Some(func_call) = part.function_call {
+                    out.extend(self.tool_call_chunk(idx, func_call));
+                }
+            }
+
+            // 处理联网搜索引文 (Grounding Metadata) - 流式
+            if let Some(grounding) = &candidate.grounding_metadata {
+                content_out.push_str(&grounding_text(grounding));
+            }
+
+            // 只有当 content 和 thought 都为空时才跳过
+            if content_out.is_empty() && thought_out.is_empty() && candidate.finish_reason.is_none() {
+                continue;
+            }
+
+            // Extract finish reason
+            let finish_reason = candidate.finish_reason.as_deref().map(|f| match f {
+                "STOP" => "stop",
+                "MAX_TOKENS" => "length",
+                "SAFETY" => "content_filter",
+                "RECITATION" => "content_filter",
+                _ => f,
+            });
+
+            // 如果有思考内容，先发送 reasoning_content chunk
+            if !thought_out.is_empty() {
+                let reasoning_chunk = json!({
+                    "id": &self.stream_id,
+                    "object": "chat.completion.chunk",
+                    "created": self.created_ts,
+                    "model": &self.model,
+                    "choices": [
+                        {
+                            "index": idx as u32,
+                            "delta": {
+                                "role": "assistant",
+                                "content": serde_json::Value::Null,
+                                "reasoning_content": thought_out
+                            },
+                            "finish_reason": serde_json::Value::Null
+                        }
+                    ]
+                });
+                out.push(Bytes::from(format!("data: {}\n\n", serde_json::to_string(&reasoning_chunk).unwrap_or_default())));
+            }
+
+            // 发送正常 content chunk
+            if !content_out.is_empty() || finish_reason.is_some() {
+                let openai_chunk = json!({
+                    "id": &self.stream_id,
+                    "object": "chat.completion.chunk",
+                    "created": self.created_ts,
+                    "model": &self.model,
+                    "choices": [
+                        {
+                            "index": idx as u32,
+                            "delta": {
+                                "content": content_out
+                            },
+                            "finish_reason": finish_reason
+                        }
+                    ]
+                });
+                out.push(Bytes::from(format!("data: {}\n\n", serde_json::to_string(&openai_chunk).unwrap_or_default())));
+            }
+        }
+        out
+    }
+
+    /// 工具调用 delta (同一调用在后续分片中重复出现时跳过)
+    fn tool_call_chunk(&mut self, idx: usize, raw: &RawValue) -> Option<Bytes> {
+        let call_key = raw.get();
+        if self.emitted_tool_calls.contains(call_key) {
+            return None;
+        }
+        self.emitted_tool_calls.insert(call_key.to_string());
+
+        let func_call: ChatStreamFunctionCall = serde_json::from_str(call_key).ok()?;
+        let name = func_call.name.as_deref().unwrap_or("unknown");
+        let args = func_call.args.map(RawValue::get).unwrap_or("{}");
+
+        // Generate stable ID
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        use std::hash::{Hash, Hasher};
+        call_key.hash(&mut hasher);
+        let call_id = format!("call_{:x}", hasher.finish());
+
+        let tool_call_chunk = json!({
+            "id": &self.stream_id,
+            "object": "chat.completion.chunk",
+            "created": self.created_ts,
+            "model": &self.model,
+            "choices": [{
+                "index": idx as u32,
+                "delta": {
+                    "role": "assistant",
+                    "tool_calls": [{
+                        "index": 0,
+                        "id": call_id,
+                        "type": "function",
+                        "function": {
+                            "name": name,
+                            "arguments": args
+                        }
+                    }]
+                },
+                "finish_reason": serde_json::Value::Null
+            }]
+        });
+        Some(Bytes::from(format!("data: {}\n\n", serde_json::to_string(&tool_call_chunk).unwrap_or_default())))
+    }
+}
+
 pub fn create_openai_sse_stream(
     mut gemini_stream: Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>,
     model: String,
 ) -> Pin<Box<dyn Stream<Item = Result<Bytes, String>> + Send>> {
     let mut buffer = BytesMut::new();
-    
     // 在流开始时生成固定的 ID 和 timestamp，所有 chunk 共用
-    let stream_id = format!("chatcmpl-{}", Uuid::new_v4());
-    let created_ts = Utc::now().timestamp();
-    
+    let mut converter = ChatChunkConverter::new(model);
+
     let stream = async_stream::stream! {
-        let mut emitted_tool_calls = std::collections::HashSet::new();
         while let Some(item) = gemini_stream.next().await {
             match item {
                 Ok(bytes) => {
@@ -72,215 +341,19 @@ pub fn create_openai_sse_stream(
                     // Process complete lines from buffer
                     while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
                         let line_raw = buffer.split_to(pos + 1);
-                        if let Ok(line_str) = std::str::from_utf8(&line_raw) {
-                            let line = line_str.trim();
-                            if line.is_empty() { continue; }
-
-                            if line.starts_with("data: ") {
-                                let json_part = line.trim_start_matches("data: ").trim();
-                                if json_part == "[DONE]" {
-                                    continue;
-                                }
-
-                                if let Ok(mut json) = serde_json::from_str::<Value>(json_part) {
-                                    // Log raw chunk for debugging gemini-3 thoughts
-                                    tracing::debug!("Gemini SSE Chunk: {}", json_part);
-
-                                    // Handle v1internal wrapper if present
-                                    let actual_data = if let Some(inner) = json.get_mut("response").map(|v| v.take()) {
-                                        inner
-                                    } else {
-                                        json
-                                    };
-
-                                    // Extract candidates
-                                    if let Some(candidates) = actual_data.get("candidates").and_then(|c| c.as_array()) {
-                                        for (idx, candidate) in candidates.iter().enumerate() {
-                                            let parts = candidate.get("content").and_then(|c| c.get("parts")).and_then(|p| p.as_array());
-
-                                            let mut content_out = String::new();
-                                            let mut thought_out = String::new();
-                                            
-                                            if let Some(parts_list) = parts {
-                                                for part in parts_list {
-                                                    let is_thought_part = part.get("thought")
-                                                        .and_then(|v| v.as_bool())
-                                                        .unwrap_or(false);
-                                                    
-                                                    if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
-                                                        if is_thought_part {
-                                                            thought_out.push_str(text);
-                                                        } else {
-                                                            content_out.push_str(text);
-                                                        }
-                                                    }
-                                                    // 捕获 thoughtSignature (Gemini 3 工具调用必需)
-                                                    if let Some(sig) = part.get("thoughtSignature").or(part.get("thought_signature")).and_then(|s| s.as_str()) {
-                                                        store_thought_signature(sig);
-                                                    }
-
-                                                    if let Some(img) = part.get("inlineData") {
-                                                        let mime_type = img.get("mimeType").and_then(|v| v.as_str()).unwrap_or("image/png");
-                                                        let data = img.get("data").and_then(|v| v.as_str()).unwrap_or("");
-                                                        if !data.is_empty() {
-                                                            content_out.push_str(&format!("![image](data:{};base64,{})", mime_type, data));
-                                                        }
-                                                    }
-
-                                                    // Handle function call
-                                                    if let Some(func_call) = part.get("functionCall") {
-                                                        let call_key = serde_json::to_string(func_call).unwrap_or_default();
-                                                        if !emitted_tool_calls.contains(&call_key) {
-                                                            emitted_tool_calls.insert(call_key);
-                                                            
-                                                            let name = func_call.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                                            let args = func_call.get("args").unwrap_or(&json!({})).to_string();
-                                                            
-                                                            // Generate stable ID
-                                                            let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                                                            use std::hash::{Hash, Hasher};
-                                                            serde_json::to_string(func_call).unwrap_or_default().hash(&mut hasher);
-                                                            let call_id = format!("call_{:x}", hasher.finish());
-                                                            
-                                                            // Emit tool_calls delta
-                                                            let tool_call_chunk = json!({
-                                                                "id": &stream_id,
-                                                                "object": "chat.completion.chunk",
-                                                                "created": created_ts,
-                                                                "model": &model,
-                                                                "choices": [{
-                                                                    "index": idx as u32,
-                                                                    "delta": {
-                                                                        "role": "assistant",
-                                                                        "tool_calls": [{
-                                                                            "index": 0,
-                                                                            "id": call_id,
-                                                                            "type": "function",
-                                                                            "function": {
-                                                                                "name": name,
-                                                                                "arguments": args
-                                                                            }
-                                                                        }]
-                                                                    },
-                                                                    "finish_reason": serde_json::Value::Null
-                                                                }]
-                                                            });
-                                                            
-                                                            let sse_out = format!("data: {}\n\n", serde_json::to_string(&tool_call_chunk).unwrap_or_default());
-                                                            yield Ok::<Bytes, String>(Bytes::from(sse_out));
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-
-                                            // 处理联网搜索引文 (Grounding Metadata) - 流式
-                                            if let Some(grounding) = candidate.get("groundingMetadata") {
-                                                let mut grounding_text = String::new();
-                                                
-                                                // 1. 处理搜索词
-                                                if let Some(queries) = grounding.get("webSearchQueries").and_then(|q| q.as_array()) {
-                                                    let query_list: Vec<&str> = queries.iter().filter_map(|v| v.as_str()).collect();
-                                                    if !query_list.is_empty() {
-                                                        grounding_text.push_str("\n\n---\n**🔍 已为您搜索：** ");
-                                                        grounding_text.push_str(&query_list.join(", "));
-                                                    }
-                                                }
-
-                                                // 2. 处理来源链接 (Chunks)
-                                                if let Some(chunks) = grounding.get("groundingChunks").and_then(|c| c.as_array()) {
-                                                    let mut links = Vec::new();
-                                                    for (i, chunk) in chunks.iter().enumerate() {
-                                                        if let Some(web) = chunk.get("web") {
-                                                            let title = web.get("title").and_then(|v| v.as_str()).unwrap_or("网页来源");
-                                                            let uri = web.get("uri").and_then(|v| v.as_str()).unwrap_or("#");
-                                                            links.push(format!("[{}] [{}]({})", i + 1, title, uri));
-                                                        }
-                                                    }
-                                                    if !links.is_empty() {
-                                                        grounding_text.push_str("\n\n**🌐 来源引文：**\n");
-                                                        grounding_text.push_str(&links.join("\n"));
-                                                    }
-                                                }
-                                                
-                                                if !grounding_text.is_empty() {
-                                                    content_out.push_str(&grounding_text);
-                                                }
-                                            }
-
-                                            // 只有当 content 和 thought 都为空时才跳过
-                                            if content_out.is_empty() && thought_out.is_empty() {
-                                                // Skip empty chunks if no text/grounding/thought was found
-                                                if candidate.get("finishReason").is_none() {
-                                                    continue;
-                                                }
-                                            }
-                                                
-                                            // Extract finish reason
-                                            let finish_reason = candidate.get("finishReason")
-                                                .and_then(|f| f.as_str())
-                                                .map(|f| match f {
-                                                    "STOP" => "stop",
-                                                    "MAX_TOKENS" => "length",
-                                                    "SAFETY" => "content_filter",
-                                                    "RECITATION" => "content_filter",
-                                                    _ => f,
-                                                });
-
-                                            // Construct OpenAI SSE chunk
-                                            // 如果有思考内容，先发送 reasoning_content chunk
-                                            if !thought_out.is_empty() {
-                                                let reasoning_chunk = json!({
-                                                    "id": &stream_id,
-                                                    "object": "chat.completion.chunk",
-                                                    "created": created_ts,
-                                                    "model": model,
-                                                    "choices": [
-                                                        {
-                                                            "index": idx as u32,
-                                                            "delta": {
-                                                                "role": "assistant",
-                                                                "content": serde_json::Value::Null,
-                                                                "reasoning_content": thought_out
-                                                            },
-                                                            "finish_reason": serde_json::Value::Null
-                                                        }
-                                                    ]
-                                                });
-                                                let sse_out = format!("data: {}\n\n", serde_json::to_string(&reasoning_chunk).unwrap_or_default());
-                                                yield Ok::<Bytes, String>(Bytes::from(sse_out));
-                                            }
-
-                                            // 发送正常 content chunk
-                                            if !content_out.is_empty() || finish_reason.is_some() {
-                                                let openai_chunk = json!({
-                                                    "id": &stream_id,
-                                                    "object": "chat.completion.chunk",
-                                                    "created": created_ts,
-                                                    "model": model,
-                                                    "choices": [
-                                                        {
-                                                            "index": idx as u32,
-                                                            "delta": {
-                                                                "content": content_out
-                                                            },
-                                                            "finish_reason": finish_reason
-                                                        }
-                                                    ]
-                                                });
-
-                                                let sse_out = format!("data: {}\n\n", serde_json::to_string(&openai_chunk).unwrap_or_default());
-                                                yield Ok::<Bytes, String>(Bytes::from(sse_out));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+                        let Ok(line_str) = std::str::from_utf8(&line_raw) else { continue };
+                        let Some(json_part) = line_str.trim().strip_prefix("data: ") else { continue };
+                        let json_part = json_part.trim();
+                        if json_part == "[DONE]" {
+                            continue;
+                        }
+                        for chunk in converter.convert(json_part) {
+                            yield Ok::<Bytes, String>(chunk);
                         }
                     }
                 }
                 Err(e) => {
-                    use crate::proxy::mappers::error_classifier::classify_stream_error;
+                    use crate::mappers::error_classifier::classify_stream_error;
                     let (error_type, user_message, i18n_key) = classify_stream_error(&e);
                     
                     tracing::error!(
@@ -293,10 +366,10 @@ pub fn create_openai_sse_stream(
                     
                     // 发送友好的 SSE 错误事件(包含 i18n_key 供前端翻译)
                     let error_chunk = json!({
-                        "id": &stream_id,
+                        "id": &converter.stream_id,
                         "object": "chat.completion.chunk",
-                        "created": created_ts,
-                        "model": &model,
+                        "created": converter.created_ts,
+                        "model": &converter.model,
                         "choices": [],
                         "error": {
                             "type": error_type,
@@ -358,7 +431,7 @@ pub fn create_legacy_sse_stream(
                                     
                                     let mut content_out = String::new();
                                     if let Some(candidates) = actual_data.get("candidates").and_then(|c| c.as_array()) {
-                                        if let Some(parts) = candidates.get(0).and_then(|c| c.get("content")).and_then(|c| c.get("parts")).and_then(|p| p.as_array()) {
+                                        if let Some(parts) = candidates.first().and_then(|c| c.get("content")).and_then(|c| c.get("parts")).and_then(|p| p.as_array()) {
                                             for part in parts {
                                                 if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
                                                     content_out.push_str(text);
@@ -379,7 +452,7 @@ pub fn create_legacy_sse_stream(
 
                                     let finish_reason = actual_data.get("candidates")
                                         .and_then(|c| c.as_array())
-                                        .and_then(|c| c.get(0))
+                                        .and_then(|c| c.first())
                                         .and_then(|c| c.get("finishReason"))
                                         .and_then(|f| f.as_str())
                                         .map(|f| match f {
@@ -415,7 +488,7 @@ pub fn create_legacy_sse_stream(
                     }
                 }
                 Err(e) => {
-                    use crate::proxy::mappers::error_classifier::classify_stream_error;
+                    use crate::mappers::error_classifier::classify_stream_error;
                     let (error_type, user_message, i18n_key) = classify_stream_error(&e);
                     
                     tracing::error!(
@@ -507,7 +580,7 @@ pub fn create_codex_sse_stream(
                                 
                                 // Capture finish reason
                                 if let Some(candidates) = actual_data.get("candidates").and_then(|c| c.as_array()) {
-                                    if let Some(candidate) = candidates.get(0) {
+                                    if let Some(candidate) = candidates.first() {
                                         if let Some(reason) = candidate.get("finishReason").and_then(|r| r.as_str()) {
                                             last_finish_reason = match reason {
                                                 "STOP" => "stop".to_string(),
@@ -521,12 +594,12 @@ pub fn create_codex_sse_stream(
                                 // text delta
                                 let mut delta_text = String::new();
                                 if let Some(candidates) = actual_data.get("candidates").and_then(|c| c.as_array()) {
-                                    if let Some(candidate) = candidates.get(0) {
+                                    if let Some(candidate) = candidates.first() {
                                         if let Some(parts) = candidate.get("content").and_then(|c| c.get("parts")).and_then(|p| p.as_array()) {
                                             for part in parts {
                                                 if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
                                                     // Sanitize smart quotes to standard quotes for JSON compatibility
-                                                    let clean_text = text.replace('“', "\"").replace('”', "\"");
+                                                    let clean_text = text.replace(['“', '”'], "\"");
                                                     delta_text.push_str(&clean_text);
                                                 }
                                                 /* 禁用思维链输出到正文
@@ -714,7 +787,7 @@ pub fn create_codex_sse_stream(
                     }
                 }
                 Err(e) => {
-                    use crate::proxy::mappers::error_classifier::classify_stream_error;
+                    use crate::mappers::error_classifier::classify_stream_error;
                     let (error_type, user_message, i18n_key) = classify_stream_error(&e);
                     
                     tracing::error!(
@@ -776,67 +849,65 @@ pub fn create_codex_sse_stream(
                 if *c == '{' {
                     if depth == 0 { start_idx = i; }
                     depth += 1;
-                } else if *c == '}' {
-                    if depth > 0 {
-                        depth -= 1;
-                        if depth == 0 {
-                            // Found a potential JSON object block [start_idx..=i]
-                            let json_str: String = chars[start_idx..=i].iter().collect();
-                            if let Ok(val) = serde_json::from_str::<Value>(&json_str) {
-                                // Check for "command" field
-                                if let Some(cmd_val) = val.get("command") {
-                                    // Found a command! Identify type.
-                                    // Case 1: "command": ["shell", ...] or ["ls", ...]
-                                    if let Some(arr) = cmd_val.as_array() {
-                                        if let Some(first) = arr.get(0).and_then(|v| v.as_str()) {
-                                            if first == "shell" || first == "powershell" || first == "cmd" || first == "ls" || first == "git" || first == "echo" {
-                                                detected_cmd_type = "shell";
-                                                detected_cmd_val = Some(cmd_val.clone());
-                                            }
+                } else if *c == '}' && depth > 0 {
+                    depth -= 1;
+                    if depth == 0 {
+                        // Found a potential JSON object block [start_idx..=i]
+                        let json_str: String = chars[start_idx..=i].iter().collect();
+                        if let Ok(val) = serde_json::from_str::<Value>(&json_str) {
+                            // Check for "command" field
+                            if let Some(cmd_val) = val.get("command") {
+                                // Found a command! Identify type.
+                                // Case 1: "command": ["shell", ...] or ["ls", ...]
+                                if let Some(arr) = cmd_val.as_array() {
+                                    if let Some(first) = arr.first().and_then(|v| v.as_str()) {
+                                        if first == "shell" || first == "powershell" || first == "cmd" || first == "ls" || first == "git" || first == "echo" {
+                                            detected_cmd_type = "shell";
+                                            detected_cmd_val = Some(cmd_val.clone());
                                         }
-                                    } 
-                                    // Case 2: "command": "shell" (String) and "args": { "command": "..." }
-                                    // This matches the user's latest screenshot which failed SSOP.
-                                    else if let Some(cmd_str) = cmd_val.as_str() {
-                                        if cmd_str == "shell" || cmd_str == "local_shell" {
-                                             // Enhanced matching for params/argument
-                                             if let Some(args) = val.get("args").or(val.get("arguments")).or(val.get("params")) {
-                                                  if let Some(inner_cmd) = args.get("command").or(args.get("code")).or(args.get("argument")) {
-                                                      // We construct a synthetic array: ["shell", inner_cmd]
-                                                      // So subsequent logic can process it.
-                                                      // Actually, let's just grab the inner command string.
-                                                      if let Some(inner_cmd_str) = inner_cmd.as_str() {
-                                                          detected_cmd_type = "shell";
-                                                          detected_cmd_val = Some(json!([inner_cmd_str]));
-                                                      }
+                                    }
+                                } 
+                                // Case 2: "command": "shell" (String) and "args": { "command": "..." }
+                                // This matches the user's latest screenshot which failed SSOP.
+                                else if let Some(cmd_str) = cmd_val.as_str() {
+                                    if cmd_str == "shell" || cmd_str == "local_shell" {
+                                         // Enhanced matching for params/argument
+                                         if let Some(args) = val.get("args").or(val.get("arguments")).or(val.get("params")) {
+                                              if let Some(inner_cmd) = args.get("command").or(args.get("code")).or(args.get("argument")) {
+                                                  // We construct a synthetic array: ["shell", inner_cmd]
+                                                  // So subsequent logic can process it.
+                                                  // Actually, let's just grab the inner command string.
+                                                  if let Some(inner_cmd_str) = inner_cmd.as_str() {
+                                                      detected_cmd_type = "shell";
+                                                      detected_cmd_val = Some(json!([inner_cmd_str]));
                                                   }
                                               }
-                                        }
+                                          }
                                     }
                                 }
-                            } else {
-                                // Fallback for malformed JSON (e.g. unescaped quotes)
-                                // 注意: 使用安全的切片方法避免 UTF-8 边界 panic
-                                if (json_str.contains("\"command\": \"shell\"") || json_str.contains("\"command\": \"local_shell\"")) 
-                                   && (json_str.contains("\"argument\":") || json_str.contains("\"code\":")) {
-                                    
-                                    let keys = ["\"argument\":", "\"code\":", "\"command\":"];
-                                    for key in keys {
-                                        if let Some(pos) = json_str.find(key) {
-                                            // 使用安全的 get() 方法替代直接索引
-                                            let slice_start = pos + key.len();
-                                            if let Some(slice_after_key) = json_str.get(slice_start..) {
-                                                if let Some(quote_idx) = slice_after_key.find('"') {
-                                                    let val_start_abs = slice_start + quote_idx + 1;
-                                                    if let Some(last_quote_idx) = json_str.rfind('"') {
-                                                        if last_quote_idx > val_start_abs {
-                                                            // 使用 get() 安全获取子字符串
-                                                            if let Some(raw_cmd) = json_str.get(val_start_abs..last_quote_idx) {
-                                                                detected_cmd_type = "shell";
-                                                                detected_cmd_val = Some(json!([raw_cmd]));
-                                                                tracing::debug!("SSOP: Recovered malformed JSON command: {}", raw_cmd);
-                                                                break;
-                                                            }
+                            }
+                        } else {
+                            // Fallback for malformed JSON (e.g. unescaped quotes)
+                            // 注意: 使用安全的切片方法避免 UTF-8 边界 panic
+                            if (json_str.contains("\"command\": \"shell\"") || json_str.contains("\"command\": \"local_shell\"")) 
+                               && (json_str.contains("\"argument\":") || json_str.contains("\"code\":")) {
+                                
+                                let keys = ["\"argument\":", "\"code\":", "\"command\":"];
+                                for key in keys {
+                                    if let Some(pos) = json_str.find(key) {
+                                        // 使用安全的 get() 方法替代直接索引
+                                        let slice_start = pos + key.len();
+                                        if let Some(slice_after_key) = json_str.get(slice_start..) {
+                                            if let Some(quote_idx) = slice_after_key.find('"') {
+                                                let val_start_abs = slice_start + quote_idx + 1;
+                                                if let Some(last_quote_idx) = json_str.rfind('"') {
+                                                    if last_quote_idx > val_start_abs {
+                                                        // 使用 get() 安全获取子字符串
+                                                        if let Some(raw_cmd) = json_str.get(val_start_abs..last_quote_idx) {
+                                                            detected_cmd_type = "shell";
+                                                            detected_cmd_val = Some(json!([raw_cmd]));
+                                                            tracing::debug!("SSOP: Recovered malformed JSON command: {}", raw_cmd);
+                                                            break;
                                                         }
                                                     }
                                                 }
@@ -946,3 +1017,41 @@ pub fn create_codex_sse_stream(
 
     Box::pin(stream)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_sse(chunk: &Bytes) -> Value {
+        let text = std::str::from_utf8(chunk).unwrap();
+        serde_json::from_str(text.strip_prefix("data: ").unwrap().trim_end()).unwrap()
+    }
+
+    #[test]
+    fn test_chat_chunk_converter_tool_call_and_text() {
+        let mut converter = ChatChunkConverter::new("gemini-3-pro".to_string());
+        let payload = r#"{"response":{"candidates":[{"content":{"parts":[
+            {"text":"plan","thought":true},
+            {"text":"hi"},
+            {"functionCall":{"name":"write_file","args":{"path":"a.rs","content":"fn main() {}\n"}}}
+        ]}}]}}"#;
+
+        let chunks = converter.convert(payload);
+        assert_eq!(chunks.len(), 3);
+        let tool = parse_sse(&chunks[0]);
+        let function = &tool["choices"][0]["delta"]["tool_calls"][0]["function"];
+        assert_eq!(function["name"], "write_file");
+        let args: Value = serde_json::from_str(function["arguments"].as_str().unwrap()).unwrap();
+        assert_eq!(args, json!({ "path": "a.rs", "content": "fn main() {}\n" }));
+        assert_eq!(parse_sse(&chunks[1])["choices"][0]["delta"]["reasoning_content"], "plan");
+        assert_eq!(parse_sse(&chunks[2])["choices"][0]["delta"]["content"], "hi");
+
+        // 重复出现的同一工具调用不再输出，finishReason 单独成块
+        let repeat = r#"{"candidates":[{"content":{"parts":[{"functionCall":{"name":"write_file","args":{"path":"a.rs","content":"fn main() {}\n"}}}]},"finishReason":"STOP"}]}"#;
+        let chunks = converter.convert(repeat);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(parse_sse(&chunks[0])["choices"][0]["finish_reason"], "stop");
+
+        assert!(converter.convert("not json").is_empty());
+    }
+}
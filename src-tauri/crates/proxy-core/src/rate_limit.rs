@@ -3,7 +3,7 @@ use std::time::{SystemTime, Duration};
 use regex::Regex;
 
 /// 限流原因类型
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
 pub enum RateLimitReason {
     /// 配额耗尽 (QUOTA_EXHAUSTED)
     QuotaExhausted,
@@ -43,6 +43,8 @@ pub struct RateLimitTracker {
     limits: DashMap<String, RateLimitInfo>,
     /// 连续失败计数（用于智能指数退避）
     failure_counts: DashMap<String, u32>,
+    /// 新增限流记录时通知冷却监视任务重新计算唤醒时间
+    changed: tokio::sync::Notify,
 }
 
 impl RateLimitTracker {
@@ -50,6 +52,7 @@ impl RateLimitTracker {
         Self {
             limits: DashMap::new(),
             failure_counts: DashMap::new(),
+            changed: tokio::sync::Notify::new(),
         }
     }
     
@@ -99,6 +102,7 @@ impl RateLimitTracker {
         };
         
         self.limits.insert(account_id.to_string(), info);
+        self.changed.notify_waiters();
         
         if let Some(m) = &model {
             tracing::info!(
@@ -171,10 +175,12 @@ impl RateLimitTracker {
         
         let mut retry_after_sec = None;
         
-        // 2. 从 Retry-After header 提取
+        // 2. 从 Retry-After header 提取 (秒数或 HTTP 日期)
         if let Some(retry_after) = retry_after_header {
-            if let Ok(seconds) = retry_after.parse::<u64>() {
+            if let Ok(seconds) = retry_after.trim().parse::<u64>() {
                 retry_after_sec = Some(seconds);
+            } else if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(retry_after.trim()) {
+                retry_after_sec = seconds_until(dt.timestamp());
             }
         }
         
@@ -256,6 +262,7 @@ impl RateLimitTracker {
         
         // 存储
         self.limits.insert(account_id.to_string(), info.clone());
+        self.changed.notify_waiters();
         
         tracing::warn!(
             "账号 {} [{}] 限流类型: {:?}, 重置延时: {}秒",
@@ -277,7 +284,7 @@ impl RateLimitTracker {
                 if let Some(reason_str) = json.get("error")
                     .and_then(|e| e.get("details"))
                     .and_then(|d| d.as_array())
-                    .and_then(|a| a.get(0))
+                    .and_then(|a| a.first())
                     .and_then(|o| o.get("reason"))
                     .and_then(|v| v.as_str()) {
                     
@@ -343,7 +350,7 @@ impl RateLimitTracker {
         tracing::debug!("[时间解析] 提取结果: {}h {}m {:.3}s {}ms", hours, minutes, seconds, milliseconds);
         
         // 计算总秒数
-        let total_seconds = hours * 3600 + minutes * 60 + seconds.ceil() as u64 + (milliseconds + 999) / 1000;
+        let total_seconds = hours * 3600 + minutes * 60 + seconds.ceil() as u64 + milliseconds.div_ceil(1000);
         
         // 如果总秒数为 0，说明解析失败
         if total_seconds == 0 {
@@ -362,23 +369,36 @@ impl RateLimitTracker {
         let trimmed = body.trim();
         if trimmed.starts_with('{') || trimmed.starts_with('[') {
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(trimmed) {
-                // 1. Google 常见的 quotaResetDelay 格式 (支持所有格式："2h1m1s", "1h30m", "42s", "500ms" 等)
-                // 路径: error.details[0].metadata.quotaResetDelay
-                if let Some(delay_str) = json.get("error")
+                // 1. Google error.details[] 中的重置提示 (任意位置):
+                //    - metadata.quotaResetDelay: "2h1m1s", "1h30m", "42s", "500ms" 等
+                //    - metadata.quotaResetTimeStamp: 绝对时间 (RFC 3339)
+                //    - RetryInfo.retryDelay: "37s"
+                let details = json.get("error")
                     .and_then(|e| e.get("details"))
                     .and_then(|d| d.as_array())
-                    .and_then(|a| a.get(0))
-                    .and_then(|o| o.get("metadata"))  // 添加 metadata 层级
-                    .and_then(|m| m.get("quotaResetDelay"))
-                    .and_then(|v| v.as_str()) {
-                    
-                    tracing::debug!("[JSON解析] 找到 quotaResetDelay: '{}'", delay_str);
-                    
-                    // 使用通用时间解析函数
-                    if let Some(seconds) = self.parse_duration_string(delay_str) {
-                        return Some(seconds);
+                    .cloned()
+                    .unwrap_or_default();
+                for detail in &details {
+                    let metadata = detail.get("metadata");
+                    if let Some(delay_str) = metadata.and_then(|m| m.get("quotaResetDelay")).and_then(|v| v.as_str()) {
+                        tracing::debug!("[JSON解析] 找到 quotaResetDelay: '{}'", delay_str);
+                        // 使用通用时间解析函数
+                        if let Some(seconds) = self.parse_duration_string(delay_str) {
+                            return Some(seconds);
+                        }
+                    }
+                    if let Some(ts) = metadata.and_then(|m| m.get("quotaResetTimeStamp")).and_then(|v| v.as_str()) {
+                        if let Some(seconds) = chrono::DateTime::parse_from_rfc3339(ts).ok().and_then(|dt| seconds_until(dt.timestamp())) {
+                            return Some(seconds);
+                        }
                     }
                 }
+                if let Some(seconds) = details.iter()
+                    .filter_map(|d| d.get("retryDelay").and_then(|v| v.as_str()))
+                    .find_map(|s| self.parse_duration_string(s))
+                {
+                    return Some(seconds);
+                }
                 
                 // 2. OpenAI 常见的 retry_after 字段 (数字)
                 if let Some(retry) = json.get("error")
@@ -438,6 +458,16 @@ impl RateLimitTracker {
         None
     }
     
+    /// 最早结束的冷却时间 (用于精确调度自动恢复)
+    pub fn next_reset(&self) -> Option<SystemTime> {
+        self.limits.iter().map(|e| e.value().reset_time).min()
+    }
+
+    /// 等待新的限流记录写入
+    pub async fn changed(&self) {
+        self.changed.notified().await
+    }
+
     /// 获取账号的限流信息
     pub fn get(&self, account_id: &str) -> Option<RateLimitInfo> {
         self.limits.get(account_id).map(|r| r.clone())
@@ -467,17 +497,7 @@ impl RateLimitTracker {
     /// 清除过期的限流记录
     #[allow(dead_code)]
     pub fn cleanup_expired(&self) -> usize {
-        let now = SystemTime::now();
-        let mut count = 0;
-        
-        self.limits.retain(|_k, v| {
-            if v.reset_time <= now {
-                count += 1;
-                false
-            } else {
-                true
-            }
-        });
+        let count = self.take_expired().len();
         
         if count > 0 {
             tracing::debug!("清除了 {} 个过期的限流记录", count);
@@ -486,6 +506,31 @@ impl RateLimitTracker {
         count
     }
     
+    /// 移除并返回已过期的限流记录 (冷却结束的账号)
+    pub fn take_expired(&self) -> Vec<(String, RateLimitInfo)> {
+        let now = SystemTime::now();
+        let expired: Vec<String> = self.limits
+            .iter()
+            .filter(|e| e.value().reset_time <= now)
+            .map(|e| e.key().clone())
+            .collect();
+        
+        expired
+            .into_iter()
+            .filter_map(|k| self.limits.remove_if(&k, |_, v| v.reset_time <= now))
+            .collect()
+    }
+    
+    /// 获取所有仍在冷却中的限流记录
+    pub fn active_limits(&self) -> Vec<(String, RateLimitInfo)> {
+        let now = SystemTime::now();
+        self.limits
+            .iter()
+            .filter(|e| e.value().reset_time > now)
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect()
+    }
+    
     /// 清除指定账号的限流记录
     #[allow(dead_code)]
     pub fn clear(&self, account_id: &str) -> bool {
@@ -503,6 +548,12 @@ impl RateLimitTracker {
     }
 }
 
+/// 距离指定 Unix 时间戳的秒数 (向上取整，已过去返回 None)
+fn seconds_until(timestamp: i64) -> Option<u64> {
+    let remaining = timestamp - chrono::Utc::now().timestamp();
+    (remaining > 0).then_some(remaining as u64)
+}
+
 impl Default for RateLimitTracker {
     fn default() -> Self {
         Self::new()
@@ -539,6 +590,29 @@ mod tests {
         assert_eq!(time, Some(42));
     }
 
+    #[test]
+    fn test_parse_reset_hints_anywhere_in_details() {
+        let tracker = RateLimitTracker::new();
+        let body = r#"{"error": {"details": [
+            {"@type": "type.googleapis.com/google.rpc.ErrorInfo", "reason": "RATE_LIMIT_EXCEEDED"},
+            {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "37s"}
+        ]}}"#;
+        assert_eq!(tracker.parse_retry_time_from_body(body), Some(37));
+
+        let reset_at = (chrono::Utc::now() + chrono::Duration::seconds(600)).to_rfc3339();
+        let body = format!(
+            r#"{{"error": {{"details": [{{"@type": "x"}}, {{"metadata": {{"quotaResetTimeStamp": "{}"}}}}]}}}}"#,
+            reset_at
+        );
+        let secs = tracker.parse_retry_time_from_body(&body).unwrap();
+        assert!((598..=600).contains(&secs));
+
+        let header = (chrono::Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+        let info = tracker.parse_from_error("acc1", 429, Some(&header), "", None).unwrap();
+        assert!((118..=120).contains(&info.retry_after_sec));
+        assert_eq!(tracker.next_reset(), Some(info.reset_time));
+    }
+
     #[test]
     fn test_parse_retry_after_ignore_case() {
         let tracker = RateLimitTracker::new();
@@ -562,7 +636,7 @@ mod tests {
         tracker.parse_from_error("acc1", 429, Some("1"), "", None);
         let wait = tracker.get_remaining_wait("acc1");
         // Due to time passing, it might be 1 or 2
-        assert!(wait >= 1 && wait <= 2);
+        assert!((1..=2).contains(&wait));
     }
 
     #[test]
@@ -574,4 +648,21 @@ mod tests {
         // 应该被识别为 RateLimitExceeded，而不是 QuotaExhausted
         assert_eq!(reason, RateLimitReason::RateLimitExceeded);
     }
+
+    #[test]
+    fn test_take_expired_returns_finished_cooldowns() {
+        let tracker = RateLimitTracker::new();
+        let now = SystemTime::now();
+        tracker.set_lockout_until("expired", now - Duration::from_secs(1), RateLimitReason::QuotaExhausted, None);
+        tracker.set_lockout_until("cooling", now + Duration::from_secs(300), RateLimitReason::QuotaExhausted, None);
+
+        let expired = tracker.take_expired();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, "expired");
+        assert!(tracker.get("expired").is_none());
+
+        let active = tracker.active_limits();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].0, "cooling");
+    }
 }
@@ -0,0 +1,343 @@
+// 账号轮换规则
+//
+// 账号池 (token_pool) 使用的无状态规则: 账号令牌 (ProxyToken) 与可用时段、
+// 候选账号排序 (轮询起点 + 选择策略 + 优先级)、多项目选择、并发占用，以及封禁 / 账号池耗尽的识别。
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::sticky_config::{ProjectStrategy, SelectionStrategy};
+
+/// 账号的一个可用时段 ("HH:MM"，本地时间)，end 早于 start 表示跨越午夜
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageWindow {
+    pub start: String,
+    pub end: String,
+}
+
+impl UsageWindow {
+    fn parse_minutes(value: &str) -> Option<u32> {
+        let (h, m) = value.trim().split_once(':')?;
+        let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+        (h < 24 && m < 60).then_some(h * 60 + m)
+    }
+
+    /// 校验时间格式
+    pub fn validate(&self) -> Result<(), String> {
+        for value in [&self.start, &self.end] {
+            if Self::parse_minutes(value).is_none() {
+                return Err(format!("无效的时间: {} (应为 HH:MM)", value));
+            }
+        }
+        Ok(())
+    }
+
+    /// 指定时刻 (当天第几分钟) 是否落在该时段内；start == end 视为全天
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        let (Some(start), Some(end)) = (Self::parse_minutes(&self.start), Self::parse_minutes(&self.end)) else {
+            return true;
+        };
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => (start..end).contains(&minute_of_day),
+            std::cmp::Ordering::Greater => minute_of_day >= start || minute_of_day < end,
+            std::cmp::Ordering::Equal => true,
+        }
+    }
+}
+
+/// 当前本地时间是当天的第几分钟 (用于匹配账号可用时段)
+pub fn current_minute_of_day() -> u32 {
+    use chrono::Timelike;
+    let now = chrono::Local::now();
+    now.hour() * 60 + now.minute()
+}
+
+/// 账号池中的一个账号 (内存中的令牌与调度属性)
+#[derive(Debug, Clone)]
+pub struct ProxyToken {
+    pub account_id: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+    pub timestamp: i64,
+    pub email: String,
+    pub project_id: Option<String>,
+    pub subscription_tier: Option<String>, // "FREE" | "PRO" | "ULTRA"
+    pub remaining_quota: Option<i32>, // [FIX #563] Remaining quota for priority sorting
+    pub protected_models: HashSet<String>, // [NEW #621]
+    pub available_models: HashSet<String>, // 配额接口返回的可用模型 (为空表示未知)
+    pub usage_windows: Vec<UsageWindow>, // 可用时段 (为空表示全天可用)
+    pub egress_proxy: Option<String>, // 账号独立出口代理
+    pub project_id_override: Option<String>, // 手动指定的 project_id (跳过自动解析)
+    pub projects: Vec<String>, // 账号可访问的全部项目 (多项目选择策略)
+    pub tags: Vec<String>, // 账号标签
+    pub priority: i32, // 调度优先级，数值越大越优先
+}
+
+/// 候选账号的尝试顺序
+///
+/// 以轮询起点 `start_idx` 为基础顺序，再按策略做稳定排序，
+/// 因此同优先级的账号之间仍然会轮换。
+pub fn candidate_order(
+    tokens: &[ProxyToken],
+    start_idx: usize,
+    strategy: SelectionStrategy,
+    last_selected: impl Fn(&str) -> Option<Instant>,
+    error_count: impl Fn(&str) -> u32,
+) -> Vec<usize> {
+    let total = tokens.len();
+    if total == 0 {
+        return Vec::new();
+    }
+    let start_idx = start_idx % total;
+    let mut order: Vec<usize> = (0..total).map(|offset| (start_idx + offset) % total).collect();
+
+    match strategy {
+        SelectionStrategy::RoundRobin => {}
+        SelectionStrategy::LeastRecentlyUsed => {
+            // 从未被选中的账号 (None) 排在最前
+            order.sort_by_key(|&i| last_selected(&tokens[i].account_id));
+        }
+        SelectionStrategy::LeastErrors => {
+            order.sort_by_key(|&i| error_count(&tokens[i].account_id));
+        }
+        SelectionStrategy::MostRemainingQuota => {
+            order.sort_by_key(|&i| std::cmp::Reverse(tokens[i].remaining_quota.unwrap_or(0)));
+        }
+    }
+
+    // 稳定排序: 策略顺序只在同一优先级内生效
+    order.sort_by_key(|&i| std::cmp::Reverse(tokens[i].priority));
+
+    order
+}
+
+/// 按策略从账号的项目中选出本次请求使用的项目
+///
+/// `resolved` 为 loadCodeAssist 返回的默认项目，`known` 为账号已知的全部项目，
+/// `rotation` 为该账号的轮换计数
+pub fn pick_project(
+    strategy: ProjectStrategy,
+    preference: &[String],
+    resolved: &str,
+    known: &[String],
+    rotation: usize,
+) -> String {
+    match strategy {
+        ProjectStrategy::First => resolved.to_string(),
+        ProjectStrategy::Preference => preference
+            .iter()
+            .find(|p| p.as_str() == resolved || known.contains(p))
+            .cloned()
+            .unwrap_or_else(|| resolved.to_string()),
+        ProjectStrategy::Rotate => {
+            let mut candidates: Vec<&str> = vec![resolved];
+            candidates.extend(known.iter().map(|s| s.as_str()).filter(|p| *p != resolved));
+            candidates[rotation % candidates.len()].to_string()
+        }
+    }
+}
+
+/// 账号并发占用，drop 时释放槽位
+///
+/// 调用方需在整个上游请求期间持有 (流式响应通过 [`AccountLease::hold_during`] 绑定到响应流)
+pub struct AccountLease {
+    account_id: String,
+    in_flight: Arc<DashMap<String, u32>>,
+    slot_released: Arc<tokio::sync::Notify>,
+}
+
+impl AccountLease {
+    /// 占用账号的一个并发槽位
+    pub fn acquire(
+        in_flight: &Arc<DashMap<String, u32>>,
+        slot_released: &Arc<tokio::sync::Notify>,
+        account_id: &str,
+    ) -> Self {
+        *in_flight.entry(account_id.to_string()).or_insert(0) += 1;
+        Self {
+            account_id: account_id.to_string(),
+            in_flight: in_flight.clone(),
+            slot_released: slot_released.clone(),
+        }
+    }
+
+    /// 将占用绑定到响应流上，流结束 (或客户端断开) 时释放
+    pub fn hold_during<S>(self, stream: S) -> impl futures::Stream<Item = S::Item>
+    where
+        S: futures::Stream,
+    {
+        use futures::StreamExt;
+        stream.map(move |item| {
+            let _lease = &self;
+            item
+        })
+    }
+}
+
+impl Drop for AccountLease {
+    fn drop(&mut self) {
+        if let Some(mut count) = self.in_flight.get_mut(&self.account_id) {
+            *count = count.saturating_sub(1);
+        }
+        self.in_flight.remove_if(&self.account_id, |_, count| *count == 0);
+        self.slot_released.notify_waiters();
+    }
+}
+
+/// 账号池耗尽错误的前缀，客户端可据此区分于普通的单次请求失败
+pub const POOL_EXHAUSTED_ERROR: &str = "pool_exhausted";
+
+/// 错误是否表示账号池已耗尽
+pub fn is_pool_exhausted(error: &str) -> bool {
+    error.starts_with(POOL_EXHAUSTED_ERROR)
+}
+
+/// Google 错误中表明账号被封禁/停用的 `reason` (ErrorInfo) 或 `error` (OAuth) 取值
+const SUSPENSION_REASONS: &[&str] = &[
+    "ACCOUNT_DISABLED",
+    "ACCOUNT_SUSPENDED",
+    "USER_DISABLED",
+    "CONSUMER_SUSPENDED",
+    "TOS_VIOLATION",
+];
+
+/// 没有结构化 reason 时使用的完整错误文案 (小写匹配)，只收录封禁专用的表述
+const SUSPENSION_MESSAGES: &[&str] = &[
+    "account has been suspended",
+    "account has been disabled",
+    "disabled in this account for violation of terms of service",
+];
+
+/// 错误体中的 reason: `error.details[].reason`、`error.status` 以及 OAuth 的 `error`
+fn error_reasons(error_body: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(error_body) else { return Vec::new() };
+    let error = &json["error"];
+    let mut reasons: Vec<String> = error["details"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|detail| detail["reason"].as_str())
+        .map(str::to_string)
+        .collect();
+    reasons.extend(error["status"].as_str().map(str::to_string));
+    reasons.extend(error.as_str().map(str::to_string));
+    reasons
+}
+
+/// 识别表明账号被封禁/停用的上游错误，返回命中的特征
+pub fn detect_account_suspension(status: u16, error_body: &str) -> Option<&'static str> {
+    // 封禁类错误只会以 400/401/403 返回，避免误伤限流或服务端错误
+    if !matches!(status, 400 | 401 | 403) {
+        return None;
+    }
+    let reasons = error_reasons(error_body);
+    if let Some(reason) = SUSPENSION_REASONS
+        .iter()
+        .copied()
+        .find(|known| reasons.iter().any(|r| r.eq_ignore_ascii_case(known)))
+    {
+        return Some(reason);
+    }
+    let body = error_body.to_lowercase();
+    SUSPENSION_MESSAGES.iter().copied().find(|sig| body.contains(sig))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(id: &str, priority: i32, remaining_quota: Option<i32>) -> ProxyToken {
+        ProxyToken {
+            account_id: id.to_string(),
+            access_token: String::new(),
+            refresh_token: String::new(),
+            expires_in: 3600,
+            timestamp: 0,
+            email: format!("{}@example.com", id),
+            project_id: None,
+            subscription_tier: None,
+            remaining_quota,
+            protected_models: HashSet::new(),
+            available_models: HashSet::new(),
+            usage_windows: Vec::new(),
+            egress_proxy: None,
+            project_id_override: None,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_candidate_order_respects_priority_and_strategy() {
+        let tokens = vec![token("a", 0, Some(10)), token("b", 0, Some(90)), token("c", 5, Some(50))];
+
+        // 轮询: 从起点开始，高优先级账号排在最前
+        let order = candidate_order(&tokens, 1, SelectionStrategy::RoundRobin, |_| None, |_| 0);
+        assert_eq!(order, [2, 1, 0]);
+
+        let order = candidate_order(&tokens, 0, SelectionStrategy::MostRemainingQuota, |_| None, |_| 0);
+        assert_eq!(order, [2, 1, 0]);
+
+        let errors = |id: &str| if id == "a" { 0 } else { 3 };
+        let order = candidate_order(&tokens, 1, SelectionStrategy::LeastErrors, |_| None, errors);
+        assert_eq!(order, [2, 0, 1]);
+
+        assert!(candidate_order(&[], 3, SelectionStrategy::RoundRobin, |_| None, |_| 0).is_empty());
+    }
+
+    #[test]
+    fn test_pick_project_strategies() {
+        let known = vec!["alpha".to_string(), "beta".to_string(), "gamma".to_string()];
+        let preference = vec!["missing".to_string(), "gamma".to_string(), "beta".to_string()];
+
+        assert_eq!(pick_project(ProjectStrategy::First, &preference, "alpha", &known, 5), "alpha");
+        assert_eq!(pick_project(ProjectStrategy::Preference, &preference, "alpha", &known, 0), "gamma");
+        assert_eq!(pick_project(ProjectStrategy::Preference, &[], "alpha", &known, 0), "alpha");
+
+        let rotated: Vec<String> = (0..4)
+            .map(|i| pick_project(ProjectStrategy::Rotate, &[], "beta", &known, i))
+            .collect();
+        assert_eq!(rotated, vec!["beta", "alpha", "gamma", "beta"]);
+        assert_eq!(pick_project(ProjectStrategy::Rotate, &[], "solo", &[], 7), "solo");
+    }
+
+    #[test]
+    fn test_usage_window_contains() {
+        let day = UsageWindow { start: "00:00".to_string(), end: "12:00".to_string() };
+        assert!(day.contains(0) && day.contains(719) && !day.contains(720));
+
+        let overnight = UsageWindow { start: "22:00".to_string(), end: "06:00".to_string() };
+        assert!(overnight.contains(23 * 60) && overnight.contains(60) && !overnight.contains(12 * 60));
+
+        assert!(UsageWindow { start: "25:00".to_string(), end: "06:00".to_string() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_detect_account_suspension() {
+        assert_eq!(
+            detect_account_suspension(403, r#"{"error":{"code":403,"message":"This account has been suspended.","status":"PERMISSION_DENIED"}}"#),
+            Some("account has been suspended")
+        );
+        assert_eq!(
+            detect_account_suspension(
+                403,
+                r#"{"error":{"code":403,"status":"PERMISSION_DENIED","details":[{"@type":"type.googleapis.com/google.rpc.ErrorInfo","reason":"ACCOUNT_DISABLED"}]}}"#
+            ),
+            Some("ACCOUNT_DISABLED")
+        );
+        assert_eq!(detect_account_suspension(400, r#"{"error":{"message":"USER_DISABLED","status":"USER_DISABLED"}}"#), Some("USER_DISABLED"));
+        // 普通权限错误、校验错误和限流不视为封禁
+        assert_eq!(detect_account_suspension(403, "The caller does not have permission"), None);
+        assert_eq!(detect_account_suspension(403, "Please verify your account settings or review the terms of service"), None);
+        assert_eq!(
+            detect_account_suspension(400, r#"{"error":{"status":"INVALID_ARGUMENT","details":[{"reason":"SERVICE_DISABLED"}]}}"#),
+            None
+        );
+        assert_eq!(detect_account_suspension(429, "account has been suspended"), None);
+    }
+}
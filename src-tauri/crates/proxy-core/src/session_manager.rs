@@ -1,6 +1,6 @@
 use sha2::{Sha256, Digest};
-use crate::proxy::mappers::claude::models::{ClaudeRequest, MessageContent};
-use crate::proxy::mappers::openai::models::{OpenAIRequest, OpenAIContent};
+use crate::mappers::claude::models::{ClaudeRequest, MessageContent};
+use crate::mappers::openai::models::{OpenAIRequest, OpenAIContent};
 use serde_json::Value;
 
 /// 会话管理器工具
@@ -40,7 +40,7 @@ impl SessionManager {
                 MessageContent::Array(blocks) => {
                     blocks.iter()
                         .filter_map(|block| match block {
-                            crate::proxy::mappers::claude::models::ContentBlock::Text { text } => Some(text.as_str()),
+                            crate::mappers::claude::models::ContentBlock::Text { text } => Some(text.as_str()),
                             _ => None,
                         })
                         .collect::<Vec<_>>()
@@ -89,7 +89,7 @@ impl SessionManager {
                     OpenAIContent::Array(blocks) => {
                         blocks.iter()
                             .filter_map(|block| match block {
-                                crate::proxy::mappers::openai::models::OpenAIContentBlock::Text { text } => Some(text.as_str()),
+                                crate::mappers::openai::models::OpenAIContentBlock::Text { text } => Some(text.as_str()),
                                 _ => None,
                             })
                             .collect::<Vec<_>>()
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, SystemTime};
 
@@ -6,23 +7,25 @@ use std::time::{Duration, SystemTime};
 const SIGNATURE_TTL: Duration = Duration::from_secs(2 * 60 * 60);
 const MIN_SIGNATURE_LENGTH: usize = 50;
 
-// Different cache limits for different layers
-const TOOL_CACHE_LIMIT: usize = 500;      // Layer 1: Tool-specific signatures
-const FAMILY_CACHE_LIMIT: usize = 200;    // Layer 2: Model family mappings
-const SESSION_CACHE_LIMIT: usize = 1000;  // Layer 3: Session-based signatures (largest)
+// Default entry limit of the session layer (largest). The tool layer gets 1/2 of it
+// and the family layer 1/5 (500 / 200 by default). Configurable via `set_max_entries`.
+const DEFAULT_MAX_ENTRIES: usize = 1000;
 
 /// Cache entry with timestamp for TTL
 #[derive(Clone, Debug)]
 struct CacheEntry<T> {
     data: T,
     timestamp: SystemTime,
+    /// Logical clock of the last read / write (for LRU eviction)
+    last_access: u64,
 }
 
 impl<T> CacheEntry<T> {
-    fn new(data: T) -> Self {
+    fn new(data: T, tick: u64) -> Self {
         Self {
             data,
             timestamp: SystemTime::now(),
+            last_access: tick,
         }
     }
 
@@ -51,6 +54,13 @@ pub struct SignatureCache {
     /// Value: The most recent valid thought signature for this session
     /// This prevents signature pollution between different conversations
     session_signatures: Mutex<HashMap<String, CacheEntry<String>>>,
+
+    /// Entry limit of the session layer (see DEFAULT_MAX_ENTRIES)
+    max_entries: AtomicUsize,
+    /// Access clock shared by all layers
+    clock: AtomicU64,
+    /// Entries evicted because a layer was full (expired entries are not counted)
+    evictions: AtomicU64,
 }
 
 impl SignatureCache {
@@ -59,6 +69,9 @@ impl SignatureCache {
             tool_signatures: Mutex::new(HashMap::new()),
             thinking_families: Mutex::new(HashMap::new()),
             session_signatures: Mutex::new(HashMap::new()),
+            max_entries: AtomicUsize::new(DEFAULT_MAX_ENTRIES),
+            clock: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
@@ -68,6 +81,56 @@ impl SignatureCache {
         INSTANCE.get_or_init(SignatureCache::new)
     }
 
+    /// Set the entry limit of the session layer (0 = unlimited); the tool and
+    /// family layers scale with it. Applied on the next insert.
+    pub fn set_max_entries(&self, entries: usize) {
+        let entries = if entries == 0 { usize::MAX } else { entries };
+        self.max_entries.store(entries, Ordering::Relaxed);
+    }
+
+    /// Total number of entries evicted to stay within the limits
+    pub fn evictions(&self) -> u64 {
+        self.evictions.load(Ordering::Relaxed)
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// (tool, family, session) entry limits
+    fn limits(&self) -> (usize, usize, usize) {
+        let max = self.max_entries.load(Ordering::Relaxed);
+        ((max / 2).max(1), (max / 5).max(1), max)
+    }
+
+    /// Once a layer exceeds its limit, drop expired entries first; if it is still full,
+    /// evict the least recently used entries down to 90% of the limit so the sort
+    /// doesn't run on every insert.
+    fn enforce_limit<T>(&self, cache: &mut HashMap<String, CacheEntry<T>>, limit: usize, layer: &str) {
+        if cache.len() <= limit {
+            return;
+        }
+        let before = cache.len();
+        cache.retain(|_, v| !v.is_expired());
+        if cache.len() > limit {
+            let target = limit - limit / 10;
+            let mut by_access: Vec<(u64, String)> = cache.iter().map(|(k, v)| (v.last_access, k.clone())).collect();
+            by_access.sort_unstable();
+            let excess = cache.len() - target;
+            for (_, key) in by_access.into_iter().take(excess) {
+                cache.remove(&key);
+            }
+            self.evictions.fetch_add(excess as u64, Ordering::Relaxed);
+        }
+        tracing::debug!(
+            "[SignatureCache] {} cache cleanup: {} -> {} entries (limit: {})",
+            layer,
+            before,
+            cache.len(),
+            limit
+        );
+    }
+
     /// Store a tool call signature
     pub fn cache_tool_signature(&self, tool_use_id: &str, signature: String) {
         if signature.len() < MIN_SIGNATURE_LENGTH {
@@ -76,26 +139,18 @@ impl SignatureCache {
         
         if let Ok(mut cache) = self.tool_signatures.lock() {
             tracing::debug!("[SignatureCache] Caching tool signature for id: {}", tool_use_id);
-            cache.insert(tool_use_id.to_string(), CacheEntry::new(signature));
-            
-            // Clean up expired entries when limit is reached
-            if cache.len() > TOOL_CACHE_LIMIT {
-                let before = cache.len();
-                cache.retain(|_, v| !v.is_expired());
-                let after = cache.len();
-                if before != after {
-                    tracing::debug!("[SignatureCache] Tool cache cleanup: {} -> {} entries", before, after);
-                }
-            }
+            cache.insert(tool_use_id.to_string(), CacheEntry::new(signature, self.tick()));
+            self.enforce_limit(&mut cache, self.limits().0, "Tool");
         }
     }
 
     /// Retrieve a signature for a tool_use_id
     pub fn get_tool_signature(&self, tool_use_id: &str) -> Option<String> {
-        if let Ok(cache) = self.tool_signatures.lock() {
-            if let Some(entry) = cache.get(tool_use_id) {
+        if let Ok(mut cache) = self.tool_signatures.lock() {
+            if let Some(entry) = cache.get_mut(tool_use_id) {
                 if !entry.is_expired() {
                     tracing::debug!("[SignatureCache] Hit tool signature for id: {}", tool_use_id);
+                    entry.last_access = self.tick();
                     return Some(entry.data.clone());
                 }
             }
@@ -111,24 +166,17 @@ impl SignatureCache {
 
         if let Ok(mut cache) = self.thinking_families.lock() {
             tracing::debug!("[SignatureCache] Caching thinking family for sig (len={}): {}", signature.len(), family);
-            cache.insert(signature, CacheEntry::new(family));
-            
-            if cache.len() > FAMILY_CACHE_LIMIT {
-                let before = cache.len();
-                cache.retain(|_, v| !v.is_expired());
-                let after = cache.len();
-                if before != after {
-                    tracing::debug!("[SignatureCache] Family cache cleanup: {} -> {} entries", before, after);
-                }
-            }
+            cache.insert(signature, CacheEntry::new(family, self.tick()));
+            self.enforce_limit(&mut cache, self.limits().1, "Family");
         }
     }
 
     /// Get model family for a signature
     pub fn get_signature_family(&self, signature: &str) -> Option<String> {
-        if let Ok(cache) = self.thinking_families.lock() {
-            if let Some(entry) = cache.get(signature) {
+        if let Ok(mut cache) = self.thinking_families.lock() {
+            if let Some(entry) = cache.get_mut(signature) {
                 if !entry.is_expired() {
+                    entry.last_access = self.tick();
                     return Some(entry.data.clone());
                 } else {
                     tracing::debug!("[SignatureCache] Signature family entry expired");
@@ -167,32 +215,20 @@ impl SignatureCache {
                     session_id,
                     signature.len()
                 );
-                cache.insert(session_id.to_string(), CacheEntry::new(signature));
+                cache.insert(session_id.to_string(), CacheEntry::new(signature, self.tick()));
             }
 
-            // Cleanup when limit is reached (Session cache has largest limit)
-            if cache.len() > SESSION_CACHE_LIMIT {
-                let before = cache.len();
-                cache.retain(|_, v| !v.is_expired());
-                let after = cache.len();
-                if before != after {
-                    tracing::info!(
-                        "[SignatureCache] Session cache cleanup: {} -> {} entries (limit: {})",
-                        before,
-                        after,
-                        SESSION_CACHE_LIMIT
-                    );
-                }
-            }
+            self.enforce_limit(&mut cache, self.limits().2, "Session");
         }
     }
 
     /// Retrieve the latest thinking signature for a session.
     /// Returns None if not found or expired.
     pub fn get_session_signature(&self, session_id: &str) -> Option<String> {
-        if let Ok(cache) = self.session_signatures.lock() {
-            if let Some(entry) = cache.get(session_id) {
+        if let Ok(mut cache) = self.session_signatures.lock() {
+            if let Some(entry) = cache.get_mut(session_id) {
                 if !entry.is_expired() {
+                    entry.last_access = self.tick();
                     tracing::debug!(
                         "[SignatureCache] Session {} -> HIT (len={})",
                         session_id,
@@ -283,6 +319,26 @@ mod tests {
         assert!(cache.get_session_signature("sid-other").is_none());
     }
 
+    #[test]
+    fn test_full_layer_evicts_least_recently_used() {
+        let cache = SignatureCache::new();
+        cache.set_max_entries(10); // tool layer: 5
+        let sig = "x".repeat(60);
+
+        for i in 0..5 {
+            cache.cache_tool_signature(&format!("tool_{}", i), sig.clone());
+        }
+        // Reading tool_0 makes tool_1 the least recently used entry
+        assert!(cache.get_tool_signature("tool_0").is_some());
+        cache.cache_tool_signature("tool_5", sig.clone());
+
+        assert!(cache.get_tool_signature("tool_1").is_none());
+        assert!(cache.get_tool_signature("tool_0").is_some());
+        assert!(cache.get_tool_signature("tool_5").is_some());
+        assert_eq!(cache.evictions(), 1);
+        assert_eq!(cache.tool_signatures.lock().unwrap().len(), 5);
+    }
+
     #[test]
     fn test_clear_all_caches() {
         let cache = SignatureCache::new();
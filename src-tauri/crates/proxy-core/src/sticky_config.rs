@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// 调度模式枚举
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SchedulingMode {
+    /// 缓存优先 (Cache-first): 尽可能锁定同一账号，限流时优先等待，极大提升 Prompt Caching 命中率
+    CacheFirst,
+    /// 平衡模式 (Balance): 锁定同一账号，限流时立即切换到备选账号，兼顾成功率和性能
+    #[default]
+    Balance,
+    /// 性能优先 (Performance-first): 纯轮询模式 (Round-robin)，账号负载最均衡，但不利用缓存
+    PerformanceFirst,
+}
+
+/// 账号选择策略 (决定候选账号的遍历顺序)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SelectionStrategy {
+    /// 轮询 (Round-robin): 依次使用每个健康账号
+    #[default]
+    RoundRobin,
+    /// 最久未使用 (Least-recently-used): 优先使用最长时间未被选中的账号
+    LeastRecentlyUsed,
+    /// 最少错误 (Least-errors): 优先使用累计错误次数最少的账号
+    LeastErrors,
+    /// 最多剩余配额 (Most-remaining-quota): 优先使用剩余配额百分比最高的账号
+    MostRemainingQuota,
+}
+
+/// 项目选择策略 (账号拥有多个项目时决定请求使用哪一个)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ProjectStrategy {
+    /// 使用 loadCodeAssist 返回的默认项目
+    #[default]
+    First,
+    /// 按 `project_preference` 列表顺序选择账号拥有的第一个项目
+    Preference,
+    /// 在账号拥有的项目间轮换
+    Rotate,
+}
+
+/// 粘性会话配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StickySessionConfig {
+    /// 当前调度模式
+    pub mode: SchedulingMode,
+    /// 缓存优先模式下的最大等待时间 (秒)
+    pub max_wait_seconds: u64,
+    /// 需要挑选新账号时使用的选择策略
+    #[serde(default)]
+    pub strategy: SelectionStrategy,
+    /// 固定使用的账号 ID: 该账号可用时始终优先使用，冷却/限流期间回退到正常调度
+    #[serde(default)]
+    pub pinned_account_id: Option<String>,
+    /// 单个账号同时承载的最大上游请求数 (0 表示不限制)
+    /// 达到上限的账号会被跳过；所有账号都满载时短暂排队，超时后允许超额使用
+    #[serde(default = "default_max_concurrent_per_account")]
+    pub max_concurrent_per_account: u32,
+    /// 多项目账号的项目选择策略
+    #[serde(default)]
+    pub project_strategy: ProjectStrategy,
+    /// `Preference` 策略使用的项目优先列表 (靠前的优先)
+    #[serde(default)]
+    pub project_preference: Vec<String>,
+}
+
+fn default_max_concurrent_per_account() -> u32 {
+    3
+}
+
+impl Default for StickySessionConfig {
+    fn default() -> Self {
+        Self {
+            mode: SchedulingMode::Balance,
+            max_wait_seconds: 60,
+            strategy: SelectionStrategy::default(),
+            pinned_account_id: None,
+            max_concurrent_per_account: default_max_concurrent_per_account(),
+            project_strategy: ProjectStrategy::default(),
+            project_preference: Vec::new(),
+        }
+    }
+}
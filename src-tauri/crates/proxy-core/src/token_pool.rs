@@ -0,0 +1,1660 @@
+// 账号池调度核心
+//
+// TokenManager 中与宿主程序无关的部分: 内存中的账号令牌、粘性会话与 60s 复用窗口、
+// 账号池 / 标签 / 可用时段 / 模型权益过滤、并发槽位排队、限流冷却与乐观重置、
+// 请求路径与启动时的 Token 刷新、项目选择，以及账号池耗尽的判定。
+// 账号的持久化、OAuth 刷新、project_id 解析与事件推送通过 `AccountStore` 交给宿主程序实现。
+
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::rate_limit::{RateLimitReason, RateLimitTracker};
+use crate::rotation::{current_minute_of_day, pick_project, AccountLease, ProxyToken, POOL_EXHAUSTED_ERROR};
+use crate::sticky_config::{ProjectStrategy, SchedulingMode, SelectionStrategy, StickySessionConfig};
+
+/// 账号满载时排队等待空闲槽位的最长时间
+const CONCURRENCY_QUEUE_WAIT: std::time::Duration = std::time::Duration::from_secs(3);
+/// 冷却检查间隔 (秒)
+const COOLDOWN_WATCH_INTERVAL_SECS: u64 = 5;
+/// 启动时后台刷新 Token 的最大并发数
+const STARTUP_REFRESH_CONCURRENCY: usize = 8;
+/// Token 在过期前多少秒视为即将过期 (与请求路径的提前刷新保持一致)
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+/// 获取 Token 的超时时间，防止锁竞争导致请求无限等待
+const TOKEN_ACQUIRE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// OAuth 刷新得到的新 access_token
+#[derive(Debug, Clone)]
+pub struct RefreshedToken {
+    pub access_token: String,
+    pub expires_in: i64,
+}
+
+/// 账号冷却状态变化事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountCooldownEvent {
+    pub account_id: String,
+    pub email: String,
+    /// "cooling" 或 "active"
+    pub state: String,
+    pub reason: Option<RateLimitReason>,
+    pub model: Option<String>,
+    /// 距离自动恢复的剩余秒数 (仅 cooling 状态)
+    pub remaining_seconds: Option<u64>,
+    /// 预计恢复可用的时间 (Unix 秒，仅 cooling 状态)
+    pub usable_at: Option<i64>,
+}
+
+/// 账号池耗尽事件载荷 (仅在进入耗尽状态时产生一次)
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolExhaustedEvent {
+    /// 冷却中的账号数
+    pub cooling: usize,
+    /// 已隔离的账号数
+    pub quarantined: usize,
+    /// 最早恢复可用的剩余秒数
+    pub retry_after_seconds: Option<u64>,
+}
+
+/// 单个账号当前承载的上游请求数
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AccountLoad {
+    pub account_id: String,
+    pub email: String,
+    pub in_flight: u32,
+}
+
+/// 宿主程序提供的账号存储
+///
+/// 账号池只在内存中维护 [`ProxyToken`]，刷新后的 Token、解析出的 project_id 与账号停用
+/// 都通过此接口写回宿主程序的存储。事件回调默认不做任何处理。
+pub trait AccountStore: Send + Sync {
+    /// 用账号的 refresh_token 换取新的 access_token
+    fn refresh_token<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<RefreshedToken, String>>;
+
+    /// 保存刷新后的 access_token
+    fn save_refreshed_token<'a>(
+        &'a self,
+        account_id: &'a str,
+        refreshed: &'a RefreshedToken,
+    ) -> BoxFuture<'a, Result<(), String>>;
+
+    /// 解析账号的 project_id，返回 (project_id, 是否与账号中保存的值不同)
+    fn resolve_project_id<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<(String, bool), String>>;
+
+    /// 保存解析出的 project_id
+    fn save_project_id<'a>(&'a self, account_id: &'a str, project_id: &'a str) -> BoxFuture<'a, Result<(), String>>;
+
+    /// 停用账号 (refresh_token 已失效)，账号池随后会移除该账号
+    fn disable_account<'a>(&'a self, account_id: &'a str, reason: &'a str) -> BoxFuture<'a, Result<(), String>>;
+
+    /// 已隔离的账号数 (隔离的账号不会加载到账号池中)
+    fn count_quarantined(&self) -> BoxFuture<'_, usize>;
+
+    /// 记录账号事件 (不应阻塞调用方)
+    fn record_event(&self, _account_id: &str, _kind: &'static str, _detail: Option<String>) {}
+
+    /// 账号进入冷却或恢复可用
+    fn cooldown_changed(&self, _event: &AccountCooldownEvent) {}
+
+    /// 账号池首次进入耗尽状态
+    fn pool_exhausted(&self, _event: &PoolExhaustedEvent) {}
+}
+
+/// 排队等待槽位期间计数，drop 时减一
+struct QueueGuard(Arc<AtomicUsize>);
+
+impl QueueGuard {
+    fn enter(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 账号池: 为每个上游请求选出账号、保证 Token 有效并占用并发槽位
+pub struct TokenPool {
+    tokens: DashMap<String, ProxyToken>, // account_id -> ProxyToken
+    current_index: AtomicUsize,
+    last_used_account: tokio::sync::Mutex<Option<(String, Instant)>>,
+    rate_limit_tracker: Arc<RateLimitTracker>,
+    sticky_config: tokio::sync::RwLock<StickySessionConfig>, // 调度配置
+    session_accounts: DashMap<String, String>, // 会话与账号映射 (SessionID -> AccountID)
+    last_selected: DashMap<String, Instant>, // 账号最近一次被选中的时间 (LRU 策略)
+    error_counts: DashMap<String, u32>, // 账号累计错误次数 (LeastErrors 策略)
+    account_pools: tokio::sync::RwLock<HashMap<String, HashSet<String>>>, // 账号池 (池名称 -> 账号 ID/邮箱)
+    in_flight: Arc<DashMap<String, u32>>, // 账号当前承载的上游请求数 (并发限制)
+    slot_released: Arc<tokio::sync::Notify>, // 有账号释放并发槽位时通知排队的请求
+    queued: Arc<AtomicUsize>, // 正在排队等待并发槽位的请求数
+    pool_exhausted: AtomicBool, // 账号池是否已耗尽 (避免重复推送耗尽事件)
+    project_rotation: DashMap<String, usize>, // 账号的项目轮换计数 (Rotate 策略)
+    last_projects: DashMap<String, String>, // email -> 最近一次请求使用的项目 (用于请求日志)
+    store: Arc<dyn AccountStore>,
+}
+
+impl TokenPool {
+    /// 创建空的账号池
+    pub fn new(store: Arc<dyn AccountStore>) -> Self {
+        Self {
+            tokens: DashMap::new(),
+            current_index: AtomicUsize::new(0),
+            last_used_account: tokio::sync::Mutex::new(None),
+            rate_limit_tracker: Arc::new(RateLimitTracker::new()),
+            sticky_config: tokio::sync::RwLock::new(StickySessionConfig::default()),
+            session_accounts: DashMap::new(),
+            last_selected: DashMap::new(),
+            error_counts: DashMap::new(),
+            account_pools: tokio::sync::RwLock::new(HashMap::new()),
+            in_flight: Arc::new(DashMap::new()),
+            slot_released: Arc::new(tokio::sync::Notify::new()),
+            queued: Arc::new(AtomicUsize::new(0)),
+            pool_exhausted: AtomicBool::new(false),
+            project_rotation: DashMap::new(),
+            last_projects: DashMap::new(),
+            store,
+        }
+    }
+
+    // ===== 账号 =====
+
+    /// 替换全部账号，并重置轮询位置与 60s 复用窗口
+    pub async fn replace_tokens(&self, tokens: Vec<ProxyToken>) {
+        self.tokens.clear();
+        self.current_index.store(0, Ordering::SeqCst);
+        *self.last_used_account.lock().await = None;
+        for token in tokens {
+            self.tokens.insert(token.account_id.clone(), token);
+        }
+    }
+
+    /// 加入或更新单个账号 (不影响其余账号的轮换位置与会话绑定)
+    pub fn insert_token(&self, token: ProxyToken) {
+        self.tokens.insert(token.account_id.clone(), token);
+    }
+
+    /// 按 account_id 获取账号令牌
+    pub fn token(&self, account_id: &str) -> Option<ProxyToken> {
+        self.tokens.get(account_id).map(|t| t.clone())
+    }
+
+    /// 按邮箱获取账号令牌
+    pub fn token_by_email(&self, email: &str) -> Option<ProxyToken> {
+        self.tokens.iter().find(|entry| entry.value().email == email).map(|entry| entry.value().clone())
+    }
+
+    /// 从账号池中移除账号 (账号存储保留)，并解除指向它的会话绑定
+    pub async fn remove_account(&self, account_id: &str) {
+        self.tokens.remove(account_id);
+        self.session_accounts.retain(|_, bound| bound != account_id);
+        let mut last_used = self.last_used_account.lock().await;
+        if last_used.as_ref().map(|(id, _)| id == account_id).unwrap_or(false) {
+            *last_used = None;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// 列出带有指定标签的账号 (account_id, email)
+    pub fn list_accounts_by_tag(&self, tag: &str) -> Vec<(String, String)> {
+        let mut accounts: Vec<(String, String)> = self.tokens.iter()
+            .filter(|e| e.value().tags.iter().any(|t| t == tag))
+            .map(|e| (e.value().account_id.clone(), e.value().email.clone()))
+            .collect();
+        accounts.sort_by(|a, b| a.1.cmp(&b.1));
+        accounts
+    }
+
+    /// 获取账号的独立出口代理 (按邮箱查找)
+    pub fn egress_proxy_for(&self, email: &str) -> Option<String> {
+        self.tokens.iter()
+            .find(|entry| entry.value().email == email)
+            .and_then(|entry| entry.value().egress_proxy.clone())
+    }
+
+    /// 通过 email 查找对应的 account_id
+    ///
+    /// handlers 传入的是 email，限流跟踪器统一使用 account_id 作为 key
+    pub fn email_to_account_id(&self, email: &str) -> Option<String> {
+        self.tokens.iter()
+            .find(|entry| entry.value().email == email)
+            .map(|entry| entry.value().account_id.clone())
+    }
+
+    /// 账号最近一次请求使用的项目
+    pub fn last_project_for(&self, email: &str) -> Option<String> {
+        self.last_projects.get(email).map(|p| p.clone())
+    }
+
+    // ===== 选择账号 =====
+
+    /// 获取当前可用的 Token（支持粘性会话与智能调度），`pool` 不为空时仅从该账号池中选择
+    /// 参数 `quota_group` 用于区分 "claude" vs "gemini" 组
+    /// 参数 `force_rotate` 为 true 时将忽略锁定，强制切换账号
+    /// 参数 `session_id` 用于跨请求维持会话粘性
+    /// 参数 `target_model` 用于检查配额保护 (Issue #621)
+    ///
+    /// 返回 (access_token, project_id, email, lease)；[`AccountLease`] 占用账号的一个并发槽位，需持有到上游请求结束
+    #[tracing::instrument(
+        name = "proxy.select_account",
+        skip(self, session_id),
+        fields(account = tracing::field::Empty, error = tracing::field::Empty)
+    )]
+    pub async fn get_token_in_pool(
+        &self,
+        quota_group: &str,
+        force_rotate: bool,
+        session_id: Option<&str>,
+        target_model: &str,
+        pool: Option<&str>,
+    ) -> Result<(String, String, String, AccountLease), String> {
+        // 【优化 Issue #284】添加 5 秒超时，防止死锁
+        let result = match tokio::time::timeout(
+            TOKEN_ACQUIRE_TIMEOUT,
+            self.get_token_internal(quota_group, force_rotate, session_id, target_model, pool, None),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err("Token acquisition timeout (5s) - system too busy or deadlock detected".to_string()),
+        };
+        match &result {
+            Ok((_, _, email, _)) => tracing::Span::current().record("account", email.as_str()),
+            Err(e) => tracing::Span::current().record("error", e.as_str()),
+        };
+        result
+    }
+
+    /// 获取 Token，仅在带有指定标签的账号中轮换
+    pub async fn get_token_with_tag(
+        &self,
+        quota_group: &str,
+        force_rotate: bool,
+        session_id: Option<&str>,
+        target_model: &str,
+        tag: &str,
+    ) -> Result<(String, String, String, AccountLease), String> {
+        match tokio::time::timeout(
+            TOKEN_ACQUIRE_TIMEOUT,
+            self.get_token_internal(quota_group, force_rotate, session_id, target_model, None, Some(tag)),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err("Token acquisition timeout (5s) - system too busy or deadlock detected".to_string()),
+        }
+    }
+
+    /// 内部实现：获取 Token 的核心逻辑
+    async fn get_token_internal(
+        &self,
+        quota_group: &str,
+        force_rotate: bool,
+        session_id: Option<&str>,
+        target_model: &str,
+        pool: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<(String, String, String, AccountLease), String> {
+        let mut tokens_snapshot: Vec<ProxyToken> = self.tokens.iter().map(|e| e.value().clone()).collect();
+        if tokens_snapshot.is_empty() {
+            // 账号都被隔离时属于耗尽，而不是未配置账号
+            if self.store.count_quarantined().await > 0 {
+                return Err(self.pool_exhausted_error(0, None).await);
+            }
+            return Err("Token pool is empty".to_string());
+        }
+
+        // 绑定账号池的请求只在池内账号中调度
+        if let Some(pool) = pool {
+            let pools = self.account_pools.read().await;
+            let members = pools.get(pool).ok_or_else(|| format!("Account pool '{}' not found", pool))?;
+            // 成员可写账号 ID、邮箱或 "tag:<标签>"
+            tokens_snapshot.retain(|t| {
+                members.contains(&t.account_id)
+                    || members.contains(&t.email)
+                    || t.tags.iter().any(|tag| members.contains(&format!("tag:{}", tag)))
+            });
+            if tokens_snapshot.is_empty() {
+                return Err(format!("Account pool '{}' has no available accounts", pool));
+            }
+        }
+
+        if let Some(tag) = tag {
+            tokens_snapshot.retain(|t| t.tags.iter().any(|t| t == tag));
+            if tokens_snapshot.is_empty() {
+                return Err(format!("No available account with tag '{}'", tag));
+            }
+        }
+
+        // 排除当前不在可用时段内的账号
+        let minute_of_day = current_minute_of_day();
+        tokens_snapshot.retain(|t| t.usage_windows.is_empty() || t.usage_windows.iter().any(|w| w.contains(minute_of_day)));
+        if tokens_snapshot.is_empty() {
+            return Err("No account is within its scheduled usage window".to_string());
+        }
+
+        // 按订阅权益过滤: 目标模型只对部分账号开放时 (如付费专属模型)，排除无权访问的账号
+        // 配额信息未知的账号保留，交由上游判定
+        if tokens_snapshot.iter().any(|t| t.available_models.contains(target_model)) {
+            tokens_snapshot.retain(|t| t.available_models.is_empty() || t.available_models.contains(target_model));
+        }
+
+        let total = tokens_snapshot.len();
+
+        // ===== 【优化】根据订阅等级和剩余配额排序 =====
+        // [FIX #563] 优先级: ULTRA > PRO > FREE, 同tier内优先高配额账号
+        // 理由: ULTRA/PRO 重置快，优先消耗；FREE 重置慢，用于兜底
+        //       高配額账号优先使用，避免低配额账号被用光
+        tokens_snapshot.sort_by(|a, b| {
+            // 用户设置的优先级最高: 高优先级账号优先，低优先级账号作为溢出备用
+            let priority_cmp = b.priority.cmp(&a.priority);
+            if priority_cmp != std::cmp::Ordering::Equal {
+                return priority_cmp;
+            }
+
+            let tier_priority = |tier: &Option<String>| match tier.as_deref() {
+                Some("ULTRA") => 0,
+                Some("PRO") => 1,
+                Some("FREE") => 2,
+                _ => 3,
+            };
+
+            // First: compare by subscription tier
+            let tier_cmp = tier_priority(&a.subscription_tier)
+                .cmp(&tier_priority(&b.subscription_tier));
+
+            if tier_cmp != std::cmp::Ordering::Equal {
+                return tier_cmp;
+            }
+
+            // [FIX #563] Second: compare by remaining quota percentage (higher is better)
+            // Accounts with unknown/zero percentage go last within their tier
+            let quota_a = a.remaining_quota.unwrap_or(0);
+            let quota_b = b.remaining_quota.unwrap_or(0);
+            let quota_cmp = quota_b.cmp(&quota_a);  // Descending: higher percentage first
+            if quota_cmp != std::cmp::Ordering::Equal {
+                return quota_cmp;
+            }
+
+            // Third: stable tiebreaker so round-robin walks the same order on every call
+            // (DashMap iteration order is not deterministic)
+            a.account_id.cmp(&b.account_id)
+        });
+
+        // 0. 读取当前调度配置
+        let scheduling = self.sticky_config.read().await.clone();
+
+        // 【优化 Issue #284】将锁操作移到循环外，避免重复获取锁
+        // 预先获取 last_used_account 的快照，避免在循环中多次加锁
+        let last_used_account_id = if quota_group != "image_gen" {
+            let last_used = self.last_used_account.lock().await;
+            last_used.clone()
+        } else {
+            None
+        };
+
+        let mut attempted: HashSet<String> = HashSet::new();
+        let mut last_error: Option<String> = None;
+        let mut need_update_last_used: Option<(String, Instant)> = None;
+
+        // 所有可用账号都已满载时短暂排队，等待其他请求释放槽位
+        let concurrency_limit = scheduling.max_concurrent_per_account;
+        if concurrency_limit > 0 {
+            self.wait_for_free_slot(&tokens_snapshot, concurrency_limit, target_model).await;
+        }
+
+        for attempt in 0..total {
+            let rotate = force_rotate || attempt > 0;
+
+            // 已满载的账号本轮跳过；没有其他可用账号时不限制 (允许超额，避免请求失败)
+            let saturated = self.saturated_accounts(&tokens_snapshot, concurrency_limit, target_model, &attempted);
+
+            // ===== 【核心】粘性会话与智能调度逻辑 =====
+            let mut target_token: Option<ProxyToken> = None;
+
+            // 固定账号: 可用时直接使用 (仍遵守限流冷却与配额保护)
+            if let Some(pinned_id) = scheduling.pinned_account_id.as_deref() {
+                if let Some(pinned) = tokens_snapshot.iter().find(|t| t.account_id == pinned_id) {
+                    if !attempted.contains(&pinned.account_id)
+                        && !saturated.contains(&pinned.account_id)
+                        && !pinned.protected_models.contains(target_model)
+                        && !self.is_rate_limited_by_account_id(&pinned.account_id)
+                    {
+                        tracing::debug!("Pinned account selected: {}", pinned.email);
+                        target_token = Some(pinned.clone());
+                    }
+                }
+            }
+
+            // 模式 A: 粘性会话处理 (CacheFirst 或 Balance 且有 session_id)
+            let sticky_session = session_id.filter(|_| target_token.is_none() && !rotate && scheduling.mode != SchedulingMode::PerformanceFirst);
+            if let Some(sid) = sticky_session {
+                // 1. 检查会话是否已绑定账号
+                if let Some(bound_id) = self.session_accounts.get(sid).map(|v| v.clone()) {
+                    // 【修复】先通过 account_id 找到对应的账号，获取其 email
+                    // 2. 转换 email -> account_id 检查绑定的账号是否限流
+                    if let Some(bound_token) = tokens_snapshot.iter().find(|t| t.account_id == bound_id) {
+                        let key = self.email_to_account_id(&bound_token.email).unwrap_or_else(|| bound_token.account_id.clone());
+                        let reset_sec = self.rate_limit_tracker.get_remaining_wait(&key);
+                        if reset_sec > 0 {
+                            // 【修复 Issue #284】立即解绑并切换账号，不再阻塞等待
+                            // 原因：阻塞等待会导致并发请求时客户端 socket 超时 (UND_ERR_SOCKET)
+                            tracing::debug!(
+                                "Sticky Session: Bound account {} is rate-limited ({}s), unbinding and switching.",
+                                bound_token.email, reset_sec
+                            );
+                            self.session_accounts.remove(sid);
+                        } else if saturated.contains(&bound_id) {
+                            // 绑定账号满载：本次请求临时使用其他账号，保留绑定
+                            tracing::debug!("Sticky Session: Bound account {} is at its concurrency limit, using another account for this request", bound_token.email);
+                        } else if !attempted.contains(&bound_id) && !bound_token.protected_models.contains(target_model) {
+                            // 3. 账号可用且未被标记为尝试失败，优先复用
+                            tracing::debug!("Sticky Session: Successfully reusing bound account {} for session {}", bound_token.email, sid);
+                            target_token = Some(bound_token.clone());
+                        } else if bound_token.protected_models.contains(target_model) {
+                            tracing::debug!("Sticky Session: Bound account {} is quota-protected for model {}, unbinding and switching.", bound_token.email, target_model);
+                            self.session_accounts.remove(sid);
+                        }
+                    } else {
+                        // 绑定的账号已不存在（可能被删除），解绑
+                        tracing::debug!("Sticky Session: Bound account not found for session {}, unbinding", sid);
+                        self.session_accounts.remove(sid);
+                    }
+                }
+            }
+
+            // 模式 B: 原子化 60s 全局锁定 (针对无 session_id 情况的默认保护)
+            // PerformanceFirst 为纯轮询，不做 60s 锁定，直接进入模式 C 在所有健康账号间轮换
+            if target_token.is_none()
+                && !rotate
+                && quota_group != "image_gen"
+                && scheduling.mode != SchedulingMode::PerformanceFirst
+            {
+                // 【优化】使用预先获取的快照，不再在循环内加锁
+                if let Some((account_id, last_time)) = &last_used_account_id {
+                    // [FIX #3] 60s 锁定逻辑应检查 `attempted` 集合，避免重复尝试失败的账号
+                    if last_time.elapsed().as_secs() < 60 && !attempted.contains(account_id) && !saturated.contains(account_id) {
+                        if let Some(found) = tokens_snapshot.iter().find(|t| &t.account_id == account_id) {
+                            // 【修复】检查限流状态和配额保护，避免复用已被锁定的账号
+                            if !self.is_rate_limited_by_account_id(&found.account_id) && !found.protected_models.contains(target_model) {
+                                tracing::debug!("60s Window: Force reusing last account: {}", found.email);
+                                target_token = Some(found.clone());
+                            } else if self.is_rate_limited_by_account_id(&found.account_id) {
+                                tracing::debug!("60s Window: Last account {} is rate-limited, skipping", found.email);
+                            } else {
+                                tracing::debug!("60s Window: Last account {} is quota-protected for model {}, skipping", found.email, target_model);
+                            }
+                        }
+                    }
+                }
+
+                // 若无锁定，则轮询选择新账号
+                if target_token.is_none() {
+                    for idx in self.candidate_order(&tokens_snapshot, scheduling.strategy) {
+                        let candidate = &tokens_snapshot[idx];
+                        if attempted.contains(&candidate.account_id) || saturated.contains(&candidate.account_id) {
+                            continue;
+                        }
+
+                        // 【新增 #621】模型级限流检查
+                        if candidate.protected_models.contains(target_model) {
+                            tracing::debug!("Account {} is quota-protected for model {}, skipping", candidate.email, target_model);
+                            continue;
+                        }
+
+                        // 【新增】主动避开限流或 5xx 锁定的账号 (来自 PR #28 的高可用思路)
+                        if self.is_rate_limited_by_account_id(&candidate.account_id) {
+                            continue;
+                        }
+
+                        target_token = Some(candidate.clone());
+                        // 【优化】标记需要更新，稍后统一写回
+                        need_update_last_used = Some((candidate.account_id.clone(), Instant::now()));
+
+                        // 如果是会话首次分配且需要粘性，在此建立绑定
+                        if let Some(sid) = session_id {
+                            if scheduling.mode != SchedulingMode::PerformanceFirst {
+                                self.session_accounts.insert(sid.to_string(), candidate.account_id.clone());
+                                tracing::debug!("Sticky Session: Bound new account {} to session {}", candidate.email, sid);
+                            }
+                        }
+                        break;
+                    }
+                }
+            } else if target_token.is_none() {
+                // 模式 C: 纯轮询模式 (Round-robin) 或强制轮换，遍历顺序由选择策略决定
+                for idx in self.candidate_order(&tokens_snapshot, scheduling.strategy) {
+                    let candidate = &tokens_snapshot[idx];
+                    if attempted.contains(&candidate.account_id) || saturated.contains(&candidate.account_id) {
+                        continue;
+                    }
+
+                    // 【新增 #621】模型级限流检查
+                    if candidate.protected_models.contains(target_model) {
+                        continue;
+                    }
+
+                    // 【新增】主动避开限流或 5xx 锁定的账号
+                    if self.is_rate_limited_by_account_id(&candidate.account_id) {
+                        continue;
+                    }
+
+                    target_token = Some(candidate.clone());
+
+                    if rotate {
+                        tracing::debug!("Force Rotation: Switched to account: {}", candidate.email);
+                    }
+                    break;
+                }
+            }
+
+            let mut token = match target_token {
+                Some(t) => t,
+                None => {
+                    // 乐观重置策略: 双层防护机制
+                    // 当所有账号都无法选择时,可能是时序竞争导致的状态不同步
+
+                    // 计算最短等待时间
+                    let min_wait = tokens_snapshot.iter()
+                        .filter_map(|t| self.rate_limit_tracker.get_reset_seconds(&t.account_id))
+                        .min();
+
+                    // Layer 1: 如果最短等待时间 <= 2秒,执行缓冲延迟
+                    if let Some(wait_sec) = min_wait {
+                        if wait_sec <= 2 {
+                            tracing::warn!(
+                                "All accounts rate-limited but shortest wait is {}s. Applying 500ms buffer for state sync...",
+                                wait_sec
+                            );
+
+                            // 缓冲延迟 500ms
+                            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+                            // 重新尝试选择账号
+                            let retry_token = tokens_snapshot.iter()
+                                .find(|t| !attempted.contains(&t.account_id) && !self.is_rate_limited_by_account_id(&t.account_id));
+
+                            if let Some(t) = retry_token {
+                                tracing::info!("✅ Buffer delay successful! Found available account: {}", t.email);
+                                t.clone()
+                            } else {
+                                // Layer 2: 缓冲后仍无可用账号,执行乐观重置
+                                tracing::warn!(
+                                    "Buffer delay failed. Executing optimistic reset for all {} accounts...",
+                                    tokens_snapshot.len()
+                                );
+
+                                // 清除所有限流记录
+                                self.rate_limit_tracker.clear_all();
+
+                                // 再次尝试选择账号
+                                let final_token = tokens_snapshot.iter()
+                                    .find(|t| !attempted.contains(&t.account_id));
+
+                                if let Some(t) = final_token {
+                                    tracing::info!("✅ Optimistic reset successful! Using account: {}", t.email);
+                                    t.clone()
+                                } else {
+                                    // 所有策略都失败,返回错误
+                                    return Err(
+                                        "All accounts failed after optimistic reset. Please check account health.".to_string()
+                                    );
+                                }
+                            }
+                        } else {
+                            // 等待时间 > 2秒,正常返回错误
+                            let cooling = tokens_snapshot
+                                .iter()
+                                .filter(|t| self.is_rate_limited_by_account_id(&t.account_id))
+                                .count();
+                            if cooling == tokens_snapshot.len() && pool.is_none() && tag.is_none() {
+                                return Err(self.pool_exhausted_error(cooling, Some(wait_sec)).await);
+                            }
+                            return Err(format!("All accounts are currently limited. Please wait {}s.", wait_sec));
+                        }
+                    } else {
+                        // 无限流记录但仍无可用账号,可能是其他问题
+                        return Err("All accounts failed or unhealthy.".to_string());
+                    }
+                }
+            };
+
+            // 3. 检查 token 是否过期（提前5分钟刷新）
+            let now = chrono::Utc::now().timestamp();
+            if now >= token.timestamp - TOKEN_REFRESH_MARGIN_SECS {
+                // 并发请求可能已经完成了刷新，直接使用共享的新 token
+                if let Some(entry) = self.tokens.get(&token.account_id) {
+                    if now < entry.timestamp - TOKEN_REFRESH_MARGIN_SECS {
+                        token.access_token = entry.access_token.clone();
+                        token.expires_in = entry.expires_in;
+                        token.timestamp = entry.timestamp;
+                    }
+                }
+            }
+            if now >= token.timestamp - TOKEN_REFRESH_MARGIN_SECS {
+                tracing::debug!("账号 {} 的 token 即将过期，正在刷新...", token.email);
+
+                match self.store.refresh_token(&token).await {
+                    Ok(refreshed) => {
+                        tracing::debug!("Token 刷新成功！");
+
+                        // 更新本地内存对象供后续使用
+                        token.access_token = refreshed.access_token.clone();
+                        token.expires_in = refreshed.expires_in;
+                        token.timestamp = now + refreshed.expires_in;
+
+                        // 同步更新跨线程共享的 DashMap
+                        if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
+                            entry.access_token = token.access_token.clone();
+                            entry.expires_in = token.expires_in;
+                            entry.timestamp = token.timestamp;
+                        }
+
+                        // 同步落盘（避免重启后继续使用过期 timestamp 导致频繁刷新）
+                        if let Err(e) = self.store.save_refreshed_token(&token.account_id, &refreshed).await {
+                            tracing::debug!("保存刷新后的 token 失败 ({}): {}", token.email, e);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Token 刷新失败 ({}): {}，尝试下一个账号", token.email, e);
+                        if e.contains("invalid_grant") {
+                            tracing::error!(
+                                "Disabling account due to invalid_grant ({}): refresh_token likely revoked/expired",
+                                token.email
+                            );
+                            self.disable_account(&token.account_id, &format!("invalid_grant: {}", e)).await;
+                        }
+                        // Avoid leaking account emails to API clients; details are still in logs.
+                        last_error = Some(format!("Token refresh failed: {}", e));
+                        attempted.insert(token.account_id.clone());
+
+                        // 【优化】标记需要清除锁定，避免在循环内加锁
+                        if quota_group != "image_gen"
+                            && matches!(&last_used_account_id, Some((id, _)) if id == &token.account_id)
+                        {
+                            need_update_last_used = Some((String::new(), Instant::now())); // 空字符串表示需要清除
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            // 4. 确保有 project_id (手动指定时直接使用，否则由宿主程序解析)
+            let project_id = if let Some(pid) = &token.project_id_override {
+                pid.clone()
+            } else {
+                match self.store.resolve_project_id(&token).await {
+                    Ok((pid, changed)) => {
+                        if changed {
+                            tracing::debug!("账号 {} 的 project_id 已更新为 {}", token.email, pid);
+                            if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
+                                entry.project_id = Some(pid.clone());
+                            }
+                            let _ = self.store.save_project_id(&token.account_id, &pid).await;
+                        }
+                        self.select_project(&token, &pid, &scheduling)
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to fetch project_id for {}: {}", token.email, e);
+                        last_error = Some(format!("Failed to fetch project_id for {}: {}", token.email, e));
+                        attempted.insert(token.account_id.clone());
+
+                        // 【优化】标记需要清除锁定，避免在循环内加锁
+                        if quota_group != "image_gen"
+                            && matches!(&last_used_account_id, Some((id, _)) if id == &token.account_id)
+                        {
+                            need_update_last_used = Some((String::new(), Instant::now())); // 空字符串表示需要清除
+                        }
+                        continue;
+                    }
+                }
+            };
+
+            // 【优化】在成功返回前，统一更新 last_used_account（如果需要）
+            if let Some((new_account_id, new_time)) = need_update_last_used {
+                if quota_group != "image_gen" {
+                    let mut last_used = self.last_used_account.lock().await;
+                    if new_account_id.is_empty() {
+                        // 空字符串表示需要清除锁定
+                        *last_used = None;
+                    } else {
+                        *last_used = Some((new_account_id, new_time));
+                    }
+                }
+            }
+
+            self.last_selected.insert(token.account_id.clone(), Instant::now());
+            self.last_projects.insert(token.email.clone(), project_id.clone());
+            self.pool_exhausted.store(false, Ordering::SeqCst);
+            let lease = self.acquire_lease(&token.account_id);
+            return Ok((token.access_token, project_id, token.email, lease));
+        }
+
+        Err(last_error.unwrap_or_else(|| "All accounts failed".to_string()))
+    }
+
+    /// 生成账号池耗尽错误，首次进入耗尽状态时通知宿主程序
+    async fn pool_exhausted_error(&self, cooling: usize, retry_after_seconds: Option<u64>) -> String {
+        let quarantined = self.store.count_quarantined().await;
+        if !self.pool_exhausted.swap(true, Ordering::SeqCst) {
+            tracing::error!(
+                "Account pool exhausted: {} cooling, {} quarantined, earliest recovery in {:?}s",
+                cooling, quarantined, retry_after_seconds
+            );
+            self.store.pool_exhausted(&PoolExhaustedEvent { cooling, quarantined, retry_after_seconds });
+        }
+        match retry_after_seconds {
+            Some(secs) => format!(
+                "{}: all accounts are cooling down or quarantined ({} cooling, {} quarantined). Retry in {}s.",
+                POOL_EXHAUSTED_ERROR, cooling, quarantined, secs
+            ),
+            None => format!(
+                "{}: all accounts are cooling down or quarantined ({} cooling, {} quarantined).",
+                POOL_EXHAUSTED_ERROR, cooling, quarantined
+            ),
+        }
+    }
+
+    /// 按选择策略生成候选账号的遍历顺序 (返回 `tokens` 的下标)
+    fn candidate_order(&self, tokens: &[ProxyToken], strategy: SelectionStrategy) -> Vec<usize> {
+        let start_idx = self.current_index.fetch_add(1, Ordering::SeqCst);
+        crate::rotation::candidate_order(
+            tokens,
+            start_idx,
+            strategy,
+            |id| self.last_selected.get(id).map(|t| *t),
+            |id| self.error_counts.get(id).map(|c| *c).unwrap_or(0),
+        )
+    }
+
+    /// 按项目选择策略为本次请求选择项目
+    fn select_project(&self, token: &ProxyToken, resolved: &str, scheduling: &StickySessionConfig) -> String {
+        let rotation = if scheduling.project_strategy == ProjectStrategy::Rotate {
+            let mut counter = self.project_rotation.entry(token.account_id.clone()).or_insert(0);
+            let current = *counter;
+            *counter = counter.wrapping_add(1);
+            current
+        } else {
+            0
+        };
+        pick_project(scheduling.project_strategy, &scheduling.project_preference, resolved, &token.projects, rotation)
+    }
+
+    /// 停用账号并从账号池中移除
+    async fn disable_account(&self, account_id: &str, reason: &str) {
+        if let Err(e) = self.store.disable_account(account_id, reason).await {
+            tracing::error!("Failed to disable account {}: {}", account_id, e);
+        }
+        // 【修复 Issue #3】从内存中移除禁用的账号，防止被60s锁定逻辑继续使用
+        self.tokens.remove(account_id);
+    }
+
+    // ===== 并发 =====
+
+    /// 占用账号的一个并发槽位
+    fn acquire_lease(&self, account_id: &str) -> AccountLease {
+        AccountLease::acquire(&self.in_flight, &self.slot_released, account_id)
+    }
+
+    /// 账号当前承载的上游请求数
+    pub fn in_flight_count(&self, account_id: &str) -> u32 {
+        self.in_flight.get(account_id).map(|c| *c).unwrap_or(0)
+    }
+
+    /// 当前负载：排队中的请求数与各账号在途请求数 (按并发数从多到少)
+    pub fn load_snapshot(&self) -> (usize, Vec<AccountLoad>) {
+        let mut accounts: Vec<AccountLoad> = self
+            .in_flight
+            .iter()
+            .filter(|entry| *entry.value() > 0)
+            .map(|entry| AccountLoad {
+                account_id: entry.key().clone(),
+                email: self.tokens.get(entry.key()).map(|t| t.email.clone()).unwrap_or_default(),
+                in_flight: *entry.value(),
+            })
+            .collect();
+        accounts.sort_by(|a, b| b.in_flight.cmp(&a.in_flight).then_with(|| a.email.cmp(&b.email)));
+        (self.queued.load(Ordering::Relaxed), accounts)
+    }
+
+    /// 账号当前是否可以承接目标模型的请求 (不考虑并发)
+    fn is_usable_for(&self, token: &ProxyToken, target_model: &str) -> bool {
+        !token.protected_models.contains(target_model) && !self.is_rate_limited_by_account_id(&token.account_id)
+    }
+
+    /// 返回已达并发上限的账号；若除此之外没有可用账号则返回空集 (允许超额使用)
+    fn saturated_accounts(
+        &self,
+        tokens: &[ProxyToken],
+        limit: u32,
+        target_model: &str,
+        attempted: &HashSet<String>,
+    ) -> HashSet<String> {
+        if limit == 0 {
+            return HashSet::new();
+        }
+        let saturated: HashSet<String> = tokens
+            .iter()
+            .filter(|t| self.in_flight_count(&t.account_id) >= limit)
+            .map(|t| t.account_id.clone())
+            .collect();
+        let any_free = tokens.iter().any(|t| {
+            !saturated.contains(&t.account_id) && !attempted.contains(&t.account_id) && self.is_usable_for(t, target_model)
+        });
+        if any_free { saturated } else { HashSet::new() }
+    }
+
+    /// 所有可用账号都满载时等待槽位释放，最多等待 [`CONCURRENCY_QUEUE_WAIT`]
+    async fn wait_for_free_slot(&self, tokens: &[ProxyToken], limit: u32, target_model: &str) {
+        let deadline = tokio::time::Instant::now() + CONCURRENCY_QUEUE_WAIT;
+        let mut _queued: Option<QueueGuard> = None;
+        loop {
+            let notified = self.slot_released.notified();
+            let usable: Vec<&ProxyToken> = tokens.iter().filter(|t| self.is_usable_for(t, target_model)).collect();
+            if usable.is_empty() || usable.iter().any(|t| self.in_flight_count(&t.account_id) < limit) {
+                return;
+            }
+            if _queued.is_none() {
+                _queued = Some(QueueGuard::enter(&self.queued));
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                tracing::debug!("All accounts are at their concurrency limit, exceeding the limit after queueing");
+                return;
+            }
+        }
+    }
+
+    // ===== Token 刷新 =====
+
+    /// 在后台以有限并发刷新已加载账号中即将过期的 Token，不阻塞反代启动
+    ///
+    /// 刷新完成前收到的请求仍按原流程在请求路径上刷新
+    pub fn start_startup_refresh(self: &Arc<Self>) {
+        let expiring = self.expiring_tokens(chrono::Utc::now().timestamp());
+        if expiring.is_empty() {
+            return;
+        }
+        let total = expiring.len();
+        tracing::info!("后台刷新 {} 个即将过期的 Token (最大并发: {})", total, STARTUP_REFRESH_CONCURRENCY);
+
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            let refreshed = futures::stream::iter(expiring)
+                .map(|token| {
+                    let weak = weak.clone();
+                    async move {
+                        match weak.upgrade() {
+                            Some(pool) => pool.refresh_expiring(&token).await,
+                            None => false,
+                        }
+                    }
+                })
+                .buffer_unordered(STARTUP_REFRESH_CONCURRENCY)
+                .filter(|ok| futures::future::ready(*ok))
+                .count()
+                .await;
+            tracing::info!("启动 Token 刷新完成: 成功 {}/{}", refreshed, total);
+        });
+    }
+
+    /// 即将过期 (或已过期) 的账号
+    fn expiring_tokens(&self, now: i64) -> Vec<ProxyToken> {
+        self.tokens
+            .iter()
+            .filter(|entry| now >= entry.timestamp - TOKEN_REFRESH_MARGIN_SECS)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// 刷新单个账号的 Token 并更新账号池与存储，返回是否成功
+    async fn refresh_expiring(&self, token: &ProxyToken) -> bool {
+        let now = chrono::Utc::now().timestamp();
+        match self.tokens.get(&token.account_id) {
+            // 已被移出账号池
+            None => return false,
+            // 请求路径已经完成了刷新
+            Some(entry) if now < entry.timestamp - TOKEN_REFRESH_MARGIN_SECS => return true,
+            Some(_) => {}
+        }
+
+        match self.store.refresh_token(token).await {
+            Ok(refreshed) => {
+                if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
+                    entry.access_token = refreshed.access_token.clone();
+                    entry.expires_in = refreshed.expires_in;
+                    entry.timestamp = chrono::Utc::now().timestamp() + refreshed.expires_in;
+                }
+                if let Err(e) = self.store.save_refreshed_token(&token.account_id, &refreshed).await {
+                    tracing::debug!("保存刷新后的 token 失败 ({}): {}", token.email, e);
+                }
+                true
+            }
+            Err(e) => {
+                tracing::warn!("启动时刷新 Token 失败 ({}): {}", token.email, e);
+                if e.contains("invalid_grant") {
+                    self.disable_account(&token.account_id, &format!("invalid_grant: {}", e)).await;
+                }
+                false
+            }
+        }
+    }
+
+    /// 通过 email 获取指定账号的 Token（用于预热等需要指定账号的场景）
+    /// 此方法会自动刷新过期的 token
+    pub async fn get_token_by_email(&self, email: &str) -> Result<(String, String, String), String> {
+        let token = self.token_by_email(email).ok_or_else(|| format!("未找到账号: {}", email))?;
+        let now = chrono::Utc::now().timestamp();
+        let project_id = token
+            .project_id_override
+            .clone()
+            .or_else(|| token.project_id.clone())
+            .unwrap_or_else(|| "bamboo-precept-lgxtn".to_string());
+
+        // 检查是否过期 (提前5分钟)
+        if now < token.timestamp + token.expires_in - TOKEN_REFRESH_MARGIN_SECS {
+            return Ok((token.access_token, project_id, email.to_string()));
+        }
+
+        tracing::info!("[Warmup] Token for {} is expiring, refreshing...", email);
+
+        match self.store.refresh_token(&token).await {
+            Ok(refreshed) => {
+                tracing::info!("[Warmup] Token refresh successful for {}", email);
+                let new_now = chrono::Utc::now().timestamp();
+
+                // 更新缓存
+                if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
+                    entry.access_token = refreshed.access_token.clone();
+                    entry.expires_in = refreshed.expires_in;
+                    entry.timestamp = new_now;
+                }
+
+                // 保存到存储
+                let _ = self.store.save_refreshed_token(&token.account_id, &refreshed).await;
+
+                Ok((refreshed.access_token, project_id, email.to_string()))
+            }
+            Err(e) => Err(format!("[Warmup] Token refresh failed for {}: {}", email, e)),
+        }
+    }
+
+    // ===== 限流管理 =====
+
+    /// 限流跟踪器 (key 为 account_id)
+    pub fn rate_limit_tracker(&self) -> &RateLimitTracker {
+        &self.rate_limit_tracker
+    }
+
+    /// 记录一次账号错误 (参数可为 email 或 account_id)
+    pub fn record_account_error(&self, key: &str) {
+        let account_id = self.email_to_account_id(key).unwrap_or_else(|| key.to_string());
+        *self.error_counts.entry(account_id).or_insert(0) += 1;
+    }
+
+    /// 标记账号限流(从外部调用,通常在 handler 中)
+    /// 参数为 email，内部会自动转换为 account_id
+    pub fn mark_rate_limited(
+        &self,
+        email: &str,
+        status: u16,
+        retry_after_header: Option<&str>,
+        error_body: &str,
+    ) {
+        self.record_account_error(email);
+        let key = self.email_to_account_id(email).unwrap_or_else(|| email.to_string());
+        let was_limited = self.rate_limit_tracker.is_rate_limited(&key);
+        self.rate_limit_tracker.parse_from_error(&key, status, retry_after_header, error_body, None);
+        // 只记录进入限流的时刻，冷却期内重复的 429 不再写入
+        if status == 429 && !was_limited {
+            let detail = match self.rate_limit_tracker.get_reset_seconds(&key) {
+                Some(secs) => format!("HTTP 429, retry in {}s", secs),
+                None => "HTTP 429".to_string(),
+            };
+            self.store.record_event(&key, "rate_limited", Some(detail));
+        }
+        self.notify_cooling(&key);
+    }
+
+    /// 检查账号是否在限流中
+    /// 参数为 email，内部会自动转换为 account_id
+    pub fn is_rate_limited(&self, email: &str) -> bool {
+        match self.email_to_account_id(email) {
+            Some(account_id) => self.rate_limit_tracker.is_rate_limited(&account_id),
+            // Fallback: 如果找不到，直接用email查询(兼容旧数据)
+            None => self.rate_limit_tracker.is_rate_limited(email),
+        }
+    }
+
+    /// 检查账号是否在限流中 (直接使用 account_id)
+    pub fn is_rate_limited_by_account_id(&self, account_id: &str) -> bool {
+        self.rate_limit_tracker.is_rate_limited(account_id)
+    }
+
+    /// 获取距离限流重置还有多少秒
+    pub fn get_rate_limit_reset_seconds(&self, account_id: &str) -> Option<u64> {
+        self.rate_limit_tracker.get_reset_seconds(account_id)
+    }
+
+    /// 清除过期的限流记录
+    pub fn clean_expired_rate_limits(&self) {
+        self.rate_limit_tracker.cleanup_expired();
+    }
+
+    /// 清除指定账号的限流记录
+    pub fn clear_rate_limit(&self, account_id: &str) -> bool {
+        self.rate_limit_tracker.clear(account_id)
+    }
+
+    /// 标记账号请求成功，重置连续失败计数
+    ///
+    /// 在请求成功完成后调用，将该账号的失败计数归零，
+    /// 下次失败时从最短的锁定时间开始（智能限流）。
+    pub fn mark_account_success(&self, account_id: &str) {
+        // handlers 传入的可能是 email,统一转换为 tracker 使用的 account_id
+        let key = self.email_to_account_id(account_id).unwrap_or_else(|| account_id.to_string());
+        self.rate_limit_tracker.mark_success(&key);
+    }
+
+    // ===== 冷却 =====
+
+    /// 启动冷却监视任务: 在冷却结束时及时清理过期的限流记录,并通知宿主程序账号已自动恢复
+    ///
+    /// 任务只持有 Weak 引用,账号池被释放 (反代停止) 后自动退出
+    pub fn start_cooldown_watcher(self: &Arc<Self>) {
+        let weak = Arc::downgrade(self);
+        let tracker = self.rate_limit_tracker.clone();
+        tokio::spawn(async move {
+            loop {
+                // 睡到最早的冷却结束时间 (最长 COOLDOWN_WATCH_INTERVAL_SECS)，有新的限流记录时提前唤醒重新计算
+                let max_wait = std::time::Duration::from_secs(COOLDOWN_WATCH_INTERVAL_SECS);
+                let wait = tracker
+                    .next_reset()
+                    .map(|t| t.duration_since(std::time::SystemTime::now()).unwrap_or_default().min(max_wait))
+                    .unwrap_or(max_wait);
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = tracker.changed() => continue,
+                }
+                let Some(pool) = weak.upgrade() else { break };
+                pool.reactivate_expired_accounts();
+            }
+        });
+    }
+
+    /// 恢复冷却已结束的账号,返回被恢复的 account_id 列表
+    pub fn reactivate_expired_accounts(&self) -> Vec<String> {
+        let expired = self.rate_limit_tracker.take_expired();
+        let mut reactivated = Vec::with_capacity(expired.len());
+        for (account_id, info) in expired {
+            tracing::info!("账号 {} 冷却结束,已自动恢复调度", account_id);
+            let email = self.tokens.get(&account_id)
+                .map(|t| t.email.clone())
+                .unwrap_or_default();
+            self.store.cooldown_changed(&AccountCooldownEvent {
+                account_id: account_id.clone(),
+                email,
+                state: "active".to_string(),
+                reason: Some(info.reason),
+                model: info.model,
+                remaining_seconds: None,
+                usable_at: None,
+            });
+            reactivated.push(account_id);
+        }
+        reactivated
+    }
+
+    /// 列出当前仍处于冷却中的账号
+    pub fn get_cooling_accounts(&self) -> Vec<AccountCooldownEvent> {
+        let now = std::time::SystemTime::now();
+        let mut list: Vec<AccountCooldownEvent> = self.rate_limit_tracker
+            .active_limits()
+            .into_iter()
+            .map(|(account_id, info)| AccountCooldownEvent {
+                email: self.tokens.get(&account_id)
+                    .map(|t| t.email.clone())
+                    .unwrap_or_default(),
+                account_id,
+                state: "cooling".to_string(),
+                reason: Some(info.reason),
+                model: info.model,
+                remaining_seconds: info.reset_time.duration_since(now).ok().map(|d| d.as_secs()),
+                usable_at: info.reset_time
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .ok()
+                    .map(|d| d.as_secs() as i64),
+            })
+            .collect();
+        list.sort_by_key(|e| e.remaining_seconds);
+        list
+    }
+
+    /// 通知宿主程序账号进入冷却
+    pub fn notify_cooling(&self, account_id: &str) {
+        if let Some(event) = self.get_cooling_accounts().into_iter().find(|e| e.account_id == account_id) {
+            self.store.cooldown_changed(&event);
+        }
+    }
+
+    // ===== 调度配置 =====
+
+    /// 获取当前调度配置
+    pub async fn get_sticky_config(&self) -> StickySessionConfig {
+        self.sticky_config.read().await.clone()
+    }
+
+    /// 更新调度配置
+    pub async fn update_sticky_config(&self, new_config: StickySessionConfig) {
+        let mut config = self.sticky_config.write().await;
+        *config = new_config;
+        tracing::debug!("Scheduling configuration updated: {:?}", *config);
+    }
+
+    /// 替换账号池 (池名称 -> 账号 ID、邮箱或 "tag:<标签>")
+    pub async fn set_account_pools(&self, pools: HashMap<String, HashSet<String>>) {
+        let mut map = self.account_pools.write().await;
+        *map = pools;
+        tracing::debug!("Account pools updated: {} pool(s)", map.len());
+    }
+
+    /// 将会话绑定到指定账号 (粘性会话)
+    pub fn bind_session(&self, session_id: &str, account_id: &str) {
+        self.session_accounts.insert(session_id.to_string(), account_id.to_string());
+    }
+
+    /// 会话当前绑定的账号
+    pub fn session_account(&self, session_id: &str) -> Option<String> {
+        self.session_accounts.get(session_id).map(|id| id.clone())
+    }
+
+    /// 清除特定会话的粘性映射
+    pub fn clear_session_binding(&self, session_id: &str) {
+        self.session_accounts.remove(session_id);
+    }
+
+    /// 清除所有会话的粘性映射
+    pub fn clear_all_sessions(&self) {
+        self.session_accounts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::{is_pool_exhausted, UsageWindow};
+    use std::sync::Mutex;
+
+    /// 内存中的账号存储，记录写回的内容
+    #[derive(Default)]
+    struct MemoryStore {
+        events: Mutex<Vec<(String, &'static str)>>,
+        disabled: Mutex<Vec<String>>,
+        fail_refresh: Mutex<Option<String>>,
+    }
+
+    impl AccountStore for MemoryStore {
+        fn refresh_token<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<RefreshedToken, String>> {
+            Box::pin(async move {
+                match self.fail_refresh.lock().unwrap().clone() {
+                    Some(e) => Err(e),
+                    None => Ok(RefreshedToken { access_token: format!("{}-refreshed", token.access_token), expires_in: 3600 }),
+                }
+            })
+        }
+
+        fn save_refreshed_token<'a>(&'a self, account_id: &'a str, _: &'a RefreshedToken) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move {
+                self.record_event(account_id, "refreshed", None);
+                Ok(())
+            })
+        }
+
+        fn resolve_project_id<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<(String, bool), String>> {
+            Box::pin(async move { token.project_id.clone().map(|p| (p, false)).ok_or_else(|| "no project".to_string()) })
+        }
+
+        fn save_project_id<'a>(&'a self, _: &'a str, _: &'a str) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn disable_account<'a>(&'a self, account_id: &'a str, _: &'a str) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move {
+                self.disabled.lock().unwrap().push(account_id.to_string());
+                Ok(())
+            })
+        }
+
+        fn count_quarantined(&self) -> BoxFuture<'_, usize> {
+            Box::pin(async { 0 })
+        }
+
+        fn record_event(&self, account_id: &str, kind: &'static str, _: Option<String>) {
+            self.events.lock().unwrap().push((account_id.to_string(), kind));
+        }
+    }
+
+    fn test_token(id: &str) -> ProxyToken {
+        ProxyToken {
+            account_id: id.to_string(),
+            access_token: format!("at-{}", id),
+            refresh_token: format!("rt-{}", id),
+            expires_in: 3600,
+            // 远未过期，避免测试中触发刷新
+            timestamp: chrono::Utc::now().timestamp() + 3600,
+            email: format!("{}@example.com", id),
+            project_id: Some(format!("project-{}", id)),
+            subscription_tier: Some("PRO".to_string()),
+            remaining_quota: Some(80),
+            protected_models: HashSet::new(),
+            available_models: HashSet::new(),
+            usage_windows: Vec::new(),
+            egress_proxy: None,
+            project_id_override: None,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            priority: 0,
+        }
+    }
+
+    fn pool_with_store(ids: &[&str], mode: SchedulingMode, store: Arc<MemoryStore>) -> TokenPool {
+        let pool = TokenPool::new(store);
+        for id in ids {
+            pool.insert_token(test_token(id));
+        }
+        *pool.sticky_config.try_write().unwrap() = StickySessionConfig {
+            mode,
+            ..Default::default()
+        };
+        pool
+    }
+
+    fn pool_with(ids: &[&str], mode: SchedulingMode) -> TokenPool {
+        pool_with_store(ids, mode, Arc::new(MemoryStore::default()))
+    }
+
+    #[test]
+    fn test_expiring_tokens_selects_only_tokens_near_expiry() {
+        let pool = pool_with(&["fresh", "expiring", "expired"], SchedulingMode::Balance);
+        let now = chrono::Utc::now().timestamp();
+        pool.tokens.get_mut("expiring").unwrap().timestamp = now + 60;
+        pool.tokens.get_mut("expired").unwrap().timestamp = now - 60;
+
+        let mut ids: Vec<String> = pool.expiring_tokens(now).into_iter().map(|t| t.account_id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["expired".to_string(), "expiring".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed_and_saved_through_store() {
+        let store = Arc::new(MemoryStore::default());
+        let pool = pool_with_store(&["a", "b"], SchedulingMode::PerformanceFirst, store.clone());
+        pool.tokens.get_mut("a").unwrap().timestamp = chrono::Utc::now().timestamp() - 60;
+        pool.tokens.get_mut("a").unwrap().priority = 10;
+
+        let (access_token, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!((access_token.as_str(), email.as_str()), ("at-a-refreshed", "a@example.com"));
+        assert_eq!(pool.token("a").unwrap().access_token, "at-a-refreshed");
+        assert_eq!(*store.events.lock().unwrap(), [("a".to_string(), "refreshed")]);
+
+        // refresh_token 失效: 停用账号并改用下一个账号
+        pool.tokens.get_mut("a").unwrap().timestamp = chrono::Utc::now().timestamp() - 60;
+        *store.fail_refresh.lock().unwrap() = Some("invalid_grant".to_string());
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "b@example.com");
+        assert_eq!(*store.disabled.lock().unwrap(), ["a".to_string()]);
+        assert!(pool.token("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_performance_first_rotates_across_all_accounts() {
+        let pool = pool_with(&["a", "b", "c"], SchedulingMode::PerformanceFirst);
+
+        let mut seen = HashSet::new();
+        for _ in 0..3 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, Some("sid"), "claude-sonnet-4-5", None).await.unwrap();
+            seen.insert(email);
+        }
+        assert_eq!(seen.len(), 3, "round-robin should visit every healthy account");
+    }
+
+    #[tokio::test]
+    async fn test_project_id_override_bypasses_resolution() {
+        let pool = pool_with(&["a"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("a").unwrap().project_id_override = Some("fixed-project".to_string());
+
+        let (_, project_id, _, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(project_id, "fixed-project");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_project_strategy_cycles_known_projects() {
+        let pool = pool_with(&["a"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("a").unwrap().projects = vec!["project-a".to_string(), "project-extra".to_string()];
+        pool.sticky_config.write().await.project_strategy = ProjectStrategy::Rotate;
+
+        let mut used = Vec::new();
+        for _ in 0..3 {
+            let (_, project_id, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(pool.last_project_for(&email).as_deref(), Some(project_id.as_str()));
+            used.push(project_id);
+        }
+        assert_eq!(used, vec!["project-a", "project-extra", "project-a"]);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_prefers_idle_account() {
+        let pool = pool_with(&["a", "b", "c"], SchedulingMode::PerformanceFirst);
+        pool.sticky_config.write().await.strategy = SelectionStrategy::LeastRecentlyUsed;
+        let now = Instant::now();
+        pool.last_selected.insert("a".to_string(), now);
+        pool.last_selected.insert("c".to_string(), now);
+
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "b@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_least_errors_and_most_remaining_quota() {
+        let pool = pool_with(&["a", "b"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("b").unwrap().remaining_quota = Some(95);
+        pool.record_account_error("b@example.com");
+
+        pool.sticky_config.write().await.strategy = SelectionStrategy::LeastErrors;
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "a@example.com");
+
+        pool.sticky_config.write().await.strategy = SelectionStrategy::MostRemainingQuota;
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "b@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_skips_rate_limited_account() {
+        let pool = pool_with(&["a", "b"], SchedulingMode::PerformanceFirst);
+        pool.rate_limit_tracker.parse_from_error("a", 429, Some("60"), "", None);
+
+        for _ in 0..4 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(email, "b@example.com");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_by_email_cools_account_and_reactivates() {
+        let store = Arc::new(MemoryStore::default());
+        let pool = pool_with_store(&["a", "b"], SchedulingMode::PerformanceFirst, store.clone());
+
+        // handlers 传入 email,冷却状态需要记录在 account_id 上
+        pool.mark_rate_limited("a@example.com", 429, Some("300"), "");
+        pool.mark_rate_limited("a@example.com", 429, Some("300"), "");
+        assert!(pool.is_rate_limited_by_account_id("a"));
+        // 冷却期内重复的 429 只记录一次事件
+        assert_eq!(*store.events.lock().unwrap(), [("a".to_string(), "rate_limited")]);
+        let cooling = pool.get_cooling_accounts();
+        assert_eq!(cooling.len(), 1);
+        assert_eq!(cooling[0].email, "a@example.com");
+        let usable_at = cooling[0].usable_at.unwrap();
+        assert!((usable_at - chrono::Utc::now().timestamp() - 300).abs() <= 1);
+
+        for _ in 0..3 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(email, "b@example.com");
+        }
+
+        // 冷却结束后自动恢复
+        pool.rate_limit_tracker.set_lockout_until(
+            "a",
+            std::time::SystemTime::now() - std::time::Duration::from_secs(1),
+            RateLimitReason::QuotaExhausted,
+            None,
+        );
+        assert_eq!(pool.reactivate_expired_accounts(), vec!["a".to_string()]);
+        assert!(!pool.is_rate_limited_by_account_id("a"));
+        assert!(pool.get_cooling_accounts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_watcher_reactivates_at_reset_time() {
+        let pool = Arc::new(pool_with(&["a"], SchedulingMode::PerformanceFirst));
+        pool.start_cooldown_watcher();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // 冷却 1 秒: 应在重置时间到达时恢复，而不是等到下一个固定检查周期
+        pool.rate_limit_tracker.set_lockout_until(
+            "a",
+            std::time::SystemTime::now() + std::time::Duration::from_secs(1),
+            RateLimitReason::RateLimitExceeded,
+            None,
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        assert!(pool.rate_limit_tracker.get("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pool_scoped_selection() {
+        let pool = pool_with(&["a", "b", "c"], SchedulingMode::PerformanceFirst);
+        // 支持账号 ID 和邮箱两种写法
+        let members = ["a".to_string(), "c@example.com".to_string()].into_iter().collect();
+        pool.set_account_pools([("work".to_string(), members)].into_iter().collect()).await;
+
+        for _ in 0..6 {
+            let (_, _, email, _) = pool
+                .get_token_in_pool("claude", false, None, "claude-sonnet-4-5", Some("work"))
+                .await
+                .unwrap();
+            assert_ne!(email, "b@example.com");
+        }
+
+        pool.mark_rate_limited("a@example.com", 429, Some("300"), "");
+        pool.mark_rate_limited("c@example.com", 429, Some("300"), "");
+        assert!(pool
+            .get_token_in_pool("claude", false, None, "claude-sonnet-4-5", Some("work"))
+            .await
+            .is_err());
+        assert!(pool
+            .get_token_in_pool("claude", false, None, "claude-sonnet-4-5", Some("missing"))
+            .await
+            .is_err());
+        // 未绑定池的请求不受影响
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "b@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_pool_exhausted_error_when_every_account_is_cooling() {
+        let pool = pool_with(&["a", "b"], SchedulingMode::PerformanceFirst);
+        pool.mark_rate_limited("a@example.com", 429, Some("60"), "");
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "b@example.com");
+
+        pool.mark_rate_limited("b@example.com", 429, Some("30"), "");
+        let Err(err) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await else { panic!("应当没有可用账号") };
+        assert!(is_pool_exhausted(&err), "unexpected error: {}", err);
+        assert!(err.contains("2 cooling"));
+        assert!(pool.pool_exhausted.load(Ordering::SeqCst));
+
+        // 有账号恢复后清除耗尽状态
+        pool.rate_limit_tracker.clear_all();
+        assert!(pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.is_ok());
+        assert!(!pool.pool_exhausted.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_tag_filtered_rotation_and_pool_membership() {
+        let pool = pool_with(&["a", "b", "c"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("a").unwrap().tags = vec!["team-x".to_string()];
+        pool.tokens.get_mut("c").unwrap().tags = vec!["team-x".to_string(), "ultra".to_string()];
+
+        assert_eq!(
+            pool.list_accounts_by_tag("team-x"),
+            vec![("a".to_string(), "a@example.com".to_string()), ("c".to_string(), "c@example.com".to_string())]
+        );
+
+        let mut seen = HashSet::new();
+        for _ in 0..4 {
+            let (_, _, email, _) = pool
+                .get_token_with_tag("claude", false, None, "claude-sonnet-4-5", "team-x")
+                .await
+                .unwrap();
+            seen.insert(email);
+        }
+        assert_eq!(seen, ["a@example.com".to_string(), "c@example.com".to_string()].into_iter().collect());
+        assert!(pool.get_token_with_tag("claude", false, None, "claude-sonnet-4-5", "missing").await.is_err());
+
+        let members = ["tag:ultra".to_string()].into_iter().collect();
+        pool.set_account_pools([("ultra".to_string(), members)].into_iter().collect()).await;
+        let (_, _, email, _) = pool
+            .get_token_in_pool("claude", false, None, "claude-sonnet-4-5", Some("ultra"))
+            .await
+            .unwrap();
+        assert_eq!(email, "c@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_priority_prefers_high_accounts_and_overflows() {
+        let pool = pool_with(&["low", "high"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("high").unwrap().priority = 10;
+
+        for _ in 0..4 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(email, "high@example.com");
+        }
+
+        // 高优先级账号冷却期间溢出到低优先级账号
+        pool.mark_rate_limited("high@example.com", 429, Some("300"), "");
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "low@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_model_entitlement() {
+        let pool = pool_with(&["free", "paid", "unknown"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("free").unwrap().available_models = ["gemini-3-flash".to_string()].into_iter().collect();
+        pool.tokens.get_mut("paid").unwrap().available_models =
+            ["gemini-3-flash".to_string(), "claude-opus-4-5-thinking".to_string()].into_iter().collect();
+
+        for _ in 0..6 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-opus-4-5-thinking", None).await.unwrap();
+            assert_ne!(email, "free@example.com");
+        }
+
+        // 所有账号都未声明的模型不做过滤
+        let mut seen = HashSet::new();
+        for _ in 0..3 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            seen.insert(email);
+        }
+        assert_eq!(seen.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_usage_windows_exclude_accounts_outside_window() {
+        let pool = pool_with(&["day", "night"], SchedulingMode::PerformanceFirst);
+        let hhmm = |m: u32| format!("{:02}:{:02}", (m / 60) % 24, m % 60);
+        let now = current_minute_of_day();
+        // "night" 的时段从 1 小时后开始，当前不可用
+        pool.tokens.get_mut("night").unwrap().usage_windows =
+            vec![UsageWindow { start: hhmm(now + 60), end: hhmm(now + 120) }];
+        // "day" 的时段包含当前时刻 (可能跨越午夜)
+        pool.tokens.get_mut("day").unwrap().usage_windows =
+            vec![UsageWindow { start: hhmm(now + 1440 - 30), end: hhmm(now + 30) }];
+
+        for _ in 0..4 {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(email, "day@example.com");
+        }
+
+        let night_windows = pool.tokens.get("night").unwrap().usage_windows.clone();
+        pool.tokens.get_mut("day").unwrap().usage_windows = night_windows;
+        assert!(pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_account_respects_cooldown() {
+        let pool = pool_with(&["a", "b", "c"], SchedulingMode::Balance);
+        pool.sticky_config.write().await.pinned_account_id = Some("c".to_string());
+
+        for sid in ["s1", "s2"] {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, Some(sid), "claude-sonnet-4-5", None).await.unwrap();
+            assert_eq!(email, "c@example.com");
+        }
+
+        pool.mark_rate_limited("c@example.com", 429, Some("300"), "");
+        let (_, _, email, _) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_ne!(email, "c@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_remove_account_unbinds_sessions() {
+        let pool = pool_with(&["a", "b"], SchedulingMode::Balance);
+        let (_, _, first, _) = pool.get_token_in_pool("claude", false, Some("s1"), "claude-sonnet-4-5", None).await.unwrap();
+        let first_id = first.split('@').next().unwrap().to_string();
+
+        pool.remove_account(&first_id).await;
+        assert!(pool.session_account("s1").is_none());
+        assert!(pool.last_used_account.lock().await.is_none());
+
+        // 暂停的账号不再参与调度
+        for sid in ["s1", "s2"] {
+            let (_, _, email, _) = pool.get_token_in_pool("claude", false, Some(sid), "claude-sonnet-4-5", None).await.unwrap();
+            assert_ne!(email, first);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_skips_busy_account_and_spills_over() {
+        let pool = pool_with(&["a", "b"], SchedulingMode::PerformanceFirst);
+        pool.tokens.get_mut("a").unwrap().priority = 10;
+        pool.sticky_config.write().await.max_concurrent_per_account = 1;
+
+        let (_, _, first, lease_a) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(first, "a@example.com");
+        // a 已满载，即使优先级更高也改用 b
+        let (_, _, second, lease_b) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(second, "b@example.com");
+        assert_eq!(pool.in_flight_count("a"), 1);
+        let (queued, accounts) = pool.load_snapshot();
+        assert_eq!(queued, 0);
+        assert_eq!(accounts.iter().map(|a| (a.email.as_str(), a.in_flight)).collect::<Vec<_>>(), [("a@example.com", 1), ("b@example.com", 1)]);
+
+        // 全部满载时排队等待，槽位释放后立即拿到空闲账号
+        let releaser = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            drop(lease_a);
+        });
+        let (_, _, third, lease_c) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        releaser.await.unwrap();
+        assert_eq!(third, "a@example.com");
+
+        drop((lease_b, lease_c));
+        assert!(pool.in_flight.is_empty());
+        assert_eq!(pool.load_snapshot(), (0, Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_exceeds_after_queue_timeout() {
+        let pool = pool_with(&["a"], SchedulingMode::PerformanceFirst);
+        pool.sticky_config.write().await.max_concurrent_per_account = 1;
+
+        let (_, _, _, _held) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        let (_, _, email, _extra) = pool.get_token_in_pool("claude", false, None, "claude-sonnet-4-5", None).await.unwrap();
+        assert_eq!(email, "a@example.com");
+        assert_eq!(pool.in_flight_count("a"), 2);
+    }
+}
@@ -0,0 +1,90 @@
+// 流式响应的有界转发通道
+//
+// 中间件在独立任务中读取上游流 (统计用量、截取输出) 再转发给客户端。通道同时限制
+// 缓冲的块数与字节数：客户端读取缓慢时发送端在 `send` 处等待，上游读取随之暂停，
+// 长时间生成 (尤其是内联图片等大块数据) 不会在内存中无限堆积。
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+/// 通道中最多缓冲的块数
+pub const MAX_BUFFERED_CHUNKS: usize = 64;
+/// 通道中最多缓冲的字节数 (超过该大小的单个块独占全部额度)
+pub const MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+type Item<E> = (Result<Bytes, E>, Option<OwnedSemaphorePermit>);
+
+/// 发送端，客户端已断开时 `send` 返回 false
+pub struct Sender<E> {
+    tx: mpsc::Sender<Item<E>>,
+    budget: Arc<Semaphore>,
+    max_bytes: usize,
+}
+
+impl<E> Sender<E> {
+    /// 等待缓冲额度后发送，块被客户端取走时释放额度
+    pub async fn send(&self, item: Result<Bytes, E>) -> bool {
+        let permit = match &item {
+            Ok(chunk) if !chunk.is_empty() => {
+                let bytes = chunk.len().min(self.max_bytes) as u32;
+                match self.budget.clone().acquire_many_owned(bytes).await {
+                    Ok(permit) => Some(permit),
+                    Err(_) => return false,
+                }
+            }
+            _ => None,
+        };
+        self.tx.send((item, permit)).await.is_ok()
+    }
+}
+
+/// 创建有界通道，返回发送端与用作响应体的接收流
+pub fn channel<E>(max_chunks: usize, max_bytes: usize) -> (Sender<E>, impl Stream<Item = Result<Bytes, E>>)
+where
+    E: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(max_chunks.max(1));
+    let max_bytes = max_bytes.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+    let sender = Sender { tx, budget: Arc::new(Semaphore::new(max_bytes)), max_bytes };
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx).map(|(item, _permit)| item);
+    (sender, stream)
+}
+
+/// 使用默认上限创建通道
+pub fn default_channel<E>() -> (Sender<E>, impl Stream<Item = Result<Bytes, E>>)
+where
+    E: Send + 'static,
+{
+    channel(MAX_BUFFERED_CHUNKS, MAX_BUFFERED_BYTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_sender_waits_for_byte_budget() {
+        let (tx, stream) = channel::<()>(16, 10);
+        futures::pin_mut!(stream);
+
+        assert!(tx.send(Ok(Bytes::from_static(b"12345678"))).await);
+        // 剩余额度不足，需等待接收端取走前一个块
+        let blocked = tokio::time::timeout(Duration::from_millis(50), tx.send(Ok(Bytes::from_static(b"abcd")))).await;
+        assert!(blocked.is_err());
+
+        assert_eq!(stream.next().await, Some(Ok(Bytes::from_static(b"12345678"))));
+        // 超过上限的块独占全部额度，仍可发送
+        assert!(tx.send(Ok(Bytes::from(vec![0u8; 32]))).await);
+        assert_eq!(stream.next().await.unwrap().unwrap().len(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_after_receiver_dropped() {
+        let (tx, stream) = default_channel::<()>();
+        drop(stream);
+        assert!(!tx.send(Ok(Bytes::from_static(b"data"))).await);
+    }
+}
@@ -0,0 +1,240 @@
+// 上游端点统计
+//
+// 按 base URL (prod / daily 等区域端点) 记录每次上游请求的响应头耗时与结果。
+// 最近一段时间的样本用于计算错误率与延迟分位数：路由时把近期错误率过高的端点排到后面，
+// 前端展示各端点的健康状况。数据只保存在内存中，服务重启后重新累计。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// 滑动窗口长度 (毫秒)
+const WINDOW_MS: i64 = 10 * 60 * 1000;
+/// 每个端点最多保留的样本数
+const MAX_SAMPLES: usize = 1000;
+/// 判定不健康所需的最少样本数
+const MIN_SAMPLES: usize = 5;
+/// 窗口内错误率达到该值视为不健康
+const UNHEALTHY_ERROR_RATE: f64 = 0.5;
+/// 延迟 EWMA 平滑系数
+const EWMA_ALPHA: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at_ms: i64,
+    latency_ms: u64,
+    is_error: bool,
+}
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    requests: u64,
+    errors: u64,
+    samples: VecDeque<Sample>,
+    ewma_ms: Option<f64>,
+    last_status: Option<u16>,
+    last_error: Option<String>,
+    last_error_at: Option<i64>,
+}
+
+impl EndpointState {
+    fn expire(&mut self, now_ms: i64) {
+        while self.samples.front().is_some_and(|s| s.at_ms <= now_ms - WINDOW_MS) {
+            self.samples.pop_front();
+        }
+    }
+
+    fn window_counts(&self) -> (usize, usize) {
+        let errors = self.samples.iter().filter(|s| s.is_error).count();
+        (self.samples.len(), errors)
+    }
+
+    fn is_healthy(&self) -> bool {
+        let (total, errors) = self.window_counts();
+        total < MIN_SAMPLES || (errors as f64 / total as f64) < UNHEALTHY_ERROR_RATE
+    }
+}
+
+/// 单个端点的统计快照
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointSnapshot {
+    pub base_url: String,
+    /// 自服务启动起的累计请求 / 失败数
+    pub requests: u64,
+    pub errors: u64,
+    /// 最近窗口内的请求 / 失败数与错误率
+    pub window_requests: usize,
+    pub window_errors: usize,
+    pub error_rate: f64,
+    /// 最近窗口内响应头耗时 (毫秒)
+    pub avg_latency_ms: Option<u64>,
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    /// 平滑后的最近延迟 (对最新样本更敏感)
+    pub ewma_latency_ms: Option<u64>,
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    /// 最近一次失败时间 (Unix 毫秒)
+    pub last_error_at: Option<i64>,
+    /// 路由是否正常使用该端点 (不健康时排到其他端点之后)
+    pub healthy: bool,
+}
+
+/// 各上游端点的延迟与错误统计
+#[derive(Debug, Default)]
+pub struct EndpointStats {
+    endpoints: DashMap<String, EndpointState>,
+}
+
+fn percentile(sorted: &[u64], q: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((sorted.len() as f64) * q).ceil().max(1.0) as usize;
+    sorted.get(rank - 1).copied()
+}
+
+/// 是否计为端点故障：网络错误、超时、限流与 5xx (其他 4xx 通常是请求本身的问题)
+fn is_endpoint_error(status: Option<u16>) -> bool {
+    match status {
+        None => true,
+        Some(code) => code == 408 || code == 429 || code >= 500,
+    }
+}
+
+impl EndpointStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次请求；`status` 为 None 表示未收到响应 (网络错误 / 首字节超时)
+    pub fn record(&self, base_url: &str, latency_ms: u64, status: Option<u16>, error: Option<&str>, now_ms: i64) {
+        let is_error = is_endpoint_error(status);
+        let mut state = self.endpoints.entry(base_url.to_string()).or_default();
+        state.requests += 1;
+        state.last_status = status;
+        if is_error {
+            state.errors += 1;
+            state.last_error_at = Some(now_ms);
+            state.last_error = Some(match (status, error) {
+                (_, Some(e)) => e.to_string(),
+                (Some(code), None) => format!("HTTP {}", code),
+                (None, None) => "request failed".to_string(),
+            });
+        }
+        // 失败请求的耗时不代表端点的正常延迟，只计入错误率
+        if !is_error {
+            let ms = latency_ms as f64;
+            state.ewma_ms = Some(state.ewma_ms.map_or(ms, |prev| prev + EWMA_ALPHA * (ms - prev)));
+        }
+        if state.samples.len() >= MAX_SAMPLES {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(Sample { at_ms: now_ms, latency_ms, is_error });
+        state.expire(now_ms);
+    }
+
+    /// 按默认优先级排列端点，近期不健康的端点移到末尾 (全部不健康时保持原顺序)
+    pub fn order<'a>(&self, candidates: &[&'a str], now_ms: i64) -> Vec<&'a str> {
+        let healthy = |url: &str| {
+            self.endpoints.get_mut(url).is_none_or(|mut state| {
+                state.expire(now_ms);
+                state.is_healthy()
+            })
+        };
+        let (mut ordered, demoted): (Vec<&str>, Vec<&str>) = candidates.iter().partition(|url| healthy(url));
+        if !demoted.is_empty() && !ordered.is_empty() {
+            tracing::debug!("[Upstream] Demoting unhealthy endpoints: {:?}", demoted);
+        }
+        ordered.extend(demoted);
+        ordered
+    }
+
+    /// 各端点统计快照 (按 base URL 排序)
+    pub fn snapshot(&self, now_ms: i64) -> Vec<EndpointSnapshot> {
+        let mut snapshots: Vec<EndpointSnapshot> = self
+            .endpoints
+            .iter_mut()
+            .map(|mut entry| {
+                let base_url = entry.key().clone();
+                let state = entry.value_mut();
+                state.expire(now_ms);
+                let (window_requests, window_errors) = state.window_counts();
+                let mut latencies: Vec<u64> =
+                    state.samples.iter().filter(|s| !s.is_error).map(|s| s.latency_ms).collect();
+                latencies.sort_unstable();
+                EndpointSnapshot {
+                    base_url,
+                    requests: state.requests,
+                    errors: state.errors,
+                    window_requests,
+                    window_errors,
+                    error_rate: if window_requests == 0 { 0.0 } else { window_errors as f64 / window_requests as f64 },
+                    avg_latency_ms: (!latencies.is_empty())
+                        .then(|| latencies.iter().sum::<u64>() / latencies.len() as u64),
+                    p50_latency_ms: percentile(&latencies, 0.5),
+                    p95_latency_ms: percentile(&latencies, 0.95),
+                    ewma_latency_ms: state.ewma_ms.map(|ms| ms.round() as u64),
+                    last_status: state.last_status,
+                    last_error: state.last_error.clone(),
+                    last_error_at: state.last_error_at,
+                    healthy: state.is_healthy(),
+                }
+            })
+            .collect();
+        snapshots.sort_by(|a, b| a.base_url.cmp(&b.base_url));
+        snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROD: &str = "https://prod.example.com/v1internal";
+    const DAILY: &str = "https://daily.example.com/v1internal";
+
+    #[test]
+    fn test_snapshot_tracks_latency_and_errors() {
+        let stats = EndpointStats::new();
+        for (i, ms) in [100, 200, 300, 400].iter().enumerate() {
+            stats.record(PROD, *ms, Some(200), None, i as i64);
+        }
+        stats.record(PROD, 30_000, None, Some("timed out"), 10);
+        // 400 由请求本身导致，不计为端点故障
+        stats.record(PROD, 50, Some(400), None, 11);
+
+        let snapshot = &stats.snapshot(20)[0];
+        assert_eq!((snapshot.requests, snapshot.errors), (6, 1));
+        assert_eq!((snapshot.window_requests, snapshot.window_errors), (6, 1));
+        assert_eq!(snapshot.p50_latency_ms, Some(200));
+        assert_eq!(snapshot.p95_latency_ms, Some(400));
+        assert_eq!(snapshot.last_error.as_deref(), Some("timed out"));
+        assert_eq!(snapshot.last_status, Some(400));
+        assert!(snapshot.healthy);
+
+        // 窗口滑过后只保留累计值
+        let later = &stats.snapshot(WINDOW_MS + 20)[0];
+        assert_eq!((later.requests, later.window_requests, later.p50_latency_ms), (6, 0, None));
+    }
+
+    #[test]
+    fn test_unhealthy_endpoint_is_demoted_until_window_expires() {
+        let stats = EndpointStats::new();
+        assert_eq!(stats.order(&[PROD, DAILY], 0), vec![PROD, DAILY]);
+
+        for i in 0..MIN_SAMPLES as i64 {
+            stats.record(PROD, 10, Some(503), None, i);
+        }
+        assert_eq!(stats.order(&[PROD, DAILY], 10), vec![DAILY, PROD]);
+
+        // 全部不健康时保持原顺序
+        for i in 0..MIN_SAMPLES as i64 {
+            stats.record(DAILY, 10, None, None, i);
+        }
+        assert_eq!(stats.order(&[PROD, DAILY], 10), vec![PROD, DAILY]);
+
+        assert_eq!(stats.order(&[DAILY, PROD], WINDOW_MS + 10), vec![DAILY, PROD]);
+        assert!(stats.snapshot(WINDOW_MS + 10).iter().all(|s| s.healthy));
+    }
+}
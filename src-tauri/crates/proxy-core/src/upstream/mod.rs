@@ -2,5 +2,10 @@
 // 对应上游通讯接口
 
 pub mod client;
+pub mod config;
+pub mod endpoint_stats;
 pub mod retry;
+pub mod resume;
+pub mod backpressure;
+pub mod tls;
 pub mod models;
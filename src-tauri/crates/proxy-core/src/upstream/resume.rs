@@ -0,0 +1,189 @@
+// 流式响应断点续传
+//
+// 上游连接在流式生成中途断开时，以已转发给客户端的文本作为 model 角色预填充重新发起请求，
+// 并把续写内容接在原有输出之后，避免长文本生成在最后阶段失败。
+// 只向下游转发完整的 SSE 行，断开时残留的半行会被丢弃，保证下游解析器不会收到截断的 JSON。
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use super::client::UpstreamClient;
+
+type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>;
+
+/// 已转发内容的进度 (用于判断能否续传以及构造预填充)
+#[derive(Debug, Default)]
+struct StreamProgress {
+    /// 已输出的正文 (不含思考内容)
+    text: String,
+    /// 已收到 finishReason，生成已经结束
+    finished: bool,
+    /// 已输出工具调用，续写无法保证调用不被重复
+    tool_call: bool,
+}
+
+impl StreamProgress {
+    /// 记录一批完整的 SSE 行
+    fn observe(&mut self, lines: &[u8]) {
+        let Ok(text) = std::str::from_utf8(lines) else { return };
+        for line in text.lines() {
+            let Some(data) = line.trim().strip_prefix("data:") else { continue };
+            let Ok(json) = serde_json::from_str::<Value>(data.trim()) else { continue };
+            let raw = json.get("response").unwrap_or(&json);
+            let Some(candidate) = raw.get("candidates").and_then(|c| c.get(0)) else { continue };
+
+            if candidate.get("finishReason").is_some() {
+                self.finished = true;
+            }
+            let parts = candidate["content"]["parts"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+            for part in parts {
+                if part.get("functionCall").is_some() {
+                    self.tool_call = true;
+                } else if let Some(t) = part.get("text").and_then(|t| t.as_str()) {
+                    if !part.get("thought").and_then(|v| v.as_bool()).unwrap_or(false) {
+                        self.text.push_str(t);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 只有已输出正文且尚未结束、没有工具调用时才续传
+    fn can_resume(&self) -> bool {
+        !self.finished && !self.tool_call && !self.text.is_empty()
+    }
+}
+
+/// 在原请求末尾追加已收到的文本作为 model 预填充
+///
+/// 原请求本身以 model 轮次结尾 (客户端预填充) 时，续写文本并入该轮次
+fn with_prefill(body: &Value, received: &str) -> Value {
+    let mut body = body.clone();
+    if let Some(contents) = body
+        .get_mut("request")
+        .and_then(|r| r.get_mut("contents"))
+        .and_then(|c| c.as_array_mut())
+    {
+        match contents.last_mut() {
+            Some(last) if last["role"] == "model" && last["parts"].is_array() => {
+                last["parts"].as_array_mut().unwrap().push(json!({ "text": received }));
+            }
+            _ => contents.push(json!({ "role": "model", "parts": [{ "text": received }] })),
+        }
+    }
+    body
+}
+
+/// 包装上游流式响应：连接中途断开时自动续传 (次数受重试策略中的 `max_stream_resumes` 限制)
+///
+/// `body` 为发起本次流式请求的原始请求体
+pub fn resumable_stream(
+    upstream: Arc<UpstreamClient>,
+    response: reqwest::Response,
+    access_token: String,
+    body: Value,
+    egress_proxy: Option<String>,
+) -> ByteStream {
+    let max_resumes = upstream.stream_resume_limit();
+    if max_resumes == 0 {
+        return Box::pin(response.bytes_stream());
+    }
+
+    Box::pin(async_stream::stream! {
+        let mut current: ByteStream = Box::pin(response.bytes_stream());
+        let mut progress = StreamProgress::default();
+        let mut pending = BytesMut::new();
+        let mut resumes = 0;
+
+        loop {
+            match current.next().await {
+                Some(Ok(chunk)) => {
+                    pending.extend_from_slice(&chunk);
+                    if let Some(pos) = pending.iter().rposition(|&b| b == b'\n') {
+                        let complete = pending.split_to(pos + 1).freeze();
+                        progress.observe(&complete);
+                        yield Ok(complete);
+                    }
+                }
+                Some(Err(e)) => {
+                    if resumes >= max_resumes || !progress.can_resume() {
+                        yield Err(e);
+                        break;
+                    }
+                    resumes += 1;
+                    pending.clear();
+                    tracing::warn!(
+                        "Upstream stream interrupted after {} chars ({}), resuming with prefill ({}/{})",
+                        progress.text.chars().count(),
+                        e,
+                        resumes,
+                        max_resumes
+                    );
+
+                    let resume_body = with_prefill(&body, &progress.text);
+                    match upstream
+                        .call_v1_internal_via("streamGenerateContent", &access_token, resume_body, Some("alt=sse"), egress_proxy.as_deref())
+                        .await
+                    {
+                        Ok(resp) if resp.status().is_success() => {
+                            current = Box::pin(resp.bytes_stream());
+                        }
+                        Ok(resp) => {
+                            tracing::warn!("Stream resume rejected by upstream: {}", resp.status());
+                            yield Err(e);
+                            break;
+                        }
+                        Err(err) => {
+                            tracing::warn!("Stream resume failed: {}", err);
+                            yield Err(e);
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    if !pending.is_empty() {
+                        yield Ok(pending.split().freeze());
+                    }
+                    break;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_tracks_text_and_prefill_appends_model_turn() {
+        let mut progress = StreamProgress::default();
+        progress.observe(br#"data: {"response":{"candidates":[{"content":{"parts":[{"text":"plan","thought":true}]}}]}}
+data: {"response":{"candidates":[{"content":{"parts":[{"text":"Hello, "}]}}]}}
+
+data: {"response":{"candidates":[{"content":{"parts":[{"text":"world"}]}}]}}
+"#);
+        assert_eq!(progress.text, "Hello, world");
+        assert!(progress.can_resume());
+
+        let body = json!({ "request": { "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }] } });
+        let resumed = with_prefill(&body, &progress.text);
+        assert_eq!(resumed["request"]["contents"][1], json!({ "role": "model", "parts": [{ "text": "Hello, world" }] }));
+
+        let prefilled = with_prefill(&resumed, "!");
+        assert_eq!(prefilled["request"]["contents"].as_array().unwrap().len(), 2);
+        assert_eq!(prefilled["request"]["contents"][1]["parts"][1]["text"], "!");
+
+        progress.observe(br#"data: {"response":{"candidates":[{"content":{"parts":[{"text":"."}]},"finishReason":"STOP"}]}}
+"#);
+        assert!(!progress.can_resume());
+
+        let mut tool = StreamProgress::default();
+        tool.observe(br#"data: {"candidates":[{"content":{"parts":[{"text":"x"},{"functionCall":{"name":"f","args":{}}}]}}]}
+"#);
+        assert!(!tool.can_resume());
+    }
+}
@@ -1,5 +1,6 @@
 // 429 重试策略
 // Duration 解析
+// 网络错误 / 5xx 的指数退避
 
 use regex::Regex;
 use once_cell::sync::Lazy;
@@ -8,6 +9,24 @@ static DURATION_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"([\d.]+)\s*(ms|s|m|h)").unwrap()
 });
 
+/// 单个请求的最大尝试次数上限 (防止配置过大导致请求长时间挂起)
+pub const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// 是否为值得重试的临时性上游错误
+pub fn is_transient_status(status: u16) -> bool {
+    matches!(status, 500 | 502 | 503 | 504)
+}
+
+/// 第 `retry` 次重试 (从 0 开始) 前的等待时间 (毫秒)
+///
+/// 基础值按 `base_ms * 2^retry` 增长并以 `max_ms` 封顶，
+/// 实际等待时间落在 [基础值/2, 基础值] 区间，`jitter` 为 [0, 1) 的随机数
+pub fn backoff_delay_ms(retry: u32, base_ms: u64, max_ms: u64, jitter: f64) -> u64 {
+    let exp = base_ms.saturating_mul(1u64 << retry.min(20)).min(max_ms);
+    let half = exp / 2;
+    half + (half as f64 * jitter.clamp(0.0, 1.0)) as u64
+}
+
 /// 解析 Duration 字符串 (e.g., "1.5s", "200ms", "1h16m0.667s")
 pub fn parse_duration_ms(duration_str: &str) -> Option<u64> {
     let mut total_ms: f64 = 0.0;
@@ -91,4 +110,17 @@ mod tests {
 
         assert_eq!(parse_retry_delay(error_json), Some(1204));
     }
+
+    #[test]
+    fn test_backoff_delay_grows_and_is_capped() {
+        assert_eq!(backoff_delay_ms(0, 500, 8000, 0.0), 250);
+        assert_eq!(backoff_delay_ms(0, 500, 8000, 1.0), 500);
+        assert_eq!(backoff_delay_ms(2, 500, 8000, 0.0), 1000);
+        assert_eq!(backoff_delay_ms(10, 500, 8000, 1.0), 8000);
+        assert_eq!(backoff_delay_ms(63, 500, 8000, 0.5), 6000);
+
+        assert!(is_transient_status(503));
+        assert!(!is_transient_status(429));
+        assert!(!is_transient_status(400));
+    }
 }
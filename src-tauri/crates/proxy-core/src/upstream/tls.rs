@@ -0,0 +1,256 @@
+// 上游 TLS 校验 (自定义 CA 与证书固定)
+//
+// 默认使用 native-tls 与系统根证书。配置了 CA 证书包或证书指纹后改用 rustls:
+// - CA 证书包: 在系统根证书之外额外信任 (企业代理的中间人证书)，也可以只信任证书包中的 CA
+// - 证书指纹: 服务端证书 (叶子证书 DER 的 SHA-256) 必须与其中之一匹配，在握手阶段校验，
+//   不匹配时连接在发送请求 (及 access_token) 之前就会失败，防止 DNS 篡改或未知的中间人
+// 配置无效 (证书包无法读取、指纹格式错误) 时拒绝所有上游连接，而不是退回不校验的状态。
+// 所有上游客户端 (v1internal、OAuth / 配额 / 项目接口、第三方模型服务、z.ai MCP) 都经过 `apply`，
+// 固定指纹时需要包含这些服务端的证书。
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, RwLock};
+
+use super::config::UpstreamTlsConfig;
+
+/// 当前生效的配置 (反代启动与配置热更新时由 `UpstreamClient::set_tls_config` 写入)
+static CURRENT: RwLock<Option<UpstreamTlsConfig>> = RwLock::new(None);
+
+/// 解析 SHA-256 指纹 (十六进制，允许冒号与空格分隔，不区分大小写)
+pub fn parse_fingerprint(text: &str) -> Result<[u8; 32], String> {
+    let hex: String = text.chars().filter(|c| !matches!(c, ':' | ' ')).collect();
+    let invalid = || format!("无效的证书指纹: {}", text);
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(out)
+}
+
+fn load_ca_bundle(path: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let content = std::fs::read(path).map_err(|e| format!("读取 CA 证书包 {} 失败: {}", path, e))?;
+    let certs = CertificateDer::pem_slice_iter(&content)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("解析 CA 证书包 {} 失败: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("CA 证书包 {} 中没有证书", path));
+    }
+    Ok(certs)
+}
+
+fn root_store(config: &UpstreamTlsConfig) -> Result<RootCertStore, String> {
+    let mut roots = RootCertStore::empty();
+    let bundle = config.ca_bundle_path.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    if !(config.ca_bundle_only && bundle.is_some()) {
+        let native = rustls_native_certs::load_native_certs();
+        for e in &native.errors {
+            tracing::warn!("[UpstreamTLS] 读取系统根证书失败: {}", e);
+        }
+        roots.add_parsable_certificates(native.certs);
+    }
+    if let Some(path) = bundle {
+        for cert in load_ca_bundle(path)? {
+            roots.add(cert).map_err(|e| format!("CA 证书包 {} 中的证书无效: {}", path, e))?;
+        }
+    }
+    if roots.is_empty() {
+        return Err("没有可用的根证书".to_string());
+    }
+    Ok(roots)
+}
+
+/// 校验配置 (证书包可读取、指纹格式正确)
+pub fn validate(config: &UpstreamTlsConfig) -> Result<(), String> {
+    for pin in &config.pinned_sha256 {
+        parse_fingerprint(pin)?;
+    }
+    if let Some(path) = config.ca_bundle_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        load_ca_bundle(path)?;
+    }
+    Ok(())
+}
+
+/// 证书链校验通过后再比对叶子证书指纹
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl PinnedVerifier {
+    fn matches(&self, end_entity: &CertificateDer<'_>) -> bool {
+        let digest = Sha256::digest(end_entity.as_ref());
+        self.pins.is_empty() || self.pins.iter().any(|pin| pin[..] == digest[..])
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        if !self.matches(end_entity) {
+            tracing::error!("[UpstreamTLS] {} 的证书指纹与固定的指纹不匹配", server_name.to_str());
+            return Err(rustls::Error::General("上游证书指纹不匹配".to_string()));
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// 配置无效时使用: 拒绝所有证书
+#[derive(Debug)]
+struct RejectAll {
+    reason: String,
+    schemes: Vec<SignatureScheme>,
+}
+
+impl ServerCertVerifier for RejectAll {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Err(rustls::Error::General(self.reason.clone()))
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General(self.reason.clone()))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General(self.reason.clone()))
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.schemes.clone()
+    }
+}
+
+fn build_verifier(config: &UpstreamTlsConfig, provider: &Arc<rustls::crypto::CryptoProvider>) -> Result<PinnedVerifier, String> {
+    let pins = config.pinned_sha256.iter().map(|p| parse_fingerprint(p)).collect::<Result<Vec<_>, _>>()?;
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(root_store(config)?), provider.clone())
+        .build()
+        .map_err(|e| format!("创建证书校验器失败: {}", e))?;
+    Ok(PinnedVerifier { inner, pins })
+}
+
+/// 按配置构建 rustls 客户端配置；未配置 CA 证书包与指纹时返回 None (使用默认的 native-tls)
+pub fn client_config(config: &UpstreamTlsConfig, http2: bool) -> Option<ClientConfig> {
+    if config.is_default() {
+        return None;
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier: Arc<dyn ServerCertVerifier> = match build_verifier(config, &provider) {
+        Ok(verifier) => Arc::new(verifier),
+        Err(e) => {
+            tracing::error!("[UpstreamTLS] {}，拒绝所有上游连接", e);
+            let schemes = provider.signature_verification_algorithms.supported_schemes();
+            Arc::new(RejectAll { reason: e, schemes })
+        }
+    };
+    let mut tls = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("ring 支持默认的 TLS 版本")
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth();
+    // 预配置的 TLS 不会自动设置 ALPN
+    tls.alpn_protocols = if http2 { vec![b"h2".to_vec(), b"http/1.1".to_vec()] } else { vec![b"http/1.1".to_vec()] };
+    Some(tls)
+}
+
+/// 为上游客户端应用 TLS 校验配置 (默认配置时保持 reqwest 的默认行为)
+pub fn apply(builder: reqwest::ClientBuilder, config: &UpstreamTlsConfig, http2: bool) -> reqwest::ClientBuilder {
+    match client_config(config, http2) {
+        Some(tls) => builder.use_preconfigured_tls(tls),
+        None => builder,
+    }
+}
+
+pub fn set_current(config: UpstreamTlsConfig) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = Some(config);
+    }
+}
+
+/// 当前生效的配置 (尚未写入时返回 None)
+pub fn current() -> Option<UpstreamTlsConfig> {
+    CURRENT.read().ok().and_then(|c| c.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fingerprint_formats() {
+        let hex = "AB".repeat(32);
+        assert_eq!(parse_fingerprint(&hex).unwrap(), [0xab; 32]);
+        let colons = ["ab"; 32].join(":");
+        assert_eq!(parse_fingerprint(&colons).unwrap(), [0xab; 32]);
+        assert!(parse_fingerprint("abcd").is_err());
+        assert!(parse_fingerprint(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config_fails_closed() {
+        assert!(client_config(&UpstreamTlsConfig::default(), true).is_none());
+
+        let config = UpstreamTlsConfig { pinned_sha256: vec!["not-a-pin".to_string()], ..Default::default() };
+        assert!(validate(&config).is_err());
+        // 配置无效时仍返回 rustls 配置 (拒绝所有证书)，而不是退回默认校验
+        let tls = client_config(&config, false).unwrap();
+        assert_eq!(tls.alpn_protocols, vec![b"http/1.1".to_vec()]);
+
+        let missing = UpstreamTlsConfig { ca_bundle_path: Some("/nonexistent/ca.pem".to_string()), ..Default::default() };
+        assert!(validate(&missing).is_err());
+    }
+}
@@ -1,6 +1,21 @@
 // Autostart 命令
 use tauri_plugin_autostart::ManagerExt;
 
+/// 开机自启时附带的参数
+pub const MINIMIZED_ARG: &str = "--minimized";
+/// 作为后台服务启动: 不显示窗口 (只有托盘) 并自动启动反代服务
+pub const HIDDEN_ARG: &str = "--hidden";
+
+/// 带 --hidden 参数或开启了「隐藏启动」设置
+pub fn launched_hidden() -> bool {
+    std::env::args().any(|arg| arg == HIDDEN_ARG)
+        || crate::modules::config::load_app_config().is_ok_and(|c| c.start_hidden)
+}
+
+pub fn launched_minimized() -> bool {
+    std::env::args().any(|arg| arg == MINIMIZED_ARG) || launched_hidden()
+}
+
 #[tauri::command]
 pub async fn toggle_auto_launch(
     app: tauri::AppHandle,
@@ -37,3 +52,9 @@ pub async fn is_auto_launch_enabled(app: tauri::AppHandle) -> Result<bool, Strin
     let manager = app.autolaunch();
     manager.is_enabled().map_err(|e| e.to_string())
 }
+
+/// 本次是否由开机自启拉起 (带 --minimized 参数) 或隐藏启动，此时窗口保持隐藏，只显示托盘
+#[tauri::command]
+pub fn is_launched_minimized() -> bool {
+    launched_minimized()
+}
@@ -54,6 +54,9 @@ pub async fn add_account(
     )
     .await;
 
+    // 7. 后台端到端验证新账号
+    modules::health::spawn_verification(app.clone(), vec![account.id.clone()]);
+
     Ok(account)
 }
 
@@ -92,6 +95,37 @@ pub async fn delete_accounts(
     Ok(())
 }
 
+/// 列出已删除 (归档) 的账号
+#[tauri::command]
+pub async fn list_archived_accounts() -> Result<Vec<modules::account_store::ArchivedAccount>, String> {
+    modules::account::list_archived_accounts()
+}
+
+/// 恢复已删除 (归档) 的账号
+#[tauri::command]
+pub async fn restore_archived_account(
+    app: tauri::AppHandle,
+    account_id: String,
+) -> Result<Account, String> {
+    let account = modules::account::restore_archived_account(&account_id)?;
+    modules::logger::log_info(&format!("已恢复归档账号: {}", account.email));
+
+    let proxy_state = app.state::<crate::commands::proxy::ProxyServiceState>();
+    if let Some(instance) = proxy_state.instance.read().await.as_ref() {
+        let _ = instance.token_manager.reload_account(&account_id).await;
+    }
+    crate::modules::tray::update_tray_menus(&app);
+    Ok(account)
+}
+
+/// 永久删除归档账号 (不可恢复)
+#[tauri::command]
+pub async fn purge_archived_accounts(account_ids: Vec<String>) -> Result<usize, String> {
+    let purged = modules::account::purge_archived_accounts(&account_ids)?;
+    modules::logger::log_info(&format!("已永久删除 {} 个归档账号", purged));
+    Ok(purged)
+}
+
 /// 重新排序账号列表
 /// 根据传入的账号ID数组顺序更新账号排列
 #[tauri::command]
@@ -149,6 +183,9 @@ async fn internal_refresh_account_quota(
         }
         Err(e) => {
             modules::logger::log_warn(&format!("自动刷新配额失败 ({}): {}", account.email, e));
+            if account.needs_reauth {
+                modules::account::emit_reauth_required(app, account);
+            }
             Err(e.to_string())
         }
     }
@@ -185,13 +222,63 @@ pub async fn fetch_account_quota(
 
 pub use modules::account::RefreshStats;
 
+/// 批量刷新所有账号的 Token，通过 account://token-refresh-progress 推送进度
+#[tauri::command]
+pub async fn refresh_all_tokens(
+    app: tauri::AppHandle,
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+) -> Result<modules::token_refresh::BulkRefreshReport, String> {
+    use tauri::Emitter;
+
+    let flagged_before: std::collections::HashSet<String> = modules::list_accounts()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|a| a.needs_reauth)
+        .map(|a| a.id)
+        .collect();
+
+    let report = modules::token_refresh::refresh_all_tokens(|progress| {
+        let _ = app.emit(modules::token_refresh::TOKEN_REFRESH_PROGRESS_EVENT, &progress);
+    })
+    .await?;
+
+    for account in modules::list_accounts().unwrap_or_default() {
+        if account.needs_reauth && !flagged_before.contains(&account.id) {
+            modules::account::emit_reauth_required(&app, &account);
+        }
+    }
+
+    // 同步到运行中的反代服务（如果已启动）
+    let instance_lock = proxy_state.instance.read().await;
+    if let Some(instance) = instance_lock.as_ref() {
+        let _ = instance.token_manager.reload_all_accounts().await;
+    }
+
+    Ok(report)
+}
+
 /// 刷新所有账号配额
 #[tauri::command]
 pub async fn refresh_all_quotas(
+    app: tauri::AppHandle,
     proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
 ) -> Result<RefreshStats, String> {
+    let flagged_before: std::collections::HashSet<String> = modules::list_accounts()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|a| a.needs_reauth)
+        .map(|a| a.id)
+        .collect();
+
     let stats = modules::account::refresh_all_quotas_logic().await?;
 
+    // 本次刷新中新发现 refresh_token 失效的账号，提示用户重新授权
+    for account in modules::list_accounts().unwrap_or_default() {
+        if account.needs_reauth && !flagged_before.contains(&account.id) {
+            modules::account::emit_reauth_required(&app, &account);
+        }
+    }
+
     // 同步到运行中的反代服务（如果已启动）
     let instance_lock = proxy_state.instance.read().await;
     if let Some(instance) = instance_lock.as_ref() {
@@ -236,8 +323,9 @@ pub async fn bind_device_profile_with_profile(
 #[tauri::command]
 pub async fn apply_device_profile(
     account_id: String,
+    close_ide: Option<bool>,
 ) -> Result<crate::models::DeviceProfile, String> {
-    modules::apply_device_profile(&account_id)
+    modules::ide::with_ide_closed(close_ide.unwrap_or(false), || modules::apply_device_profile(&account_id))
 }
 
 /// 恢复最早的 storage.json 备份（近似“原始”状态）
@@ -246,6 +334,27 @@ pub async fn restore_original_device() -> Result<String, String> {
     modules::restore_original_device()
 }
 
+/// 备份当前 storage.json 并为 IDE 生成全新的设备标识
+#[tauri::command]
+pub async fn reset_device_identity(close_ide: Option<bool>) -> Result<modules::device::DeviceReset, String> {
+    modules::ide::with_ide_closed(close_ide.unwrap_or(false), modules::device::reset_device_identity)
+}
+
+/// 列出 storage.json 备份
+#[tauri::command]
+pub async fn list_device_backups() -> Result<Vec<modules::device::StorageBackup>, String> {
+    modules::device::list_storage_backups()
+}
+
+/// 还原指定的 storage.json 备份
+#[tauri::command]
+pub async fn restore_device_backup(
+    file_name: String,
+    close_ide: Option<bool>,
+) -> Result<crate::models::DeviceProfile, String> {
+    modules::ide::with_ide_closed(close_ide.unwrap_or(false), || modules::device::restore_storage_backup(&file_name))
+}
+
 /// 列出指纹版本
 #[tauri::command]
 pub async fn list_device_versions(
@@ -286,7 +395,7 @@ pub async fn open_device_folder(app: tauri::AppHandle) -> Result<(), String> {
 /// 加载配置
 #[tauri::command]
 pub async fn load_config() -> Result<AppConfig, String> {
-    modules::load_app_config()
+    modules::load_app_config_async().await
 }
 
 /// 保存配置
@@ -296,31 +405,51 @@ pub async fn save_config(
     proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
     config: AppConfig,
 ) -> Result<(), String> {
-    modules::save_app_config(&config)?;
+    modules::hotkey::validate(config.proxy_toggle_shortcut.as_deref())?;
+    modules::save_app_config_async(config.clone()).await?;
+    apply_saved_config(&app, &proxy_state, &config).await;
+    Ok(())
+}
 
+/// 配置保存后通知托盘并热应用
+async fn apply_saved_config(
+    app: &tauri::AppHandle,
+    proxy_state: &crate::commands::proxy::ProxyServiceState,
+    config: &AppConfig,
+) -> crate::commands::proxy::ConfigReloadResult {
     // 通知托盘配置已更新
     let _ = app.emit("config://updated", ());
 
-    // 热更新正在运行的服务
-    let instance_lock = proxy_state.instance.read().await;
-    if let Some(instance) = instance_lock.as_ref() {
-        // 更新模型映射
-        instance.axum_server.update_mapping(&config.proxy).await;
-        // 更新上游代理
-        instance
-            .axum_server
-            .update_proxy(config.proxy.upstream_proxy.clone())
-            .await;
-        // 更新安全策略 (auth)
-        instance.axum_server.update_security(&config.proxy).await;
-        // 更新 z.ai 配置
-        instance.axum_server.update_zai(&config.proxy).await;
-        // 更新实验性配置
-        instance.axum_server.update_experimental(&config.proxy).await;
-        tracing::debug!("已同步热更新反代服务配置");
+    // 链路导出与指标推送配置
+    modules::otel::set_config(config.otel.clone());
+    modules::statsd::set_config(config.statsd.clone());
+
+    // 切换反代服务的全局快捷键
+    if let Err(e) = modules::hotkey::register_proxy_shortcut(app, config.proxy_toggle_shortcut.as_deref()) {
+        modules::logger::log_warn(&e);
     }
 
-    Ok(())
+    // 热更新正在运行的服务 (监听端口与地址需重启生效)
+    crate::commands::proxy::apply_proxy_config(proxy_state, &config.proxy).await
+}
+
+/// 配置变更历史 (新版本在前)
+#[tauri::command]
+pub async fn list_config_history() -> Result<Vec<modules::config_history::ConfigHistoryItem>, String> {
+    Ok(modules::config_history::list(&modules::load_app_config_async().await?))
+}
+
+/// 回滚到指定的历史版本并热应用 (回滚本身也记入历史，可再次回滚撤销)
+#[tauri::command]
+pub async fn rollback_config(
+    app: tauri::AppHandle,
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    id: String,
+) -> Result<crate::commands::proxy::ConfigReloadResult, String> {
+    let config = modules::config_history::get(&id)?;
+    modules::config::save_app_config_as(&config, modules::config_history::HistorySource::Rollback)?;
+    modules::logger::log_info(&format!("已回滚配置到历史版本 {}", id));
+    Ok(apply_saved_config(&app, &proxy_state, &config).await)
 }
 
 // --- OAuth 命令 ---
@@ -349,10 +478,11 @@ pub async fn start_oauth_login(app_handle: tauri::AppHandle) -> Result<Account,
     let user_info = modules::oauth::get_user_info(&token_res.access_token).await?;
     modules::logger::log_info(&format!("获取用户信息成功: {}", user_info.email));
 
-    // 4. 尝试获取项目ID
-    let project_id = crate::proxy::project_resolver::fetch_project_id(&token_res.access_token)
+    // 4. 尝试获取项目ID (账号没有项目时可按配置自动创建)
+    let resolved = crate::proxy::project_resolver::fetch_project_id(&token_res.access_token)
         .await
         .ok();
+    let project_id = resolved.as_ref().map(|(pid, _)| pid.clone());
 
     if let Some(ref pid) = project_id {
         modules::logger::log_info(&format!("获取项目ID成功: {}", pid));
@@ -377,6 +507,9 @@ pub async fn start_oauth_login(app_handle: tauri::AppHandle) -> Result<Account,
         user_info.get_display_name(),
         token_data,
     )?;
+    if let Some((pid, outcome)) = &resolved {
+        crate::proxy::project_resolver::record_outcome(&account.id, pid, outcome);
+    }
 
     // 7. 自动触发刷新额度
     let _ = internal_refresh_account_quota(&app_handle, &mut account).await;
@@ -387,6 +520,9 @@ pub async fn start_oauth_login(app_handle: tauri::AppHandle) -> Result<Account,
     )
     .await;
 
+    // 9. 后台端到端验证新账号
+    modules::health::spawn_verification(app_handle.clone(), vec![account.id.clone()]);
+
     Ok(account)
 }
 
@@ -415,10 +551,11 @@ pub async fn complete_oauth_login(app_handle: tauri::AppHandle) -> Result<Accoun
     let user_info = modules::oauth::get_user_info(&token_res.access_token).await?;
     modules::logger::log_info(&format!("获取用户信息成功: {}", user_info.email));
 
-    // 4. 尝试获取项目ID
-    let project_id = crate::proxy::project_resolver::fetch_project_id(&token_res.access_token)
+    // 4. 尝试获取项目ID (账号没有项目时可按配置自动创建)
+    let resolved = crate::proxy::project_resolver::fetch_project_id(&token_res.access_token)
         .await
         .ok();
+    let project_id = resolved.as_ref().map(|(pid, _)| pid.clone());
 
     if let Some(ref pid) = project_id {
         modules::logger::log_info(&format!("获取项目ID成功: {}", pid));
@@ -443,6 +580,9 @@ pub async fn complete_oauth_login(app_handle: tauri::AppHandle) -> Result<Accoun
         user_info.get_display_name(),
         token_data,
     )?;
+    if let Some((pid, outcome)) = &resolved {
+        crate::proxy::project_resolver::record_outcome(&account.id, pid, outcome);
+    }
 
     // 7. 自动触发刷新额度
     let _ = internal_refresh_account_quota(&app_handle, &mut account).await;
@@ -453,6 +593,51 @@ pub async fn complete_oauth_login(app_handle: tauri::AppHandle) -> Result<Accoun
     )
     .await;
 
+    // 9. 后台端到端验证新账号
+    modules::health::spawn_verification(app_handle.clone(), vec![account.id.clone()]);
+
+    Ok(account)
+}
+
+/// 重新授权 refresh_token 已失效的账号
+///
+/// 打开浏览器完成 OAuth，校验登录的邮箱与原账号一致后替换 Token 并重新启用账号
+#[tauri::command]
+pub async fn reauthorize_account(app_handle: tauri::AppHandle, account_id: String) -> Result<Account, String> {
+    let existing = modules::load_account(&account_id)?;
+    modules::logger::log_info(&format!("开始重新授权账号: {}", existing.email));
+
+    let token_res = modules::oauth_server::start_oauth_flow(app_handle.clone()).await?;
+    let refresh_token = token_res.refresh_token.ok_or_else(|| {
+        "未获取到 Refresh Token，请在 https://myaccount.google.com/permissions 撤销授权后重试".to_string()
+    })?;
+
+    let user_info = modules::oauth::get_user_info(&token_res.access_token).await?;
+    if !user_info.email.eq_ignore_ascii_case(&existing.email) {
+        return Err(format!(
+            "登录的账号 ({}) 与需要重新授权的账号 ({}) 不一致",
+            user_info.email, existing.email
+        ));
+    }
+
+    let token_data = TokenData::new(
+        token_res.access_token,
+        refresh_token,
+        token_res.expires_in,
+        Some(existing.email.clone()),
+        existing.token.project_id.clone(),
+        None,
+    );
+    // upsert 在 Token 变化时会自动清除 disabled / needs_reauth
+    let mut account = modules::upsert_account(existing.email.clone(), user_info.get_display_name().or(existing.name), token_data)?;
+    modules::logger::log_info(&format!("账号重新授权成功: {}", account.email));
+
+    let _ = internal_refresh_account_quota(&app_handle, &mut account).await;
+    let _ = crate::commands::proxy::reload_proxy_accounts(
+        app_handle.state::<crate::commands::proxy::ProxyServiceState>(),
+    )
+    .await;
+
     Ok(account)
 }
 
@@ -482,6 +667,283 @@ pub async fn import_v1_accounts(app: tauri::AppHandle) -> Result<Vec<Account>, S
     Ok(accounts)
 }
 
+/// 从 JSON/CSV 文件批量导入账号，返回逐行校验结果
+#[tauri::command]
+pub async fn import_accounts_batch(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<modules::batch_import::BatchImportReport, String> {
+    let report = modules::batch_import::import_accounts_from_file(&path).await?;
+    after_batch_import(&app, &report).await;
+    Ok(report)
+}
+
+/// 导入 gemini-cli 生成的 oauth_creds.json (path 为空时读取 ~/.gemini/oauth_creds.json)
+#[tauri::command]
+pub async fn import_gemini_cli_credentials(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<modules::batch_import::BatchImportReport, String> {
+    let report = modules::batch_import::import_gemini_cli_creds(path.as_deref()).await?;
+    after_batch_import(&app, &report).await;
+    Ok(report)
+}
+
+/// 从 CLIProxyAPI / gcli2api 等工具的凭据文件或凭据目录导入账号
+#[tauri::command]
+pub async fn import_tool_credentials(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<modules::batch_import::BatchImportReport, String> {
+    let report = modules::account_interop::import_credentials(&path).await?;
+    after_batch_import(&app, &report).await;
+    Ok(report)
+}
+
+/// 按 CLIProxyAPI / gcli2api 的格式导出账号凭据 (每个账号一个文件)，account_ids 为空时导出全部
+#[tauri::command]
+pub async fn export_tool_credentials(
+    dir: String,
+    format: modules::account_interop::InteropFormat,
+    account_ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    modules::account_interop::export_credentials(&dir, format, account_ids.as_deref())
+}
+
+async fn after_batch_import(app: &tauri::AppHandle, report: &modules::batch_import::BatchImportReport) {
+    modules::logger::log_info(&format!(
+        "批量导入完成: 新增 {}, 更新 {}, 跳过 {}, 失败 {}",
+        report.imported, report.updated, report.skipped, report.failed
+    ));
+
+    if report.imported + report.updated > 0 {
+        crate::modules::tray::update_tray_menus(app);
+        let _ = crate::commands::proxy::reload_proxy_accounts(
+            app.state::<crate::commands::proxy::ProxyServiceState>(),
+        )
+        .await;
+        // 后台刷新配额，避免阻塞导入结果返回
+        tauri::async_runtime::spawn(async {
+            let _ = modules::account::refresh_all_quotas_logic().await;
+        });
+    }
+
+    // 后台端到端验证新增的账号
+    let imported: Vec<&str> = report
+        .rows
+        .iter()
+        .filter(|r| r.status == modules::batch_import::ImportRowStatus::Imported)
+        .filter_map(|r| r.email.as_deref())
+        .collect();
+    if !imported.is_empty() {
+        let ids = modules::list_accounts()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|a| imported.iter().any(|e| e.eq_ignore_ascii_case(&a.email)))
+            .map(|a| a.id)
+            .collect();
+        modules::health::spawn_verification(app.clone(), ids);
+    }
+}
+
+/// 导出诊断包 (版本信息、脱敏配置、账号健康摘要与最近日志的 zip)，用于附在问题反馈中
+#[tauri::command]
+pub async fn export_diagnostics_bundle(path: String) -> Result<modules::diagnostics::DiagnosticsSummary, String> {
+    tokio::task::spawn_blocking(move || modules::diagnostics::export_bundle(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 列出崩溃报告 (新的在前)；pending_only 时只返回尚未查看的，用于启动时提示
+#[tauri::command]
+pub async fn list_crash_reports(pending_only: Option<bool>) -> Result<Vec<modules::crash::CrashReport>, String> {
+    modules::crash::list_crash_reports(pending_only.unwrap_or(false))
+}
+
+/// 标记崩溃报告已查看
+#[tauri::command]
+pub async fn acknowledge_crash_report(id: String) -> Result<(), String> {
+    modules::crash::acknowledge_crash_report(&id)
+}
+
+/// 生成预填崩溃信息的 GitHub Issue 链接
+#[tauri::command]
+pub async fn get_crash_report_issue_url(id: String) -> Result<String, String> {
+    modules::crash::crash_report_issue_url(&id)
+}
+
+/// 导出口令加密的账号备份
+#[tauri::command]
+pub async fn export_accounts_backup(path: String, passphrase: String) -> Result<usize, String> {
+    modules::backup::export_encrypted_backup(&path, &passphrase)
+}
+
+/// 从口令加密的账号备份恢复
+#[tauri::command]
+pub async fn restore_accounts_backup(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<modules::backup::RestoreReport, String> {
+    let report = modules::backup::restore_encrypted_backup(&path, &passphrase)?;
+
+    crate::modules::tray::update_tray_menus(&app);
+    let _ = crate::commands::proxy::reload_proxy_accounts(
+        app.state::<crate::commands::proxy::ProxyServiceState>(),
+    )
+    .await;
+
+    Ok(report)
+}
+
+/// 导出配置包 (应用配置与配置方案)；提供口令时凭证加密后一并导出，否则不导出凭证
+#[tauri::command]
+pub async fn export_config_bundle(
+    path: String,
+    passphrase: Option<String>,
+) -> Result<modules::config_bundle::ConfigExportSummary, String> {
+    tokio::task::spawn_blocking(move || modules::config_bundle::export_config(&path, passphrase.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 导入配置包并热应用到运行中的服务；配置包未带凭证的字段保留本机现有值
+#[tauri::command]
+pub async fn import_config_bundle(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<modules::config_bundle::ConfigImportReport, String> {
+    let (config, report) =
+        tokio::task::spawn_blocking(move || modules::config_bundle::import_config(&path, passphrase.as_deref()))
+            .await
+            .map_err(|e| e.to_string())??;
+    save_config(app.clone(), app.state::<crate::commands::proxy::ProxyServiceState>(), config).await?;
+    Ok(report)
+}
+
+/// 远程配置同步设置
+#[tauri::command]
+pub async fn get_config_sync_settings() -> Result<modules::config_sync::ConfigSyncSettings, String> {
+    modules::config_sync::load_settings()
+}
+
+/// 设置远程同步后端 (为空时关闭同步)
+#[tauri::command]
+pub async fn set_config_sync_backend(
+    backend: Option<modules::config_sync::SyncBackend>,
+) -> Result<modules::config_sync::ConfigSyncSettings, String> {
+    modules::config_sync::set_backend(backend)
+}
+
+/// 与远程后端同步配置；冲突时不做改动，可指定 force 为 push / pull 选择保留哪一边
+#[tauri::command]
+pub async fn sync_config(
+    app: tauri::AppHandle,
+    force: Option<modules::config_sync::SyncDirection>,
+) -> Result<modules::config_sync::ConfigSyncReport, String> {
+    let (report, pulled) = modules::config_sync::sync(force).await?;
+    if let Some(config) = pulled {
+        save_config(app.clone(), app.state::<crate::commands::proxy::ProxyServiceState>(), config).await?;
+    }
+    Ok(report)
+}
+
+/// 将单个账号导出为口令加密的分享串 (附二维码)
+#[tauri::command]
+pub async fn export_account_share(
+    account_id: String,
+    passphrase: String,
+) -> Result<modules::account_share::AccountShare, String> {
+    modules::account_share::export_account_share(&account_id, &passphrase)
+}
+
+/// 导入口令加密的账号分享串
+#[tauri::command]
+pub async fn import_account_share(
+    app: tauri::AppHandle,
+    share: String,
+    passphrase: String,
+) -> Result<Account, String> {
+    let account = modules::account_share::import_account_share(&share, &passphrase).await?;
+
+    crate::modules::tray::update_tray_menus(&app);
+    let _ = crate::commands::proxy::reload_proxy_accounts(
+        app.state::<crate::commands::proxy::ProxyServiceState>(),
+    )
+    .await;
+    modules::health::spawn_verification(app.clone(), vec![account.id.clone()]);
+
+    Ok(account)
+}
+
+/// 取出待处理的深度链接操作 (应用由链接启动或收到新链接时调用)
+#[tauri::command]
+pub async fn take_pending_deep_link() -> Result<Option<modules::deep_link::DeepLinkAction>, String> {
+    Ok(modules::deep_link::take_pending())
+}
+
+/// 探测 Antigravity IDE 是否安装、版本、运行状态与数据目录
+#[tauri::command]
+pub async fn detect_ide_installation() -> Result<modules::ide::IdeInstallation, String> {
+    tokio::task::spawn_blocking(modules::ide::detect)
+        .await
+        .map_err(|e| format!("探测 IDE 失败: {}", e))
+}
+
+/// 正常关闭正在运行的 Antigravity IDE，返回 IDE 原本是否在运行
+#[tauri::command]
+pub async fn close_ide() -> Result<bool, String> {
+    tokio::task::spawn_blocking(|| modules::ide::ensure_closed(true))
+        .await
+        .map_err(|e| format!("关闭 IDE 失败: {}", e))?
+}
+
+/// 启动 Antigravity IDE (沿用配置或进程中的启动参数)
+#[tauri::command]
+pub async fn launch_ide() -> Result<(), String> {
+    modules::process::start_antigravity()
+}
+
+/// 列出 IDE 配置备份 (新到旧)
+#[tauri::command]
+pub async fn list_ide_backups() -> Result<Vec<modules::ide_backup::IdeBackup>, String> {
+    modules::ide_backup::list_backups()
+}
+
+/// 立即备份 IDE 配置
+#[tauri::command]
+pub async fn create_ide_backup() -> Result<modules::ide_backup::IdeBackup, String> {
+    tokio::task::spawn_blocking(|| modules::ide_backup::create_backup(modules::ide_backup::BackupReason::Manual))
+        .await
+        .map_err(|e| format!("备份 IDE 配置失败: {}", e))?
+}
+
+/// 恢复 IDE 配置备份 (IDE 运行中时需 close_ide 同意关闭)
+#[tauri::command]
+pub async fn restore_ide_backup(
+    id: String,
+    close_ide: Option<bool>,
+) -> Result<modules::ide_backup::IdeBackup, String> {
+    tokio::task::spawn_blocking(move || {
+        modules::ide::with_ide_closed(close_ide.unwrap_or(false), || modules::ide_backup::restore_backup(&id))
+    })
+    .await
+    .map_err(|e| format!("恢复 IDE 配置失败: {}", e))?
+}
+
+/// 删除 IDE 配置备份
+#[tauri::command]
+pub async fn delete_ide_backup(id: String) -> Result<(), String> {
+    modules::ide_backup::delete_backup(&id)
+}
+
+/// 探测本机 Antigravity IDE 的登录状态，供导入页提示一键导入
+#[tauri::command]
+pub async fn detect_ide_credentials() -> Result<modules::migration::IdeCredentialStatus, String> {
+    modules::migration::detect_ide_credentials()
+}
+
 #[tauri::command]
 pub async fn import_from_db(app: tauri::AppHandle) -> Result<Account, String> {
     // 同步函数包装为 async
@@ -497,6 +959,9 @@ pub async fn import_from_db(app: tauri::AppHandle) -> Result<Account, String> {
     // 刷新托盘图标展示
     crate::modules::tray::update_tray_menus(&app);
 
+    // 后台端到端验证导入的账号
+    modules::health::spawn_verification(app.clone(), vec![account.id.clone()]);
+
     Ok(account)
 }
 
@@ -516,6 +981,9 @@ pub async fn import_custom_db(app: tauri::AppHandle, path: String) -> Result<Acc
     // 刷新托盘图标展示
     crate::modules::tray::update_tray_menus(&app);
 
+    // 后台端到端验证导入的账号
+    modules::health::spawn_verification(app.clone(), vec![account.id.clone()]);
+
     Ok(account)
 }
 
@@ -565,6 +1033,26 @@ pub async fn clear_log_cache() -> Result<(), String> {
     modules::logger::clear_logs()
 }
 
+/// 查询应用日志 (按级别 / 模块 / 关键字过滤，分页，新的在前)
+#[tauri::command]
+pub async fn query_app_logs(query: modules::log_viewer::LogQuery) -> Result<modules::log_viewer::LogPage, String> {
+    tokio::task::spawn_blocking(move || modules::log_viewer::query_logs(&query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 获取当前日志过滤规则
+#[tauri::command]
+pub async fn get_log_filter() -> Result<String, String> {
+    Ok(modules::logger::get_log_filter())
+}
+
+/// 运行时调整日志过滤规则 (如 `info,proxy::mappers=debug`)，返回实际生效的规则
+#[tauri::command]
+pub async fn set_log_filter(filter: String) -> Result<String, String> {
+    modules::logger::set_log_filter(&filter)
+}
+
 /// 打开数据目录
 #[tauri::command]
 pub async fn open_data_folder() -> Result<(), String> {
@@ -610,6 +1098,31 @@ pub async fn show_main_window(window: tauri::Window) -> Result<(), String> {
     window.show().map_err(|e| e.to_string())
 }
 
+/// 关闭方式为「每次询问」时，通知前端弹出确认框的事件
+pub const CLOSE_REQUESTED_EVENT: &str = "window://close-requested";
+
+/// 处理关闭窗口确认框的选择 (`remember` 为 true 时写入配置，下次不再询问)
+#[tauri::command]
+pub async fn confirm_window_close(
+    window: tauri::Window,
+    action: crate::models::CloseAction,
+    remember: bool,
+) -> Result<(), String> {
+    use crate::models::CloseAction;
+
+    if remember && action != CloseAction::Ask {
+        let mut config = modules::config::load_app_config()?;
+        config.close_action = action;
+        modules::config::save_app_config(&config)?;
+    }
+
+    match action {
+        CloseAction::Quit => window.app_handle().exit(0),
+        _ => modules::tray::hide_to_tray(&window),
+    }
+    Ok(())
+}
+
 /// 获取 Antigravity 可执行文件路径
 #[tauri::command]
 pub async fn get_antigravity_path(bypass_config: Option<bool>) -> Result<String, String> {
@@ -652,20 +1165,80 @@ pub async fn check_for_updates() -> Result<UpdateInfo, String> {
 
 #[tauri::command]
 pub async fn should_check_updates() -> Result<bool, String> {
-    let settings = crate::modules::update_checker::load_update_settings()?;
+    let settings = crate::utils::blocking::run(crate::modules::update_checker::load_update_settings).await?;
     Ok(crate::modules::update_checker::should_check_for_updates(&settings))
 }
 
 #[tauri::command]
 pub async fn update_last_check_time() -> Result<(), String> {
-    crate::modules::update_checker::update_last_check_time()
+    crate::utils::blocking::run(crate::modules::update_checker::update_last_check_time).await
+}
+
+/// 跳过指定版本 (不再自动提示该版本，更新的版本仍会提示)
+#[tauri::command]
+pub async fn skip_update_version(version: String) -> Result<(), String> {
+    crate::utils::blocking::run(move || crate::modules::update_checker::skip_version(&version)).await
+}
+
+
+/// 下载当前平台的新版本安装包 (进度通过 update://download-progress 推送)
+#[tauri::command]
+pub async fn download_update(app: tauri::AppHandle) -> Result<crate::modules::update_installer::DownloadedUpdate, String> {
+    crate::modules::update_installer::download_update(&app).await
+}
+
+/// 已下载、等待安装的更新
+#[tauri::command]
+pub async fn get_downloaded_update() -> Result<Option<crate::modules::update_installer::DownloadedUpdate>, String> {
+    Ok(crate::modules::update_installer::downloaded_update())
+}
+
+/// 安装已下载的更新 (前端确认后调用)；需要时退出或重启应用
+#[tauri::command]
+pub async fn install_update(app: tauri::AppHandle) -> Result<crate::modules::update_installer::InstallOutcome, String> {
+    let outcome = tokio::task::spawn_blocking(crate::modules::update_installer::install_downloaded)
+        .await
+        .map_err(|e| e.to_string())??;
+    finish_install(&app, &outcome);
+    Ok(outcome)
+}
+
+/// 可回滚到的旧版本 (没有保留旧版本时为 None)
+#[tauri::command]
+pub async fn get_rollback_version() -> Result<Option<crate::modules::update_installer::KeptPackage>, String> {
+    Ok(crate::modules::update_installer::rollback_target())
+}
+
+/// 回滚到更新前的版本 (前端确认后调用)；需要时退出或重启应用
+#[tauri::command]
+pub async fn rollback_update(app: tauri::AppHandle) -> Result<crate::modules::update_installer::InstallOutcome, String> {
+    let outcome = tokio::task::spawn_blocking(crate::modules::update_installer::rollback)
+        .await
+        .map_err(|e| e.to_string())??;
+    finish_install(&app, &outcome);
+    Ok(outcome)
 }
 
+fn finish_install(app: &tauri::AppHandle, outcome: &crate::modules::update_installer::InstallOutcome) {
+    if outcome.exit_required || outcome.restart_required {
+        // 先把结果返回给前端，稍后再退出 / 重启
+        let handle = app.clone();
+        let restart = outcome.restart_required;
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+            if restart {
+                handle.restart();
+            } else {
+                handle.exit(0);
+            }
+        });
+    }
+}
 
 /// 获取更新设置
 #[tauri::command]
 pub async fn get_update_settings() -> Result<crate::modules::update_checker::UpdateSettings, String> {
-    crate::modules::update_checker::load_update_settings()
+    crate::utils::blocking::run(crate::modules::update_checker::load_update_settings).await
 }
 
 /// 保存更新设置
@@ -673,7 +1246,61 @@ pub async fn get_update_settings() -> Result<crate::modules::update_checker::Upd
 pub async fn save_update_settings(
     settings: crate::modules::update_checker::UpdateSettings,
 ) -> Result<(), String> {
-    crate::modules::update_checker::save_update_settings(&settings)
+    crate::utils::blocking::run(move || crate::modules::update_checker::save_update_settings(&settings)).await
+}
+
+/// 电源与网络状态，以及后台任务当前是否因此暂停
+#[tauri::command]
+pub async fn get_power_status() -> Result<modules::power::PowerStatus, String> {
+    tokio::task::spawn_blocking(modules::power::status)
+        .await
+        .map_err(|e| format!("获取电源状态失败: {}", e))
+}
+
+/// 向 Webhook 发送测试事件，返回 HTTP 状态码
+#[tauri::command]
+pub async fn test_webhook(webhook: crate::models::WebhookConfig) -> Result<u16, String> {
+    modules::webhooks::send_test(&webhook).await
+}
+
+/// 向外部通知渠道发送测试消息
+#[tauri::command]
+pub async fn test_notification_channel(channel: crate::models::NotificationChannel) -> Result<(), String> {
+    modules::notification_channels::send_test(&channel).await
+}
+
+/// 列出定时任务及下一次运行时间
+#[tauri::command]
+pub async fn list_scheduled_tasks() -> Result<Vec<modules::task_scheduler::TaskStatus>, String> {
+    Ok(modules::task_scheduler::list())
+}
+
+/// 修改定时任务的启用状态与计划 (cron 表达式或 @every 间隔)
+#[tauri::command]
+pub async fn update_scheduled_task(
+    kind: modules::task_scheduler::TaskKind,
+    enabled: bool,
+    schedule: String,
+) -> Result<modules::task_scheduler::ScheduledTask, String> {
+    modules::task_scheduler::update(kind, enabled, &schedule)
+}
+
+/// 立即运行一次定时任务
+#[tauri::command]
+pub async fn run_scheduled_task(
+    app: tauri::AppHandle,
+    kind: modules::task_scheduler::TaskKind,
+) -> Result<modules::task_scheduler::TaskRun, String> {
+    modules::task_scheduler::run(kind, Some(app), true).await
+}
+
+/// 定时任务运行历史 (新到旧)
+#[tauri::command]
+pub async fn get_scheduled_task_history(
+    kind: Option<modules::task_scheduler::TaskKind>,
+    limit: Option<usize>,
+) -> Result<Vec<modules::task_scheduler::TaskRun>, String> {
+    Ok(modules::task_scheduler::history(kind, limit.unwrap_or(50)))
 }
 
 
@@ -693,55 +1320,259 @@ pub async fn toggle_proxy_status(
         if enable { "启用" } else { "禁用" }
     ));
 
-    // 1. 读取账号文件
+    // 1. 在单个事务中更新 proxy_disabled 字段
     let data_dir = modules::account::get_data_dir()?;
-    let account_path = data_dir.join("accounts").join(format!("{}.json", account_id));
-
-    if !account_path.exists() {
-        return Err(format!("账号文件不存在: {}", account_id));
-    }
-
-    let content = std::fs::read_to_string(&account_path)
-        .map_err(|e| format!("读取账号文件失败: {}", e))?;
-
-    let mut account_json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("解析账号文件失败: {}", e))?;
+    let reason = reason.unwrap_or_else(|| "用户手动禁用".to_string());
+    modules::account_store::update_account(&data_dir, &account_id, |account_json| {
+        if enable {
+            // 启用反代
+            account_json["proxy_disabled"] = serde_json::Value::Bool(false);
+            account_json["proxy_disabled_reason"] = serde_json::Value::Null;
+            account_json["proxy_disabled_at"] = serde_json::Value::Null;
+        } else {
+            // 禁用反代
+            let now = chrono::Utc::now().timestamp();
+            account_json["proxy_disabled"] = serde_json::Value::Bool(true);
+            account_json["proxy_disabled_at"] = serde_json::Value::Number(now.into());
+            account_json["proxy_disabled_reason"] = serde_json::Value::String(reason.clone());
+        }
+    })?;
 
-    // 2. 更新 proxy_disabled 字段
     if enable {
-        // 启用反代
-        account_json["proxy_disabled"] = serde_json::Value::Bool(false);
-        account_json["proxy_disabled_reason"] = serde_json::Value::Null;
-        account_json["proxy_disabled_at"] = serde_json::Value::Null;
+        modules::account_store::record_event(&data_dir, &account_id, "reenabled", Some("proxy enabled"));
     } else {
-        // 禁用反代
-        let now = chrono::Utc::now().timestamp();
-        account_json["proxy_disabled"] = serde_json::Value::Bool(true);
-        account_json["proxy_disabled_at"] = serde_json::Value::Number(now.into());
-        account_json["proxy_disabled_reason"] = serde_json::Value::String(
-            reason.unwrap_or_else(|| "用户手动禁用".to_string())
-        );
+        modules::account_store::record_event(&data_dir, &account_id, "proxy_disabled", Some(&reason));
     }
 
-    // 3. 保存到磁盘
-    std::fs::write(&account_path, serde_json::to_string_pretty(&account_json).unwrap())
-        .map_err(|e| format!("写入账号文件失败: {}", e))?;
-
     modules::logger::log_info(&format!(
         "账号反代状态已更新: {} ({})",
         account_id,
         if enable { "已启用" } else { "已禁用" }
     ));
 
-    // 4. 如果反代服务正在运行,重新加载账号池
-    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    // 2. 如果反代服务正在运行,只增删该账号,不影响其他账号的轮换与会话绑定
+    if let Some(instance) = proxy_state.instance.read().await.as_ref() {
+        if enable {
+            if let Err(e) = instance.token_manager.reload_account(&account_id).await {
+                modules::logger::log_warn(&format!("启用账号后加载到反代账号池失败: {}", e));
+            }
+        } else {
+            instance.token_manager.remove_account(&account_id).await;
+        }
+    }
 
-    // 5. 更新托盘菜单
+    // 3. 更新托盘菜单
     crate::modules::tray::update_tray_menus(&app);
 
     Ok(())
 }
 
+/// 解除账号隔离，重新加入反代账号池
+#[tauri::command]
+pub async fn release_account_quarantine(
+    app: tauri::AppHandle,
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+) -> Result<Account, String> {
+    let account = modules::account::release_quarantine(&account_id)?;
+    modules::logger::log_info(&format!("已解除账号隔离: {}", account.email));
+
+    if let Some(instance) = proxy_state.instance.read().await.as_ref() {
+        let _ = instance.token_manager.reload_account(&account_id).await;
+    }
+    crate::modules::tray::update_tray_menus(&app);
+    Ok(account)
+}
+
+/// 清除账号的 project_id 缓存并重新解析 (用于 project_id 失效后的恢复)
+#[tauri::command]
+pub async fn resolve_account_project_id(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+) -> Result<String, String> {
+    let project_id = modules::account::re_resolve_project_id(&account_id).await?;
+    if let Some(instance) = proxy_state.instance.read().await.as_ref() {
+        let _ = instance.token_manager.reload_account(&account_id).await;
+    }
+    Ok(project_id)
+}
+
+/// 发现账号可访问的全部项目 (供多项目选择策略使用)
+#[tauri::command]
+pub async fn discover_account_projects(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+) -> Result<Vec<String>, String> {
+    let projects = modules::account::discover_projects(&account_id).await?;
+    if let Some(instance) = proxy_state.instance.read().await.as_ref() {
+        let _ = instance.token_manager.reload_account(&account_id).await;
+    }
+    Ok(projects)
+}
+
+/// 按账号当前使用的项目查询上游配额，最接近上限的排在最前
+///
+/// `threshold` 为判定"接近上限"的剩余百分比 (默认 20)
+#[tauri::command]
+pub async fn get_project_quotas(
+    threshold: Option<i32>,
+) -> Result<Vec<modules::project_quota::ProjectQuotaStatus>, String> {
+    modules::project_quota::inspect_all(threshold).await
+}
+
+/// 获取所有账号 access_token 的剩余有效期与刷新状态
+#[tauri::command]
+pub async fn get_token_statuses() -> Result<Vec<modules::token_status::TokenStatus>, String> {
+    modules::token_status::get_token_statuses()
+}
+
+/// 查询账号事件 (新增、刷新、限流、禁用、隔离、恢复、验证、创建项目、删除)，最新的在前
+#[tauri::command]
+pub async fn list_account_events(
+    account_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<modules::account_store::AccountEvent>, String> {
+    let data_dir = modules::account::get_data_dir()?;
+    modules::account_store::list_events(&data_dir, account_id.as_deref(), limit.unwrap_or(100))
+}
+
+/// 列出配置档案
+#[tauri::command]
+pub async fn list_profiles() -> Result<Vec<modules::profile::ProfileInfo>, String> {
+    modules::profile::list_profiles(&modules::account::get_root_data_dir()?)
+}
+
+/// 新建配置档案 (独立的账号与配置)
+#[tauri::command]
+pub async fn create_profile(name: String) -> Result<(), String> {
+    modules::profile::create_profile(&modules::account::get_root_data_dir()?, name.trim())
+}
+
+/// 删除配置档案及其全部数据
+#[tauri::command]
+pub async fn delete_profile(name: String) -> Result<(), String> {
+    modules::profile::delete_profile(&modules::account::get_root_data_dir()?, &name)
+}
+
+/// 切换配置档案
+///
+/// 反代服务正在运行时会先停止，切换后用新档案的配置与账号重新启动
+#[tauri::command]
+pub async fn switch_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let root = modules::account::get_root_data_dir()?;
+    if modules::profile::active_profile(&root) == name {
+        return Ok(());
+    }
+
+    let proxy_state = app.state::<crate::commands::proxy::ProxyServiceState>();
+    let was_running = proxy_state.instance.read().await.is_some();
+    if was_running {
+        crate::commands::proxy::stop_proxy_service(proxy_state).await?;
+    }
+
+    modules::profile::set_active_profile(&root, &name)?;
+    modules::logger::log_info(&format!("已切换配置档案: {}", name));
+
+    if was_running {
+        let config = modules::config::load_app_config()?;
+        if let Err(e) = crate::commands::proxy::start_proxy_service(
+            config.proxy,
+            app.state::<crate::commands::proxy::ProxyServiceState>(),
+            app.clone(),
+        )
+        .await
+        {
+            modules::logger::log_warn(&format!("切换档案后重启反代服务失败: {}", e));
+        }
+    }
+
+    crate::modules::tray::update_tray_menus(&app);
+    let _ = app.emit("profile://switched", &name);
+    Ok(())
+}
+
+/// 设置账号的反代调度优先级 (数值越大越优先)
+#[tauri::command]
+pub async fn set_account_priority(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+    priority: i32,
+) -> Result<Account, String> {
+    let account = modules::account::set_account_priority(&account_id, priority)?;
+    modules::logger::log_info(&format!("账号调度优先级已更新: {} -> {}", account.email, priority));
+
+    // 重新加载账号池使新优先级生效
+    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    Ok(account)
+}
+
+/// 设置账号的反代可用时段 (本地时间 HH:MM，为空表示全天可用)
+#[tauri::command]
+pub async fn set_account_usage_windows(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+    windows: Vec<crate::models::UsageWindow>,
+) -> Result<Account, String> {
+    let account = modules::account::set_account_usage_windows(&account_id, windows)?;
+    modules::logger::log_info(&format!("账号可用时段已更新: {} ({} 个时段)", account.email, account.usage_windows.len()));
+
+    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    Ok(account)
+}
+
+/// 设置账号的独立出口代理 (http/https/socks5)，传空表示使用全局上游代理
+#[tauri::command]
+pub async fn set_account_egress_proxy(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+    egress_proxy: Option<String>,
+) -> Result<Account, String> {
+    if let Some(url) = egress_proxy.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        crate::proxy::upstream::client::validate_egress_proxy(url)?;
+    }
+    let account = modules::account::set_account_egress_proxy(&account_id, egress_proxy)?;
+    modules::logger::log_info(&format!(
+        "账号出口代理已更新: {} -> {}",
+        account.email,
+        account.egress_proxy.as_deref().unwrap_or("全局")
+    ));
+
+    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    Ok(account)
+}
+
+/// 设置账号固定使用的 project_id，传空表示恢复自动解析
+#[tauri::command]
+pub async fn set_account_project_override(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+    project_id: Option<String>,
+) -> Result<Account, String> {
+    let account = modules::account::set_account_project_override(&account_id, project_id)?;
+    modules::logger::log_info(&format!(
+        "账号 project_id 已更新: {} -> {}",
+        account.email,
+        account.project_id_override.as_deref().unwrap_or("自动解析")
+    ));
+
+    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    Ok(account)
+}
+
+/// 设置账号标签
+#[tauri::command]
+pub async fn set_account_tags(
+    proxy_state: tauri::State<'_, crate::commands::proxy::ProxyServiceState>,
+    account_id: String,
+    tags: Vec<String>,
+) -> Result<Account, String> {
+    let account = modules::account::set_account_tags(&account_id, tags)?;
+    modules::logger::log_info(&format!("账号标签已更新: {} -> {:?}", account.email, account.tags));
+
+    let _ = crate::commands::proxy::reload_proxy_accounts(proxy_state).await;
+    Ok(account)
+}
+
 /// 预热所有可用账号
 #[tauri::command]
 pub async fn warm_up_all_accounts() -> Result<String, String> {
@@ -753,3 +1584,15 @@ pub async fn warm_up_all_accounts() -> Result<String, String> {
 pub async fn warm_up_account(account_id: String) -> Result<String, String> {
     modules::quota::warm_up_account(&account_id).await
 }
+
+/// 检查指定账号健康状态 (Token 刷新 + 最小 generateContent 请求)
+#[tauri::command]
+pub async fn check_account_health(account_id: String) -> Result<modules::health::AccountHealthReport, String> {
+    modules::health::check_account_by_id(&account_id).await
+}
+
+/// 并发检查所有账号健康状态
+#[tauri::command]
+pub async fn check_all_accounts_health() -> Result<Vec<modules::health::AccountHealthReport>, String> {
+    modules::health::check_all_accounts().await
+}
@@ -5,6 +5,14 @@ use serde::{Serialize, Deserialize};
 use crate::proxy::{ProxyConfig, TokenManager};
 use tokio::time::Duration;
 use crate::proxy::monitor::{ProxyMonitor, ProxyRequestLog, ProxyStats};
+use crate::modules::i18n::tr;
+
+/// 配置文件检测间隔
+const CONFIG_WATCH_INTERVAL_SECS: u64 = 2;
+/// 检查服务器任务是否意外退出的间隔 (秒)
+const SERVER_WATCHDOG_INTERVAL_SECS: u64 = 5;
+/// 配置文件被外部修改并已热重载
+pub const CONFIG_RELOADED_EVENT: &str = "proxy://config-reloaded";
 
 
 /// 反代服务状态
@@ -28,6 +36,8 @@ pub struct ProxyServiceInstance {
     pub token_manager: Arc<TokenManager>,
     pub axum_server: crate::proxy::AxumServer,
     pub server_handle: tokio::task::JoinHandle<()>,
+    /// 运行时长统计的会话 ID (记录失败时为 None)
+    pub uptime_session: Option<i64>,
 }
 
 impl ProxyServiceState {
@@ -50,8 +60,17 @@ pub async fn start_proxy_service(
     
     // 防止重复启动
     if instance_lock.is_some() {
-        return Err("服务已在运行中".to_string());
+        return Err(crate::modules::i18n::tr("backend.errors.proxy_already_running", &[]));
+    }
+
+    // 环境变量覆盖与凭证引用只作用于运行中的服务，保存到配置文件的仍是传入的配置
+    let requested = config;
+    let config = requested.with_env_overrides().resolve_secret_refs()?;
+    for o in crate::proxy::env_overrides::active_overrides(&requested) {
+        tracing::info!("[EnvOverride] {} -> {} = {}", o.var, o.field, o.value);
     }
+    // 安全模式: 未开启鉴权时不监听非本机地址
+    config.check_bind_safety()?;
 
     // Ensure monitor exists
     {
@@ -62,6 +81,12 @@ pub async fn start_proxy_service(
         // Sync enabled state from config
         if let Some(monitor) = monitor_lock.as_ref() {
             monitor.set_enabled(config.enable_logging);
+            monitor.alerts.set_config(config.alerts.clone());
+            monitor.anomaly.set_config(config.anomaly.clone());
+            monitor.inspector.set_config(config.inspector.clone());
+            monitor.transcripts.set_config(config.transcripts.clone());
+            monitor.set_memory_limits(&config.memory_limits);
+            monitor.hooks.set_config(config.hooks.clone());
         }
     }
     
@@ -69,13 +94,22 @@ pub async fn start_proxy_service(
     
     // 2. 初始化 Token 管理器
     let app_data_dir = crate::modules::account::get_data_dir()?;
-    // Ensure accounts dir exists even if the user will only use non-Google providers (e.g. z.ai).
-    let _ = crate::modules::account::get_accounts_dir()?;
     let accounts_dir = app_data_dir.clone();
     
-    let token_manager = Arc::new(TokenManager::new(accounts_dir));
+    let token_manager = Arc::new(TokenManager::new(accounts_dir).with_app_handle(app_handle.clone()));
+    // 冷却结束的账号自动恢复,并通知前端
+    token_manager.start_cooldown_watcher();
+    // 账号存储被外部修改时 (脚本或另一实例) 实时同步
+    token_manager.start_store_watcher();
+    // 负载仪表 (进行中 / 排队 / 各账号并发) 变化时推送给前端
+    crate::proxy::load::start_emitter(Arc::downgrade(&token_manager), monitor.in_flight.clone(), app_handle.clone());
+    // 配置文件被外部修改时热重载
+    start_config_watcher(app_handle.clone(), Arc::downgrade(&token_manager));
+    // 服务器任务意外退出 (panic) 时自动重启
+    start_server_watchdog(app_handle.clone(), Arc::downgrade(&token_manager));
     // 同步 UI 传递的调度配置
     token_manager.update_sticky_config(config.scheduling.clone()).await;
+    token_manager.update_account_pools(&config.account_pools).await;
     
     // 3. 加载账号
     let active_accounts = token_manager.load_accounts().await
@@ -85,9 +119,11 @@ pub async fn start_proxy_service(
         let zai_enabled = config.zai.enabled
             && !matches!(config.zai.dispatch_mode, crate::proxy::ZaiDispatchMode::Off);
         if !zai_enabled {
-            return Err("没有可用账号，请先添加账号".to_string());
+            return Err(crate::modules::i18n::tr("backend.errors.no_accounts", &[]));
         }
     }
+    // 即将过期的 Token 在后台并发刷新，不阻塞服务启动
+    token_manager.start_startup_refresh();
     
     // 启动 Axum 服务器
     let (axum_server, server_handle) =
@@ -107,6 +143,20 @@ pub async fn start_proxy_service(
             Ok((server, handle)) => (server, handle),
             Err(e) => return Err(format!("启动 Axum 服务器失败: {}", e)),
         };
+    axum_server.update_upstream_client(&config);
+    axum_server.update_providers(&config).await;
+
+    // 记录启动时间并开始可用性采样
+    let uptime_session = match crate::modules::uptime::record_start() {
+        Ok(id) => {
+            crate::modules::uptime::start_sampler(id, Arc::downgrade(&token_manager), axum_server.upstream_client());
+            Some(id)
+        }
+        Err(e) => {
+            tracing::warn!("记录反代启动时间失败: {}", e);
+            None
+        }
+    };
     
     // 创建服务实例
     let instance = ProxyServiceInstance {
@@ -114,22 +164,34 @@ pub async fn start_proxy_service(
         token_manager: token_manager.clone(), // Clone for ProxyServiceInstance
         axum_server,
         server_handle,
+        uptime_session,
     };
     
     *instance_lock = Some(instance);
+    monitor.warn_if_exposed(&config);
     
 
     // 保存配置到全局 AppConfig
-    let mut app_config = crate::modules::config::load_app_config().map_err(|e| e)?;
-    app_config.proxy = config.clone();
-    crate::modules::config::save_app_config(&app_config).map_err(|e| e)?;
+    let mut app_config = crate::modules::config::load_app_config_async().await?;
+    app_config.proxy = requested;
+    crate::modules::config::save_app_config_async(app_config).await?;
+    drop(instance_lock);
+
+    // 自动建立远程访问隧道 (失败不影响反代服务)
+    if config.tunnel.auto_start {
+        if let Err(e) = crate::modules::tunnel::start(&config.tunnel, config.port, &config.auth_mode, &config.api_key).await {
+            tracing::warn!("[Tunnel] 自动建立隧道失败: {}", e);
+        }
+    }
     
-    Ok(ProxyStatus {
+    let status = ProxyStatus {
         running: true,
         port: config.port,
         base_url: format!("http://127.0.0.1:{}", config.port),
         active_accounts,
-    })
+    };
+    crate::modules::webhooks::fire(crate::models::WebhookEvent::ProxyStarted, &status);
+    Ok(status)
 }
 
 /// 停止反代服务
@@ -140,19 +202,543 @@ pub async fn stop_proxy_service(
     let mut instance_lock = state.instance.write().await;
     
     if instance_lock.is_none() {
-        return Err("服务未运行".to_string());
+        return Err(crate::modules::i18n::tr("backend.errors.proxy_not_running", &[]));
     }
     
     // 停止 Axum 服务器
     if let Some(instance) = instance_lock.take() {
+        crate::modules::tunnel::stop();
         instance.axum_server.stop();
         // 等待服务器任务完成
         instance.server_handle.await.ok();
+        if let Some(id) = instance.uptime_session {
+            if let Err(e) = crate::modules::uptime::record_stop(id) {
+                tracing::warn!("记录反代停止时间失败: {}", e);
+            }
+        }
+        crate::modules::webhooks::fire(crate::models::WebhookEvent::ProxyStopped, serde_json::json!({ "port": instance.config.port }));
     }
     
     Ok(())
 }
 
+/// 配置热重载结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReloadResult {
+    /// 服务正在运行且已应用新配置
+    pub applied: bool,
+    /// 已变更但需要重启服务才能生效的字段
+    pub restart_required: Vec<String>,
+}
+
+/// 把配置应用到运行中的服务 (模型映射、账号池、调度、安全策略、上游客户端等)
+///
+/// 不重启 Axum、不中断进行中的流；监听端口与地址需重启服务才能生效
+pub(crate) async fn apply_proxy_config(state: &ProxyServiceState, config: &ProxyConfig) -> ConfigReloadResult {
+    let config = &config.effective();
+    // 监控相关配置 (监控器在服务未运行时也会保留)
+    if let Some(monitor) = state.monitor.read().await.as_ref() {
+        monitor.set_enabled(config.enable_logging);
+        monitor.alerts.set_config(config.alerts.clone());
+        monitor.anomaly.set_config(config.anomaly.clone());
+        monitor.inspector.set_config(config.inspector.clone());
+        monitor.transcripts.set_config(config.transcripts.clone());
+        monitor.set_memory_limits(&config.memory_limits);
+        monitor.hooks.set_config(config.hooks.clone());
+    }
+
+    let mut instance_lock = state.instance.write().await;
+    let Some(instance) = instance_lock.as_mut() else {
+        return ConfigReloadResult { applied: false, restart_required: Vec::new() };
+    };
+    let restart_required = config.restart_required_fields(&instance.config);
+    // 更新模型映射
+    instance.axum_server.update_mapping(config).await;
+    // 更新上游代理
+    instance.axum_server.update_proxy(config.upstream_proxy.clone()).await;
+    // 更新安全策略 (auth)
+    instance.axum_server.update_security(config).await;
+    // 更新账号池与调度策略
+    instance.token_manager.update_account_pools(&config.account_pools).await;
+    instance.token_manager.update_sticky_config(config.scheduling.clone()).await;
+    // 更新 z.ai 配置
+    instance.axum_server.update_zai(config).await;
+    // 更新实验性配置
+    instance.axum_server.update_experimental(config).await;
+    // 更新上游重试策略、超时与连接池
+    instance.axum_server.update_upstream_client(config);
+    // 更新上游后端
+    instance.axum_server.update_providers(config).await;
+    let exposed_before = instance.config.exposed_without_auth() && !instance.config.require_auth_for_lan;
+    instance.config = config.hot_reloadable(&instance.config);
+    if !exposed_before {
+        if let Some(monitor) = state.monitor.read().await.as_ref() {
+            monitor.warn_if_exposed(&instance.config);
+        }
+    }
+    tracing::debug!("已同步热更新反代服务配置");
+
+    ConfigReloadResult { applied: true, restart_required }
+}
+
+/// 从配置文件重新加载并应用到运行中的服务
+#[tauri::command]
+pub async fn reload_proxy_config(state: State<'_, ProxyServiceState>) -> Result<ConfigReloadResult, String> {
+    let config = crate::modules::config::load_app_config_async().await?;
+    Ok(apply_proxy_config(&state, &config.proxy).await)
+}
+
+/// 列出反代配置方案
+#[tauri::command]
+pub async fn list_config_presets() -> Result<Vec<crate::modules::config_preset::ConfigPresetInfo>, String> {
+    crate::modules::config_preset::list_presets()
+}
+
+/// 将当前反代配置保存为方案 (同名覆盖)
+#[tauri::command]
+pub async fn save_config_preset(name: String) -> Result<String, String> {
+    crate::modules::config_preset::save_current_as(&name)
+}
+
+#[tauri::command]
+pub async fn delete_config_preset(name: String) -> Result<(), String> {
+    crate::modules::config_preset::delete_preset(&name)
+}
+
+/// 配置方案切换结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetSwitchResult {
+    pub name: String,
+    /// 服务正在运行且已应用
+    pub applied: bool,
+    /// 端口或监听地址变化，已重启服务
+    pub restarted: bool,
+}
+
+/// 切换到配置方案：热应用到运行中的服务，端口或监听地址变化时重启服务
+#[tauri::command]
+pub async fn switch_config_preset(
+    state: State<'_, ProxyServiceState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<PresetSwitchResult, String> {
+    use tauri::Emitter;
+    let config = crate::modules::config_preset::activate_preset(&name)?;
+    let result = apply_proxy_config(&state, &config).await;
+    let restarted = result.applied && !result.restart_required.is_empty();
+    if restarted {
+        stop_proxy_service(state.clone()).await?;
+        start_proxy_service(config, state.clone(), app_handle.clone()).await?;
+    }
+    crate::modules::logger::log_info(&format!("已切换反代配置方案: {}", name));
+    let _ = app_handle.emit("config://updated", ());
+    Ok(PresetSwitchResult { name, applied: result.applied, restarted })
+}
+
+/// 服务器任务意外退出时按已保存的配置重启反代服务并发送通知；所属的服务实例被正常停止或替换后退出
+fn start_server_watchdog(app_handle: tauri::AppHandle, token_manager: std::sync::Weak<TokenManager>) {
+    use tauri::Manager;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SERVER_WATCHDOG_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let Some(manager) = token_manager.upgrade() else { break };
+            let state = app_handle.state::<ProxyServiceState>();
+            let crashed = {
+                let mut instance_lock = state.instance.write().await;
+                match instance_lock.as_ref() {
+                    Some(instance) if Arc::ptr_eq(&instance.token_manager, &manager) => {
+                        if instance.server_handle.is_finished() {
+                            instance_lock.take()
+                        } else {
+                            None
+                        }
+                    }
+                    _ => break,
+                }
+            };
+            drop(manager);
+            let Some(instance) = crashed else { continue };
+
+            tracing::error!("反代服务器任务意外退出，正在自动重启");
+            if let Some(id) = instance.uptime_session {
+                let _ = crate::modules::uptime::record_stop(id);
+            }
+            drop(instance);
+
+            // 使用配置文件中的配置重启 (运行中的配置已展开环境变量与凭证引用，不能写回)
+            let result = match crate::modules::config::load_app_config() {
+                Ok(config) => start_proxy_service(config.proxy, state, app_handle.clone()).await.map(|s| s.port),
+                Err(e) => Err(e),
+            };
+            match &result {
+                Ok(port) => tracing::info!("反代服务已在端口 {} 上自动重启", port),
+                Err(e) => tracing::error!("反代服务自动重启失败: {}", e),
+            }
+            crate::modules::notifications::proxy_restarted(result.as_ref().copied().map_err(String::as_str));
+            crate::modules::tray::update_tray_menus(&app_handle);
+            break;
+        }
+    });
+}
+
+/// 配置文件被外部修改时 (手动编辑、脚本) 自动热重载；所属的服务实例停止后退出
+fn start_config_watcher(app_handle: tauri::AppHandle, token_manager: std::sync::Weak<TokenManager>) {
+    use tauri::{Emitter, Manager};
+    tokio::spawn(async move {
+        let mut last_modified = crate::modules::config::config_modified_at();
+        let mut interval = tokio::time::interval(Duration::from_secs(CONFIG_WATCH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let Some(manager) = token_manager.upgrade() else { break };
+            let state = app_handle.state::<ProxyServiceState>();
+            let running = match state.instance.read().await.as_ref() {
+                Some(instance) if Arc::ptr_eq(&instance.token_manager, &manager) => instance.config.clone(),
+                _ => break,
+            };
+            drop(manager);
+
+            let modified = crate::modules::config::config_modified_at();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            let config = match crate::modules::config::load_app_config() {
+                Ok(config) => {
+                    // 外部修改记入变更历史 (应用内保存的版本已记录，内容相同会跳过)
+                    crate::modules::config_history::record(&config, crate::modules::config_history::HistorySource::External, None);
+                    config.proxy
+                }
+                Err(e) => {
+                    tracing::warn!("配置文件已修改但无法加载，保持当前配置: {}", e);
+                    continue;
+                }
+            };
+            // save_config 写入后已经应用过，内容相同则跳过
+            let effective = config.effective();
+            let unchanged = serde_json::to_value(effective.hot_reloadable(&running)).ok() == serde_json::to_value(&running).ok();
+            if unchanged && effective.restart_required_fields(&running).is_empty() {
+                continue;
+            }
+            let result = apply_proxy_config(&state, &config).await;
+            tracing::info!("配置文件已变更，已热重载反代配置 (需重启生效: {:?})", result.restart_required);
+            let _ = app_handle.emit(CONFIG_RELOADED_EVENT, &result);
+        }
+    });
+}
+
+/// 校验反代配置 (未传入时校验当前生效的配置)，返回结构化的错误与警告
+#[tauri::command]
+pub async fn validate_proxy_config(
+    state: State<'_, ProxyServiceState>,
+    config: Option<ProxyConfig>,
+) -> Result<crate::proxy::config_validation::ConfigValidationReport, String> {
+    let config = match config {
+        Some(config) => config,
+        None => crate::modules::config::load_app_config()?.proxy,
+    }
+    .with_env_overrides();
+
+    let mut ctx = crate::proxy::config_validation::ValidationContext::default();
+    for account in crate::modules::account::list_accounts().unwrap_or_default() {
+        ctx.accounts.insert(account.id.to_lowercase());
+        ctx.accounts.insert(account.email.to_lowercase());
+        ctx.tags.extend(account.tags);
+    }
+    // 服务已在同一地址和端口上运行时不再检测占用
+    ctx.probe_port = match state.instance.read().await.as_ref() {
+        Some(instance) => instance.config.port != config.port || instance.config.allow_lan_access != config.allow_lan_access,
+        None => true,
+    };
+    Ok(crate::proxy::config_validation::validate(&config, &ctx))
+}
+
+/// 预览配置变更 (不应用)：与运行中的配置 (服务未运行时为已保存的配置) 对比，
+/// 列出变化的字段以及需要重启服务的项。未传入时预览配置文件中的配置
+#[tauri::command]
+pub async fn preview_proxy_config_change(
+    state: State<'_, ProxyServiceState>,
+    config: Option<ProxyConfig>,
+) -> Result<crate::proxy::config_diff::ConfigDiff, String> {
+    let saved = crate::modules::config::load_app_config()?.proxy;
+    let proposed = config.unwrap_or_else(|| saved.clone());
+    let running = state.instance.read().await.as_ref().map(|instance| instance.config.clone());
+    Ok(match running {
+        Some(running) => crate::proxy::config_diff::diff(&running, &proposed.effective(), true),
+        None => crate::proxy::config_diff::diff(&saved, &proposed, false),
+    })
+}
+
+/// 客户端连接用的反代配置 (运行中以实际生效的配置为准)
+async fn client_proxy_config(state: &ProxyServiceState) -> Result<ProxyConfig, String> {
+    Ok(match state.instance.read().await.as_ref() {
+        Some(instance) => instance.config.clone(),
+        None => crate::modules::config::load_app_config()?.proxy.effective(),
+    })
+}
+
+/// 客户端访问反代的根地址；host 默认 127.0.0.1，非本机地址需要开启局域网访问
+fn client_base_url(config: &ProxyConfig, host: Option<String>) -> Result<String, String> {
+    let host = host.map(|h| h.trim().to_string()).filter(|h| !h.is_empty()).unwrap_or_else(|| "127.0.0.1".to_string());
+    let loopback = host == "localhost" || host.parse::<std::net::IpAddr>().map(|ip| ip.is_loopback()).unwrap_or(false);
+    if !loopback && !config.allow_lan_access {
+        return Err(tr("backend.errors.proxy.lan_disabled", &[("host", &host)]));
+    }
+    // IPv6 地址需要加方括号
+    let host = if host.contains(':') && !host.starts_with('[') { format!("[{}]", host) } else { host };
+    Ok(format!("http://{}:{}", host, config.port))
+}
+
+/// 选定的 API Key (主 Key 或绑定到账号池的 Key)，默认为主 Key
+fn client_api_key(config: &ProxyConfig, api_key: Option<String>) -> Result<String, String> {
+    match api_key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()) {
+        None => Ok(config.api_key.clone()),
+        Some(key) if key == config.api_key || config.account_pools.iter().any(|p| p.api_keys.contains(&key)) => Ok(key),
+        Some(_) => Err(tr("backend.errors.proxy.key_not_configured", &[])),
+    }
+}
+
+/// 将客户端接入需要的单个值 (根地址 / API Key / Claude Code 环境变量行) 复制到剪贴板
+#[tauri::command]
+pub async fn copy_client_value(
+    app_handle: tauri::AppHandle,
+    state: State<'_, ProxyServiceState>,
+    target: crate::proxy::client_snippets::CopyTarget,
+    host: Option<String>,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let config = client_proxy_config(&state).await?;
+    let value = crate::proxy::client_snippets::copy_value(
+        target,
+        &client_base_url(&config, host)?,
+        &client_api_key(&config, api_key)?,
+    );
+    app_handle
+        .clipboard()
+        .write_text(value)
+        .map_err(|e| format!("写入剪贴板失败: {}", e))
+}
+
+/// 供移动端扫码配置的二维码
+#[derive(Debug, Clone, Serialize)]
+pub struct MobileClientQr {
+    pub base_url: String,
+    pub openai_base_url: String,
+    pub api_key: String,
+    /// 临时 Key 的过期时间 (unix 秒)，使用主 Key 时为 None
+    pub expires_at: Option<i64>,
+    /// 二维码编码的 JSON 内容
+    pub payload: String,
+    pub qr_svg: String,
+}
+
+/// 本机局域网地址 (通过 UDP connect 选出默认出口网卡，不会实际发送数据)
+fn detect_lan_ip() -> Option<String> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then(|| ip.to_string())
+}
+
+/// 生成移动端扫码配置用的二维码 (局域网地址 + API Key)
+///
+/// host 默认自动探测本机局域网地址；temporary 默认为 true，签发仅保存在内存中的临时 Key
+#[tauri::command]
+pub async fn generate_mobile_client_qr(
+    state: State<'_, ProxyServiceState>,
+    host: Option<String>,
+    temporary: Option<bool>,
+    ttl_minutes: Option<u32>,
+) -> Result<MobileClientQr, String> {
+    use crate::proxy::temp_keys;
+
+    let config = client_proxy_config(&state).await?;
+    let host = host
+        .filter(|h| !h.trim().is_empty())
+        .or_else(detect_lan_ip)
+        .ok_or_else(|| tr("backend.errors.proxy.no_lan_address", &[]))?;
+    let base_url = client_base_url(&config, Some(host))?;
+    let (api_key, expires_at) = if temporary.unwrap_or(true) {
+        let now = chrono::Utc::now().timestamp();
+        let (key, expires_at) = temp_keys::issue(ttl_minutes.unwrap_or(temp_keys::DEFAULT_TTL_MINUTES), now);
+        crate::modules::audit_log::record(crate::modules::audit_log::AuditEvent::KeyCreated {
+            kind: "temporary",
+            pool: None,
+            key: crate::modules::audit_log::key_hint(&key),
+            expires_at: Some(expires_at),
+        });
+        (key, Some(expires_at))
+    } else {
+        (config.api_key.clone(), None)
+    };
+    let openai_base_url = format!("{}/v1", base_url);
+    let payload = serde_json::json!({
+        "base_url": base_url,
+        "openai_base_url": openai_base_url,
+        "api_key": api_key,
+    })
+    .to_string();
+    let qr_svg = crate::utils::qr::QrCode::encode(&payload)?.to_svg();
+    Ok(MobileClientQr { base_url, openai_base_url, api_key, expires_at, payload, qr_svg })
+}
+
+/// 吊销所有扫码签发的临时 API Key
+#[tauri::command]
+pub async fn revoke_temporary_api_keys() -> Result<usize, String> {
+    let count = crate::proxy::temp_keys::revoke_all();
+    crate::modules::audit_log::record(crate::modules::audit_log::AuditEvent::KeyRevoked {
+        kind: "temporary",
+        pool: None,
+        key: None,
+        count,
+    });
+    Ok(count)
+}
+
+/// 生成客户端配置片段 (Claude Code / Cline / Roo Code / Continue / OpenAI SDK)
+///
+/// host 默认为 127.0.0.1，其他设备使用时传入本机局域网地址；传入 base_url 时直接使用该地址；api_key 默认为当前配置的 Key
+#[tauri::command]
+pub async fn get_client_snippets(
+    state: State<'_, ProxyServiceState>,
+    host: Option<String>,
+    api_key: Option<String>,
+    openai_model: Option<String>,
+    claude_model: Option<String>,
+    base_url: Option<String>,
+) -> Result<Vec<crate::proxy::client_snippets::ClientSnippet>, String> {
+    use crate::proxy::client_snippets::{generate, SnippetOptions, DEFAULT_CLAUDE_MODEL, DEFAULT_OPENAI_MODEL};
+
+    let config = client_proxy_config(&state).await?;
+    let base_url = match base_url.map(|u| u.trim().trim_end_matches('/').to_string()).filter(|u| !u.is_empty()) {
+        // 指向其他机器上的反代 (如深度链接分享的地址)
+        Some(url) => url,
+        None => client_base_url(&config, host)?,
+    };
+    let pick = |value: Option<String>, default: &str| value.filter(|v| !v.trim().is_empty()).unwrap_or_else(|| default.to_string());
+    Ok(generate(&SnippetOptions {
+        base_url,
+        api_key: pick(api_key, &config.api_key),
+        openai_model: pick(openai_model, DEFAULT_OPENAI_MODEL),
+        claude_model: pick(claude_model, DEFAULT_CLAUDE_MODEL),
+    }))
+}
+
+/// 把反代地址、API Key 与模型映射写入 Claude Code 的 settings.json (首次写入前自动备份)
+#[tauri::command]
+pub async fn configure_claude_code(
+    state: State<'_, ProxyServiceState>,
+    host: Option<String>,
+    api_key: Option<String>,
+    models: Option<crate::modules::claude_code::ClaudeModelMapping>,
+) -> Result<crate::modules::claude_code::ClaudeCodeStatus, String> {
+    let config = client_proxy_config(&state).await?;
+    let base_url = client_base_url(&config, host)?;
+    let api_key = client_api_key(&config, api_key)?;
+    let mut models = models.unwrap_or_default();
+    if models.model.as_deref().map_or(true, |m| m.trim().is_empty()) {
+        models.model = Some(crate::proxy::client_snippets::DEFAULT_CLAUDE_MODEL.to_string());
+    }
+    crate::modules::claude_code::configure(&base_url, &api_key, &models)
+}
+
+/// 恢复配置前的 Claude Code settings.json
+#[tauri::command]
+pub async fn undo_claude_code_config() -> Result<crate::modules::claude_code::ClaudeCodeStatus, String> {
+    crate::modules::claude_code::undo()
+}
+
+#[tauri::command]
+pub async fn get_claude_code_status() -> Result<crate::modules::claude_code::ClaudeCodeStatus, String> {
+    crate::modules::claude_code::status()
+}
+
+/// 建立远程访问隧道 (需反代服务运行中，使用当前生效的隧道与认证配置)
+#[tauri::command]
+pub async fn start_tunnel(state: State<'_, ProxyServiceState>) -> Result<crate::modules::tunnel::TunnelStatus, String> {
+    let config = state
+        .instance
+        .read()
+        .await
+        .as_ref()
+        .map(|instance| instance.config.clone())
+        .ok_or_else(|| crate::modules::i18n::tr("backend.errors.proxy_not_running", &[]))?;
+    crate::modules::tunnel::start(&config.tunnel, config.port, &config.auth_mode, &config.api_key).await
+}
+
+#[tauri::command]
+pub async fn stop_tunnel() -> Result<crate::modules::tunnel::TunnelStatus, String> {
+    Ok(crate::modules::tunnel::stop())
+}
+
+#[tauri::command]
+pub async fn get_tunnel_status() -> Result<crate::modules::tunnel::TunnelStatus, String> {
+    Ok(crate::modules::tunnel::status())
+}
+
+/// 当前生效的环境变量配置覆盖 (AGM_PROXY_* / AGM_BIND)，凭证类字段的值已隐藏
+#[tauri::command]
+pub async fn get_env_overrides() -> Result<Vec<crate::proxy::env_overrides::EnvOverride>, String> {
+    let config = crate::modules::config::load_app_config()?.proxy;
+    Ok(crate::proxy::env_overrides::active_overrides(&config))
+}
+
+/// 最近若干天 (默认 7 天，含今天) 的每日运行时长与可用率
+#[tauri::command]
+pub async fn get_uptime_summary(window_days: Option<u32>) -> Result<crate::modules::uptime::UptimeSummary, String> {
+    let days = window_days.unwrap_or(7).clamp(1, 90);
+    tokio::task::spawn_blocking(move || crate::modules::uptime::get_uptime_summary(days))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 端到端自检：对每种协议入口各发送一个极小的请求，按阶段报告结果
+///
+/// 服务未运行时用已保存的配置临时启动，自检结束后停止
+#[tauri::command]
+pub async fn run_proxy_self_test(
+    state: State<'_, ProxyServiceState>,
+    app_handle: tauri::AppHandle,
+    model: Option<String>,
+) -> Result<crate::proxy::self_test::SelfTestReport, String> {
+    let running = state.instance.read().await.as_ref().map(|instance| instance.config.clone());
+    let (config, started) = match running {
+        Some(config) => (config, false),
+        None => {
+            let config = crate::modules::config::load_app_config()?.proxy;
+            start_proxy_service(config.clone(), state.clone(), app_handle).await?;
+            (config, true)
+        }
+    };
+
+    let report = crate::proxy::self_test::run(&config, model, started).await;
+    if started {
+        if let Err(e) = stop_proxy_service(state).await {
+            tracing::warn!("[SelfTest] Failed to stop the proxy started for the self-test: {}", e);
+        }
+    }
+    report
+}
+
+/// 压测：以配置的并发数通过反代发送合成请求，报告吞吐与延迟分位数 (需先启动服务)
+#[tauri::command]
+pub async fn run_proxy_benchmark(
+    state: State<'_, ProxyServiceState>,
+    options: Option<crate::proxy::benchmark::BenchmarkOptions>,
+) -> Result<crate::proxy::benchmark::BenchmarkReport, String> {
+    let (port, api_key, upstream) = {
+        let instance_lock = state.instance.read().await;
+        let instance = instance_lock.as_ref().ok_or_else(|| crate::modules::i18n::tr("backend.errors.proxy_not_running", &[]))?;
+        (instance.config.port, instance.config.api_key.clone(), instance.axum_server.upstream_client())
+    };
+    crate::proxy::benchmark::run(port, &api_key, options.unwrap_or_default(), |url| {
+        upstream.set_base_url_override(url)
+    })
+    .await
+}
+
 /// 获取反代服务状态
 #[tauri::command]
 pub async fn get_proxy_status(
@@ -248,6 +834,124 @@ pub async fn get_proxy_log_detail(
     crate::modules::proxy_db::get_log_detail(&log_id)
 }
 
+/// 获取各账号在最近 window_hours 小时内的用量 (默认 24 小时)
+#[tauri::command]
+pub async fn get_account_usage(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::proxy_db::AccountUsage>, String> {
+    crate::modules::proxy_db::get_account_usage(window_hours.unwrap_or(24))
+}
+
+/// 获取所有账号的用量统计 (最近 window_hours 小时 + 累计，包含从未使用过的账号)
+#[tauri::command]
+pub async fn get_account_usage_stats(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::proxy_db::AccountUsageStats>, String> {
+    crate::modules::proxy_db::get_account_usage_stats(window_hours.unwrap_or(24))
+}
+
+/// 各账号最近 window_hours 小时按类别 (429 / 认证 / 安全拦截 / 网络 / 5xx / 4xx) 的错误统计
+#[tauri::command]
+pub async fn get_account_error_breakdown(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::proxy_db::AccountErrorBreakdown>, String> {
+    crate::modules::proxy_db::get_account_error_breakdown(window_hours.unwrap_or(24))
+}
+
+/// 按 API Key / 模型 / 账号汇总最近 window_hours 小时的 Token 用量 (附带估算成本)
+#[tauri::command]
+pub async fn get_token_usage_summary(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::token_stats::TokenUsageSummary>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours.unwrap_or(24) as i64 * 3600;
+    let prices = crate::modules::config::load_app_config()
+        .map(|c| c.model_prices)
+        .unwrap_or_default();
+    crate::modules::token_stats::get_summary(since, now + 1, &prices)
+}
+
+/// 按 API Key 汇总最近 window_hours 小时的请求、错误率、Token 与用到的模型
+#[tauri::command]
+pub async fn get_key_usage_stats(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::token_stats::KeyUsageSummary>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours.unwrap_or(24) as i64 * 3600;
+    let prices = crate::modules::config::load_app_config()
+        .map(|c| c.model_prices)
+        .unwrap_or_default();
+    crate::modules::token_stats::get_key_usage(since, now + 1, None, &prices)
+}
+
+/// 最近 window_hours 小时客户端请求的模型与实际使用的上游模型 (用于调整模型映射)
+#[tauri::command]
+pub async fn get_model_mapping_stats(
+    window_hours: Option<u32>,
+) -> Result<Vec<crate::modules::token_stats::ModelMappingUsage>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours.unwrap_or(24) as i64 * 3600;
+    crate::modules::token_stats::get_model_mapping_usage(since, now + 1)
+}
+
+fn usage_report(
+    period: crate::modules::token_stats::ReportPeriod,
+    window_days: Option<u32>,
+) -> Result<Vec<crate::modules::token_stats::UsageReportRow>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_days.unwrap_or(30) as i64 * 86_400;
+    let prices = crate::modules::config::load_app_config()
+        .map(|c| c.model_prices)
+        .unwrap_or_default();
+    crate::modules::token_stats::get_report(period, since, now + 1, &prices)
+}
+
+/// 按日 / 周 / 月聚合最近 window_days 天的请求、错误与 Token 用量 (按 Key / 模型 / 账号)
+#[tauri::command]
+pub async fn get_usage_report(
+    period: crate::modules::token_stats::ReportPeriod,
+    window_days: Option<u32>,
+) -> Result<Vec<crate::modules::token_stats::UsageReportRow>, String> {
+    usage_report(period, window_days)
+}
+
+/// 导出用量报表为 CSV 文件，返回导出的行数
+#[tauri::command]
+pub async fn export_usage_report_csv(
+    path: String,
+    period: crate::modules::token_stats::ReportPeriod,
+    window_days: Option<u32>,
+) -> Result<usize, String> {
+    let rows = usage_report(period, window_days)?;
+    std::fs::write(&path, crate::modules::token_stats::report_to_csv(&rows))
+        .map_err(|e| format!("写入文件失败: {}", e))?;
+    Ok(rows.len())
+}
+
+/// 将时间范围内 (Unix 毫秒) 记录的请求导出为 HAR 文件 (已去除凭证)，返回导出的请求数
+#[tauri::command]
+pub async fn export_proxy_logs_har(
+    path: String,
+    since: i64,
+    until: Option<i64>,
+) -> Result<usize, String> {
+    let until = until.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let logs = crate::modules::proxy_db::get_logs_in_range(since, until, crate::modules::har::MAX_HAR_ENTRIES)?;
+    if logs.is_empty() {
+        return Err(tr("backend.errors.proxy.no_recorded_requests", &[]));
+    }
+
+    let config = crate::modules::config::load_app_config()?;
+    let redactor = crate::proxy::inspector::Inspector::new();
+    redactor.set_config(config.proxy.inspector.clone());
+    let base_url = format!("http://127.0.0.1:{}", config.proxy.port);
+    let har = crate::modules::har::build_har(&logs, &base_url, &redactor);
+
+    let content = serde_json::to_string_pretty(&har).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    Ok(logs.len())
+}
+
 /// 生成 API Key
 #[tauri::command]
 pub fn generate_api_key() -> String {
@@ -267,7 +971,7 @@ pub async fn reload_proxy_accounts(
             .map_err(|e| format!("重新加载账号失败: {}", e))?;
         Ok(count)
     } else {
-        Err("服务未运行".to_string())
+        Err(crate::modules::i18n::tr("backend.errors.proxy_not_running", &[]))
     }
 }
 
@@ -287,9 +991,9 @@ pub async fn update_model_mapping(
     }
     
     // 2. 无论是否运行，都保存到全局配置持久化
-    let mut app_config = crate::modules::config::load_app_config().map_err(|e| e)?;
+    let mut app_config = crate::modules::config::load_app_config_async().await?;
     app_config.proxy.custom_mapping = config.custom_mapping;
-    crate::modules::config::save_app_config(&app_config).map_err(|e| e)?;
+    crate::modules::config::save_app_config_async(app_config).await?;
     
     Ok(())
 }
@@ -365,23 +1069,16 @@ pub async fn fetch_zai_models(
     if zai.api_key.trim().is_empty() {
         return Err("z.ai api_key is not set".to_string());
     }
+    let api_key = crate::proxy::secret_refs::resolve_value(&zai.api_key)?;
 
     let url = join_base_url(&zai.base_url, "/v1/models");
 
-    let mut builder = reqwest::Client::builder().timeout(Duration::from_secs(request_timeout.max(5)));
-    if upstream_proxy.enabled && !upstream_proxy.url.is_empty() {
-        let proxy = reqwest::Proxy::all(&upstream_proxy.url)
-            .map_err(|e| format!("Invalid upstream proxy url: {}", e))?;
-        builder = builder.proxy(proxy);
-    }
-    let client = builder
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let client = crate::proxy::providers::build_client(Some(upstream_proxy), request_timeout)?;
 
     let resp = client
         .get(&url)
-        .header("Authorization", format!("Bearer {}", zai.api_key))
-        .header("x-api-key", zai.api_key)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("x-api-key", api_key)
         .header("anthropic-version", "2023-06-01")
         .header("accept", "application/json")
         .send()
@@ -433,6 +1130,86 @@ pub async fn update_proxy_scheduling_config(
     }
 }
 
+/// 获取当前处于冷却中的账号 (429/配额耗尽后自动冷却)
+#[tauri::command]
+pub async fn get_proxy_account_cooldowns(
+    state: State<'_, ProxyServiceState>,
+) -> Result<Vec<crate::proxy::token_manager::AccountCooldownEvent>, String> {
+    let instance_lock = state.instance.read().await;
+    if let Some(instance) = instance_lock.as_ref() {
+        Ok(instance.token_manager.get_cooling_accounts())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// 获取按模型 / 账号划分的延迟直方图
+#[tauri::command]
+pub async fn get_latency_stats(
+    state: State<'_, ProxyServiceState>,
+) -> Result<crate::proxy::latency::LatencyReport, String> {
+    let monitor_lock = state.monitor.read().await;
+    match monitor_lock.as_ref() {
+        Some(monitor) => Ok(monitor.latency.report()),
+        None => Ok(crate::proxy::latency::LatencyRegistry::new().report()),
+    }
+}
+
+/// 获取上游连接复用统计 (服务未运行时返回全零)
+#[tauri::command]
+pub async fn get_upstream_connection_stats(
+    state: State<'_, ProxyServiceState>,
+) -> Result<crate::proxy::upstream::client::ConnectionStats, String> {
+    let instance_lock = state.instance.read().await;
+    Ok(instance_lock
+        .as_ref()
+        .map(|instance| instance.axum_server.upstream_connection_stats())
+        .unwrap_or_default())
+}
+
+/// 获取各上游端点 (prod / daily) 的延迟与错误率 (服务未运行时返回空列表)
+#[tauri::command]
+pub async fn get_upstream_endpoint_stats(
+    state: State<'_, ProxyServiceState>,
+) -> Result<Vec<crate::proxy::upstream::endpoint_stats::EndpointSnapshot>, String> {
+    let instance_lock = state.instance.read().await;
+    Ok(instance_lock
+        .as_ref()
+        .map(|instance| instance.axum_server.upstream_endpoint_stats())
+        .unwrap_or_default())
+}
+
+/// 获取当前负载：进行中请求、排队数与各账号并发 (服务未运行时均为 0)
+#[tauri::command]
+pub async fn get_proxy_load(
+    state: State<'_, ProxyServiceState>,
+) -> Result<crate::proxy::load::LoadSnapshot, String> {
+    let in_flight = state.monitor.read().await.as_ref().map(|m| m.in_flight.get()).unwrap_or(0);
+    let instance_lock = state.instance.read().await;
+    Ok(crate::proxy::load::LoadSnapshot::collect(
+        in_flight,
+        instance_lock.as_ref().map(|instance| instance.token_manager.as_ref()),
+    ))
+}
+
+/// 列出反代账号池中带有指定标签的账号
+#[tauri::command]
+pub async fn list_proxy_accounts_by_tag(
+    state: State<'_, ProxyServiceState>,
+    tag: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let instance_lock = state.instance.read().await;
+    let Some(instance) = instance_lock.as_ref() else {
+        return Ok(Vec::new());
+    };
+    Ok(instance
+        .token_manager
+        .list_accounts_by_tag(&tag)
+        .into_iter()
+        .map(|(account_id, email)| serde_json::json!({ "account_id": account_id, "email": email }))
+        .collect())
+}
+
 /// 清除所有会话粘性绑定
 #[tauri::command]
 pub async fn clear_proxy_session_bindings(
@@ -443,7 +1220,7 @@ pub async fn clear_proxy_session_bindings(
         instance.token_manager.clear_all_sessions();
         Ok(())
     } else {
-        Err("服务未运行".to_string())
+        Err(crate::modules::i18n::tr("backend.errors.proxy_not_running", &[]))
     }
 }
 
@@ -0,0 +1,322 @@
+// 无界面模式
+//
+// `antigravity_tools serve [--config <path>] [--port <port>] [--log-level <filter>]`
+// 只加载配置、账号与 Token 管理器并运行反代服务，不创建 Tauri 窗口与托盘，
+// 同一个可执行文件可以直接在 VPS / tmux 中运行。Ctrl+C 退出。
+//
+// 浏览器访问 `/dashboard` 可查看服务状态、账号与用量 (需输入主 API Key)。
+// 依赖界面的功能 (托盘、系统通知、配置热重载、前端事件推送、定时预热) 在此模式下不启用，
+// 运行事件可通过外部通知渠道 (Telegram / Discord / 邮件) 发送。
+// Windows 发布版为 GUI 子系统程序，终端看不到输出，运行日志以数据目录下的日志文件为准。
+//
+// `cargo build --no-default-features` 构建不依赖 tauri 的版本 (Linux 下无需 WebKitGTK)，
+// 可执行文件直接进入本模式 (控制台程序，`serve` 可省略)。监控器、Token 管理器等组件持有的
+// 界面句柄见 utils::app，此时不会被构造。
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::modules::{self, config, logger};
+use crate::proxy::monitor::ProxyMonitor;
+use crate::proxy::{AxumServer, TokenManager};
+use crate::modules::i18n::tr;
+
+/// 进入无界面模式的子命令
+pub const SERVE_COMMAND: &str = "serve";
+
+const USAGE: &str = "用法: antigravity_tools serve [选项]
+
+选项:
+  --config <path>      使用指定的配置文件 (.json / .toml / .yaml)，默认为数据目录下的 gui_config
+  --port <port>        覆盖配置中的反代端口
+  --log-level <filter> 日志过滤规则，如 info、debug、proxy::mappers=debug
+  -h, --help           显示帮助";
+
+/// 命令行参数
+#[derive(Debug, Default, PartialEq)]
+pub struct HeadlessOptions {
+    pub config: Option<PathBuf>,
+    pub port: Option<u16>,
+    pub log_level: Option<String>,
+    pub help: bool,
+}
+
+/// 解析 `serve` 之后的参数 (支持 `--port 8045` 与 `--port=8045` 两种写法)
+pub fn parse_args(args: &[String]) -> Result<HeadlessOptions, String> {
+    let mut options = HeadlessOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| iter.next().cloned())
+                .ok_or_else(|| format!("参数 {} 缺少取值", flag))
+        };
+        match flag {
+            "--config" => options.config = Some(PathBuf::from(value()?)),
+            "--port" => {
+                let port = value()?;
+                options.port = Some(port.parse().map_err(|_| format!("无效的端口: {}", port))?);
+            }
+            "--log-level" => options.log_level = Some(value()?),
+            "-h" | "--help" => options.help = true,
+            other => return Err(tr("backend.errors.headless.unknown_arg", &[("arg", other)])),
+        }
+    }
+    Ok(options)
+}
+
+/// 应用配置路径并初始化日志、配置迁移与崩溃钩子 (无界面模式与 JSON-RPC 模式共用)
+pub(crate) fn init_environment(options: &HeadlessOptions) -> Result<(), String> {
+    if let Some(path) = &options.config {
+        if !path.is_file() {
+            return Err(tr("backend.errors.headless.config_not_found", &[("path", &path.display().to_string())]));
+        }
+        config::set_config_path(path.clone());
+    }
+
+    modules::config_schema::migrate_all();
+    logger::init_logger();
+    modules::crash::install_panic_hook();
+    if let Some(filter) = &options.log_level {
+        logger::set_log_filter(filter)?;
+    }
+    Ok(())
+}
+
+/// 无界面模式入口，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return 0;
+    }
+    if let Err(e) = init_environment(&options) {
+        eprintln!("{}", e);
+        return 2;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建异步运行时失败: {}", e);
+            return 1;
+        }
+    };
+    match runtime.block_on(serve(options.port)) {
+        Ok(()) => 0,
+        Err(e) => {
+            error!("{}", e);
+            1
+        }
+    }
+}
+
+/// 读取配置并应用环境变量覆盖、密钥引用与命令行端口
+pub(crate) fn load_proxy_config(port: Option<u16>) -> Result<crate::proxy::ProxyConfig, String> {
+    let app_config = config::load_app_config()?;
+    let mut proxy = app_config.proxy.with_env_overrides().resolve_secret_refs()?;
+    if let Some(port) = port {
+        proxy.port = port;
+    }
+    Ok(proxy)
+}
+
+/// 已启动的反代服务 (无界面模式与 JSON-RPC 模式共用)
+pub(crate) struct RunningProxy {
+    pub axum_server: AxumServer,
+    pub token_manager: Arc<TokenManager>,
+    pub monitor: Arc<ProxyMonitor>,
+    pub bind_address: String,
+    pub port: u16,
+    pub active_accounts: usize,
+    server_handle: Option<tokio::task::JoinHandle<()>>,
+    uptime_session: Option<i64>,
+}
+
+impl RunningProxy {
+    /// 加载账号并启动 Axum 服务器 (按配置自动建立远程访问隧道)
+    pub(crate) async fn start(proxy: &crate::proxy::ProxyConfig) -> Result<Self, String> {
+        // 安全模式: 未开启鉴权时不监听非本机地址
+        proxy.check_bind_safety()?;
+        let monitor = Arc::new(ProxyMonitor::new(1000, None));
+        monitor.set_enabled(proxy.enable_logging);
+        monitor.alerts.set_config(proxy.alerts.clone());
+        monitor.anomaly.set_config(proxy.anomaly.clone());
+        monitor.inspector.set_config(proxy.inspector.clone());
+        monitor.transcripts.set_config(proxy.transcripts.clone());
+        monitor.set_memory_limits(&proxy.memory_limits);
+        monitor.hooks.set_config(proxy.hooks.clone());
+
+        let token_manager = Arc::new(TokenManager::new(modules::account::get_data_dir()?));
+        token_manager.start_cooldown_watcher();
+        token_manager.start_store_watcher();
+        token_manager.update_sticky_config(proxy.scheduling.clone()).await;
+        token_manager.update_account_pools(&proxy.account_pools).await;
+
+        let active_accounts = token_manager.load_accounts().await.map_err(|e| format!("加载账号失败: {}", e))?;
+        let zai_enabled = proxy.zai.enabled && !matches!(proxy.zai.dispatch_mode, crate::proxy::ZaiDispatchMode::Off);
+        if active_accounts == 0 && !zai_enabled {
+            return Err(modules::i18n::tr("backend.errors.no_accounts", &[]));
+        }
+        // 即将过期的 Token 在后台并发刷新，不阻塞服务启动
+        token_manager.start_startup_refresh();
+
+        let (axum_server, server_handle) = AxumServer::start(
+            proxy.get_bind_address().to_string(),
+            proxy.port,
+            token_manager.clone(),
+            proxy.custom_mapping.clone(),
+            proxy.request_timeout,
+            proxy.upstream_proxy.clone(),
+            crate::proxy::ProxySecurityConfig::from_proxy_config(proxy),
+            proxy.zai.clone(),
+            monitor.clone(),
+            proxy.experimental.clone(),
+        )
+        .await
+        .map_err(|e| format!("启动 Axum 服务器失败: {}", e))?;
+        axum_server.update_upstream_client(proxy);
+        axum_server.update_providers(proxy).await;
+        monitor.warn_if_exposed(proxy);
+
+        let uptime_session = match modules::uptime::record_start() {
+            Ok(id) => {
+                modules::uptime::start_sampler(id, Arc::downgrade(&token_manager), axum_server.upstream_client());
+                Some(id)
+            }
+            Err(e) => {
+                warn!("记录反代启动时间失败: {}", e);
+                None
+            }
+        };
+        if proxy.tunnel.auto_start {
+            match modules::tunnel::start(&proxy.tunnel, proxy.port, &proxy.auth_mode, &proxy.api_key).await {
+                Ok(status) => info!("[Headless] 远程访问地址: {}", status.public_url.unwrap_or_default()),
+                Err(e) => warn!("[Headless] 建立远程访问隧道失败: {}", e),
+            }
+        }
+        modules::webhooks::fire(
+            crate::models::WebhookEvent::ProxyStarted,
+            serde_json::json!({ "port": proxy.port, "active_accounts": active_accounts }),
+        );
+
+        Ok(Self {
+            axum_server,
+            token_manager,
+            monitor,
+            bind_address: proxy.get_bind_address().to_string(),
+            port: proxy.port,
+            active_accounts,
+            server_handle: Some(server_handle),
+            uptime_session,
+        })
+    }
+
+    /// 等待服务器任务退出 (可取消)
+    pub(crate) async fn wait(&mut self) {
+        if let Some(handle) = &mut self.server_handle {
+            let _ = handle.await;
+            self.server_handle = None;
+        }
+    }
+
+    /// 停止服务器并等待退出
+    pub(crate) async fn stop(mut self) {
+        modules::tunnel::stop();
+        self.axum_server.stop();
+        if let Some(handle) = self.server_handle.take() {
+            let _ = handle.await;
+        }
+        record_stopped(self.uptime_session, self.port);
+    }
+}
+
+fn record_stopped(uptime_session: Option<i64>, port: u16) {
+    if let Some(id) = uptime_session {
+        if let Err(e) = modules::uptime::record_stop(id) {
+            warn!("记录反代停止时间失败: {}", e);
+        }
+    }
+    modules::webhooks::fire(crate::models::WebhookEvent::ProxyStopped, serde_json::json!({ "port": port }));
+}
+
+/// 启动反代服务并运行到收到 Ctrl+C 或服务器任务退出
+async fn serve(port: Option<u16>) -> Result<(), String> {
+    let proxy = load_proxy_config(port)?;
+    // 定时任务 (发现新版本时发送到外部通知渠道)
+    modules::task_scheduler::start(None);
+    let mut running = RunningProxy::start(&proxy).await?;
+    info!(
+        "[Headless] 反代服务已启动: http://{}:{} ({} 个账号)，按 Ctrl+C (或发送 SIGTERM) 退出",
+        running.bind_address, running.port, running.active_accounts
+    );
+    info!("[Headless] 管理面板: http://{}:{}/dashboard", running.bind_address, running.port);
+
+    let interrupted = tokio::select! {
+        _ = shutdown_signal() => true,
+        _ = running.wait() => false,
+    };
+    if interrupted {
+        info!("[Headless] 收到退出信号，正在停止反代服务");
+        running.stop().await;
+        Ok(())
+    } else {
+        modules::tunnel::stop();
+        record_stopped(running.uptime_session, running.port);
+        Err(tr("backend.errors.headless.proxy_exited", &[]))
+    }
+}
+
+/// 等待 Ctrl+C；Unix 下也响应 SIGTERM (systemd / docker stop)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("[Headless] 无法监听 SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(&args(&["--config", "/etc/ag.toml", "--port=9000", "--log-level", "debug"])).unwrap();
+        assert_eq!(options.config, Some(PathBuf::from("/etc/ag.toml")));
+        assert_eq!(options.port, Some(9000));
+        assert_eq!(options.log_level.as_deref(), Some("debug"));
+
+        assert_eq!(parse_args(&[]).unwrap(), HeadlessOptions::default());
+        assert!(parse_args(&args(&["-h"])).unwrap().help);
+        assert!(parse_args(&args(&["--port", "abc"])).is_err());
+        assert!(parse_args(&args(&["--port"])).is_err());
+        assert!(parse_args(&args(&["--verbose"])).is_err());
+    }
+}
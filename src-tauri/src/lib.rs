@@ -1,54 +1,115 @@
+// 未启用 gui 特性时只有无界面模式使用到部分模块
+#![cfg_attr(not(feature = "gui"), allow(dead_code, unused_imports))]
+
 mod models;
 mod modules;
+#[cfg(feature = "gui")]
 mod commands;
 mod utils;
 mod proxy;  // 反代服务模块
 pub mod error;
+pub mod headless;  // 无界面模式 (serve 子命令)
+pub mod rpc;  // JSON-RPC 自动化模式 (rpc 子命令)
 
+#[cfg(feature = "gui")]
 use tauri::Manager;
 use modules::logger;
 use tracing::{info, error};
 
 // 测试命令
+#[cfg(feature = "gui")]
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 先把旧版本格式的配置文件迁移到最新版本 (日志初始化需要读取配置，原文件会保留 .bak 备份)
+    modules::config_schema::migrate_all();
     // 初始化日志
     logger::init_logger();
+    // 崩溃报告 (默认关闭)
+    modules::crash::install_panic_hook();
     
     tauri::Builder::default()
+        // 必须最先注册: 重复启动时把参数 (含深度链接) 转交给已运行的实例后直接退出，
+        // 避免第二个实例再去绑定反代端口失败
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            info!("检测到重复启动，已切换到正在运行的实例");
+            if args
+                .iter()
+                .any(|arg| arg == commands::autostart::MINIMIZED_ARG || arg == commands::autostart::HIDDEN_ARG)
+            {
+                // 开机自启等以最小化方式重复启动时只提示，不弹出窗口
+                use tauri_plugin_notification::NotificationExt;
+                let lang = modules::config::load_app_config().map(|c| c.language).unwrap_or_default();
+                let _ = app
+                    .notification()
+                    .builder()
+                    .title("Antigravity Tools")
+                    .body(modules::i18n::get_tray_texts(&lang).already_running)
+                    .show();
+            } else {
+                modules::tray::show_main_window(app);
+            }
+        }))
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            Some(vec!["--minimized"]),
+            Some(vec![commands::autostart::MINIMIZED_ARG]),
         ))
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            let _ = app.get_webview_window("main")
-                .map(|window| {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    #[cfg(target_os = "macos")]
-                    app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-                });
-        }))
         .manage(commands::proxy::ProxyServiceState::new())
         .setup(|app| {
             info!("Setup starting...");
+            modules::notifications::init(app.handle());
             modules::tray::create_tray(app.handle())?;
             info!("Tray created");
+
+            // 开机自启 / 隐藏启动时只显示托盘 (窗口由前端在非最小化启动时显示)
+            if commands::autostart::launched_minimized() {
+                info!("以最小化方式启动，窗口保持隐藏");
+                #[cfg(target_os = "macos")]
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            }
             
+            // antigravity-manager:// 深度链接 (第二个实例收到的链接由 single-instance 转交)
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Err(e) = app.deep_link().register_all() {
+                    error!("注册深度链接协议失败: {}", e);
+                }
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    modules::deep_link::handle_urls(&handle, event.urls());
+                });
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    modules::deep_link::handle_urls(app.handle(), urls);
+                }
+            }
+
+            // 切换反代服务的全局快捷键
+            if let Ok(config) = modules::config::load_app_config() {
+                if let Err(e) = modules::hotkey::register_proxy_shortcut(app.handle(), config.proxy_toggle_shortcut.as_deref()) {
+                    error!("{}", e);
+                }
+            }
+
             // 自动启动反代服务
             let handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 // 加载配置
                 if let Ok(config) = modules::config::load_app_config() {
-                    if config.proxy.auto_start {
+                    // 隐藏启动即作为后台服务运行，无论是否开启自动启动都启动反代
+                    if config.proxy.with_env_overrides().auto_start || commands::autostart::launched_hidden() {
                         let state = handle.state::<commands::proxy::ProxyServiceState>();
                         // 尝试启动服务
                         if let Err(e) = commands::proxy::start_proxy_service(
@@ -64,20 +125,52 @@ pub fn run() {
                 }
             });
             
+            // 将旧版明文 Token 迁移为加密存储
+            if let Err(e) = modules::token_vault::migrate_plaintext_accounts() {
+                error!("Token 加密迁移失败: {}", e);
+            }
+
             // 启动智能调度器
             modules::scheduler::start_scheduler(app.handle().clone());
+
+            // 定时任务 (归档清理、IDE 备份、检查更新、Token 刷新、健康检查、用量报表)
+            modules::task_scheduler::start(Some(app.handle().clone()));
+
+            // 定期推送各账号 Token 剩余有效期
+            modules::token_status::start_token_status_emitter(app.handle().clone());
+
+            // OpenTelemetry 链路导出 (默认关闭)
+            modules::otel::start_exporter();
+
+            // StatsD 指标推送 (默认关闭)
+            modules::statsd::start_exporter();
+
+            // 空闲账号保活 (默认关闭)
+            modules::keep_warm::start_keep_warm(app.handle().clone());
+            modules::ide_token_sync::start_watcher(app.handle().clone());
             
             Ok(())
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                let _ = window.hide();
-                #[cfg(target_os = "macos")]
-                {
-                    use tauri::Manager;
-                    window.app_handle().set_activation_policy(tauri::ActivationPolicy::Accessory).unwrap_or(());
+                let action = modules::config::load_app_config()
+                    .map(|c| c.close_action)
+                    .unwrap_or_default();
+                match action {
+                    models::CloseAction::Tray => {
+                        modules::tray::hide_to_tray(window);
+                        api.prevent_close();
+                    }
+                    models::CloseAction::Ask => {
+                        // 交给前端弹窗确认 (隐藏到托盘 / 退出)
+                        use tauri::Emitter;
+                        api.prevent_close();
+                        let _ = window.emit(commands::CLOSE_REQUESTED_EVENT, ());
+                    }
+                    models::CloseAction::Quit => {
+                        window.app_handle().exit(0);
+                    }
                 }
-                api.prevent_close();
             }
         })
         .invoke_handler(tauri::generate_handler![
@@ -87,6 +180,9 @@ pub fn run() {
             commands::add_account,
             commands::delete_account,
             commands::delete_accounts,
+            commands::list_archived_accounts,
+            commands::restore_archived_account,
+            commands::purge_archived_accounts,
             commands::reorder_accounts,
             commands::switch_account,
             // 设备指纹
@@ -96,6 +192,9 @@ pub fn run() {
             commands::preview_generate_profile,
             commands::apply_device_profile,
             commands::restore_original_device,
+            commands::reset_device_identity,
+            commands::list_device_backups,
+            commands::restore_device_backup,
             commands::list_device_versions,
             commands::restore_device_version,
             commands::delete_device_version,
@@ -104,6 +203,7 @@ pub fn run() {
             // 配额命令
             commands::fetch_account_quota,
             commands::refresh_all_quotas,
+            commands::refresh_all_tokens,
             // 配置命令
             commands::load_config,
             commands::save_config,
@@ -112,31 +212,120 @@ pub fn run() {
             commands::start_oauth_login,
             commands::complete_oauth_login,
             commands::cancel_oauth_login,
+            commands::reauthorize_account,
             commands::import_v1_accounts,
+            commands::detect_ide_credentials,
+            commands::detect_ide_installation,
+            commands::close_ide,
+            commands::launch_ide,
+            commands::list_ide_backups,
+            commands::create_ide_backup,
+            commands::restore_ide_backup,
+            commands::delete_ide_backup,
             commands::import_from_db,
+            commands::import_accounts_batch,
+            commands::import_gemini_cli_credentials,
+            commands::import_tool_credentials,
+            commands::export_tool_credentials,
+            commands::export_diagnostics_bundle,
+            commands::list_crash_reports,
+            commands::acknowledge_crash_report,
+            commands::get_crash_report_issue_url,
+            commands::export_accounts_backup,
+            commands::restore_accounts_backup,
+            commands::export_config_bundle,
+            commands::import_config_bundle,
+            commands::get_config_sync_settings,
+            commands::set_config_sync_backend,
+            commands::sync_config,
+            commands::list_config_history,
+            commands::rollback_config,
+            commands::export_account_share,
+            commands::import_account_share,
+            commands::take_pending_deep_link,
             commands::import_custom_db,
             commands::sync_account_from_db,
             commands::save_text_file,
             commands::clear_log_cache,
+            commands::query_app_logs,
+            commands::get_log_filter,
+            commands::set_log_filter,
             commands::open_data_folder,
             commands::get_data_dir_path,
             commands::show_main_window,
+            commands::confirm_window_close,
             commands::get_antigravity_path,
             commands::get_antigravity_args,
             commands::check_for_updates,
             commands::get_update_settings,
             commands::save_update_settings,
+            commands::get_power_status,
+            commands::test_webhook,
+            commands::test_notification_channel,
+            commands::list_scheduled_tasks,
+            commands::update_scheduled_task,
+            commands::run_scheduled_task,
+            commands::get_scheduled_task_history,
             commands::should_check_updates,
             commands::update_last_check_time,
+            commands::skip_update_version,
+            commands::download_update,
+            commands::get_downloaded_update,
+            commands::install_update,
+            commands::get_rollback_version,
+            commands::rollback_update,
             commands::toggle_proxy_status,
+            commands::set_account_priority,
+            commands::set_account_usage_windows,
+            commands::set_account_egress_proxy,
+            commands::set_account_project_override,
+            commands::set_account_tags,
+            commands::release_account_quarantine,
+            commands::resolve_account_project_id,
+            commands::discover_account_projects,
+            commands::get_project_quotas,
+            commands::get_token_statuses,
+            commands::list_account_events,
+            commands::list_profiles,
+            commands::create_profile,
+            commands::delete_profile,
+            commands::switch_profile,
             // 反代服务命令
             commands::proxy::start_proxy_service,
             commands::proxy::stop_proxy_service,
+            commands::proxy::get_uptime_summary,
+            commands::proxy::reload_proxy_config,
+            commands::proxy::get_env_overrides,
+            commands::proxy::validate_proxy_config,
+            commands::proxy::preview_proxy_config_change,
+            commands::proxy::get_client_snippets,
+            commands::proxy::configure_claude_code,
+            commands::proxy::undo_claude_code_config,
+            commands::proxy::get_claude_code_status,
+            commands::proxy::start_tunnel,
+            commands::proxy::stop_tunnel,
+            commands::proxy::get_tunnel_status,
+            commands::proxy::copy_client_value,
+            commands::proxy::generate_mobile_client_qr,
+            commands::proxy::revoke_temporary_api_keys,
+            commands::proxy::list_config_presets,
+            commands::proxy::save_config_preset,
+            commands::proxy::delete_config_preset,
+            commands::proxy::switch_config_preset,
             commands::proxy::get_proxy_status,
             commands::proxy::get_proxy_stats,
             commands::proxy::get_proxy_logs,
             commands::proxy::get_proxy_logs_paginated,
             commands::proxy::get_proxy_log_detail,
+            commands::proxy::export_proxy_logs_har,
+            commands::proxy::get_account_usage,
+            commands::proxy::get_account_usage_stats,
+            commands::proxy::get_account_error_breakdown,
+            commands::proxy::get_token_usage_summary,
+            commands::proxy::get_key_usage_stats,
+            commands::proxy::get_model_mapping_stats,
+            commands::proxy::get_usage_report,
+            commands::proxy::export_usage_report_csv,
             commands::proxy::set_proxy_monitor_enabled,
             commands::proxy::clear_proxy_logs,
             commands::proxy::generate_api_key,
@@ -146,12 +335,23 @@ pub fn run() {
             commands::proxy::get_proxy_scheduling_config,
             commands::proxy::update_proxy_scheduling_config,
             commands::proxy::clear_proxy_session_bindings,
+            commands::proxy::get_proxy_account_cooldowns,
+            commands::proxy::get_upstream_connection_stats,
+            commands::proxy::get_upstream_endpoint_stats,
+            commands::proxy::get_proxy_load,
+            commands::proxy::run_proxy_self_test,
+            commands::proxy::run_proxy_benchmark,
+            commands::proxy::get_latency_stats,
+            commands::proxy::list_proxy_accounts_by_tag,
             // Autostart 命令
             commands::autostart::toggle_auto_launch,
             commands::autostart::is_auto_launch_enabled,
+            commands::autostart::is_launched_minimized,
             // 预热命令
             commands::warm_up_all_accounts,
             commands::warm_up_account,
+            commands::check_account_health,
+            commands::check_all_accounts_health,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -159,12 +359,7 @@ pub fn run() {
             // Handle macOS dock icon click to reopen window
             #[cfg(target_os = "macos")]
             if let tauri::RunEvent::Reopen { .. } = event {
-                if let Some(window) = app_handle.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.unminimize();
-                    let _ = window.set_focus();
-                    app_handle.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-                }
+                modules::tray::show_main_window(app_handle);
             }
             // Suppress unused variable warnings on non-macOS platforms
             #[cfg(not(target_os = "macos"))]
@@ -1,7 +1,33 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
-#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+// 未启用 gui 特性的构建为控制台程序，可以直接看到无界面模式的输出
+#![cfg_attr(all(not(debug_assertions), feature = "gui"), windows_subsystem = "windows")]
 
+#[cfg(not(feature = "gui"))]
 fn main() {
+    // 只有无界面模式，`serve` 子命令可省略
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(antigravity_tools_lib::rpc::RPC_COMMAND) {
+        std::process::exit(antigravity_tools_lib::rpc::run(&args[1..]));
+    }
+    let args = match args.first().map(String::as_str) {
+        Some(antigravity_tools_lib::headless::SERVE_COMMAND) => &args[1..],
+        _ => &args[..],
+    };
+    std::process::exit(antigravity_tools_lib::headless::run(args));
+}
+
+#[cfg(feature = "gui")]
+fn main() {
+    // `serve` 子命令: 不创建窗口，只运行反代服务
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(antigravity_tools_lib::headless::SERVE_COMMAND) {
+        std::process::exit(antigravity_tools_lib::headless::run(&args[1..]));
+    }
+    // `rpc` 子命令: 通过 stdin / stdout 的 JSON-RPC 控制反代服务
+    if args.first().map(String::as_str) == Some(antigravity_tools_lib::rpc::RPC_COMMAND) {
+        std::process::exit(antigravity_tools_lib::rpc::run(&args[1..]));
+    }
+
     #[cfg(target_os = "linux")]
     {
         // Fix for transparent window on some Linux systems
@@ -25,6 +25,18 @@ pub struct Account {
     /// Unix timestamp when the account was disabled.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub disabled_at: Option<i64>,
+    /// refresh_token 已被撤销 (invalid_grant)，需要用户重新登录授权
+    #[serde(default)]
+    pub needs_reauth: bool,
+    /// 上游返回封禁/停用错误后被自动隔离，不参与反代轮换
+    #[serde(default)]
+    pub quarantined: bool,
+    /// 隔离原因 (命中的错误特征与上游错误内容)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_reason: Option<String>,
+    /// Unix timestamp when the account was quarantined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined_at: Option<i64>,
     /// User manually disabled proxy feature (does not affect app usage).
     #[serde(default)]
     pub proxy_disabled: bool,
@@ -37,6 +49,24 @@ pub struct Account {
     /// 受配额保护禁用的模型列表 [NEW #621]
     #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub protected_models: HashSet<String>,
+    /// 反代调度优先级 (数值越大越优先，低优先级账号仅在高优先级账号不可用时使用)
+    #[serde(default)]
+    pub priority: i32,
+    /// 反代可用时段 (本地时间)，为空表示全天可用；不在任一时段内的账号不参与轮换
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage_windows: Vec<UsageWindow>,
+    /// 反代请求上游时使用的独立出口代理 (http/https/socks5)，为空时使用全局上游代理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress_proxy: Option<String>,
+    /// 固定使用的 project_id (设置后跳过自动解析，适用于拥有多个项目的账号)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id_override: Option<String>,
+    /// 账号可访问的全部项目 (通过项目发现获取，供多项目选择策略使用)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// 自定义标签 (来源、归属人、用途等)，可用于筛选和账号池成员匹配 ("tag:<标签>")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub created_at: i64,
     pub last_used: i64,
 }
@@ -55,10 +85,20 @@ impl Account {
             disabled: false,
             disabled_reason: None,
             disabled_at: None,
+            needs_reauth: false,
+            quarantined: false,
+            quarantine_reason: None,
+            quarantined_at: None,
             proxy_disabled: false,
             proxy_disabled_reason: None,
             proxy_disabled_at: None,
             protected_models: HashSet::new(),
+            priority: 0,
+            usage_windows: Vec::new(),
+            egress_proxy: None,
+            project_id_override: None,
+            projects: Vec::new(),
+            tags: Vec::new(),
             created_at: now,
             last_used: now,
         }
@@ -68,11 +108,18 @@ impl Account {
         self.last_used = chrono::Utc::now().timestamp();
     }
 
-    pub fn update_quota(&mut self, quota: QuotaData) {
+    pub fn update_quota(&mut self, mut quota: QuotaData) {
+        // 本次未识别到订阅等级 (loadCodeAssist 失败或使用了缓存的 project_id) 时保留上次的结果
+        if quota.subscription_tier.is_none() {
+            quota.subscription_tier = self.quota.as_ref().and_then(|q| q.subscription_tier.clone());
+        }
         self.quota = Some(quota);
     }
 }
 
+// 可用时段由账号轮换规则使用，定义在 antigravity-proxy-core
+pub use antigravity_proxy_core::rotation::UsageWindow;
+
 /// 账号索引数据（accounts.json）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountIndex {
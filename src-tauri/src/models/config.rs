@@ -18,9 +18,210 @@ pub struct AppConfig {
     #[serde(default)]
     pub auto_launch: bool,  // 开机自动启动
     #[serde(default)]
+    pub start_hidden: bool, // 启动时不显示窗口 (只有托盘) 并自动启动反代服务，等同 --hidden
+    #[serde(default)]
     pub scheduled_warmup: ScheduledWarmupConfig, // [NEW] 定时预热配置
     #[serde(default)]
     pub quota_protection: QuotaProtectionConfig, // [NEW] 配额保护配置
+    #[serde(default)]
+    pub keep_warm: KeepWarmConfig, // 空闲账号保活配置
+    #[serde(default)]
+    pub archive_purge_days: u32, // 已删除 (归档) 账号保留天数，超过后永久删除；0 表示永久保留
+    #[serde(default)]
+    pub auto_create_project: bool, // 账号没有可用项目时通过 onboardUser 自动创建
+    #[serde(default = "default_model_prices")]
+    pub model_prices: Vec<ModelPrice>, // 估算成本用的模型单价表
+    #[serde(default)]
+    pub log_rotation: LogRotationConfig, // 应用日志滚动与保留策略 (重启后生效)
+    #[serde(default)]
+    pub otel: OtelConfig, // OpenTelemetry 链路导出
+    #[serde(default)]
+    pub statsd: StatsdConfig, // StatsD / DogStatsD 指标推送
+    #[serde(default)]
+    pub crash_reports: bool, // 崩溃时在数据目录写入崩溃报告 (重启后生效)
+    #[serde(default)]
+    pub close_action: CloseAction, // 关闭主窗口时的行为
+    #[serde(default)]
+    pub proxy_toggle_shortcut: Option<String>, // 启动 / 停止反代服务的全局快捷键 (如 CmdOrCtrl+Shift+P)
+    #[serde(default)]
+    pub ide_backup: IdeBackupConfig, // IDE 配置定时备份
+    #[serde(default = "default_ide_token_sync")]
+    pub ide_token_sync: bool, // IDE 刷新了同一账号的 Token 时自动写回管理器
+    #[serde(default)]
+    pub notifications: NotificationConfig, // 系统通知 (逐项开关)
+    #[serde(default)]
+    pub power_saving: PowerSavingConfig, // 电池供电 / 计费网络 / 离线时暂停后台任务
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>, // 运行事件 Webhook (账号隔离、配额耗尽、反代启停、告警等)
+}
+
+fn default_ide_token_sync() -> bool {
+    true
+}
+
+/// 关闭主窗口时的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseAction {
+    /// 隐藏到托盘，反代服务继续在后台运行
+    #[default]
+    Tray,
+    /// 每次关闭时询问
+    Ask,
+    /// 退出应用 (会停止反代服务)
+    Quit,
+}
+
+/// StatsD / DogStatsD 指标推送配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsdConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// StatsD 服务地址 (UDP，host:port)
+    #[serde(default = "default_statsd_address")]
+    pub address: String,
+    /// 指标名前缀
+    #[serde(default = "default_statsd_prefix")]
+    pub prefix: String,
+    /// 使用 DogStatsD 扩展 (以 tag 发送模型与状态码)
+    #[serde(default)]
+    pub dogstatsd: bool,
+    /// 推送间隔 (秒)
+    #[serde(default = "default_statsd_flush_interval_secs")]
+    pub flush_interval_secs: u64,
+}
+
+fn default_statsd_address() -> String {
+    "127.0.0.1:8125".to_string()
+}
+
+fn default_statsd_prefix() -> String {
+    "antigravity".to_string()
+}
+
+fn default_statsd_flush_interval_secs() -> u64 {
+    10
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: default_statsd_address(),
+            prefix: default_statsd_prefix(),
+            dogstatsd: false,
+            flush_interval_secs: default_statsd_flush_interval_secs(),
+        }
+    }
+}
+
+/// OpenTelemetry 链路导出配置 (OTLP/HTTP JSON)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtelConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Collector 的 traces 接收地址
+    #[serde(default = "default_otel_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_otel_service_name")]
+    pub service_name: String,
+    /// 附加请求头 (如 Collector 的认证 Token)
+    #[serde(default)]
+    pub headers: std::collections::HashMap<String, String>,
+}
+
+fn default_otel_endpoint() -> String {
+    "http://127.0.0.1:4318/v1/traces".to_string()
+}
+
+fn default_otel_service_name() -> String {
+    "antigravity-manager".to_string()
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: default_otel_endpoint(),
+            service_name: default_otel_service_name(),
+            headers: std::collections::HashMap::new(),
+        }
+    }
+}
+
+/// 应用日志滚动与保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRotationConfig {
+    /// 单个日志文件上限 (MB)，超过后滚动
+    #[serde(default = "default_log_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    /// 最多保留的日志文件数 (含当前文件)
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    /// 日志保留天数 (0 表示只按文件数清理)
+    #[serde(default = "default_log_retention_days")]
+    pub retention_days: u64,
+}
+
+fn default_log_max_file_size_mb() -> u64 {
+    20
+}
+
+fn default_log_max_files() -> usize {
+    20
+}
+
+fn default_log_retention_days() -> u64 {
+    7
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: default_log_max_file_size_mb(),
+            max_files: default_log_max_files(),
+            retention_days: default_log_retention_days(),
+        }
+    }
+}
+
+/// 模型单价 (美元 / 百万 Token)，用于估算反代流量在付费 API 上的等价成本
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelPrice {
+    /// 模型名，以 `*` 结尾表示前缀匹配 (如 `gemini-2.5-pro*`)
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    fn new(model: &str, input_per_million: f64, output_per_million: f64) -> Self {
+        Self { model: model.to_string(), input_per_million, output_per_million }
+    }
+
+    /// 匹配程度 (越大越精确)，不匹配返回 None
+    pub fn match_len(&self, model: &str) -> Option<usize> {
+        match self.model.strip_suffix('*') {
+            Some(prefix) if model.starts_with(prefix) => Some(prefix.len()),
+            None if self.model == model => Some(usize::MAX),
+            _ => None,
+        }
+    }
+}
+
+/// 默认单价 (参考各官方 API 的标准价格，可在配置中修改)
+fn default_model_prices() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice::new("gemini-3-pro*", 2.0, 12.0),
+        ModelPrice::new("gemini-3-flash*", 0.5, 3.0),
+        ModelPrice::new("gemini-2.5-pro*", 1.25, 10.0),
+        ModelPrice::new("gemini-2.5-flash-lite*", 0.1, 0.4),
+        ModelPrice::new("gemini-2.5-flash*", 0.3, 2.5),
+        ModelPrice::new("claude-opus-4-5*", 5.0, 25.0),
+        ModelPrice::new("claude-opus*", 15.0, 75.0),
+        ModelPrice::new("claude-sonnet*", 3.0, 15.0),
+        ModelPrice::new("claude-haiku*", 1.0, 5.0),
+    ]
 }
 
 /// 定时预热配置
@@ -92,6 +293,271 @@ impl Default for QuotaProtectionConfig {
     }
 }
 
+/// 空闲账号保活配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeepWarmConfig {
+    /// 是否启用保活
+    pub enabled: bool,
+
+    /// 账号空闲超过该时长 (分钟) 后才会被保活
+    #[serde(default = "default_keep_warm_idle_minutes")]
+    pub idle_minutes: u32,
+
+    /// 每小时最多发起的保活次数 (所有账号合计)
+    #[serde(default = "default_keep_warm_max_pings_per_hour")]
+    pub max_pings_per_hour: u32,
+}
+
+fn default_keep_warm_idle_minutes() -> u32 {
+    120
+}
+
+fn default_keep_warm_max_pings_per_hour() -> u32 {
+    6
+}
+
+impl KeepWarmConfig {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: default_keep_warm_idle_minutes(),
+            max_pings_per_hour: default_keep_warm_max_pings_per_hour(),
+        }
+    }
+}
+
+impl Default for KeepWarmConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// IDE 配置 (settings / 凭据) 定时备份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdeBackupConfig {
+    /// 是否启用定时备份 (手动备份与切换账号前的备份不受影响)
+    pub enabled: bool,
+
+    /// 定时备份间隔 (小时)
+    #[serde(default = "default_ide_backup_interval_hours")]
+    pub interval_hours: u32,
+
+    /// 最多保留的备份数 (0 表示不限制)
+    #[serde(default = "default_ide_backup_keep")]
+    pub keep: usize,
+}
+
+fn default_ide_backup_interval_hours() -> u32 {
+    24
+}
+
+fn default_ide_backup_keep() -> usize {
+    10
+}
+
+impl IdeBackupConfig {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_ide_backup_interval_hours(),
+            keep: default_ide_backup_keep(),
+        }
+    }
+}
+
+impl Default for IdeBackupConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 运行事件的通知开关 (同时作用于系统通知与外部通知渠道)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// 所有账号都在冷却或被隔离
+    #[serde(default = "default_notification_enabled")]
+    pub pool_exhausted: bool,
+
+    /// 账号凭据失效，需要重新授权
+    #[serde(default = "default_notification_enabled")]
+    pub reauth_required: bool,
+
+    /// 反代服务意外退出并已自动重启
+    #[serde(default = "default_notification_enabled")]
+    pub proxy_restarted: bool,
+
+    /// 模型剩余配额降到该百分比及以下时通知 (0 表示关闭)
+    #[serde(default = "default_notification_quota_threshold")]
+    pub quota_threshold: u32,
+
+    /// 发现新版本
+    #[serde(default = "default_notification_enabled")]
+    pub update_available: bool,
+
+    /// 外部通知渠道 (无人值守运行在服务器上时使用)
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+}
+
+/// 外部通知渠道
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationChannel {
+    #[serde(default = "default_notification_enabled")]
+    pub enabled: bool,
+
+    #[serde(flatten)]
+    pub target: NotificationTarget,
+}
+
+/// 通知渠道的目标与凭据
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationTarget {
+    /// Telegram Bot (sendMessage)
+    Telegram { bot_token: String, chat_id: String },
+    /// Discord 频道 Webhook
+    Discord { webhook_url: String },
+    /// SMTP 邮件
+    Email {
+        smtp_host: String,
+        #[serde(default = "default_smtp_port")]
+        smtp_port: u16,
+        #[serde(default)]
+        security: SmtpSecurity,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        password: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+/// SMTP 连接加密方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// 明文连接后升级 (587)
+    #[default]
+    Starttls,
+    /// 直接 TLS (465)
+    Tls,
+    /// 不加密 (仅限本机 / 内网中继)
+    None,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_notification_enabled() -> bool {
+    true
+}
+
+fn default_notification_quota_threshold() -> u32 {
+    10
+}
+
+impl NotificationConfig {
+    pub fn new() -> Self {
+        Self {
+            pool_exhausted: default_notification_enabled(),
+            reauth_required: default_notification_enabled(),
+            proxy_restarted: default_notification_enabled(),
+            quota_threshold: default_notification_quota_threshold(),
+            update_available: default_notification_enabled(),
+            channels: Vec::new(),
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 电源与网络感知: 条件满足时暂停保活、定时预热、后台检查更新与自动刷新配额 (离线时总是暂停)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerSavingConfig {
+    #[serde(default = "default_power_saving_enabled")]
+    pub enabled: bool,
+
+    /// 使用电池供电时暂停
+    #[serde(default = "default_power_saving_enabled")]
+    pub pause_on_battery: bool,
+
+    /// 按流量计费的网络下暂停
+    #[serde(default = "default_power_saving_enabled")]
+    pub pause_on_metered: bool,
+}
+
+fn default_power_saving_enabled() -> bool {
+    true
+}
+
+impl PowerSavingConfig {
+    pub fn new() -> Self {
+        Self {
+            enabled: default_power_saving_enabled(),
+            pause_on_battery: default_power_saving_enabled(),
+            pause_on_metered: default_power_saving_enabled(),
+        }
+    }
+}
+
+impl Default for PowerSavingConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Webhook 事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// 账号因封禁 / 停用错误被隔离
+    AccountQuarantined,
+    /// 账号需要重新授权
+    ReauthRequired,
+    /// 某个模型的剩余配额降到 0
+    QuotaExhausted,
+    /// 所有账号都在冷却或被隔离
+    PoolExhausted,
+    ProxyStarted,
+    ProxyStopped,
+    /// 错误率 / 连续 429 / 用量异常告警
+    Alert,
+}
+
+/// 单个 Webhook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookConfig {
+    #[serde(default = "default_webhook_enabled")]
+    pub enabled: bool,
+
+    pub url: String,
+
+    /// 订阅的事件，为空表示全部
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+
+    /// HMAC-SHA256 签名密钥，设置后请求带 `X-Antigravity-Signature: sha256=<hex>`
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+fn default_webhook_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    /// 是否订阅了该事件
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.enabled && !self.url.trim().is_empty() && (self.events.is_empty() || self.events.contains(&event))
+    }
+}
+
 impl AppConfig {
     pub fn new() -> Self {
         Self {
@@ -106,8 +572,24 @@ impl AppConfig {
             antigravity_executable: None,
             antigravity_args: None,
             auto_launch: false,
+            start_hidden: false,
             scheduled_warmup: ScheduledWarmupConfig::default(),
             quota_protection: QuotaProtectionConfig::default(),
+            keep_warm: KeepWarmConfig::default(),
+            archive_purge_days: 0,
+            auto_create_project: false,
+            model_prices: default_model_prices(),
+            log_rotation: LogRotationConfig::default(),
+            otel: OtelConfig::default(),
+            statsd: StatsdConfig::default(),
+            crash_reports: false,
+            close_action: CloseAction::default(),
+            proxy_toggle_shortcut: None,
+            ide_backup: IdeBackupConfig::default(),
+            ide_token_sync: default_ide_token_sync(),
+            notifications: NotificationConfig::default(),
+            power_saving: PowerSavingConfig::default(),
+            webhooks: Vec::new(),
         }
     }
 }
@@ -4,22 +4,21 @@ use serde_json;
 use uuid::Uuid;
 use serde::Serialize;
 
-use crate::models::{Account, AccountIndex, AccountSummary, TokenData, QuotaData, DeviceProfile, DeviceProfileVersion,};
+use crate::models::{Account, AccountIndex, AccountSummary, TokenData, QuotaData, DeviceProfile, DeviceProfileVersion, UsageWindow};
 use crate::modules;
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
+use crate::modules::i18n::tr;
 
 /// 全局账号写入锁，防止并发操作导致索引文件损坏
 static ACCOUNT_INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 // ... existing constants ...
 const DATA_DIR: &str = ".antigravity_tools";
-const ACCOUNTS_INDEX: &str = "accounts.json";
-const ACCOUNTS_DIR: &str = "accounts";
 
-// ... existing functions get_data_dir, get_accounts_dir, load_account_index, save_account_index ...
-/// 获取数据目录路径
-pub fn get_data_dir() -> Result<PathBuf, String> {
+// ... existing functions get_data_dir, load_account_index, save_account_index ...
+/// 获取数据根目录路径 (所有档案共享，如日志与更新设置)
+pub fn get_root_data_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
     let data_dir = home.join(DATA_DIR);
     
@@ -32,90 +31,56 @@ pub fn get_data_dir() -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
-/// 获取账号目录路径
-pub fn get_accounts_dir() -> Result<PathBuf, String> {
-    let data_dir = get_data_dir()?;
-    let accounts_dir = data_dir.join(ACCOUNTS_DIR);
-    
-    if !accounts_dir.exists() {
-        fs::create_dir_all(&accounts_dir)
-            .map_err(|e| format!("创建账号目录失败: {}", e))?;
+/// 获取当前档案的数据目录路径 (账号、配置、密钥等按档案隔离)
+pub fn get_data_dir() -> Result<PathBuf, String> {
+    let root = get_root_data_dir()?;
+    let data_dir = modules::profile::profile_dir(&root, &modules::profile::active_profile(&root));
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)
+            .map_err(|e| format!("创建数据目录失败: {}", e))?;
     }
-    
-    Ok(accounts_dir)
+    Ok(data_dir)
 }
 
 /// 加载账号索引
 pub fn load_account_index() -> Result<AccountIndex, String> {
     let data_dir = get_data_dir()?;
-    let index_path = data_dir.join(ACCOUNTS_INDEX);
-    // modules::logger::log_info(&format!("正在加载账号索引: {:?}", index_path)); // Optional: reduce noise
-    
-    if !index_path.exists() {
-        crate::modules::logger::log_warn("账号索引文件不存在");
-        return Ok(AccountIndex::new());
-    }
-    
-    let content = fs::read_to_string(&index_path)
-        .map_err(|e| format!("读取账号索引失败: {}", e))?;
-    
-    // 如果文件内容为空，视为新索引
-    if content.trim().is_empty() {
-        crate::modules::logger::log_warn("账号索引文件内容为空，正在初始化新索引");
-        return Ok(AccountIndex::new());
-    }
-    
-    let index: AccountIndex = serde_json::from_str(&content)
-        .map_err(|e| format!("解析账号索引失败: {}", e))?;
-        
+    let index = modules::account_store::load_index(&data_dir)?;
     crate::modules::logger::log_info(&format!("成功加载索引，包含 {} 个账号", index.accounts.len()));
     Ok(index)
 }
 
-/// 保存账号索引 (原子化写入)
+/// 保存账号索引 (单事务写入)
 pub fn save_account_index(index: &AccountIndex) -> Result<(), String> {
     let data_dir = get_data_dir()?;
-    let index_path = data_dir.join(ACCOUNTS_INDEX);
-    let temp_path = data_dir.join(format!("{}.tmp", ACCOUNTS_INDEX));
-    
-    let content = serde_json::to_string_pretty(index)
-        .map_err(|e| format!("序列化账号索引失败: {}", e))?;
-    
-    // 写入临时文件
-    fs::write(&temp_path, content)
-        .map_err(|e| format!("写入临时索引文件失败: {}", e))?;
-        
-    // 原子重命名
-    fs::rename(temp_path, index_path)
-        .map_err(|e| format!("替换索引文件失败: {}", e))
+    modules::account_store::save_index(&data_dir, index)
 }
 
 /// 加载账号数据
 pub fn load_account(account_id: &str) -> Result<Account, String> {
-    let accounts_dir = get_accounts_dir()?;
-    let account_path = accounts_dir.join(format!("{}.json", account_id));
-    
-    if !account_path.exists() {
-        return Err(format!("账号不存在: {}", account_id));
-    }
-    
-    let content = fs::read_to_string(&account_path)
-        .map_err(|e| format!("读取账号数据失败: {}", e))?;
+    let data_dir = get_data_dir()?;
+    let mut value = modules::account_store::read_account(&data_dir, account_id)?
+        .ok_or_else(|| format!("账号不存在: {}", account_id))?;
+    crate::modules::token_vault::decrypt_token_fields(&mut value)?;
     
-    serde_json::from_str(&content)
+    serde_json::from_value(value)
         .map_err(|e| format!("解析账号数据失败: {}", e))
 }
 
 /// 保存账号数据
 pub fn save_account(account: &Account) -> Result<(), String> {
-    let accounts_dir = get_accounts_dir()?;
-    let account_path = accounts_dir.join(format!("{}.json", account.id));
+    let data_dir = get_data_dir()?;
     
-    let content = serde_json::to_string_pretty(account)
+    let mut value = serde_json::to_value(account)
         .map_err(|e| format!("序列化账号数据失败: {}", e))?;
+    // Token 加密存储；密钥不可用时保留明文，避免账号数据丢失
+    if let Err(e) = crate::modules::token_vault::encrypt_token_fields(&mut value) {
+        crate::modules::logger::log_warn(&format!("Token 加密失败，将以明文保存: {}", e));
+        value = serde_json::to_value(account)
+            .map_err(|e| format!("序列化账号数据失败: {}", e))?;
+    }
     
-    fs::write(&account_path, content)
-        .map_err(|e| format!("保存账号数据失败: {}", e))
+    modules::account_store::write_account(&data_dir, &value)
 }
 
 /// 列出所有账号
@@ -133,7 +98,7 @@ pub fn list_accounts() -> Result<Vec<Account>, String> {
                 crate::modules::logger::log_error(&format!("加载账号 {} 失败: {}", summary.id, e));
                 // 如果是文件不存在导致的错误，标记为无效 ID
                 // load_account 返回 "账号不存在: id" 或者底层 io error
-                if e.contains("账号不存在") || e.contains("Os { code: 2,") || e.contains("No such file") {
+                if e.contains("账号不存在") {
                     invalid_ids.push(summary.id.clone());
                 }
             },
@@ -170,7 +135,7 @@ pub fn add_account(email: String, name: Option<String>, token: TokenData) -> Res
     let mut index = load_account_index()?;
     
     // 检查是否已存在
-    if index.accounts.iter().any(|s| s.email == email) {
+    if index.accounts.iter().any(|s| same_identity(&s.email, &email)) {
         return Err(format!("账号已存在: {}", email));
     }
     
@@ -197,52 +162,101 @@ pub fn add_account(email: String, name: Option<String>, token: TokenData) -> Res
     }
     
     save_account_index(&index)?;
+    modules::account_store::record_event(&get_data_dir()?, &account.id, "created", None);
     
     Ok(account)
 }
 
+/// 账号身份比较 (邮箱忽略大小写与首尾空白)
+pub fn same_identity(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// 导入的账号合并到已有账号时的变更摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeInfo {
+    pub account_id: String,
+    pub email: String,
+    /// 被更新的字段 (access_token / refresh_token / name / reenabled)
+    pub changes: Vec<String>,
+}
+
+/// 将新的凭据合并到已有账号，返回发生变化的字段
+///
+/// 新凭据缺少的 project_id / session_id / name 沿用已有值
+fn merge_into(account: &mut Account, name: Option<String>, mut token: TokenData) -> Vec<String> {
+    let mut changes = Vec::new();
+    if token.refresh_token != account.token.refresh_token {
+        changes.push("refresh_token".to_string());
+    }
+    if token.access_token != account.token.access_token {
+        changes.push("access_token".to_string());
+    }
+    if name.is_some() && name != account.name {
+        changes.push("name".to_string());
+    }
+
+    token.project_id = token.project_id.or_else(|| account.token.project_id.take());
+    token.session_id = token.session_id.or_else(|| account.token.session_id.take());
+    let credentials_changed = token.refresh_token != account.token.refresh_token
+        || token.access_token != account.token.access_token;
+    account.token = token;
+    account.name = name.or_else(|| account.name.take());
+
+    // If an account was previously disabled (e.g. invalid_grant), any explicit token upsert
+    // should re-enable it (user manually updated credentials in the UI).
+    if account.disabled && credentials_changed {
+        account.disabled = false;
+        account.disabled_reason = None;
+        account.disabled_at = None;
+        account.needs_reauth = false;
+        changes.push("reenabled".to_string());
+    }
+    changes
+}
+
 /// 添加或更新账号
 pub fn upsert_account(email: String, name: Option<String>, token: TokenData) -> Result<Account, String> {
+    upsert_account_detailed(email, name, token).map(|(account, _)| account)
+}
+
+/// 添加或更新账号，按身份 (邮箱) 合并到已有账号时一并返回合并摘要
+pub fn upsert_account_detailed(email: String, name: Option<String>, token: TokenData) -> Result<(Account, Option<MergeInfo>), String> {
     let _lock = ACCOUNT_INDEX_LOCK.lock().map_err(|e| format!("获取锁失败: {}", e))?;
     let mut index = load_account_index()?;
     
     // 先找到账号 ID（如果存在）
     let existing_account_id = index.accounts.iter()
-        .find(|s| s.email == email)
+        .find(|s| same_identity(&s.email, &email))
         .map(|s| s.id.clone());
     
     if let Some(account_id) = existing_account_id {
         // 更新现有账号
         match load_account(&account_id) {
             Ok(mut account) => {
-                let old_access_token = account.token.access_token.clone();
-                let old_refresh_token = account.token.refresh_token.clone();
-                account.token = token;
-                account.name = name.clone();
-                // If an account was previously disabled (e.g. invalid_grant), any explicit token upsert
-                // should re-enable it (user manually updated credentials in the UI).
-                if account.disabled
-                    && (account.token.refresh_token != old_refresh_token
-                        || account.token.access_token != old_access_token)
-                {
-                    account.disabled = false;
-                    account.disabled_reason = None;
-                    account.disabled_at = None;
-                }
+                let changes = merge_into(&mut account, name, token);
                 account.update_last_used();
                 save_account(&account)?;
                 
                 // 同步更新索引中的 name
                 if let Some(idx_summary) = index.accounts.iter_mut().find(|s| s.id == account_id) {
-                    idx_summary.name = name;
+                    idx_summary.name = account.name.clone();
                     save_account_index(&index)?;
                 }
                 
-                return Ok(account);
+                if !changes.is_empty() {
+                    let data_dir = get_data_dir()?;
+                    modules::account_store::record_event(&data_dir, &account_id, "merged", Some(&changes.join(",")));
+                    if changes.iter().any(|c| c == "reenabled") {
+                        modules::account_store::record_event(&data_dir, &account_id, "reenabled", Some("credentials updated"));
+                    }
+                }
+                let merge = MergeInfo { account_id, email: account.email.clone(), changes };
+                return Ok((account, Some(merge)));
             },
             Err(e) => {
                 crate::modules::logger::log_warn(&format!("Account {} file missing ({}), recreating...", account_id, e));
-                // 索引存在但文件丢失，重新创建
+                // 索引存在但账号数据丢失，重新创建
                 let mut account = Account::new(account_id.clone(), email.clone(), token);
                 account.name = name.clone();
                 save_account(&account)?;
@@ -253,7 +267,7 @@ pub fn upsert_account(email: String, name: Option<String>, token: TokenData) ->
                     save_account_index(&index)?;
                 }
                 
-                return Ok(account);
+                return Ok((account, None));
             }
         }
     }
@@ -264,7 +278,7 @@ pub fn upsert_account(email: String, name: Option<String>, token: TokenData) ->
     
     // 释放锁，让 add_account 处理
     drop(_lock);
-    add_account(email, name, token)
+    add_account(email, name, token).map(|account| (account, None))
 }
 
 /// 删除账号
@@ -287,25 +301,126 @@ pub fn delete_account(account_id: &str) -> Result<(), String> {
     
     save_account_index(&index)?;
     
-    // 删除账号文件
-    let accounts_dir = get_accounts_dir()?;
-    let account_path = accounts_dir.join(format!("{}.json", account_id));
+    // 移入归档 (保留 Token，可恢复)
+    let data_dir = get_data_dir()?;
+    modules::account_store::archive_accounts(&data_dir, &[account_id.to_string()])?;
+    modules::account_store::record_event(&data_dir, account_id, "archived", None);
     
-    if account_path.exists() {
-        fs::remove_file(&account_path)
-            .map_err(|e| format!("删除账号文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 设置账号的反代调度优先级
+pub fn set_account_priority(account_id: &str, priority: i32) -> Result<Account, String> {
+    let mut account = load_account(account_id)?;
+    account.priority = priority;
+    save_account(&account)?;
+    Ok(account)
+}
+
+/// 设置账号的反代可用时段
+pub fn set_account_usage_windows(account_id: &str, windows: Vec<UsageWindow>) -> Result<Account, String> {
+    for window in &windows {
+        window.validate()?;
+    }
+    let mut account = load_account(account_id)?;
+    account.usage_windows = windows;
+    save_account(&account)?;
+    Ok(account)
+}
+
+/// 设置账号的独立出口代理 (None 表示使用全局上游代理)
+pub fn set_account_egress_proxy(account_id: &str, egress_proxy: Option<String>) -> Result<Account, String> {
+    let mut account = load_account(account_id)?;
+    account.egress_proxy = egress_proxy.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    save_account(&account)?;
+    Ok(account)
+}
+
+/// 校验手动指定的 project_id
+fn validate_project_id(project_id: &str) -> Result<(), String> {
+    let valid = project_id.len() <= 128
+        && project_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    if !valid {
+        return Err(tr("backend.errors.account.invalid_project_id", &[("project_id", project_id)]));
     }
-    
     Ok(())
 }
 
+/// 设置账号固定使用的 project_id (None 表示恢复自动解析)
+pub fn set_account_project_override(account_id: &str, project_id: Option<String>) -> Result<Account, String> {
+    let project_id = project_id.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(pid) = &project_id {
+        validate_project_id(pid)?;
+    }
+    let mut account = load_account(account_id)?;
+    account.project_id_override = project_id;
+    save_account(&account)?;
+    Ok(account)
+}
+
+/// 设置账号标签 (去除首尾空白、空标签与重复标签)
+pub fn set_account_tags(account_id: &str, tags: Vec<String>) -> Result<Account, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    let mut account = load_account(account_id)?;
+    account.tags = normalized;
+    save_account(&account)?;
+    Ok(account)
+}
+
+/// 解除账号隔离
+pub fn release_quarantine(account_id: &str) -> Result<Account, String> {
+    let mut account = load_account(account_id)?;
+    account.quarantined = false;
+    account.quarantine_reason = None;
+    account.quarantined_at = None;
+    save_account(&account)?;
+    modules::account_store::record_event(&get_data_dir()?, account_id, "reenabled", Some("quarantine released"));
+    Ok(account)
+}
+
+/// 清除账号的 project_id 缓存并立即重新解析
+pub async fn re_resolve_project_id(account_id: &str) -> Result<String, String> {
+    crate::proxy::project_resolver::invalidate_project_id(account_id);
+
+    let mut account = load_account(account_id)?;
+    account.token = modules::oauth::ensure_fresh_token(&account.token).await?;
+    let (project_id, outcome) = crate::proxy::project_resolver::fetch_project_id(&account.token.access_token).await?;
+    crate::proxy::project_resolver::record_outcome(account_id, &project_id, &outcome);
+    account.token.project_id = Some(project_id.clone());
+    save_account(&account)?;
+
+    crate::proxy::project_resolver::remember_project_id(account_id, &project_id);
+    modules::logger::log_info(&format!("已重新解析 {} 的 project_id: {}", account.email, project_id));
+    Ok(project_id)
+}
+
+/// 发现账号可访问的全部项目并保存 (默认项目排在最前)
+pub async fn discover_projects(account_id: &str) -> Result<Vec<String>, String> {
+    let mut account = load_account(account_id)?;
+    account.token = modules::oauth::ensure_fresh_token(&account.token).await?;
+
+    let mut projects = crate::proxy::project_resolver::list_projects(&account.token.access_token).await?;
+    if let Some(default) = account.token.project_id.clone() {
+        projects.retain(|p| p != &default);
+        projects.insert(0, default);
+    }
+    account.projects = projects.clone();
+    save_account(&account)?;
+
+    modules::logger::log_info(&format!("已发现 {} 的 {} 个项目", account.email, projects.len()));
+    Ok(projects)
+}
+
 /// 批量删除账号 (原子性操作索引)
 pub fn delete_accounts(account_ids: &[String]) -> Result<(), String> {
     let _lock = ACCOUNT_INDEX_LOCK.lock().map_err(|e| format!("获取锁失败: {}", e))?;
     let mut index = load_account_index()?;
     
-    let accounts_dir = get_accounts_dir()?;
-    
     for account_id in account_ids {
         // 从索引中移除
         index.accounts.retain(|s| &s.id != account_id);
@@ -314,12 +429,6 @@ pub fn delete_accounts(account_ids: &[String]) -> Result<(), String> {
         if index.current_account_id.as_deref() == Some(account_id) {
             index.current_account_id = None;
         }
-        
-        // 删除账号文件
-        let account_path = accounts_dir.join(format!("{}.json", account_id));
-        if account_path.exists() {
-            let _ = fs::remove_file(&account_path);
-        }
     }
     
     // 如果当前账号为空，尝试选取第一个作为默认
@@ -327,7 +436,77 @@ pub fn delete_accounts(account_ids: &[String]) -> Result<(), String> {
         index.current_account_id = index.accounts.first().map(|s| s.id.clone());
     }
     
-    save_account_index(&index)
+    save_account_index(&index)?;
+
+    let data_dir = get_data_dir()?;
+    modules::account_store::archive_accounts(&data_dir, account_ids)?;
+    for account_id in account_ids {
+        modules::account_store::record_event(&data_dir, account_id, "archived", None);
+    }
+    Ok(())
+}
+
+/// 列出已删除 (归档) 的账号
+pub fn list_archived_accounts() -> Result<Vec<modules::account_store::ArchivedAccount>, String> {
+    modules::account_store::list_archived(&get_data_dir()?)
+}
+
+/// 从归档中恢复账号 (追加到账号列表末尾)
+pub fn restore_archived_account(account_id: &str) -> Result<Account, String> {
+    let _lock = ACCOUNT_INDEX_LOCK.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    let data_dir = get_data_dir()?;
+    let mut index = load_account_index()?;
+
+    let archived = modules::account_store::list_archived(&data_dir)?
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("归档中不存在该账号: {}", account_id))?;
+    if index.accounts.iter().any(|s| same_identity(&s.email, &archived.email)) {
+        return Err(tr("backend.errors.account.duplicate_email", &[("email", &archived.email)]));
+    }
+
+    modules::account_store::restore_archived(&data_dir, account_id)?;
+    let account = load_account(account_id)?;
+    index.accounts.push(AccountSummary {
+        id: account.id.clone(),
+        email: account.email.clone(),
+        name: account.name.clone(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+    });
+    if index.current_account_id.is_none() {
+        index.current_account_id = Some(account.id.clone());
+    }
+    save_account_index(&index)?;
+    modules::account_store::record_event(&data_dir, account_id, "restored", None);
+    Ok(account)
+}
+
+/// 永久删除归档账号
+pub fn purge_archived_accounts(account_ids: &[String]) -> Result<usize, String> {
+    let data_dir = get_data_dir()?;
+    let purged = modules::account_store::purge_archived(&data_dir, account_ids, None)?;
+    for account_id in &purged {
+        modules::account_store::record_event(&data_dir, account_id, "deleted", None);
+    }
+    Ok(purged.len())
+}
+
+/// 按配置的保留天数清理过期的归档账号
+pub fn purge_expired_archives(retention_days: u32) -> Result<usize, String> {
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    let data_dir = get_data_dir()?;
+    let cutoff = chrono::Utc::now().timestamp() - retention_days as i64 * 86400;
+    let purged = modules::account_store::purge_archived(&data_dir, &[], Some(cutoff))?;
+    for account_id in &purged {
+        modules::account_store::record_event(&data_dir, account_id, "deleted", Some("archive retention expired"));
+    }
+    if !purged.is_empty() {
+        crate::modules::logger::log_info(&format!("已永久删除 {} 个超过保留期的归档账号", purged.len()));
+    }
+    Ok(purged.len())
 }
 
 /// 重新排序账号列表
@@ -364,8 +543,16 @@ pub fn reorder_accounts(account_ids: &[String]) -> Result<(), String> {
     save_account_index(&index)
 }
 
-/// 切换当前账号
+/// 切换当前账号 (写入 IDE 后总是重新启动 IDE)
 pub async fn switch_account(account_id: &str) -> Result<(), String> {
+    switch_ide_account(account_id, Some(true)).await.map(|_| ())
+}
+
+/// 将账号凭据写入 Antigravity IDE 并设为当前账号
+///
+/// 写入前先关闭正在运行的 IDE；`relaunch` 为 None 时只在 IDE 原本运行时重新启动。
+/// 返回 IDE 是否已重新启动
+async fn switch_ide_account(account_id: &str, relaunch: Option<bool>) -> Result<bool, String> {
     use crate::modules::{oauth, process, db, device};
     
     let index = {
@@ -375,7 +562,7 @@ pub async fn switch_account(account_id: &str) -> Result<(), String> {
     
     // 1. 验证账号存在
     if !index.accounts.iter().any(|s| s.id == account_id) {
-        return Err(format!("账号不存在: {}", account_id));
+        return Err(crate::modules::i18n::tr("backend.errors.account_not_found", &[("id", account_id)]));
     }
     
     let mut account = load_account(account_id)?;
@@ -391,9 +578,12 @@ pub async fn switch_account(account_id: &str) -> Result<(), String> {
         save_account(&account)?;
     }
     
-    // 3. 关闭 Antigravity (增加超时时间到 20 秒)
-    if process::is_antigravity_running() {
-        process::close_antigravity(20)?;
+    // 3. 关闭 Antigravity (等待正常退出，避免写入被运行中的 IDE 覆盖)
+    let was_running = crate::modules::ide::ensure_closed(true)?;
+
+    // 切换前备份 IDE 配置，便于撤销
+    if let Err(e) = crate::modules::ide_backup::create_backup(crate::modules::ide_backup::BackupReason::BeforeSwitch) {
+        crate::modules::logger::log_warn(&format!("切换前备份 IDE 配置失败: {}", e));
     }
 
     // 4. 写入设备指纹（缺失则生成并绑定），仅在切换时改 storage
@@ -423,22 +613,28 @@ pub async fn switch_account(account_id: &str) -> Result<(), String> {
 
     // 5. 获取数据库路径并备份
     let db_path = db::get_db_path()?;
-    if db_path.exists() {
-        let backup_path = db_path.with_extension("vscdb.backup");
+    let backup_path = db_path.with_extension("vscdb.backup");
+    let backed_up = db_path.exists();
+    if backed_up {
         fs::copy(&db_path, &backup_path)
             .map_err(|e| format!("备份数据库失败: {}", e))?;
     } else {
         crate::modules::logger::log_info("数据库不存在，跳过备份");
     }
 
-    // 6. 注入 Token
+    // 6. 注入 Token (失败时还原备份，避免 IDE 数据库处于半写入状态)
     crate::modules::logger::log_info("正在注入 Token 到数据库...");
-    db::inject_token(
+    if let Err(e) = db::inject_token(
         &db_path,
         &account.token.access_token,
         &account.token.refresh_token,
         account.token.expiry_timestamp,
-    )?;
+    ) {
+        if backed_up {
+            let _ = fs::copy(&backup_path, &db_path);
+        }
+        return Err(e);
+    }
 
     // 7. 更新工具内部状态
     {
@@ -452,10 +648,13 @@ pub async fn switch_account(account_id: &str) -> Result<(), String> {
     save_account(&account)?;
 
     // 8. 重启 Antigravity
-    process::start_antigravity()?;
+    let relaunch = relaunch.unwrap_or(was_running);
+    if relaunch {
+        process::start_antigravity()?;
+    }
     crate::modules::logger::log_info(&format!("账号切换完成: {}", account.email));
 
-    Ok(())
+    Ok(relaunch)
 }
 
 /// 获取设备指纹信息：当前 storage.json + 账号绑定的 profile
@@ -627,6 +826,21 @@ pub fn set_current_account_id(account_id: &str) -> Result<(), String> {
 /// 更新账号配额
 pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), String> {
     let mut account = load_account(account_id)?;
+    let previous: std::collections::HashMap<String, i32> = account
+        .quota
+        .as_ref()
+        .map(|q| q.models.iter().map(|m| (m.name.clone(), m.percentage)).collect())
+        .unwrap_or_default();
+    for model in &quota.models {
+        let before = previous.get(&model.name).copied();
+        crate::modules::notifications::quota_crossed(&account.email, &model.name, before, model.percentage);
+        if model.percentage <= 0 && before.is_none_or(|p| p > 0) {
+            crate::modules::webhooks::fire(
+                crate::models::WebhookEvent::QuotaExhausted,
+                serde_json::json!({ "account_id": account.id, "email": account.email, "model": model.name }),
+            );
+        }
+    }
     account.update_quota(quota);
 
     // --- 配额保护逻辑开始 ---
@@ -667,7 +881,7 @@ pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), St
 
                 // [兼容性] 如果该账号之前是因为账号级配额保护被禁用的，现在迁移到模型级
                 if account.proxy_disabled && 
-                   account.proxy_disabled_reason.as_ref().map_or(false, |r| r == "quota_protection") {
+                   account.proxy_disabled_reason.as_ref().is_some_and(|r| r == "quota_protection") {
                     crate::modules::logger::log_info(&format!(
                         "[Quota] 迁移账号 {} 从账号级保护到模型级保护",
                         account.email
@@ -697,6 +911,47 @@ pub fn export_accounts() -> Result<Vec<(String, String)>, String> {
     Ok(exports)
 }
 
+/// 需要重新授权事件 (refresh_token 被撤销)
+pub const REAUTH_REQUIRED_EVENT: &str = "account://reauth-required";
+
+/// 账号因上游封禁/停用被自动隔离
+pub const QUARANTINED_EVENT: &str = "account://quarantined";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedEvent {
+    pub account_id: String,
+    pub email: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReauthRequiredEvent {
+    pub account_id: String,
+    pub email: String,
+    pub reason: String,
+}
+
+/// 标记账号需要重新授权并禁用
+pub fn flag_needs_reauth(account: &mut Account, error: &str) {
+    account.disabled = true;
+    account.disabled_at = Some(chrono::Utc::now().timestamp());
+    account.disabled_reason = Some(format!("invalid_grant: {}", error));
+    account.needs_reauth = true;
+    let _ = save_account(account);
+}
+
+/// 通知前端账号需要重新授权
+pub fn emit_reauth_required(app: &crate::utils::app::AppHandle, account: &Account) {
+    crate::modules::notifications::reauth_required(&account.email);
+    let event = ReauthRequiredEvent {
+        account_id: account.id.clone(),
+        email: account.email.clone(),
+        reason: account.disabled_reason.clone().unwrap_or_default(),
+    };
+    crate::modules::webhooks::fire(crate::models::WebhookEvent::ReauthRequired, &event);
+    crate::utils::app::emit(app, REAUTH_REQUIRED_EVENT, event);
+}
+
 /// 带有重试机制的配额查询 (从 commands 移动到 modules 以便共享)
 pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppResult<QuotaData> {
     use crate::modules::oauth;
@@ -712,10 +967,7 @@ pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppR
                     "Disabling account {} due to invalid_grant during token refresh (quota check)",
                     account.email
                 ));
-                account.disabled = true;
-                account.disabled_at = Some(chrono::Utc::now().timestamp());
-                account.disabled_reason = Some(format!("invalid_grant: {}", e));
-                let _ = save_account(account);
+                flag_needs_reauth(account, &e);
             }
             return Err(AppError::OAuth(e));
         }
@@ -726,7 +978,7 @@ pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppR
         account.token = token.clone();
         
         // 重新获取用户名 (Token 刷新后顺便获取)
-        let name = if account.name.is_none() || account.name.as_ref().map_or(false, |n| n.trim().is_empty()) {
+        let name = if account.name.is_none() || account.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
             match oauth::get_user_info(&token.access_token).await {
                 Ok(user_info) => user_info.get_display_name(),
                 Err(_) => None
@@ -740,7 +992,7 @@ pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppR
     }
 
     // 0. 补充用户名 (如果 Token 没过期但也没用户名，或者上面没获取到)
-    if account.name.is_none() || account.name.as_ref().map_or(false, |n| n.trim().is_empty()) {
+    if account.name.is_none() || account.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
         modules::logger::log_info(&format!("账号 {} 缺少用户名，尝试获取...", account.email));
         // 使用更新后的 token
         match oauth::get_user_info(&account.token.access_token).await {
@@ -788,10 +1040,7 @@ pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppR
                                 "Disabling account {} due to invalid_grant during forced refresh (quota check)",
                                 account.email
                             ));
-                            account.disabled = true;
-                            account.disabled_at = Some(chrono::Utc::now().timestamp());
-                            account.disabled_reason = Some(format!("invalid_grant: {}", e));
-                            let _ = save_account(account);
+                            flag_needs_reauth(account, &e);
                         }
                         return Err(AppError::OAuth(e));
                     }
@@ -807,7 +1056,7 @@ pub async fn fetch_quota_with_retry(account: &mut Account) -> crate::error::AppR
                 );
                 
                 // 重新获取用户名
-                let name = if account.name.is_none() || account.name.as_ref().map_or(false, |n| n.trim().is_empty()) {
+                let name = if account.name.is_none() || account.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
                     match oauth::get_user_info(&token_res.access_token).await {
                         Ok(user_info) => user_info.get_display_name(),
                         Err(_) => None
@@ -950,3 +1199,38 @@ pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
         details,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_into_keeps_existing_fields_and_reports_changes() {
+        let mut old_token = TokenData::new("at".to_string(), "1//old".to_string(), 3600, None, Some("proj-1".to_string()), None);
+        old_token.session_id = Some("sess".to_string());
+        let mut account = Account::new("id".to_string(), "User@Example.com".to_string(), old_token);
+        account.name = Some("User".to_string());
+        account.disabled = true;
+        account.needs_reauth = true;
+
+        let token = TokenData::new("at2".to_string(), "1//new".to_string(), 3600, None, None, None);
+        let changes = merge_into(&mut account, None, token);
+
+        assert_eq!(changes, vec!["refresh_token", "access_token", "reenabled"]);
+        assert_eq!(account.name.as_deref(), Some("User"));
+        assert_eq!(account.token.project_id.as_deref(), Some("proj-1"));
+        assert_eq!(account.token.session_id.as_deref(), Some("sess"));
+        assert!(!account.disabled && !account.needs_reauth);
+
+        let same = account.token.clone();
+        assert!(merge_into(&mut account, Some("User".to_string()), same).is_empty());
+        assert!(same_identity(" user@example.com", "USER@example.com"));
+    }
+
+    #[test]
+    fn test_validate_project_id() {
+        assert!(validate_project_id("my-project-123").is_ok());
+        assert!(validate_project_id("bad project").is_err());
+        assert!(validate_project_id("../etc").is_err());
+    }
+}
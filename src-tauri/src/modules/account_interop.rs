@@ -0,0 +1,179 @@
+// 与其他反代工具互通账号凭据
+//
+// 导入: 选择单个凭据文件或整个凭据目录 (目录下的每个 .json 文件)，按批量导入的规则解析，
+// 兼容 CLIProxyAPI 的 auth 文件 ({"type": "antigravity", "refresh_token": ...} 或
+// {"token": {"refresh_token": ...}}) 以及 gcli2api / google-auth 的凭据文件 ({"client_id", "refresh_token", ...})。
+// 导出: 每个账号写入一个 JSON 文件，可直接放入对方的凭据目录:
+// - CLIProxyAPI: antigravity-<邮箱>.json
+// - gcli2api:    <邮箱>.json (google.oauth2 Credentials 格式，附带本应用的 OAuth 客户端)
+// 导出的文件包含明文 refresh_token，请妥善保管。
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::models::Account;
+use crate::modules::batch_import::{self, BatchImportReport, ImportRow};
+use crate::modules::{account, logger, oauth};
+use crate::modules::i18n::tr;
+
+/// 凭据文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteropFormat {
+    CliProxyApi,
+    Gcli2api,
+}
+
+fn rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// 单个账号的凭据 JSON
+fn credential_json(account: &Account, format: InteropFormat) -> Value {
+    let token = &account.token;
+    match format {
+        InteropFormat::CliProxyApi => json!({
+            "type": "antigravity",
+            "email": account.email,
+            "access_token": token.access_token,
+            "refresh_token": token.refresh_token,
+            "expires_in": token.expires_in,
+            "timestamp": (token.expiry_timestamp - token.expires_in) * 1000,
+            "expired": rfc3339(token.expiry_timestamp),
+            "project_id": token.project_id,
+        }),
+        InteropFormat::Gcli2api => json!({
+            "client_id": oauth::CLIENT_ID,
+            "client_secret": oauth::CLIENT_SECRET,
+            "token": token.access_token,
+            "refresh_token": token.refresh_token,
+            "scopes": oauth::SCOPES,
+            "token_uri": oauth::TOKEN_URL,
+            "project_id": token.project_id,
+            "expiry": rfc3339(token.expiry_timestamp),
+            "email": account.email,
+        }),
+    }
+}
+
+/// 凭据文件名 (去掉邮箱中不适合作为文件名的字符)
+fn credential_file_name(email: &str, format: InteropFormat) -> String {
+    let safe: String = email
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '_' | '-' | '+') { c } else { '_' })
+        .collect();
+    match format {
+        InteropFormat::CliProxyApi => format!("antigravity-{}.json", safe),
+        InteropFormat::Gcli2api => format!("{}.json", safe),
+    }
+}
+
+/// 导出账号凭据到目录，`account_ids` 为空时导出全部账号，返回导出数量
+pub fn export_credentials(dir: &str, format: InteropFormat, account_ids: Option<&[String]>) -> Result<usize, String> {
+    let dir = Path::new(dir);
+    std::fs::create_dir_all(dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+
+    let accounts: Vec<Account> = account::list_accounts()?
+        .into_iter()
+        .filter(|a| account_ids.is_none_or(|ids| ids.contains(&a.id)))
+        .collect();
+    for account in &accounts {
+        let content = serde_json::to_string_pretty(&credential_json(account, format)).map_err(|e| e.to_string())?;
+        let path = dir.join(credential_file_name(&account.email, format));
+        std::fs::write(&path, content).map_err(|e| format!("写入凭据文件失败 ({}): {}", path.display(), e))?;
+    }
+
+    logger::log_info(&format!("已导出 {} 个账号凭据 ({:?}): {}", accounts.len(), format, dir.display()));
+    Ok(accounts.len())
+}
+
+/// 读取凭据目录下的所有 .json 文件，行号按文件顺序连续编号
+fn rows_from_dir(dir: &Path) -> Result<Vec<ImportRow>, String> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("读取凭据目录失败: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+        .collect();
+    files.sort();
+
+    let mut rows = Vec::new();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(&file) else { continue };
+        match batch_import::parse_import_content(&content) {
+            Ok(parsed) => rows.extend(parsed),
+            // 目录中可能有对方工具的其他配置文件
+            Err(e) => logger::log_warn(&format!("跳过凭据文件 {}: {}", file.display(), e)),
+        }
+    }
+    if rows.is_empty() {
+        return Err(tr("backend.errors.account.no_credential_files", &[]));
+    }
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.row = i + 1;
+    }
+    Ok(rows)
+}
+
+/// 从其他工具的凭据文件或凭据目录导入账号
+pub async fn import_credentials(path: &str) -> Result<BatchImportReport, String> {
+    let path = Path::new(path);
+    if path.is_dir() {
+        batch_import::import_rows(rows_from_dir(path)?).await
+    } else {
+        batch_import::import_accounts_from_file(&path.to_string_lossy()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenData;
+
+    const TOKEN: &str = "1//0aaaaaaaaaaaaaaaa";
+
+    fn sample_account() -> Account {
+        let mut token = TokenData::new("ya29.x".to_string(), TOKEN.to_string(), 3600, None, Some("proj-1".to_string()), None);
+        token.expiry_timestamp = 1_760_000_000;
+        Account::new("id-1".to_string(), "a/b@example.com".to_string(), token)
+    }
+
+    #[test]
+    fn test_exported_credentials_parse_back() {
+        let account = sample_account();
+        for format in [InteropFormat::CliProxyApi, InteropFormat::Gcli2api] {
+            let content = serde_json::to_string(&credential_json(&account, format)).unwrap();
+            let rows = batch_import::parse_import_content(&content).unwrap();
+            assert_eq!(rows[0].refresh_token.as_deref(), Some(TOKEN));
+            assert_eq!(rows[0].email.as_deref(), Some("a/b@example.com"));
+        }
+
+        let cli = credential_json(&account, InteropFormat::CliProxyApi);
+        assert_eq!(cli["type"], "antigravity");
+        assert_eq!(cli["expired"], "2025-10-09T08:53:20Z");
+        assert_eq!(credential_file_name(&account.email, InteropFormat::CliProxyApi), "antigravity-a_b@example.com.json");
+        assert_eq!(credential_file_name(&account.email, InteropFormat::Gcli2api), "a_b@example.com.json");
+    }
+
+    #[test]
+    fn test_rows_from_dir() {
+        let dir = std::env::temp_dir().join(format!("ag-interop-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(rows_from_dir(&dir).is_err());
+
+        std::fs::write(dir.join("a.json"), format!(r#"{{"type":"antigravity","email":"a@example.com","refresh_token":"{}"}}"#, TOKEN)).unwrap();
+        std::fs::write(dir.join("b.json"), r#"{"client_id":"x","token":"ya29.y","refresh_token":"1//0bbbbbbbbbbbbbbbb"}"#).unwrap();
+        std::fs::write(dir.join("config.yaml"), "port: 8317").unwrap();
+        std::fs::write(dir.join("empty.json"), "").unwrap();
+
+        let rows = rows_from_dir(&dir).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].row, rows[0].email.as_deref()), (1, Some("a@example.com")));
+        assert_eq!((rows[1].row, rows[1].refresh_token.as_deref()), (2, Some("1//0bbbbbbbbbbbbbbbb")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
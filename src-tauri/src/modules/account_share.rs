@@ -0,0 +1,143 @@
+// 单账号加密分享
+//
+// 将一个账号的凭据打包为口令加密的紧凑字符串 (同时提供二维码)，
+// 在另一台机器上粘贴或扫码并输入口令即可导入，无需导出整个账号库。
+// 格式: `agshare1.` + base64url(salt | nonce | AES-256-GCM 密文)
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Account, TokenData};
+use crate::utils::crypto::{self, EncryptedBlob};
+use crate::modules::i18n::tr;
+
+const SHARE_PREFIX: &str = "agshare1.";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 加密前的分享内容 (只包含重建账号所需的最少字段)
+#[derive(Debug, Serialize, Deserialize)]
+struct SharePayload {
+    email: String,
+    #[serde(default)]
+    name: Option<String>,
+    refresh_token: String,
+    #[serde(default)]
+    project_id: Option<String>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountShare {
+    pub email: String,
+    pub share: String,
+    /// 分享串对应的二维码 (SVG)
+    pub qr_svg: String,
+    /// 点击即可在另一台机器上打开导入流程的深度链接
+    pub link: String,
+}
+
+fn seal(payload: &SharePayload, passphrase: &str) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err(tr("backend.errors.account_share.empty_passphrase", &[]));
+    }
+    let plaintext = serde_json::to_vec(payload).map_err(|e| format!("序列化分享内容失败: {}", e))?;
+    let blob = crypto::encrypt_with_passphrase(passphrase, &plaintext)?;
+
+    let mut packed = Vec::new();
+    for field in [&blob.salt, &blob.nonce, &blob.ciphertext] {
+        packed.extend(general_purpose::STANDARD.decode(field).map_err(|e| e.to_string())?);
+    }
+    Ok(format!("{}{}", SHARE_PREFIX, general_purpose::URL_SAFE_NO_PAD.encode(packed)))
+}
+
+fn unseal(share: &str, passphrase: &str) -> Result<SharePayload, String> {
+    let encoded = share
+        .trim()
+        .strip_prefix(SHARE_PREFIX)
+        .ok_or_else(|| "不是有效的账号分享串".to_string())?;
+    let packed = general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|_| "账号分享串已损坏".to_string())?;
+    if packed.len() <= SALT_LEN + NONCE_LEN {
+        return Err(tr("backend.errors.account_share.corrupted", &[]));
+    }
+
+    let (salt, rest) = packed.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let blob = EncryptedBlob {
+        kdf: "argon2id".to_string(),
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    let plaintext = crypto::decrypt_with_passphrase(passphrase, &blob)?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("分享内容解析失败: {}", e))
+}
+
+/// 将单个账号导出为加密分享串与二维码
+pub fn export_account_share(account_id: &str, passphrase: &str) -> Result<AccountShare, String> {
+    let account = crate::modules::account::load_account(account_id)?;
+    let payload = SharePayload {
+        email: account.email.clone(),
+        name: account.name.clone(),
+        refresh_token: account.token.refresh_token.clone(),
+        project_id: account.token.project_id.clone(),
+    };
+    let share = seal(&payload, passphrase)?;
+    let qr_svg = crate::utils::qr::QrCode::encode(&share)?.to_svg();
+
+    crate::modules::logger::log_info(&format!("已导出账号分享串: {}", account.email));
+    let link = crate::modules::deep_link::import_account_link(&share);
+    Ok(AccountShare { email: account.email, share, qr_svg, link })
+}
+
+/// 导入加密分享串 (按邮箱合并到已有账号)
+pub async fn import_account_share(share: &str, passphrase: &str) -> Result<Account, String> {
+    let payload = unseal(share, passphrase)?;
+
+    // 刷新一次以校验凭据仍然有效，并取得新的 access_token
+    let token_res = crate::modules::oauth::refresh_access_token(&payload.refresh_token).await?;
+    let token = TokenData::new(
+        token_res.access_token,
+        token_res.refresh_token.unwrap_or(payload.refresh_token),
+        token_res.expires_in,
+        Some(payload.email.clone()),
+        payload.project_id,
+        None,
+    );
+
+    let (account, _) = crate::modules::account::upsert_account_detailed(payload.email, payload.name, token)?;
+    crate::modules::logger::log_info(&format!("已从分享串导入账号: {}", account.email));
+    Ok(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_roundtrip_fits_in_qr() {
+        let payload = SharePayload {
+            email: "a@example.com".to_string(),
+            name: Some("A".to_string()),
+            refresh_token: format!("1//{}", "r".repeat(100)),
+            project_id: Some("project-a".to_string()),
+        };
+
+        let share = seal(&payload, "passphrase").unwrap();
+        assert!(share.starts_with(SHARE_PREFIX));
+        assert!(!share.contains("a@example.com"));
+        assert!(crate::utils::qr::QrCode::encode(&share).is_ok());
+
+        let opened = unseal(&format!("  {}\n", share), "passphrase").unwrap();
+        assert_eq!(opened.email, "a@example.com");
+        assert_eq!(opened.refresh_token, payload.refresh_token);
+        assert_eq!(opened.project_id.as_deref(), Some("project-a"));
+
+        assert!(unseal(&share, "wrong").is_err());
+        assert!(unseal("agshare1.!!", "passphrase").is_err());
+        assert!(unseal("1//plain", "passphrase").is_err());
+        assert!(seal(&payload, "").is_err());
+    }
+}
@@ -0,0 +1,589 @@
+// SQLite 账号存储
+//
+// 账号数据保存在 `accounts.db` 中 (accounts / tokens / account_index / account_events / archived_accounts)，
+// 替代原先的 accounts.json 索引 + accounts/*.json 单文件存储，避免并发写入导致文件损坏。
+// 首次打开时自动从旧文件迁移，迁移完成后旧文件重命名为 *.bak 保留。
+//
+// 账号以与旧文件相同的 JSON 结构存储 (Token 字段仍由 token_vault 加密)，
+// `token` 部分单独存放在 tokens 表中。请求用量统计仍在 proxy_logs.db 中。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::models::{AccountIndex, AccountSummary};
+use crate::modules::i18n::tr;
+
+const DB_FILE: &str = "accounts.db";
+const LEGACY_INDEX: &str = "accounts.json";
+const LEGACY_DIR: &str = "accounts";
+const MIGRATED_KEY: &str = "legacy_files_migrated";
+/// 每个账号最多保留的事件数
+const MAX_EVENTS_PER_ACCOUNT: i64 = 500;
+
+/// 已归档 (软删除) 的账号
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedAccount {
+    pub id: String,
+    pub email: String,
+    pub name: Option<String>,
+    pub archived_at: i64,
+}
+
+/// 账号事件 (新增、删除、禁用、隔离等)
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountEvent {
+    pub id: i64,
+    pub account_id: String,
+    pub kind: String,
+    pub detail: Option<String>,
+    pub created_at: i64,
+}
+
+pub fn get_store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DB_FILE)
+}
+
+/// 已完成建表与旧文件迁移的数据目录
+static INITIALIZED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+
+/// 打开账号数据库 (每个数据目录首次打开时建表并迁移旧文件)
+pub fn open(data_dir: &Path) -> Result<Connection, String> {
+    let path = get_store_path(data_dir);
+    // 数据库文件被删除后 (如删除档案) 需要重新初始化
+    let existed = path.exists();
+    let mut conn = Connection::open(&path).map_err(|e| format!("打开账号数据库失败: {}", e))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5)).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA foreign_keys = ON;").map_err(|e| e.to_string())?;
+
+    let mut initialized = INITIALIZED.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if !existed || !initialized.contains(data_dir) {
+        init_schema(&mut conn, data_dir)?;
+        initialized.insert(data_dir.to_path_buf());
+    }
+    Ok(conn)
+}
+
+/// 建表并迁移旧文件 (只在每个数据目录首次打开时执行)
+fn init_schema(conn: &mut Connection, data_dir: &Path) -> Result<(), String> {
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS accounts (
+            id TEXT PRIMARY KEY,
+            email TEXT NOT NULL,
+            data TEXT NOT NULL,
+            updated_at INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_accounts_email ON accounts (email);
+        CREATE TABLE IF NOT EXISTS tokens (
+            account_id TEXT PRIMARY KEY REFERENCES accounts (id) ON DELETE CASCADE,
+            data TEXT NOT NULL,
+            expiry_timestamp INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS account_index (
+            account_id TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            email TEXT NOT NULL,
+            name TEXT,
+            created_at INTEGER NOT NULL DEFAULT 0,
+            last_used INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS account_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            detail TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_account_events ON account_events (account_id, created_at DESC);
+        CREATE TABLE IF NOT EXISTS archived_accounts (
+            id TEXT PRIMARY KEY,
+            email TEXT NOT NULL,
+            data TEXT NOT NULL,
+            archived_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT
+        );",
+    )
+    .map_err(|e| format!("初始化账号数据库失败: {}", e))?;
+
+    if get_meta(conn, MIGRATED_KEY)?.is_none() {
+        migrate_legacy_files(conn, data_dir)?;
+    }
+    Ok(())
+}
+
+fn get_meta(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map(|v| v.flatten())
+        .map_err(|e| e.to_string())
+}
+
+fn set_meta(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// 从 accounts.json + accounts/*.json 迁移，返回迁移的账号数
+fn migrate_legacy_files(conn: &mut Connection, data_dir: &Path) -> Result<usize, String> {
+    let index_path = data_dir.join(LEGACY_INDEX);
+    let accounts_dir = data_dir.join(LEGACY_DIR);
+
+    let index: Option<AccountIndex> = std::fs::read_to_string(&index_path)
+        .ok()
+        .filter(|c| !c.trim().is_empty())
+        .and_then(|c| serde_json::from_str(&c).ok());
+
+    let mut accounts = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&accounts_dir) {
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|c| serde_json::from_str::<Value>(&c).map_err(|e| e.to_string())) {
+                Ok(value) if value.get("id").and_then(|v| v.as_str()).is_some() => accounts.push(value),
+                Ok(_) => crate::modules::logger::log_warn(&format!("跳过无效的账号文件: {:?}", path)),
+                Err(e) => crate::modules::logger::log_warn(&format!("跳过无法解析的账号文件 {:?}: {}", path, e)),
+            }
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for account in &accounts {
+        write_account_tx(&tx, account)?;
+    }
+    if let Some(index) = &index {
+        write_index_tx(&tx, index)?;
+    }
+    set_meta(&tx, MIGRATED_KEY, Some(&chrono::Utc::now().timestamp().to_string()))?;
+    tx.commit().map_err(|e| format!("迁移账号数据失败: {}", e))?;
+
+    // 旧文件保留为备份，不再读写
+    if index_path.exists() {
+        let _ = std::fs::rename(&index_path, data_dir.join(format!("{}.bak", LEGACY_INDEX)));
+    }
+    if accounts_dir.exists() {
+        let _ = std::fs::rename(&accounts_dir, data_dir.join(format!("{}.bak", LEGACY_DIR)));
+    }
+
+    if !accounts.is_empty() {
+        crate::modules::logger::log_info(&format!("已将 {} 个账号从 JSON 文件迁移到 {}", accounts.len(), DB_FILE));
+    }
+    Ok(accounts.len())
+}
+
+fn write_account_tx(conn: &Connection, account: &Value) -> Result<(), String> {
+    let id = account.get("id").and_then(|v| v.as_str()).ok_or_else(|| tr("backend.errors.account.missing_id", &[]))?;
+    let email = account.get("email").and_then(|v| v.as_str()).unwrap_or_default();
+
+    let mut data = account.clone();
+    let token = data.as_object_mut().and_then(|o| o.remove("token")).unwrap_or(Value::Null);
+    let expiry = token.get("expiry_timestamp").and_then(|v| v.as_i64()).unwrap_or(0);
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO accounts (id, email, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET email = excluded.email, data = excluded.data, updated_at = excluded.updated_at",
+        params![id, email, data.to_string(), now],
+    )
+    .map_err(|e| format!("保存账号数据失败: {}", e))?;
+    conn.execute(
+        "INSERT INTO tokens (account_id, data, expiry_timestamp) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id) DO UPDATE SET data = excluded.data, expiry_timestamp = excluded.expiry_timestamp",
+        params![id, token.to_string(), expiry],
+    )
+    .map_err(|e| format!("保存账号 Token 失败: {}", e))?;
+    Ok(())
+}
+
+fn write_index_tx(conn: &Connection, index: &AccountIndex) -> Result<(), String> {
+    conn.execute("DELETE FROM account_index", []).map_err(|e| e.to_string())?;
+    for (position, summary) in index.accounts.iter().enumerate() {
+        conn.execute(
+            "INSERT OR REPLACE INTO account_index (account_id, position, email, name, created_at, last_used)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![summary.id, position as i64, summary.email, summary.name, summary.created_at, summary.last_used],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    set_meta(conn, "index_version", Some(&index.version))?;
+    set_meta(conn, "current_account_id", index.current_account_id.as_deref())
+}
+
+fn row_to_account(data: String, token: Option<String>) -> Result<Value, String> {
+    let mut value: Value = serde_json::from_str(&data).map_err(|e| format!("解析账号数据失败: {}", e))?;
+    let token = match token {
+        Some(t) => serde_json::from_str(&t).map_err(|e| format!("解析账号 Token 失败: {}", e))?,
+        None => Value::Null,
+    };
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("token".to_string(), token);
+    }
+    Ok(value)
+}
+
+/// 读取账号 (Token 字段保持加密状态)
+pub fn read_account(data_dir: &Path, account_id: &str) -> Result<Option<Value>, String> {
+    let conn = open(data_dir)?;
+    let row: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT a.data, t.data FROM accounts a LEFT JOIN tokens t ON t.account_id = a.id WHERE a.id = ?1",
+            [account_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("读取账号数据失败: {}", e))?;
+    row.map(|(data, token)| row_to_account(data, token)).transpose()
+}
+
+/// 写入账号 (不存在则新增)
+pub fn write_account(data_dir: &Path, account: &Value) -> Result<(), String> {
+    let mut conn = open(data_dir)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    write_account_tx(&tx, account)?;
+    tx.commit().map_err(|e| format!("保存账号数据失败: {}", e))
+}
+
+/// 在同一事务中读取、修改并写回账号，避免并发写入互相覆盖
+pub fn update_account<F>(data_dir: &Path, account_id: &str, f: F) -> Result<Value, String>
+where
+    F: FnOnce(&mut Value),
+{
+    let mut conn = open(data_dir)?;
+    let tx = conn
+        .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let row: Option<(String, Option<String>)> = tx
+        .query_row(
+            "SELECT a.data, t.data FROM accounts a LEFT JOIN tokens t ON t.account_id = a.id WHERE a.id = ?1",
+            [account_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("读取账号数据失败: {}", e))?;
+    let (data, token) = row.ok_or_else(|| format!("账号不存在: {}", account_id))?;
+    let mut account = row_to_account(data, token)?;
+    f(&mut account);
+    write_account_tx(&tx, &account)?;
+    tx.commit().map_err(|e| format!("保存账号数据失败: {}", e))?;
+    Ok(account)
+}
+
+/// 列出所有账号 (包括未出现在索引中的账号)
+pub fn list_accounts(data_dir: &Path) -> Result<Vec<Value>, String> {
+    let conn = open(data_dir)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.data, t.data FROM accounts a
+             LEFT JOIN tokens t ON t.account_id = a.id
+             LEFT JOIN account_index i ON i.account_id = a.id
+             ORDER BY i.position IS NULL, i.position, a.email",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut accounts = Vec::new();
+    for row in rows {
+        let (data, token) = row.map_err(|e| e.to_string())?;
+        match row_to_account(data, token) {
+            Ok(account) => accounts.push(account),
+            Err(e) => tracing::debug!("Skipping unreadable account row: {}", e),
+        }
+    }
+    Ok(accounts)
+}
+
+/// 各账号当前内容的指纹 (account_id -> hash)，用于检测外部修改
+pub fn fingerprints(data_dir: &Path) -> Result<HashMap<String, u64>, String> {
+    let conn = open(data_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT a.id, a.data, t.data FROM accounts a LEFT JOIN tokens t ON t.account_id = a.id")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let mut hasher = DefaultHasher::new();
+            row.get::<_, String>(1)?.hash(&mut hasher);
+            row.get::<_, Option<String>>(2)?.hash(&mut hasher);
+            Ok((row.get::<_, String>(0)?, hasher.finish()))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>().map_err(|e| format!("读取账号数据失败: {}", e))
+}
+
+/// 数据库文件 (含 WAL) 的最近修改时间
+pub fn modified_at(data_dir: &Path) -> Option<std::time::SystemTime> {
+    let db = get_store_path(data_dir);
+    let wal = data_dir.join(format!("{}-wal", DB_FILE));
+    [db, wal]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// 归档账号: 完整数据 (含 Token) 移入 archived_accounts，并从账号列表中移除
+pub fn archive_accounts(data_dir: &Path, account_ids: &[String]) -> Result<(), String> {
+    let mut conn = open(data_dir)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().timestamp();
+    for id in account_ids {
+        let row: Option<(String, String, Option<String>)> = tx
+            .query_row(
+                "SELECT a.email, a.data, t.data FROM accounts a LEFT JOIN tokens t ON t.account_id = a.id WHERE a.id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| format!("读取账号数据失败: {}", e))?;
+        let Some((email, data, token)) = row else { continue };
+        let account = row_to_account(data, token)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO archived_accounts (id, email, data, archived_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, email, account.to_string(), now],
+        )
+        .map_err(|e| format!("归档账号失败: {}", e))?;
+        tx.execute("DELETE FROM accounts WHERE id = ?1", [id]).map_err(|e| format!("归档账号失败: {}", e))?;
+    }
+    tx.commit().map_err(|e| format!("归档账号失败: {}", e))
+}
+
+/// 列出已归档的账号 (最近归档的在前)
+pub fn list_archived(data_dir: &Path) -> Result<Vec<ArchivedAccount>, String> {
+    let conn = open(data_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT id, email, data, archived_at FROM archived_accounts ORDER BY archived_at DESC, email")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let data: String = row.get(2)?;
+            let name = serde_json::from_str::<Value>(&data)
+                .ok()
+                .and_then(|v| v.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()));
+            Ok(ArchivedAccount { id: row.get(0)?, email: row.get(1)?, name, archived_at: row.get(3)? })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| format!("读取归档账号失败: {}", e))
+}
+
+/// 从归档中恢复账号，返回恢复后的账号数据
+pub fn restore_archived(data_dir: &Path, account_id: &str) -> Result<Value, String> {
+    let mut conn = open(data_dir)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let data: String = tx
+        .query_row("SELECT data FROM archived_accounts WHERE id = ?1", [account_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("读取归档账号失败: {}", e))?
+        .ok_or_else(|| format!("归档中不存在该账号: {}", account_id))?;
+    let account: Value = serde_json::from_str(&data).map_err(|e| format!("解析归档账号失败: {}", e))?;
+    write_account_tx(&tx, &account)?;
+    tx.execute("DELETE FROM archived_accounts WHERE id = ?1", [account_id])
+        .map_err(|e| format!("恢复账号失败: {}", e))?;
+    tx.commit().map_err(|e| format!("恢复账号失败: {}", e))?;
+    Ok(account)
+}
+
+/// 永久删除归档账号，`older_than` 为空时删除 `account_ids` 指定的账号，否则删除归档时间早于该时间戳的账号
+///
+/// 返回被删除的账号 ID
+pub fn purge_archived(data_dir: &Path, account_ids: &[String], older_than: Option<i64>) -> Result<Vec<String>, String> {
+    let conn = open(data_dir)?;
+    let ids: Vec<String> = match older_than {
+        Some(ts) => {
+            let mut stmt = conn
+                .prepare("SELECT id FROM archived_accounts WHERE archived_at < ?1")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt.query_map([ts], |row| row.get(0)).map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+        }
+        None => account_ids.to_vec(),
+    };
+    let mut purged = Vec::new();
+    for id in ids {
+        let removed = conn
+            .execute("DELETE FROM archived_accounts WHERE id = ?1", [&id])
+            .map_err(|e| format!("删除归档账号失败: {}", e))?;
+        if removed > 0 {
+            purged.push(id);
+        }
+    }
+    Ok(purged)
+}
+
+pub fn load_index(data_dir: &Path) -> Result<AccountIndex, String> {
+    let conn = open(data_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT account_id, email, name, created_at, last_used FROM account_index ORDER BY position")
+        .map_err(|e| e.to_string())?;
+    let accounts = stmt
+        .query_map([], |row| {
+            Ok(AccountSummary {
+                id: row.get(0)?,
+                email: row.get(1)?,
+                name: row.get(2)?,
+                created_at: row.get(3)?,
+                last_used: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取账号索引失败: {}", e))?;
+
+    let mut index = AccountIndex::new();
+    if let Some(version) = get_meta(&conn, "index_version")? {
+        index.version = version;
+    }
+    index.accounts = accounts;
+    index.current_account_id = get_meta(&conn, "current_account_id")?;
+    Ok(index)
+}
+
+pub fn save_index(data_dir: &Path, index: &AccountIndex) -> Result<(), String> {
+    let mut conn = open(data_dir)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    write_index_tx(&tx, index)?;
+    tx.commit().map_err(|e| format!("保存账号索引失败: {}", e))
+}
+
+/// 记录账号事件 (失败仅记录日志)
+pub fn record_event(data_dir: &Path, account_id: &str, kind: &str, detail: Option<&str>) {
+    let result = open(data_dir).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO account_events (account_id, kind, detail, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![account_id, kind, detail, chrono::Utc::now().timestamp()],
+        )
+        .and_then(|_| {
+            // 仅保留最近的事件，避免高频事件 (如 429) 无限增长
+            conn.execute(
+                "DELETE FROM account_events WHERE account_id = ?1 AND id NOT IN (
+                    SELECT id FROM account_events WHERE account_id = ?1 ORDER BY id DESC LIMIT ?2
+                )",
+                params![account_id, MAX_EVENTS_PER_ACCOUNT],
+            )
+        })
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        tracing::debug!("Failed to record account event {} for {}: {}", kind, account_id, e);
+    }
+}
+
+/// 查询账号事件 (最新的在前)
+pub fn list_events(data_dir: &Path, account_id: Option<&str>, limit: usize) -> Result<Vec<AccountEvent>, String> {
+    let conn = open(data_dir)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, account_id, kind, detail, created_at FROM account_events
+             WHERE ?1 IS NULL OR account_id = ?1
+             ORDER BY created_at DESC, id DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let events = stmt
+        .query_map(params![account_id, limit as i64], |row| {
+            Ok(AccountEvent {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                kind: row.get(2)?,
+                detail: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取账号事件失败: {}", e))?;
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_data_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ag-account-store-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_migrates_legacy_files_and_roundtrips() {
+        let dir = temp_data_dir();
+        std::fs::create_dir_all(dir.join(LEGACY_DIR)).unwrap();
+        for id in ["b", "a"] {
+            let account = serde_json::json!({
+                "id": id,
+                "email": format!("{}@example.com", id),
+                "token": {"access_token": "at", "refresh_token": "rt", "expiry_timestamp": 100},
+                "created_at": 1,
+                "last_used": 1,
+            });
+            std::fs::write(dir.join(LEGACY_DIR).join(format!("{}.json", id)), account.to_string()).unwrap();
+        }
+        let mut index = AccountIndex::new();
+        index.accounts = ["b", "a"]
+            .iter()
+            .map(|id| AccountSummary { id: id.to_string(), email: format!("{}@example.com", id), name: None, created_at: 1, last_used: 1 })
+            .collect();
+        index.current_account_id = Some("a".to_string());
+        std::fs::write(dir.join(LEGACY_INDEX), serde_json::to_string(&index).unwrap()).unwrap();
+
+        let migrated = load_index(&dir).unwrap();
+        assert_eq!(migrated.accounts.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!(migrated.current_account_id.as_deref(), Some("a"));
+        assert!(!dir.join(LEGACY_DIR).exists());
+        assert!(dir.join(format!("{}.bak", LEGACY_INDEX)).exists());
+
+        let listed = list_accounts(&dir).unwrap();
+        assert_eq!(listed.iter().map(|a| a["id"].as_str().unwrap()).collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!(listed[0]["token"]["refresh_token"], "rt");
+
+        let updated = update_account(&dir, "a", |a| a["disabled"] = Value::Bool(true)).unwrap();
+        assert_eq!(updated["token"]["access_token"], "at");
+        assert_eq!(read_account(&dir, "a").unwrap().unwrap()["disabled"], true);
+        assert!(update_account(&dir, "missing", |_| {}).is_err());
+
+        let before = fingerprints(&dir).unwrap();
+        update_account(&dir, "b", |b| b["token"]["access_token"] = Value::String("at2".to_string())).unwrap();
+        let after = fingerprints(&dir).unwrap();
+        assert_eq!(before["a"], after["a"]);
+        assert_ne!(before["b"], after["b"]);
+
+        record_event(&dir, "a", "disabled", Some("test"));
+        assert_eq!(list_events(&dir, Some("a"), 10).unwrap()[0].kind, "disabled");
+
+        archive_accounts(&dir, &["a".to_string()]).unwrap();
+        assert!(read_account(&dir, "a").unwrap().is_none());
+        let conn = open(&dir).unwrap();
+        let tokens: i64 = conn.query_row("SELECT COUNT(*) FROM tokens", [], |r| r.get(0)).unwrap();
+        assert_eq!(tokens, 1);
+
+        // 归档保留完整数据 (含 Token)，可恢复
+        let archived = list_archived(&dir).unwrap();
+        assert_eq!(archived.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), vec!["a"]);
+        let restored = restore_archived(&dir, "a").unwrap();
+        assert_eq!(restored["token"]["refresh_token"], "rt");
+        assert_eq!(read_account(&dir, "a").unwrap().unwrap()["disabled"], true);
+        assert!(list_archived(&dir).unwrap().is_empty());
+        assert!(restore_archived(&dir, "a").is_err());
+
+        archive_accounts(&dir, &["a".to_string(), "b".to_string()]).unwrap();
+        assert!(purge_archived(&dir, &[], Some(0)).unwrap().is_empty());
+        assert_eq!(purge_archived(&dir, &["b".to_string()], None).unwrap(), vec!["b"]);
+        let now = chrono::Utc::now().timestamp();
+        assert_eq!(purge_archived(&dir, &[], Some(now + 1)).unwrap(), vec!["a"]);
+        assert!(list_archived(&dir).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
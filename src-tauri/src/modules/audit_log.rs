@@ -0,0 +1,264 @@
+// 安全审计日志
+//
+// 与运行日志分开，把安全相关事件逐行 (JSON Lines) 追加到数据目录的 `audit.log`:
+// API Key 的签发与吊销、认证失败 (附来源 IP)、会修改状态的管理接口调用以及配置变更。
+// 文件只追加不改写 (Unix 下权限为 0600)，也不参与运行日志的滚动清理。
+// Key 只记录末 4 位，配置变更只记录字段名，不记录取值。
+// 来自客户端的事件 (认证失败、未授权的管理调用) 按来源 IP 限流：每个 IP 每分钟只写一条，
+// 其余计数后在下一条记录前以 `suppressed` 汇总，避免能访问端口的人刷满磁盘。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::sync::Mutex;
+
+use crate::modules::config_history::HistorySource;
+
+const AUDIT_FILE: &str = "audit.log";
+
+/// 来自客户端的事件每个 IP 在该时间窗口内只写一条
+const CLIENT_WINDOW_SECS: i64 = 60;
+/// 单独限流的 IP 数上限，超出后共用一个窗口
+const MAX_TRACKED_CLIENTS: usize = 256;
+
+/// 串行化追加，保证每条记录独占一行
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+static CLIENT_THROTTLE: Lazy<Mutex<ClientThrottle>> = Lazy::new(|| Mutex::new(ClientThrottle::default()));
+
+/// 审计事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// 新增 API Key (kind: master / pool / temporary)
+    KeyCreated {
+        kind: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pool: Option<String>,
+        key: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires_at: Option<i64>,
+    },
+    /// 移除或吊销 API Key (批量吊销临时 Key 时 key 为 None)
+    KeyRevoked {
+        kind: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pool: Option<String>,
+        key: Option<String>,
+        count: usize,
+    },
+    /// 反代请求认证失败
+    AuthFailed { ip: Option<String>, method: String, path: String, reason: &'static str },
+    /// 管理接口调用 (authorized 为 false 时请求会被拒绝)
+    AdminCall { ip: Option<String>, method: String, path: String, authorized: bool },
+    /// 配置变更 (字段名，proxy 展开一级)
+    ConfigChanged { source: HistorySource, fields: Vec<String> },
+    /// 限流期间该 IP 未写入的客户端事件数
+    Suppressed { ip: Option<String>, count: u64 },
+}
+
+/// 按来源 IP 的写入窗口: IP -> (窗口开始时间, 窗口内未写入的事件数)
+#[derive(Default)]
+struct ClientThrottle {
+    windows: HashMap<String, (i64, u64)>,
+}
+
+impl ClientThrottle {
+    /// 是否写入该 IP 的事件；写入时返回上一窗口内未写入的事件数
+    fn admit(&mut self, ip: &str, now: i64) -> Option<u64> {
+        if !self.windows.contains_key(ip) && self.windows.len() >= MAX_TRACKED_CLIENTS {
+            self.windows.retain(|_, (start, _)| now - *start < CLIENT_WINDOW_SECS);
+        }
+        let key = if self.windows.contains_key(ip) || self.windows.len() < MAX_TRACKED_CLIENTS { ip } else { "*" };
+        match self.windows.get_mut(key) {
+            Some((start, suppressed)) if now - *start < CLIENT_WINDOW_SECS => {
+                *suppressed += 1;
+                None
+            }
+            Some((start, suppressed)) => {
+                *start = now;
+                Some(std::mem::take(suppressed))
+            }
+            None => {
+                self.windows.insert(key.to_string(), (now, 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AuditEntry<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+fn audit_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(AUDIT_FILE))
+}
+
+fn append(line: &str) -> Result<(), String> {
+    let path = audit_path()?;
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| format!("打开审计日志失败: {}", e))?;
+    file.write_all(format!("{}\n", line).as_bytes()).map_err(|e| format!("写入审计日志失败: {}", e))
+}
+
+/// 记录一条审计事件 (在阻塞线程池中写入，失败只记录运行日志)
+pub fn record(event: AuditEvent) {
+    let entry = AuditEntry { timestamp: chrono::Utc::now().to_rfc3339(), event: &event };
+    let Ok(line) = serde_json::to_string(&entry) else { return };
+    crate::utils::blocking::spawn(move || {
+        if let Err(e) = append(&line) {
+            tracing::warn!("[Audit] {}", e);
+        }
+    });
+}
+
+/// 记录来自客户端的事件 (按来源 IP 限流)
+pub fn record_client_event(ip: Option<&str>, event: AuditEvent) {
+    let now = chrono::Utc::now().timestamp();
+    let admitted = CLIENT_THROTTLE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .admit(ip.unwrap_or("unknown"), now);
+    match admitted {
+        None => {}
+        Some(0) => record(event),
+        Some(count) => {
+            record(AuditEvent::Suppressed { ip: ip.map(str::to_string), count });
+            record(event);
+        }
+    }
+}
+
+/// Key 的可识别片段 (只保留末 4 位)
+pub fn key_hint(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+}
+
+/// 配置中的 API Key: (kind, 账号池, key)
+fn config_keys(config: &Value) -> BTreeSet<(&'static str, Option<String>, String)> {
+    let mut keys = BTreeSet::new();
+    let proxy = &config["proxy"];
+    if let Some(key) = proxy["api_key"].as_str().filter(|k| !k.is_empty()) {
+        keys.insert(("master", None, key.to_string()));
+    }
+    for pool in proxy["account_pools"].as_array().into_iter().flatten() {
+        let name = pool["name"].as_str().unwrap_or_default().to_string();
+        for key in pool["api_keys"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !key.is_empty() {
+                keys.insert(("pool", Some(name.clone()), key.to_string()));
+            }
+        }
+    }
+    keys
+}
+
+/// 对比两个配置版本，得到 Key 的新增 / 移除与配置变更事件
+fn config_events(source: HistorySource, before: &Value, after: &Value) -> Vec<AuditEvent> {
+    let (old_keys, new_keys) = (config_keys(before), config_keys(after));
+    let mut events: Vec<AuditEvent> = new_keys
+        .difference(&old_keys)
+        .map(|(kind, pool, key)| AuditEvent::KeyCreated { kind, pool: pool.clone(), key: key_hint(key), expires_at: None })
+        .collect();
+    events.extend(old_keys.difference(&new_keys).map(|(kind, pool, key)| AuditEvent::KeyRevoked {
+        kind,
+        pool: pool.clone(),
+        key: Some(key_hint(key)),
+        count: 1,
+    }));
+    let fields = crate::modules::config_history::changed_fields(Some(before), after);
+    if !fields.is_empty() {
+        events.push(AuditEvent::ConfigChanged { source, fields });
+    }
+    events
+}
+
+/// 记录配置变更 (由配置历史在写入新版本时调用)
+pub fn record_config_change(source: HistorySource, before: &Value, after: &Value) {
+    config_events(source, before, after).into_iter().for_each(record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_config_events_mask_keys_and_list_fields() {
+        let before = json!({
+            "language": "zh",
+            "proxy": { "port": 8045, "api_key": "sk-old0000000000001111", "account_pools": [] },
+        });
+        let after = json!({
+            "language": "zh",
+            "proxy": {
+                "port": 9000,
+                "api_key": "sk-new0000000000002222",
+                "account_pools": [{ "name": "team", "api_keys": ["sk-pool000000000003333"] }],
+            },
+        });
+        let events = config_events(HistorySource::App, &before, &after);
+        assert!(events.contains(&AuditEvent::KeyCreated {
+            kind: "master",
+            pool: None,
+            key: "****2222".to_string(),
+            expires_at: None
+        }));
+        assert!(events.contains(&AuditEvent::KeyCreated {
+            kind: "pool",
+            pool: Some("team".to_string()),
+            key: "****3333".to_string(),
+            expires_at: None
+        }));
+        assert!(events.contains(&AuditEvent::KeyRevoked {
+            kind: "master",
+            pool: None,
+            key: Some("****1111".to_string()),
+            count: 1
+        }));
+        let Some(AuditEvent::ConfigChanged { fields, .. }) = events.last() else { panic!("缺少配置变更事件") };
+        assert_eq!(fields, &["proxy.account_pools", "proxy.api_key", "proxy.port"]);
+
+        let line = serde_json::to_string(&events.last().unwrap()).unwrap();
+        assert!(!line.contains("sk-"));
+        assert!(line.contains(r#""event":"config_changed""#));
+        assert_eq!(key_hint("short"), "****");
+    }
+
+    #[test]
+    fn test_client_events_are_throttled_per_ip() {
+        let mut throttle = ClientThrottle::default();
+        assert_eq!(throttle.admit("10.0.0.1", 0), Some(0));
+        assert_eq!(throttle.admit("10.0.0.1", 10), None);
+        assert_eq!(throttle.admit("10.0.0.1", 20), None);
+        // 其他 IP 有自己的窗口
+        assert_eq!(throttle.admit("10.0.0.2", 20), Some(0));
+        // 新窗口写入时带上之前未写入的数量
+        assert_eq!(throttle.admit("10.0.0.1", CLIENT_WINDOW_SECS), Some(2));
+        assert_eq!(throttle.admit("10.0.0.1", CLIENT_WINDOW_SECS + 1), None);
+
+        // 超出单独限流的 IP 数后共用一个窗口
+        let mut throttle = ClientThrottle::default();
+        for i in 0..MAX_TRACKED_CLIENTS {
+            throttle.admit(&format!("192.168.{}.{}", i / 256, i % 256), 100);
+        }
+        assert_eq!(throttle.admit("172.16.0.1", 100), Some(0));
+        assert_eq!(throttle.admit("172.16.0.2", 100), None);
+        assert_eq!(throttle.windows.len(), MAX_TRACKED_CLIENTS + 1);
+    }
+}
@@ -0,0 +1,142 @@
+// 账号加密备份与恢复
+//
+// 将所有账号 (含 Token) 打包为单个 JSON 文件，内容使用口令加密 (AES-256-GCM + Argon2id)，
+// 用于迁移到新机器时免去逐个重新授权。
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Account;
+use crate::utils::crypto::{self, EncryptedBlob};
+use crate::modules::i18n::tr;
+
+const BACKUP_FORMAT: &str = "antigravity-accounts-backup";
+const BACKUP_VERSION: u32 = 1;
+
+/// 备份文件 (外层明文，payload 为加密内容)
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    version: u32,
+    created_at: i64,
+    account_count: usize,
+    payload: EncryptedBlob,
+}
+
+/// 加密前的备份内容
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupPayload {
+    accounts: Vec<Account>,
+    current_account_email: Option<String>,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// 新增账号数
+    pub restored: usize,
+    /// 已存在并被覆盖的账号数
+    pub updated: usize,
+    pub errors: Vec<String>,
+}
+
+fn build_backup(accounts: Vec<Account>, current_account_email: Option<String>, passphrase: &str) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err(tr("backend.errors.backup.empty_passphrase", &[]));
+    }
+    let account_count = accounts.len();
+    let payload = BackupPayload { accounts, current_account_email };
+    let plaintext = serde_json::to_vec(&payload).map_err(|e| format!("序列化备份失败: {}", e))?;
+
+    let file = BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: chrono::Utc::now().timestamp(),
+        account_count,
+        payload: crypto::encrypt_with_passphrase(passphrase, &plaintext)?,
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| format!("序列化备份失败: {}", e))
+}
+
+fn open_backup(content: &str, passphrase: &str) -> Result<BackupPayload, String> {
+    let file: BackupFile = serde_json::from_str(content).map_err(|e| format!("备份文件格式错误: {}", e))?;
+    if file.format != BACKUP_FORMAT {
+        return Err(tr("backend.errors.backup.invalid_file", &[]));
+    }
+    if file.version > BACKUP_VERSION {
+        return Err(tr("backend.errors.backup.version_too_new", &[("version", &file.version.to_string())]));
+    }
+    let plaintext = crypto::decrypt_with_passphrase(passphrase, &file.payload)?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("备份内容解析失败: {}", e))
+}
+
+/// 导出加密备份，返回导出的账号数
+pub fn export_encrypted_backup(path: &str, passphrase: &str) -> Result<usize, String> {
+    let accounts = crate::modules::account::list_accounts()?;
+    let current_account_email = crate::modules::account::get_current_account()?.map(|a| a.email);
+    let count = accounts.len();
+
+    let content = build_backup(accounts, current_account_email, passphrase)?;
+    std::fs::write(path, content).map_err(|e| format!("写入备份文件失败: {}", e))?;
+
+    crate::modules::logger::log_info(&format!("已导出 {} 个账号的加密备份: {}", count, path));
+    Ok(count)
+}
+
+/// 从加密备份恢复账号 (按邮箱合并，已存在的账号会被备份内容覆盖)
+pub fn restore_encrypted_backup(path: &str, passphrase: &str) -> Result<RestoreReport, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取备份文件失败: {}", e))?;
+    let payload = open_backup(&content, passphrase)?;
+
+    let mut report = RestoreReport { restored: 0, updated: 0, errors: Vec::new() };
+    for mut account in payload.accounts {
+        let email = account.email.clone();
+        // 通过 upsert 建立/复用本地账号 ID 与索引，再写回备份中的完整账号数据
+        let result = crate::modules::account::upsert_account_detailed(email.clone(), account.name.clone(), account.token.clone())
+            .and_then(|(local, merge)| {
+                account.id = local.id;
+                account.email = local.email;
+                crate::modules::account::save_account(&account).map(|_| merge.is_some())
+            });
+
+        match result {
+            Ok(true) => report.updated += 1,
+            Ok(false) => report.restored += 1,
+            Err(e) => report.errors.push(format!("{}: {}", email, e)),
+        }
+    }
+
+    if let Some(email) = payload.current_account_email {
+        if let Some(account) = crate::modules::account::list_accounts()?.into_iter().find(|a| crate::modules::account::same_identity(&a.email, &email)) {
+            let _ = crate::modules::account::set_current_account_id(&account.id);
+        }
+    }
+
+    crate::modules::logger::log_info(&format!(
+        "账号备份恢复完成: 新增 {}, 覆盖 {}, 失败 {}",
+        report.restored, report.updated, report.errors.len()
+    ));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenData;
+
+    #[test]
+    fn test_backup_roundtrip() {
+        let token = TokenData::new("at".to_string(), "1//rt".to_string(), 3600, Some("a@example.com".to_string()), None, None);
+        let account = Account::new("id-1".to_string(), "a@example.com".to_string(), token);
+
+        let content = build_backup(vec![account], Some("a@example.com".to_string()), "passphrase").unwrap();
+        assert!(!content.contains("1//rt"));
+
+        let payload = open_backup(&content, "passphrase").unwrap();
+        assert_eq!(payload.accounts.len(), 1);
+        assert_eq!(payload.accounts[0].token.refresh_token, "1//rt");
+        assert_eq!(payload.current_account_email.as_deref(), Some("a@example.com"));
+
+        assert!(open_backup(&content, "wrong").is_err());
+        assert!(build_backup(Vec::new(), None, "").is_err());
+    }
+}
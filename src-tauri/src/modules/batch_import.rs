@@ -0,0 +1,397 @@
+// 批量导入账号
+//
+// 支持从 JSON / CSV 文件一次性导入多个账号：
+// - JSON: ["1//xxx", ...] / [{"email": "...", "refresh_token": "..."}, ...] / {"accounts": [...]}
+// - CSV:  每行一个账号，可带表头；包含 "1//" 开头的字段视为 refresh_token，包含 "@" 的字段视为邮箱
+// - gemini-cli: ~/.gemini/oauth_creds.json (单个 Google OAuth 凭据对象)
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::models::TokenData;
+use crate::modules::i18n::tr;
+
+/// 文件中解析出的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// 行号 (从 1 开始，JSON 为数组下标 + 1)
+    pub row: usize,
+    pub email: Option<String>,
+    pub refresh_token: Option<String>,
+}
+
+/// 单行导入状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    /// 新增账号
+    Imported,
+    /// 账号已存在，已更新 Token
+    Updated,
+    /// 与文件中的其他行或已有账号重复，已跳过
+    Duplicate,
+    /// 格式校验失败
+    Invalid,
+    /// Token 刷新或保存失败
+    Failed,
+}
+
+/// 单行导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    pub row: usize,
+    pub email: Option<String>,
+    pub status: ImportRowStatus,
+    pub message: Option<String>,
+}
+
+/// 批量导入汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchImportReport {
+    pub total: usize,
+    pub imported: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+fn looks_like_refresh_token(s: &str) -> bool {
+    s.starts_with("1//") && s.len() > 10
+}
+
+fn row_from_json(row: usize, value: &Value) -> ImportRow {
+    let str_field = |v: &Value, keys: &[&str]| -> Option<String> {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|x| x.as_str()))
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    match value {
+        Value::String(s) => ImportRow {
+            row,
+            email: None,
+            refresh_token: Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        },
+        Value::Object(_) => ImportRow {
+            row,
+            email: str_field(value, &["email"]),
+            // 兼容本应用导出的账号文件 ({"token": {"refresh_token": ...}})
+            refresh_token: str_field(value, &["refresh_token", "refreshToken"])
+                .or_else(|| value.get("token").and_then(|t| str_field(t, &["refresh_token", "refreshToken"]))),
+        },
+        _ => ImportRow { row, email: None, refresh_token: None },
+    }
+}
+
+/// 解析导入文件内容
+pub fn parse_import_content(content: &str) -> Result<Vec<ImportRow>, String> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim();
+    if trimmed.is_empty() {
+        return Err(tr("backend.errors.import.empty_file", &[]));
+    }
+
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        let json: Value = serde_json::from_str(trimmed).map_err(|e| format!("JSON 解析失败: {}", e))?;
+        let items = match &json {
+            Value::Array(arr) => arr.clone(),
+            Value::Object(map) => match map.get("accounts") {
+                Some(Value::Array(arr)) => arr.clone(),
+                _ => vec![json.clone()],
+            },
+            _ => return Err(tr("backend.errors.import.unsupported_json", &[])),
+        };
+        return Ok(items.iter().enumerate().map(|(i, v)| row_from_json(i + 1, v)).collect());
+    }
+
+    // CSV / 纯文本
+    let mut rows = Vec::new();
+    for (i, line) in trimmed.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line
+            .split([',', ';', '\t'])
+            .map(|f| f.trim().trim_matches('"').trim())
+            .collect();
+
+        // 跳过表头
+        if i == 0 && fields.iter().any(|f| f.eq_ignore_ascii_case("refresh_token") || f.eq_ignore_ascii_case("email")) {
+            continue;
+        }
+
+        rows.push(ImportRow {
+            row: i + 1,
+            email: fields.iter().find(|f| f.contains('@')).map(|s| s.to_string()),
+            refresh_token: fields
+                .iter()
+                .find(|f| looks_like_refresh_token(f))
+                .or_else(|| fields.iter().find(|f| !f.is_empty() && !f.contains('@')))
+                .map(|s| s.to_string()),
+        });
+    }
+    Ok(rows)
+}
+
+/// gemini-cli 凭据需包含的授权范围 (Cloud Code 接口依赖)
+const REQUIRED_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// gemini-cli 默认凭据文件路径
+pub fn default_gemini_cli_creds_path() -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|home| home.join(".gemini").join("oauth_creds.json"))
+}
+
+/// 从 id_token (JWT) 中读取邮箱，仅作为导入提示，不校验签名
+fn email_from_id_token(id_token: &str) -> Option<String> {
+    use base64::{engine::general_purpose, Engine as _};
+    let payload = id_token.split('.').nth(1)?;
+    let bytes = general_purpose::URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("email").and_then(|e| e.as_str()).map(|s| s.to_string())
+}
+
+/// 解析并校验 gemini-cli / Google OAuth 工具生成的 oauth_creds.json
+pub fn parse_gemini_cli_creds(content: &str) -> Result<ImportRow, String> {
+    let json: Value = serde_json::from_str(content.trim_start_matches('\u{feff}').trim())
+        .map_err(|e| format!("凭据文件 JSON 解析失败: {}", e))?;
+    if !json.is_object() {
+        return Err(tr("backend.errors.import.not_object", &[]));
+    }
+
+    let mut row = row_from_json(1, &json);
+    if row.refresh_token.is_none() {
+        return Err(tr("backend.errors.import.missing_refresh_token", &[]));
+    }
+    if let Some(scope) = json.get("scope").and_then(|s| s.as_str()) {
+        if !scope.split_whitespace().any(|s| s == REQUIRED_SCOPE) {
+            return Err(tr("backend.errors.import.missing_scope", &[("scope", REQUIRED_SCOPE)]));
+        }
+    }
+    if row.email.is_none() {
+        row.email = json.get("id_token").and_then(|t| t.as_str()).and_then(email_from_id_token);
+    }
+    Ok(row)
+}
+
+/// 通过本地校验、待联网导入的行: (行号, refresh_token, 邮箱提示)
+type PendingRow = (usize, String, Option<String>);
+
+/// 本地校验与去重，返回待导入的行和已判定的结果
+fn validate_rows(
+    rows: Vec<ImportRow>,
+    existing_tokens: &HashSet<String>,
+) -> (Vec<PendingRow>, Vec<ImportRowResult>) {
+    let mut seen = HashSet::new();
+    let mut pending = Vec::new();
+    let mut results = Vec::new();
+
+    for row in rows {
+        let Some(token) = row.refresh_token.filter(|t| looks_like_refresh_token(t)) else {
+            results.push(ImportRowResult {
+                row: row.row,
+                email: row.email,
+                status: ImportRowStatus::Invalid,
+                message: Some("缺少有效的 refresh_token (应以 1// 开头)".to_string()),
+            });
+            continue;
+        };
+
+        if !seen.insert(token.clone()) {
+            results.push(ImportRowResult {
+                row: row.row,
+                email: row.email,
+                status: ImportRowStatus::Duplicate,
+                message: Some("与文件中的其他行重复".to_string()),
+            });
+            continue;
+        }
+
+        if existing_tokens.contains(&token) {
+            results.push(ImportRowResult {
+                row: row.row,
+                email: row.email,
+                status: ImportRowStatus::Duplicate,
+                message: Some("账号已存在".to_string()),
+            });
+            continue;
+        }
+
+        pending.push((row.row, token, row.email));
+    }
+
+    (pending, results)
+}
+
+async fn import_one(row: usize, refresh_token: String, email_hint: Option<String>) -> ImportRowResult {
+    let fail = |message: String| ImportRowResult {
+        row,
+        email: email_hint.clone(),
+        status: ImportRowStatus::Failed,
+        message: Some(message),
+    };
+
+    let token_res = match crate::modules::oauth::refresh_access_token(&refresh_token).await {
+        Ok(t) => t,
+        Err(e) => return fail(e),
+    };
+    let user_info = match crate::modules::oauth::get_user_info(&token_res.access_token).await {
+        Ok(u) => u,
+        Err(e) => return fail(e),
+    };
+
+    let token = TokenData::new(
+        token_res.access_token,
+        refresh_token,
+        token_res.expires_in,
+        Some(user_info.email.clone()),
+        None,
+        None,
+    );
+
+    match crate::modules::account::upsert_account_detailed(user_info.email.clone(), user_info.get_display_name(), token) {
+        Ok((account, None)) => ImportRowResult {
+            row,
+            email: Some(account.email),
+            status: ImportRowStatus::Imported,
+            message: None,
+        },
+        // 已存在同一身份的账号：合并凭据而不是新建
+        Ok((account, Some(merge))) => ImportRowResult {
+            row,
+            email: Some(account.email),
+            status: ImportRowStatus::Updated,
+            message: Some(merge_message(&merge.changes)),
+        },
+        Err(e) => fail(e),
+    }
+}
+
+fn merge_message(changes: &[String]) -> String {
+    if changes.is_empty() {
+        "已合并到现有账号 (无变化)".to_string()
+    } else {
+        format!("已合并到现有账号: {}", changes.join(", "))
+    }
+}
+
+/// 从文件批量导入账号
+pub async fn import_accounts_from_file(path: &str) -> Result<BatchImportReport, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    import_accounts_from_content(&content).await
+}
+
+/// 从文件内容批量导入账号
+pub async fn import_accounts_from_content(content: &str) -> Result<BatchImportReport, String> {
+    import_rows(parse_import_content(content)?).await
+}
+
+/// 导入 gemini-cli 凭据文件，`path` 为空时使用默认路径 (~/.gemini/oauth_creds.json)
+pub async fn import_gemini_cli_creds(path: Option<&str>) -> Result<BatchImportReport, String> {
+    let path = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => default_gemini_cli_creds_path().ok_or_else(|| tr("backend.errors.no_home_dir", &[]))?,
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取 gemini-cli 凭据文件失败 ({}): {}", path.display(), e))?;
+    import_rows(vec![parse_gemini_cli_creds(&content)?]).await
+}
+
+/// 导入已解析的行 (本地校验、去重后逐个刷新 Token 并保存)
+pub async fn import_rows(rows: Vec<ImportRow>) -> Result<BatchImportReport, String> {
+    let total = rows.len();
+
+    let accounts = crate::modules::account::list_accounts()?;
+    let existing_tokens: HashSet<String> = accounts.iter().map(|a| a.token.refresh_token.clone()).collect();
+
+    let (pending, mut results) = validate_rows(rows, &existing_tokens);
+
+    crate::modules::logger::log_info(&format!(
+        "批量导入: 共 {} 行, 待导入 {} 个账号",
+        total,
+        pending.len()
+    ));
+
+    // 顺序导入：upsert_account 会写入账号索引，避免并发写冲突
+    for (row, token, email_hint) in pending {
+        results.push(import_one(row, token, email_hint).await);
+    }
+
+    results.sort_by_key(|r| r.row);
+    let count = |s: ImportRowStatus| results.iter().filter(|r| r.status == s).count();
+
+    Ok(BatchImportReport {
+        total,
+        imported: count(ImportRowStatus::Imported),
+        updated: count(ImportRowStatus::Updated),
+        skipped: count(ImportRowStatus::Duplicate) + count(ImportRowStatus::Invalid),
+        failed: count(ImportRowStatus::Failed),
+        rows: results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_A: &str = "1//0aaaaaaaaaaaaaaaa";
+    const TOKEN_B: &str = "1//0bbbbbbbbbbbbbbbb";
+
+    #[test]
+    fn test_parse_json_formats() {
+        let rows = parse_import_content(&format!(r#"["{}", {{"email": "b@example.com", "refresh_token": "{}"}}]"#, TOKEN_A, TOKEN_B)).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].refresh_token.as_deref(), Some(TOKEN_A));
+        assert_eq!(rows[1].email.as_deref(), Some("b@example.com"));
+
+        let rows = parse_import_content(&format!(r#"{{"accounts": [{{"token": {{"refresh_token": "{}"}}}}]}}"#, TOKEN_A)).unwrap();
+        assert_eq!(rows[0].refresh_token.as_deref(), Some(TOKEN_A));
+    }
+
+    #[test]
+    fn test_parse_csv_with_header() {
+        let csv = format!("email,refresh_token\na@example.com,{}\n\n{}\n", TOKEN_A, TOKEN_B);
+        let rows = parse_import_content(&csv).unwrap();
+        assert_eq!(rows, vec![
+            ImportRow { row: 2, email: Some("a@example.com".to_string()), refresh_token: Some(TOKEN_A.to_string()) },
+            ImportRow { row: 4, email: None, refresh_token: Some(TOKEN_B.to_string()) },
+        ]);
+    }
+
+    #[test]
+    fn test_validate_rows_detects_duplicates_and_invalid() {
+        let rows = parse_import_content(&format!("{}\n{}\nnot-a-token\n{}\n", TOKEN_A, TOKEN_A, TOKEN_B)).unwrap();
+        let existing: HashSet<String> = [TOKEN_B.to_string()].into_iter().collect();
+
+        let (pending, results) = validate_rows(rows, &existing);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].1, TOKEN_A);
+
+        let statuses: Vec<(usize, ImportRowStatus)> = results.iter().map(|r| (r.row, r.status)).collect();
+        assert_eq!(statuses, vec![
+            (2, ImportRowStatus::Duplicate),
+            (3, ImportRowStatus::Invalid),
+            (4, ImportRowStatus::Duplicate),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_gemini_cli_creds() {
+        use base64::{engine::general_purpose, Engine as _};
+        let claims = general_purpose::URL_SAFE_NO_PAD.encode(r#"{"email":"cli@example.com","aud":"x"}"#);
+        let creds = format!(
+            r#"{{"access_token":"ya29.x","refresh_token":"{}","scope":"https://www.googleapis.com/auth/cloud-platform https://www.googleapis.com/auth/userinfo.email openid","token_type":"Bearer","id_token":"h.{}.s","expiry_date":1760000000000}}"#,
+            TOKEN_A, claims
+        );
+        let row = parse_gemini_cli_creds(&creds).unwrap();
+        assert_eq!(row.refresh_token.as_deref(), Some(TOKEN_A));
+        assert_eq!(row.email.as_deref(), Some("cli@example.com"));
+
+        let narrow = format!(r#"{{"refresh_token":"{}","scope":"openid"}}"#, TOKEN_A);
+        assert!(parse_gemini_cli_creds(&narrow).is_err());
+        assert!(parse_gemini_cli_creds(r#"{"access_token":"ya29.x"}"#).is_err());
+        assert!(parse_gemini_cli_creds(&format!(r#"["{}"]"#, TOKEN_A)).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+// Claude Code 一键配置
+//
+// 把反代地址、API Key 与模型映射写入 Claude Code 的用户配置 (`~/.claude/settings.json`，
+// 设置了 CLAUDE_CONFIG_DIR 时为该目录) 的 `env` 中，保留文件中的其他设置。
+// 第一次写入前把原文件 (或 "原本不存在") 记录到数据目录的 claude_code_backup.json，
+// 重复配置不会覆盖该备份；撤销时恢复原文件并删除备份。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::modules::logger;
+use crate::modules::i18n::tr;
+
+const BACKUP_FILE: &str = "claude_code_backup.json";
+/// 与反代认证冲突、配置时移除的 env 项 (原值保存在备份中)
+const CONFLICTING_ENV: &[&str] = &["ANTHROPIC_API_KEY"];
+
+/// 模型映射 (为空的项不写入)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClaudeModelMapping {
+    /// 默认模型 (ANTHROPIC_MODEL)
+    pub model: Option<String>,
+    pub opus_model: Option<String>,
+    pub sonnet_model: Option<String>,
+    pub haiku_model: Option<String>,
+}
+
+/// Claude Code 当前配置状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaudeCodeStatus {
+    pub settings_path: String,
+    pub exists: bool,
+    /// 当前的 ANTHROPIC_BASE_URL
+    pub base_url: Option<String>,
+    /// 当前的 ANTHROPIC_MODEL
+    pub model: Option<String>,
+    /// 是否有可撤销的备份
+    pub has_backup: bool,
+    pub backup_created_at: Option<i64>,
+}
+
+/// 配置前的原始状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsBackup {
+    settings_path: String,
+    created_at: i64,
+    /// 原文件内容，文件原本不存在时为 None
+    original: Option<String>,
+}
+
+/// Claude Code 用户配置文件路径
+pub fn settings_path() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os("CLAUDE_CONFIG_DIR").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("settings.json"));
+    }
+    let home = dirs::home_dir().ok_or_else(|| tr("backend.errors.no_home_dir", &[]))?;
+    Ok(home.join(".claude").join("settings.json"))
+}
+
+fn backup_path() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_root_data_dir()?.join(BACKUP_FILE))
+}
+
+fn read_backup(path: &Path) -> Option<SettingsBackup> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 配置文件原文与解析后的对象
+type Settings = (String, Map<String, Value>);
+
+fn read_settings(path: &Path) -> Result<Option<Settings>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("读取 Claude Code 配置失败: {}", e))?;
+    let settings = if content.trim().is_empty() {
+        Map::new()
+    } else {
+        match serde_json::from_str::<Value>(&content) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => return Err(tr("backend.errors.claude_code.not_object", &[])),
+            Err(e) => return Err(tr("backend.errors.claude_code.parse_failed", &[("error", &e.to_string())])),
+        }
+    };
+    Ok(Some((content, settings)))
+}
+
+/// 写入 (先写临时文件再替换，避免写到一半被 Claude Code 读取)
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("写入 Claude Code 配置失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("写入 Claude Code 配置失败: {}", e))
+}
+
+/// 把反代设置合并到 settings 的 env 中
+fn merge_settings(settings: &mut Map<String, Value>, base_url: &str, auth_token: &str, models: &ClaudeModelMapping) {
+    let env = settings.entry("env").or_insert_with(|| Value::Object(Map::new()));
+    if !env.is_object() {
+        *env = Value::Object(Map::new());
+    }
+    let Some(env) = env.as_object_mut() else { return };
+    for key in CONFLICTING_ENV {
+        env.remove(*key);
+    }
+    env.insert("ANTHROPIC_BASE_URL".to_string(), Value::from(base_url));
+    env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), Value::from(auth_token));
+    let mapping = [
+        ("ANTHROPIC_MODEL", &models.model),
+        ("ANTHROPIC_DEFAULT_OPUS_MODEL", &models.opus_model),
+        ("ANTHROPIC_DEFAULT_SONNET_MODEL", &models.sonnet_model),
+        ("ANTHROPIC_DEFAULT_HAIKU_MODEL", &models.haiku_model),
+    ];
+    for (key, value) in mapping {
+        if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            env.insert(key.to_string(), Value::from(value));
+        }
+    }
+    // apiKeyHelper 的优先级高于环境变量中的 Token
+    settings.remove("apiKeyHelper");
+}
+
+fn configure_at(
+    settings_file: &Path,
+    backup_file: &Path,
+    base_url: &str,
+    auth_token: &str,
+    models: &ClaudeModelMapping,
+) -> Result<(), String> {
+    let existing = read_settings(settings_file)?;
+    if read_backup(backup_file).is_none() {
+        let backup = SettingsBackup {
+            settings_path: settings_file.to_string_lossy().to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+            original: existing.as_ref().map(|(content, _)| content.clone()),
+        };
+        let content = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+        write_file(backup_file, &content)?;
+    }
+
+    let mut settings = existing.map(|(_, settings)| settings).unwrap_or_default();
+    merge_settings(&mut settings, base_url, auth_token, models);
+    let content = serde_json::to_string_pretty(&Value::Object(settings)).map_err(|e| e.to_string())?;
+    write_file(settings_file, &content)
+}
+
+fn undo_at(backup_file: &Path) -> Result<(), String> {
+    let backup = read_backup(backup_file).ok_or_else(|| tr("backend.errors.claude_code.nothing_to_undo", &[]))?;
+    let settings_file = PathBuf::from(&backup.settings_path);
+    match &backup.original {
+        Some(content) => write_file(&settings_file, content)?,
+        None => {
+            if settings_file.exists() {
+                fs::remove_file(&settings_file).map_err(|e| format!("删除 Claude Code 配置失败: {}", e))?;
+            }
+        }
+    }
+    fs::remove_file(backup_file).map_err(|e| format!("删除备份失败: {}", e))
+}
+
+fn status_at(settings_file: &Path, backup_file: &Path) -> ClaudeCodeStatus {
+    let settings = read_settings(settings_file).ok().flatten().map(|(_, s)| s);
+    let env = |key: &str| {
+        settings
+            .as_ref()
+            .and_then(|s| s.get("env"))
+            .and_then(|env| env.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let backup = read_backup(backup_file);
+    ClaudeCodeStatus {
+        settings_path: settings_file.to_string_lossy().to_string(),
+        exists: settings_file.exists(),
+        base_url: env("ANTHROPIC_BASE_URL"),
+        model: env("ANTHROPIC_MODEL"),
+        has_backup: backup.is_some(),
+        backup_created_at: backup.map(|b| b.created_at),
+    }
+}
+
+/// 写入反代设置，返回配置后的状态
+pub fn configure(base_url: &str, auth_token: &str, models: &ClaudeModelMapping) -> Result<ClaudeCodeStatus, String> {
+    let settings_file = settings_path()?;
+    let backup_file = backup_path()?;
+    configure_at(&settings_file, &backup_file, base_url, auth_token, models)?;
+    logger::log_info(&format!("已配置 Claude Code 使用反代: {} ({})", base_url, settings_file.display()));
+    Ok(status_at(&settings_file, &backup_file))
+}
+
+/// 恢复配置前的 settings.json
+pub fn undo() -> Result<ClaudeCodeStatus, String> {
+    let backup_file = backup_path()?;
+    undo_at(&backup_file)?;
+    logger::log_info("已撤销 Claude Code 反代配置");
+    Ok(status_at(&settings_path()?, &backup_file))
+}
+
+pub fn status() -> Result<ClaudeCodeStatus, String> {
+    Ok(status_at(&settings_path()?, &backup_path()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ag-claude-code-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_configure_preserves_settings_and_undo_restores() {
+        let dir = temp_dir();
+        let settings_file = dir.join(".claude").join("settings.json");
+        let backup_file = dir.join(BACKUP_FILE);
+        fs::create_dir_all(settings_file.parent().unwrap()).unwrap();
+        let original = r#"{"theme":"dark","apiKeyHelper":"get-key.sh","env":{"ANTHROPIC_API_KEY":"sk-ant","FOO":"1"}}"#;
+        fs::write(&settings_file, original).unwrap();
+
+        let models = ClaudeModelMapping {
+            model: Some("claude-sonnet-4-5".to_string()),
+            haiku_model: Some(" ".to_string()),
+            ..Default::default()
+        };
+        configure_at(&settings_file, &backup_file, "http://127.0.0.1:8045", "sk-proxy", &models).unwrap();
+        // 重复配置不覆盖最初的备份
+        configure_at(&settings_file, &backup_file, "http://127.0.0.1:9000", "sk-proxy", &models).unwrap();
+
+        let settings: Value = serde_json::from_str(&fs::read_to_string(&settings_file).unwrap()).unwrap();
+        assert_eq!(settings["theme"], "dark");
+        assert!(settings.get("apiKeyHelper").is_none());
+        assert_eq!(settings["env"]["FOO"], "1");
+        assert!(settings["env"].get("ANTHROPIC_API_KEY").is_none());
+        assert_eq!(settings["env"]["ANTHROPIC_BASE_URL"], "http://127.0.0.1:9000");
+        assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-proxy");
+        assert_eq!(settings["env"]["ANTHROPIC_MODEL"], "claude-sonnet-4-5");
+        assert!(settings["env"].get("ANTHROPIC_DEFAULT_HAIKU_MODEL").is_none());
+
+        let status = status_at(&settings_file, &backup_file);
+        assert!(status.has_backup);
+        assert_eq!(status.base_url.as_deref(), Some("http://127.0.0.1:9000"));
+
+        undo_at(&backup_file).unwrap();
+        assert_eq!(fs::read_to_string(&settings_file).unwrap(), original);
+        assert!(!backup_file.exists());
+        assert!(undo_at(&backup_file).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_undo_removes_created_settings() {
+        let dir = temp_dir();
+        let settings_file = dir.join(".claude").join("settings.json");
+        let backup_file = dir.join(BACKUP_FILE);
+
+        configure_at(&settings_file, &backup_file, "http://127.0.0.1:8045", "sk-proxy", &ClaudeModelMapping::default()).unwrap();
+        assert!(settings_file.exists());
+        undo_at(&backup_file).unwrap();
+        assert!(!settings_file.exists());
+
+        fs::write(&settings_file, "[1, 2]").unwrap();
+        assert!(configure_at(&settings_file, &backup_file, "http://x", "k", &ClaudeModelMapping::default()).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,88 +1,70 @@
+use once_cell::sync::OnceCell;
 use std::fs;
-use serde_json;
+use std::path::PathBuf;
 
 use crate::models::AppConfig;
 use super::account::get_data_dir;
+use super::config_history::{self, HistorySource};
+use super::config_format::{resolve_path, ConfigFormat};
+use super::config_schema::{load_migrated, to_versioned_string_as, APP_CONFIG_MIGRATIONS};
 
-const CONFIG_FILE: &str = "gui_config.json";
+const CONFIG_STEM: &str = "gui_config";
 
-/// 加载应用配置
-pub fn load_app_config() -> Result<AppConfig, String> {
-    let data_dir = get_data_dir()?;
-    let config_path = data_dir.join(CONFIG_FILE);
-    
-    if !config_path.exists() {
-        return Ok(AppConfig::new());
-    }
-    
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("读取配置文件失败: {}", e))?;
-    
-    let mut v: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("解析配置文件失败: {}", e))?;
-    
-    let mut modified = false;
-
-    // 迁移逻辑
-    if let Some(proxy) = v.get_mut("proxy") {
-        let mut custom_mapping = proxy.get("custom_mapping")
-            .and_then(|m| m.as_object())
-            .map(|m| m.clone())
-            .unwrap_or_default();
-
-        // 迁移 Anthropic 映射
-        if let Some(anthropic) = proxy.get_mut("anthropic_mapping").and_then(|m| m.as_object_mut()) {
-            for (k, v) in anthropic.iter() {
-                // 只有非系列字段才搬移。因为系列字段现在由 Preset 逻辑或内置表处理
-                if !k.ends_with("-series") {
-                    if !custom_mapping.contains_key(k) {
-                        custom_mapping.insert(k.clone(), v.clone());
-                    }
-                }
-            }
-            // 移除旧字段
-            proxy.as_object_mut().unwrap().remove("anthropic_mapping");
-            modified = true;
-        }
+/// 命令行指定的配置文件 (无界面模式 --config)
+static CONFIG_PATH_OVERRIDE: OnceCell<PathBuf> = OnceCell::new();
 
-        // 迁移 OpenAI 映射
-        if let Some(openai) = proxy.get_mut("openai_mapping").and_then(|m| m.as_object_mut()) {
-            for (k, v) in openai.iter() {
-                if !k.ends_with("-series") {
-                    if !custom_mapping.contains_key(k) {
-                        custom_mapping.insert(k.clone(), v.clone());
-                    }
-                }
-            }
-            // 移除旧字段
-            proxy.as_object_mut().unwrap().remove("openai_mapping");
-            modified = true;
-        }
+/// 使用指定的配置文件代替数据目录下的 gui_config (需在首次读取配置前调用)
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH_OVERRIDE.set(path);
+}
 
-        if modified {
-            proxy.as_object_mut().unwrap().insert("custom_mapping".to_string(), serde_json::Value::Object(custom_mapping));
-        }
+/// 配置文件路径 (gui_config.json，也可以是手工编写的 .toml / .yaml / .yml)
+pub fn config_path() -> Result<PathBuf, String> {
+    if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+        return Ok(path.clone());
     }
+    Ok(resolve_path(&get_data_dir()?, CONFIG_STEM))
+}
 
-    let config: AppConfig = serde_json::from_value(v)
-        .map_err(|e| format!("迁移后转换配置失败: {}", e))?;
-    
-    // 如果发生了迁移，自动保存一次以清理文件
-    if modified {
-        let _ = save_app_config(&config);
-    }
+/// 加载应用配置 (旧版本格式会先迁移并备份，见 config_schema)
+pub fn load_app_config() -> Result<AppConfig, String> {
+    let Some(v) = load_migrated(&config_path()?, APP_CONFIG_MIGRATIONS)? else {
+        return Ok(AppConfig::new());
+    };
+    serde_json::from_value(v).map_err(|e| format!("迁移后转换配置失败: {}", e))
+}
 
-    Ok(config)
+/// 在阻塞线程池中加载应用配置 (供异步命令与反代请求路径使用)
+pub async fn load_app_config_async() -> Result<AppConfig, String> {
+    crate::utils::blocking::run(load_app_config).await
 }
 
-/// 保存应用配置
+/// 配置文件的修改时间 (文件不存在时为 None)，用于检测外部修改
+pub fn config_modified_at() -> Option<std::time::SystemTime> {
+    fs::metadata(config_path().ok()?).ok()?.modified().ok()
+}
+
+/// 保存应用配置 (写回现有配置文件的格式)
 pub fn save_app_config(config: &AppConfig) -> Result<(), String> {
-    let data_dir = get_data_dir()?;
-    let config_path = data_dir.join(CONFIG_FILE);
-    
-    let content = serde_json::to_string_pretty(config)
+    save_app_config_as(config, HistorySource::App)
+}
+
+/// 在阻塞线程池中保存应用配置
+pub async fn save_app_config_async(config: AppConfig) -> Result<(), String> {
+    crate::utils::blocking::run(move || save_app_config(&config)).await
+}
+
+/// 保存应用配置并按指定来源记入变更历史
+pub fn save_app_config_as(config: &AppConfig, source: HistorySource) -> Result<(), String> {
+    let path = config_path()?;
+    let content = to_versioned_string_as(config, APP_CONFIG_MIGRATIONS, ConfigFormat::from_path(&path))
         .map_err(|e| format!("序列化配置失败: {}", e))?;
-    
-    fs::write(&config_path, content)
-        .map_err(|e| format!("保存配置失败: {}", e))
+    // 历史为空时以覆盖前的配置作为第一个版本
+    let baseline = if path.exists() && config_history::is_empty() { load_app_config().ok() } else { None };
+
+    fs::write(&path, content)
+        .map_err(|e| format!("保存配置失败: {}", e))?;
+    config_history::record(config, source, baseline);
+    crate::modules::i18n::set_language(&config.language);
+    Ok(())
 }
@@ -0,0 +1,288 @@
+// 配置导入 / 导出
+//
+// 把完整配置 (应用配置，含反代配置、API Key 列表、模型映射、路由规则，以及配置方案)
+// 导出为单个 JSON 文件，用于迁移机器或分享配置。凭证类字段 (沿用诊断包的判定规则)
+// 从明文部分拆出：提供口令时加密后一并导出 (AES-256-GCM + Argon2id)，否则不导出。
+// 导入时不带凭证的字段保留本机现有的值，因此分享出去的配置不会泄露也不会清空对方的密钥。
+// 账号不在配置包内，迁移账号请使用账号加密备份。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::models::AppConfig;
+use crate::utils::redact::is_secret_field;
+use crate::proxy::secret_refs::is_secret_ref;
+use crate::utils::crypto::{self, EncryptedBlob};
+use crate::modules::i18n::tr;
+
+const BUNDLE_FORMAT: &str = "antigravity-config-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfigBundle {
+    format: String,
+    version: u32,
+    created_at: i64,
+    app_version: String,
+    /// 去掉凭证后的配置: { "app_config": ..., "presets": ... }
+    config: Value,
+    /// 被拆出的凭证字段 (JSON Pointer)
+    secret_fields: Vec<String>,
+    /// 加密的凭证 (JSON Pointer -> 原值)，导出时未提供口令则为空
+    #[serde(default)]
+    secrets: Option<EncryptedBlob>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigExportSummary {
+    pub path: String,
+    pub secret_fields: usize,
+    /// 凭证已加密导出 (否则未导出)
+    pub secrets_included: bool,
+    pub presets: usize,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigImportReport {
+    /// 从配置包恢复的凭证字段数
+    pub secrets_restored: usize,
+    /// 配置包未带凭证、保留了本机值的字段数
+    pub secrets_kept_local: usize,
+    pub presets: usize,
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// 拆出凭证字段：原值按 JSON Pointer 记录到 `out`，原处置空
+fn extract_secrets(value: &mut Value, path: &str, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                let pointer = format!("{}/{}", path, escape_pointer(name));
+                let secret = is_secret_field(name)
+                    && match field {
+                        // env: / keychain: 引用本身不是凭证，原样导出
+                        Value::String(s) => !s.is_empty() && !is_secret_ref(s),
+                        Value::Array(items) => !items.is_empty() && items.iter().all(Value::is_string),
+                        _ => false,
+                    };
+                if secret {
+                    let empty = if field.is_array() { json!([]) } else { json!("") };
+                    out.insert(pointer, std::mem::replace(field, empty));
+                } else {
+                    extract_secrets(field, &pointer, out);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                extract_secrets(item, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn build_bundle(mut config: Value, passphrase: Option<&str>) -> Result<(ConfigBundle, usize), String> {
+    let mut secrets = BTreeMap::new();
+    extract_secrets(&mut config, "", &mut secrets);
+    let encrypted = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let plaintext = serde_json::to_vec(&secrets).map_err(|e| e.to_string())?;
+            Some(crypto::encrypt_with_passphrase(passphrase, &plaintext)?)
+        }
+        None => None,
+    };
+    let count = secrets.len();
+    Ok((
+        ConfigBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            created_at: chrono::Utc::now().timestamp(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            config,
+            secret_fields: secrets.into_keys().collect(),
+            secrets: encrypted,
+        },
+        count,
+    ))
+}
+
+/// 还原凭证：优先用配置包中解密的值，没有则保留本机同一位置的值。返回 (恢复数, 保留本机数)
+fn open_bundle(bundle: ConfigBundle, passphrase: Option<&str>, local: &Value) -> Result<(Value, usize, usize), String> {
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(tr("backend.errors.config_bundle.invalid_file", &[]));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(tr("backend.errors.config_bundle.version_too_new", &[("version", &bundle.version.to_string())]));
+    }
+    let secrets: BTreeMap<String, Value> = match (&bundle.secrets, passphrase.filter(|p| !p.is_empty())) {
+        (Some(blob), Some(passphrase)) => {
+            let plaintext = crypto::decrypt_with_passphrase(passphrase, blob)?;
+            serde_json::from_slice(&plaintext).map_err(|e| format!("凭证内容解析失败: {}", e))?
+        }
+        (Some(_), None) => return Err(tr("backend.errors.config_bundle.passphrase_required", &[])),
+        (None, _) => BTreeMap::new(),
+    };
+
+    let mut config = bundle.config;
+    let (mut restored, mut kept) = (0, 0);
+    for pointer in &bundle.secret_fields {
+        let value = match secrets.get(pointer) {
+            Some(value) => {
+                restored += 1;
+                value.clone()
+            }
+            None => match local.pointer(pointer) {
+                Some(value) => {
+                    kept += 1;
+                    value.clone()
+                }
+                None => continue,
+            },
+        };
+        if let Some(slot) = config.pointer_mut(pointer) {
+            *slot = value;
+        }
+    }
+    Ok((config, restored, kept))
+}
+
+fn current_value() -> Result<Value, String> {
+    let app_config = crate::modules::config::load_app_config()?;
+    Ok(json!({
+        "app_config": serde_json::to_value(&app_config).map_err(|e| e.to_string())?,
+        "presets": crate::modules::config_preset::export_value()?,
+    }))
+}
+
+/// 序列化当前配置为配置包，返回 (配置包内容, 凭证字段数, 方案数, 凭证是否已加密导出)
+pub(crate) fn export_string(passphrase: Option<&str>) -> Result<(String, usize, usize, bool), String> {
+    let config = current_value()?;
+    let presets = config["presets"]["presets"].as_object().map(|p| p.len()).unwrap_or(0);
+    let (bundle, secret_fields) = build_bundle(config, passphrase)?;
+    let secrets_included = bundle.secrets.is_some();
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    Ok((content, secret_fields, presets, secrets_included))
+}
+
+/// 导出配置包；提供口令时凭证加密后一并导出
+pub fn export_config(path: &str, passphrase: Option<&str>) -> Result<ConfigExportSummary, String> {
+    let (content, secret_fields, presets, secrets_included) = export_string(passphrase)?;
+    std::fs::write(path, content).map_err(|e| format!("写入配置包失败: {}", e))?;
+    crate::modules::logger::log_info(&format!("已导出配置包: {} (凭证{})", path, if secrets_included { "已加密" } else { "未导出" }));
+    Ok(ConfigExportSummary { path: path.to_string(), secret_fields, secrets_included, presets })
+}
+
+/// 解析配置包内容并写入本机配置方案，返回待保存的应用配置 (由调用方保存并热应用)
+pub(crate) fn import_string(content: &str, passphrase: Option<&str>) -> Result<(AppConfig, ConfigImportReport), String> {
+    let bundle: ConfigBundle = serde_json::from_str(content).map_err(|e| format!("配置包格式错误: {}", e))?;
+    let (mut config, secrets_restored, secrets_kept_local) = open_bundle(bundle, passphrase, &current_value()?)?;
+
+    let app_config: AppConfig =
+        serde_json::from_value(config["app_config"].take()).map_err(|e| format!("配置包中的应用配置无效: {}", e))?;
+    let presets = match config.get_mut("presets").map(Value::take) {
+        Some(Value::Null) | None => 0,
+        Some(presets) => crate::modules::config_preset::import_value(presets)?,
+    };
+    Ok((app_config, ConfigImportReport { secrets_restored, secrets_kept_local, presets }))
+}
+
+/// 读取配置包并写入本机配置方案，返回待保存的应用配置 (由调用方保存并热应用)
+pub fn import_config(path: &str, passphrase: Option<&str>) -> Result<(AppConfig, ConfigImportReport), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取配置包失败: {}", e))?;
+    let (app_config, report) = import_string(&content, passphrase)?;
+    crate::modules::logger::log_info(&format!(
+        "已导入配置包: 恢复凭证 {}, 保留本机凭证 {}, 配置方案 {}",
+        report.secrets_restored, report.secrets_kept_local, report.presets
+    ));
+    Ok((app_config, report))
+}
+
+/// 配置包中去掉凭证后的配置内容的指纹 (与导出时间、版本无关，用于判断两份配置是否一致)
+pub(crate) fn fingerprint(content: &str) -> Result<String, String> {
+    let bundle: ConfigBundle = serde_json::from_str(content).map_err(|e| format!("配置包格式错误: {}", e))?;
+    let canonical = canonicalize(bundle.config);
+    let bytes = serde_json::to_vec(&canonical).map_err(|e| e.to_string())?;
+    Ok(format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(&bytes)))
+}
+
+/// 对象键排序，避免键顺序影响指纹
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<String, Value> = map.into_iter().map(|(k, v)| (k, canonicalize(v))).collect();
+            Value::Object(sorted.into_iter().collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Value {
+        json!({
+            "app_config": {
+                "proxy": {
+                    "api_key": "sk-exported",
+                    "api_keys": ["sk-a", "sk-b"],
+                    "port": 8045,
+                    "max_tokens": 4096,
+                    "custom_mapping": { "gpt-4o": "gemini-2.5-pro" },
+                    "upstream_proxy": { "password": "", "url": "http://10.0.0.1:7890" },
+                }
+            },
+            "presets": { "presets": { "LAN": { "proxy": { "api_key": "sk-preset" } } } }
+        })
+    }
+
+    #[test]
+    fn test_secrets_are_stripped_and_encrypted() {
+        let (bundle, count) = build_bundle(sample(), Some("passphrase")).unwrap();
+        let text = serde_json::to_string(&bundle).unwrap();
+        assert!(!text.contains("sk-exported") && !text.contains("sk-preset") && !text.contains("sk-a"));
+        assert_eq!(count, 3);
+        assert_eq!(bundle.config["app_config"]["proxy"]["custom_mapping"]["gpt-4o"], "gemini-2.5-pro");
+
+        assert!(open_bundle(serde_json::from_str(&text).unwrap(), None, &json!({})).is_err());
+        assert!(open_bundle(serde_json::from_str(&text).unwrap(), Some("wrong"), &json!({})).is_err());
+        let (config, restored, kept) = open_bundle(serde_json::from_str(&text).unwrap(), Some("passphrase"), &json!({})).unwrap();
+        assert_eq!((restored, kept), (3, 0));
+        assert_eq!(config, sample());
+    }
+
+    #[test]
+    fn test_import_without_secrets_keeps_local_values() {
+        let (bundle, _) = build_bundle(sample(), None).unwrap();
+        assert!(bundle.secrets.is_none());
+        let local = json!({ "app_config": { "proxy": { "api_key": "sk-local", "api_keys": ["sk-local-2"] } } });
+        let (config, restored, kept) = open_bundle(bundle, None, &local).unwrap();
+        assert_eq!((restored, kept), (0, 2));
+        assert_eq!(config["app_config"]["proxy"]["api_key"], "sk-local");
+        assert_eq!(config["app_config"]["proxy"]["api_keys"], json!(["sk-local-2"]));
+        // 本机没有对应字段时保持为空
+        assert_eq!(config["presets"]["presets"]["LAN"]["proxy"]["api_key"], "");
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_secrets_and_metadata() {
+        let (a, _) = build_bundle(sample(), None).unwrap();
+        let mut other = sample();
+        other["app_config"]["proxy"]["api_key"] = json!("sk-different");
+        let (mut b, _) = build_bundle(other, Some("passphrase")).unwrap();
+        b.created_at += 60;
+        let fp = |bundle: &ConfigBundle| fingerprint(&serde_json::to_string(bundle).unwrap()).unwrap();
+        assert_eq!(fp(&a), fp(&b));
+
+        b.config["app_config"]["proxy"]["port"] = json!(9000);
+        assert_ne!(fp(&a), fp(&b));
+    }
+}
@@ -0,0 +1,104 @@
+// 配置文件格式 (JSON / TOML / YAML)
+//
+// 无界面部署的用户通常手工编辑配置，希望能写注释。应用配置除 `gui_config.json` 外也接受
+// `gui_config.toml`、`gui_config.yaml` / `gui_config.yml`，按扩展名识别格式；内部统一转换为
+// JSON 值处理 (版本迁移、反序列化)，保存时写回原格式。注意界面保存会重新生成文件，注释不会保留。
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// 按扩展名识别，未知扩展名按 JSON 处理
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn parse(self, content: &str) -> Result<Value, String> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn serialize(self, value: &Value) -> Result<String, String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            // TOML 没有 null，空值字段直接省略 (反序列化时回落到默认值)
+            ConfigFormat::Toml => toml::to_string_pretty(&strip_nulls(value.clone())).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn strip_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter().filter(|(_, v)| !v.is_null()).map(|(k, v)| (k, strip_nulls(v))).collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().filter(|v| !v.is_null()).map(strip_nulls).collect()),
+        other => other,
+    }
+}
+
+/// 在目录中查找 `<stem>.json|toml|yaml|yml`，都不存在时返回 JSON 路径
+pub fn resolve_path(dir: &Path, stem: &str) -> PathBuf {
+    ["json", "toml", "yaml", "yml"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .find(|p| p.exists())
+        .unwrap_or_else(|| dir.join(format!("{}.json", stem)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AppConfig;
+
+    #[test]
+    fn test_detects_format_by_extension() {
+        assert_eq!(ConfigFormat::from_path(Path::new("gui_config.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("gui_config.YML")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("gui_config.json")), ConfigFormat::Json);
+    }
+
+    #[test]
+    fn test_hand_written_toml_and_yaml_parse() {
+        let toml = r#"
+            # 本机 Claude Code 使用
+            language = "en"
+
+            [proxy]
+            port = 9090
+            allow_lan_access = true
+        "#;
+        let yaml = "language: en\nproxy:\n  port: 9090  # 端口\n  allow_lan_access: true\n";
+        for (format, content) in [(ConfigFormat::Toml, toml), (ConfigFormat::Yaml, yaml)] {
+            let value = format.parse(content).unwrap();
+            assert_eq!(value["proxy"]["port"], 9090);
+            assert_eq!(value["proxy"]["allow_lan_access"], true);
+        }
+    }
+
+    #[test]
+    fn test_full_config_round_trips() {
+        let config = AppConfig::new();
+        let value = serde_json::to_value(&config).unwrap();
+        for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let text = format.serialize(&value).unwrap();
+            let parsed: AppConfig = serde_json::from_value(format.parse(&text).unwrap()).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), value, "{:?}", format);
+        }
+    }
+}
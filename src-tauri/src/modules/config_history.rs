@@ -0,0 +1,176 @@
+// 配置变更历史
+//
+// 每次保存应用配置 (界面修改、切换方案、导入、同步) 以及检测到配置文件被外部修改时，
+// 把新版本记录到数据目录的 `config_history.json`，最多保留 MAX_ENTRIES 个版本。
+// 与上一版本内容相同时不记录。回滚即把选中的版本重新保存为当前配置 (回滚本身也会记入历史，
+// 因此可以撤销回滚)。新版本的变更字段与 API Key 增减同时记入安全审计日志 (见 audit_log)。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::AppConfig;
+
+const HISTORY_FILE: &str = "config_history.json";
+const MAX_ENTRIES: usize = 30;
+
+/// 版本来源
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySource {
+    /// 应用内保存
+    App,
+    /// 配置文件被外部修改
+    External,
+    /// 回滚
+    Rollback,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    id: String,
+    /// Unix 毫秒
+    timestamp: i64,
+    source: HistorySource,
+    config: Value,
+}
+
+/// 历史版本摘要 (新版本在前)
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigHistoryItem {
+    pub id: String,
+    pub timestamp: i64,
+    pub source: HistorySource,
+    /// 相对上一版本变化的字段 (顶层字段，proxy 展开一级，如 `proxy.port`)
+    pub changed_fields: Vec<String>,
+    /// 与当前配置相同
+    pub current: bool,
+}
+
+fn history_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(HISTORY_FILE))
+}
+
+fn load_entries() -> Vec<HistoryEntry> {
+    let Ok(path) = history_path() else { return Vec::new() };
+    std::fs::read_to_string(path).ok().and_then(|content| serde_json::from_str(&content).ok()).unwrap_or_default()
+}
+
+fn save_entries(entries: &[HistoryEntry]) -> Result<(), String> {
+    let content = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    std::fs::write(history_path()?, content).map_err(|e| format!("保存配置历史失败: {}", e))
+}
+
+/// 追加一个版本；与最新版本相同时跳过，超出上限时丢弃最旧的版本。返回是否追加
+fn push_entry(entries: &mut Vec<HistoryEntry>, config: Value, source: HistorySource, timestamp: i64) -> bool {
+    if entries.last().is_some_and(|last| last.config == config) {
+        return false;
+    }
+    entries.push(HistoryEntry { id: uuid::Uuid::new_v4().simple().to_string(), timestamp, source, config });
+    if entries.len() > MAX_ENTRIES {
+        let excess = entries.len() - MAX_ENTRIES;
+        entries.drain(..excess);
+    }
+    true
+}
+
+pub fn is_empty() -> bool {
+    load_entries().is_empty()
+}
+
+/// 记录一个配置版本 (失败只记录日志，不影响保存)
+///
+/// 历史为空时先用 `baseline` (覆盖前的配置) 建立第一个版本，保证第一次修改也能回滚
+pub fn record(config: &AppConfig, source: HistorySource, baseline: Option<AppConfig>) {
+    let Ok(value) = serde_json::to_value(config) else { return };
+    let mut entries = load_entries();
+    let now = chrono::Utc::now().timestamp_millis();
+    if entries.is_empty() {
+        if let Some(previous) = baseline.and_then(|c| serde_json::to_value(c).ok()) {
+            push_entry(&mut entries, previous, HistorySource::App, now);
+        }
+    }
+    let previous = entries.last().map(|entry| entry.config.clone());
+    if push_entry(&mut entries, value.clone(), source, now) {
+        if let Some(previous) = &previous {
+            crate::modules::audit_log::record_config_change(source, previous, &value);
+        }
+        if let Err(e) = save_entries(&entries) {
+            tracing::warn!("[ConfigHistory] {}", e);
+        }
+    }
+}
+
+pub(crate) fn changed_fields(before: Option<&Value>, after: &Value) -> Vec<String> {
+    let Some(before) = before else { return Vec::new() };
+    let mut fields = Vec::new();
+    let (Some(a), Some(b)) = (before.as_object(), after.as_object()) else { return fields };
+    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        match (a.get(key), b.get(key)) {
+            (Some(Value::Object(x)), Some(Value::Object(y))) if key == "proxy" => {
+                let mut nested: Vec<&String> = x.keys().chain(y.keys()).collect();
+                nested.sort();
+                nested.dedup();
+                fields.extend(nested.into_iter().filter(|k| x.get(*k) != y.get(*k)).map(|k| format!("proxy.{}", k)));
+            }
+            (x, y) if x != y => fields.push(key.clone()),
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// 列出历史版本 (新版本在前)
+pub fn list(current: &AppConfig) -> Vec<ConfigHistoryItem> {
+    let current = serde_json::to_value(current).unwrap_or_default();
+    let entries = load_entries();
+    let mut items: Vec<ConfigHistoryItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| ConfigHistoryItem {
+            id: entry.id.clone(),
+            timestamp: entry.timestamp,
+            source: entry.source,
+            changed_fields: changed_fields(i.checked_sub(1).map(|p| &entries[p].config), &entry.config),
+            current: entry.config == current,
+        })
+        .collect();
+    items.reverse();
+    items
+}
+
+/// 取出历史版本的配置 (由调用方保存并热应用)
+pub fn get(id: &str) -> Result<AppConfig, String> {
+    let entry = load_entries().into_iter().find(|e| e.id == id).ok_or_else(|| format!("找不到配置版本 {}", id))?;
+    serde_json::from_value(entry.config).map_err(|e| format!("历史版本格式无效: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_history_is_bounded_and_deduplicated() {
+        let mut entries = Vec::new();
+        assert!(push_entry(&mut entries, json!({ "n": 0 }), HistorySource::App, 1));
+        assert!(!push_entry(&mut entries, json!({ "n": 0 }), HistorySource::External, 2));
+        for n in 1..(MAX_ENTRIES as i64 + 5) {
+            push_entry(&mut entries, json!({ "n": n }), HistorySource::App, 10 + n);
+        }
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries.last().unwrap().config, json!({ "n": MAX_ENTRIES as i64 + 4 }));
+        let ids: std::collections::HashSet<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids.len(), entries.len());
+    }
+
+    #[test]
+    fn test_changed_fields_expand_proxy() {
+        let before = json!({ "language": "zh", "proxy": { "port": 8045, "api_key": "a" } });
+        let after = json!({ "language": "en", "proxy": { "port": 9000, "api_key": "a" } });
+        assert_eq!(changed_fields(Some(&before), &after), vec!["language", "proxy.port"]);
+        assert!(changed_fields(None, &after).is_empty());
+    }
+}
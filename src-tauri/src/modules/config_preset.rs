@@ -0,0 +1,195 @@
+// 反代配置方案 (Preset)
+//
+// 在当前档案内保存多套命名的反代配置 (端口、模型映射、账号池、路由规则等)，
+// 例如 "Claude Code 本机" 与 "OpenWebUI 局域网"，可一键切换。与档案 (profile) 不同，
+// 方案共享同一账号库，切换时热应用到运行中的服务，只有端口或监听地址变化才重启。
+// 方案保存在数据目录的 `config_presets.json` 中。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::modules::config_schema::{load_migrated, to_versioned_string, PRESETS_MIGRATIONS};
+use crate::proxy::ProxyConfig;
+use crate::modules::i18n::tr;
+
+const PRESETS_FILE: &str = "config_presets.json";
+const MAX_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredPreset {
+    /// Unix 秒
+    updated_at: i64,
+    proxy: ProxyConfig,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PresetStore {
+    /// 最近一次切换到的方案
+    #[serde(default)]
+    active: Option<String>,
+    #[serde(default)]
+    presets: BTreeMap<String, StoredPreset>,
+}
+
+/// 方案信息
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ConfigPresetInfo {
+    pub name: String,
+    /// 最近一次切换到的方案
+    pub active: bool,
+    /// 与当前反代配置完全一致 (切换后未再修改)
+    pub matches_current: bool,
+    pub port: u16,
+    pub allow_lan_access: bool,
+    pub mapping_count: usize,
+    pub pool_count: usize,
+    pub updated_at: i64,
+}
+
+/// 名称去除首尾空白后需为 1-64 个字符，且不含控制字符
+fn normalize_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(tr("backend.errors.config_preset.name_length", &[("max", &MAX_NAME_CHARS.to_string())]));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(tr("backend.errors.config_preset.name_control_chars", &[]));
+    }
+    Ok(name.to_string())
+}
+
+fn same_config(a: &ProxyConfig, b: &ProxyConfig) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+impl PresetStore {
+    fn list(&self, current: &ProxyConfig) -> Vec<ConfigPresetInfo> {
+        self.presets
+            .iter()
+            .map(|(name, preset)| ConfigPresetInfo {
+                name: name.clone(),
+                active: self.active.as_deref() == Some(name.as_str()),
+                matches_current: same_config(&preset.proxy, current),
+                port: preset.proxy.port,
+                allow_lan_access: preset.proxy.allow_lan_access,
+                mapping_count: preset.proxy.custom_mapping.len(),
+                pool_count: preset.proxy.account_pools.len(),
+                updated_at: preset.updated_at,
+            })
+            .collect()
+    }
+
+    fn save(&mut self, name: &str, proxy: ProxyConfig, now: i64) -> Result<String, String> {
+        let name = normalize_name(name)?;
+        self.presets.insert(name.clone(), StoredPreset { updated_at: now, proxy });
+        self.active = Some(name.clone());
+        Ok(name)
+    }
+
+    fn delete(&mut self, name: &str) -> Result<(), String> {
+        self.presets.remove(name).ok_or_else(|| format!("方案不存在: {}", name))?;
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        Ok(())
+    }
+
+    fn activate(&mut self, name: &str) -> Result<ProxyConfig, String> {
+        let preset = self.presets.get(name).ok_or_else(|| format!("方案不存在: {}", name))?;
+        let proxy = preset.proxy.clone();
+        self.active = Some(name.to_string());
+        Ok(proxy)
+    }
+}
+
+pub(crate) fn presets_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(PRESETS_FILE))
+}
+
+fn load_store() -> Result<PresetStore, String> {
+    match load_migrated(&presets_path()?, PRESETS_MIGRATIONS).map_err(|e| format!("读取配置方案失败: {}", e))? {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("解析配置方案失败: {}", e)),
+        None => Ok(PresetStore::default()),
+    }
+}
+
+fn save_store(store: &PresetStore) -> Result<(), String> {
+    let content = to_versioned_string(store, PRESETS_MIGRATIONS)?;
+    std::fs::write(presets_path()?, content).map_err(|e| format!("保存配置方案失败: {}", e))
+}
+
+/// 方案文件的原始内容 (供配置包导出)
+pub(crate) fn export_value() -> Result<serde_json::Value, String> {
+    serde_json::to_value(load_store()?).map_err(|e| e.to_string())
+}
+
+/// 用配置包中的方案替换本地方案 (先校验格式)
+pub(crate) fn import_value(value: serde_json::Value) -> Result<usize, String> {
+    let store: PresetStore = serde_json::from_value(value).map_err(|e| format!("配置方案格式错误: {}", e))?;
+    save_store(&store)?;
+    Ok(store.presets.len())
+}
+
+/// 列出所有方案 (按名称排序)
+pub fn list_presets() -> Result<Vec<ConfigPresetInfo>, String> {
+    let current = crate::modules::config::load_app_config()?.proxy;
+    Ok(load_store()?.list(&current))
+}
+
+/// 将当前反代配置保存为方案 (同名覆盖)，返回规范化后的名称
+pub fn save_current_as(name: &str) -> Result<String, String> {
+    let current = crate::modules::config::load_app_config()?.proxy;
+    let mut store = load_store()?;
+    let name = store.save(name, current, chrono::Utc::now().timestamp())?;
+    save_store(&store)?;
+    Ok(name)
+}
+
+pub fn delete_preset(name: &str) -> Result<(), String> {
+    let mut store = load_store()?;
+    store.delete(name)?;
+    save_store(&store)
+}
+
+/// 把方案写入应用配置并标记为当前方案，返回方案的反代配置 (由调用方应用到运行中的服务)
+pub fn activate_preset(name: &str) -> Result<ProxyConfig, String> {
+    let mut store = load_store()?;
+    let proxy = store.activate(name)?;
+    let mut app_config = crate::modules::config::load_app_config()?;
+    app_config.proxy = proxy.clone();
+    crate::modules::config::save_app_config(&app_config)?;
+    save_store(&store)?;
+    Ok(proxy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_track_active_and_current() {
+        let mut store = PresetStore::default();
+        let local = ProxyConfig::default();
+        let mut lan = ProxyConfig { allow_lan_access: true, port: 8080, ..ProxyConfig::default() };
+        lan.custom_mapping.insert("gpt-4o".to_string(), "gemini-2.5-pro".to_string());
+
+        assert_eq!(store.save("  Claude Code  ", local.clone(), 1).unwrap(), "Claude Code");
+        store.save("OpenWebUI LAN", lan.clone(), 2).unwrap();
+        assert!(store.save(" ", local.clone(), 3).is_err());
+        assert!(store.save("bad\nname", local.clone(), 3).is_err());
+
+        let activated = store.activate("Claude Code").unwrap();
+        assert_eq!(activated.port, local.port);
+        let listed = store.list(&activated);
+        assert_eq!(
+            listed.iter().map(|p| (p.name.as_str(), p.active, p.matches_current)).collect::<Vec<_>>(),
+            [("Claude Code", true, true), ("OpenWebUI LAN", false, false)]
+        );
+        assert_eq!((listed[1].port, listed[1].mapping_count), (8080, 1));
+
+        store.delete("Claude Code").unwrap();
+        assert_eq!(store.active, None);
+        assert!(store.delete("Claude Code").is_err());
+        assert!(store.activate("missing").is_err());
+    }
+}
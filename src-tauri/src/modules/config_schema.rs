@@ -0,0 +1,192 @@
+// 配置文件版本与迁移
+//
+// 持久化的配置文件 (应用配置、配置方案、更新设置) 顶层带 `schema_version` 字段。读取时按顺序
+// 执行版本号大于文件版本的迁移步骤，先把原文件备份为 `<文件名>.v<旧版本>.bak` 再写回新格式，
+// 避免格式变化后解析失败被当成空配置而重置用户设置。文件版本高于当前程序支持的版本时
+// 直接报错 (不覆盖文件)，提示升级应用。启动时 [`migrate_all`] 会迁移所有文件。
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use super::config_format::ConfigFormat;
+use crate::modules::i18n::tr;
+
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// 单个迁移步骤：把文件从 `to - 1` 升级到 `to`
+pub struct Migration {
+    pub to: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut Map<String, Value>),
+}
+
+/// 应用配置 (gui_config.json / .toml / .yaml)
+pub const APP_CONFIG_MIGRATIONS: &[Migration] = &[Migration {
+    to: 1,
+    description: "合并旧版 anthropic_mapping / openai_mapping 到 custom_mapping",
+    apply: merge_legacy_mappings,
+}];
+
+/// 配置方案 (config_presets.json)
+pub const PRESETS_MIGRATIONS: &[Migration] = &[Migration { to: 1, description: "引入版本号", apply: |_| {} }];
+
+/// 更新设置 (update_settings.json)
+pub const UPDATE_SETTINGS_MIGRATIONS: &[Migration] = &[Migration { to: 1, description: "引入版本号", apply: |_| {} }];
+
+/// 远程同步设置 (config_sync.json)
+pub const CONFIG_SYNC_MIGRATIONS: &[Migration] = &[Migration { to: 1, description: "初始版本", apply: |_| {} }];
+
+/// 迁移到的最新版本
+pub fn current_version(migrations: &[Migration]) -> u32 {
+    migrations.last().map(|m| m.to).unwrap_or(0)
+}
+
+fn merge_legacy_mappings(config: &mut Map<String, Value>) {
+    let Some(Value::Object(proxy)) = config.get_mut("proxy") else { return };
+    let mut custom_mapping = proxy.get("custom_mapping").and_then(Value::as_object).cloned().unwrap_or_default();
+    let mut modified = false;
+    for legacy in ["anthropic_mapping", "openai_mapping"] {
+        if let Some(Value::Object(mapping)) = proxy.remove(legacy) {
+            // 只有非系列字段才搬移。因为系列字段现在由 Preset 逻辑或内置表处理
+            for (k, v) in mapping {
+                if !k.ends_with("-series") && !custom_mapping.contains_key(&k) {
+                    custom_mapping.insert(k, v);
+                }
+            }
+            modified = true;
+        }
+    }
+    if modified {
+        proxy.insert("custom_mapping".to_string(), Value::Object(custom_mapping));
+    }
+}
+
+/// 把 JSON 升级到最新版本，返回迁移前的版本 (已是最新则为 None)
+pub fn migrate_value(value: &mut Value, migrations: &[Migration]) -> Result<Option<u32>, String> {
+    let current = current_version(migrations);
+    let Value::Object(map) = value else { return Err(tr("backend.errors.config.not_object", &[])) };
+    let version = map.get(SCHEMA_VERSION_FIELD).and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > current {
+        return Err(tr("backend.errors.config.version_too_new", &[("version", &version.to_string()), ("current", &current.to_string())]));
+    }
+    if version == current {
+        return Ok(None);
+    }
+    for migration in migrations.iter().filter(|m| m.to > version) {
+        (migration.apply)(map);
+        tracing::info!("[Schema] v{}: {}", migration.to, migration.description);
+    }
+    map.insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(current));
+    Ok(Some(version))
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}.v{}.bak", name, version))
+}
+
+/// 读取配置文件并迁移到最新版本 (文件不存在时返回 None)
+///
+/// 需要迁移时先备份原文件，再写回迁移后的内容
+pub fn load_migrated(path: &Path, migrations: &[Migration]) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let format = ConfigFormat::from_path(path);
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let mut value = format.parse(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    if let Some(from) = migrate_value(&mut value, migrations)? {
+        let backup = backup_path(path, from);
+        std::fs::copy(path, &backup).map_err(|e| format!("备份旧版配置失败: {}", e))?;
+        let migrated = format.serialize(&value)?;
+        std::fs::write(path, migrated).map_err(|e| format!("写入迁移后的配置失败: {}", e))?;
+        tracing::info!(
+            "[Schema] {} 已从 v{} 迁移到 v{} (原文件备份为 {})",
+            path.display(),
+            from,
+            current_version(migrations),
+            backup.display()
+        );
+    }
+    Ok(Some(value))
+}
+
+/// 序列化并带上当前版本号
+pub fn to_versioned_string<T: Serialize>(value: &T, migrations: &[Migration]) -> Result<String, String> {
+    to_versioned_string_as(value, migrations, ConfigFormat::Json)
+}
+
+/// 按指定格式序列化并带上当前版本号
+pub fn to_versioned_string_as<T: Serialize>(
+    value: &T,
+    migrations: &[Migration],
+    format: ConfigFormat,
+) -> Result<String, String> {
+    let mut json = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    if let Value::Object(map) = &mut json {
+        map.insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(current_version(migrations)));
+    }
+    format.serialize(&json)
+}
+
+/// 启动时迁移所有配置文件；单个文件失败只记录错误，不影响其他文件
+pub fn migrate_all() {
+    let files: Vec<(Result<PathBuf, String>, &[Migration])> = vec![
+        (crate::modules::config::config_path(), APP_CONFIG_MIGRATIONS),
+        (crate::modules::config_preset::presets_path(), PRESETS_MIGRATIONS),
+        (crate::modules::update_checker::settings_path(), UPDATE_SETTINGS_MIGRATIONS),
+        (crate::modules::config_sync::settings_path(), CONFIG_SYNC_MIGRATIONS),
+    ];
+    for (path, migrations) in files {
+        if let Err(e) = path.and_then(|path| load_migrated(&path, migrations)) {
+            tracing::error!("[Schema] 配置文件迁移失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_legacy_config_is_migrated_once() {
+        let mut value = json!({
+            "language": "zh",
+            "proxy": {
+                "custom_mapping": { "gpt-4o": "gemini-2.5-pro" },
+                "anthropic_mapping": { "claude-opus-4": "claude-opus-4-5-thinking", "claude-4-series": "x" },
+                "openai_mapping": { "gpt-4o": "gemini-2.5-flash" },
+            }
+        });
+        assert_eq!(migrate_value(&mut value, APP_CONFIG_MIGRATIONS).unwrap(), Some(0));
+        assert_eq!(value[SCHEMA_VERSION_FIELD], 1);
+        assert_eq!(
+            value["proxy"],
+            json!({ "custom_mapping": { "gpt-4o": "gemini-2.5-pro", "claude-opus-4": "claude-opus-4-5-thinking" } })
+        );
+        assert_eq!(migrate_value(&mut value, APP_CONFIG_MIGRATIONS).unwrap(), None);
+
+        let mut future = json!({ "schema_version": 99 });
+        assert!(migrate_value(&mut future, APP_CONFIG_MIGRATIONS).is_err());
+    }
+
+    #[test]
+    fn test_migration_backs_up_original_file() {
+        let dir = std::env::temp_dir().join(format!("ag-schema-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("update_settings.json");
+        std::fs::write(&path, r#"{"auto_check":true,"last_check_time":0}"#).unwrap();
+
+        let value = load_migrated(&path, UPDATE_SETTINGS_MIGRATIONS).unwrap().unwrap();
+        assert_eq!(value[SCHEMA_VERSION_FIELD], 1);
+        let backup = std::fs::read_to_string(dir.join("update_settings.json.v0.bak")).unwrap();
+        assert!(!backup.contains(SCHEMA_VERSION_FIELD));
+        let written: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, value);
+        assert!(load_migrated(&dir.join("missing.json"), UPDATE_SETTINGS_MIGRATIONS).unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
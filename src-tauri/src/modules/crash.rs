@@ -0,0 +1,227 @@
+// 崩溃报告 (可选，默认关闭)
+//
+// 开启后安装 panic hook：发生 panic 时在数据目录的 `crashes/` 下写入崩溃报告
+// (版本与系统信息、panic 信息、调用栈、最近的日志末尾)。下次启动时前端通过
+// [`list_crash_reports`] 取出未查看的报告提示用户，并可生成预填内容的 GitHub Issue 链接。
+// 报告只保存在本机，日志末尾沿用诊断包的脱敏规则。
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::modules::i18n::tr;
+
+/// 报告中保留的日志末尾行数
+const LOG_TAIL_LINES: usize = 200;
+/// 最多保留的崩溃报告数 (超出后删除最旧的)
+const MAX_REPORTS: usize = 20;
+/// Issue 链接中调用栈的最大字符数 (编码后的 URL 需在 GitHub 限制的约 8KB 内)
+const MAX_ISSUE_BACKTRACE_CHARS: usize = 2000;
+const ISSUE_URL: &str = "https://github.com/lbjlaq/Antigravity-Manager/issues/new";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    /// Unix 毫秒
+    pub timestamp: i64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    /// panic 位置 (文件:行:列)
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub log_tail: String,
+    /// 用户已查看 (不再提示)
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+fn crashes_dir() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join("crashes"))
+}
+
+/// id 由本模块生成，只允许字母数字与 '-'，防止路径穿越
+fn validate_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(tr("backend.errors.crash.invalid_id", &[("id", id)]));
+    }
+    Ok(())
+}
+
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    validate_id(id)?;
+    Ok(crashes_dir()?.join(format!("{}.json", id)))
+}
+
+fn panic_message(info: &std::panic::PanicHookInfo<'_>) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// 最新日志文件的末尾若干行 (脱敏后)
+fn log_tail() -> String {
+    let Ok(dir) = crate::modules::logger::get_log_dir() else { return String::new() };
+    let latest = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .filter(|(_, path)| path.is_file())
+        .max_by_key(|(modified, _)| *modified);
+    let Some((_, path)) = latest else { return String::new() };
+    let Ok(bytes) = std::fs::read(path) else { return String::new() };
+    let text = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n");
+    crate::modules::diagnostics::redact_string(&tail)
+}
+
+fn write_report(report: &CrashReport) -> Result<PathBuf, String> {
+    let dir = crashes_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建崩溃报告目录失败: {}", e))?;
+    let path = report_path(&report.id)?;
+    let content = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入崩溃报告失败: {}", e))?;
+    Ok(path)
+}
+
+/// 删除超出保留数量的旧报告
+fn prune_reports() {
+    let Ok(mut reports) = list_all() else { return };
+    if reports.len() <= MAX_REPORTS {
+        return;
+    }
+    reports.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+    for report in &reports[MAX_REPORTS..] {
+        if let Ok(path) = report_path(&report.id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 根据配置安装 panic hook (启动时调用一次)；原有的 hook 仍会执行
+pub fn install_panic_hook() {
+    let enabled = crate::modules::config::load_app_config().map(|c| c.crash_reports).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let now = chrono::Local::now();
+        let report = CrashReport {
+            id: format!("crash-{}-{}", now.format("%Y%m%d-%H%M%S"), &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            timestamp: now.timestamp_millis(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current().name().unwrap_or("<unnamed>").to_string(),
+            message: panic_message(info),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            log_tail: log_tail(),
+            acknowledged: false,
+        };
+        match write_report(&report) {
+            Ok(path) => tracing::error!("Panic: {} (crash report saved to {})", report.message, path.display()),
+            Err(e) => tracing::error!("Panic: {} (failed to save crash report: {})", report.message, e),
+        }
+        prune_reports();
+        previous(info);
+    }));
+}
+
+fn list_all() -> Result<Vec<CrashReport>, String> {
+    let dir = crashes_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reports: Vec<CrashReport> = std::fs::read_dir(&dir)
+        .map_err(|e| format!("读取崩溃报告目录失败: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| serde_json::from_str(&std::fs::read_to_string(entry.path()).ok()?).ok())
+        .collect();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+    Ok(reports)
+}
+
+/// 列出崩溃报告 (新的在前)；`pending_only` 时只返回未查看的
+pub fn list_crash_reports(pending_only: bool) -> Result<Vec<CrashReport>, String> {
+    Ok(list_all()?.into_iter().filter(|r| !pending_only || !r.acknowledged).collect())
+}
+
+/// 标记报告已查看，不再提示
+pub fn acknowledge_crash_report(id: &str) -> Result<(), String> {
+    let path = report_path(id)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取崩溃报告失败: {}", e))?;
+    let mut report: CrashReport = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    report.acknowledged = true;
+    write_report(&report).map(|_| ())
+}
+
+/// 生成预填标题与正文的 GitHub Issue 链接 (日志末尾不放入链接，需用户自行附上报告文件)
+pub fn issue_url(report: &CrashReport) -> String {
+    let backtrace: String = report.backtrace.chars().take(MAX_ISSUE_BACKTRACE_CHARS).collect();
+    let body = format!(
+        "### 崩溃信息\n\n- 版本: {}\n- 系统: {} ({})\n- 线程: {}\n- 位置: {}\n\n```\n{}\n```\n\n### 调用栈\n\n```\n{}\n```\n\n### 复现步骤\n\n",
+        report.app_version,
+        report.os,
+        report.arch,
+        report.thread,
+        report.location.as_deref().unwrap_or("unknown"),
+        report.message,
+        backtrace
+    );
+    let title = format!("[Crash] {}", report.message.lines().next().unwrap_or_default().chars().take(80).collect::<String>());
+    url::Url::parse_with_params(ISSUE_URL, &[("title", title.as_str()), ("body", body.as_str())])
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| ISSUE_URL.to_string())
+}
+
+/// 按 ID 生成 Issue 链接
+pub fn crash_report_issue_url(id: &str) -> Result<String, String> {
+    let content = std::fs::read_to_string(report_path(id)?).map_err(|e| format!("读取崩溃报告失败: {}", e))?;
+    let report: CrashReport = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    Ok(issue_url(&report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_url_is_prefilled_and_bounded() {
+        let report = CrashReport {
+            id: "crash-20250101-000000-abcdef12".to_string(),
+            timestamp: 0,
+            app_version: "1.0.0".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            thread: "tokio-runtime-worker".to_string(),
+            message: "index out of bounds: the len is 0 but the index is 1\nmore".to_string(),
+            location: Some("src/proxy/monitor.rs:10:5".to_string()),
+            backtrace: "frame\n".repeat(10_000),
+            log_tail: "secret logs".to_string(),
+            acknowledged: false,
+        };
+        let url = url::Url::parse(&issue_url(&report)).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["title"], "[Crash] index out of bounds: the len is 0 but the index is 1");
+        assert!(params["body"].contains("src/proxy/monitor.rs:10:5"));
+        assert!(!params["body"].contains("secret logs"));
+        assert!(params["body"].len() < MAX_ISSUE_BACKTRACE_CHARS + 500);
+    }
+
+    #[test]
+    fn test_report_ids_cannot_escape_the_crash_dir() {
+        assert!(validate_id("../config").is_err());
+        assert!(validate_id("a/b").is_err());
+        assert!(validate_id("").is_err());
+        assert!(validate_id("crash-20250101-000000-abcdef12").is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+// `antigravity-manager://` 深度链接
+//
+// 通过链接打开应用并预填操作，方便在多台机器之间分享配置:
+// - `antigravity-manager://import-account?share=agshare1.xxx` 导入加密账号分享串 (口令由用户输入)
+// - `antigravity-manager://client-setup?base_url=http://192.168.1.2:8045&api_key=sk-xxx` 生成指向该地址的客户端配置
+//
+// 收到的链接先放入待处理队列再通知前端，应用由链接冷启动时前端加载完成后再取出。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::modules::logger;
+use crate::modules::i18n::tr;
+
+pub const SCHEME: &str = "antigravity-manager";
+/// 收到新链接时通知前端 (前端通过 `take_pending_deep_link` 取出)
+pub const DEEP_LINK_EVENT: &str = "deep-link://received";
+
+/// 链接对应的预填操作
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLinkAction {
+    /// 导入加密账号分享串
+    ImportAccount { share: String },
+    /// 生成指向指定反代地址的客户端配置
+    ClientSetup { base_url: String, api_key: Option<String> },
+}
+
+static PENDING: Lazy<Mutex<Option<DeepLinkAction>>> = Lazy::new(|| Mutex::new(None));
+
+/// 生成导入账号分享串的链接
+pub fn import_account_link(share: &str) -> String {
+    let mut url = Url::parse(&format!("{}://import-account", SCHEME)).expect("scheme 合法");
+    url.query_pairs_mut().append_pair("share", share);
+    url.to_string()
+}
+
+/// 解析深度链接
+pub fn parse(link: &str) -> Result<DeepLinkAction, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("无效的链接: {}", e))?;
+    if url.scheme() != SCHEME {
+        return Err(tr("backend.errors.deep_link.unsupported_scheme", &[("scheme", url.scheme())]));
+    }
+    // `scheme://action?..` 与 `scheme:action?..` 两种写法都接受
+    let action = url
+        .host_str()
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_ascii_lowercase();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    match action.as_str() {
+        "import-account" => {
+            let share = param("share").ok_or_else(|| tr("backend.errors.deep_link.missing_param", &[("param", "share")]))?;
+            Ok(DeepLinkAction::ImportAccount { share })
+        }
+        "client-setup" => {
+            let base_url = param("base_url").ok_or_else(|| tr("backend.errors.deep_link.missing_param", &[("param", "base_url")]))?;
+            let parsed = Url::parse(&base_url).map_err(|e| format!("无效的 base_url: {}", e))?;
+            if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+                return Err(tr("backend.errors.deep_link.invalid_base_url", &[("url", &base_url)]));
+            }
+            Ok(DeepLinkAction::ClientSetup {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                api_key: param("api_key"),
+            })
+        }
+        other => Err(tr("backend.errors.deep_link.unsupported_action", &[("action", other)])),
+    }
+}
+
+/// 处理系统转交的链接: 记录待处理操作、显示主窗口并通知前端
+#[cfg(feature = "gui")]
+pub fn handle_urls(app: &crate::utils::app::AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        match parse(url.as_str()) {
+            Ok(action) => {
+                logger::log_info(&format!("收到深度链接: {}://{}", SCHEME, url.host_str().unwrap_or_default()));
+                *PENDING.lock().unwrap() = Some(action);
+                crate::modules::tray::show_main_window(app);
+                crate::utils::app::emit(app, DEEP_LINK_EVENT, ());
+            }
+            Err(e) => logger::log_warn(&format!("忽略深度链接: {}", e)),
+        }
+    }
+}
+
+/// 取出待处理的链接操作 (取出后清空)
+pub fn take_pending() -> Option<DeepLinkAction> {
+    PENDING.lock().unwrap().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_import_account_roundtrip() {
+        let link = import_account_link("agshare1.abc-_=+/");
+        assert_eq!(
+            parse(&link).unwrap(),
+            DeepLinkAction::ImportAccount { share: "agshare1.abc-_=+/".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_parse_client_setup() {
+        let action = parse("antigravity-manager://client-setup?base_url=http%3A%2F%2F192.168.1.2%3A8045%2F&api_key=sk-1").unwrap();
+        assert_eq!(
+            action,
+            DeepLinkAction::ClientSetup {
+                base_url: "http://192.168.1.2:8045".to_string(),
+                api_key: Some("sk-1".to_string()),
+            }
+        );
+        assert!(parse("antigravity-manager:client-setup?base_url=http://localhost:8045").is_ok());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_links() {
+        assert!(parse("https://import-account?share=x").is_err());
+        assert!(parse("antigravity-manager://import-account").is_err());
+        assert!(parse("antigravity-manager://client-setup?base_url=file:///etc/passwd").is_err());
+        assert!(parse("antigravity-manager://delete-all").is_err());
+    }
+}
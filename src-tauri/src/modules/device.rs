@@ -3,13 +3,16 @@ use crate::modules::{logger, process};
 use chrono::Local;
 use rand::{distributions::Alphanumeric, Rng};
 use rusqlite::Connection;
+use serde::Serialize;
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
+use crate::modules::i18n::tr;
 
 const DATA_DIR: &str = ".antigravity_tools";
 const GLOBAL_BASELINE: &str = "device_original.json";
+const BACKUP_PREFIX: &str = "storage.json.backup_";
 
 fn get_data_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
@@ -96,7 +99,6 @@ pub fn get_state_db_path() -> Result<PathBuf, String> {
 }
 
 /// 备份 storage.json，返回备份文件路径
-#[allow(dead_code)]
 pub fn backup_storage(storage_path: &Path) -> Result<PathBuf, String> {
     if !storage_path.exists() {
         return Err(format!("storage.json 不存在: {:?}", storage_path));
@@ -105,7 +107,8 @@ pub fn backup_storage(storage_path: &Path) -> Result<PathBuf, String> {
         .parent()
         .ok_or_else(|| "无法获取 storage.json 的父目录".to_string())?;
     let backup_path = dir.join(format!(
-        "storage.json.backup_{}",
+        "{}{}",
+        BACKUP_PREFIX,
         Local::now().format("%Y%m%d_%H%M%S")
     ));
     fs::copy(storage_path, &backup_path).map_err(|e| format!("备份 storage.json 失败: {}", e))?;
@@ -156,7 +159,7 @@ pub fn write_profile(storage_path: &Path, profile: &DeviceProfile) -> Result<(),
         serde_json::from_str(&content).map_err(|e| format!("解析 storage.json 失败: {}", e))?;
 
     // 确保 telemetry 是对象
-    if !json.get("telemetry").map_or(false, |v| v.is_object()) {
+    if !json.get("telemetry").is_some_and(|v| v.is_object()) {
         if json.as_object_mut().is_some() {
             json["telemetry"] = serde_json::json!({});
         } else {
@@ -245,7 +248,6 @@ pub fn sync_service_machine_id(storage_path: &Path, service_id: &str) -> Result<
 }
 
 /// 从现有 storage.json 读取 serviceMachineId（无则用 telemetry.devDeviceId），回写缺失项并同步 state.vscdb
-#[allow(dead_code)]
 pub fn sync_service_machine_id_from_storage(storage_path: &Path) -> Result<(), String> {
     if !storage_path.exists() {
         return Err("storage.json 不存在，无法同步 serviceMachineId".to_string());
@@ -343,7 +345,6 @@ pub fn save_global_original(profile: &DeviceProfile) -> Result<(), String> {
 }
 
 /// 罗列当前目录下的 storage.json 备份（按时间降序）
-#[allow(dead_code)]
 pub fn list_backups(storage_path: &Path) -> Result<Vec<PathBuf>, String> {
     let dir = storage_path
         .parent()
@@ -353,7 +354,7 @@ pub fn list_backups(storage_path: &Path) -> Result<Vec<PathBuf>, String> {
         for entry in entries.flatten() {
             let path = entry.path();
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.starts_with("storage.json.backup_") {
+                if name.starts_with(BACKUP_PREFIX) {
                     backups.push(path);
                 }
             }
@@ -387,6 +388,84 @@ pub fn restore_backup(storage_path: &Path, use_oldest: bool) -> Result<PathBuf,
     Ok(target)
 }
 
+/// storage.json 备份
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageBackup {
+    pub file_name: String,
+    /// 备份时间 (unix 秒，取文件修改时间)
+    pub created_at: i64,
+    /// 备份中的设备指纹 (解析失败为 None)
+    pub profile: Option<DeviceProfile>,
+}
+
+/// 重置设备标识的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceReset {
+    /// 重置前的 storage.json 备份文件名
+    pub backup: String,
+    pub profile: DeviceProfile,
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// 只接受同目录下的备份文件名，避免路径穿越
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with(BACKUP_PREFIX) && !name.contains(['/', '\\']) && !name.contains("..")
+}
+
+/// 备份当前 storage.json 后写入一组全新的设备标识 (与账号绑定无关)
+pub fn reset_device_identity() -> Result<DeviceReset, String> {
+    let storage_path = get_storage_path()?;
+    // 首次重置前记录原始指纹
+    if let Ok(current) = read_profile(&storage_path) {
+        let _ = save_global_original(&current);
+    }
+    let backup = backup_storage(&storage_path)?;
+    let profile = generate_profile();
+    write_profile(&storage_path, &profile)?;
+    logger::log_info(&format!("已重置设备标识，原 storage.json 备份为 {:?}", backup));
+    Ok(DeviceReset { backup: file_name_of(&backup), profile })
+}
+
+/// 列出 storage.json 备份及其中的设备指纹 (新到旧)
+pub fn list_storage_backups() -> Result<Vec<StorageBackup>, String> {
+    let storage_path = get_storage_path()?;
+    Ok(list_backups(&storage_path)?
+        .into_iter()
+        .map(|path| StorageBackup {
+            file_name: file_name_of(&path),
+            created_at: fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp())
+                .unwrap_or(0),
+            profile: read_profile(&path).ok(),
+        })
+        .collect())
+}
+
+/// 还原指定的 storage.json 备份 (还原前先备份当前文件)，并同步 state.vscdb 中的 serviceMachineId
+pub fn restore_storage_backup(file_name: &str) -> Result<DeviceProfile, String> {
+    if !is_backup_name(file_name) {
+        return Err(tr("backend.errors.backup.invalid_name", &[("name", file_name)]));
+    }
+    let storage_path = get_storage_path()?;
+    let source = storage_path
+        .parent()
+        .ok_or_else(|| "无法获取 storage.json 的父目录".to_string())?
+        .join(file_name);
+    if !source.exists() {
+        return Err(tr("backend.errors.backup.not_found", &[("name", file_name)]));
+    }
+    let profile = read_profile(&source)?;
+    backup_storage(&storage_path)?;
+    fs::copy(&source, &storage_path).map_err(|e| format!("恢复备份失败: {}", e))?;
+    sync_service_machine_id_from_storage(&storage_path)?;
+    logger::log_info(&format!("已从备份恢复设备标识: {}", file_name));
+    Ok(profile)
+}
+
 /// 生成一组新的设备指纹（符合 Cursor/VSCode 风格）
 pub fn generate_profile() -> DeviceProfile {
     DeviceProfile {
@@ -421,3 +500,16 @@ fn new_standard_machine_id() -> String {
     }
     id
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_name_rejects_paths() {
+        assert!(is_backup_name("storage.json.backup_20250101_120000"));
+        assert!(!is_backup_name("storage.json"));
+        assert!(!is_backup_name("storage.json.backup_/../../etc/passwd"));
+        assert!(!is_backup_name("storage.json.backup_..\\x"));
+    }
+}
@@ -0,0 +1,153 @@
+// 导出已记录的反代流量为 HAR 文件
+//
+// 把请求监控 (capture) 记录下来的请求转换为 HAR 1.2，可直接拖进浏览器开发者工具查看，
+// 或附在 Issue 中发给维护者。导出前去掉凭证：URL 中的 key / token 参数 (utils::redact)、
+// 请求与响应内容里的密钥 (沿用实时检查器的脱敏规则)，账号邮箱只保留首字母与域名。
+// 请求监控不记录请求头，因此 headers 为空。
+
+use serde_json::{json, Value};
+
+use crate::proxy::inspector::Inspector;
+use crate::proxy::monitor::ProxyRequestLog;
+
+/// 单次最多导出的请求数
+pub const MAX_HAR_ENTRIES: usize = 5000;
+
+fn query_string(url: &str) -> Vec<Value> {
+    let Some((_, query)) = url.split_once('?') else { return Vec::new() };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            json!({ "name": name, "value": value })
+        })
+        .collect()
+}
+
+/// `alice@example.com` -> `a***@example.com`
+pub(crate) fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((user, domain)) => format!("{}***@{}", user.chars().next().unwrap_or('*'), domain),
+        None => "***".to_string(),
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    axum::http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("")
+}
+
+fn entry(log: &ProxyRequestLog, base_url: &str, redactor: &Inspector) -> Value {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), crate::utils::redact::redact(&log.url));
+    let started = chrono::DateTime::from_timestamp_millis(log.timestamp)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+    let mut request = json!({
+        "method": log.method,
+        "url": url,
+        "httpVersion": "HTTP/1.1",
+        "cookies": [],
+        "headers": [],
+        "queryString": query_string(&url),
+        "headersSize": -1,
+        "bodySize": -1,
+    });
+    if let Some(body) = &log.request_body {
+        let text = redactor.redact(body);
+        request["bodySize"] = json!(text.len());
+        request["postData"] = json!({ "mimeType": "application/json", "text": text });
+    }
+
+    let body = log.response_body.as_deref().or(log.error.as_deref()).unwrap_or("");
+    let mime_type = if body == "[Stream Data]" { "text/event-stream" } else { "application/json" };
+    let text = redactor.redact(body);
+
+    let mut entry = json!({
+        "startedDateTime": started,
+        "time": log.duration,
+        "request": request,
+        "response": {
+            "status": log.status,
+            "statusText": status_text(log.status),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": [],
+            "content": { "size": text.len(), "mimeType": mime_type, "text": text },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "cache": {},
+        // 只记录了拿到响应头的耗时
+        "timings": { "send": 0, "wait": log.duration, "receive": 0 },
+        "_model": log.model,
+        "_mappedModel": log.mapped_model,
+        "_inputTokens": log.input_tokens,
+        "_outputTokens": log.output_tokens,
+    });
+    if let Some(email) = &log.account_email {
+        entry["_account"] = json!(mask_email(email));
+    }
+    entry
+}
+
+/// 生成 HAR 文档；`base_url` 为反代监听地址 (日志中只保存了路径)
+pub fn build_har(logs: &[ProxyRequestLog], base_url: &str, redactor: &Inspector) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "Antigravity Tools", "version": env!("CARGO_PKG_VERSION") },
+            "entries": logs.iter().map(|log| entry(log, base_url, redactor)).collect::<Vec<_>>(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log() -> ProxyRequestLog {
+        ProxyRequestLog {
+            id: "id".to_string(),
+            timestamp: 1_717_200_000_000,
+            method: "POST".to_string(),
+            url: "/v1beta/models/gemini-2.5-pro:generateContent?key=AIzaSecret&alt=sse".to_string(),
+            status: 429,
+            duration: 321,
+            model: Some("gemini-2.5-pro".to_string()),
+            mapped_model: None,
+            account_email: Some("alice@example.com".to_string()),
+            project_id: None,
+            error: Some("quota exhausted".to_string()),
+            request_body: Some(r#"{"api_key": "sk-abcdefghijklmnopqrstuvwx", "contents": []}"#.to_string()),
+            response_body: None,
+            input_tokens: None,
+            output_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_har_strips_credentials() {
+        let redactor = Inspector::new();
+        redactor.set_config(Default::default());
+        let har = build_har(&[log()], "http://127.0.0.1:8045/", &redactor);
+        let entry = &har["log"]["entries"][0];
+
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entry["startedDateTime"], "2024-06-01T00:00:00.000Z");
+        assert_eq!(
+            entry["request"]["url"],
+            "http://127.0.0.1:8045/v1beta/models/gemini-2.5-pro:generateContent?key=[REDACTED]&alt=sse"
+        );
+        assert_eq!(entry["request"]["queryString"][1], json!({ "name": "alt", "value": "sse" }));
+        let body = entry["request"]["postData"]["text"].as_str().unwrap();
+        assert!(!body.contains("sk-abc"), "{}", body);
+        assert_eq!(entry["response"]["statusText"], "Too Many Requests");
+        assert_eq!(entry["response"]["content"]["text"], "quota exhausted");
+        assert_eq!(entry["_account"], "a***@example.com");
+    }
+}
@@ -0,0 +1,382 @@
+// 账号健康检查
+//
+// 对账号执行一次最小化的真实请求：
+// 1. 强制刷新 Token (验证 refresh_token 是否仍然有效)
+// 2. loadCodeAssist 获取 project_id 与订阅类型
+// 3. 发送一次极小的 generateContent 请求，测量延迟并分类错误
+//
+// 新添加的账号会在后台自动执行一次同样的检查 (见 `spawn_verification`)，
+// 按阶段报告结果，尽早发现导入后无法使用的凭据。
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Instant;
+
+use crate::models::Account;
+
+/// 健康检查默认使用的模型 (轻量且所有订阅类型可用)
+const HEALTH_CHECK_MODEL: &str = "gemini-2.5-flash";
+/// 批量检查的最大并发数
+const MAX_CONCURRENT: usize = 5;
+/// 新账号验证完成事件
+pub const VERIFIED_EVENT: &str = "account://verified";
+
+/// 健康检查错误分类
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthErrorKind {
+    /// refresh_token 已失效，需要重新授权
+    InvalidGrant,
+    /// 账号无权限 (403)
+    Forbidden,
+    /// 速率限制 (429)
+    RateLimited,
+    /// 配额耗尽
+    QuotaExhausted,
+    /// 上游服务器错误 (5xx)
+    ServerError,
+    /// 网络错误 (连接失败、超时)
+    Network,
+    /// 其他错误
+    Unknown,
+}
+
+/// 检查阶段
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStage {
+    TokenRefresh,
+    ProjectResolution,
+    Generation,
+}
+
+impl HealthStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStage::TokenRefresh => "token_refresh",
+            HealthStage::ProjectResolution => "project_resolution",
+            HealthStage::Generation => "generation",
+        }
+    }
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageResult {
+    pub stage: HealthStage,
+    pub ok: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// 单个账号的健康检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHealthReport {
+    pub account_id: String,
+    pub email: String,
+    pub healthy: bool,
+    /// 订阅类型: FREE/PRO/ULTRA
+    pub tier: Option<String>,
+    /// Token 刷新耗时 (毫秒)
+    pub refresh_latency_ms: Option<u64>,
+    /// generateContent 请求耗时 (毫秒)
+    pub request_latency_ms: Option<u64>,
+    /// 上游 HTTP 状态码
+    pub status: Option<u16>,
+    pub error_kind: Option<HealthErrorKind>,
+    pub error: Option<String>,
+    /// 已执行的各阶段结果 (按执行顺序，失败的阶段之后不再继续)
+    #[serde(default)]
+    pub stages: Vec<StageResult>,
+    /// 按界面语言生成的结果说明
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+impl AccountHealthReport {
+    /// 第一个失败的阶段
+    pub fn failed_stage(&self) -> Option<HealthStage> {
+        self.stages.iter().find(|s| !s.ok).map(|s| s.stage)
+    }
+
+    fn push_stage(&mut self, stage: HealthStage, latency_ms: u64, error: Option<String>) {
+        self.stages.push(StageResult { stage, ok: error.is_none(), latency_ms: Some(latency_ms), error });
+    }
+
+    /// 结果说明 (只描述结论，原始错误保留在 `error` 中)
+    fn describe(&self, lang: &str) -> String {
+        use crate::modules::i18n::tr_lang;
+        if self.healthy {
+            let latency = self.request_latency_ms.unwrap_or_default().to_string();
+            return tr_lang(lang, "backend.health.ok", &[("latency", &latency)]);
+        }
+        if self.failed_stage() == Some(HealthStage::ProjectResolution) {
+            return tr_lang(lang, "backend.health.no_project", &[]);
+        }
+        let kind = match self.error_kind.unwrap_or(HealthErrorKind::Unknown) {
+            HealthErrorKind::InvalidGrant => "invalid_grant",
+            HealthErrorKind::Forbidden => "forbidden",
+            HealthErrorKind::RateLimited => "rate_limited",
+            HealthErrorKind::QuotaExhausted => "quota_exhausted",
+            HealthErrorKind::ServerError => "server_error",
+            HealthErrorKind::Network => "network",
+            HealthErrorKind::Unknown => "unknown",
+        };
+        tr_lang(lang, &format!("backend.health.{}", kind), &[])
+    }
+}
+
+/// 根据状态码和错误文本分类错误
+pub fn classify_error(status: Option<u16>, body: &str) -> HealthErrorKind {
+    let lower = body.to_lowercase();
+    if lower.contains("invalid_grant") {
+        return HealthErrorKind::InvalidGrant;
+    }
+    match status {
+        Some(429) => {
+            if lower.contains("quota") || lower.contains("exhausted") {
+                HealthErrorKind::QuotaExhausted
+            } else {
+                HealthErrorKind::RateLimited
+            }
+        }
+        Some(401) | Some(403) => HealthErrorKind::Forbidden,
+        Some(s) if s >= 500 => HealthErrorKind::ServerError,
+        None if lower.contains("timed out") || lower.contains("timeout") || lower.contains("connect") || lower.contains("请求失败") => {
+            HealthErrorKind::Network
+        }
+        _ => HealthErrorKind::Unknown,
+    }
+}
+
+/// 检查单个账号
+pub async fn check_account(account: Account) -> AccountHealthReport {
+    let mut report = run_checks(account).await;
+    report.summary = Some(report.describe(&crate::modules::i18n::current_language()));
+    report
+}
+
+async fn run_checks(mut account: Account) -> AccountHealthReport {
+    let mut report = AccountHealthReport {
+        account_id: account.id.clone(),
+        email: account.email.clone(),
+        healthy: false,
+        tier: account.quota.as_ref().and_then(|q| q.subscription_tier.clone()),
+        refresh_latency_ms: None,
+        request_latency_ms: None,
+        status: None,
+        error_kind: None,
+        error: None,
+        stages: Vec::new(),
+        summary: None,
+    };
+
+    // 1. 强制刷新 Token
+    let refresh_start = Instant::now();
+    let token = match crate::modules::oauth::refresh_access_token(&account.token.refresh_token).await {
+        Ok(t) => t,
+        Err(e) => {
+            report.push_stage(HealthStage::TokenRefresh, refresh_start.elapsed().as_millis() as u64, Some(e.clone()));
+            report.error_kind = Some(classify_error(None, &e));
+            report.error = Some(e);
+            return report;
+        }
+    };
+    report.refresh_latency_ms = Some(refresh_start.elapsed().as_millis() as u64);
+    report.push_stage(HealthStage::TokenRefresh, report.refresh_latency_ms.unwrap_or(0), None);
+
+    account.token = crate::models::TokenData::new(
+        token.access_token,
+        account.token.refresh_token.clone(),
+        token.expires_in,
+        account.token.email.clone(),
+        account.token.project_id.clone(),
+        None,
+    );
+    if let Err(e) = crate::modules::account::save_account(&account) {
+        crate::modules::logger::log_warn(&format!("[Health] 保存刷新后的 Token 失败: {}", e));
+    }
+
+    // 2. 获取 project_id 与订阅类型
+    let project_start = Instant::now();
+    let (project_id, tier) = crate::modules::quota::fetch_project_id(&account.token.access_token, &account.email).await;
+    let project_latency = project_start.elapsed().as_millis() as u64;
+    if tier.is_some() {
+        report.tier = tier;
+    }
+    // 手动指定的 project_id 优先
+    let project_id = account.project_id_override.clone().or(project_id).or_else(|| account.token.project_id.clone());
+    let Some(project_id) = project_id else {
+        let error = crate::modules::i18n::tr("backend.health.no_project", &[]);
+        report.push_stage(HealthStage::ProjectResolution, project_latency, Some(error.clone()));
+        report.error_kind = Some(HealthErrorKind::Forbidden);
+        report.error = Some(error);
+        return report;
+    };
+    report.push_stage(HealthStage::ProjectResolution, project_latency, None);
+
+    // 3. 极小的 generateContent 请求
+    let proxy_config = crate::modules::config::load_app_config().map(|c| c.proxy).ok();
+    let client = crate::proxy::upstream::client::UpstreamClient::new(
+        proxy_config.as_ref().map(|p| p.upstream_proxy.clone()),
+    );
+    if let Some(proxy_config) = &proxy_config {
+        client.set_timeout_config(proxy_config.upstream_timeouts.clone());
+    }
+    let base_request = json!({
+        "model": HEALTH_CHECK_MODEL,
+        "contents": [{"role": "user", "parts": [{"text": "ping"}]}],
+        "generationConfig": {"maxOutputTokens": 1}
+    });
+    let body = crate::proxy::mappers::gemini::wrapper::wrap_request(&base_request, &project_id, HEALTH_CHECK_MODEL);
+
+    let request_start = Instant::now();
+    let result = client
+        .call_v1_internal("generateContent", &account.token.access_token, body, None)
+        .await;
+    let request_latency = request_start.elapsed().as_millis() as u64;
+    report.request_latency_ms = Some(request_latency);
+
+    match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            report.status = Some(status);
+            if response.status().is_success() {
+                report.healthy = true;
+            } else {
+                let text = response.text().await.unwrap_or_default();
+                report.error_kind = Some(classify_error(Some(status), &text));
+                report.error = Some(text);
+            }
+        }
+        Err(e) => {
+            report.error_kind = Some(HealthErrorKind::Network);
+            report.error = Some(e);
+        }
+    }
+    let generation_error = if report.healthy { None } else { report.error.clone() };
+    report.push_stage(HealthStage::Generation, request_latency, generation_error);
+
+    crate::modules::logger::log_info(&format!(
+        "[Health] {} healthy={} tier={:?} latency={:?}ms",
+        report.email, report.healthy, report.tier, report.request_latency_ms
+    ));
+    report
+}
+
+/// 检查指定账号
+pub async fn check_account_by_id(account_id: &str) -> Result<AccountHealthReport, String> {
+    let account = crate::modules::account::load_account(account_id)?;
+    Ok(check_account(account).await)
+}
+
+/// 并发检查所有未禁用的账号
+pub async fn check_all_accounts() -> Result<Vec<AccountHealthReport>, String> {
+    use futures::future::join_all;
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    let accounts = crate::modules::account::list_accounts()?;
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+
+    let tasks: Vec<_> = accounts
+        .into_iter()
+        .filter(|account| !account.disabled)
+        .map(|account| {
+            let permit = semaphore.clone();
+            async move {
+                let _guard = permit.acquire().await.unwrap();
+                check_account(account).await
+            }
+        })
+        .collect();
+
+    Ok(join_all(tasks).await)
+}
+
+/// 在后台验证新添加的账号，逐个发送 `account://verified` 事件并记录到账号事件
+pub fn spawn_verification(app: crate::utils::app::AppHandle, account_ids: Vec<String>) {
+    if account_ids.is_empty() {
+        return;
+    }
+    crate::utils::app::spawn(async move {
+        use futures::future::join_all;
+        use std::sync::Arc;
+        use tokio::sync::Semaphore;
+
+        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+        let tasks = account_ids.into_iter().map(|account_id| {
+            let permit = semaphore.clone();
+            let app = app.clone();
+            async move {
+                let _guard = permit.acquire().await.unwrap();
+                let report = match check_account_by_id(&account_id).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        crate::modules::logger::log_warn(&format!("[Verify] 加载账号 {} 失败: {}", account_id, e));
+                        return;
+                    }
+                };
+                record_verification(&report);
+                crate::utils::app::emit(&app, VERIFIED_EVENT, &report);
+            }
+        });
+        join_all(tasks).await;
+    });
+}
+
+fn record_verification(report: &AccountHealthReport) {
+    let Ok(data_dir) = crate::modules::account::get_data_dir() else { return };
+    match report.failed_stage() {
+        None => crate::modules::account_store::record_event(&data_dir, &report.account_id, "verified", None),
+        Some(stage) => {
+            let detail = format!("{}: {}", stage.as_str(), report.error.as_deref().unwrap_or_default());
+            crate::modules::logger::log_warn(&format!("[Verify] 新账号 {} 验证失败 ({})", report.email, detail));
+            crate::modules::account_store::record_event(&data_dir, &report.account_id, "verification_failed", Some(&detail));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(classify_error(None, "刷新失败: {\"error\": \"invalid_grant\"}"), HealthErrorKind::InvalidGrant);
+        assert_eq!(classify_error(Some(429), "RESOURCE_EXHAUSTED: quota exceeded"), HealthErrorKind::QuotaExhausted);
+        assert_eq!(classify_error(Some(429), "Too many requests"), HealthErrorKind::RateLimited);
+        assert_eq!(classify_error(Some(403), "PERMISSION_DENIED"), HealthErrorKind::Forbidden);
+        assert_eq!(classify_error(Some(503), "unavailable"), HealthErrorKind::ServerError);
+        assert_eq!(classify_error(None, "刷新请求失败: operation timed out"), HealthErrorKind::Network);
+        assert_eq!(classify_error(Some(400), "bad request"), HealthErrorKind::Unknown);
+    }
+
+    #[test]
+    fn test_failed_stage_is_first_unsuccessful_stage() {
+        let mut report = AccountHealthReport {
+            account_id: "a".to_string(),
+            email: "a@example.com".to_string(),
+            healthy: false,
+            tier: None,
+            refresh_latency_ms: None,
+            request_latency_ms: None,
+            status: None,
+            error_kind: None,
+            error: None,
+            stages: Vec::new(),
+            summary: None,
+        };
+        assert_eq!(report.failed_stage(), None);
+
+        report.push_stage(HealthStage::TokenRefresh, 10, None);
+        report.push_stage(HealthStage::ProjectResolution, 20, Some("无法获取 project_id".to_string()));
+        assert_eq!(report.failed_stage(), Some(HealthStage::ProjectResolution));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["stages"][1]["stage"], "project_resolution");
+        assert_eq!(json["stages"][1]["ok"], false);
+        assert_eq!(report.describe("en"), "Could not resolve the account's project_id");
+        assert_eq!(report.describe("zh"), "无法获取 project_id");
+    }
+}
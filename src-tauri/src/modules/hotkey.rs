@@ -0,0 +1,67 @@
+// 全局快捷键: 启动 / 停止反代服务
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::modules::{self, logger};
+
+/// 解析快捷键 (如 `CmdOrCtrl+Shift+P`)，空字符串视为未设置
+fn parse(shortcut: Option<&str>) -> Result<Option<Shortcut>, String> {
+    match shortcut.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<Shortcut>()
+            .map(Some)
+            .map_err(|e| format!("无效的快捷键 {}: {}", s, e)),
+        None => Ok(None),
+    }
+}
+
+/// 保存配置前校验快捷键格式
+pub fn validate(shortcut: Option<&str>) -> Result<(), String> {
+    parse(shortcut).map(|_| ())
+}
+
+/// 按配置 (重新) 注册切换反代服务的全局快捷键
+pub fn register_proxy_shortcut(app: &AppHandle, shortcut: Option<&str>) -> Result<(), String> {
+    let manager = app.global_shortcut();
+    manager
+        .unregister_all()
+        .map_err(|e| format!("注销全局快捷键失败: {}", e))?;
+
+    let Some(parsed) = parse(shortcut)? else { return Ok(()) };
+    manager
+        .on_shortcut(parsed, |app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = modules::tray::toggle_proxy(&app).await;
+                notify_proxy_state(&app, result);
+            });
+        })
+        .map_err(|e| format!("注册全局快捷键失败 (可能已被其他程序占用): {}", e))?;
+
+    logger::log_info(&format!("已注册反代服务切换快捷键: {}", shortcut.unwrap_or_default().trim()));
+    Ok(())
+}
+
+/// 弹出系统通知告知切换后的反代状态
+fn notify_proxy_state(app: &AppHandle, result: Result<Option<u16>, String>) {
+    let lang = modules::load_app_config().map(|c| c.language).unwrap_or_default();
+    let texts = modules::i18n::get_tray_texts(&lang);
+    let body = match result {
+        Ok(Some(port)) => format!("{} (:{})", texts.proxy_running, port),
+        Ok(None) => texts.proxy_stopped,
+        Err(e) => e,
+    };
+    let shown = app
+        .notification()
+        .builder()
+        .title(texts.proxy)
+        .body(body)
+        .show();
+    if let Err(e) = shown {
+        logger::log_warn(&format!("显示反代状态通知失败: {}", e));
+    }
+}
@@ -1,5 +1,8 @@
+use once_cell::sync::Lazy;
 use serde_json::Value;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::sync::RwLock;
 
 /// 托盘文本结构
 #[derive(Debug, Clone)]
@@ -13,22 +16,92 @@ pub struct TrayTexts {
     pub no_account: String,
     pub unknown_quota: String,
     pub forbidden: String,
+    pub proxy: String,
+    pub proxy_running: String,
+    pub proxy_stopped: String,
+    pub start_proxy: String,
+    pub stop_proxy: String,
+    pub pool: String,
+    pub recent_errors: String,
+    pub switch_account: String,
+    pub already_running: String,
 }
 
-/// 从 JSON 加载翻译
-fn load_translations(lang: &str) -> HashMap<String, String> {
-    let json_content = match lang {
-        "en" | "en-US" => include_str!("../../../src/locales/en.json"),
-        _ => include_str!("../../../src/locales/zh.json"),
-    };
-    
-    let v: Value = serde_json::from_str(json_content)
-        .unwrap_or_else(|_| serde_json::json!({}));
-    
+/// 后端文案目录 (与前端共用 src/locales 下的 JSON，目前提供英文与中文)
+static CATALOGS: Lazy<[(&str, Value); 2]> = Lazy::new(|| {
+    let parse = |content: &str| serde_json::from_str(content).unwrap_or_else(|_| serde_json::json!({}));
+    [
+        ("en", parse(include_str!("../../../src/locales/en.json"))),
+        ("zh", parse(include_str!("../../../src/locales/zh.json"))),
+    ]
+});
+
+/// 将界面语言映射到后端目录 (zh / zh-TW 使用中文，其余使用英文)
+fn catalog_lang(lang: &str) -> &'static str {
+    if lang.starts_with("zh") {
+        "zh"
+    } else {
+        "en"
+    }
+}
+
+fn catalog(lang: &str) -> &'static Value {
+    let lang = catalog_lang(lang);
+    &CATALOGS.iter().find(|(l, _)| *l == lang).unwrap_or(&CATALOGS[0]).1
+}
+
+/// 按点分路径 (如 `backend.errors.account_not_found`) 查找文案
+fn lookup(lang: &str, key: &str) -> Option<&'static str> {
+    key.split('.').try_fold(catalog(lang), |v, part| v.get(part))?.as_str()
+}
+
+/// 界面语言缓存，首次使用时从配置读取，保存配置时由 `set_language` 更新
+static LANGUAGE: RwLock<Option<String>> = RwLock::new(None);
+
+thread_local! {
+    /// 正在读取配置 (读取过程中的错误信息也会翻译，此时使用默认语言避免递归)
+    static LOADING_LANGUAGE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 当前界面语言 (读取配置失败时与默认配置一致使用中文)
+pub fn current_language() -> String {
+    if let Some(lang) = LANGUAGE.read().ok().and_then(|l| l.clone()) {
+        return lang;
+    }
+    if LOADING_LANGUAGE.with(|loading| loading.replace(true)) {
+        return "zh".to_string();
+    }
+    let lang = crate::modules::config::load_app_config().map(|c| c.language).unwrap_or_else(|_| "zh".to_string());
+    LOADING_LANGUAGE.with(|loading| loading.set(false));
+    set_language(&lang);
+    lang
+}
+
+/// 更新界面语言缓存 (保存配置后调用)
+pub fn set_language(lang: &str) {
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = Some(lang.to_string());
+    }
+}
+
+/// 按指定语言翻译，缺失时回退到英文，仍缺失则返回 key；`{name}` 占位符替换为 args 中的值
+pub fn tr_lang(lang: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let template = lookup(lang, key).or_else(|| lookup("en", key)).unwrap_or(key);
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// 按当前界面语言翻译
+pub fn tr(key: &str, args: &[(&str, &str)]) -> String {
+    tr_lang(&current_language(), key, args)
+}
+
+/// 从 JSON 加载指定分组的翻译
+fn load_translations(lang: &str, section: &str) -> HashMap<String, String> {
     let mut map = HashMap::new();
     
-    if let Some(tray) = v.get("tray").and_then(|t| t.as_object()) {
-        for (key, value) in tray {
+    if let Some(entries) = catalog(lang).get(section).and_then(|t| t.as_object()) {
+        for (key, value) in entries {
             if let Some(s) = value.as_str() {
                 map.insert(key.clone(), s.to_string());
             }
@@ -40,7 +113,7 @@ fn load_translations(lang: &str) -> HashMap<String, String> {
 
 /// 获取托盘文本（根据语言）
 pub fn get_tray_texts(lang: &str) -> TrayTexts {
-    let t = load_translations(lang);
+    let t = load_translations(lang, "tray");
     
     TrayTexts {
         current: t.get("current").cloned().unwrap_or_else(|| "Current".to_string()),
@@ -52,5 +125,42 @@ pub fn get_tray_texts(lang: &str) -> TrayTexts {
         no_account: t.get("no_account").cloned().unwrap_or_else(|| "No Account".to_string()),
         unknown_quota: t.get("unknown_quota").cloned().unwrap_or_else(|| "Unknown".to_string()),
         forbidden: t.get("forbidden").cloned().unwrap_or_else(|| "Account Forbidden".to_string()),
+        proxy: t.get("proxy").cloned().unwrap_or_else(|| "Proxy".to_string()),
+        proxy_running: t.get("proxy_running").cloned().unwrap_or_else(|| "Running".to_string()),
+        proxy_stopped: t.get("proxy_stopped").cloned().unwrap_or_else(|| "Stopped".to_string()),
+        start_proxy: t.get("start_proxy").cloned().unwrap_or_else(|| "Start Proxy".to_string()),
+        stop_proxy: t.get("stop_proxy").cloned().unwrap_or_else(|| "Stop Proxy".to_string()),
+        pool: t.get("pool").cloned().unwrap_or_else(|| "Account Pool".to_string()),
+        recent_errors: t.get("recent_errors").cloned().unwrap_or_else(|| "Recent Errors (5 min)".to_string()),
+        switch_account: t.get("switch_account").cloned().unwrap_or_else(|| "Switch Account".to_string()),
+        already_running: t.get("already_running").cloned().unwrap_or_else(|| "Antigravity Tools is already running in the tray".to_string()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tr_lang_falls_back_to_english_then_key() {
+        assert_eq!(tr_lang("en", "tray.proxy", &[]), "Proxy");
+        assert_eq!(tr_lang("zh-TW", "tray.proxy", &[]), tr_lang("zh", "tray.proxy", &[]));
+        assert_eq!(tr_lang("ja", "tray.proxy", &[]), "Proxy");
+        assert_eq!(tr_lang("en", "backend.missing.key", &[]), "backend.missing.key");
+        assert_eq!(
+            tr_lang("en", "system_notifications.reauth_body", &[("email", "a@example.com")]),
+            "a@example.com needs to sign in again"
+        );
+    }
+
+    #[test]
+    fn test_set_language_updates_cached_language() {
+        let previous = current_language();
+        // zh-TW 与 zh 共用中文目录，不影响并行测试中的文案
+        set_language("zh-TW");
+        assert_eq!(current_language(), "zh-TW");
+        assert_eq!(tr("tray.proxy", &[]), tr_lang("zh", "tray.proxy", &[]));
+        set_language(&previous);
     }
 }
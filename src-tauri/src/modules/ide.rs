@@ -0,0 +1,155 @@
+// Antigravity IDE 安装探测
+//
+// 汇总 IDE 是否安装、版本号、是否运行以及数据目录 (storage.json / state.vscdb) 的位置，
+// 供凭据导入、账号切换等 IDE 集成功能展示准确的可用选项。
+//
+// 修改 IDE 的凭据或配置文件前必须先关闭 IDE (运行中的 IDE 会在退出时覆盖写入的内容)，
+// 未经用户同意时返回以 `IDE_RUNNING` 开头的错误，由界面询问后带上 close_ide 重试。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::modules::{db, device, logger, process};
+
+/// IDE 正在运行且未允许关闭时返回的错误前缀
+pub const IDE_RUNNING_ERROR: &str = "IDE_RUNNING";
+/// 等待 IDE 正常退出的超时 (秒)
+const CLOSE_TIMEOUT_SECS: u64 = 20;
+
+/// IDE 安装与数据目录探测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct IdeInstallation {
+    /// 是否找到 IDE 可执行文件
+    pub installed: bool,
+    pub executable: Option<String>,
+    /// IDE 版本 (读取 resources/app 下的 package.json / product.json)
+    pub version: Option<String>,
+    /// IDE 是否正在运行
+    pub running: bool,
+    /// 便携模式 (数据保存在可执行文件旁的 data/user-data)
+    pub portable: bool,
+    /// 通过 --user-data-dir 指定的数据目录
+    pub user_data_dir: Option<String>,
+    /// storage.json (设备指纹) 路径
+    pub storage_path: Option<String>,
+    /// state.vscdb (登录凭据) 路径
+    pub state_db_path: Option<String>,
+    /// state.vscdb 是否存在
+    pub state_db_exists: bool,
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// 可执行文件对应的 `resources/app` 目录候选
+fn resource_dirs(executable: &Path) -> Vec<PathBuf> {
+    // Linux 下常见 /usr/bin/antigravity -> /usr/share/antigravity/bin/antigravity 的软链接
+    let resolved = executable.canonicalize().unwrap_or_else(|_| executable.to_path_buf());
+    let mut dirs = vec![
+        // macOS: Antigravity.app/Contents/Resources/app
+        resolved.join("Contents").join("Resources").join("app"),
+    ];
+    for ancestor in resolved.ancestors().skip(1).take(3) {
+        dirs.push(ancestor.join("resources").join("app"));
+    }
+    #[cfg(target_os = "linux")]
+    {
+        dirs.push(PathBuf::from("/usr/share/antigravity/resources/app"));
+        dirs.push(PathBuf::from("/opt/Antigravity/resources/app"));
+    }
+    dirs
+}
+
+/// 从 `resources/app` 目录读取版本号
+fn read_version(app_dir: &Path) -> Option<String> {
+    ["package.json", "product.json"].iter().find_map(|name| {
+        let content = std::fs::read_to_string(app_dir.join(name)).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+        json.get("version")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// IDE 版本号
+pub fn detect_version(executable: &Path) -> Option<String> {
+    resource_dirs(executable).into_iter().find_map(|dir| read_version(&dir))
+}
+
+/// 探测 IDE 安装、版本、运行状态与数据目录 (只读取本地文件)
+pub fn detect() -> IdeInstallation {
+    let executable = crate::modules::config::load_app_config()
+        .ok()
+        .and_then(|c| c.antigravity_executable)
+        .map(PathBuf::from)
+        .filter(|p| p.exists())
+        .or_else(process::get_antigravity_executable_path);
+    let user_data_dir = process::get_user_data_dir_from_process();
+    let portable = executable
+        .as_ref()
+        .and_then(|p| p.parent())
+        .is_some_and(|dir| dir.join("data").join("user-data").exists());
+    let storage_path = device::get_storage_path().ok();
+    let state_db_path = db::get_db_path().ok();
+
+    IdeInstallation {
+        installed: executable.is_some(),
+        version: executable.as_deref().and_then(detect_version),
+        executable: executable.as_deref().map(path_string),
+        running: process::is_antigravity_running(),
+        portable,
+        user_data_dir: user_data_dir.as_deref().map(path_string),
+        storage_path: storage_path.as_deref().map(path_string),
+        state_db_exists: state_db_path.as_ref().is_some_and(|p| p.exists()),
+        state_db_path: state_db_path.as_deref().map(path_string),
+    }
+}
+
+/// 写入 IDE 文件前确保 IDE 已关闭，返回 IDE 原本是否在运行
+///
+/// `allow_close` 为 false 且 IDE 正在运行时不做任何改动，返回 `IDE_RUNNING` 错误
+pub fn ensure_closed(allow_close: bool) -> Result<bool, String> {
+    if !process::is_antigravity_running() {
+        return Ok(false);
+    }
+    if !allow_close {
+        return Err(format!("{}: {}", IDE_RUNNING_ERROR, crate::modules::i18n::tr("backend.errors.ide_running", &[])));
+    }
+    logger::log_info("修改 IDE 数据前关闭正在运行的 Antigravity");
+    process::close_antigravity(CLOSE_TIMEOUT_SECS)?;
+    Ok(true)
+}
+
+/// 关闭 IDE 后执行写入，IDE 原本在运行时写入完成后重新启动
+pub fn with_ide_closed<T>(allow_close: bool, write: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let was_running = ensure_closed(allow_close)?;
+    let result = write();
+    if was_running {
+        if let Err(e) = process::start_antigravity() {
+            logger::log_warn(&format!("重新启动 Antigravity 失败: {}", e));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_version_prefers_package_json() {
+        let dir = std::env::temp_dir().join(format!("ag-ide-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(read_version(&dir), None);
+
+        std::fs::write(dir.join("product.json"), r#"{"nameShort":"Antigravity","version":"1.0.0"}"#).unwrap();
+        assert_eq!(read_version(&dir).as_deref(), Some("1.0.0"));
+
+        std::fs::write(dir.join("package.json"), r#"{"name":"antigravity","version":"1.11.2"}"#).unwrap();
+        assert_eq!(read_version(&dir).as_deref(), Some("1.11.2"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
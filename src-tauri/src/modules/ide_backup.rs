@@ -0,0 +1,232 @@
+// IDE 配置备份与恢复
+//
+// 将 Antigravity IDE 的设置与凭据文件 (settings.json、storage.json、state.vscdb 等) 复制到
+// 管理器数据目录下的 ide_backups/<id>/，支持手动、定时以及切换账号前的自动备份，
+// 恢复时先关闭 IDE 并备份当前状态，保证每一次切换实验都可以撤销。
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::modules::{config, device, logger};
+use crate::modules::i18n::tr;
+
+const BACKUP_DIR: &str = "ide_backups";
+const MANIFEST_FILE: &str = "manifest.json";
+/// 相对 IDE `User` 目录备份的文件
+const BACKUP_FILES: &[&str] = &[
+    "settings.json",
+    "keybindings.json",
+    "globalStorage/storage.json",
+    "globalStorage/state.vscdb",
+];
+
+/// 备份触发原因
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    Manual,
+    Scheduled,
+    /// 切换 IDE 账号前自动备份
+    BeforeSwitch,
+    /// 恢复其他备份前自动备份
+    BeforeRestore,
+}
+
+/// 单个备份的描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdeBackup {
+    pub id: String,
+    pub created_at: i64,
+    pub reason: BackupReason,
+    /// 备份中包含的文件 (相对 IDE `User` 目录)
+    pub files: Vec<String>,
+    /// 合计大小 (字节)
+    #[serde(default)]
+    pub size_bytes: u64,
+}
+
+fn backups_root() -> Result<PathBuf, String> {
+    let dir = crate::modules::account::get_root_data_dir()?.join(BACKUP_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建 IDE 备份目录失败: {}", e))?;
+    Ok(dir)
+}
+
+/// IDE 的 `User` 目录 (globalStorage 的上一级)
+fn ide_user_dir() -> Result<PathBuf, String> {
+    device::get_storage_dir()?
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "无法获取 IDE User 目录".to_string())
+}
+
+/// 备份 ID 只能是目录名，避免路径穿越
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn backup_dir(id: &str) -> Result<PathBuf, String> {
+    if !is_valid_id(id) {
+        return Err(tr("backend.errors.backup.invalid_id", &[("id", id)]));
+    }
+    let dir = backups_root()?.join(id);
+    if !dir.join(MANIFEST_FILE).exists() {
+        return Err(tr("backend.errors.backup.not_found", &[("name", id)]));
+    }
+    Ok(dir)
+}
+
+/// 将 `files` 从 `from` 复制到 `to` (保持相对路径)，返回实际复制的文件与总大小
+fn copy_files(from: &Path, to: &Path, files: &[&str]) -> Result<(Vec<String>, u64), String> {
+    let mut copied = Vec::new();
+    let mut size = 0;
+    for rel in files {
+        let source = from.join(rel);
+        if !source.is_file() {
+            continue;
+        }
+        let target = to.join(rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        size += fs::copy(&source, &target).map_err(|e| format!("复制 {} 失败: {}", rel, e))?;
+        copied.push(rel.to_string());
+    }
+    Ok((copied, size))
+}
+
+/// 立即备份 IDE 配置
+pub fn create_backup(reason: BackupReason) -> Result<IdeBackup, String> {
+    let user_dir = ide_user_dir()?;
+    let root = backups_root()?;
+
+    let base = Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut n = 1;
+    while root.join(&id).exists() {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    let dir = root.join(&id);
+
+    let (files, size_bytes) = copy_files(&user_dir, &dir, BACKUP_FILES).inspect_err(|_| {
+        let _ = fs::remove_dir_all(&dir);
+    })?;
+    if files.is_empty() {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(tr("backend.errors.backup.nothing_to_backup", &[]));
+    }
+
+    let backup = IdeBackup { id, created_at: chrono::Utc::now().timestamp(), reason, files, size_bytes };
+    let manifest = serde_json::to_string_pretty(&backup).map_err(|e| format!("序列化备份清单失败: {}", e))?;
+    fs::write(dir.join(MANIFEST_FILE), manifest).map_err(|e| format!("写入备份清单失败: {}", e))?;
+    logger::log_info(&format!("已备份 IDE 配置: {} ({} 个文件)", backup.id, backup.files.len()));
+
+    if let Ok(app_config) = config::load_app_config() {
+        let _ = prune(app_config.ide_backup.keep);
+    }
+    Ok(backup)
+}
+
+/// 列出所有备份 (新到旧)
+pub fn list_backups() -> Result<Vec<IdeBackup>, String> {
+    let mut backups: Vec<IdeBackup> = fs::read_dir(backups_root()?)
+        .map_err(|e| format!("读取 IDE 备份目录失败: {}", e))?
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join(MANIFEST_FILE)).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+    Ok(backups)
+}
+
+/// 删除指定备份
+pub fn delete_backup(id: &str) -> Result<(), String> {
+    let dir = backup_dir(id)?;
+    fs::remove_dir_all(&dir).map_err(|e| format!("删除备份失败: {}", e))
+}
+
+/// 只保留最近 `keep` 个备份 (0 表示不限制)，返回删除数量
+pub fn prune(keep: usize) -> Result<usize, String> {
+    if keep == 0 {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for backup in list_backups()?.into_iter().skip(keep) {
+        if delete_backup(&backup.id).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 恢复指定备份 (调用方负责先关闭 IDE)，恢复前自动备份当前配置
+pub fn restore_backup(id: &str) -> Result<IdeBackup, String> {
+    let dir = backup_dir(id)?;
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| format!("读取备份清单失败: {}", e))?;
+    let backup: IdeBackup = serde_json::from_str(&content).map_err(|e| format!("解析备份清单失败: {}", e))?;
+
+    if let Err(e) = create_backup(BackupReason::BeforeRestore) {
+        logger::log_warn(&format!("恢复前备份当前 IDE 配置失败: {}", e));
+    }
+
+    let user_dir = ide_user_dir()?;
+    let files: Vec<&str> = BACKUP_FILES.iter().copied().filter(|f| backup.files.iter().any(|b| b == f)).collect();
+    // 旧的 WAL / 共享内存文件会让 SQLite 在恢复后的数据库上重放过期的写入
+    if files.contains(&"globalStorage/state.vscdb") {
+        for suffix in ["-wal", "-shm", "-journal"] {
+            let _ = fs::remove_file(user_dir.join(format!("globalStorage/state.vscdb{}", suffix)));
+        }
+    }
+    copy_files(&dir, &user_dir, &files)?;
+    logger::log_info(&format!("已恢复 IDE 配置备份: {}", id));
+    Ok(backup)
+}
+
+/// 定时备份 (由定时任务每小时调用)：按配置的间隔检查最近一次定时备份，到期时创建备份，
+/// 未开启或未到期时返回 None
+pub fn run_scheduled_backup() -> Result<Option<IdeBackup>, String> {
+    let settings = config::load_app_config()?.ide_backup;
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let last = list_backups()?
+        .into_iter()
+        .find(|b| b.reason == BackupReason::Scheduled)
+        .map(|b| b.created_at)
+        .unwrap_or(0);
+    if chrono::Utc::now().timestamp() - last < settings.interval_hours.max(1) as i64 * 3600 {
+        return Ok(None);
+    }
+    create_backup(BackupReason::Scheduled).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_files_keeps_relative_paths() {
+        let root = std::env::temp_dir().join(format!("ag-ide-backup-{}", uuid::Uuid::new_v4()));
+        let from = root.join("User");
+        let to = root.join("backup");
+        fs::create_dir_all(from.join("globalStorage")).unwrap();
+        fs::write(from.join("settings.json"), "{}").unwrap();
+        fs::write(from.join("globalStorage/storage.json"), r#"{"a":1}"#).unwrap();
+
+        let (files, size) = copy_files(&from, &to, BACKUP_FILES).unwrap();
+        assert_eq!(files, vec!["settings.json", "globalStorage/storage.json"]);
+        assert_eq!(size, 9);
+        assert_eq!(fs::read_to_string(to.join("globalStorage/storage.json")).unwrap(), r#"{"a":1}"#);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_backup_id_validation() {
+        assert!(is_valid_id("20250101_120000-2"));
+        assert!(!is_valid_id("../etc"));
+        assert!(!is_valid_id(""));
+    }
+}
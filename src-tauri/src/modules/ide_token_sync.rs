@@ -0,0 +1,162 @@
+// IDE 凭据回写同步
+//
+// 轮询 IDE 的 state.vscdb，当 IDE 为管理器中同一账号刷新了 Token 时把新 Token 写回管理器，
+// 避免两边各持一份、其中一份已被上游作废的凭据:
+// - refresh_token 相同: IDE 持有更新的 access_token 时更新管理器副本
+// - refresh_token 不同: 用 IDE 的 access_token 查询邮箱，匹配到已有账号时整体替换凭据
+
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{Emitter, Manager};
+
+use crate::models::{Account, TokenData};
+use crate::modules::migration::{self, IdeOAuthToken};
+use crate::modules::{account, config, db, logger, oauth};
+
+/// 同步一个账号后通知前端 (payload 为账号 ID)
+pub const IDE_TOKEN_SYNCED_EVENT: &str = "account://ide-token-synced";
+/// 检查数据库是否变化的间隔 (秒)
+const POLL_SECS: u64 = 15;
+
+/// 上次看到的数据库 (及 WAL) 修改时间
+static LAST_MODIFIED: Lazy<Mutex<Option<(Option<SystemTime>, Option<SystemTime>)>>> = Lazy::new(|| Mutex::new(None));
+/// 已查询过邮箱但未匹配到账号的 refresh_token，避免重复请求
+static UNMATCHED_REFRESH_TOKEN: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// refresh_token 相同时，IDE 持有更新的 access_token 则返回需要写回的 Token
+fn newer_token(account: &Account, ide: &IdeOAuthToken, now: i64) -> Option<TokenData> {
+    if account.token.refresh_token != ide.refresh_token {
+        return None;
+    }
+    let access_token = ide.access_token.as_ref()?;
+    let expiry = ide.expiry?;
+    if *access_token == account.token.access_token || expiry <= account.token.expiry_timestamp || expiry <= now {
+        return None;
+    }
+    Some(TokenData::new(
+        access_token.clone(),
+        ide.refresh_token.clone(),
+        expiry - now,
+        account.token.email.clone(),
+        account.token.project_id.clone(),
+        account.token.session_id.clone(),
+    ))
+}
+
+/// 保存同步后的凭据，并让运行中的反代重新加载该账号
+async fn apply(app: &tauri::AppHandle, mut account: Account, token: TokenData, detail: &str) -> Result<(), String> {
+    account.token = token;
+    account::save_account(&account)?;
+    if let Ok(data_dir) = account::get_data_dir() {
+        crate::modules::account_store::record_event(&data_dir, &account.id, "ide_synced", Some(detail));
+    }
+    if let Some(state) = app.try_state::<crate::commands::proxy::ProxyServiceState>() {
+        if let Some(instance) = state.instance.read().await.as_ref() {
+            let _ = instance.token_manager.reload_account(&account.id).await;
+        }
+    }
+    logger::log_info(&format!("[IdeSync] 已从 IDE 同步 {} 的凭据 ({})", account.email, detail));
+    let _ = app.emit(IDE_TOKEN_SYNCED_EVENT, account.id);
+    Ok(())
+}
+
+/// 检查一次 IDE 凭据，返回被同步的账号 ID
+pub async fn sync_once(app: &tauri::AppHandle) -> Result<Option<String>, String> {
+    let db_path = db::get_db_path()?;
+    let ide = migration::extract_oauth_token_from_file(&db_path)?;
+    let now = chrono::Utc::now().timestamp();
+    let accounts = account::list_accounts()?;
+
+    if let Some(account) = accounts.iter().find(|a| a.token.refresh_token == ide.refresh_token) {
+        let Some(token) = newer_token(account, &ide, now) else { return Ok(None) };
+        let id = account.id.clone();
+        apply(app, account.clone(), token, "access_token").await?;
+        return Ok(Some(id));
+    }
+
+    // refresh_token 不属于任何账号: 可能是 IDE 重新登录了管理器中的某个账号
+    let Some(access_token) = ide.access_token.clone().filter(|_| ide.expiry.is_some_and(|e| e > now)) else {
+        return Ok(None);
+    };
+    if UNMATCHED_REFRESH_TOKEN.lock().unwrap().as_deref() == Some(ide.refresh_token.as_str()) {
+        return Ok(None);
+    }
+    let email = oauth::get_user_info(&access_token).await?.email;
+    let Some(account) = accounts.into_iter().find(|a| a.email.eq_ignore_ascii_case(&email)) else {
+        *UNMATCHED_REFRESH_TOKEN.lock().unwrap() = Some(ide.refresh_token);
+        return Ok(None);
+    };
+    let token = TokenData::new(
+        access_token,
+        ide.refresh_token.clone(),
+        ide.expiry.unwrap_or(now) - now,
+        account.token.email.clone(),
+        account.token.project_id.clone(),
+        account.token.session_id.clone(),
+    );
+    let id = account.id.clone();
+    apply(app, account, token, "refresh_token").await?;
+    Ok(Some(id))
+}
+
+/// 启动同步任务 (配置关闭时空转，数据库未变化时不读取)
+pub fn start_watcher(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(POLL_SECS));
+        loop {
+            interval.tick().await;
+            let Ok(app_config) = config::load_app_config() else { continue };
+            if !app_config.ide_token_sync {
+                continue;
+            }
+            let Ok(db_path) = db::get_db_path() else { continue };
+            let wal_path = db_path.with_file_name("state.vscdb-wal");
+            let current = (modified(&db_path), modified(&wal_path));
+            if current.0.is_none() {
+                continue;
+            }
+            {
+                let mut last = LAST_MODIFIED.lock().unwrap();
+                if last.as_ref() == Some(&current) {
+                    continue;
+                }
+                *last = Some(current);
+            }
+            if let Err(e) = sync_once(&app_handle).await {
+                tracing::debug!("[IdeSync] 检查 IDE 凭据失败: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_token_only_when_ide_is_ahead() {
+        let now = 1_700_000_000;
+        let mut token = TokenData::new("at-old".to_string(), "1//rt".to_string(), 3600, None, Some("p".to_string()), None);
+        token.expiry_timestamp = now + 600;
+        let account = Account::new("a".to_string(), "a@example.com".to_string(), token);
+        let ide = |at: &str, rt: &str, expiry: i64| IdeOAuthToken {
+            access_token: Some(at.to_string()),
+            refresh_token: rt.to_string(),
+            expiry: Some(expiry),
+        };
+
+        let synced = newer_token(&account, &ide("at-new", "1//rt", now + 3000), now).unwrap();
+        assert_eq!(synced.access_token, "at-new");
+        assert_eq!(synced.project_id.as_deref(), Some("p"));
+
+        assert!(newer_token(&account, &ide("at-old", "1//rt", now + 3000), now).is_none());
+        assert!(newer_token(&account, &ide("at-new", "1//rt", now + 300), now).is_none());
+        assert!(newer_token(&account, &ide("at-new", "1//other", now + 3000), now).is_none());
+    }
+}
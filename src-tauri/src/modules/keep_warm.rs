@@ -0,0 +1,161 @@
+// 空闲账号保活
+//
+// 可选地定期对长时间未使用的账号发起一次极小的上游调用 (强制刷新 Token + loadCodeAssist，
+// 不消耗模型配额)，保持会话活跃并尽早发现已被静默吊销的凭据。
+// 所有账号合计每小时的保活次数受 `max_pings_per_hour` 严格限制。
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::models::{Account, TokenData};
+use crate::modules::{account, config, logger, oauth, proxy_db, quota};
+
+/// 保活检查间隔 (秒)
+const KEEP_WARM_TICK_SECS: u64 = 300;
+const BUDGET_WINDOW_SECS: i64 = 3600;
+
+/// 最近一小时内的保活时间 (用于预算控制)
+static PING_LOG: Lazy<Mutex<VecDeque<i64>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+/// email -> 最近一次保活时间
+static LAST_PING: Lazy<DashMap<String, i64>> = Lazy::new(DashMap::new);
+
+/// 按空闲时长选出需要保活的账号 (最久未活动的优先)，最多 `budget` 个
+///
+/// `candidates` 为 (account_id, 最近一次活动时间)
+fn select_due(candidates: &[(String, i64)], now: i64, idle_secs: i64, budget: usize) -> Vec<String> {
+    let mut due: Vec<&(String, i64)> = candidates
+        .iter()
+        .filter(|(_, last_active)| now - last_active >= idle_secs)
+        .collect();
+    due.sort_by_key(|(_, last_active)| *last_active);
+    due.into_iter().take(budget).map(|(id, _)| id.clone()).collect()
+}
+
+/// 当前小时内剩余的保活预算
+fn remaining_budget(log: &mut VecDeque<i64>, now: i64, max_per_hour: u32) -> usize {
+    while log.front().is_some_and(|ts| now - ts >= BUDGET_WINDOW_SECS) {
+        log.pop_front();
+    }
+    (max_per_hour as usize).saturating_sub(log.len())
+}
+
+/// 对单个账号执行一次保活调用
+async fn ping_account(app: &crate::utils::app::AppHandle, mut account: Account) {
+    LAST_PING.insert(account.email.clone(), chrono::Utc::now().timestamp());
+
+    match oauth::refresh_access_token(&account.token.refresh_token).await {
+        Ok(response) => {
+            account.token = TokenData::new(
+                response.access_token,
+                response.refresh_token.unwrap_or_else(|| account.token.refresh_token.clone()),
+                response.expires_in,
+                account.token.email.clone(),
+                account.token.project_id.clone(),
+                account.token.session_id.clone(),
+            );
+            if let Err(e) = account::save_account(&account) {
+                logger::log_warn(&format!("[KeepWarm] 保存刷新后的 Token 失败 {}: {}", account.email, e));
+            } else if let Ok(data_dir) = account::get_data_dir() {
+                crate::modules::account_store::record_event(&data_dir, &account.id, "refreshed", Some("keep_warm"));
+            }
+            let _ = quota::fetch_project_id(&account.token.access_token, &account.email).await;
+            logger::log_info(&format!("[KeepWarm] 已保活 {}", account.email));
+        }
+        Err(e) if e.contains("invalid_grant") => {
+            logger::log_warn(&format!("[KeepWarm] {} 的凭据已失效: {}", account.email, e));
+            account::flag_needs_reauth(&mut account, &e);
+            account::emit_reauth_required(app, &account);
+        }
+        Err(e) => logger::log_warn(&format!("[KeepWarm] 保活失败 {}: {}", account.email, e)),
+    }
+}
+
+async fn run_once(app: &crate::utils::app::AppHandle, idle_minutes: u32, max_pings_per_hour: u32) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    let budget = {
+        let mut log = PING_LOG.lock().map_err(|e| e.to_string())?;
+        remaining_budget(&mut log, now, max_pings_per_hour)
+    };
+    if budget == 0 {
+        return Ok(());
+    }
+
+    let accounts: Vec<Account> = account::list_accounts()?.into_iter().filter(|a| !a.disabled).collect();
+    let last_requests = proxy_db::get_last_request_times().unwrap_or_default();
+    let candidates: Vec<(String, i64)> = accounts
+        .iter()
+        .map(|a| {
+            let last_active = [
+                Some(a.last_used),
+                last_requests.get(&a.email).copied(),
+                LAST_PING.get(&a.email).map(|t| *t),
+            ]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0);
+            (a.id.clone(), last_active)
+        })
+        .collect();
+
+    let due = select_due(&candidates, now, idle_minutes as i64 * 60, budget);
+    for account_id in due {
+        let Some(account) = accounts.iter().find(|a| a.id == account_id).cloned() else { continue };
+        if let Ok(mut log) = PING_LOG.lock() {
+            log.push_back(chrono::Utc::now().timestamp());
+        }
+        ping_account(app, account).await;
+    }
+    Ok(())
+}
+
+/// 启动保活任务 (配置未启用时空转)
+pub fn start_keep_warm(app_handle: crate::utils::app::AppHandle) {
+    crate::utils::app::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(KEEP_WARM_TICK_SECS));
+        loop {
+            interval.tick().await;
+            let Ok(app_config) = config::load_app_config() else { continue };
+            let keep_warm = app_config.keep_warm;
+            if !keep_warm.enabled || keep_warm.max_pings_per_hour == 0 {
+                continue;
+            }
+            if crate::modules::power::should_pause_background().await {
+                continue;
+            }
+            if let Err(e) = run_once(&app_handle, keep_warm.idle_minutes, keep_warm.max_pings_per_hour).await {
+                logger::log_warn(&format!("[KeepWarm] 保活检查失败: {}", e));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_due_prefers_most_idle_within_budget() {
+        let now = 100_000;
+        let candidates = vec![
+            ("busy".to_string(), now - 60),
+            ("idle".to_string(), now - 10_000),
+            ("idlest".to_string(), 0),
+            ("idle2".to_string(), now - 8_000),
+        ];
+        assert_eq!(select_due(&candidates, now, 7200, 2), vec!["idlest", "idle"]);
+        assert!(select_due(&candidates, now, 7200, 0).is_empty());
+    }
+
+    #[test]
+    fn test_remaining_budget_drops_pings_older_than_an_hour() {
+        let now = 10_000;
+        let mut log: VecDeque<i64> = vec![now - 4000, now - 3000, now - 10].into();
+        assert_eq!(remaining_budget(&mut log, now, 3), 1);
+        assert_eq!(log.len(), 2);
+        log.push_back(now);
+        assert_eq!(remaining_budget(&mut log, now, 3), 0);
+    }
+}
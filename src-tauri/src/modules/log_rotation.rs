@@ -0,0 +1,154 @@
+// 日志文件滚动
+//
+// 当前日志始终写入 `app.log`；跨天或超过单文件大小上限时重命名为
+// `app.<日期>.<序号>.log` 并新建 `app.log`。滚动时按保留天数与文件数上限清理旧文件，
+// 避免日志目录无限增长。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::models::config::LogRotationConfig;
+
+const ACTIVE_FILE: &str = "app.log";
+const ARCHIVE_PREFIX: &str = "app.";
+
+pub struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    retention: Duration,
+    file: File,
+    size: u64,
+    date: chrono::NaiveDate,
+}
+
+fn open_active(dir: &Path) -> io::Result<(File, u64)> {
+    // append 模式：日志被外部截断 (清理日志缓存) 后仍从文件末尾写入
+    let file = OpenOptions::new().create(true).append(true).open(dir.join(ACTIVE_FILE))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok((file, size))
+}
+
+impl RotatingFile {
+    pub fn new(dir: PathBuf, config: &LogRotationConfig) -> io::Result<Self> {
+        let (file, size) = open_active(&dir)?;
+        // 沿用已有 app.log 的日期，跨天启动时第一条日志写入前就会滚动
+        let date = file
+            .metadata()
+            .and_then(|m| m.modified())
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).date_naive())
+            .unwrap_or_else(|_| chrono::Local::now().date_naive());
+        let rotating = Self {
+            dir,
+            max_bytes: config.max_file_size_mb.max(1) * 1024 * 1024,
+            max_files: config.max_files.max(1),
+            retention: Duration::from_secs(config.retention_days * 24 * 60 * 60),
+            file,
+            size,
+            date,
+        };
+        rotating.prune();
+        Ok(rotating)
+    }
+
+    fn archive_path(&self) -> PathBuf {
+        let date = self.date.format("%Y-%m-%d");
+        (1..)
+            .map(|seq| self.dir.join(format!("{}{}.{}.log", ARCHIVE_PREFIX, date, seq)))
+            .find(|p| !p.exists())
+            .unwrap_or_else(|| self.dir.join(format!("{}{}.log", ARCHIVE_PREFIX, date)))
+    }
+
+    fn rotate(&mut self, today: chrono::NaiveDate) -> io::Result<()> {
+        self.file.flush()?;
+        if self.size > 0 {
+            fs::rename(self.dir.join(ACTIVE_FILE), self.archive_path())?;
+        }
+        let (file, size) = open_active(&self.dir)?;
+        self.file = file;
+        self.size = size;
+        self.date = today;
+        self.prune();
+        Ok(())
+    }
+
+    /// 删除超过保留期的归档文件，并只保留最新的 max_files - 1 个 (加上当前的 app.log)
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else { return };
+        let mut archives: Vec<(SystemTime, PathBuf)> = entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.starts_with(ARCHIVE_PREFIX) && name != ACTIVE_FILE
+            })
+            .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+            .collect();
+        archives.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+        let now = SystemTime::now();
+        for (idx, (modified, path)) in archives.iter().enumerate() {
+            let expired = self.retention > Duration::ZERO
+                && now.duration_since(*modified).map(|age| age > self.retention).unwrap_or(false);
+            if expired || idx + 1 >= self.max_files {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = chrono::Local::now().date_naive();
+        if today != self.date || (self.size > 0 && self.size + buf.len() as u64 > self.max_bytes) {
+            self.rotate(today)?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_caps_file_count() {
+        let dir = std::env::temp_dir().join(format!("ag-log-rotation-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("unrelated.txt"), "keep").unwrap();
+
+        let config = LogRotationConfig { max_file_size_mb: 1, max_files: 3, retention_days: 7 };
+        let mut writer = RotatingFile::new(dir.clone(), &config).unwrap();
+        let line = vec![b'x'; 600 * 1024];
+        for _ in 0..6 {
+            writer.write_all(&line).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let names = log_files(&dir);
+        // app.log + 2 个最新归档 + 无关文件
+        assert_eq!(names.len(), 4, "{:?}", names);
+        assert!(names.contains(&"app.log".to_string()));
+        assert!(names.contains(&"unrelated.txt".to_string()));
+        assert!(fs::metadata(dir.join("app.log")).unwrap().len() <= 1024 * 1024);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
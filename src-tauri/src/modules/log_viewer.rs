@@ -0,0 +1,185 @@
+// 应用日志查询 (应用内日志查看器)
+//
+// 读取日志目录中最新的几个日志文件 (当前 app.log 与最近的归档)，解析为结构化条目，
+// 支持按最低级别、模块 (target)、关键字过滤与分页，结果从新到旧排列。
+// 不以时间戳开头的行视为上一条日志的续行 (多行消息)。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 最多扫描的日志文件数 (含当前文件)
+const MAX_FILES_SCANNED: usize = 3;
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// 最低级别 (trace / debug / info / warn / error)
+    pub level: Option<String>,
+    /// 模块过滤 (target 包含该字符串，如 `proxy::mappers`)
+    pub module: Option<String>,
+    /// 关键字过滤 (不区分大小写，匹配消息与模块)
+    pub text: Option<String>,
+    pub offset: usize,
+    /// 每页条数 (默认 200，最大 1000)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    /// 过滤后的总条数
+    pub total: usize,
+    pub has_more: bool,
+}
+
+fn severity(level: &str) -> u8 {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => 0,
+        "DEBUG" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        "ERROR" => 4,
+        _ => 0,
+    }
+}
+
+/// 解析 `<RFC3339 时间>  LEVEL target: message` 格式的日志行
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    chrono::DateTime::parse_from_rfc3339(timestamp).ok()?;
+    let rest = rest.trim_start();
+    let (level, rest) = rest.split_once(' ')?;
+    if !matches!(level, "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR") {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (target, message) = rest.split_once(": ").unwrap_or(("", rest));
+    Some(LogEntry {
+        timestamp: timestamp.to_string(),
+        level: level.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+    })
+}
+
+fn parse_entries(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in content.lines() {
+        match parse_line(line) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+            }
+        }
+    }
+    entries
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(level) = self.level.as_deref().filter(|l| !l.is_empty()) {
+            if severity(&entry.level) < severity(level) {
+                return false;
+            }
+        }
+        if let Some(module) = self.module.as_deref().filter(|m| !m.is_empty()) {
+            if !entry.target.contains(module) {
+                return false;
+            }
+        }
+        if let Some(text) = self.text.as_deref().filter(|t| !t.is_empty()) {
+            let text = text.to_lowercase();
+            if !entry.message.to_lowercase().contains(&text) && !entry.target.to_lowercase().contains(&text) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 按修改时间从新到旧列出日志文件
+fn recent_log_files() -> Result<Vec<PathBuf>, String> {
+    let dir = crate::modules::logger::get_log_dir()?;
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(&dir)
+        .map_err(|e| format!("读取日志目录失败: {}", e))?
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("app."))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(files.into_iter().take(MAX_FILES_SCANNED).map(|(_, p)| p).collect())
+}
+
+fn paginate(entries: Vec<LogEntry>, query: &LogQuery) -> LogPage {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let filtered: Vec<LogEntry> = entries.into_iter().filter(|e| query.matches(e)).collect();
+    let total = filtered.len();
+    let page: Vec<LogEntry> = filtered.into_iter().skip(query.offset).take(limit).collect();
+    LogPage { has_more: query.offset + page.len() < total, entries: page, total }
+}
+
+/// 查询最近的应用日志 (新的在前)
+pub fn query_logs(query: &LogQuery) -> Result<LogPage, String> {
+    let mut entries = Vec::new();
+    for path in recent_log_files()? {
+        // 日志中可能混入非 UTF-8 字节，按有损方式解码
+        let bytes = fs::read(&path).map_err(|e| format!("读取日志文件失败: {}", e))?;
+        let mut file_entries = parse_entries(&String::from_utf8_lossy(&bytes));
+        file_entries.reverse();
+        entries.extend(file_entries);
+    }
+    Ok(paginate(entries, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+2024-06-01T10:00:00.000+08:00  INFO antigravity_tools_lib::proxy::server: Proxy started on 8045
+2024-06-01T10:00:01.000+08:00 DEBUG antigravity_tools_lib::proxy::mappers::claude: converted request
+{\"model\": \"gemini-2.5-pro\"}
+2024-06-01T10:00:02.000+08:00  WARN antigravity_tools_lib::modules::quota: quota fetch failed: 403
+2024-06-01T10:00:03.000+08:00 ERROR antigravity_tools_lib::proxy::handlers::claude: upstream error
+";
+
+    #[test]
+    fn test_parse_joins_continuation_lines() {
+        let entries = parse_entries(SAMPLE);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].level, "INFO");
+        assert_eq!(entries[0].target, "antigravity_tools_lib::proxy::server");
+        assert_eq!(entries[1].message, "converted request\n{\"model\": \"gemini-2.5-pro\"}");
+        assert_eq!(entries[2].message, "quota fetch failed: 403");
+    }
+
+    #[test]
+    fn test_filters_and_pagination() {
+        let entries = parse_entries(SAMPLE);
+        let warn = LogQuery { level: Some("warn".into()), ..Default::default() };
+        assert_eq!(paginate(entries.clone(), &warn).total, 2);
+
+        let proxy = LogQuery { module: Some("proxy::".into()), limit: Some(2), ..Default::default() };
+        let page = paginate(entries.clone(), &proxy);
+        assert_eq!((page.total, page.entries.len(), page.has_more), (3, 2, true));
+        let next = paginate(entries.clone(), &LogQuery { offset: 2, ..proxy });
+        assert_eq!((next.entries.len(), next.has_more), (1, false));
+
+        let text = LogQuery { text: Some("GEMINI".into()), ..Default::default() };
+        assert_eq!(paginate(entries, &text).entries[0].level, "DEBUG");
+    }
+}
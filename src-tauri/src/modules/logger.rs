@@ -1,8 +1,23 @@
 use tracing::{info, warn, error};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{filter::filter_fn, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Layer, Registry};
+use once_cell::sync::OnceCell;
 use std::fs;
 use std::path::PathBuf;
-use crate::modules::account::get_data_dir;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use crate::modules::account::get_root_data_dir;
+use crate::modules::i18n::tr;
+
+/// 运行时可替换的过滤层句柄 (见 set_log_filter)
+static FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+/// 启动时的过滤规则 (RUST_LOG 或 info)，重置时恢复
+static DEFAULT_FILTER: OnceCell<String> = OnceCell::new();
+static CURRENT_FILTER: Mutex<String> = Mutex::new(String::new());
+/// 终端日志改写到 stderr (JSON-RPC 模式下 stdout 只输出协议消息)
+static CONSOLE_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// 本 crate 的顶层模块，过滤规则中可省略 crate 名前缀 (如 `proxy::mappers=debug`)
+const CRATE_MODULES: [&str; 5] = ["proxy", "modules", "commands", "utils", "models"];
 
 // 自定义本地时区时间格式化器
 struct LocalTimer;
@@ -14,8 +29,40 @@ impl tracing_subscriber::fmt::time::FormatTime for LocalTimer {
     }
 }
 
+/// 写出前对每条日志做凭证脱敏 (见 utils::redact)
+struct RedactingMakeWriter<M>(M);
+
+impl<'a, M: fmt::MakeWriter<'a>> fmt::MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+        RedactingWriter(self.0.make_writer_for(meta))
+    }
+}
+
+struct RedactingWriter<W>(W);
+
+impl<W: std::io::Write> std::io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // fmt 层按整行写入，非 UTF-8 内容原样输出
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(crate::utils::redact::redact(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
 pub fn get_log_dir() -> Result<PathBuf, String> {
-    let data_dir = get_data_dir()?;
+    let data_dir = get_root_data_dir()?;
     let log_dir = data_dir.join("logs");
     
     if !log_dir.exists() {
@@ -26,6 +73,11 @@ pub fn get_log_dir() -> Result<PathBuf, String> {
 }
 
 /// 初始化日志系统
+/// 在 init_logger 之前调用，终端日志输出到 stderr
+pub fn console_to_stderr() {
+    CONSOLE_STDERR.store(true, Ordering::Relaxed);
+}
+
 pub fn init_logger() {
     // 捕获 log 宏日志
     let _ = tracing_log::LogTracer::init();
@@ -38,112 +90,113 @@ pub fn init_logger() {
         }
     };
     
-    // 1. 设置文件 Appender (使用 tracing-appender 实现滚动记录)
-    // 这里使用每天滚动的策略
-    let file_appender = tracing_appender::rolling::daily(log_dir, "app.log");
+    // 1. 设置文件 Appender (按天 / 按大小滚动，并按保留策略清理旧文件)
+    let app_config = crate::modules::config::load_app_config().ok();
+    let rotation = app_config.as_ref().map(|c| c.log_rotation.clone()).unwrap_or_default();
+    if let Some(config) = &app_config {
+        crate::modules::otel::set_config(config.otel.clone());
+        crate::modules::statsd::set_config(config.statsd.clone());
+    }
+    let file_appender = match crate::modules::log_rotation::RotatingFile::new(log_dir, &rotation) {
+        Ok(appender) => appender,
+        Err(e) => {
+            eprintln!("无法打开日志文件: {}", e);
+            return;
+        }
+    };
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
     
     // 2. 终端输出层（使用本地时区）
+    let console_writer = if CONSOLE_STDERR.load(Ordering::Relaxed) {
+        fmt::writer::BoxMakeWriter::new(std::io::stderr)
+    } else {
+        fmt::writer::BoxMakeWriter::new(std::io::stdout)
+    };
     let console_layer = fmt::Layer::new()
+        .with_writer(RedactingMakeWriter(console_writer))
         .with_target(false)
         .with_thread_ids(false)
         .with_level(true)
-        .with_timer(LocalTimer);
+        .with_timer(LocalTimer)
+        // 链路 span 只用于 OTLP 导出，不出现在日志行中
+        .with_filter(filter_fn(|meta| meta.is_event()));
         
     // 3. 文件输出层 (关闭 ANSI 格式化，使用本地时区)
     let file_layer = fmt::Layer::new()
-        .with_writer(non_blocking)
+        .with_writer(RedactingMakeWriter(non_blocking))
         .with_ansi(false)
         .with_target(true)
         .with_level(true)
-        .with_timer(LocalTimer);
+        .with_timer(LocalTimer)
+        .with_filter(filter_fn(|meta| meta.is_event()));
 
-    // 4. 设置过滤层 (默认使用 INFO 级别以减少日志体积)
-    let filter_layer = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info"));
+    // 4. 设置过滤层 (默认使用 INFO 级别以减少日志体积，可通过 set_log_filter 运行时调整)
+    let default_filter = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|f| EnvFilter::try_new(f).is_ok())
+        .unwrap_or_else(|| "info".to_string());
+    let (filter_layer, filter_handle) = reload::Layer::new(EnvFilter::new(&default_filter));
+    let _ = FILTER_HANDLE.set(filter_handle);
+    *CURRENT_FILTER.lock().unwrap() = default_filter.clone();
+    let _ = DEFAULT_FILTER.set(default_filter);
 
     // 5. 初始化全局订阅器 (使用 try_init 避免重复初始化崩溃)
     let _ = tracing_subscriber::registry()
         .with(filter_layer)
         .with(console_layer)
         .with(file_layer)
+        .with(crate::modules::otel::OtlpLayer)
         .try_init();
 
     // 泄漏 _guard 以确保其生命周期持续到程序退出
     // 这是使用 tracing_appender::non_blocking 时的推荐做法（如果不需要手动刷盘）
     std::mem::forget(_guard);
     
-    info!("日志系统已完成初始化 (终端控制台 + 文件持久化)");
-    
-    // 自动清理 7 天前的旧日志
-    if let Err(e) = cleanup_old_logs(7) {
-        warn!("清理旧日志失败: {}", e);
-    }
+    info!(
+        "日志系统已完成初始化 (终端控制台 + 文件持久化，单文件 {} MB / 最多 {} 个 / 保留 {} 天)",
+        rotation.max_file_size_mb, rotation.max_files, rotation.retention_days
+    );
 }
 
-/// 清理指定天数之前的旧日志文件
-pub fn cleanup_old_logs(days_to_keep: u64) -> Result<(), String> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let log_dir = get_log_dir()?;
-    if !log_dir.exists() {
-        return Ok(());
-    }
-    
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| format!("获取系统时间失败: {}", e))?
-        .as_secs();
-    
-    let cutoff_time = now.saturating_sub(days_to_keep * 24 * 60 * 60);
-    let mut deleted_count = 0;
-    let mut total_size_freed = 0u64;
-    
-    let entries = fs::read_dir(&log_dir)
-        .map_err(|e| format!("读取日志目录失败: {}", e))?;
-    
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            
-            // 只处理文件，跳过目录
-            if !path.is_file() {
-                continue;
-            }
-            
-            // 获取文件修改时间
-            if let Ok(metadata) = fs::metadata(&path) {
-                if let Ok(modified) = metadata.modified() {
-                    let modified_secs = modified
-                        .duration_since(UNIX_EPOCH)
-                        .map(|d| d.as_secs())
-                        .unwrap_or(0);
-                    
-                    // 如果文件早于截止时间，删除它
-                    if modified_secs < cutoff_time {
-                        let file_size = metadata.len();
-                        if let Err(e) = fs::remove_file(&path) {
-                            warn!("删除旧日志文件失败 {:?}: {}", path, e);
-                        } else {
-                            deleted_count += 1;
-                            total_size_freed += file_size;
-                            info!("已删除旧日志文件: {:?}", path.file_name());
-                        }
-                    }
-                }
+/// 补全本 crate 模块的 target 前缀：`proxy::mappers=debug` -> `antigravity_tools_lib::proxy::mappers=debug`
+fn expand_filter(filter: &str) -> String {
+    let crate_name = module_path!().split("::").next().unwrap_or_default();
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|directive| {
+            let target = directive.split(['=', '[']).next().unwrap_or_default();
+            let top = target.split("::").next().unwrap_or_default();
+            if directive.contains('=') && CRATE_MODULES.contains(&top) {
+                format!("{}::{}", crate_name, directive)
+            } else {
+                directive.to_string()
             }
-        }
-    }
-    
-    if deleted_count > 0 {
-        let size_mb = total_size_freed as f64 / 1024.0 / 1024.0;
-        info!(
-            "日志清理完成: 删除了 {} 个文件，释放 {:.2} MB 空间",
-            deleted_count, size_mb
-        );
-    }
-    
-    Ok(())
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 当前生效的日志过滤规则
+pub fn get_log_filter() -> String {
+    CURRENT_FILTER.lock().unwrap().clone()
+}
+
+/// 运行时替换日志过滤规则 (EnvFilter 语法，如 `info,proxy::mappers=debug`)，空字符串恢复默认
+///
+/// 仅对本次运行生效，重启后恢复为 RUST_LOG / info
+pub fn set_log_filter(filter: &str) -> Result<String, String> {
+    let filter = match filter.trim() {
+        "" => DEFAULT_FILTER.get().cloned().unwrap_or_else(|| "info".to_string()),
+        f => expand_filter(f),
+    };
+    let env_filter = EnvFilter::try_new(&filter).map_err(|e| format!("无效的日志过滤规则: {}", e))?;
+    let handle = FILTER_HANDLE.get().ok_or_else(|| tr("backend.errors.logger_not_initialized", &[]))?;
+    handle.reload(env_filter).map_err(|e| format!("更新日志过滤规则失败: {}", e))?;
+    *CURRENT_FILTER.lock().unwrap() = filter.clone();
+    info!("日志过滤规则已更新: {}", filter);
+    Ok(filter)
 }
 
 /// 清理日志缓存 (采用截断模式以保持文件句柄有效)
@@ -152,16 +205,14 @@ pub fn clear_logs() -> Result<(), String> {
     if log_dir.exists() {
         // 遍历目录下的所有文件并截断，而不是删除目录
         let entries = fs::read_dir(&log_dir).map_err(|e| format!("读取日志目录失败: {}", e))?;
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    // 使用截断模式打开文件，将大小设为 0
-                    let _ = fs::OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .open(path);
-                }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                // 使用截断模式打开文件，将大小设为 0
+                let _ = fs::OpenOptions::new()
+                    .write(true)
+                    .truncate(true)
+                    .open(path);
             }
         }
     }
@@ -182,3 +233,19 @@ pub fn log_warn(message: &str) {
 pub fn log_error(message: &str) {
     error!("{}", message);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_filter_prefixes_crate_modules() {
+        assert_eq!(
+            expand_filter("info, proxy::mappers=debug,hyper=warn"),
+            "info,antigravity_tools_lib::proxy::mappers=debug,hyper=warn"
+        );
+        assert_eq!(expand_filter("modules[quota]=trace"), "antigravity_tools_lib::modules[quota]=trace");
+        assert_eq!(expand_filter("antigravity_tools_lib::proxy=debug"), "antigravity_tools_lib::proxy=debug");
+        assert!(EnvFilter::try_new(expand_filter("warn,proxy::upstream=debug")).is_ok());
+    }
+}
@@ -5,6 +5,7 @@ use base64::{Engine as _, engine::general_purpose};
 use crate::models::{TokenData, Account};
 use crate::modules::{account, db};
 use crate::utils::protobuf;
+use crate::modules::i18n::tr;
 
 /// 扫描并导入 V1 数据
 pub async fn import_from_v1() -> Result<Vec<Account>, String> {
@@ -222,14 +223,101 @@ pub async fn import_from_custom_db_path(path_str: String) -> Result<Account, Str
     account::upsert_account(email.clone(), user_info.name, token_data)
 }
 
+/// 本机 Antigravity IDE 登录状态探测结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IdeCredentialStatus {
+    /// 探测到的 state.vscdb 路径
+    pub db_path: Option<String>,
+    /// 数据库文件是否存在
+    pub found: bool,
+    /// 数据库中是否包含可导入的登录凭据
+    pub logged_in: bool,
+    /// 若该凭据已导入，对应的账号 ID 与邮箱
+    pub imported_account_id: Option<String>,
+    pub imported_email: Option<String>,
+}
+
+/// 探测本机 IDE 中已登录的账号 (仅读取本地数据库，不发起网络请求)
+pub fn detect_ide_credentials() -> Result<IdeCredentialStatus, String> {
+    let db_path = match db::get_db_path() {
+        Ok(path) => path,
+        Err(_) => {
+            return Ok(IdeCredentialStatus {
+                db_path: None,
+                found: false,
+                logged_in: false,
+                imported_account_id: None,
+                imported_email: None,
+            })
+        }
+    };
+
+    let found = db_path.exists();
+    let refresh_token = if found { extract_refresh_token_from_file(&db_path).ok() } else { None };
+    let imported = match refresh_token.as_deref() {
+        Some(token) => find_account_by_refresh_token(&account::list_accounts()?, token).cloned(),
+        None => None,
+    };
+
+    Ok(IdeCredentialStatus {
+        db_path: Some(db_path.to_string_lossy().to_string()),
+        found,
+        logged_in: refresh_token.is_some(),
+        imported_account_id: imported.as_ref().map(|a| a.id.clone()),
+        imported_email: imported.map(|a| a.email),
+    })
+}
+
+fn find_account_by_refresh_token<'a>(accounts: &'a [Account], refresh_token: &str) -> Option<&'a Account> {
+    accounts.iter().find(|a| a.token.refresh_token == refresh_token)
+}
+
 /// 从默认 IDE 数据库导入当前登录账号
 pub async fn import_from_db() -> Result<Account, String> {
     let db_path = db::get_db_path()?;
     import_from_custom_db_path(db_path.to_string_lossy().to_string()).await
 }
 
-/// 从数据库获取当前 Refresh Token (通用逻辑)
-pub fn extract_refresh_token_from_file(db_path: &PathBuf) -> Result<String, String> {
+/// IDE 数据库中保存的 OAuth 凭据 (oauthTokenInfo)
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdeOAuthToken {
+    pub access_token: Option<String>,
+    pub refresh_token: String,
+    /// access_token 过期时间 (unix 秒)
+    pub expiry: Option<i64>,
+}
+
+/// 解析 agentManagerInitState 中的 oauthTokenInfo
+fn parse_oauth_token(blob: &[u8]) -> Result<IdeOAuthToken, String> {
+    // 1. 查找 oauthTokenInfo (Field 6)
+    let oauth_data = protobuf::find_field(blob, 6)
+        .map_err(|e| format!("解析 Protobuf 失败: {}", e))?
+        .ok_or_else(|| tr("backend.errors.migration.no_oauth_data", &[]))?;
+
+    // 2. 提取 refresh_token (Field 3)
+    let refresh_bytes = protobuf::find_field(&oauth_data, 3)
+        .map_err(|e| format!("解析 OAuth 数据失败: {}", e))?
+        .ok_or_else(|| tr("backend.errors.migration.no_refresh_token", &[]))?;
+    let refresh_token = String::from_utf8(refresh_bytes)
+        .map_err(|_| "Refresh Token 非 UTF-8 编码".to_string())?;
+
+    // 3. access_token (Field 1) 与过期时间 (Field 4: Timestamp.seconds)
+    let access_token = protobuf::find_field(&oauth_data, 1)
+        .ok()
+        .flatten()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|t| !t.is_empty());
+    let expiry = protobuf::find_field(&oauth_data, 4)
+        .ok()
+        .flatten()
+        .and_then(|ts| protobuf::find_varint_field(&ts, 1).ok().flatten())
+        .map(|secs| secs as i64);
+
+    Ok(IdeOAuthToken { access_token, refresh_token, expiry })
+}
+
+/// 从数据库读取 IDE 当前登录的 OAuth 凭据
+pub fn extract_oauth_token_from_file(db_path: &PathBuf) -> Result<IdeOAuthToken, String> {
     if !db_path.exists() {
         return Err(format!("找不到数据库文件: {:?}", db_path));
     }
@@ -251,19 +339,13 @@ pub fn extract_refresh_token_from_file(db_path: &PathBuf) -> Result<String, Stri
     let blob = general_purpose::STANDARD
         .decode(&current_data)
         .map_err(|e| format!("Base64 解码失败: {}", e))?;
-        
-    // 1. 查找 oauthTokenInfo (Field 6)
-    let oauth_data = protobuf::find_field(&blob, 6)
-        .map_err(|e| format!("解析 Protobuf 失败: {}", e))?
-        .ok_or("未找到 OAuth 数据 (Field 6)")?;
-        
-    // 2. 提取 refresh_token (Field 3)
-    let refresh_bytes = protobuf::find_field(&oauth_data, 3)
-        .map_err(|e| format!("解析 OAuth 数据失败: {}", e))?
-        .ok_or("数据中未包含 Refresh Token (Field 3)")?;
-        
-    String::from_utf8(refresh_bytes)
-        .map_err(|_| "Refresh Token 非 UTF-8 编码".to_string())
+
+    parse_oauth_token(&blob)
+}
+
+/// 从数据库获取当前 Refresh Token (通用逻辑)
+pub fn extract_refresh_token_from_file(db_path: &PathBuf) -> Result<String, String> {
+    extract_oauth_token_from_file(db_path).map(|token| token.refresh_token)
 }
 
 /// 从默认数据库获取当前 Refresh Token (兼容旧调用)
@@ -271,3 +353,34 @@ pub fn get_refresh_token_from_db() -> Result<String, String> {
     let db_path = db::get_db_path()?;
     extract_refresh_token_from_file(&db_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_account_by_refresh_token() {
+        let make = |id: &str, rt: &str| {
+            let token = TokenData::new("at".to_string(), rt.to_string(), 3600, None, None, None);
+            Account::new(id.to_string(), format!("{}@example.com", id), token)
+        };
+        let accounts = vec![make("a", "1//rt-a"), make("b", "1//rt-b")];
+
+        assert_eq!(find_account_by_refresh_token(&accounts, "1//rt-b").map(|a| a.id.as_str()), Some("b"));
+        assert!(find_account_by_refresh_token(&accounts, "1//rt-c").is_none());
+    }
+
+    #[test]
+    fn test_parse_oauth_token_roundtrip() {
+        let blob = [vec![0x0a, 0x01, b'x'], protobuf::create_oauth_field("ya29.at", "1//rt", 1_700_000_000)].concat();
+        assert_eq!(
+            parse_oauth_token(&blob).unwrap(),
+            IdeOAuthToken {
+                access_token: Some("ya29.at".to_string()),
+                refresh_token: "1//rt".to_string(),
+                expiry: Some(1_700_000_000),
+            }
+        );
+        assert!(parse_oauth_token(&[0x0a, 0x01, b'x']).is_err());
+    }
+}
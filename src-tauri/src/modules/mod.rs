@@ -1,18 +1,62 @@
 pub mod account;
+pub mod account_store;
+pub mod profile;
 pub mod quota;
+pub mod project_quota;
 pub mod config;
 pub mod logger;
+pub mod log_rotation;
+pub mod log_viewer;
+pub mod otel;
+pub mod statsd;
 pub mod db;
 pub mod process;
+pub mod ide;
+pub mod ide_backup;
+#[cfg(feature = "gui")]
+pub mod ide_token_sync;
 pub mod oauth;
 pub mod oauth_server;
 pub mod migration;
+#[cfg(feature = "gui")]
 pub mod tray;
+#[cfg(feature = "gui")]
+pub mod hotkey;
+pub mod deep_link;
 pub mod i18n;
+pub mod notifications;
+pub mod notification_channels;
+pub mod power;
+pub mod webhooks;
+pub mod claude_code;
 pub mod proxy_db;
+pub mod har;
+pub mod diagnostics;
+pub mod crash;
+pub mod token_stats;
 pub mod device;
 pub mod update_checker;
+pub mod update_installer;
 pub mod scheduler;
+pub mod task_scheduler;
+pub mod health;
+pub mod batch_import;
+pub mod account_interop;
+pub mod tunnel;
+pub mod backup;
+pub mod account_share;
+pub mod token_vault;
+pub mod token_status;
+pub mod token_refresh;
+pub mod keep_warm;
+pub mod uptime;
+pub mod config_preset;
+pub mod config_bundle;
+pub mod config_schema;
+pub mod config_format;
+pub mod config_sync;
+pub mod config_history;
+pub mod audit_log;
 
 use crate::models;
 
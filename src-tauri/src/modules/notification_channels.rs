@@ -0,0 +1,151 @@
+// 外部通知渠道
+//
+// 系统通知之外，把同样的运行事件 (账号池耗尽、需要重新授权、新版本等) 发送到 Telegram、
+// Discord 或邮件，适合在服务器上无人值守运行 (无界面模式没有系统通知)。
+// 是否发送由通知开关与冷却期决定 (见 notifications)，发送在后台进行，失败只记日志。
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::models::{NotificationChannel, NotificationTarget, SmtpSecurity};
+use crate::modules::logger;
+use crate::modules::i18n::tr;
+
+/// 单次发送超时 (秒)
+const CHANNEL_TIMEOUT_SECS: u64 = 15;
+
+/// 渠道名称 (日志用，不含凭据)
+fn channel_name(target: &NotificationTarget) -> &'static str {
+    match target {
+        NotificationTarget::Telegram { .. } => "telegram",
+        NotificationTarget::Discord { .. } => "discord",
+        NotificationTarget::Email { .. } => "email",
+    }
+}
+
+/// Telegram / Discord 的消息正文
+fn message_text(target: &NotificationTarget, title: &str, body: &str) -> String {
+    match target {
+        NotificationTarget::Discord { .. } => format!("**{}**\n{}", title, body),
+        _ => format!("{}\n{}", title, body),
+    }
+}
+
+async fn post_json(client: &reqwest::Client, url: &str, payload: serde_json::Value) -> Result<(), String> {
+    let resp = client.post(url).json(&payload).send().await.map_err(|e| format!("请求失败: {}", e.without_url()))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(tr("backend.errors.notify.http_error", &[("status", &status.to_string()), ("body", &text.chars().take(200).collect::<String>())]));
+    }
+    Ok(())
+}
+
+async fn send_email(target: &NotificationTarget, title: &str, body: &str) -> Result<(), String> {
+    let NotificationTarget::Email { smtp_host, smtp_port, security, username, password, from, to } = target else {
+        return Ok(());
+    };
+    let mut builder = Message::builder()
+        .from(from.trim().parse().map_err(|e| format!("无效的发件人 {}: {}", from, e))?)
+        .subject(title)
+        .header(ContentType::TEXT_PLAIN);
+    let recipients: Vec<&str> = to.iter().map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+    if recipients.is_empty() {
+        return Err(tr("backend.errors.notify.no_recipient", &[]));
+    }
+    for recipient in recipients {
+        builder = builder.to(recipient.parse().map_err(|e| format!("无效的收件人 {}: {}", recipient, e))?);
+    }
+    let message = builder.body(body.to_string()).map_err(|e| format!("构建邮件失败: {}", e))?;
+
+    let host = smtp_host.trim();
+    let transport = match security {
+        SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+    }
+    .map_err(|e| format!("无效的 SMTP 服务器 {}: {}", host, e))?;
+    let mut transport = transport.port(*smtp_port).timeout(Some(Duration::from_secs(CHANNEL_TIMEOUT_SECS)));
+    if !username.is_empty() {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport.build().send(message).await.map_err(|e| format!("发送邮件失败: {}", e))?;
+    Ok(())
+}
+
+/// 向单个渠道发送一条通知
+pub async fn send(channel: &NotificationChannel, title: &str, body: &str) -> Result<(), String> {
+    let target = &channel.target;
+    let client = crate::utils::http::create_client(CHANNEL_TIMEOUT_SECS);
+    match target {
+        NotificationTarget::Telegram { bot_token, chat_id } => {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token.trim());
+            let payload = json!({ "chat_id": chat_id.trim(), "text": message_text(target, title, body), "disable_web_page_preview": true });
+            post_json(&client, &url, payload).await
+        }
+        NotificationTarget::Discord { webhook_url } => {
+            post_json(&client, webhook_url.trim(), json!({ "content": message_text(target, title, body) })).await
+        }
+        NotificationTarget::Email { .. } => send_email(target, title, body).await,
+    }
+}
+
+/// 在后台发送到所有启用的渠道
+pub fn dispatch(channels: &[NotificationChannel], title: String, body: String) {
+    let targets: Vec<NotificationChannel> = channels.iter().filter(|c| c.enabled).cloned().collect();
+    if targets.is_empty() {
+        return;
+    }
+    crate::utils::app::spawn(async move {
+        for channel in targets {
+            if let Err(e) = send(&channel, &title, &body).await {
+                logger::log_warn(&format!("[Notify] 发送到 {} 失败: {}", channel_name(&channel.target), e));
+            }
+        }
+    });
+}
+
+/// 发送测试消息 (设置界面校验渠道配置)
+pub async fn send_test(channel: &NotificationChannel) -> Result<(), String> {
+    send(channel, "Antigravity Tools", "Test notification / 测试通知").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_config_format() {
+        let channels: Vec<NotificationChannel> = serde_json::from_str(
+            r#"[
+                {"type": "telegram", "bot_token": "123:abc", "chat_id": "42"},
+                {"type": "discord", "enabled": false, "webhook_url": "https://discord.com/api/webhooks/1/x"},
+                {"type": "email", "smtp_host": "smtp.example.com", "from": "ag@example.com", "to": ["me@example.com"]}
+            ]"#,
+        )
+        .unwrap();
+        assert!(channels[0].enabled);
+        assert!(!channels[1].enabled);
+        match &channels[2].target {
+            NotificationTarget::Email { smtp_port, security, .. } => {
+                assert_eq!(*smtp_port, 587);
+                assert_eq!(*security, SmtpSecurity::Starttls);
+            }
+            other => panic!("unexpected target: {:?}", other),
+        }
+        let value = serde_json::to_value(&channels[0]).unwrap();
+        assert_eq!(value["type"], "telegram");
+        assert_eq!(value["chat_id"], "42");
+    }
+
+    #[test]
+    fn test_message_text() {
+        let discord = NotificationTarget::Discord { webhook_url: String::new() };
+        assert_eq!(message_text(&discord, "Title", "Body"), "**Title**\nBody");
+        let telegram = NotificationTarget::Telegram { bot_token: String::new(), chat_id: String::new() };
+        assert_eq!(message_text(&telegram, "Title", "Body"), "Title\nBody");
+    }
+}
@@ -0,0 +1,179 @@
+// 运行事件的系统通知
+//
+// 账号池耗尽、账号需要重新授权、反代服务崩溃后自动重启、模型配额降到阈值以下、发现新版本、
+// 反代在未开启鉴权时可从本机以外访问时弹出系统通知，
+// 并发送到配置的外部通知渠道 (见 notification_channels)。
+// 每一类都可以在设置中单独关闭 (安全警告除外)。同一事件 (类型 + 对象) 在冷却期内只通知一次。
+
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::utils::app::AppHandle;
+#[cfg(feature = "gui")]
+use tauri_plugin_notification::NotificationExt;
+
+use crate::models::NotificationConfig;
+use crate::modules::{config, i18n, logger, notification_channels};
+
+/// 同一事件重复通知的最小间隔 (秒)
+const NOTIFY_COOLDOWN_SECS: i64 = 30 * 60;
+
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+/// (类型, 对象) -> 上次通知时间
+static LAST_SENT: Lazy<Mutex<HashMap<(NotificationKind, String), i64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    PoolExhausted,
+    ReauthRequired,
+    ProxyRestarted,
+    QuotaThreshold,
+    UpdateAvailable,
+    UnauthenticatedExposure,
+}
+
+/// 保存 AppHandle，供没有持有句柄的模块发送通知 (应用启动时调用)
+pub fn init(app: &AppHandle) {
+    let _ = APP_HANDLE.set(app.clone());
+}
+
+fn is_enabled(config: &NotificationConfig, kind: NotificationKind) -> bool {
+    match kind {
+        NotificationKind::PoolExhausted => config.pool_exhausted,
+        NotificationKind::ReauthRequired => config.reauth_required,
+        NotificationKind::ProxyRestarted => config.proxy_restarted,
+        NotificationKind::QuotaThreshold => config.quota_threshold > 0,
+        NotificationKind::UpdateAvailable => config.update_available,
+        // 安全警告不可关闭
+        NotificationKind::UnauthenticatedExposure => true,
+    }
+}
+
+/// 冷却期内是否已通知过，未通知过则记录本次时间
+fn should_send(kind: NotificationKind, key: &str, now: i64) -> bool {
+    let mut sent = LAST_SENT.lock().unwrap();
+    let entry = (kind, key.to_string());
+    if sent.get(&entry).is_some_and(|last| now - last < NOTIFY_COOLDOWN_SECS) {
+        return false;
+    }
+    sent.insert(entry, now);
+    true
+}
+
+fn send(kind: NotificationKind, key: &str, title_key: &str, body_key: &str, args: &[(&str, &str)]) {
+    let Ok(app_config) = config::load_app_config() else { return };
+    // 无界面模式下没有 AppHandle，只发送到外部渠道
+    let app = APP_HANDLE.get();
+    if app.is_none() && app_config.notifications.channels.is_empty() {
+        return;
+    }
+    if !is_enabled(&app_config.notifications, kind) || !should_send(kind, key, chrono::Utc::now().timestamp()) {
+        return;
+    }
+    let lang = &app_config.language;
+    let title = i18n::tr_lang(lang, &format!("system_notifications.{}", title_key), &[]);
+    let body = i18n::tr_lang(lang, &format!("system_notifications.{}", body_key), args);
+    notification_channels::dispatch(&app_config.notifications.channels, title.clone(), body.clone());
+    if let Some(app) = app {
+        show(app, title, body);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn show(app: &AppHandle, title: String, body: String) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        logger::log_warn(&format!("显示系统通知失败: {}", e));
+    }
+}
+
+/// 未启用界面时没有系统通知 (也不会调用 init)
+#[cfg(not(feature = "gui"))]
+fn show(_app: &AppHandle, _title: String, _body: String) {}
+
+/// 所有账号都在冷却或被隔离
+pub fn pool_exhausted() {
+    send(NotificationKind::PoolExhausted, "", "pool_exhausted_title", "pool_exhausted_body", &[]);
+}
+
+/// 账号需要重新授权
+pub fn reauth_required(email: &str) {
+    send(NotificationKind::ReauthRequired, email, "reauth_title", "reauth_body", &[("email", email)]);
+}
+
+/// 反代服务意外退出后的重启结果
+pub fn proxy_restarted(result: Result<u16, &str>) {
+    match result {
+        Ok(port) => send(
+            NotificationKind::ProxyRestarted,
+            "",
+            "proxy_restarted_title",
+            "proxy_restarted_body",
+            &[("port", &port.to_string())],
+        ),
+        Err(error) => send(
+            NotificationKind::ProxyRestarted,
+            "",
+            "proxy_restarted_title",
+            "proxy_restart_failed_body",
+            &[("error", error)],
+        ),
+    }
+}
+
+/// 模型剩余配额从阈值以上降到阈值及以下时通知
+pub fn quota_crossed(email: &str, model: &str, previous: Option<i32>, percentage: i32) {
+    let Ok(app_config) = config::load_app_config() else { return };
+    let threshold = app_config.notifications.quota_threshold as i32;
+    if !crossed_threshold(previous, percentage, threshold) {
+        return;
+    }
+    send(
+        NotificationKind::QuotaThreshold,
+        &format!("{}/{}", email, model),
+        "quota_threshold_title",
+        "quota_threshold_body",
+        &[("email", email), ("model", model), ("percentage", &percentage.to_string())],
+    );
+}
+
+/// 反代监听非本机地址且未开启鉴权
+pub fn unauthenticated_exposure(address: &str) {
+    send(NotificationKind::UnauthenticatedExposure, address, "exposure_title", "exposure_body", &[("address", address)]);
+}
+
+/// 发现新版本 (每个版本只通知一次)
+pub fn update_available(latest_version: &str, current_version: &str) {
+    send(
+        NotificationKind::UpdateAvailable,
+        latest_version,
+        "update_available_title",
+        "update_available_body",
+        &[("version", latest_version), ("current", current_version)],
+    );
+}
+
+fn crossed_threshold(previous: Option<i32>, percentage: i32, threshold: i32) -> bool {
+    threshold > 0 && percentage <= threshold && previous.is_none_or(|p| p > threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_crossing() {
+        assert!(crossed_threshold(Some(20), 10, 10));
+        assert!(crossed_threshold(None, 3, 10));
+        assert!(!crossed_threshold(Some(8), 5, 10), "already below threshold");
+        assert!(!crossed_threshold(Some(20), 15, 10));
+        assert!(!crossed_threshold(Some(20), 0, 0), "disabled");
+    }
+
+    #[test]
+    fn test_should_send_respects_cooldown() {
+        assert!(should_send(NotificationKind::ReauthRequired, "cooldown@test", 1_000));
+        assert!(!should_send(NotificationKind::ReauthRequired, "cooldown@test", 1_000 + 60));
+        assert!(should_send(NotificationKind::QuotaThreshold, "cooldown@test", 1_000 + 60));
+        assert!(should_send(NotificationKind::ReauthRequired, "cooldown@test", 1_000 + NOTIFY_COOLDOWN_SECS));
+    }
+}
@@ -1,14 +1,31 @@
+use futures::future::{BoxFuture, FutureExt, Shared};
 use serde::{Deserialize, Serialize};
 
 // Google OAuth 配置
-const CLIENT_ID: &str = "1071006060591-tmhssin2h21lcre235vtolojh4g403ep.apps.googleusercontent.com";
-const CLIENT_SECRET: &str = "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";
-const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+pub const CLIENT_ID: &str = "1071006060591-tmhssin2h21lcre235vtolojh4g403ep.apps.googleusercontent.com";
+pub const CLIENT_SECRET: &str = "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";
+// gemini-cli 使用的公开 OAuth 客户端：其签发的 refresh_token 只能由同一客户端刷新
+const GEMINI_CLI_CLIENT_ID: &str = "681255809395-oo8ft2oprdrnp9e3aqf6av3hmdib135j.apps.googleusercontent.com";
+const GEMINI_CLI_CLIENT_SECRET: &str = "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";
+/// 刷新 Token 时依次尝试的客户端 (client_id, client_secret)
+const REFRESH_CLIENTS: [(&str, &str); 2] = [
+    (CLIENT_ID, CLIENT_SECRET),
+    (GEMINI_CLI_CLIENT_ID, GEMINI_CLI_CLIENT_SECRET),
+];
+pub const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
 const USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v2/userinfo";
 
 const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+/// 授权范围 (导出给其他工具的凭据中也会写入)
+pub const SCOPES: [&str; 5] = [
+    "https://www.googleapis.com/auth/cloud-platform",
+    "https://www.googleapis.com/auth/userinfo.email",
+    "https://www.googleapis.com/auth/userinfo.profile",
+    "https://www.googleapis.com/auth/cclog",
+    "https://www.googleapis.com/auth/experimentsandconfigs",
+];
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenResponse {
     pub access_token: String,
     pub expires_in: i64,
@@ -49,14 +66,10 @@ impl UserInfo {
 
 
 /// 生成 OAuth 授权 URL
-pub fn get_auth_url(redirect_uri: &str) -> String {
-    let scopes = vec![
-        "https://www.googleapis.com/auth/cloud-platform",
-        "https://www.googleapis.com/auth/userinfo.email",
-        "https://www.googleapis.com/auth/userinfo.profile",
-        "https://www.googleapis.com/auth/cclog",
-        "https://www.googleapis.com/auth/experimentsandconfigs"
-    ].join(" ");
+///
+/// `state` 会原样回传到回调地址，用于防止 CSRF
+pub fn get_auth_url(redirect_uri: &str, state: &str) -> String {
+    let scopes = SCOPES.join(" ");
 
     let params = vec![
         ("client_id", CLIENT_ID),
@@ -66,6 +79,7 @@ pub fn get_auth_url(redirect_uri: &str) -> String {
         ("access_type", "offline"),
         ("prompt", "consent"),
         ("include_granted_scopes", "true"),
+        ("state", state),
     ];
     
     let url = url::Url::parse_with_params(AUTH_URL, &params).expect("无效的 Auth URL");
@@ -74,7 +88,7 @@ pub fn get_auth_url(redirect_uri: &str) -> String {
 
 /// 使用 Authorization Code 交换 Token
 pub async fn exchange_code(code: &str, redirect_uri: &str) -> Result<TokenResponse, String> {
-    let client = crate::utils::http::create_client(15);
+    let client = crate::utils::http::create_upstream_client(15);
     
     let params = [
         ("client_id", CLIENT_ID),
@@ -120,13 +134,90 @@ pub async fn exchange_code(code: &str, redirect_uri: &str) -> Result<TokenRespon
     }
 }
 
+/// refresh_token -> 可刷新它的非默认客户端下标 (仅本次运行期间，避免每次先用默认客户端失败一次)
+static REFRESH_CLIENT_HINTS: once_cell::sync::Lazy<dashmap::DashMap<String, usize>> =
+    once_cell::sync::Lazy::new(dashmap::DashMap::new);
+
+/// Token 刷新失败是否因为 refresh_token 不属于该 OAuth 客户端
+fn is_client_mismatch(error_text: &str) -> bool {
+    error_text.contains("unauthorized_client")
+}
+
+type RefreshFuture = Shared<BoxFuture<'static, Result<TokenResponse, String>>>;
+
+/// refresh_token -> 正在进行中的刷新请求 (同一 refresh_token 同时只允许一个刷新)
+static REFRESH_INFLIGHT: once_cell::sync::Lazy<dashmap::DashMap<String, RefreshFuture>> =
+    once_cell::sync::Lazy::new(dashmap::DashMap::new);
+
+/// 加入 `key` 对应的进行中请求；没有时用 `start` 发起一个新请求，完成后自动移除
+fn join_or_start<F>(
+    inflight: &'static dashmap::DashMap<String, RefreshFuture>,
+    key: &str,
+    start: F,
+) -> RefreshFuture
+where
+    F: FnOnce() -> BoxFuture<'static, Result<TokenResponse, String>>,
+{
+    inflight
+        .entry(key.to_string())
+        .or_insert_with(|| {
+            let key = key.to_string();
+            let request = start();
+            async move {
+                let result = request.await;
+                inflight.remove(&key);
+                result
+            }
+            .boxed()
+            .shared()
+        })
+        .clone()
+}
+
 /// 使用 refresh_token 刷新 access_token
+///
+/// 同一 refresh_token 的并发刷新会合并为一次请求，所有调用方共享同一结果，
+/// 避免多个刷新相互竞争导致先拿到的 access_token 被后一次刷新作废
 pub async fn refresh_access_token(refresh_token: &str) -> Result<TokenResponse, String> {
-    let client = crate::utils::http::create_client(15);
+    let owned = refresh_token.to_string();
+    join_or_start(&REFRESH_INFLIGHT, refresh_token, move || {
+        async move { refresh_access_token_once(&owned).await }.boxed()
+    })
+    .await
+}
+
+/// 实际发起一次刷新
+///
+/// 默认使用 Antigravity 客户端；若 refresh_token 由其他已知客户端 (如 gemini-cli) 签发，自动改用对应客户端
+async fn refresh_access_token_once(refresh_token: &str) -> Result<TokenResponse, String> {
+    let hint = REFRESH_CLIENT_HINTS.get(refresh_token).map(|h| *h).unwrap_or(0);
+    let mut order: Vec<usize> = vec![hint];
+    order.extend((0..REFRESH_CLIENTS.len()).filter(|i| *i != hint));
+
+    let mut last_error = String::new();
+    for idx in order {
+        match refresh_with_client(refresh_token, REFRESH_CLIENTS[idx]).await {
+            Ok(token_data) => {
+                if idx == 0 {
+                    REFRESH_CLIENT_HINTS.remove(refresh_token);
+                } else {
+                    REFRESH_CLIENT_HINTS.insert(refresh_token.to_string(), idx);
+                }
+                return Ok(token_data);
+            }
+            Err(e) if is_client_mismatch(&e) => last_error = e,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error)
+}
+
+async fn refresh_with_client(refresh_token: &str, (client_id, client_secret): (&str, &str)) -> Result<TokenResponse, String> {
+    let client = crate::utils::http::create_upstream_client(15);
     
     let params = [
-        ("client_id", CLIENT_ID),
-        ("client_secret", CLIENT_SECRET),
+        ("client_id", client_id),
+        ("client_secret", client_secret),
         ("refresh_token", refresh_token),
         ("grant_type", "refresh_token"),
     ];
@@ -156,7 +247,7 @@ pub async fn refresh_access_token(refresh_token: &str) -> Result<TokenResponse,
 
 /// 获取用户信息
 pub async fn get_user_info(access_token: &str) -> Result<UserInfo, String> {
-    let client = crate::utils::http::create_client(15);
+    let client = crate::utils::http::create_upstream_client(15);
     
     let response = client
         .get(USERINFO_URL)
@@ -189,7 +280,15 @@ pub async fn ensure_fresh_token(
     
     // 需要刷新
     crate::modules::logger::log_info("Token 即将过期，正在刷新...");
-    let response = refresh_access_token(&current_token.refresh_token).await?;
+    let email = current_token.email.as_deref();
+    if let Some(email) = email {
+        crate::modules::token_status::mark_refresh_started(email);
+    }
+    let result = refresh_access_token(&current_token.refresh_token).await;
+    if let Some(email) = email {
+        crate::modules::token_status::mark_refresh_finished(email, result.as_ref().map(|_| ()).map_err(|e| e.as_str()));
+    }
+    let response = result?;
     
     // 构造新 TokenData
     Ok(crate::models::TokenData::new(
@@ -201,3 +300,52 @@ pub async fn ensure_fresh_token(
         None,  // session_id 会在 token_manager 中生成
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_client_mismatch() {
+        assert!(is_client_mismatch(r#"刷新失败: {"error": "unauthorized_client", "error_description": "Unauthorized"}"#));
+        assert!(!is_client_mismatch(r#"刷新失败: {"error": "invalid_grant", "error_description": "Token has been expired or revoked."}"#));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_share_one_request() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        static INFLIGHT: once_cell::sync::Lazy<dashmap::DashMap<String, RefreshFuture>> =
+            once_cell::sync::Lazy::new(dashmap::DashMap::new);
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let start = |started: Arc<AtomicUsize>| {
+            move || {
+                async move {
+                    let n = started.fetch_add(1, Ordering::SeqCst) + 1;
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                    Ok(TokenResponse {
+                        access_token: format!("at-{}", n),
+                        expires_in: 3600,
+                        token_type: "Bearer".to_string(),
+                        refresh_token: None,
+                    })
+                }
+                .boxed()
+            }
+        };
+
+        let waiters: Vec<_> = (0..5)
+            .map(|_| join_or_start(&INFLIGHT, "1//rt", start(started.clone())))
+            .collect();
+        let results = futures::future::join_all(waiters).await;
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|r| r.as_ref().unwrap().access_token == "at-1"));
+        assert!(INFLIGHT.is_empty());
+
+        // 上一次刷新结束后再次刷新会发起新请求
+        let next = join_or_start(&INFLIGHT, "1//rt", start(started.clone())).await.unwrap();
+        assert_eq!(next.access_token, "at-2");
+    }
+}
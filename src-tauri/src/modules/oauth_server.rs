@@ -3,8 +3,9 @@ use tokio::net::TcpListener;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
 use std::sync::{Mutex, OnceLock};
-use tauri::Url;
+use url::Url;
 use crate::modules::oauth;
+use crate::modules::i18n::tr;
 
 struct OAuthFlowState {
     auth_url: String,
@@ -13,6 +14,9 @@ struct OAuthFlowState {
     code_rx: Option<oneshot::Receiver<Result<String, String>>>,
 }
 
+/// 等待用户在浏览器中完成授权的最长时间
+const OAUTH_CALLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
 static OAUTH_FLOW_STATE: OnceLock<Mutex<Option<OAuthFlowState>>> = OnceLock::new();
 
 fn get_oauth_flow_state() -> &'static Mutex<Option<OAuthFlowState>> {
@@ -40,9 +44,79 @@ fn oauth_fail_html() -> &'static str {
     </html>"
 }
 
-async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<String, String> {
-    use tauri::Emitter;
+/// IPv4/IPv6 回调监听器共享的 code 发送端
+type SharedCodeSender = std::sync::Arc<tokio::sync::Mutex<Option<oneshot::Sender<Result<String, String>>>>>;
+
+/// 解析回调请求路径
+///
+/// 返回 None 表示不是 OAuth 回调 (如 /favicon.ico)，应忽略并继续等待
+fn parse_callback(path: &str, port: u16, expected_state: &str) -> Option<Result<String, String>> {
+    let url = Url::parse(&format!("http://127.0.0.1:{}{}", port, path)).ok()?;
+    if url.path() != "/oauth-callback" {
+        return None;
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    if let Some(error) = param("error") {
+        return Some(Err(tr("backend.errors.oauth.denied", &[("error", &error)])));
+    }
+    if param("state").as_deref() != Some(expected_state) {
+        return Some(Err(tr("backend.errors.oauth.state_mismatch", &[])));
+    }
+    Some(param("code").ok_or_else(|| "未能在回调中获取 Authorization Code".to_string()))
+}
+
+/// 在回调端口上等待 OAuth 回调，忽略与授权无关的请求
+async fn serve_callback(
+    listener: TcpListener,
+    port: u16,
+    expected_state: String,
+    code_tx: SharedCodeSender,
+    mut cancel_rx: watch::Receiver<bool>,
+    app_handle: crate::utils::app::AppHandle,
+) {
+    loop {
+        let mut stream = match tokio::select! {
+            res = listener.accept() => res.map_err(|e| format!("接受连接失败: {}", e)),
+            _ = cancel_rx.changed() => return,
+        } {
+            Ok((stream, _)) => stream,
+            Err(_) => return,
+        };
+
+        let mut buffer = [0u8; 4096];
+        let _ = stream.read(&mut buffer).await;
+        let request = String::from_utf8_lossy(&buffer);
+        let path = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/");
+
+        let Some(result) = parse_callback(path, port, &expected_state) else {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").await;
+            continue;
+        };
+
+        let response_html = if result.is_ok() { oauth_success_html() } else { oauth_fail_html() };
+        let _ = stream.write_all(response_html.as_bytes()).await;
+        let _ = stream.flush().await;
+
+        // IPv4/IPv6 两个监听器共享同一个 sender，只有先到的回调生效
+        if let Some(sender) = code_tx.lock().await.take() {
+            crate::utils::app::emit(&app_handle, "oauth-callback-received", ());
+            let _ = sender.send(result);
+        }
+        return;
+    }
+}
 
+async fn ensure_oauth_flow_prepared(app_handle: &crate::utils::app::AppHandle) -> Result<String, String> {
     // 如果已有 flow，直接返回 URL
     if let Ok(state) = get_oauth_flow_state().lock() {
         if let Some(s) = state.as_ref() {
@@ -111,7 +185,8 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
         format!("http://[::1]:{}/oauth-callback", port)
     };
 
-    let auth_url = oauth::get_auth_url(&redirect_uri);
+    let csrf_state = uuid::Uuid::new_v4().simple().to_string();
+    let auth_url = oauth::get_auth_url(&redirect_uri, &csrf_state);
 
     // 取消信号（支持多消费者）
     let (cancel_tx, cancel_rx) = watch::channel(false);
@@ -124,81 +199,25 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
     let app_handle_for_tasks = app_handle.clone();
 
     if let Some(l4) = ipv4_listener {
-        let tx = code_tx.clone();
-        let mut rx = cancel_rx.clone();
-        let app_handle = app_handle_for_tasks.clone();
-        tokio::spawn(async move {
-            if let Ok((mut stream, _)) = tokio::select! {
-                res = l4.accept() => res.map_err(|e| format!("接受连接失败: {}", e)),
-                _ = rx.changed() => Err("OAuth cancelled".to_string()),
-            } {
-                // Reuse the existing parsing/response code by constructing a temporary listener task
-                // that sends into the shared oneshot.
-                let mut buffer = [0u8; 4096];
-                let _ = stream.read(&mut buffer).await;
-                let request = String::from_utf8_lossy(&buffer);
-                let code = request
-                    .lines()
-                    .next()
-                    .and_then(|line| line.split_whitespace().nth(1))
-                    .and_then(|path| Url::parse(&format!("http://127.0.0.1:{}{}", port, path)).ok())
-                    .and_then(|url| {
-                        url.query_pairs()
-                            .find(|(k, _)| k == "code")
-                            .map(|(_, v)| v.into_owned())
-                    });
-
-                let (result, response_html) = match code {
-                    Some(code) => (Ok(code), oauth_success_html()),
-                    None => (Err("未能在回调中获取 Authorization Code".to_string()), oauth_fail_html()),
-                };
-                let _ = stream.write_all(response_html.as_bytes()).await;
-                let _ = stream.flush().await;
-
-                if let Some(sender) = tx.lock().await.take() {
-                    let _ = app_handle.emit("oauth-callback-received", ());
-                    let _ = sender.send(result);
-                }
-            }
-        });
+        tokio::spawn(serve_callback(
+            l4,
+            port,
+            csrf_state.clone(),
+            code_tx.clone(),
+            cancel_rx.clone(),
+            app_handle_for_tasks.clone(),
+        ));
     }
 
     if let Some(l6) = ipv6_listener {
-        let tx = code_tx.clone();
-        let mut rx = cancel_rx;
-        let app_handle = app_handle_for_tasks;
-        tokio::spawn(async move {
-            if let Ok((mut stream, _)) = tokio::select! {
-                res = l6.accept() => res.map_err(|e| format!("接受连接失败: {}", e)),
-                _ = rx.changed() => Err("OAuth cancelled".to_string()),
-            } {
-                let mut buffer = [0u8; 4096];
-                let _ = stream.read(&mut buffer).await;
-                let request = String::from_utf8_lossy(&buffer);
-                let code = request
-                    .lines()
-                    .next()
-                    .and_then(|line| line.split_whitespace().nth(1))
-                    .and_then(|path| Url::parse(&format!("http://127.0.0.1:{}{}", port, path)).ok())
-                    .and_then(|url| {
-                        url.query_pairs()
-                            .find(|(k, _)| k == "code")
-                            .map(|(_, v)| v.into_owned())
-                    });
-
-                let (result, response_html) = match code {
-                    Some(code) => (Ok(code), oauth_success_html()),
-                    None => (Err("未能在回调中获取 Authorization Code".to_string()), oauth_fail_html()),
-                };
-                let _ = stream.write_all(response_html.as_bytes()).await;
-                let _ = stream.flush().await;
-
-                if let Some(sender) = tx.lock().await.take() {
-                    let _ = app_handle.emit("oauth-callback-received", ());
-                    let _ = sender.send(result);
-                }
-            }
-        });
+        tokio::spawn(serve_callback(
+            l6,
+            port,
+            csrf_state,
+            code_tx,
+            cancel_rx,
+            app_handle_for_tasks,
+        ));
     }
 
     // 保存状态
@@ -212,13 +231,13 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
     }
 
     // 发送事件给前端（用于展示/复制链接）
-    let _ = app_handle.emit("oauth-url-generated", &auth_url);
+    crate::utils::app::emit(app_handle, "oauth-url-generated", &auth_url);
 
     Ok(auth_url)
 }
 
 /// 预生成 OAuth URL (不打开浏览器、不阻塞等待回调)
-pub async fn prepare_oauth_url(app_handle: tauri::AppHandle) -> Result<String, String> {
+pub async fn prepare_oauth_url(app_handle: crate::utils::app::AppHandle) -> Result<String, String> {
     ensure_oauth_flow_prepared(&app_handle).await
 }
 
@@ -232,17 +251,35 @@ pub fn cancel_oauth_flow() {
     }
 }
 
+/// 等待回调中的 Authorization Code，超时或失败时清理流程状态
+async fn wait_for_code(code_rx: oneshot::Receiver<Result<String, String>>) -> Result<String, String> {
+    let result = match tokio::time::timeout(OAUTH_CALLBACK_TIMEOUT, code_rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(tr("backend.errors.oauth.callback_failed", &[])),
+        Err(_) => Err(tr("backend.errors.oauth.timeout", &[])),
+    };
+    if result.is_err() {
+        cancel_oauth_flow();
+    }
+    result
+}
+
 /// 启动 OAuth 流程并等待回调，再交换 token
-pub async fn start_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::TokenResponse, String> {
+pub async fn start_oauth_flow(app_handle: crate::utils::app::AppHandle) -> Result<oauth::TokenResponse, String> {
     // 确保已准备好 URL + listener（这样即使用户先授权，也不会卡住）
     let auth_url = ensure_oauth_flow_prepared(&app_handle).await?;
 
     // 打开默认浏览器
-    use tauri_plugin_opener::OpenerExt;
-    app_handle
-        .opener()
-        .open_url(&auth_url, None::<String>)
-        .map_err(|e| format!("无法打开浏览器: {}", e))?;
+    #[cfg(feature = "gui")]
+    {
+        use tauri_plugin_opener::OpenerExt;
+        app_handle
+            .opener()
+            .open_url(&auth_url, None::<String>)
+            .map_err(|e| format!("无法打开浏览器: {}", e))?;
+    }
+    #[cfg(not(feature = "gui"))]
+    crate::modules::logger::log_info(&format!("请在浏览器中打开授权链接: {}", auth_url));
 
     // 取出 code_rx 用于等待
     let (code_rx, redirect_uri) = {
@@ -260,11 +297,7 @@ pub async fn start_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::Tok
     };
 
     // 等待 code（如果用户已完成授权，此处会立即返回）
-    let code = match code_rx.await {
-        Ok(Ok(code)) => code,
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("等待 OAuth 回调失败".to_string()),
-    };
+    let code = wait_for_code(code_rx).await?;
 
     // 清理 flow state（释放 cancel_tx 等）
     if let Ok(mut lock) = get_oauth_flow_state().lock() {
@@ -277,7 +310,7 @@ pub async fn start_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::Tok
 /// Завершить OAuth flow без открытия браузера.
 /// Предполагается, что пользователь открыл ссылку вручную (или ранее была открыта),
 /// а мы только ждём callback и обмениваем code на token.
-pub async fn complete_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::TokenResponse, String> {
+pub async fn complete_oauth_flow(app_handle: crate::utils::app::AppHandle) -> Result<oauth::TokenResponse, String> {
     // Ensure URL + listeners exist
     let _ = ensure_oauth_flow_prepared(&app_handle).await?;
 
@@ -296,11 +329,7 @@ pub async fn complete_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::
         (rx, state.redirect_uri.clone())
     };
 
-    let code = match code_rx.await {
-        Ok(Ok(code)) => code,
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("等待 OAuth 回调失败".to_string()),
-    };
+    let code = wait_for_code(code_rx).await?;
 
     if let Ok(mut lock) = get_oauth_flow_state().lock() {
         *lock = None;
@@ -308,3 +337,20 @@ pub async fn complete_oauth_flow(app_handle: tauri::AppHandle) -> Result<oauth::
 
     oauth::exchange_code(&code, &redirect_uri).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_callback() {
+        assert_eq!(
+            parse_callback("/oauth-callback?code=abc&state=s1", 8080, "s1"),
+            Some(Ok("abc".to_string()))
+        );
+        assert!(matches!(parse_callback("/oauth-callback?code=abc&state=other", 8080, "s1"), Some(Err(_))));
+        assert!(matches!(parse_callback("/oauth-callback?error=access_denied&state=s1", 8080, "s1"), Some(Err(_))));
+        assert!(matches!(parse_callback("/oauth-callback?state=s1", 8080, "s1"), Some(Err(_))));
+        assert_eq!(parse_callback("/favicon.ico", 8080, "s1"), None);
+    }
+}
@@ -0,0 +1,317 @@
+// OpenTelemetry 链路导出 (OTLP/HTTP JSON)
+//
+// 作为 tracing Layer 收集本 crate 的 span (入站请求 -> 选择账号 -> 上游调用 -> 协议转换)，
+// 按 OTLP JSON 编码后每隔几秒批量 POST 到用户配置的 Collector (如 `http://127.0.0.1:4318/v1/traces`)。
+// 默认关闭；关闭时 Layer 不记录任何数据。导出队列有上限，Collector 不可用时丢弃最旧的 span。
+
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::models::config::OtelConfig;
+use crate::utils::crypto::hex;
+
+const EXPORT_INTERVAL_SECS: u64 = 5;
+const MAX_QUEUED_SPANS: usize = 4096;
+const MAX_BATCH: usize = 512;
+
+/// OTLP SpanKind
+const KIND_INTERNAL: i64 = 1;
+const KIND_SERVER: i64 = 2;
+const KIND_CLIENT: i64 = 3;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CONFIG: Lazy<RwLock<OtelConfig>> = Lazy::new(|| RwLock::new(OtelConfig::default()));
+static QUEUE: Lazy<Mutex<VecDeque<FinishedSpan>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// 更新导出配置 (启动时与保存设置时调用)
+pub fn set_config(config: OtelConfig) {
+    let enabled = config.enabled && !config.endpoint.trim().is_empty();
+    *CONFIG.write().unwrap() = config;
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        QUEUE.lock().unwrap().clear();
+    }
+}
+
+fn crate_name() -> &'static str {
+    module_path!().split("::").next().unwrap_or_default()
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+}
+
+/// 进行中的 span 数据 (存放在 span extensions 中)
+struct SpanData {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    start_nanos: u64,
+    kind: i64,
+    attributes: Vec<(String, Value)>,
+}
+
+#[derive(Debug, Clone)]
+struct FinishedSpan {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    name: &'static str,
+    kind: i64,
+    start_nanos: u64,
+    end_nanos: u64,
+    attributes: Vec<(String, Value)>,
+}
+
+impl FinishedSpan {
+    /// 带 `error` 字段或 5xx 状态码视为失败
+    fn is_error(&self) -> bool {
+        self.attributes.iter().any(|(k, v)| {
+            k == "error" || (k == "http.status_code" && v.as_u64().is_some_and(|code| code >= 500))
+        })
+    }
+}
+
+struct FieldVisitor<'a> {
+    attributes: &'a mut Vec<(String, Value)>,
+    kind: &'a mut i64,
+}
+
+impl FieldVisitor<'_> {
+    fn put(&mut self, field: &tracing::field::Field, value: Value) {
+        if field.name() == "otel.kind" {
+            *self.kind = match value.as_str() {
+                Some("server") => KIND_SERVER,
+                Some("client") => KIND_CLIENT,
+                _ => KIND_INTERNAL,
+            };
+            return;
+        }
+        match self.attributes.iter_mut().find(|(k, _)| k == field.name()) {
+            Some(slot) => slot.1 = value,
+            None => self.attributes.push((field.name().to_string(), value)),
+        }
+    }
+}
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if crate::utils::redact::is_secret_field(field.name()) {
+            return self.put(field, json!(crate::utils::redact::REDACTED));
+        }
+        self.put(field, json!(crate::utils::redact::redact(value)));
+    }
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.put(field, json!(value));
+    }
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.put(field, json!(value));
+    }
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.put(field, json!(value));
+    }
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.put(field, json!(value));
+    }
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        let text = format!("{:?}", value);
+        self.put(field, json!(crate::utils::redact::redact(&text)));
+    }
+}
+
+/// 收集 span 的 tracing Layer
+pub struct OtlpLayer;
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !ENABLED.load(Ordering::Relaxed) || !attrs.metadata().target().starts_with(crate_name()) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else { return };
+        let parent = span
+            .scope()
+            .skip(1)
+            .find_map(|s| s.extensions().get::<SpanData>().map(|d| (d.trace_id, d.span_id)));
+
+        let mut data = SpanData {
+            trace_id: parent.map(|(trace, _)| trace).unwrap_or_else(rand::random),
+            span_id: rand::random(),
+            parent_span_id: parent.map(|(_, span)| span),
+            start_nanos: now_nanos(),
+            kind: KIND_INTERNAL,
+            attributes: Vec::new(),
+        };
+        attrs.record(&mut FieldVisitor { attributes: &mut data.attributes, kind: &mut data.kind });
+        span.extensions_mut().insert(data);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SpanData>() {
+            values.record(&mut FieldVisitor { attributes: &mut data.attributes, kind: &mut data.kind });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else { return };
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut queue = QUEUE.lock().unwrap();
+        if queue.len() >= MAX_QUEUED_SPANS {
+            queue.pop_front();
+        }
+        queue.push_back(FinishedSpan {
+            trace_id: data.trace_id,
+            span_id: data.span_id,
+            parent_span_id: data.parent_span_id,
+            name: span.name(),
+            kind: data.kind,
+            start_nanos: data.start_nanos,
+            end_nanos: now_nanos(),
+            attributes: data.attributes,
+        });
+    }
+}
+
+fn attribute_value(value: &Value) -> Value {
+    match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) if n.is_f64() => json!({ "doubleValue": n }),
+        Value::Number(n) => json!({ "intValue": n.to_string() }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    }
+}
+
+fn attributes(attrs: &[(String, Value)]) -> Vec<Value> {
+    attrs.iter().map(|(k, v)| json!({ "key": k, "value": attribute_value(v) })).collect()
+}
+
+/// 编码为 OTLP/HTTP JSON 的 ExportTraceServiceRequest
+fn encode(spans: &[FinishedSpan], service_name: &str) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|s| {
+            let mut span = json!({
+                "traceId": hex(&s.trace_id),
+                "spanId": hex(&s.span_id),
+                "name": s.name,
+                "kind": s.kind,
+                "startTimeUnixNano": s.start_nanos.to_string(),
+                "endTimeUnixNano": s.end_nanos.to_string(),
+                "attributes": attributes(&s.attributes),
+                // STATUS_CODE_OK = 1, STATUS_CODE_ERROR = 2
+                "status": { "code": if s.is_error() { 2 } else { 1 } },
+            });
+            if let Some(parent) = s.parent_span_id {
+                span["parentSpanId"] = json!(hex(&parent));
+            }
+            span
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": attributes(&[
+                    ("service.name".to_string(), json!(service_name)),
+                    ("service.version".to_string(), json!(env!("CARGO_PKG_VERSION"))),
+                ]),
+            },
+            "scopeSpans": [{
+                "scope": { "name": crate_name() },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+async fn export_batch(client: &reqwest::Client) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let batch: Vec<FinishedSpan> = {
+        let mut queue = QUEUE.lock().unwrap();
+        let n = queue.len().min(MAX_BATCH);
+        queue.drain(..n).collect()
+    };
+    if batch.is_empty() {
+        return;
+    }
+    let config = CONFIG.read().unwrap().clone();
+    let mut request = client.post(config.endpoint.trim()).json(&encode(&batch, &config.service_name));
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    match request.send().await {
+        Ok(resp) if !resp.status().is_success() => {
+            tracing::warn!("[OTLP] Collector rejected {} spans: {}", batch.len(), resp.status());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("[OTLP] Failed to export {} spans: {}", batch.len(), e),
+    }
+}
+
+/// 启动后台导出任务
+pub fn start_exporter() {
+    crate::utils::app::spawn(async move {
+        // Collector 一般部署在本机或局域网，不经过上游代理
+        let client = crate::utils::http::create_client_with_proxy(10, None);
+        let mut interval = tokio::time::interval(Duration::from_secs(EXPORT_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            export_batch(&client).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_layer_links_child_spans_and_encodes_otlp_json() {
+        set_config(OtelConfig { enabled: true, ..Default::default() });
+        let subscriber = tracing_subscriber::registry().with(OtlpLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("proxy.request", otel.kind = "server", http.status_code = tracing::field::Empty);
+            outer.in_scope(|| {
+                let _inner = tracing::info_span!("upstream.call", otel.kind = "client", method = "generateContent").entered();
+            });
+            outer.record("http.status_code", 502u64);
+        });
+        let spans: Vec<FinishedSpan> = QUEUE.lock().unwrap().drain(..).collect();
+        set_config(OtelConfig::default());
+
+        assert_eq!(spans.len(), 2);
+        let (inner, outer) = (&spans[0], &spans[1]);
+        assert_eq!((inner.name, inner.kind), ("upstream.call", KIND_CLIENT));
+        assert_eq!(inner.trace_id, outer.trace_id);
+        assert_eq!(inner.parent_span_id, Some(outer.span_id));
+        assert!(outer.parent_span_id.is_none());
+        assert!(outer.is_error());
+
+        let body = encode(&spans, "antigravity");
+        let encoded = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(encoded[0]["parentSpanId"], json!(hex(&outer.span_id)));
+        assert_eq!(encoded[0]["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(encoded[1]["kind"], json!(KIND_SERVER));
+        assert_eq!(encoded[1]["status"]["code"], json!(2));
+        assert_eq!(encoded[1]["attributes"][0], json!({ "key": "http.status_code", "value": { "intValue": "502" } }));
+        assert_eq!(body["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"], "antigravity");
+    }
+}
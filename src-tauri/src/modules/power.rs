@@ -0,0 +1,212 @@
+// 电源与网络感知
+//
+// 使用电池供电、处于按流量计费的网络或离线时暂停后台任务 (保活、定时预热、后台检查更新、
+// 前端自动刷新配额)，条件解除后自动恢复，让常驻后台的反代服务对笔记本更友好。
+// 反代请求本身不受影响。状态探测结果缓存一段时间，避免每个任务都调用系统命令。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::models::PowerSavingConfig;
+use crate::modules::{config, logger};
+
+/// 状态缓存时长 (秒)
+const STATE_CACHE_SECS: i64 = 60;
+
+/// 当前电源与网络状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PowerState {
+    /// 正在使用电池供电
+    pub on_battery: bool,
+    /// 当前网络按流量计费
+    pub metered: bool,
+    /// 是否有可用的网络路由
+    pub online: bool,
+}
+
+/// 后台任务是否应暂停及原因
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerStatus {
+    #[serde(flatten)]
+    pub state: PowerState,
+    /// 后台任务当前是否暂停
+    pub paused: bool,
+    /// 暂停原因: battery / metered / offline
+    pub reason: Option<&'static str>,
+}
+
+/// (探测时间, 状态)
+static CACHE: Lazy<Mutex<Option<(i64, PowerState)>>> = Lazy::new(|| Mutex::new(None));
+/// 上一次的暂停原因 (状态变化时写日志)
+static LAST_REASON: Lazy<Mutex<Option<&'static str>>> = Lazy::new(|| Mutex::new(None));
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `pmset -g batt` 输出中的供电来源
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> bool {
+    output.contains("'Battery Power'")
+}
+
+/// `nmcli -t -f GENERAL.METERED device show` 输出中是否有按流量计费的连接 (含推测)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nmcli_metered(output: &str) -> bool {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .any(|(key, value)| key == "GENERAL.METERED" && value.trim().starts_with("yes"))
+}
+
+/// Windows PowerShell 输出的两行: 电池状态 (1 为放电) 与网络费用类型
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_windows(output: &str) -> (bool, bool) {
+    let mut lines = output.lines().map(str::trim);
+    let on_battery = lines.next() == Some("1");
+    let metered = lines.next().is_some_and(|cost| cost == "Fixed" || cost == "Variable");
+    (on_battery, metered)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_battery_and_metered() -> (bool, bool) {
+    // 有电池且没有接通的外部电源时视为电池供电
+    let mut has_battery = false;
+    let mut mains_online = false;
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+            match kind.trim() {
+                "Battery" => has_battery = true,
+                "Mains" | "USB" if std::fs::read_to_string(path.join("online")).is_ok_and(|v| v.trim() == "1") => {
+                    mains_online = true;
+                }
+                _ => {}
+            }
+        }
+    }
+    let metered = command_output("nmcli", &["-t", "-f", "GENERAL.METERED", "device", "show"])
+        .is_some_and(|out| parse_nmcli_metered(&out));
+    (has_battery && !mains_online, metered)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_battery_and_metered() -> (bool, bool) {
+    // macOS 没有可供命令行查询的计费网络标记
+    let on_battery = command_output("pmset", &["-g", "batt"]).is_some_and(|out| parse_pmset(&out));
+    (on_battery, false)
+}
+
+#[cfg(target_os = "windows")]
+fn detect_battery_and_metered() -> (bool, bool) {
+    // 每项固定输出一行 (台式机没有电池时输出空行)
+    const SCRIPT: &str = "$b = (Get-CimInstance Win32_Battery | Select-Object -First 1).BatteryStatus; Write-Output \"$b\"; \
+        [void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+        $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+        $c = if ($p) { $p.GetConnectionCost().NetworkCostType } else { '' }; Write-Output \"$c\"";
+    command_output("powershell", &["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .map(|out| parse_windows(&out))
+        .unwrap_or_default()
+}
+
+/// 是否存在默认路由 (UDP connect 只查路由表，不发送数据)
+fn detect_online() -> bool {
+    std::net::UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| socket.connect("8.8.8.8:53"))
+        .is_ok()
+}
+
+/// 当前电源与网络状态 (带缓存)
+pub fn current_state() -> PowerState {
+    let now = chrono::Utc::now().timestamp();
+    if let Some((at, state)) = *CACHE.lock().unwrap() {
+        if now - at < STATE_CACHE_SECS {
+            return state;
+        }
+    }
+    let (on_battery, metered) = detect_battery_and_metered();
+    let state = PowerState { on_battery, metered, online: detect_online() };
+    *CACHE.lock().unwrap() = Some((now, state));
+    state
+}
+
+/// 按配置判断暂停原因
+fn pause_reason(config: &PowerSavingConfig, state: &PowerState) -> Option<&'static str> {
+    if !config.enabled {
+        return None;
+    }
+    if !state.online {
+        Some("offline")
+    } else if config.pause_on_battery && state.on_battery {
+        Some("battery")
+    } else if config.pause_on_metered && state.metered {
+        Some("metered")
+    } else {
+        None
+    }
+}
+
+/// 当前状态与后台任务是否暂停
+pub fn status() -> PowerStatus {
+    let state = current_state();
+    let reason = config::load_app_config()
+        .ok()
+        .and_then(|c| pause_reason(&c.power_saving, &state));
+
+    let mut last = LAST_REASON.lock().unwrap();
+    if *last != reason {
+        match reason {
+            Some(reason) => logger::log_info(&format!("[Power] 后台任务已暂停 ({})", reason)),
+            None => logger::log_info("[Power] 后台任务已恢复"),
+        }
+        *last = reason;
+    }
+    PowerStatus { state, paused: reason.is_some(), reason }
+}
+
+/// 后台任务是否应跳过本轮 (状态探测可能调用系统命令，放到阻塞线程执行)
+pub async fn should_pause_background() -> bool {
+    tokio::task::spawn_blocking(|| status().paused).await.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_platform_outputs() {
+        assert!(parse_pmset("Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1)\t80%; discharging"));
+        assert!(!parse_pmset("Now drawing from 'AC Power'"));
+
+        assert!(parse_nmcli_metered("GENERAL.METERED:no\nGENERAL.METERED:yes (guessed)\n"));
+        assert!(!parse_nmcli_metered("GENERAL.METERED:no (guessed)\nGENERAL.METERED:unknown\n"));
+
+        assert_eq!(parse_windows("1\r\nFixed\r\n"), (true, true));
+        assert_eq!(parse_windows("2\r\nUnrestricted\r\n"), (false, false));
+        assert_eq!(parse_windows("\r\nVariable\r\n"), (false, true));
+    }
+
+    #[test]
+    fn test_pause_reason_priority() {
+        let config = PowerSavingConfig::default();
+        let state = |on_battery, metered, online| PowerState { on_battery, metered, online };
+        assert_eq!(pause_reason(&config, &state(true, true, false)), Some("offline"));
+        assert_eq!(pause_reason(&config, &state(true, true, true)), Some("battery"));
+        assert_eq!(pause_reason(&config, &state(false, true, true)), Some("metered"));
+        assert_eq!(pause_reason(&config, &state(false, false, true)), None);
+
+        let disabled = PowerSavingConfig { enabled: false, ..PowerSavingConfig::default() };
+        assert_eq!(pause_reason(&disabled, &state(true, true, false)), None);
+    }
+}
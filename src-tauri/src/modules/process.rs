@@ -606,9 +606,7 @@ pub fn close_antigravity(#[allow(unused_variables)] timeout_secs: u64) -> Result
                                     || name.contains("sandbox");
                                 if !is_helper_by_args && !is_helper_by_name {
                                     main_pid = Some(pid_u32);
-                                    crate::modules::logger::log_info(&format!(
-                                        "   => 识别为主进程 (匹配手动配置路径)"
-                                    ));
+                                    crate::modules::logger::log_info("   => 识别为主进程 (匹配手动配置路径)");
                                     break;
                                 }
                             }
@@ -630,14 +628,10 @@ pub fn close_antigravity(#[allow(unused_variables)] timeout_secs: u64) -> Result
                     if !is_helper_by_args && !is_helper_by_name {
                         if main_pid.is_none() {
                             main_pid = Some(pid_u32);
-                            crate::modules::logger::log_info(&format!(
-                                "   => 识别为主进程 (特征分析)"
-                            ));
+                            crate::modules::logger::log_info("   => 识别为主进程 (特征分析)");
                         }
                     } else {
-                        crate::modules::logger::log_info(&format!(
-                            "   => 识别为辅助进程 (Helper/Args)"
-                        ));
+                        crate::modules::logger::log_info("   => 识别为辅助进程 (Helper/Args)");
                     }
                 }
             }
@@ -0,0 +1,149 @@
+// 配置档案 (Profile) 隔离
+//
+// 每个档案拥有独立的数据子目录 (账号库、配置与 API Key、Token 加密密钥、请求日志)，
+// 互不可见。默认档案直接使用数据根目录以兼容旧版本，其余档案位于 `profiles/<名称>/`。
+// 当前档案名称记录在根目录的 `active_profile` 文件中。
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::modules::i18n::tr;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+const MAX_NAME_LEN: usize = 32;
+
+/// 档案信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub account_count: usize,
+}
+
+/// 校验档案名称 (仅允许字母、数字、`-`、`_`)
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(tr("backend.errors.profile.name_length", &[("max", &MAX_NAME_LEN.to_string())]));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(tr("backend.errors.profile.name_chars", &[]));
+    }
+    Ok(())
+}
+
+/// 档案的数据目录
+pub fn profile_dir(root: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        root.to_path_buf()
+    } else {
+        root.join(PROFILES_DIR).join(name)
+    }
+}
+
+/// 当前档案名称 (记录缺失或指向已删除的档案时回退到默认档案)
+pub fn active_profile(root: &Path) -> String {
+    fs::read_to_string(root.join(ACTIVE_PROFILE_FILE))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|name| validate_name(name).is_ok() && profile_dir(root, name).is_dir())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// 列出所有档案 (默认档案在前，其余按名称排序)
+pub fn list_profiles(root: &Path) -> Result<Vec<ProfileInfo>, String> {
+    let mut names = Vec::new();
+    if let Ok(entries) = fs::read_dir(root.join(PROFILES_DIR)) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() && validate_name(&name).is_ok() && name != DEFAULT_PROFILE {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+
+    let active = active_profile(root);
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let dir = profile_dir(root, &name);
+            let account_count = if crate::modules::account_store::get_store_path(&dir).exists() {
+                crate::modules::account_store::load_index(&dir).map(|i| i.accounts.len()).unwrap_or(0)
+            } else {
+                0
+            };
+            ProfileInfo { active: name == active, name, account_count }
+        })
+        .collect())
+}
+
+/// 创建空档案
+pub fn create_profile(root: &Path, name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    let dir = profile_dir(root, name);
+    if name == DEFAULT_PROFILE || dir.exists() {
+        return Err(tr("backend.errors.profile.exists", &[("name", name)]));
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("创建档案目录失败: {}", e))
+}
+
+/// 切换当前档案
+pub fn set_active_profile(root: &Path, name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    if !profile_dir(root, name).is_dir() {
+        return Err(tr("backend.errors.profile.not_found", &[("name", name)]));
+    }
+    fs::write(root.join(ACTIVE_PROFILE_FILE), name).map_err(|e| format!("保存当前档案失败: {}", e))
+}
+
+/// 删除档案及其全部数据 (不能删除默认档案或当前档案)
+pub fn delete_profile(root: &Path, name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    if name == DEFAULT_PROFILE {
+        return Err(tr("backend.errors.profile.delete_default", &[]));
+    }
+    if active_profile(root) == name {
+        return Err(tr("backend.errors.profile.delete_active", &[]));
+    }
+    let dir = profile_dir(root, name);
+    if !dir.is_dir() {
+        return Err(tr("backend.errors.profile.not_found", &[("name", name)]));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("删除档案失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_have_isolated_directories() {
+        let root = std::env::temp_dir().join(format!("ag-profiles-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        assert_eq!(active_profile(&root), DEFAULT_PROFILE);
+
+        create_profile(&root, "work").unwrap();
+        assert!(create_profile(&root, "work").is_err());
+        assert!(create_profile(&root, "../escape").is_err());
+        assert!(set_active_profile(&root, "missing").is_err());
+
+        set_active_profile(&root, "work").unwrap();
+        assert_eq!(active_profile(&root), "work");
+        assert_eq!(profile_dir(&root, "work"), root.join("profiles").join("work"));
+        assert_eq!(profile_dir(&root, DEFAULT_PROFILE), root);
+
+        let listed: Vec<(String, bool)> = list_profiles(&root).unwrap().into_iter().map(|p| (p.name, p.active)).collect();
+        assert_eq!(listed, vec![("default".to_string(), false), ("work".to_string(), true)]);
+
+        assert!(delete_profile(&root, "work").is_err());
+        assert!(delete_profile(&root, DEFAULT_PROFILE).is_err());
+        set_active_profile(&root, DEFAULT_PROFILE).unwrap();
+        delete_profile(&root, "work").unwrap();
+        assert!(!profile_dir(&root, "work").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
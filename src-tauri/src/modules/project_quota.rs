@@ -0,0 +1,143 @@
+// 项目配额检查
+//
+// 按账号当前使用的项目查询上游配额 (fetchAvailableModels 的 remainingFraction)，
+// 汇总每个账号剩余最少的模型，便于发现哪些账号/项目即将触顶。
+
+use serde::Serialize;
+
+use crate::models::{Account, ModelQuota, QuotaData};
+
+/// 默认的"接近上限"阈值 (剩余百分比)
+const DEFAULT_NEAR_LIMIT_PERCENT: i32 = 20;
+/// 批量检查的最大并发数
+const MAX_CONCURRENT: usize = 5;
+
+/// 单个账号 (项目) 的配额状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectQuotaStatus {
+    pub account_id: String,
+    pub email: String,
+    pub project_id: Option<String>,
+    /// 项目无权限访问 (403)
+    pub forbidden: bool,
+    pub models: Vec<ModelQuota>,
+    /// 剩余配额最少的模型及其剩余百分比
+    pub lowest_model: Option<String>,
+    pub lowest_percentage: Option<i32>,
+    /// 最低剩余百分比不高于阈值
+    pub near_limit: bool,
+    pub error: Option<String>,
+}
+
+impl ProjectQuotaStatus {
+    fn failed(account: &Account, project_id: Option<String>, error: String) -> Self {
+        Self {
+            account_id: account.id.clone(),
+            email: account.email.clone(),
+            project_id,
+            forbidden: false,
+            models: Vec::new(),
+            lowest_model: None,
+            lowest_percentage: None,
+            near_limit: false,
+            error: Some(error),
+        }
+    }
+}
+
+fn summarize(account: &Account, project_id: Option<String>, quota: QuotaData, threshold: i32) -> ProjectQuotaStatus {
+    let lowest = quota.models.iter().min_by_key(|m| m.percentage);
+    let lowest_model = lowest.map(|m| m.name.clone());
+    let lowest_percentage = lowest.map(|m| m.percentage);
+    ProjectQuotaStatus {
+        account_id: account.id.clone(),
+        email: account.email.clone(),
+        project_id,
+        forbidden: quota.is_forbidden,
+        near_limit: quota.is_forbidden || lowest_percentage.is_some_and(|p| p <= threshold),
+        lowest_model,
+        lowest_percentage,
+        models: quota.models,
+        error: None,
+    }
+}
+
+/// 查询单个账号当前项目的配额状态
+pub async fn inspect_account(account: Account, threshold: i32) -> ProjectQuotaStatus {
+    let project_id = account.project_id_override.clone().or_else(|| account.token.project_id.clone());
+    let token = match crate::modules::oauth::ensure_fresh_token(&account.token).await {
+        Ok(token) => token,
+        Err(e) => return ProjectQuotaStatus::failed(&account, project_id, e),
+    };
+
+    match crate::modules::quota::fetch_quota_with_cache(&token.access_token, &account.email, project_id.as_deref()).await {
+        Ok((quota, resolved)) => summarize(&account, resolved.or(project_id), quota, threshold),
+        Err(e) => ProjectQuotaStatus::failed(&account, project_id, e.to_string()),
+    }
+}
+
+/// 并发查询所有未禁用账号的项目配额，最接近上限的排在最前
+pub async fn inspect_all(threshold: Option<i32>) -> Result<Vec<ProjectQuotaStatus>, String> {
+    use futures::future::join_all;
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    let threshold = threshold.unwrap_or(DEFAULT_NEAR_LIMIT_PERCENT);
+    let accounts = crate::modules::account::list_accounts()?;
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+
+    let tasks: Vec<_> = accounts
+        .into_iter()
+        .filter(|account| !account.disabled)
+        .map(|account| {
+            let permit = semaphore.clone();
+            async move {
+                let _guard = permit.acquire().await.unwrap();
+                inspect_account(account, threshold).await
+            }
+        })
+        .collect();
+
+    let mut statuses = join_all(tasks).await;
+    sort_by_headroom(&mut statuses);
+    Ok(statuses)
+}
+
+/// 按剩余配额升序排序 (无权限的最前，查询失败的最后)
+fn sort_by_headroom(statuses: &mut [ProjectQuotaStatus]) {
+    statuses.sort_by_key(|s| (s.error.is_some(), !s.forbidden, s.lowest_percentage.unwrap_or(i32::MAX)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenData;
+
+    fn account(id: &str) -> Account {
+        let token = TokenData::new("at".to_string(), "1//rt".to_string(), 3600, None, None, None);
+        Account::new(id.to_string(), format!("{}@example.com", id), token)
+    }
+
+    #[test]
+    fn test_summarize_flags_accounts_near_their_ceiling() {
+        let mut quota = QuotaData::new();
+        quota.add_model("gemini-2.5-pro".to_string(), 55, String::new());
+        quota.add_model("claude-sonnet-4-5".to_string(), 12, String::new());
+
+        let low = summarize(&account("low"), Some("p1".to_string()), quota, 20);
+        assert_eq!(low.lowest_model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(low.lowest_percentage, Some(12));
+        assert!(low.near_limit);
+
+        let mut roomy_quota = QuotaData::new();
+        roomy_quota.add_model("gemini-2.5-pro".to_string(), 90, String::new());
+        let roomy = summarize(&account("roomy"), Some("p2".to_string()), roomy_quota, 20);
+        assert!(!roomy.near_limit);
+
+        let failed = ProjectQuotaStatus::failed(&account("failed"), None, "boom".to_string());
+        let mut statuses = vec![roomy, failed, low];
+        sort_by_headroom(&mut statuses);
+        let order: Vec<&str> = statuses.iter().map(|s| s.account_id.as_str()).collect();
+        assert_eq!(order, vec!["low", "roomy", "failed"]);
+    }
+}
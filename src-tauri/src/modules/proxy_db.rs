@@ -10,7 +10,14 @@ pub fn get_proxy_db_path() -> Result<PathBuf, String> {
 pub fn init_db() -> Result<(), String> {
     let db_path = get_proxy_db_path()?;
     let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
-    
+    init_log_table(&conn)?;
+    init_usage_table(&conn)?;
+
+    Ok(())
+}
+
+/// 请求日志表
+fn init_log_table(conn: &Connection) -> Result<(), String> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS request_logs (
             id TEXT PRIMARY KEY,
@@ -32,6 +39,7 @@ pub fn init_db() -> Result<(), String> {
     let _ = conn.execute("ALTER TABLE request_logs ADD COLUMN output_tokens INTEGER", []);
     let _ = conn.execute("ALTER TABLE request_logs ADD COLUMN account_email TEXT", []);
     let _ = conn.execute("ALTER TABLE request_logs ADD COLUMN mapped_model TEXT", []);
+    let _ = conn.execute("ALTER TABLE request_logs ADD COLUMN project_id TEXT", []);
 
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_timestamp ON request_logs (timestamp DESC)",
@@ -47,13 +55,432 @@ pub fn init_db() -> Result<(), String> {
     Ok(())
 }
 
+/// 按账号、按小时聚合的用量表 (不受请求日志开关影响，重启后保留)
+fn init_usage_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS account_usage (
+            account_email TEXT NOT NULL,
+            hour_bucket INTEGER NOT NULL,
+            requests INTEGER NOT NULL DEFAULT 0,
+            errors INTEGER NOT NULL DEFAULT 0,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            last_request_at INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (account_email, hour_bucket)
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    // 累计用量 (不随日志清理删除)，用于识别长期闲置或高错误率的账号
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS account_usage_totals (
+            account_email TEXT PRIMARY KEY,
+            requests INTEGER NOT NULL DEFAULT 0,
+            errors INTEGER NOT NULL DEFAULT 0,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            first_request_at INTEGER NOT NULL DEFAULT 0,
+            last_request_at INTEGER NOT NULL DEFAULT 0,
+            last_error_at INTEGER
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    // 按账号、错误类别、小时聚合的错误数
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS account_errors (
+            account_email TEXT NOT NULL,
+            hour_bucket INTEGER NOT NULL,
+            error_class TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            last_error_at INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (account_email, hour_bucket, error_class)
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// 429 限流 / 配额耗尽
+    RateLimit,
+    /// 401 / 403、Token 失效
+    Auth,
+    /// 内容安全拦截
+    Safety,
+    /// 网络错误、网关错误与超时
+    Network,
+    /// 其他 5xx
+    Server,
+    /// 其他 4xx
+    Client,
+}
+
+impl ErrorClass {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::RateLimit => "rate_limit",
+            ErrorClass::Auth => "auth",
+            ErrorClass::Safety => "safety",
+            ErrorClass::Network => "network",
+            ErrorClass::Server => "server",
+            ErrorClass::Client => "client",
+        }
+    }
+
+    /// 根据状态码与错误内容分类，成功的请求返回 None
+    pub fn classify(status: u16, error: Option<&str>) -> Option<Self> {
+        if status < 400 {
+            return None;
+        }
+        let error = error.unwrap_or("");
+        let mentions = |needles: &[&str]| needles.iter().any(|n| error.contains(n));
+        Some(if status == 429 || mentions(&["RESOURCE_EXHAUSTED"]) {
+            ErrorClass::RateLimit
+        } else if mentions(&["SAFETY", "PROHIBITED_CONTENT", "blockReason", "RECITATION", "BLOCKLIST"]) {
+            ErrorClass::Safety
+        } else if status == 401 || status == 403 || mentions(&["UNAUTHENTICATED", "PERMISSION_DENIED", "invalid_grant"]) {
+            ErrorClass::Auth
+        } else if status == 502
+            || status == 504
+            || mentions(&["timeout", "timed out", "error sending request", "connection", "上游请求失败"])
+        {
+            ErrorClass::Network
+        } else if status >= 500 {
+            ErrorClass::Server
+        } else {
+            ErrorClass::Client
+        })
+    }
+}
+
+/// 单个账号在统计窗口内的错误分类统计
+#[derive(Debug, Clone, Default, serde::Serialize, PartialEq)]
+pub struct AccountErrorBreakdown {
+    pub account_email: String,
+    /// 窗口内请求数 (用于计算错误率)
+    pub requests: u64,
+    pub errors: u64,
+    pub rate_limit: u64,
+    pub auth: u64,
+    pub safety: u64,
+    pub network: u64,
+    pub server: u64,
+    pub client: u64,
+    /// 最近一次错误时间 (Unix 秒)
+    pub last_error_at: Option<i64>,
+}
+
+/// 单个账号在统计窗口内的用量
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AccountUsage {
+    pub account_email: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 最近一次请求时间 (Unix 秒)
+    pub last_request_at: i64,
+}
+
+/// 账号用量统计 (统计窗口 + 累计)
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct AccountUsageStats {
+    pub account_id: String,
+    pub account_email: String,
+    pub window_requests: u64,
+    pub window_errors: u64,
+    pub window_input_tokens: u64,
+    pub window_output_tokens: u64,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    /// 首次/最近一次请求时间 (Unix 秒)，从未使用过为 None
+    pub first_request_at: Option<i64>,
+    pub last_request_at: Option<i64>,
+    pub last_error_at: Option<i64>,
+}
+
+/// account_usage_totals 中的一行
+#[derive(Debug, Clone, PartialEq)]
+struct UsageTotals {
+    account_email: String,
+    requests: u64,
+    errors: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    first_request_at: i64,
+    last_request_at: i64,
+    last_error_at: Option<i64>,
+}
+
+/// 累加一次请求的用量 (timestamp 为 Unix 秒)
+pub fn record_account_usage(
+    account_email: &str,
+    timestamp: i64,
+    is_error: bool,
+    input_tokens: u32,
+    output_tokens: u32,
+) -> Result<(), String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    upsert_account_usage(&conn, account_email, timestamp, is_error, input_tokens, output_tokens)
+}
+
+fn upsert_account_usage(
+    conn: &Connection,
+    account_email: &str,
+    timestamp: i64,
+    is_error: bool,
+    input_tokens: u32,
+    output_tokens: u32,
+) -> Result<(), String> {
+    let hour_bucket = timestamp - timestamp.rem_euclid(3600);
+    conn.execute(
+        "INSERT INTO account_usage (account_email, hour_bucket, requests, errors, input_tokens, output_tokens, last_request_at)
+         VALUES (?1, ?2, 1, ?3, ?4, ?5, ?6)
+         ON CONFLICT(account_email, hour_bucket) DO UPDATE SET
+            requests = requests + 1,
+            errors = errors + excluded.errors,
+            input_tokens = input_tokens + excluded.input_tokens,
+            output_tokens = output_tokens + excluded.output_tokens,
+            last_request_at = MAX(last_request_at, excluded.last_request_at)",
+        params![account_email, hour_bucket, is_error as i64, input_tokens, output_tokens, timestamp],
+    ).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO account_usage_totals (account_email, requests, errors, input_tokens, output_tokens, first_request_at, last_request_at, last_error_at)
+         VALUES (?1, 1, ?2, ?3, ?4, ?5, ?5, CASE WHEN ?2 = 1 THEN ?5 END)
+         ON CONFLICT(account_email) DO UPDATE SET
+            requests = requests + 1,
+            errors = errors + excluded.errors,
+            input_tokens = input_tokens + excluded.input_tokens,
+            output_tokens = output_tokens + excluded.output_tokens,
+            first_request_at = MIN(first_request_at, excluded.first_request_at),
+            last_request_at = MAX(last_request_at, excluded.last_request_at),
+            last_error_at = COALESCE(MAX(last_error_at, excluded.last_error_at), last_error_at, excluded.last_error_at)",
+        params![account_email, is_error as i64, input_tokens, output_tokens, timestamp],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 记录一次失败请求的错误类别 (timestamp 为 Unix 秒)
+pub fn record_account_error(account_email: &str, timestamp: i64, class: ErrorClass) -> Result<(), String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    upsert_account_error(&conn, account_email, timestamp, class)
+}
+
+fn upsert_account_error(conn: &Connection, account_email: &str, timestamp: i64, class: ErrorClass) -> Result<(), String> {
+    let hour_bucket = timestamp - timestamp.rem_euclid(3600);
+    conn.execute(
+        "INSERT INTO account_errors (account_email, hour_bucket, error_class, count, last_error_at)
+         VALUES (?1, ?2, ?3, 1, ?4)
+         ON CONFLICT(account_email, hour_bucket, error_class) DO UPDATE SET
+            count = count + 1,
+            last_error_at = MAX(last_error_at, excluded.last_error_at)",
+        params![account_email, hour_bucket, class.as_str(), timestamp],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn query_error_breakdown(conn: &Connection, since: i64) -> Result<Vec<AccountErrorBreakdown>, String> {
+    let since_bucket = since - since.rem_euclid(3600);
+    let mut stmt = conn.prepare(
+        "SELECT account_email, error_class, SUM(count), MAX(last_error_at)
+         FROM account_errors
+         WHERE hour_bucket >= ?1
+         GROUP BY account_email, error_class"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([since_bucket], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u64>(2)?, row.get::<_, i64>(3)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut breakdown: Vec<AccountErrorBreakdown> = Vec::new();
+    for row in rows {
+        let (email, class, count, last_error_at) = row.map_err(|e| e.to_string())?;
+        let idx = match breakdown.iter().position(|b| b.account_email == email) {
+            Some(idx) => idx,
+            None => {
+                breakdown.push(AccountErrorBreakdown { account_email: email, ..Default::default() });
+                breakdown.len() - 1
+            }
+        };
+        let entry = &mut breakdown[idx];
+        entry.errors += count;
+        entry.last_error_at = entry.last_error_at.max(Some(last_error_at));
+        match class.as_str() {
+            "rate_limit" => entry.rate_limit += count,
+            "auth" => entry.auth += count,
+            "safety" => entry.safety += count,
+            "network" => entry.network += count,
+            "server" => entry.server += count,
+            _ => entry.client += count,
+        }
+    }
+
+    for usage in query_account_usage(conn, since)? {
+        if let Some(entry) = breakdown.iter_mut().find(|b| b.account_email == usage.account_email) {
+            entry.requests = usage.requests;
+        }
+    }
+    breakdown.sort_by(|a, b| b.errors.cmp(&a.errors).then(a.account_email.cmp(&b.account_email)));
+    Ok(breakdown)
+}
+
+/// 最近 window_hours 小时内各账号按类别的错误数 (只返回出现过错误的账号，错误多的在前)
+pub fn get_account_error_breakdown(window_hours: u32) -> Result<Vec<AccountErrorBreakdown>, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    init_usage_table(&conn)?;
+    let since = chrono::Utc::now().timestamp() - (window_hours as i64) * 3600;
+    query_error_breakdown(&conn, since)
+}
+
+/// 获取最近 window_hours 小时内每个账号的用量
+pub fn get_account_usage(window_hours: u32) -> Result<Vec<AccountUsage>, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    init_usage_table(&conn)?;
+    let since = chrono::Utc::now().timestamp() - (window_hours as i64) * 3600;
+    query_account_usage(&conn, since)
+}
+
+fn query_account_usage(conn: &Connection, since: i64) -> Result<Vec<AccountUsage>, String> {
+    // 包含 since 所在的小时桶
+    let since_bucket = since - since.rem_euclid(3600);
+    let mut stmt = conn.prepare(
+        "SELECT account_email, SUM(requests), SUM(errors), SUM(input_tokens), SUM(output_tokens), MAX(last_request_at)
+         FROM account_usage
+         WHERE hour_bucket >= ?1
+         GROUP BY account_email
+         ORDER BY SUM(requests) DESC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([since_bucket], |row| {
+        Ok(AccountUsage {
+            account_email: row.get(0)?,
+            requests: row.get(1)?,
+            errors: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            last_request_at: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut usage = Vec::new();
+    for row in rows {
+        usage.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(usage)
+}
+
+fn query_usage_totals(conn: &Connection) -> Result<Vec<UsageTotals>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT account_email, requests, errors, input_tokens, output_tokens, first_request_at, last_request_at, last_error_at
+         FROM account_usage_totals"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok(UsageTotals {
+            account_email: row.get(0)?,
+            requests: row.get(1)?,
+            errors: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            first_request_at: row.get(5)?,
+            last_request_at: row.get(6)?,
+            last_error_at: row.get(7)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut totals = Vec::new();
+    for row in rows {
+        totals.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(totals)
+}
+
+/// 按账号合并窗口用量与累计用量，未产生过请求的账号也会返回 (计数为 0)
+fn merge_usage_stats(
+    accounts: &[(String, String)],
+    window: &[AccountUsage],
+    totals: &[UsageTotals],
+) -> Vec<AccountUsageStats> {
+    let mut stats: Vec<AccountUsageStats> = accounts
+        .iter()
+        .map(|(id, email)| {
+            let mut s = AccountUsageStats {
+                account_id: id.clone(),
+                account_email: email.clone(),
+                ..Default::default()
+            };
+            if let Some(w) = window.iter().find(|w| &w.account_email == email) {
+                s.window_requests = w.requests;
+                s.window_errors = w.errors;
+                s.window_input_tokens = w.input_tokens;
+                s.window_output_tokens = w.output_tokens;
+            }
+            if let Some(t) = totals.iter().find(|t| &t.account_email == email) {
+                s.total_requests = t.requests;
+                s.total_errors = t.errors;
+                s.total_input_tokens = t.input_tokens;
+                s.total_output_tokens = t.output_tokens;
+                s.first_request_at = Some(t.first_request_at);
+                s.last_request_at = Some(t.last_request_at);
+                s.last_error_at = t.last_error_at;
+            }
+            s
+        })
+        .collect();
+
+    stats.sort_by(|a, b| {
+        b.window_requests
+            .cmp(&a.window_requests)
+            .then(b.total_requests.cmp(&a.total_requests))
+            .then(a.account_email.cmp(&b.account_email))
+    });
+    stats
+}
+
+/// 各账号最近一次经反代发出请求的时间 (email -> Unix 秒)
+pub fn get_last_request_times() -> Result<std::collections::HashMap<String, i64>, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    init_usage_table(&conn)?;
+    Ok(query_usage_totals(&conn)?
+        .into_iter()
+        .map(|t| (t.account_email, t.last_request_at))
+        .collect())
+}
+
+/// 获取所有账号的用量统计 (统计窗口 window_hours 小时 + 累计)
+pub fn get_account_usage_stats(window_hours: u32) -> Result<Vec<AccountUsageStats>, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    init_usage_table(&conn)?;
+
+    let since = chrono::Utc::now().timestamp() - (window_hours as i64) * 3600;
+    let window = query_account_usage(&conn, since)?;
+    let totals = query_usage_totals(&conn)?;
+    let accounts: Vec<(String, String)> = crate::modules::account::list_accounts()?
+        .into_iter()
+        .map(|a| (a.id, a.email))
+        .collect();
+
+    Ok(merge_usage_stats(&accounts, &window, &totals))
+}
+
 pub fn save_log(log: &ProxyRequestLog) -> Result<(), String> {
     let db_path = get_proxy_db_path()?;
     let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
 
     conn.execute(
-        "INSERT INTO request_logs (id, timestamp, method, url, status, duration, model, error, request_body, response_body, input_tokens, output_tokens, account_email, mapped_model)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        "INSERT INTO request_logs (id, timestamp, method, url, status, duration, model, error, request_body, response_body, input_tokens, output_tokens, account_email, mapped_model, project_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
         params![
             log.id,
             log.timestamp,
@@ -69,6 +496,7 @@ pub fn save_log(log: &ProxyRequestLog) -> Result<(), String> {
             log.output_tokens,
             log.account_email,
             log.mapped_model,
+            log.project_id,
         ],
     ).map_err(|e| e.to_string())?;
 
@@ -83,7 +511,7 @@ pub fn get_logs_summary(limit: usize, offset: usize) -> Result<Vec<ProxyRequestL
     let mut stmt = conn.prepare(
         "SELECT id, timestamp, method, url, status, duration, model, error, 
                 NULL as request_body, NULL as response_body,
-                input_tokens, output_tokens, account_email, mapped_model
+                input_tokens, output_tokens, account_email, mapped_model, project_id
          FROM request_logs 
          ORDER BY timestamp DESC 
          LIMIT ?1 OFFSET ?2"
@@ -100,6 +528,7 @@ pub fn get_logs_summary(limit: usize, offset: usize) -> Result<Vec<ProxyRequestL
             model: row.get(6)?,
             mapped_model: row.get(13).unwrap_or(None),
             account_email: row.get(12).unwrap_or(None),
+            project_id: row.get(14).unwrap_or(None),
             error: row.get(7)?,
             request_body: None,  // Don't query large fields for list view
             response_body: None, // Don't query large fields for list view
@@ -150,29 +579,56 @@ pub fn get_log_detail(log_id: &str) -> Result<ProxyRequestLog, String> {
     let mut stmt = conn.prepare(
         "SELECT id, timestamp, method, url, status, duration, model, error, 
                 request_body, response_body, input_tokens, output_tokens, 
-                account_email, mapped_model
+                account_email, mapped_model, project_id
          FROM request_logs 
          WHERE id = ?1"
     ).map_err(|e| e.to_string())?;
 
-    stmt.query_row([log_id], |row| {
-        Ok(ProxyRequestLog {
-            id: row.get(0)?,
-            timestamp: row.get(1)?,
-            method: row.get(2)?,
-            url: row.get(3)?,
-            status: row.get(4)?,
-            duration: row.get(5)?,
-            model: row.get(6)?,
-            mapped_model: row.get(13).unwrap_or(None),
-            account_email: row.get(12).unwrap_or(None),
-            error: row.get(7)?,
-            request_body: row.get(8).unwrap_or(None),
-            response_body: row.get(9).unwrap_or(None),
-            input_tokens: row.get(10).unwrap_or(None),
-            output_tokens: row.get(11).unwrap_or(None),
-        })
-    }).map_err(|e| e.to_string())
+    stmt.query_row([log_id], log_from_row).map_err(|e| e.to_string())
+}
+
+/// 按完整日志查询的列顺序解析一行 (含请求 / 响应内容)
+fn log_from_row(row: &rusqlite::Row) -> rusqlite::Result<ProxyRequestLog> {
+    Ok(ProxyRequestLog {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        method: row.get(2)?,
+        url: row.get(3)?,
+        status: row.get(4)?,
+        duration: row.get(5)?,
+        model: row.get(6)?,
+        mapped_model: row.get(13).unwrap_or(None),
+        account_email: row.get(12).unwrap_or(None),
+        project_id: row.get(14).unwrap_or(None),
+        error: row.get(7)?,
+        request_body: row.get(8).unwrap_or(None),
+        response_body: row.get(9).unwrap_or(None),
+        input_tokens: row.get(10).unwrap_or(None),
+        output_tokens: row.get(11).unwrap_or(None),
+    })
+}
+
+/// 获取时间范围内 (Unix 毫秒，含两端) 的完整日志，按时间从早到晚排列
+pub fn get_logs_in_range(since_ms: i64, until_ms: i64, limit: usize) -> Result<Vec<ProxyRequestLog>, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, method, url, status, duration, model, error,
+                request_body, response_body, input_tokens, output_tokens,
+                account_email, mapped_model, project_id
+         FROM request_logs
+         WHERE timestamp >= ?1 AND timestamp <= ?2
+         ORDER BY timestamp ASC
+         LIMIT ?3"
+    ).map_err(|e| e.to_string())?;
+
+    let logs = stmt
+        .query_map(params![since_ms, until_ms, limit as i64], log_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(logs)
 }
 
 /// Cleanup old logs (keep last N days)
@@ -186,6 +642,11 @@ pub fn cleanup_old_logs(days: i64) -> Result<usize, String> {
         "DELETE FROM request_logs WHERE timestamp < ?1",
         [cutoff_timestamp],
     ).map_err(|e| e.to_string())?;
+
+    let _ = conn.execute(
+        "DELETE FROM account_usage WHERE hour_bucket < ?1",
+        [cutoff_timestamp],
+    );
     
     // Execute VACUUM to reclaim disk space
     conn.execute("VACUUM", []).map_err(|e| e.to_string())?;
@@ -211,9 +672,182 @@ pub fn limit_max_logs(max_count: usize) -> Result<usize, String> {
     Ok(deleted)
 }
 
+/// 单条日志中正文、错误信息与 URL 的字节数
+const LOG_ROW_BYTES: &str = "COALESCE(LENGTH(CAST(request_body AS BLOB)), 0)
+    + COALESCE(LENGTH(CAST(response_body AS BLOB)), 0)
+    + COALESCE(LENGTH(CAST(error AS BLOB)), 0)
+    + COALESCE(LENGTH(CAST(url AS BLOB)), 0)";
+
+/// 数据库已使用的字节数 (不含空闲页)
+fn used_bytes(conn: &Connection) -> Result<u64, String> {
+    let pragma = |name: &str| {
+        conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())
+    };
+    Ok(((pragma("page_count")? - pragma("freelist_count")?) * pragma("page_size")?).max(0) as u64)
+}
+
+/// 数据库超出容量上限时从最旧的日志开始删除，返回删除条数
+///
+/// 不执行 VACUUM: 删除后的空闲页会被新日志复用，文件大小随之稳定
+pub fn trim_logs_to_size(max_bytes: u64) -> Result<usize, String> {
+    let db_path = get_proxy_db_path()?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    trim_logs(&conn, max_bytes)
+}
+
+fn trim_logs(conn: &Connection, max_bytes: u64) -> Result<usize, String> {
+    let excess = used_bytes(conn)?.saturating_sub(max_bytes);
+    if excess == 0 {
+        return Ok(0);
+    }
+
+    // 按时间从旧到新的累计字节数首次达到超出量的时间点即为删除截止点 (不够时全部删除)
+    conn.execute(
+        &format!(
+            "DELETE FROM request_logs WHERE timestamp <= (
+                SELECT COALESCE(MIN(CASE WHEN freed >= ?1 THEN timestamp END), MAX(timestamp))
+                FROM (SELECT timestamp, SUM({}) OVER (ORDER BY timestamp ROWS UNBOUNDED PRECEDING) AS freed
+                      FROM request_logs)
+            )",
+            LOG_ROW_BYTES
+        ),
+        [excess.min(i64::MAX as u64) as i64],
+    )
+    .map_err(|e| e.to_string())
+}
+
 pub fn clear_logs() -> Result<(), String> {
     let db_path = get_proxy_db_path()?;
     let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM request_logs", []).map_err(|e| e.to_string())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_usage_aggregates_by_account_and_window() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_usage_table(&conn).unwrap();
+
+        let now = 1_700_000_000;
+        upsert_account_usage(&conn, "a@example.com", now, false, 100, 20).unwrap();
+        upsert_account_usage(&conn, "a@example.com", now + 10, true, 0, 0).unwrap();
+        upsert_account_usage(&conn, "a@example.com", now - 7200, false, 50, 5).unwrap();
+        upsert_account_usage(&conn, "b@example.com", now, false, 1, 1).unwrap();
+
+        let usage = query_account_usage(&conn, now - 60).unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0], AccountUsage {
+            account_email: "a@example.com".to_string(),
+            requests: 2,
+            errors: 1,
+            input_tokens: 100,
+            output_tokens: 20,
+            last_request_at: now + 10,
+        });
+
+        let usage = query_account_usage(&conn, now - 3 * 3600).unwrap();
+        assert_eq!(usage[0].requests, 3);
+        assert_eq!(usage[0].input_tokens, 150);
+    }
+
+    #[test]
+    fn test_error_classes_and_breakdown() {
+        assert_eq!(ErrorClass::classify(200, None), None);
+        assert_eq!(ErrorClass::classify(429, None), Some(ErrorClass::RateLimit));
+        assert_eq!(ErrorClass::classify(400, Some(r#"{"finishReason":"SAFETY"}"#)), Some(ErrorClass::Safety));
+        assert_eq!(ErrorClass::classify(401, None), Some(ErrorClass::Auth));
+        assert_eq!(ErrorClass::classify(500, Some("error sending request for url")), Some(ErrorClass::Network));
+        assert_eq!(ErrorClass::classify(503, Some("overloaded")), Some(ErrorClass::Server));
+        assert_eq!(ErrorClass::classify(400, Some("invalid argument")), Some(ErrorClass::Client));
+
+        let conn = Connection::open_in_memory().unwrap();
+        init_usage_table(&conn).unwrap();
+        let now = 1_700_000_000;
+        for _ in 0..3 {
+            upsert_account_usage(&conn, "a@example.com", now, true, 0, 0).unwrap();
+            upsert_account_error(&conn, "a@example.com", now, ErrorClass::RateLimit).unwrap();
+        }
+        upsert_account_usage(&conn, "a@example.com", now, false, 10, 1).unwrap();
+        upsert_account_error(&conn, "a@example.com", now + 5, ErrorClass::Auth).unwrap();
+        upsert_account_error(&conn, "b@example.com", now, ErrorClass::Network).unwrap();
+        upsert_account_error(&conn, "a@example.com", now - 86400, ErrorClass::Safety).unwrap();
+
+        let breakdown = query_error_breakdown(&conn, now - 60).unwrap();
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(breakdown[0], AccountErrorBreakdown {
+            account_email: "a@example.com".to_string(),
+            requests: 4,
+            errors: 4,
+            rate_limit: 3,
+            auth: 1,
+            last_error_at: Some(now + 5),
+            ..Default::default()
+        });
+        assert_eq!((breakdown[1].network, breakdown[1].requests), (1, 0));
+    }
+
+    #[test]
+    fn test_trim_logs_deletes_oldest_until_under_limit() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_log_table(&conn).unwrap();
+        for (i, body) in ["a", "b", "c"].iter().map(|c| c.repeat(100_000)).enumerate() {
+            conn.execute(
+                "INSERT INTO request_logs (id, timestamp, url, request_body) VALUES (?1, ?2, '', ?3)",
+                params![format!("log-{}", i), 1_700_000_000_000i64 + i as i64, body],
+            )
+            .unwrap();
+        }
+
+        let used = used_bytes(&conn).unwrap();
+        assert!(used > 300_000);
+        assert_eq!(trim_logs(&conn, used).unwrap(), 0);
+        assert_eq!(trim_logs(&conn, used - 150_000).unwrap(), 2);
+        let remaining: String = conn.query_row("SELECT id FROM request_logs", [], |row| row.get(0)).unwrap();
+        assert_eq!(remaining, "log-2");
+    }
+
+    #[test]
+    fn test_usage_stats_include_totals_and_idle_accounts() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_usage_table(&conn).unwrap();
+
+        let now = 1_700_000_000;
+        upsert_account_usage(&conn, "a@example.com", now - 10 * 86400, true, 10, 1).unwrap();
+        upsert_account_usage(&conn, "a@example.com", now, false, 100, 20).unwrap();
+        upsert_account_usage(&conn, "gone@example.com", now, false, 1, 1).unwrap();
+
+        let window = query_account_usage(&conn, now - 3600).unwrap();
+        let totals = query_usage_totals(&conn).unwrap();
+        let accounts = vec![
+            ("id-b".to_string(), "b@example.com".to_string()),
+            ("id-a".to_string(), "a@example.com".to_string()),
+        ];
+        let stats = merge_usage_stats(&accounts, &window, &totals);
+
+        // 已删除的账号不返回，从未使用过的账号排在后面
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0], AccountUsageStats {
+            account_id: "id-a".to_string(),
+            account_email: "a@example.com".to_string(),
+            window_requests: 1,
+            window_errors: 0,
+            window_input_tokens: 100,
+            window_output_tokens: 20,
+            total_requests: 2,
+            total_errors: 1,
+            total_input_tokens: 110,
+            total_output_tokens: 21,
+            first_request_at: Some(now - 10 * 86400),
+            last_request_at: Some(now),
+            last_error_at: Some(now - 10 * 86400),
+        });
+        assert_eq!(stats[1].account_id, "id-b");
+        assert_eq!(stats[1].last_request_at, None);
+        assert_eq!(stats[1].total_requests, 0);
+    }
+}
@@ -55,17 +55,17 @@ struct Tier {
 
 /// 创建配置好的 HTTP Client
 fn create_client() -> reqwest::Client {
-    crate::utils::http::create_client(15)
+    crate::utils::http::create_upstream_client(15)
 }
 
 fn create_warmup_client() -> reqwest::Client {
-    crate::utils::http::create_client(60) // 60 秒超时
+    crate::utils::http::create_upstream_client(60) // 60 秒超时
 }
 
 const CLOUD_CODE_BASE_URL: &str = "https://cloudcode-pa.googleapis.com";
 
 /// 获取项目 ID 和订阅类型
-async fn fetch_project_id(access_token: &str, email: &str) -> (Option<String>, Option<String>) {
+pub async fn fetch_project_id(access_token: &str, email: &str) -> (Option<String>, Option<String>) {
     let client = create_client();
     let meta = json!({"metadata": {"ideType": "ANTIGRAVITY"}});
 
@@ -153,14 +153,12 @@ pub async fn fetch_quota_with_cache(
         {
             Ok(response) => {
                 // 将 HTTP 错误状态转换为 AppError
-                if let Err(_) = response.error_for_status_ref() {
+                if response.error_for_status_ref().is_err() {
                     let status = response.status();
                     
                     // ✅ 特殊处理 403 Forbidden - 直接返回,不重试
                     if status == reqwest::StatusCode::FORBIDDEN {
-                        crate::modules::logger::log_warn(&format!(
-                            "账号无权限 (403 Forbidden),标记为 forbidden 状态"
-                        ));
+                        crate::modules::logger::log_warn("账号无权限 (403 Forbidden),标记为 forbidden 状态");
                         let mut q = QuotaData::new();
                         q.is_forbidden = true;
                         q.subscription_tier = subscription_tier.clone();
@@ -183,7 +181,7 @@ pub async fn fetch_quota_with_cache(
                 let quota_response: QuotaResponse = response
                     .json()
                     .await
-                    .map_err(|e| AppError::Network(e))?;
+                    .map_err(AppError::Network)?;
                 
                 let mut quota_data = QuotaData::new();
                 
@@ -260,8 +258,11 @@ pub async fn get_valid_token_for_warmup(account: &crate::models::account::Accoun
         }
     }
     
-    // 获取 project_id
-    let (project_id, _) = fetch_project_id(&account.token.access_token, &account.email).await;
+    // 获取 project_id (手动指定时跳过解析)
+    let project_id = match account.project_id_override.clone() {
+        Some(pid) => Some(pid),
+        None => fetch_project_id(&account.token.access_token, &account.email).await.0,
+    };
     let final_pid = project_id.unwrap_or_else(|| "bamboo-precept-lgxtn".to_string());
     
     Ok((account.token.access_token, final_pid))
@@ -425,17 +426,14 @@ pub async fn warm_up_all_accounts() -> Result<String, String> {
                     }
                     
                     for handle in handles {
-                        match handle.await {
-                            Ok((true, email, model)) => {
-                                success += 1;
-                                let history_key = format!("{}:{}:100", email, model);
-                                crate::modules::scheduler::record_warmup_history(&history_key, now_ts);
-                            }
-                            _ => {}
+                        if let Ok((true, email, model)) = handle.await {
+                            success += 1;
+                            let history_key = format!("{}:{}:100", email, model);
+                            crate::modules::scheduler::record_warmup_history(&history_key, now_ts);
                         }
                     }
                     
-                    if batch_idx < (warmup_items.len() + batch_size - 1) / batch_size - 1 {
+                    if batch_idx < warmup_items.len().div_ceil(batch_size) - 1 {
                         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
                     }
                 }
@@ -2,7 +2,9 @@ use chrono::Utc;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::sync::Mutex;
+#[cfg(feature = "gui")]
 use tokio::time::{self, Duration};
+#[cfg(feature = "gui")]
 use tauri::Manager;
 use crate::modules::{config, logger, quota, account};
 use crate::models::Account;
@@ -52,8 +54,9 @@ pub fn check_cooldown(key: &str, cooldown_seconds: i64) -> bool {
     }
 }
 
-pub fn start_scheduler(app_handle: tauri::AppHandle) {
-    tauri::async_runtime::spawn(async move {
+#[cfg(feature = "gui")]
+pub fn start_scheduler(app_handle: crate::utils::app::AppHandle) {
+    crate::utils::app::spawn(async move {
         logger::log_info("Smart Warmup Scheduler started. Monitoring quota at 100%...");
         
         // 每 10 分钟扫描一次
@@ -70,6 +73,10 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
             if !app_config.scheduled_warmup.enabled {
                 continue;
             }
+
+            if crate::modules::power::should_pause_background().await {
+                continue;
+            }
             
             // 获取所有账号（不再过滤等级）
             let Ok(accounts) = account::list_accounts() else {
@@ -233,7 +240,7 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
                     // 刷新配额，同步到前端
                     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
                     let state = handle_for_warmup.state::<crate::commands::proxy::ProxyServiceState>();
-                    let _ = crate::commands::refresh_all_quotas(state).await;
+                    let _ = crate::commands::refresh_all_quotas(handle_for_warmup.clone(), state).await;
                 });
             } else if skipped_cooldown > 0 {
                 logger::log_info(&format!(
@@ -249,7 +256,7 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
             tokio::spawn(async move {
                 tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 let state = handle_inner.state::<crate::commands::proxy::ProxyServiceState>();
-                let _ = crate::commands::refresh_all_quotas(state).await;
+                let _ = crate::commands::refresh_all_quotas(handle_inner.clone(), state).await;
                 logger::log_info("[Scheduler] Quota data synced to frontend");
             });
 
@@ -0,0 +1,232 @@
+// StatsD / DogStatsD 指标推送
+//
+// 与 Prometheus 的 `/metrics` 拉取互补：每个反代请求结束时在内存中累计计数器与计时器，
+// 按配置的间隔通过 UDP 推送到 StatsD 服务。DogStatsD 模式下模型、状态码作为 tag 发送；
+// 普通 StatsD 不支持 tag，改为追加到指标名中 (如 `antigravity.proxy.requests.gemini-2_5-pro.200`)。
+// 默认关闭；UDP 发送失败只记录日志，不影响请求。
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use crate::models::config::StatsdConfig;
+use crate::proxy::monitor::ProxyRequestLog;
+
+/// 单个 UDP 包的最大负载 (保证以太网 MTU 内不分片)
+const MAX_PACKET_BYTES: usize = 1432;
+/// 每个刷新周期最多缓存的计时器样本数，超出后丢弃
+const MAX_TIMER_SAMPLES: usize = 10_000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CONFIG: Lazy<RwLock<StatsdConfig>> = Lazy::new(|| RwLock::new(StatsdConfig::default()));
+static AGGREGATOR: Lazy<Mutex<Aggregator>> = Lazy::new(|| Mutex::new(Aggregator::default()));
+
+type MetricKey = (&'static str, Vec<(&'static str, String)>);
+
+/// 一个刷新周期内累计的指标
+#[derive(Debug, Default)]
+struct Aggregator {
+    counters: HashMap<MetricKey, u64>,
+    timers: Vec<(MetricKey, u64)>,
+}
+
+impl Aggregator {
+    fn count(&mut self, name: &'static str, tags: &[(&'static str, String)], value: u64) {
+        if value > 0 {
+            *self.counters.entry((name, tags.to_vec())).or_insert(0) += value;
+        }
+    }
+
+    fn time(&mut self, name: &'static str, tags: &[(&'static str, String)], ms: u64) {
+        if self.timers.len() < MAX_TIMER_SAMPLES {
+            self.timers.push(((name, tags.to_vec()), ms));
+        }
+    }
+
+    fn observe(&mut self, log: &ProxyRequestLog, total_ms: u64) {
+        let model = log.mapped_model.as_deref().or(log.model.as_deref()).unwrap_or("unknown");
+        let tags = [("model", model.to_string()), ("status", log.status.to_string())];
+        self.count("proxy.requests", &tags, 1);
+        if log.status >= 400 {
+            self.count("proxy.errors", &tags, 1);
+        }
+        let model_tag = &tags[..1];
+        self.count("proxy.tokens.input", model_tag, log.input_tokens.unwrap_or(0) as u64);
+        self.count("proxy.tokens.output", model_tag, log.output_tokens.unwrap_or(0) as u64);
+        self.time("proxy.latency.ttfb", model_tag, log.duration);
+        self.time("proxy.latency.total", model_tag, total_ms);
+    }
+
+    /// 取出并编码为 StatsD 行协议
+    fn drain(&mut self, config: &StatsdConfig) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .counters
+            .drain()
+            .map(|(key, value)| format!("{}:{}|c{}", metric_name(config, &key), value, tag_suffix(config, &key)))
+            .collect();
+        lines.sort();
+        lines.extend(
+            self.timers
+                .drain(..)
+                .map(|(key, ms)| format!("{}:{}|ms{}", metric_name(config, &key), ms, tag_suffix(config, &key))),
+        );
+        lines
+    }
+}
+
+/// StatsD 名称与 tag 中不允许出现的字符替换为 `_`
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn metric_name(config: &StatsdConfig, (name, tags): &MetricKey) -> String {
+    let prefix = config.prefix.trim().trim_end_matches('.');
+    let mut full = if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+    if !config.dogstatsd {
+        for (_, value) in tags {
+            full.push('.');
+            full.push_str(&sanitize(value));
+        }
+    }
+    full
+}
+
+fn tag_suffix(config: &StatsdConfig, (_, tags): &MetricKey) -> String {
+    if !config.dogstatsd || tags.is_empty() {
+        return String::new();
+    }
+    let tags: Vec<String> = tags.iter().map(|(k, v)| format!("{}:{}", k, sanitize(v))).collect();
+    format!("|#{}", tags.join(","))
+}
+
+/// 按最大包长把多行合并为若干 UDP 包
+fn pack(lines: &[String]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > MAX_PACKET_BYTES {
+            packets.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        packets.push(current);
+    }
+    packets
+}
+
+/// 更新推送配置 (启动时与保存设置时调用)
+pub fn set_config(config: StatsdConfig) {
+    let enabled = config.enabled && !config.address.trim().is_empty();
+    *CONFIG.write().unwrap() = config;
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        *AGGREGATOR.lock().unwrap() = Aggregator::default();
+    }
+}
+
+/// 记录一个已完成的反代请求 (未开启时直接返回)
+pub fn record_request(log: &ProxyRequestLog, total_ms: u64) {
+    if ENABLED.load(Ordering::Relaxed) {
+        AGGREGATOR.lock().unwrap().observe(log, total_ms);
+    }
+}
+
+async fn flush(config: &StatsdConfig) -> Result<usize, String> {
+    let lines = AGGREGATOR.lock().unwrap().drain(config);
+    if lines.is_empty() {
+        return Ok(0);
+    }
+    let target = tokio::net::lookup_host(config.address.trim())
+        .await
+        .map_err(|e| format!("解析 StatsD 地址失败: {}", e))?
+        .next()
+        .ok_or_else(|| format!("无法解析 StatsD 地址: {}", config.address))?;
+    let bind = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+    let socket = tokio::net::UdpSocket::bind(bind).await.map_err(|e| e.to_string())?;
+    for packet in pack(&lines) {
+        socket.send_to(packet.as_bytes(), target).await.map_err(|e| e.to_string())?;
+    }
+    Ok(lines.len())
+}
+
+/// 启动后台推送任务
+pub fn start_exporter() {
+    crate::utils::app::spawn(async move {
+        loop {
+            let interval = CONFIG.read().unwrap().flush_interval_secs.max(1);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if !ENABLED.load(Ordering::Relaxed) {
+                continue;
+            }
+            let config = CONFIG.read().unwrap().clone();
+            if let Err(e) = flush(&config).await {
+                tracing::warn!("[StatsD] Failed to push metrics: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(model: &str, status: u16) -> ProxyRequestLog {
+        ProxyRequestLog {
+            id: "id".to_string(),
+            timestamp: 0,
+            method: "POST".to_string(),
+            url: "/v1/chat/completions".to_string(),
+            status,
+            duration: 120,
+            model: Some(model.to_string()),
+            mapped_model: None,
+            account_email: Some("a@example.com".to_string()),
+            project_id: None,
+            error: None,
+            request_body: None,
+            response_body: None,
+            input_tokens: Some(10),
+            output_tokens: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_aggregates_and_encodes_both_dialects() {
+        let mut agg = Aggregator::default();
+        agg.observe(&log("gemini-2.5-pro", 200), 900);
+        agg.observe(&log("gemini-2.5-pro", 200), 800);
+        agg.observe(&log("gemini-2.5-pro", 429), 50);
+
+        let dog = StatsdConfig { dogstatsd: true, ..Default::default() };
+        let lines = agg.drain(&dog);
+        assert!(lines.contains(&"antigravity.proxy.requests:2|c|#model:gemini-2_5-pro,status:200".to_string()));
+        assert!(lines.contains(&"antigravity.proxy.errors:1|c|#model:gemini-2_5-pro,status:429".to_string()));
+        assert!(lines.contains(&"antigravity.proxy.tokens.input:30|c|#model:gemini-2_5-pro".to_string()));
+        assert_eq!(lines.iter().filter(|l| l.starts_with("antigravity.proxy.latency.total:")).count(), 3);
+        assert!(agg.drain(&dog).is_empty());
+
+        agg.observe(&log("claude-sonnet-4-5", 200), 300);
+        let plain = StatsdConfig { prefix: "ag.".to_string(), ..Default::default() };
+        let lines = agg.drain(&plain);
+        assert!(lines.contains(&"ag.proxy.requests.claude-sonnet-4-5.200:1|c".to_string()));
+        assert!(lines.contains(&"ag.proxy.latency.ttfb.claude-sonnet-4-5:120|ms".to_string()));
+    }
+
+    #[test]
+    fn test_pack_respects_packet_size() {
+        let lines: Vec<String> = (0..100).map(|i| format!("antigravity.proxy.latency.total:{}|ms", i)).collect();
+        let packets = pack(&lines);
+        assert!(packets.len() > 1);
+        assert!(packets.iter().all(|p| p.len() <= MAX_PACKET_BYTES));
+        assert_eq!(packets.iter().map(|p| p.lines().count()).sum::<usize>(), 100);
+    }
+}
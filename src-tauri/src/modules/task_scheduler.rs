@@ -0,0 +1,545 @@
+// 定时任务
+//
+// 统一调度周期性后台任务 (Token 刷新、账号健康检查、用量报表、IDE 配置备份、检查更新、归档清理)，
+// 取代各模块各自的定时器。计划使用类 cron 表达式 (`分 时 日 月 周`，支持 `*`、`*/n`、`a-b`、`a,b`，
+// 以及 `@hourly` / `@daily` / `@weekly` / `@monthly` / `@every 30m`)，按本地时间计算。
+// 任务设置与运行历史保存在数据目录 `scheduled_tasks.json`，重启后从上次运行时间继续，
+// 错过的运行只补一次。IDE 备份与检查更新仍由各自的设置决定是否真正执行。
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Mutex;
+
+use crate::modules::{account, config, health, ide_backup, logger, token_refresh, token_stats, update_checker};
+use crate::modules::i18n::tr;
+
+const TASKS_FILE: &str = "scheduled_tasks.json";
+const REPORTS_DIR: &str = "reports";
+/// 检查到期任务的间隔
+const TICK_SECS: u64 = 30;
+/// 保留的运行历史条数
+const MAX_HISTORY: usize = 200;
+/// 用量报表覆盖的天数
+const USAGE_REPORT_DAYS: i64 = 7;
+
+/// 任务类型 (同时作为任务 ID)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    TokenRefresh,
+    HealthCheck,
+    UsageReport,
+    IdeBackup,
+    UpdateCheck,
+    ArchivePurge,
+}
+
+impl TaskKind {
+    pub const ALL: [TaskKind; 6] = [
+        TaskKind::TokenRefresh,
+        TaskKind::HealthCheck,
+        TaskKind::UsageReport,
+        TaskKind::IdeBackup,
+        TaskKind::UpdateCheck,
+        TaskKind::ArchivePurge,
+    ];
+
+    /// 默认计划与是否启用
+    fn defaults(self) -> (&'static str, bool) {
+        match self {
+            TaskKind::TokenRefresh => ("0 */6 * * *", false),
+            TaskKind::HealthCheck => ("30 3 * * *", false),
+            TaskKind::UsageReport => ("0 9 * * 1", false),
+            // 是否备份与备份间隔由 IDE 备份设置决定
+            TaskKind::IdeBackup => ("@every 1h", true),
+            // 检查频率由更新设置决定 (启动时的检查由窗口完成)
+            TaskKind::UpdateCheck => ("@every 1h", true),
+            TaskKind::ArchivePurge => ("@hourly", true),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskKind::TokenRefresh => "token_refresh",
+            TaskKind::HealthCheck => "health_check",
+            TaskKind::UsageReport => "usage_report",
+            TaskKind::IdeBackup => "ide_backup",
+            TaskKind::UpdateCheck => "update_check",
+            TaskKind::ArchivePurge => "archive_purge",
+        }
+    }
+}
+
+/// 任务计划
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// 距上次运行的固定间隔 (秒)
+    Every(u64),
+    Cron(CronSchedule),
+}
+
+/// 5 段 cron 表达式，每段为允许值的位图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// 日与周都被限定时按 cron 惯例满足其一即可
+    day_restricted: bool,
+    weekday_restricted: bool,
+}
+
+fn parse_number(value: &str, field: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| tr("backend.errors.scheduler.invalid_field", &[("field", &field_name(field)), ("value", value)]))
+}
+
+/// 字段名 (minute / hour / day / month / weekday) 的界面文案
+fn field_name(name: &str) -> String {
+    tr(&format!("backend.errors.scheduler.fields.{}", name), &[])
+}
+
+/// 解析一段 cron 字段为位图
+fn parse_field(value: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in value.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = parse_number(step, name)?;
+                if step == 0 {
+                    return Err(tr("backend.errors.scheduler.invalid_field", &[("field", &field_name(name)), ("value", part)]));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_number(a, name)?, parse_number(b, name)?)
+        } else {
+            let v = parse_number(range, name)?;
+            // `5/15` 表示从 5 开始每 15 个
+            (v, if step > 1 { max } else { v })
+        };
+        if start < min || end > max || start > end {
+            return Err(tr("backend.errors.scheduler.field_out_of_range", &[("field", &field_name(name)), ("min", &min.to_string()), ("max", &max.to_string()), ("value", part)]));
+        }
+        let mut v = start;
+        while v <= end {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(tr("backend.errors.scheduler.cron_fields", &[("expr", expr)]));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, "weekday")?;
+        // 0 和 7 都表示周日
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days: parse_field(day, 1, 31, "day")?,
+            months: parse_field(month, 1, 12, "month")?,
+            weekdays,
+            day_restricted: *day != "*",
+            weekday_restricted: *weekday != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.day_restricted && self.weekday_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// `after` 之后 (不含) 的下一个匹配时间，最多向后查找 4 年
+    fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let limit = t + ChronoDuration::days(366 * 4);
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(t.date()) {
+                t = (t.date() + ChronoDuration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + ChronoDuration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += ChronoDuration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let cron = match expr {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            _ => {
+                if let Some(interval) = expr.strip_prefix("@every") {
+                    return parse_interval(interval.trim()).map(Schedule::Every);
+                }
+                expr
+            }
+        };
+        CronSchedule::parse(cron).map(Schedule::Cron)
+    }
+
+    /// `after` (Unix 秒) 之后的下一次运行时间
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        match self {
+            Schedule::Every(secs) => Some(after + *secs as i64),
+            Schedule::Cron(cron) => {
+                let mut t = Local.timestamp_opt(after, 0).single()?.naive_local();
+                // 夏令时跳过的本地时间不存在，继续找下一个
+                loop {
+                    t = cron.next_after(t)?;
+                    if let Some(local) = Local.from_local_datetime(&t).earliest() {
+                        return Some(local.timestamp());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `30m` / `6h` / `1d`，至少 1 分钟
+fn parse_interval(value: &str) -> Result<u64, String> {
+    let invalid = || format!("无效的间隔: {} (示例: 30m、6h、1d)", value);
+    let split = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let amount: u64 = value[..split].parse().map_err(|_| invalid())?;
+    let unit = match &value[split..] {
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(invalid()),
+    };
+    if amount == 0 {
+        return Err(invalid());
+    }
+    Ok(amount * unit)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledTask {
+    pub kind: TaskKind,
+    pub enabled: bool,
+    pub schedule: String,
+    #[serde(default)]
+    pub last_run_at: Option<i64>,
+}
+
+/// 一次运行的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub kind: TaskKind,
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub message: String,
+    /// 手动触发 (不影响计划)
+    #[serde(default)]
+    pub manual: bool,
+}
+
+/// 任务列表中的一项
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    #[serde(flatten)]
+    pub task: ScheduledTask,
+    pub next_run_at: Option<i64>,
+    pub running: bool,
+    pub last_result: Option<TaskRun>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TaskStore {
+    #[serde(default)]
+    tasks: Vec<ScheduledTask>,
+    /// 新到旧
+    #[serde(default)]
+    history: Vec<TaskRun>,
+}
+
+impl TaskStore {
+    /// 补齐缺少的任务 (新版本增加的任务使用默认计划)
+    fn normalized(mut self) -> Self {
+        for kind in TaskKind::ALL {
+            if !self.tasks.iter().any(|t| t.kind == kind) {
+                let (schedule, enabled) = kind.defaults();
+                self.tasks.push(ScheduledTask { kind, enabled, schedule: schedule.to_string(), last_run_at: None });
+            }
+        }
+        self.tasks.sort_by_key(|t| TaskKind::ALL.iter().position(|k| *k == t.kind));
+        self
+    }
+
+    fn task_mut(&mut self, kind: TaskKind) -> &mut ScheduledTask {
+        let index = self.tasks.iter().position(|t| t.kind == kind).expect("normalized store contains every task");
+        &mut self.tasks[index]
+    }
+}
+
+static STORE: Lazy<Mutex<TaskStore>> = Lazy::new(|| Mutex::new(load_store()));
+static RUNNING: Lazy<Mutex<HashSet<TaskKind>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+static STARTED: AtomicBool = AtomicBool::new(false);
+/// 调度器启动时间，从未运行过的任务从此时开始计算
+static STARTED_AT: AtomicI64 = AtomicI64::new(0);
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(account::get_data_dir()?.join(TASKS_FILE))
+}
+
+fn load_store() -> TaskStore {
+    let store = store_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| match serde_json::from_str::<TaskStore>(&content) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                logger::log_warn(&format!("[Tasks] 解析定时任务文件失败，使用默认设置: {}", e));
+                None
+            }
+        })
+        .unwrap_or_default();
+    store.normalized()
+}
+
+fn save_store(store: &TaskStore) {
+    let result = store_path().and_then(|path| {
+        let content = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        logger::log_warn(&format!("[Tasks] 保存定时任务失败: {}", e));
+    }
+}
+
+fn next_run(task: &ScheduledTask) -> Option<i64> {
+    if !task.enabled {
+        return None;
+    }
+    let base = task.last_run_at.unwrap_or_else(|| STARTED_AT.load(Ordering::Relaxed));
+    Schedule::parse(&task.schedule).ok()?.next_after(base)
+}
+
+/// 所有任务及下一次运行时间
+pub fn list() -> Vec<TaskStatus> {
+    let store = STORE.lock().unwrap();
+    let running = RUNNING.lock().unwrap();
+    store
+        .tasks
+        .iter()
+        .map(|task| TaskStatus {
+            task: task.clone(),
+            next_run_at: next_run(task),
+            running: running.contains(&task.kind),
+            last_result: store.history.iter().find(|r| r.kind == task.kind).cloned(),
+        })
+        .collect()
+}
+
+/// 修改任务的启用状态与计划
+pub fn update(kind: TaskKind, enabled: bool, schedule: &str) -> Result<ScheduledTask, String> {
+    Schedule::parse(schedule)?;
+    let mut store = STORE.lock().unwrap();
+    let task = store.task_mut(kind);
+    task.enabled = enabled;
+    task.schedule = schedule.trim().to_string();
+    let updated = task.clone();
+    save_store(&store);
+    Ok(updated)
+}
+
+/// 运行历史 (新到旧)，可按任务过滤
+pub fn history(kind: Option<TaskKind>, limit: usize) -> Vec<TaskRun> {
+    let store = STORE.lock().unwrap();
+    store.history.iter().filter(|r| kind.is_none_or(|k| r.kind == k)).take(limit).cloned().collect()
+}
+
+fn write_usage_report() -> Result<String, String> {
+    let now = chrono::Utc::now().timestamp();
+    let prices = config::load_app_config().map(|c| c.model_prices).unwrap_or_default();
+    let rows = token_stats::get_report(
+        token_stats::ReportPeriod::Day,
+        now - USAGE_REPORT_DAYS * 86400,
+        now + 1,
+        &prices,
+    )?;
+    let dir = account::get_data_dir()?.join(REPORTS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建报表目录失败: {}", e))?;
+    let path = dir.join(format!("usage-{}.csv", Local::now().format("%Y%m%d-%H%M")));
+    std::fs::write(&path, token_stats::report_to_csv(&rows)).map_err(|e| format!("写入报表失败: {}", e))?;
+    Ok(format!("已生成 {} ({} 行)", path.display(), rows.len()))
+}
+
+async fn execute(kind: TaskKind, app_handle: Option<&crate::utils::app::AppHandle>) -> Result<String, String> {
+    match kind {
+        TaskKind::TokenRefresh => {
+            let report = token_refresh::refresh_all_tokens(|_| {}).await?;
+            Ok(format!("成功 {}, 失败 {}, 跳过 {}", report.refreshed, report.failed, report.skipped))
+        }
+        TaskKind::HealthCheck => {
+            let reports = health::check_all_accounts().await?;
+            let unhealthy = reports.iter().filter(|r| !r.healthy).count();
+            Ok(format!("检查 {} 个账号, {} 个异常", reports.len(), unhealthy))
+        }
+        TaskKind::UsageReport => tokio::task::spawn_blocking(write_usage_report).await.map_err(|e| e.to_string())?,
+        TaskKind::IdeBackup => {
+            let backup = tokio::task::spawn_blocking(ide_backup::run_scheduled_backup)
+                .await
+                .map_err(|e| e.to_string())??;
+            Ok(match backup {
+                Some(backup) => format!("已备份 {}", backup.id),
+                None => "未开启或未到备份时间".to_string(),
+            })
+        }
+        TaskKind::UpdateCheck => update_checker::run_background_check(app_handle).await,
+        TaskKind::ArchivePurge => {
+            let days = config::load_app_config()?.archive_purge_days;
+            let removed = account::purge_expired_archives(days)?;
+            Ok(format!("已清理 {} 个归档账号", removed))
+        }
+    }
+}
+
+/// 运行一次任务并记录结果，同一任务不会并发运行
+pub async fn run(kind: TaskKind, app_handle: Option<crate::utils::app::AppHandle>, manual: bool) -> Result<TaskRun, String> {
+    if !RUNNING.lock().unwrap().insert(kind) {
+        return Err(tr("backend.errors.scheduler.task_running", &[]));
+    }
+    let started_at = chrono::Utc::now().timestamp();
+    let timer = std::time::Instant::now();
+    let result = execute(kind, app_handle.as_ref()).await;
+    RUNNING.lock().unwrap().remove(&kind);
+
+    let run = TaskRun {
+        kind,
+        started_at,
+        duration_ms: timer.elapsed().as_millis() as u64,
+        success: result.is_ok(),
+        message: result.unwrap_or_else(|e| e),
+        manual,
+    };
+    if run.success {
+        logger::log_info(&format!("[Tasks] {} 完成: {}", kind.as_str(), run.message));
+    } else {
+        logger::log_warn(&format!("[Tasks] {} 失败: {}", kind.as_str(), run.message));
+    }
+
+    let mut store = STORE.lock().unwrap();
+    if !manual {
+        store.task_mut(kind).last_run_at = Some(started_at);
+    }
+    store.history.insert(0, run.clone());
+    store.history.truncate(MAX_HISTORY);
+    save_store(&store);
+    Ok(run)
+}
+
+/// 启动调度器 (重复调用无效)。无界面模式不传 AppHandle
+pub fn start(app_handle: Option<crate::utils::app::AppHandle>) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    STARTED_AT.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+    crate::utils::app::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECS));
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now().timestamp();
+            let due: Vec<TaskKind> = list()
+                .into_iter()
+                .filter(|s| !s.running && s.next_run_at.is_some_and(|next| next <= now))
+                .map(|s| s.task.kind)
+                .collect();
+            for kind in due {
+                let app_handle = app_handle.clone();
+                crate::utils::app::spawn(async move {
+                    let _ = run(kind, app_handle, false).await;
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        let Schedule::Cron(cron) = Schedule::parse(expr).unwrap() else { panic!("not cron") };
+        cron.next_after(at(after)).unwrap().format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    #[test]
+    fn test_cron_next() {
+        assert_eq!(next("0 */6 * * *", "2024-06-01 05:59"), "2024-06-01 06:00");
+        assert_eq!(next("0 */6 * * *", "2024-06-01 06:00"), "2024-06-01 12:00");
+        assert_eq!(next("30 3 * * *", "2024-12-31 04:00"), "2025-01-01 03:30");
+        // 2024-06-03 是周一
+        assert_eq!(next("0 9 * * 1", "2024-06-01 00:00"), "2024-06-03 09:00");
+        assert_eq!(next("@monthly", "2024-02-15 10:00"), "2024-03-01 00:00");
+        assert_eq!(next("0 0 29 2 *", "2023-03-01 00:00"), "2024-02-29 00:00");
+        // 日与周都限定时满足其一即可
+        assert_eq!(next("0 0 15 * 0", "2024-06-10 00:00"), "2024-06-15 00:00");
+        assert_eq!(next("15,45 8-9 * * 7", "2024-06-02 08:20"), "2024-06-02 08:45");
+    }
+
+    #[test]
+    fn test_schedule_parse() {
+        assert_eq!(Schedule::parse("@every 30m"), Ok(Schedule::Every(1800)));
+        assert_eq!(Schedule::parse("@every 2d").unwrap().next_after(100), Some(100 + 2 * 86400));
+        assert!(Schedule::parse("@every 0h").is_err());
+        assert!(Schedule::parse("@every 10s").is_err());
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        for kind in TaskKind::ALL {
+            assert!(Schedule::parse(kind.defaults().0).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_store_normalization() {
+        let store: TaskStore = serde_json::from_str(
+            r#"{"tasks": [{"kind": "update_check", "enabled": false, "schedule": "@daily", "last_run_at": 5}]}"#,
+        )
+        .unwrap();
+        let store = store.normalized();
+        assert_eq!(store.tasks.len(), TaskKind::ALL.len());
+        assert_eq!(store.tasks[0].kind, TaskKind::TokenRefresh);
+        let update = store.tasks.iter().find(|t| t.kind == TaskKind::UpdateCheck).unwrap();
+        assert_eq!((update.enabled, update.schedule.as_str(), update.last_run_at), (false, "@daily", Some(5)));
+    }
+}
@@ -0,0 +1,205 @@
+// 批量刷新 Token
+//
+// 以有限并发强制刷新所有账号的 access_token，每完成一个账号推送一次进度
+// (`account://token-refresh-progress`)，结束后汇总需要用户处理的账号 (需重新授权 / 刷新失败)。
+
+use futures::future::join_all;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::models::{Account, TokenData};
+use crate::modules::{account, oauth, token_status};
+
+pub const TOKEN_REFRESH_PROGRESS_EVENT: &str = "account://token-refresh-progress";
+const MAX_CONCURRENT: usize = 5;
+
+/// 单个账号刷新完成时推送的进度
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenRefreshProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub account_id: String,
+    pub email: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 需要用户处理的账号
+#[derive(Debug, Clone, Serialize)]
+pub struct AttentionItem {
+    pub account_id: String,
+    pub email: String,
+    /// refresh_token 已失效，需要重新登录授权
+    pub needs_reauth: bool,
+    pub reason: String,
+}
+
+/// 批量刷新汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkRefreshReport {
+    pub total: usize,
+    pub refreshed: usize,
+    pub failed: usize,
+    /// 已禁用而未刷新的账号
+    pub skipped: usize,
+    pub needs_attention: Vec<AttentionItem>,
+}
+
+/// 强制刷新单个账号的 access_token 并保存
+async fn refresh_one(account: &mut Account) -> Result<(), String> {
+    token_status::mark_refresh_started(&account.email);
+    let result = oauth::refresh_access_token(&account.token.refresh_token).await;
+    token_status::mark_refresh_finished(&account.email, result.as_ref().map(|_| ()).map_err(|e| e.as_str()));
+
+    match result {
+        Ok(response) => {
+            account.token = TokenData::new(
+                response.access_token,
+                response.refresh_token.unwrap_or_else(|| account.token.refresh_token.clone()),
+                response.expires_in,
+                account.token.email.clone(),
+                account.token.project_id.clone(),
+                account.token.session_id.clone(),
+            );
+            account::save_account(account)?;
+            if let Ok(data_dir) = account::get_data_dir() {
+                crate::modules::account_store::record_event(&data_dir, &account.id, "refreshed", Some("manual"));
+            }
+            Ok(())
+        }
+        Err(e) => {
+            if e.contains("invalid_grant") {
+                account::flag_needs_reauth(account, &e);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn summarize(total: usize, skipped: Vec<&Account>, results: Vec<(Account, Result<(), String>)>) -> BulkRefreshReport {
+    let mut needs_attention: Vec<AttentionItem> = skipped
+        .into_iter()
+        .filter(|a| a.needs_reauth)
+        .map(|a| AttentionItem {
+            account_id: a.id.clone(),
+            email: a.email.clone(),
+            needs_reauth: true,
+            reason: a.disabled_reason.clone().unwrap_or_else(|| "invalid_grant".to_string()),
+        })
+        .collect();
+
+    let mut refreshed = 0;
+    let mut failed = 0;
+    for (account, result) in results {
+        match result {
+            Ok(()) => refreshed += 1,
+            Err(reason) => {
+                failed += 1;
+                needs_attention.push(AttentionItem {
+                    account_id: account.id,
+                    email: account.email,
+                    needs_reauth: account.needs_reauth,
+                    reason,
+                });
+            }
+        }
+    }
+
+    BulkRefreshReport {
+        total,
+        refreshed,
+        failed,
+        skipped: total - refreshed - failed,
+        needs_attention,
+    }
+}
+
+/// 刷新所有未禁用账号的 Token，`on_progress` 在每个账号完成后调用
+pub async fn refresh_all_tokens<F>(on_progress: F) -> Result<BulkRefreshReport, String>
+where
+    F: Fn(TokenRefreshProgress) + Send + Sync,
+{
+    let accounts = account::list_accounts()?;
+    let total = accounts.len();
+    let (skipped, pending): (Vec<&Account>, Vec<&Account>) = accounts.iter().partition(|a| a.disabled);
+
+    crate::modules::logger::log_info(&format!(
+        "开始批量刷新 Token: 共 {} 个账号, 跳过已禁用 {} 个 (最大并发: {})",
+        total,
+        skipped.len(),
+        MAX_CONCURRENT
+    ));
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let completed = AtomicUsize::new(0);
+    let pending_total = pending.len();
+
+    let tasks = pending.into_iter().cloned().map(|mut account| {
+        let semaphore = semaphore.clone();
+        let completed = &completed;
+        let on_progress = &on_progress;
+        async move {
+            let _permit = semaphore.acquire().await;
+            let result = refresh_one(&mut account).await;
+            if let Err(e) = &result {
+                crate::modules::logger::log_warn(&format!("Token 刷新失败 {}: {}", account.email, e));
+            }
+            on_progress(TokenRefreshProgress {
+                completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                total: pending_total,
+                account_id: account.id.clone(),
+                email: account.email.clone(),
+                success: result.is_ok(),
+                error: result.as_ref().err().cloned(),
+            });
+            (account, result)
+        }
+    });
+
+    let results = join_all(tasks).await;
+    let report = summarize(total, skipped, results);
+    crate::modules::logger::log_info(&format!(
+        "批量刷新 Token 完成: 成功 {}, 失败 {}, 跳过 {}",
+        report.refreshed, report.failed, report.skipped
+    ));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: &str) -> Account {
+        let token = TokenData::new("at".to_string(), "1//rt".to_string(), 3600, None, None, None);
+        Account::new(id.to_string(), format!("{}@example.com", id), token)
+    }
+
+    #[test]
+    fn test_summarize_collects_accounts_needing_attention() {
+        let mut revoked = account("revoked");
+        revoked.disabled = true;
+        revoked.needs_reauth = true;
+        let mut manual = account("manual");
+        manual.disabled = true;
+
+        let mut newly_revoked = account("newly-revoked");
+        newly_revoked.needs_reauth = true;
+        let results = vec![
+            (account("ok"), Ok(())),
+            (newly_revoked, Err("invalid_grant".to_string())),
+            (account("flaky"), Err("刷新请求失败: timeout".to_string())),
+        ];
+
+        let report = summarize(5, vec![&revoked, &manual], results);
+        assert_eq!((report.refreshed, report.failed, report.skipped), (1, 2, 2));
+
+        let attention: Vec<(&str, bool)> = report
+            .needs_attention
+            .iter()
+            .map(|a| (a.account_id.as_str(), a.needs_reauth))
+            .collect();
+        assert_eq!(attention, vec![("revoked", true), ("newly-revoked", true), ("flaky", false)]);
+    }
+}
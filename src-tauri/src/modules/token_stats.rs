@@ -0,0 +1,671 @@
+// Token 用量统计库
+//
+// 每个经过反代的上游请求记录一行 (时间、API Key、模型、账号、输入 / 输出 Token)，
+// 存放在独立的 `token_stats.db` 中，不受请求日志开关与日志清理影响，重启后保留。
+// API Key 只保存 SHA-256 指纹与末 4 位，不落盘明文。
+// 同时提供按日 / 周 / 月 (UTC) 聚合的用量报表与 CSV 导出，以及按 API Key 的使用方视角统计。
+// 另记录客户端请求的模型名，用于对比映射 / 降级后实际使用的上游模型。
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::models::ModelPrice;
+
+pub fn get_db_path() -> Result<PathBuf, String> {
+    let data_dir = crate::modules::account::get_data_dir()?;
+    Ok(data_dir.join("token_stats.db"))
+}
+
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS token_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            key_id TEXT NOT NULL DEFAULT '',
+            key_hint TEXT NOT NULL DEFAULT '',
+            model TEXT NOT NULL,
+            account_email TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_token_usage_ts ON token_usage (timestamp);
+        CREATE INDEX IF NOT EXISTS idx_token_usage_key ON token_usage (key_id, timestamp);",
+    )
+    .map_err(|e| e.to_string())?;
+    let _ = conn.execute("ALTER TABLE token_usage ADD COLUMN is_error INTEGER NOT NULL DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE token_usage ADD COLUMN requested_model TEXT NOT NULL DEFAULT ''", []);
+    Ok(())
+}
+
+fn open() -> Result<Connection, String> {
+    let conn = Connection::open(get_db_path()?).map_err(|e| e.to_string())?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+pub fn init_db() -> Result<(), String> {
+    open().map(|_| ())
+}
+
+/// 请求使用的 API Key 标识
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyId {
+    /// SHA-256 指纹前 16 位 (未携带 Key 时为空)
+    pub id: String,
+    /// Key 末 4 位，便于在界面上辨认
+    pub hint: String,
+}
+
+impl KeyId {
+    pub fn from_key(key: Option<&str>) -> Self {
+        let Some(key) = key.map(str::trim).filter(|k| !k.is_empty()) else {
+            return Self::default();
+        };
+        let digest = format!("{:x}", Sha256::digest(key.as_bytes()));
+        let chars: Vec<char> = key.chars().collect();
+        let hint: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+        Self { id: digest[..16].to_string(), hint }
+    }
+
+    /// 从请求头 (Authorization / x-api-key / x-goog-api-key) 或 `key=` 查询参数中提取
+    pub fn from_request(headers: &axum::http::HeaderMap, query: Option<&str>) -> Self {
+        let header_key = headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.strip_prefix("Bearer ").unwrap_or(s))
+            .or_else(|| headers.get("x-api-key").and_then(|h| h.to_str().ok()))
+            .or_else(|| headers.get("x-goog-api-key").and_then(|h| h.to_str().ok()));
+        let query_key = query.and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("key=")));
+        Self::from_key(header_key.or(query_key))
+    }
+}
+
+/// 单次请求的用量
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenUsageRecord {
+    /// Unix 秒
+    pub timestamp: i64,
+    pub key_id: String,
+    pub key_hint: String,
+    /// 实际使用的上游模型 (映射 / 降级之后)
+    pub model: String,
+    /// 客户端请求的模型名
+    pub requested_model: String,
+    pub account_email: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// 状态码 >= 400
+    pub is_error: bool,
+}
+
+/// 按 (Key, 模型, 账号) 聚合的用量
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenUsageSummary {
+    pub key_id: String,
+    pub key_hint: String,
+    pub model: String,
+    pub account_email: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 按单价表估算的成本 (美元)，没有匹配的单价时为 None
+    pub estimated_cost_usd: Option<f64>,
+}
+
+/// 查找模型单价 (精确匹配优先，其次最长前缀)
+pub fn find_price<'a>(prices: &'a [ModelPrice], model: &str) -> Option<&'a ModelPrice> {
+    prices
+        .iter()
+        .filter_map(|p| p.match_len(model).map(|len| (len, p)))
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, p)| p)
+}
+
+/// 估算一组 Token 的成本 (美元)
+pub fn estimate_cost(prices: &[ModelPrice], model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    find_price(prices, model).map(|p| {
+        (input_tokens as f64 * p.input_per_million + output_tokens as f64 * p.output_per_million) / 1_000_000.0
+    })
+}
+
+fn insert(conn: &Connection, record: &TokenUsageRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO token_usage (timestamp, key_id, key_hint, model, account_email, input_tokens, output_tokens, is_error, requested_model)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            record.timestamp,
+            record.key_id,
+            record.key_hint,
+            record.model,
+            record.account_email,
+            record.input_tokens,
+            record.output_tokens,
+            record.is_error,
+            record.requested_model
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+fn summarize(conn: &Connection, since: i64, until: i64) -> Result<Vec<TokenUsageSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT key_id, MAX(key_hint), model, account_email, COUNT(*), SUM(input_tokens), SUM(output_tokens)
+             FROM token_usage
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY key_id, model, account_email
+             ORDER BY SUM(input_tokens) + SUM(output_tokens) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since, until], |row| {
+            Ok(TokenUsageSummary {
+                key_id: row.get(0)?,
+                key_hint: row.get(1)?,
+                model: row.get(2)?,
+                account_email: row.get(3)?,
+                requests: row.get::<_, i64>(4)? as u64,
+                input_tokens: row.get::<_, i64>(5)? as u64,
+                output_tokens: row.get::<_, i64>(6)? as u64,
+                estimated_cost_usd: None,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// 报表聚合周期 (按 UTC 划分)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    /// 周期标签的 SQLite strftime 格式 (如 2024-06-01 / 2024-W22 / 2024-06)
+    fn sql_format(self) -> &'static str {
+        match self {
+            ReportPeriod::Day => "%Y-%m-%d",
+            ReportPeriod::Week => "%Y-W%W",
+            ReportPeriod::Month => "%Y-%m",
+        }
+    }
+}
+
+/// 报表中的一行：某个周期内 (Key, 模型, 账号) 的请求、错误与 Token 数
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReportRow {
+    pub period: String,
+    pub key_id: String,
+    pub key_hint: String,
+    pub model: String,
+    pub account_email: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub estimated_cost_usd: Option<f64>,
+}
+
+fn report(conn: &Connection, period: ReportPeriod, since: i64, until: i64) -> Result<Vec<UsageReportRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT strftime(?3, timestamp, 'unixepoch') AS period, key_id, MAX(key_hint), model, account_email,
+                    COUNT(*), SUM(is_error), SUM(input_tokens), SUM(output_tokens)
+             FROM token_usage
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY period, key_id, model, account_email
+             ORDER BY period ASC, SUM(input_tokens) + SUM(output_tokens) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since, until, period.sql_format()], |row| {
+            Ok(UsageReportRow {
+                period: row.get(0)?,
+                key_id: row.get(1)?,
+                key_hint: row.get(2)?,
+                model: row.get(3)?,
+                account_email: row.get(4)?,
+                requests: row.get::<_, i64>(5)? as u64,
+                errors: row.get::<_, i64>(6)? as u64,
+                input_tokens: row.get::<_, i64>(7)? as u64,
+                output_tokens: row.get::<_, i64>(8)? as u64,
+                estimated_cost_usd: None,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 将报表转换为 CSV (带表头，Key 只导出指纹与末 4 位)
+pub fn report_to_csv(rows: &[UsageReportRow]) -> String {
+    let mut out = String::from(
+        "period,key_id,key_hint,model,account_email,requests,errors,input_tokens,output_tokens,estimated_cost_usd\n",
+    );
+    for row in rows {
+        let cost = row.estimated_cost_usd.map(|c| format!("{:.6}", c)).unwrap_or_default();
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&row.period),
+            csv_field(&row.key_id),
+            csv_field(&row.key_hint),
+            csv_field(&row.model),
+            csv_field(&row.account_email),
+            row.requests,
+            row.errors,
+            row.input_tokens,
+            row.output_tokens,
+            cost
+        ));
+    }
+    out
+}
+
+/// 单个 Key 在某个模型上的用量
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyModelUsage {
+    pub model: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// 按 API Key 汇总的用量 (使用方视角，与按账号的统计互补)
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsageSummary {
+    pub key_id: String,
+    pub key_hint: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub estimated_cost_usd: Option<f64>,
+    /// 最近一次请求时间 (Unix 秒)
+    pub last_used_at: i64,
+    /// 用到的模型 (按 Token 数从多到少)
+    pub models: Vec<KeyModelUsage>,
+}
+
+fn key_usage(conn: &Connection, since: i64, until: i64, key_id: Option<&str>) -> Result<Vec<KeyUsageSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT key_id, MAX(key_hint), model, COUNT(*), SUM(is_error), SUM(input_tokens), SUM(output_tokens), MAX(timestamp)
+             FROM token_usage
+             WHERE timestamp >= ?1 AND timestamp < ?2 AND (?3 IS NULL OR key_id = ?3)
+             GROUP BY key_id, model
+             ORDER BY SUM(input_tokens) + SUM(output_tokens) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since, until, key_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(7)?,
+                KeyModelUsage {
+                    model: row.get(2)?,
+                    requests: row.get::<_, i64>(3)? as u64,
+                    errors: row.get::<_, i64>(4)? as u64,
+                    input_tokens: row.get::<_, i64>(5)? as u64,
+                    output_tokens: row.get::<_, i64>(6)? as u64,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut keys: Vec<KeyUsageSummary> = Vec::new();
+    for row in rows {
+        let (key_id, key_hint, last_used_at, usage) = row.map_err(|e| e.to_string())?;
+        let idx = match keys.iter().position(|k| k.key_id == key_id) {
+            Some(idx) => idx,
+            None => {
+                keys.push(KeyUsageSummary {
+                    key_id,
+                    key_hint,
+                    requests: 0,
+                    errors: 0,
+                    error_rate: 0.0,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    estimated_cost_usd: None,
+                    last_used_at,
+                    models: Vec::new(),
+                });
+                keys.len() - 1
+            }
+        };
+        let key = &mut keys[idx];
+        key.requests += usage.requests;
+        key.errors += usage.errors;
+        key.input_tokens += usage.input_tokens;
+        key.output_tokens += usage.output_tokens;
+        key.last_used_at = key.last_used_at.max(last_used_at);
+        key.models.push(usage);
+    }
+    for key in &mut keys {
+        key.error_rate = if key.requests == 0 { 0.0 } else { key.errors as f64 / key.requests as f64 };
+    }
+    keys.sort_by_key(|k| std::cmp::Reverse(k.input_tokens + k.output_tokens));
+    Ok(keys)
+}
+
+/// 请求模型实际落到的一个上游模型
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRoute {
+    pub upstream_model: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// 按客户端请求的模型名汇总的用量，以及映射 / 降级后实际使用的上游模型
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelMappingUsage {
+    pub requested_model: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 有请求被映射到了其他模型
+    pub remapped: bool,
+    /// 实际使用的上游模型 (按请求数从多到少)
+    pub routes: Vec<ModelRoute>,
+}
+
+fn model_mapping_usage(conn: &Connection, since: i64, until: i64) -> Result<Vec<ModelMappingUsage>, String> {
+    // 早期记录没有 requested_model，视为未经映射
+    let mut stmt = conn
+        .prepare(
+            "SELECT CASE WHEN requested_model = '' THEN model ELSE requested_model END AS requested,
+                    model, COUNT(*), SUM(is_error), SUM(input_tokens), SUM(output_tokens)
+             FROM token_usage
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY requested, model
+             ORDER BY COUNT(*) DESC, model",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since, until], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ModelRoute {
+                    upstream_model: row.get(1)?,
+                    requests: row.get::<_, i64>(2)? as u64,
+                    errors: row.get::<_, i64>(3)? as u64,
+                    input_tokens: row.get::<_, i64>(4)? as u64,
+                    output_tokens: row.get::<_, i64>(5)? as u64,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut models: Vec<ModelMappingUsage> = Vec::new();
+    for row in rows {
+        let (requested_model, route) = row.map_err(|e| e.to_string())?;
+        let idx = match models.iter().position(|m| m.requested_model == requested_model) {
+            Some(idx) => idx,
+            None => {
+                models.push(ModelMappingUsage {
+                    requested_model,
+                    requests: 0,
+                    errors: 0,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    remapped: false,
+                    routes: Vec::new(),
+                });
+                models.len() - 1
+            }
+        };
+        let model = &mut models[idx];
+        model.requests += route.requests;
+        model.errors += route.errors;
+        model.input_tokens += route.input_tokens;
+        model.output_tokens += route.output_tokens;
+        model.remapped |= route.upstream_model != model.requested_model;
+        model.routes.push(route);
+    }
+    models.sort_by_key(|m| std::cmp::Reverse(m.requests));
+    Ok(models)
+}
+
+/// 记录一次请求的用量
+pub fn record(record: &TokenUsageRecord) -> Result<(), String> {
+    insert(&open()?, record)
+}
+
+/// 汇总 [since, until) 区间的用量 (Unix 秒)，并按单价表估算成本
+pub fn get_summary(since: i64, until: i64, prices: &[ModelPrice]) -> Result<Vec<TokenUsageSummary>, String> {
+    let mut rows = summarize(&open()?, since, until)?;
+    for row in &mut rows {
+        row.estimated_cost_usd = estimate_cost(prices, &row.model, row.input_tokens, row.output_tokens);
+    }
+    Ok(rows)
+}
+
+/// 生成 [since, until) 区间按周期聚合的用量报表
+pub fn get_report(
+    period: ReportPeriod,
+    since: i64,
+    until: i64,
+    prices: &[ModelPrice],
+) -> Result<Vec<UsageReportRow>, String> {
+    let mut rows = report(&open()?, period, since, until)?;
+    for row in &mut rows {
+        row.estimated_cost_usd = estimate_cost(prices, &row.model, row.input_tokens, row.output_tokens);
+    }
+    Ok(rows)
+}
+
+/// 汇总 [since, until) 区间每个 API Key 的用量；`key_id` 不为空时只返回该 Key
+pub fn get_key_usage(
+    since: i64,
+    until: i64,
+    key_id: Option<&str>,
+    prices: &[ModelPrice],
+) -> Result<Vec<KeyUsageSummary>, String> {
+    let mut keys = key_usage(&open()?, since, until, key_id)?;
+    for key in &mut keys {
+        let costs: Vec<Option<f64>> = key
+            .models
+            .iter()
+            .map(|m| estimate_cost(prices, &m.model, m.input_tokens, m.output_tokens))
+            .collect();
+        // 部分模型没有单价时仍给出已知部分的估算
+        key.estimated_cost_usd = costs.iter().any(Option::is_some).then(|| costs.iter().flatten().sum());
+    }
+    Ok(keys)
+}
+
+/// 汇总 [since, until) 区间客户端请求的模型与实际使用的上游模型
+pub fn get_model_mapping_usage(since: i64, until: i64) -> Result<Vec<ModelMappingUsage>, String> {
+    model_mapping_usage(&open()?, since, until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_rows_are_grouped_by_key_model_and_account() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let key = KeyId::from_key(Some("sk-secret-abcd"));
+        assert_eq!(key.hint, "abcd");
+        assert_eq!(key.id.len(), 16);
+        assert!(!key.id.contains("secret"));
+        assert_eq!(KeyId::from_key(Some("  ")), KeyId::default());
+
+        let row = |ts: i64, model: &str, input: u32, output: u32| TokenUsageRecord {
+            timestamp: ts,
+            key_id: key.id.clone(),
+            key_hint: key.hint.clone(),
+            model: model.to_string(),
+            requested_model: model.to_string(),
+            account_email: "a@example.com".to_string(),
+            input_tokens: input,
+            output_tokens: output,
+            is_error: false,
+        };
+        insert(&conn, &row(100, "gemini-2.5-pro", 1000, 200)).unwrap();
+        insert(&conn, &row(200, "gemini-2.5-pro", 500, 100)).unwrap();
+        insert(&conn, &row(300, "gemini-2.5-flash", 10, 5)).unwrap();
+        insert(&conn, &row(5000, "gemini-2.5-pro", 9999, 9999)).unwrap();
+
+        let summary = summarize(&conn, 0, 1000).unwrap();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].model, "gemini-2.5-pro");
+        assert_eq!((summary[0].requests, summary[0].input_tokens, summary[0].output_tokens), (2, 1500, 300));
+        assert_eq!(summary[1].key_hint, "abcd");
+    }
+
+    #[test]
+    fn test_report_groups_by_period_and_exports_csv() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        // 同一张表重复迁移不应报错
+        ensure_schema(&conn).unwrap();
+
+        let day = 86_400;
+        let row = |ts: i64, account: &str, is_error: bool| TokenUsageRecord {
+            timestamp: ts,
+            key_id: "k1".to_string(),
+            key_hint: "abcd".to_string(),
+            model: "gemini-2.5-pro".to_string(),
+            requested_model: "gemini-2.5-pro".to_string(),
+            account_email: account.to_string(),
+            input_tokens: 100,
+            output_tokens: 10,
+            is_error,
+        };
+        // 1970-01-01 / 1970-01-02 / 1970-02-01
+        insert(&conn, &row(10, "a@example.com", false)).unwrap();
+        insert(&conn, &row(20, "a@example.com", true)).unwrap();
+        insert(&conn, &row(day + 5, "a@example.com", false)).unwrap();
+        insert(&conn, &row(31 * day, "a,b@example.com", false)).unwrap();
+
+        let daily = report(&conn, ReportPeriod::Day, 0, 40 * day).unwrap();
+        assert_eq!(daily.len(), 3);
+        assert_eq!(daily[0].period, "1970-01-01");
+        assert_eq!((daily[0].requests, daily[0].errors, daily[0].input_tokens), (2, 1, 200));
+
+        let monthly = report(&conn, ReportPeriod::Month, 0, 40 * day).unwrap();
+        assert_eq!(monthly.len(), 2);
+        assert_eq!((monthly[0].period.as_str(), monthly[0].requests), ("1970-01", 3));
+
+        let csv = report_to_csv(&monthly);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("period,key_id"));
+        assert_eq!(lines[2], "1970-02,k1,abcd,gemini-2.5-pro,\"a,b@example.com\",1,0,100,10,");
+    }
+
+    #[test]
+    fn test_key_usage_groups_models_per_key() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let row = |ts: i64, key: &str, model: &str, tokens: u32, is_error: bool| TokenUsageRecord {
+            timestamp: ts,
+            key_id: key.to_string(),
+            key_hint: key[..2].to_string(),
+            model: model.to_string(),
+            requested_model: model.to_string(),
+            account_email: "a@example.com".to_string(),
+            input_tokens: tokens,
+            output_tokens: 0,
+            is_error,
+        };
+        insert(&conn, &row(10, "k1", "gemini-2.5-pro", 100, false)).unwrap();
+        insert(&conn, &row(20, "k1", "gemini-2.5-flash", 300, true)).unwrap();
+        insert(&conn, &row(30, "k1", "gemini-2.5-flash", 300, false)).unwrap();
+        insert(&conn, &row(40, "k2", "gemini-2.5-pro", 50, false)).unwrap();
+
+        let keys = key_usage(&conn, 0, 100, None).unwrap();
+        assert_eq!(keys.len(), 2);
+        let k1 = &keys[0];
+        assert_eq!((k1.key_id.as_str(), k1.requests, k1.errors, k1.input_tokens), ("k1", 3, 1, 700));
+        assert!((k1.error_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(k1.last_used_at, 30);
+        assert_eq!(k1.models[0].model, "gemini-2.5-flash");
+
+        let only = key_usage(&conn, 0, 100, Some("k2")).unwrap();
+        assert_eq!((only.len(), only[0].requests), (1, 1));
+    }
+
+    #[test]
+    fn test_model_mapping_usage_groups_upstream_models_per_requested_model() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let row = |requested: &str, model: &str, is_error: bool| TokenUsageRecord {
+            timestamp: 10,
+            key_id: "k1".to_string(),
+            key_hint: "k1".to_string(),
+            model: model.to_string(),
+            requested_model: requested.to_string(),
+            account_email: "a@example.com".to_string(),
+            input_tokens: 10,
+            output_tokens: 1,
+            is_error,
+        };
+        insert(&conn, &row("claude-sonnet-4-5", "claude-sonnet-4-5", false)).unwrap();
+        insert(&conn, &row("claude-sonnet-4-5", "claude-sonnet-4-5", false)).unwrap();
+        insert(&conn, &row("claude-sonnet-4-5", "gemini-2.5-pro", true)).unwrap();
+        insert(&conn, &row("gpt-4o", "gemini-2.5-flash", false)).unwrap();
+        // 早期没有记录请求模型的行
+        insert(&conn, &row("", "gemini-2.5-pro", false)).unwrap();
+
+        let models = model_mapping_usage(&conn, 0, 100).unwrap();
+        assert_eq!(models.len(), 3);
+        let sonnet = &models[0];
+        assert_eq!((sonnet.requested_model.as_str(), sonnet.requests, sonnet.errors), ("claude-sonnet-4-5", 3, 1));
+        assert!(sonnet.remapped);
+        let routes: Vec<(&str, u64)> = sonnet.routes.iter().map(|r| (r.upstream_model.as_str(), r.requests)).collect();
+        assert_eq!(routes, [("claude-sonnet-4-5", 2), ("gemini-2.5-pro", 1)]);
+        let legacy = models.iter().find(|m| m.requested_model == "gemini-2.5-pro").unwrap();
+        assert!(!legacy.remapped);
+        assert!(models.iter().find(|m| m.requested_model == "gpt-4o").unwrap().remapped);
+    }
+
+    #[test]
+    fn test_cost_uses_most_specific_price() {
+        let prices = vec![
+            ModelPrice { model: "gemini-2.5-flash*".to_string(), input_per_million: 0.3, output_per_million: 2.5 },
+            ModelPrice { model: "gemini-2.5-flash-lite*".to_string(), input_per_million: 0.1, output_per_million: 0.4 },
+            ModelPrice { model: "gemini-2.5-flash-lite".to_string(), input_per_million: 0.0, output_per_million: 0.0 },
+        ];
+        assert_eq!(find_price(&prices, "gemini-2.5-flash-thinking").unwrap().input_per_million, 0.3);
+        assert_eq!(find_price(&prices, "gemini-2.5-flash-lite-001").unwrap().input_per_million, 0.1);
+        assert_eq!(estimate_cost(&prices, "gemini-2.5-flash-lite", 1_000_000, 1_000_000), Some(0.0));
+        assert!(estimate_cost(&prices, "claude-sonnet-4-5", 10, 10).is_none());
+
+        let cost = estimate_cost(&prices, "gemini-2.5-flash", 2_000_000, 400_000).unwrap();
+        assert!((cost - 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_key_id_reads_headers_and_query() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(KeyId::from_request(&headers, Some("alt=sse&key=k-1234")), KeyId::from_key(Some("k-1234")));
+        headers.insert("authorization", "Bearer sk-9999".parse().unwrap());
+        assert_eq!(KeyId::from_request(&headers, None).hint, "9999");
+    }
+}
@@ -0,0 +1,151 @@
+// Access Token 过期倒计时
+//
+// 定期向前端推送每个账号 access_token 的剩余有效期与刷新状态 (`account://token-status`)，
+// 前端据此在两次推送之间本地倒计时。刷新状态由反代与配额刷新流程在刷新 Token 时上报。
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::models::Account;
+
+pub const TOKEN_STATUS_EVENT: &str = "account://token-status";
+const TOKEN_STATUS_INTERVAL_SECS: u64 = 30;
+/// 剩余有效期低于该值视为即将过期 (与刷新提前量一致)
+const EXPIRING_THRESHOLD_SECS: i64 = 300;
+
+/// Token 刷新状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenRefreshState {
+    Valid,
+    Expiring,
+    Expired,
+    Refreshing,
+    RefreshFailed,
+}
+
+/// 单个账号的 Token 状态
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenStatus {
+    pub account_id: String,
+    pub email: String,
+    /// access_token 过期时间 (Unix 秒)
+    pub expires_at: i64,
+    /// 剩余有效秒数 (已过期为 0)
+    pub seconds_remaining: i64,
+    pub state: TokenRefreshState,
+    /// 最近一次成功刷新的时间 (仅本次运行期间)
+    pub last_refresh_at: Option<i64>,
+    /// 最近一次刷新失败的原因 (刷新成功后清除)
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RefreshRecord {
+    in_progress: bool,
+    last_refresh_at: Option<i64>,
+    last_error: Option<String>,
+}
+
+/// email -> 刷新记录
+static REFRESH_RECORDS: Lazy<DashMap<String, RefreshRecord>> = Lazy::new(DashMap::new);
+
+/// 上报开始刷新 Token
+pub fn mark_refresh_started(email: &str) {
+    REFRESH_RECORDS.entry(email.to_string()).or_default().in_progress = true;
+}
+
+/// 上报 Token 刷新结果
+pub fn mark_refresh_finished(email: &str, result: Result<(), &str>) {
+    let mut record = REFRESH_RECORDS.entry(email.to_string()).or_default();
+    record.in_progress = false;
+    match result {
+        Ok(()) => {
+            record.last_refresh_at = Some(chrono::Utc::now().timestamp());
+            record.last_error = None;
+        }
+        Err(e) => record.last_error = Some(e.to_string()),
+    }
+}
+
+fn build_status(account: &Account, now: i64, record: Option<&RefreshRecord>) -> TokenStatus {
+    let expires_at = account.token.expiry_timestamp;
+    let seconds_remaining = (expires_at - now).max(0);
+
+    let state = match record {
+        Some(r) if r.in_progress => TokenRefreshState::Refreshing,
+        Some(r) if r.last_error.is_some() => TokenRefreshState::RefreshFailed,
+        _ if seconds_remaining == 0 => TokenRefreshState::Expired,
+        _ if seconds_remaining <= EXPIRING_THRESHOLD_SECS => TokenRefreshState::Expiring,
+        _ => TokenRefreshState::Valid,
+    };
+
+    TokenStatus {
+        account_id: account.id.clone(),
+        email: account.email.clone(),
+        expires_at,
+        seconds_remaining,
+        state,
+        last_refresh_at: record.and_then(|r| r.last_refresh_at),
+        last_error: record.and_then(|r| r.last_error.clone()),
+    }
+}
+
+/// 获取所有账号当前的 Token 状态
+pub fn get_token_statuses() -> Result<Vec<TokenStatus>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let accounts = crate::modules::account::list_accounts()?;
+    Ok(accounts
+        .iter()
+        .map(|a| build_status(a, now, REFRESH_RECORDS.get(&a.email).as_deref()))
+        .collect())
+}
+
+/// 启动 Token 状态推送任务
+pub fn start_token_status_emitter(app_handle: crate::utils::app::AppHandle) {
+    crate::utils::app::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TOKEN_STATUS_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match get_token_statuses() {
+                Ok(statuses) => {
+                    crate::utils::app::emit(&app_handle, TOKEN_STATUS_EVENT, &statuses);
+                }
+                Err(e) => tracing::debug!("Failed to collect token statuses: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenData;
+
+    #[test]
+    fn test_build_status_states() {
+        let token = TokenData::new("at".to_string(), "1//rt".to_string(), 3600, None, None, None);
+        let mut account = Account::new("id-1".to_string(), "a@example.com".to_string(), token);
+        let now = account.token.expiry_timestamp - 3000;
+
+        let status = build_status(&account, now, None);
+        assert_eq!(status.state, TokenRefreshState::Valid);
+        assert_eq!(status.seconds_remaining, 3000);
+
+        assert_eq!(build_status(&account, account.token.expiry_timestamp - 60, None).state, TokenRefreshState::Expiring);
+
+        account.token.expiry_timestamp = now - 1;
+        let status = build_status(&account, now, None);
+        assert_eq!(status.state, TokenRefreshState::Expired);
+        assert_eq!(status.seconds_remaining, 0);
+
+        let refreshing = RefreshRecord { in_progress: true, ..Default::default() };
+        assert_eq!(build_status(&account, now, Some(&refreshing)).state, TokenRefreshState::Refreshing);
+
+        let failed = RefreshRecord { last_error: Some("invalid_grant".to_string()), ..Default::default() };
+        let status = build_status(&account, now, Some(&failed));
+        assert_eq!(status.state, TokenRefreshState::RefreshFailed);
+        assert_eq!(status.last_error.as_deref(), Some("invalid_grant"));
+    }
+}
@@ -0,0 +1,296 @@
+// Token 静态加密
+//
+// 账号文件中的 access_token / refresh_token 以 "enc:v1:<nonce>:<ciphertext>" 形式存储，
+// 主密钥保存在系统钥匙串 (macOS Keychain / Windows Credential Manager / Linux Secret Service)。
+// 钥匙串不可用时 (如没有 Secret Service 的无界面 Linux，或未启用 keychain 特性的构建) 退化为数据目录下
+// 权限 0600 的密钥文件并记录警告；此时复制数据目录即可解密 Token。之后钥匙串可用时，密钥文件会迁移到钥匙串并删除。
+// 密钥存入钥匙串后会留下标记文件；之后钥匙串无法访问或条目丢失时直接报错，不会生成新密钥导致已加密的 Token 无法解密。
+// 旧版明文 Token 读取时原样返回，下次保存或启动迁移时自动加密。
+
+use base64::{engine::general_purpose, Engine as _};
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::utils::crypto::{self, EncryptedBlob};
+use crate::modules::i18n::tr;
+
+const ENC_PREFIX: &str = "enc:v1:";
+const KEY_FILE_NAME: &str = ".token_key";
+const KEYCHAIN_MARKER_NAME: &str = ".token_key_keychain";
+#[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+const KEYCHAIN_SERVICE: &str = "com.lbjlaq.antigravity-tools";
+#[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+const KEYCHAIN_USER: &str = "token-encryption-key";
+
+static MASTER_KEY: OnceCell<[u8; 32]> = OnceCell::new();
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], String> {
+    general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("主密钥格式错误: {}", e))?
+        .try_into()
+        .map_err(|_| "主密钥长度错误".to_string())
+}
+
+#[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn load_or_create_keychain_key() -> Result<Option<[u8; 32]>, String> {
+    let entry = match keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER) {
+        Ok(entry) => entry,
+        Err(e) => return keychain_unavailable(format!("访问系统钥匙串失败: {}", e)),
+    };
+    match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded).map(Some),
+        Err(keyring::Error::NoEntry) => {
+            // 已有密钥文件时 (之前钥匙串不可用) 沿用其中的密钥，避免已加密的 Token 无法解密
+            let path = key_file_path()?;
+            let existing = if path.exists() { Some(load_or_create_key_file()?) } else { None };
+            // 密钥曾保存在钥匙串中但条目已丢失: 新密钥无法解密已有 Token
+            if existing.is_none() && keychain_marker_path()?.exists() {
+                return Err(lost_keychain_key_error("系统钥匙串中的 Token 加密密钥已丢失"));
+            }
+            let key = existing.unwrap_or_else(crypto::generate_key);
+            match entry.set_password(&general_purpose::STANDARD.encode(key)) {
+                Ok(()) => {
+                    if let Err(e) = std::fs::write(keychain_marker_path()?, "token key is stored in the system keychain\n") {
+                        crate::modules::logger::log_warn(&format!("写入钥匙串标记文件失败: {}", e));
+                    }
+                    if existing.is_some() {
+                        match std::fs::remove_file(&path) {
+                            Ok(()) => crate::modules::logger::log_info("已将 Token 加密密钥从密钥文件迁移到系统钥匙串"),
+                            Err(e) => crate::modules::logger::log_warn(&format!("删除已迁移的密钥文件失败: {}", e)),
+                        }
+                    }
+                    Ok(Some(key))
+                }
+                Err(e) => {
+                    crate::modules::logger::log_warn(&format!("写入系统钥匙串失败，改用密钥文件: {}", e));
+                    Ok(None)
+                }
+            }
+        }
+        Err(e) => keychain_unavailable(format!("读取系统钥匙串失败: {}", e)),
+    }
+}
+
+/// Linux 上 Secret Service 可能不存在 (无界面服务器)，此时退回密钥文件；其他平台视为错误
+#[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn keychain_unavailable(message: String) -> Result<Option<[u8; 32]>, String> {
+    if cfg!(target_os = "linux") {
+        crate::modules::logger::log_warn(&format!("{}，改用密钥文件", message));
+        Ok(None)
+    } else {
+        Err(message)
+    }
+}
+
+/// 密钥文件位于数据根目录，所有档案共用同一主密钥
+fn key_file_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_root_data_dir()?.join(KEY_FILE_NAME))
+}
+
+/// 标记文件: 主密钥已保存在系统钥匙串中 (存在时不允许改用新生成的密钥文件)
+fn keychain_marker_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_root_data_dir()?.join(KEYCHAIN_MARKER_NAME))
+}
+
+fn lost_keychain_key_error(reason: &str) -> String {
+    format!(
+        "{}，已加密的 Token 无法解密。请恢复钥匙串访问 (或启用 keychain 特性) 后重试；确认放弃现有 Token 时删除数据目录下的 {} 并重新登录账号",
+        reason, KEYCHAIN_MARKER_NAME
+    )
+}
+
+fn load_or_create_key_file() -> Result<[u8; 32], String> {
+    let path = key_file_path()?;
+    if path.exists() {
+        let encoded = std::fs::read_to_string(&path).map_err(|e| format!("读取密钥文件失败: {}", e))?;
+        return decode_key(&encoded);
+    }
+
+    if keychain_marker_path()?.exists() {
+        return Err(lost_keychain_key_error("无法访问保存 Token 加密密钥的系统钥匙串"));
+    }
+
+    let key = crypto::generate_key();
+    write_new_key_file(&path, &general_purpose::STANDARD.encode(key)).map_err(|e| format!("写入密钥文件失败: {}", e))?;
+    crate::modules::logger::log_info("已生成 Token 加密密钥文件");
+    Ok(key)
+}
+
+/// 创建密钥文件 (Unix 下创建时即为 0600，已存在时失败而不是覆盖)
+fn write_new_key_file(path: &std::path::Path, encoded: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(encoded.as_bytes())?;
+    file.sync_all()
+}
+
+/// 获取主密钥 (首次调用时从钥匙串/密钥文件加载，不存在则生成)
+fn master_key() -> Result<&'static [u8; 32], String> {
+    MASTER_KEY.get_or_try_init(|| {
+        #[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        {
+            if let Some(key) = load_or_create_keychain_key()? {
+                return Ok(key);
+            }
+        }
+        crate::modules::logger::log_warn("Token 主密钥保存在数据目录的密钥文件中，复制数据目录即可解密 Token");
+        load_or_create_key_file()
+    })
+}
+
+/// 读取系统钥匙串中本应用服务下的条目 (供配置中的 `keychain:NAME` 凭证引用使用)
+#[cfg(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn read_keychain_entry(name: &str) -> Result<String, String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, name).map_err(|e| format!("访问系统钥匙串失败: {}", e))?;
+    match entry.get_password() {
+        Ok(value) => Ok(value),
+        Err(keyring::Error::NoEntry) => Err(tr("backend.errors.keychain.no_entry", &[("name", name)])),
+        Err(e) => Err(tr("backend.errors.keychain.read_failed", &[("error", &e.to_string())])),
+    }
+}
+
+#[cfg(not(all(feature = "keychain", any(target_os = "macos", target_os = "windows", target_os = "linux"))))]
+pub fn read_keychain_entry(name: &str) -> Result<String, String> {
+    Err(tr("backend.errors.keychain.unsupported", &[("name", name)]))
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENC_PREFIX)
+}
+
+fn encrypt_secret_with(key: &[u8; 32], plain: &str) -> Result<String, String> {
+    if is_encrypted(plain) {
+        return Ok(plain.to_string());
+    }
+    let blob = crypto::encrypt_with_key(key, plain.as_bytes())?;
+    Ok(format!("{}{}:{}", ENC_PREFIX, blob.nonce, blob.ciphertext))
+}
+
+fn decrypt_secret_with(key: &[u8; 32], value: &str) -> Result<String, String> {
+    let Some(rest) = value.strip_prefix(ENC_PREFIX) else {
+        // 旧版明文
+        return Ok(value.to_string());
+    };
+    let (nonce, ciphertext) = rest.split_once(':').ok_or_else(|| tr("backend.errors.invalid_encrypted_token", &[]))?;
+    let blob = EncryptedBlob {
+        kdf: "none".to_string(),
+        salt: String::new(),
+        nonce: nonce.to_string(),
+        ciphertext: ciphertext.to_string(),
+    };
+    let plain = crypto::decrypt_with_key(key, &blob)?;
+    String::from_utf8(plain).map_err(|e| format!("Token 解码失败: {}", e))
+}
+
+/// 加密单个 Token
+pub fn encrypt_secret(plain: &str) -> Result<String, String> {
+    encrypt_secret_with(master_key()?, plain)
+}
+
+/// 解密单个 Token (明文原样返回)
+pub fn decrypt_secret(value: &str) -> Result<String, String> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+    decrypt_secret_with(master_key()?, value)
+}
+
+fn transform_token_fields(account: &mut Value, f: impl Fn(&str) -> Result<String, String>) -> Result<(), String> {
+    if let Some(token) = account.get_mut("token").and_then(|t| t.as_object_mut()) {
+        for field in ["access_token", "refresh_token"] {
+            if let Some(Value::String(s)) = token.get_mut(field) {
+                *s = f(s)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 加密账号 JSON 中的 Token 字段
+pub fn encrypt_token_fields(account: &mut Value) -> Result<(), String> {
+    transform_token_fields(account, encrypt_secret)
+}
+
+/// 解密账号 JSON 中的 Token 字段
+pub fn decrypt_token_fields(account: &mut Value) -> Result<(), String> {
+    transform_token_fields(account, decrypt_secret)
+}
+
+/// 将仍为明文的账号迁移为加密存储，返回迁移的账号数
+pub fn migrate_plaintext_accounts() -> Result<usize, String> {
+    let data_dir = crate::modules::account::get_data_dir()?;
+    let mut migrated = 0;
+
+    for mut account in crate::modules::account_store::list_accounts(&data_dir)? {
+        let has_plaintext = ["access_token", "refresh_token"].iter().any(|f| {
+            account["token"][*f].as_str().map(|s| !is_encrypted(s)).unwrap_or(false)
+        });
+        if !has_plaintext {
+            continue;
+        }
+
+        encrypt_token_fields(&mut account)?;
+        crate::modules::account_store::write_account(&data_dir, &account)?;
+        migrated += 1;
+    }
+
+    if migrated > 0 {
+        crate::modules::logger::log_info(&format!("已将 {} 个账号的 Token 迁移为加密存储", migrated));
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_roundtrip_and_plaintext_passthrough() {
+        let key = [3u8; 32];
+        let enc = encrypt_secret_with(&key, "1//refresh").unwrap();
+        assert!(is_encrypted(&enc));
+        assert!(!enc.contains("1//refresh"));
+        // 已加密的值不会被二次加密
+        assert_eq!(encrypt_secret_with(&key, &enc).unwrap(), enc);
+        assert_eq!(decrypt_secret_with(&key, &enc).unwrap(), "1//refresh");
+        assert_eq!(decrypt_secret_with(&key, "plain").unwrap(), "plain");
+        assert!(decrypt_secret_with(&[4u8; 32], &enc).is_err());
+    }
+
+    #[test]
+    fn test_transform_token_fields() {
+        let mut account = serde_json::json!({
+            "email": "a@example.com",
+            "token": {"access_token": "at", "refresh_token": "rt", "expires_in": 3600}
+        });
+        transform_token_fields(&mut account, |s| Ok(format!("x{}", s))).unwrap();
+        assert_eq!(account["token"]["access_token"], "xat");
+        assert_eq!(account["token"]["refresh_token"], "xrt");
+        assert_eq!(account["token"]["expires_in"], 3600);
+    }
+
+    #[test]
+    fn test_write_new_key_file_is_private_and_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("ag_token_key_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(KEY_FILE_NAME);
+
+        write_new_key_file(&path, "first").unwrap();
+        assert!(write_new_key_file(&path, "second").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,95 @@
 use tauri::{
     image::Image,
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     Manager, Runtime, Emitter, Listener,
 };
+use crate::commands::proxy::ProxyServiceState;
 use crate::modules;
 
-pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+/// 快速切换子菜单最多列出的账号数
+const MAX_SWITCH_ACCOUNTS: usize = 30;
+/// 反代状态 (端口、账号池、错误数) 的刷新间隔
+const STATUS_REFRESH_SECS: u64 = 15;
+const SWITCH_TO_PREFIX: &str = "switch_to:";
+
+/// 托盘展示的反代状态
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct ProxySnapshot {
+    /// 运行中的监听端口 (未运行为 None)
+    port: Option<u16>,
+    pool: usize,
+    recent_errors: usize,
+}
+
+async fn proxy_snapshot<R: Runtime>(app: &tauri::AppHandle<R>) -> ProxySnapshot {
+    let mut snapshot = ProxySnapshot::default();
+    let Some(state) = app.try_state::<ProxyServiceState>() else { return snapshot };
+    if let Some(instance) = state.instance.read().await.as_ref() {
+        snapshot.port = Some(instance.config.port);
+        snapshot.pool = instance.token_manager.len();
+    }
+    if let Some(monitor) = state.monitor.read().await.as_ref() {
+        snapshot.recent_errors = monitor.live.recent_errors(chrono::Utc::now().timestamp_millis());
+    }
+    snapshot
+}
+
+/// 启动 / 停止反代服务 (托盘菜单与全局快捷键共用)，返回切换后的监听端口 (已停止为 None)
+pub(crate) async fn toggle_proxy(app: &tauri::AppHandle) -> Result<Option<u16>, String> {
+    let state = app.state::<ProxyServiceState>();
+    let running = state.instance.read().await.is_some();
+    let result = if running {
+        crate::commands::proxy::stop_proxy_service(state).await.map(|_| None)
+    } else {
+        match modules::load_app_config() {
+            Ok(config) => crate::commands::proxy::start_proxy_service(config.proxy, state, app.clone())
+                .await
+                .map(|status| Some(status.port)),
+            Err(e) => Err(e),
+        }
+    };
+    match &result {
+        Ok(_) => {
+            modules::logger::log_info(if running { "托盘: 已停止反代服务" } else { "托盘: 已启动反代服务" });
+            let _ = app.emit("tray://proxy-toggled", !running);
+        }
+        Err(e) => modules::logger::log_error(&format!("托盘切换反代服务失败: {}", e)),
+    }
+    update_tray_menus(app);
+    result
+}
+
+/// 托盘切换到指定账号
+async fn switch_to(app: &tauri::AppHandle, account_id: String) {
+    match modules::switch_account(&account_id).await {
+        Ok(()) => {
+            let _ = app.emit("tray://account-switched", account_id);
+            update_tray_menus(app);
+        }
+        Err(e) => modules::logger::log_error(&format!("托盘切换账号失败: {}", e)),
+    }
+}
+
+/// 显示并聚焦主窗口 (托盘、重复启动、深度链接共用)
+pub fn show_main_window<R: Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
+    }
+}
+
+/// 隐藏窗口，只保留托盘 (反代服务继续在后台运行)
+pub fn hide_to_tray<R: Runtime>(window: &tauri::Window<R>) {
+    let _ = window.hide();
+    #[cfg(target_os = "macos")]
+    window.app_handle().set_activation_policy(tauri::ActivationPolicy::Accessory).unwrap_or(());
+}
+
+pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
     // 1. 加载配置获取语言设置
     let config = modules::load_app_config().unwrap_or_default();
     let texts = modules::i18n::get_tray_texts(&config.language);
@@ -59,17 +142,16 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
         .on_menu_event(move |app, event| {
             let app_handle = app.clone();
             match event.id().as_ref() {
-                "show" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        #[cfg(target_os = "macos")]
-                        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-                    }
-                }
+                "show" => show_main_window(app),
                 "quit" => {
                     app.exit(0);
                 }
+                "toggle_proxy" => {
+                    tauri::async_runtime::spawn(async move {
+                        let _ = toggle_proxy(&app_handle).await;
+                    });
+                }
+
                 "refresh_curr" => {
                     // 异步执行刷新
                     tauri::async_runtime::spawn(async move {
@@ -121,7 +203,14 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
                          }
                     });
                 }
-                _ => {}
+                other => {
+                    if let Some(account_id) = other.strip_prefix(SWITCH_TO_PREFIX) {
+                        let account_id = account_id.to_string();
+                        tauri::async_runtime::spawn(async move {
+                            switch_to(&app_handle, account_id).await;
+                        });
+                    }
+                }
             }
         })
         .on_tray_icon_event(|tray, event| {
@@ -130,13 +219,7 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
                 ..
             } = event
             {
-               let app = tray.app_handle();
-               if let Some(window) = app.get_webview_window("main") {
-                   let _ = window.show();
-                   let _ = window.set_focus();
-                   #[cfg(target_os = "macos")]
-                   app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-               }
+               show_main_window(tray.app_handle());
             }
         })
         .build(app)?;
@@ -154,6 +237,21 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
         update_tray_menus(&handle);
     });
 
+    // 反代状态变化 (界面启停、错误数) 时刷新托盘
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = ProxySnapshot::default();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(STATUS_REFRESH_SECS));
+        loop {
+            interval.tick().await;
+            let snapshot = proxy_snapshot(&handle).await;
+            if snapshot != last {
+                last = snapshot;
+                update_tray_menus(&handle);
+            }
+        }
+    });
+
     Ok(())
 }
 
@@ -171,8 +269,8 @@ pub fn update_tray_menus<R: Runtime>(app: &tauri::AppHandle<R>) {
          let mut menu_lines = Vec::new();
          let mut user_text = format!("{}: {}", texts.current, texts.no_account);
 
-         if let Some(id) = current {
-             if let Ok(account) = modules::load_account(&id) {
+         if let Some(id) = &current {
+             if let Ok(account) = modules::load_account(id) {
                  user_text = format!("{}: {}", texts.current, account.email);
                  
                  if let Some(q) = account.quota {
@@ -207,48 +305,99 @@ pub fn update_tray_menus<R: Runtime>(app: &tauri::AppHandle<R>) {
              menu_lines.push(texts.unknown_quota.clone());
          };
 
-         // 重新构建菜单项
-         let info_user = MenuItem::with_id(&app_clone, "info_user", &user_text, false, None::<&str>);
-         
-         // 动态创建额度项
-         let mut quota_items = Vec::new();
-         for (i, line) in menu_lines.iter().enumerate() {
-             let item = MenuItem::with_id(&app_clone, format!("info_quota_{}", i), line, false, None::<&str>);
-             if let Ok(item) = item {
-                 quota_items.push(item);
-             }
-         }
-         
-         let switch_next = MenuItem::with_id(&app_clone, "switch_next", &texts.switch_next, true, None::<&str>);
-         let refresh_curr = MenuItem::with_id(&app_clone, "refresh_curr", &texts.refresh_current, true, None::<&str>);
-         
-         let show_i = MenuItem::with_id(&app_clone, "show", &texts.show_window, true, None::<&str>);
-         let quit_i = MenuItem::with_id(&app_clone, "quit", &texts.quit, true, None::<&str>);
-         
-         if let (Ok(i_u), Ok(s_n), Ok(r_c), Ok(s), Ok(q)) = (info_user, switch_next, refresh_curr, show_i, quit_i) {
-             let sep1 = PredefinedMenuItem::separator(&app_clone).ok();
-             let sep2 = PredefinedMenuItem::separator(&app_clone).ok();
-             let sep3 = PredefinedMenuItem::separator(&app_clone).ok();
-             
-             let mut items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&i_u];
-             // 添加动态的额度项
-             for item in &quota_items {
-                 items.push(item);
-             }
-             
-             if let Some(ref s) = sep1 { items.push(s); }
-             items.push(&s_n);
-             items.push(&r_c);
-             if let Some(ref s) = sep2 { items.push(s); }
-             items.push(&s);
-             if let Some(ref s) = sep3 { items.push(s); }
-             items.push(&q);
-             
-             if let Ok(menu) = Menu::with_items(&app_clone, &items) {
+         let proxy = proxy_snapshot(&app_clone).await;
+         let accounts = modules::list_accounts().unwrap_or_default();
+
+         match build_menu(&app_clone, &texts, &user_text, &menu_lines, proxy, &accounts, current.as_deref()) {
+             Ok(menu) => {
                  if let Some(tray) = app_clone.tray_by_id("main") {
                      let _ = tray.set_menu(Some(menu));
+                     // 错误角标 (macOS / Linux 显示在图标旁，Windows 只显示在提示文字中)
+                     let badge = (proxy.recent_errors > 0).then(|| format!("⚠ {}", proxy.recent_errors));
+                     let _ = tray.set_title(badge.as_deref());
+                     let mut tooltip = format!("Antigravity Tools · {}: {}", texts.proxy, proxy_status_text(&texts, proxy));
+                     if let Some(badge) = &badge {
+                         tooltip.push_str(&format!(" · {}", badge));
+                     }
+                     let _ = tray.set_tooltip(Some(tooltip));
                  }
              }
+             Err(e) => modules::logger::log_error(&format!("构建托盘菜单失败: {}", e)),
          }
     });
 }
+
+fn proxy_status_text(texts: &modules::i18n::TrayTexts, proxy: ProxySnapshot) -> String {
+    match proxy.port {
+        Some(port) => format!("{} :{}", texts.proxy_running, port),
+        None => texts.proxy_stopped.clone(),
+    }
+}
+
+fn build_menu<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    texts: &modules::i18n::TrayTexts,
+    user_text: &str,
+    quota_lines: &[String],
+    proxy: ProxySnapshot,
+    accounts: &[crate::models::Account],
+    current: Option<&str>,
+) -> tauri::Result<Menu<R>> {
+    let info_user = MenuItem::with_id(app, "info_user", user_text, false, None::<&str>)?;
+    let quota_items = quota_lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| MenuItem::with_id(app, format!("info_quota_{}", i), line, false, None::<&str>))
+        .collect::<tauri::Result<Vec<_>>>()?;
+
+    // 反代状态与启停
+    let proxy_status = format!("{}: {}", texts.proxy, proxy_status_text(texts, proxy));
+    let mut proxy_items = vec![MenuItem::with_id(app, "info_proxy", &proxy_status, false, None::<&str>)?];
+    if proxy.port.is_some() {
+        let pool = format!("{}: {}", texts.pool, proxy.pool);
+        proxy_items.push(MenuItem::with_id(app, "info_pool", &pool, false, None::<&str>)?);
+    }
+    if proxy.recent_errors > 0 {
+        let errors = format!("⚠ {}: {}", texts.recent_errors, proxy.recent_errors);
+        proxy_items.push(MenuItem::with_id(app, "info_errors", &errors, false, None::<&str>)?);
+    }
+    let toggle_text = if proxy.port.is_some() { &texts.stop_proxy } else { &texts.start_proxy };
+    proxy_items.push(MenuItem::with_id(app, "toggle_proxy", toggle_text, true, None::<&str>)?);
+
+    // 账号快速切换
+    let account_items = accounts
+        .iter()
+        .take(MAX_SWITCH_ACCOUNTS)
+        .map(|a| {
+            let id = format!("{}{}", SWITCH_TO_PREFIX, a.id);
+            CheckMenuItem::with_id(app, id, &a.email, true, current == Some(a.id.as_str()), None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let account_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        account_items.iter().map(|i| i as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let switch_menu = Submenu::with_id_and_items(app, "switch_account", &texts.switch_account, !accounts.is_empty(), &account_refs)?;
+
+    let switch_next = MenuItem::with_id(app, "switch_next", &texts.switch_next, true, None::<&str>)?;
+    let refresh_curr = MenuItem::with_id(app, "refresh_curr", &texts.refresh_current, true, None::<&str>)?;
+    let show_i = MenuItem::with_id(app, "show", &texts.show_window, true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "quit", &texts.quit, true, None::<&str>)?;
+    let sep1 = PredefinedMenuItem::separator(app)?;
+    let sep2 = PredefinedMenuItem::separator(app)?;
+    let sep3 = PredefinedMenuItem::separator(app)?;
+    let sep4 = PredefinedMenuItem::separator(app)?;
+
+    let mut items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&info_user];
+    items.extend(quota_items.iter().map(|i| i as &dyn tauri::menu::IsMenuItem<R>));
+    items.push(&sep1);
+    items.extend(proxy_items.iter().map(|i| i as &dyn tauri::menu::IsMenuItem<R>));
+    items.push(&sep2);
+    items.push(&switch_next);
+    items.push(&switch_menu);
+    items.push(&refresh_curr);
+    items.push(&sep3);
+    items.push(&show_i);
+    items.push(&sep4);
+    items.push(&quit_i);
+
+    Menu::with_items(app, &items)
+}
@@ -0,0 +1,337 @@
+// 远程访问隧道
+//
+// 在外也能访问家中电脑上的反代，无需在路由器上做端口转发:
+// - SSH: `ssh -N -R [bind:]<remote_port>:127.0.0.1:<port> user@host`，把本机端口转发到自己的服务器
+// - Cloudflared: `cloudflared tunnel --url http://127.0.0.1:<port>`，分配随机的 https://*.trycloudflare.com 地址
+// 隧道进程由本程序启动和结束，异常退出时记录最后一行输出作为错误。
+// 经隧道转发的请求来自本机，因此隧道运行期间认证中间件强制要求 API Key (见 `is_active`)，
+// 认证模式为关闭或未设置 API Key 时拒绝建立隧道。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::modules::logger;
+use crate::proxy::config::{ProxyAuthMode, TunnelConfig, TunnelProvider};
+use crate::modules::i18n::tr;
+
+/// SSH 启动后在此时间内未退出即视为转发建立成功 (已开启 ExitOnForwardFailure)
+const SSH_READY_SECS: u64 = 5;
+/// 等待 cloudflared 分配地址的时间
+const CLOUDFLARED_READY_SECS: u64 = 30;
+
+static CLOUDFLARE_URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"https://[a-z0-9-]+\.trycloudflare\.com").unwrap());
+
+/// 隧道进程运行中 (认证中间件据此强制要求 API Key)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// 每次建立隧道递增，避免旧进程退出时覆盖新隧道的状态
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static STATE: Lazy<Mutex<TunnelState>> = Lazy::new(|| Mutex::new(TunnelState::default()));
+
+#[derive(Default)]
+struct TunnelState {
+    status: TunnelStatus,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+/// 隧道状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TunnelStatus {
+    pub running: bool,
+    pub provider: Option<TunnelProvider>,
+    /// 对外访问地址
+    pub public_url: Option<String>,
+    pub started_at: Option<i64>,
+    /// 建立失败或异常退出的原因
+    pub last_error: Option<String>,
+}
+
+/// 隧道是否正在运行
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+pub fn status() -> TunnelStatus {
+    STATE.lock().unwrap().status.clone()
+}
+
+/// 远程访问必须经过 API Key 认证
+fn check_auth(auth_mode: &ProxyAuthMode, api_key: &str) -> Result<(), String> {
+    if api_key.trim().is_empty() {
+        return Err(tr("backend.errors.tunnel.api_key_required", &[]));
+    }
+    if matches!(auth_mode, ProxyAuthMode::Off) {
+        return Err(tr("backend.errors.tunnel.auth_required", &[]));
+    }
+    Ok(())
+}
+
+fn ssh_host(target: &str) -> &str {
+    target.rsplit_once('@').map_or(target, |(_, host)| host)
+}
+
+/// ssh 参数 (目标不允许以 `-` 开头，避免被当作选项)
+fn ssh_args(config: &TunnelConfig, local_port: u16) -> Result<Vec<String>, String> {
+    let target = config.ssh_target.trim();
+    if target.is_empty() || target.starts_with('-') || target.contains(char::is_whitespace) {
+        return Err(tr("backend.errors.tunnel.invalid_ssh_target", &[]));
+    }
+    let bind = config.remote_bind_address.trim();
+    let forward = if bind.is_empty() {
+        format!("{}:127.0.0.1:{}", config.remote_port, local_port)
+    } else {
+        format!("{}:{}:127.0.0.1:{}", bind, config.remote_port, local_port)
+    };
+
+    let mut args: Vec<String> = [
+        "-N",
+        "-o", "ExitOnForwardFailure=yes",
+        "-o", "ServerAliveInterval=30",
+        "-o", "ServerAliveCountMax=3",
+        // 不弹出密码输入，需使用密钥或 ssh-agent
+        "-o", "BatchMode=yes",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    args.extend(["-p".to_string(), config.ssh_port.to_string()]);
+    if let Some(identity) = config.ssh_identity_file.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        args.extend(["-i".to_string(), identity.to_string()]);
+    }
+    args.extend(["-R".to_string(), forward, target.to_string()]);
+    Ok(args)
+}
+
+fn ssh_public_url(config: &TunnelConfig) -> String {
+    match config.public_url.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => format!("http://{}:{}", ssh_host(config.ssh_target.trim()), config.remote_port),
+    }
+}
+
+fn find_cloudflare_url(line: &str) -> Option<String> {
+    CLOUDFLARE_URL_RE.find(line).map(|m| m.as_str().to_string())
+}
+
+fn tunnel_command(config: &TunnelConfig, local_port: u16) -> Result<tokio::process::Command, String> {
+    let (program, args) = match config.provider {
+        TunnelProvider::Ssh => ("ssh".to_string(), ssh_args(config, local_port)?),
+        TunnelProvider::Cloudflared => {
+            let program = config
+                .cloudflared_path
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or("cloudflared")
+                .to_string();
+            let url = format!("http://127.0.0.1:{}", local_port);
+            (program, vec!["tunnel".to_string(), "--no-autoupdate".to_string(), "--url".to_string(), url])
+        }
+    };
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    Ok(cmd)
+}
+
+/// 把进程输出逐行发送到通道
+fn forward_lines<R: AsyncRead + Unpin + Send + 'static>(reader: R, tx: mpsc::UnboundedSender<String>) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = line.trim().to_string();
+            if !line.is_empty() && tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// 等待隧道就绪，返回 cloudflared 分配的地址 (SSH 为 None)
+async fn wait_ready(
+    child: &mut tokio::process::Child,
+    lines: &mut mpsc::UnboundedReceiver<String>,
+    provider: TunnelProvider,
+) -> Result<Option<String>, String> {
+    let timeout_secs = match provider {
+        TunnelProvider::Ssh => SSH_READY_SECS,
+        TunnelProvider::Cloudflared => CLOUDFLARED_READY_SECS,
+    };
+    let deadline = tokio::time::sleep(Duration::from_secs(timeout_secs));
+    tokio::pin!(deadline);
+    let mut last_line = String::new();
+    loop {
+        tokio::select! {
+            Some(line) = lines.recv() => {
+                if provider == TunnelProvider::Cloudflared {
+                    if let Some(url) = find_cloudflare_url(&line) {
+                        return Ok(Some(url));
+                    }
+                }
+                last_line = line;
+            }
+            status = child.wait() => {
+                let code = status.ok().and_then(|s| s.code()).unwrap_or(-1);
+                return Err(tr("backend.errors.tunnel.exited", &[("code", &code.to_string()), ("output", &last_line)]));
+            }
+            _ = &mut deadline => {
+                return match provider {
+                    TunnelProvider::Ssh => Ok(None),
+                    TunnelProvider::Cloudflared => Err(tr("backend.errors.tunnel.cloudflared_timeout", &[("output", &last_line)])),
+                };
+            }
+        }
+    }
+}
+
+/// 隧道进程结束 (被停止时 error 为 None)
+fn finish(generation: u64, error: Option<String>) {
+    let mut state = STATE.lock().unwrap();
+    if GENERATION.load(Ordering::SeqCst) != generation {
+        return;
+    }
+    ACTIVE.store(false, Ordering::SeqCst);
+    state.stop = None;
+    state.status.running = false;
+    if let Some(error) = &error {
+        logger::log_warn(&format!("[Tunnel] 隧道已断开: {}", error));
+    }
+    state.status.last_error = error;
+}
+
+/// 建立隧道，把本机反代端口暴露到远程地址 (已有隧道时先停止)
+pub async fn start(config: &TunnelConfig, local_port: u16, auth_mode: &ProxyAuthMode, api_key: &str) -> Result<TunnelStatus, String> {
+    check_auth(auth_mode, api_key)?;
+    stop();
+
+    let mut cmd = tunnel_command(config, local_port)?;
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // 进程启动前即强制认证，避免隧道先于认证生效
+    ACTIVE.store(true, Ordering::SeqCst);
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let error = format!("启动隧道进程失败: {}", e);
+            finish(generation, Some(error.clone()));
+            return Err(error);
+        }
+    };
+    let (tx, mut lines) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, tx);
+    }
+
+    let public_url = match wait_ready(&mut child, &mut lines, config.provider).await {
+        Ok(Some(url)) => url,
+        Ok(None) => ssh_public_url(config),
+        Err(e) => {
+            finish(generation, Some(e.clone()));
+            return Err(e);
+        }
+    };
+
+    let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+    let status = {
+        let mut state = STATE.lock().unwrap();
+        state.stop = Some(stop_tx);
+        state.status = TunnelStatus {
+            running: true,
+            provider: Some(config.provider),
+            public_url: Some(public_url.clone()),
+            started_at: Some(chrono::Utc::now().timestamp()),
+            last_error: None,
+        };
+        state.status.clone()
+    };
+    logger::log_info(&format!("[Tunnel] 远程访问已开启: {} -> 127.0.0.1:{}", public_url, local_port));
+
+    tokio::spawn(async move {
+        let mut last_line = String::new();
+        let error = loop {
+            tokio::select! {
+                Some(line) = lines.recv() => last_line = line,
+                status = child.wait() => {
+                    let code = status.ok().and_then(|s| s.code()).unwrap_or(-1);
+                    break Some(format!("隧道进程已退出 (退出码 {}): {}", code, last_line));
+                }
+                _ = &mut stop_rx => {
+                    let _ = child.kill().await;
+                    break None;
+                }
+            }
+        };
+        finish(generation, error);
+    });
+    Ok(status)
+}
+
+/// 停止隧道 (进程结束后才解除强制认证)
+pub fn stop() -> TunnelStatus {
+    let mut state = STATE.lock().unwrap();
+    if let Some(stop) = state.stop.take() {
+        let _ = stop.send(());
+        logger::log_info("[Tunnel] 远程访问已关闭");
+    }
+    state.status.running = false;
+    state.status.last_error = None;
+    state.status.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_args() {
+        let mut config = TunnelConfig {
+            ssh_target: "me@vps.example.com".to_string(),
+            remote_port: 9000,
+            ..Default::default()
+        };
+        let args = ssh_args(&config, 8045).unwrap();
+        assert_eq!(&args[args.len() - 3..], ["-R", "9000:127.0.0.1:8045", "me@vps.example.com"]);
+        assert!(args.windows(2).any(|w| w == ["-p", "22"]));
+        assert_eq!(ssh_public_url(&config), "http://vps.example.com:9000");
+
+        config.remote_bind_address = "0.0.0.0".to_string();
+        config.ssh_identity_file = Some("/home/me/.ssh/id_ed25519".to_string());
+        config.public_url = Some("https://ag.example.com/".to_string());
+        let args = ssh_args(&config, 8045).unwrap();
+        assert!(args.contains(&"0.0.0.0:9000:127.0.0.1:8045".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-i", "/home/me/.ssh/id_ed25519"]));
+        assert_eq!(ssh_public_url(&config), "https://ag.example.com");
+
+        config.ssh_target = "-oProxyCommand=evil".to_string();
+        assert!(ssh_args(&config, 8045).is_err());
+        config.ssh_target = " ".to_string();
+        assert!(ssh_args(&config, 8045).is_err());
+    }
+
+    #[test]
+    fn test_find_cloudflare_url() {
+        let line = "2024-01-01T00:00:00Z INF |  https://quiet-river-1234.trycloudflare.com                          |";
+        assert_eq!(find_cloudflare_url(line).as_deref(), Some("https://quiet-river-1234.trycloudflare.com"));
+        assert!(find_cloudflare_url("INF Requesting new quick Tunnel on trycloudflare.com...").is_none());
+    }
+
+    #[test]
+    fn test_remote_access_requires_auth() {
+        assert!(check_auth(&ProxyAuthMode::Auto, "sk-1").is_ok());
+        assert!(check_auth(&ProxyAuthMode::Auto, " ").is_err());
+        assert!(check_auth(&ProxyAuthMode::Off, "sk-1").is_err());
+    }
+}
@@ -1,10 +1,24 @@
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
+use crate::modules::config_schema::UPDATE_SETTINGS_MIGRATIONS;
 use crate::modules::logger;
+use crate::modules::i18n::tr;
 
-const GITHUB_API_URL: &str = "https://api.github.com/repos/lbjlaq/Antigravity-Manager/releases/latest";
+const GITHUB_API_BASE: &str = "https://api.github.com";
+const RELEASES_PATH: &str = "/repos/lbjlaq/Antigravity-Manager/releases?per_page=30";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 24;
+const RELEASE_CACHE_FILE: &str = "update_cache.json";
+/// Emitted with the `UpdateInfo` when a background check finds a newer version
+pub const UPDATE_AVAILABLE_EVENT: &str = "update://available";
+/// Backoff when rate limited without a reset hint (doubles per consecutive hit)
+const MIN_BACKOFF_SECS: u64 = 60;
+const MAX_BACKOFF_SECS: u64 = 6 * 3600;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInfo {
@@ -14,6 +28,54 @@ pub struct UpdateInfo {
     pub download_url: String, // 原为 release_url
     pub release_notes: String,
     pub published_at: String,
+    /// Installer asset for the current platform (None if the release has no matching asset)
+    #[serde(default)]
+    pub asset: Option<UpdateAsset>,
+    /// The latest release is a pre-release (beta channel only)
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Notes of every release newer than the installed version (newest first)
+    #[serde(default)]
+    pub changelog: Vec<ReleaseNote>,
+    /// The user chose to skip this version (automatic reminders stay quiet; newer releases are announced again)
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+/// Release notes of a single version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReleaseNote {
+    pub version: String,
+    pub published_at: String,
+    pub notes: String,
+}
+
+/// A downloadable release asset
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UpdateAsset {
+    pub name: String,
+    pub url: String,
+    pub size: u64,
+    /// SHA-256 published by GitHub for this asset (lowercase hex)
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Checksum file covering this asset (`<name>.sha256` or SHA256SUMS)
+    #[serde(default)]
+    pub checksums_url: Option<String>,
+    /// Detached signature (`<name>.sig`)
+    #[serde(default)]
+    pub signature_url: Option<String>,
+}
+
+/// Release channel to follow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// Only stable releases
+    #[default]
+    Stable,
+    /// Stable releases and pre-releases
+    Beta,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +84,20 @@ pub struct UpdateSettings {
     pub last_check_time: u64,
     #[serde(default = "default_check_interval")]
     pub check_interval_hours: u64,
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    /// Base URL replacing `https://api.github.com` (mirror / reverse proxy); None = official API
+    #[serde(default)]
+    pub github_api_mirror: Option<String>,
+    /// Prefix for asset download URLs, e.g. `https://ghproxy.net` (gh-proxy style: `{prefix}/{url}`)
+    #[serde(default)]
+    pub download_mirror: Option<String>,
+    /// Release the user dismissed with "skip this version"
+    #[serde(default)]
+    pub skipped_version: Option<String>,
+    /// Version the user rolled back from after an in-app update
+    #[serde(default)]
+    pub rolled_back_from: Option<String>,
 }
 
 fn default_check_interval() -> u64 {
@@ -34,6 +110,11 @@ impl Default for UpdateSettings {
             auto_check: true,
             last_check_time: 0,
             check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            channel: UpdateChannel::Stable,
+            github_api_mirror: None,
+            download_mirror: None,
+            skipped_version: None,
+            rolled_back_from: None,
         }
     }
 }
@@ -44,13 +125,195 @@ struct GitHubRelease {
     html_url: String,
     body: String,
     published_at: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: u64,
+    /// e.g. `sha256:<hex>`
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+fn configured(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Releases API URL (through the API mirror when configured)
+fn releases_api_url(settings: &UpdateSettings) -> String {
+    let base = configured(&settings.github_api_mirror).unwrap_or(GITHUB_API_BASE);
+    format!("{}{}", base.trim_end_matches('/'), RELEASES_PATH)
+}
+
+/// Asset download URL (through the download mirror when configured)
+fn mirror_download_url(settings: &UpdateSettings, url: &str) -> String {
+    match configured(&settings.download_mirror) {
+        Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), url),
+        None => url.to_string(),
+    }
+}
+
+/// Reject mirror URLs that are not http(s)
+fn validate_mirrors(settings: &UpdateSettings) -> Result<(), String> {
+    for (name, value) in [("github_api_mirror", &settings.github_api_mirror), ("download_mirror", &settings.download_mirror)] {
+        if let Some(url) = configured(value) {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(tr("backend.errors.update.invalid_url", &[("name", name), ("url", url)]));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Last releases response and rate-limit state, persisted between checks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ReleaseCache {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    /// Unix seconds until which the API must not be called
+    #[serde(default)]
+    limited_until: u64,
+    /// Consecutive rate-limited responses (drives the backoff)
+    #[serde(default)]
+    limit_strikes: u32,
+}
+
+fn release_cache_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::modules::account::get_root_data_dir()?.join(RELEASE_CACHE_FILE))
+}
+
+async fn load_release_cache() -> ReleaseCache {
+    crate::utils::blocking::run(|| {
+        Ok(release_cache_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default())
+    })
+    .await
+    .unwrap_or_default()
+}
+
+async fn save_release_cache(cache: &ReleaseCache) {
+    let cache = cache.clone();
+    let result = crate::utils::blocking::run(move || {
+        let path = release_cache_path()?;
+        let content = serde_json::to_string(&cache).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| e.to_string())
+    })
+    .await;
+    if let Err(e) = result {
+        logger::log_warn(&format!("保存更新检查缓存失败: {}", e));
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// If the response says we are rate limited, the Unix time to wait until
+/// (Retry-After, then X-RateLimit-Reset, then exponential backoff)
+fn rate_limited_until(status: StatusCode, headers: &HeaderMap, now: u64, strikes: u32) -> Option<u64> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse::<u64>().ok());
+    let remaining = header("x-ratelimit-remaining");
+    let retry_after = header("retry-after");
+    let limited = status == StatusCode::TOO_MANY_REQUESTS
+        || remaining == Some(0)
+        || (status == StatusCode::FORBIDDEN && retry_after.is_some());
+    if !limited {
+        return None;
+    }
+    if let Some(secs) = retry_after {
+        return Some(now + secs);
+    }
+    if let (Some(0), Some(reset)) = (remaining, header("x-ratelimit-reset")) {
+        return Some(reset.max(now));
+    }
+    Some(now + (MIN_BACKOFF_SECS << strikes.min(10)).min(MAX_BACKOFF_SECS))
+}
+
+fn parse_releases(body: &str) -> Result<Vec<GitHubRelease>, String> {
+    serde_json::from_str(body).map_err(|e| format!("Failed to parse release info: {}", e))
+}
+
+/// Fetch the releases list with a conditional request; the cached copy is reused on
+/// 304 and while rate limited, so unauthenticated quota is not burned on every check
+async fn fetch_releases(client: &reqwest::Client, url: &str) -> Result<Vec<GitHubRelease>, String> {
+    let now = now_secs();
+    let mut cache = load_release_cache().await;
+    if cache.url != url {
+        cache = ReleaseCache { url: url.to_string(), ..Default::default() };
+    }
+
+    if now < cache.limited_until {
+        return match &cache.body {
+            Some(body) => {
+                logger::log_info("GitHub API 限流中，使用缓存的版本信息");
+                parse_releases(body)
+            }
+            None => Err(tr("backend.errors.update.rate_limited", &[("seconds", &(cache.limited_until - now).to_string())])),
+        };
+    }
+
+    let mut request = client.get(url);
+    if let (Some(etag), Some(_)) = (&cache.etag, &cache.body) {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await.map_err(|e| {
+        let err_msg = format!("Failed to fetch release info: {}", e);
+        logger::log_error(&err_msg);
+        err_msg
+    })?;
+
+    let status = response.status();
+    let limited = rate_limited_until(status, response.headers(), now, cache.limit_strikes);
+    cache.limited_until = limited.unwrap_or(0);
+
+    let releases = if status == StatusCode::NOT_MODIFIED && cache.body.is_some() {
+        cache.limit_strikes = 0;
+        parse_releases(cache.body.as_deref().unwrap_or_default())?
+    } else if status.is_success() {
+        cache.limit_strikes = 0;
+        let etag = response.headers().get(ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let body = response.text().await.map_err(|e| format!("Failed to fetch release info: {}", e))?;
+        let releases = parse_releases(&body)?;
+        cache.etag = etag;
+        cache.body = Some(body);
+        releases
+    } else if let Some(until) = limited {
+        cache.limit_strikes += 1;
+        logger::log_warn(&format!("GitHub API 限流 ({})，{} 秒内不再请求", status, until.saturating_sub(now)));
+        save_release_cache(&cache).await;
+        return match &cache.body {
+            Some(body) => parse_releases(body),
+            None => Err(tr("backend.errors.update.rate_limited", &[("seconds", &until.saturating_sub(now).to_string())])),
+        };
+    } else {
+        return Err(format!("GitHub API returned status: {}", status));
+    };
+    save_release_cache(&cache).await;
+    Ok(releases)
 }
 
 /// Check for updates from GitHub releases
 pub async fn check_for_updates() -> Result<UpdateInfo, String> {
-    let client = reqwest::Client::builder()
+    let settings = crate::utils::blocking::run(load_update_settings).await.unwrap_or_default();
+    // Same outbound proxy as the Google endpoints (GitHub is often blocked on the same networks)
+    let client = crate::utils::http::client_builder(10)
         .user_agent("Antigravity-Manager")
-        .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| {
             let err_msg = format!("Failed to create HTTP client: {}", e);
@@ -58,34 +321,44 @@ pub async fn check_for_updates() -> Result<UpdateInfo, String> {
             err_msg
         })?;
 
-    logger::log_info("正在从 GitHub 检查新版本...");
+    let api_url = releases_api_url(&settings);
+    if configured(&settings.github_api_mirror).is_some() {
+        logger::log_info(&format!("正在通过镜像检查新版本: {}", api_url));
+    } else {
+        logger::log_info("正在从 GitHub 检查新版本...");
+    }
 
-    let response = client
-        .get(GITHUB_API_URL)
-        .send()
-        .await
-        .map_err(|e| {
-            let err_msg = format!("Failed to fetch release info: {}", e);
-            logger::log_error(&err_msg);
-            err_msg
-        })?;
+    let releases = fetch_releases(&client, &api_url).await?;
 
-    if !response.status().is_success() {
-        return Err(format!("GitHub API returned status: {}", response.status()));
-    }
+    let channel = settings.channel;
+    let release = pick_release(&releases, channel)
+        .ok_or_else(|| format!("No release found for channel {:?}", channel))?;
 
-    let release: GitHubRelease = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse release info: {}", e))?;
+    let assets: Vec<UpdateAsset> = release
+        .assets
+        .iter()
+        .map(|a| UpdateAsset {
+            name: a.name.clone(),
+            url: mirror_download_url(&settings, &a.browser_download_url),
+            size: a.size,
+            sha256: a.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")).map(str::to_ascii_lowercase),
+            ..Default::default()
+        })
+        .collect();
 
     // Remove 'v' prefix if present
     let latest_version = release.tag_name.trim_start_matches('v').to_string();
     let current_version = CURRENT_VERSION.to_string();
 
     let has_update = compare_versions(&latest_version, &current_version);
-
-    if has_update {
+    let skipped = has_update && is_skipped(&settings, &latest_version);
+    let changelog = collect_changelog(&releases, channel, &current_version);
+    // Aggregate notes across skipped versions so nothing is missed when several releases behind
+    let release_notes = if changelog.len() > 1 { join_changelog(&changelog) } else { release.body.clone() };
+
+    if skipped {
+        logger::log_info(&format!("发现新版本: {} (已设置跳过此版本)", latest_version));
+    } else if has_update {
         logger::log_info(&format!("发现新版本: {} (当前版本: {})", latest_version, current_version));
     } else {
         logger::log_info(&format!("已是最新版本: {} (与远程版本 {} 一致)", current_version, latest_version));
@@ -95,35 +368,121 @@ pub async fn check_for_updates() -> Result<UpdateInfo, String> {
         current_version,
         latest_version,
         has_update,
-        download_url: release.html_url,
-        release_notes: release.body,
-        published_at: release.published_at,
+        download_url: release.html_url.clone(),
+        release_notes,
+        published_at: release.published_at.clone(),
+        asset: crate::modules::update_installer::select_asset(&assets, std::env::consts::OS, std::env::consts::ARCH)
+            .map(|asset| crate::modules::update_installer::attach_verification(asset, &assets)),
+        prerelease: release.prerelease,
+        changelog,
+        skipped,
     })
 }
 
-/// Compare two semantic versions (e.g., "3.3.30" vs "3.3.29")
-fn compare_versions(latest: &str, current: &str) -> bool {
-    let parse_version = |v: &str| -> Vec<u32> {
-        v.split('.')
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect()
-    };
+fn is_skipped(settings: &UpdateSettings, version: &str) -> bool {
+    settings.skipped_version.as_deref().map(|v| v.trim_start_matches('v')) == Some(version)
+}
 
-    let latest_parts = parse_version(latest);
-    let current_parts = parse_version(current);
+/// Stop announcing `version`; any newer release is announced as usual
+pub fn skip_version(version: &str) -> Result<(), String> {
+    let mut settings = load_update_settings()?;
+    settings.skipped_version = Some(version.trim_start_matches('v').to_string());
+    save_update_settings(&settings)
+}
+
+/// Drafts are never offered; pre-releases only on the beta channel
+fn in_channel(release: &GitHubRelease, channel: UpdateChannel) -> bool {
+    !release.draft && (channel == UpdateChannel::Beta || !release.prerelease)
+}
+
+/// Pick the highest version in the channel (publish order is not version order once
+/// pre-releases and back-ported fixes are mixed)
+fn pick_release(releases: &[GitHubRelease], channel: UpdateChannel) -> Option<&GitHubRelease> {
+    releases
+        .iter()
+        .filter(|r| in_channel(r, channel))
+        .max_by(|a, b| compare_semver(&a.tag_name, &b.tag_name))
+}
 
-    for i in 0..latest_parts.len().max(current_parts.len()) {
-        let latest_part = latest_parts.get(i).unwrap_or(&0);
-        let current_part = current_parts.get(i).unwrap_or(&0);
+/// Notes of every release in the channel that is newer than `current` (newest first)
+fn collect_changelog(releases: &[GitHubRelease], channel: UpdateChannel, current: &str) -> Vec<ReleaseNote> {
+    let mut notes: Vec<ReleaseNote> = releases
+        .iter()
+        .filter(|r| in_channel(r, channel))
+        .filter_map(|r| {
+            let version = r.tag_name.trim_start_matches('v');
+            compare_versions(version, current).then(|| ReleaseNote {
+                version: version.to_string(),
+                published_at: r.published_at.clone(),
+                notes: r.body.trim().to_string(),
+            })
+        })
+        .collect();
+    notes.sort_by(|a, b| compare_semver(&b.version, &a.version));
+    notes
+}
+
+/// Concatenate release notes into one markdown document
+fn join_changelog(changelog: &[ReleaseNote]) -> String {
+    changelog
+        .iter()
+        .map(|n| format!("## v{}\n\n{}", n.version, n.notes))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Compare two semantic versions (e.g., "3.3.30" vs "3.3.29"); true if `latest` is newer
+fn compare_versions(latest: &str, current: &str) -> bool {
+    compare_semver(latest, current) == Ordering::Greater
+}
+
+/// Full semver ordering: a leading `v` and build metadata (`+...`) are ignored, a pre-release
+/// (`-beta.2`) sorts before its release, and pre-release identifiers compare numerically when
+/// both are numbers, otherwise lexically (numbers sort first, longer lists win ties)
+fn compare_semver(a: &str, b: &str) -> Ordering {
+    fn split(v: &str) -> (Vec<u64>, Option<&str>) {
+        let v = v.trim().trim_start_matches('v');
+        let v = v.split_once('+').map_or(v, |(core, _)| core);
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (v, None),
+        };
+        (core.split('.').map(|p| p.parse().unwrap_or(0)).collect(), pre)
+    }
 
-        if latest_part > current_part {
-            return true;
-        } else if latest_part < current_part {
-            return false; // e.g. local: 3.3.30, remote: 3.3.30 => false
+    let (core_a, pre_a) = split(a);
+    let (core_b, pre_b) = split(b);
+    for i in 0..core_a.len().max(core_b.len()) {
+        match core_a.get(i).unwrap_or(&0).cmp(core_b.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
         }
     }
 
-    false
+    match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => {
+            let (mut xs, mut ys) = (x.split('.'), y.split('.'));
+            loop {
+                let ord = match (xs.next(), ys.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(p), Some(q)) => match (p.parse::<u64>(), q.parse::<u64>()) {
+                        (Ok(m), Ok(n)) => m.cmp(&n),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => p.cmp(q),
+                    },
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
 }
 
 /// Check if enough time has passed since last check
@@ -146,36 +505,77 @@ pub fn should_check_for_updates(settings: &UpdateSettings) -> bool {
     elapsed_hours >= interval
 }
 
-/// Load update settings from config file
-pub fn load_update_settings() -> Result<UpdateSettings, String> {
-    let data_dir = crate::modules::account::get_data_dir()
+/// Path of the update settings file (shared by all profiles)
+pub fn settings_path() -> Result<std::path::PathBuf, String> {
+    let data_dir = crate::modules::account::get_root_data_dir()
         .map_err(|e| format!("Failed to get data dir: {}", e))?;
-    let settings_path = data_dir.join("update_settings.json");
+    Ok(data_dir.join("update_settings.json"))
+}
 
-    if !settings_path.exists() {
-        return Ok(UpdateSettings::default());
+/// Load update settings from config file (older formats are migrated first)
+pub fn load_update_settings() -> Result<UpdateSettings, String> {
+    let settings_path = settings_path()?;
+    match crate::modules::config_schema::load_migrated(&settings_path, UPDATE_SETTINGS_MIGRATIONS)? {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("Failed to parse settings: {}", e)),
+        None => Ok(UpdateSettings::default()),
     }
-
-    let content = std::fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings file: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings: {}", e))
 }
 
 /// Save update settings to config file
 pub fn save_update_settings(settings: &UpdateSettings) -> Result<(), String> {
-    let data_dir = crate::modules::account::get_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?;
-    let settings_path = data_dir.join("update_settings.json");
+    validate_mirrors(settings)?;
+    let settings_path = settings_path()?;
 
-    let content = serde_json::to_string_pretty(settings)
+    let content = crate::modules::config_schema::to_versioned_string(settings, UPDATE_SETTINGS_MIGRATIONS)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
     std::fs::write(&settings_path, content)
         .map_err(|e| format!("Failed to write settings file: {}", e))
 }
 
+/// Remember a rollback; the rolled-back version is also skipped so it is not offered again
+pub fn record_rollback(from_version: &str) -> Result<(), String> {
+    let mut settings = load_update_settings()?;
+    settings.rolled_back_from = Some(from_version.to_string());
+    settings.skipped_version = Some(from_version.to_string());
+    save_update_settings(&settings)
+}
+
+/// Version already announced in this session (avoids repeating the notification every interval)
+static LAST_NOTIFIED: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Background update check, run hourly by the task scheduler (the startup check is done by the
+/// window). A newer, non-skipped version raises a notification (native and external channels) and
+/// emits `update://available` so the window shows the update dialog. Headless mode passes no
+/// handle and only notifies the channels
+pub async fn run_background_check(app_handle: Option<&crate::utils::app::AppHandle>) -> Result<String, String> {
+    let settings = crate::utils::blocking::run(load_update_settings).await?;
+    if !should_check_for_updates(&settings) || crate::modules::power::should_pause_background().await {
+        return Ok("未到检查时间，已跳过".to_string());
+    }
+    let _ = crate::utils::blocking::run(update_last_check_time).await;
+    let info = check_for_updates().await?;
+    if info.has_update && !info.skipped {
+        notify_update(app_handle, &info);
+        return Ok(format!("发现新版本 {}", info.latest_version));
+    }
+    Ok("已是最新版本".to_string())
+}
+
+fn notify_update(app_handle: Option<&crate::utils::app::AppHandle>, info: &UpdateInfo) {
+    {
+        let mut last = LAST_NOTIFIED.lock().unwrap();
+        if last.as_deref() == Some(info.latest_version.as_str()) {
+            return;
+        }
+        *last = Some(info.latest_version.clone());
+    }
+    if let Some(app_handle) = app_handle {
+        crate::utils::app::emit(app_handle, UPDATE_AVAILABLE_EVENT, info);
+    }
+    crate::modules::notifications::update_available(&info.latest_version, &info.current_version);
+}
+
 /// Update last check time
 pub fn update_last_check_time() -> Result<(), String> {
     let mut settings = load_update_settings()?;
@@ -199,6 +599,118 @@ mod tests {
         assert!(!compare_versions("3.3.32", "3.3.32"));
     }
 
+    #[test]
+    fn test_compare_prerelease_versions() {
+        assert!(compare_versions("3.4.0", "3.4.0-beta.2"));
+        assert!(!compare_versions("3.4.0-beta.2", "3.4.0"));
+        assert!(compare_versions("3.4.0-beta.2", "3.3.9"));
+        assert!(compare_versions("3.4.0-beta.10", "3.4.0-beta.2"));
+        assert!(compare_versions("3.4.0-rc.1", "3.4.0-beta.9"));
+        assert!(compare_versions("3.4.0-alpha.1", "3.4.0-alpha"));
+        assert!(compare_versions("3.4.0-alpha.beta", "3.4.0-alpha.1"));
+        assert!(!compare_versions("v3.4.0+build.7", "3.4.0"));
+        assert_eq!(compare_semver("3.4", "3.4.0"), Ordering::Equal);
+    }
+
+    fn release(tag: &str, prerelease: bool, draft: bool) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            html_url: String::new(),
+            body: String::new(),
+            published_at: String::new(),
+            prerelease,
+            draft,
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pick_release_by_channel() {
+        let releases = || vec![
+            release("v3.5.0", false, true),
+            release("v3.5.0-beta.1", true, false),
+            release("v3.4.2", false, false),
+        ];
+        assert_eq!(pick_release(&releases(), UpdateChannel::Stable).unwrap().tag_name, "v3.4.2");
+        assert_eq!(pick_release(&releases(), UpdateChannel::Beta).unwrap().tag_name, "v3.5.0-beta.1");
+        assert!(pick_release(&[release("v3.5.0-beta.1", true, false)], UpdateChannel::Stable).is_none());
+
+        let legacy: UpdateSettings = serde_json::from_str(r#"{"auto_check":true,"last_check_time":0}"#).unwrap();
+        assert_eq!(legacy.channel, UpdateChannel::Stable);
+    }
+
+    #[test]
+    fn test_changelog_spans_skipped_versions() {
+        let mut releases = vec![
+            release("v3.4.2", false, false),
+            release("v3.4.1", false, false),
+            release("v3.4.0", false, false),
+            release("v3.3.9", false, false),
+        ];
+        for r in &mut releases {
+            r.body = format!("notes {}\n", r.tag_name);
+        }
+        let changelog = collect_changelog(&releases, UpdateChannel::Stable, "3.4.0");
+        let versions: Vec<&str> = changelog.iter().map(|n| n.version.as_str()).collect();
+        assert_eq!(versions, vec!["3.4.2", "3.4.1"]);
+        assert_eq!(join_changelog(&changelog), "## v3.4.2\n\nnotes v3.4.2\n\n## v3.4.1\n\nnotes v3.4.1");
+        assert!(collect_changelog(&releases, UpdateChannel::Stable, "3.4.2").is_empty());
+    }
+
+    #[test]
+    fn test_skipped_version_only_matches_that_release() {
+        let mut settings = UpdateSettings::default();
+        assert!(!is_skipped(&settings, "3.4.1"));
+        settings.skipped_version = Some("v3.4.1".to_string());
+        assert!(is_skipped(&settings, "3.4.1"));
+        assert!(!is_skipped(&settings, "3.4.2"));
+    }
+
+    #[test]
+    fn test_rate_limit_backoff() {
+        let headers = |pairs: &[(&'static str, &str)]| {
+            let mut map = HeaderMap::new();
+            for (k, v) in pairs {
+                map.insert(*k, v.parse().unwrap());
+            }
+            map
+        };
+        let now = 1_000;
+        assert_eq!(rate_limited_until(StatusCode::OK, &headers(&[("x-ratelimit-remaining", "12")]), now, 0), None);
+        assert_eq!(rate_limited_until(StatusCode::FORBIDDEN, &HeaderMap::new(), now, 0), None);
+
+        let exhausted = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "4600")]);
+        assert_eq!(rate_limited_until(StatusCode::FORBIDDEN, &exhausted, now, 0), Some(4_600));
+        // The last allowed request succeeded, but the next one would be refused
+        assert_eq!(rate_limited_until(StatusCode::OK, &exhausted, now, 0), Some(4_600));
+        assert_eq!(rate_limited_until(StatusCode::FORBIDDEN, &headers(&[("retry-after", "30")]), now, 0), Some(1_030));
+
+        assert_eq!(rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), now, 0), Some(now + MIN_BACKOFF_SECS));
+        assert_eq!(rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), now, 2), Some(now + MIN_BACKOFF_SECS * 4));
+        assert_eq!(rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), now, 30), Some(now + MAX_BACKOFF_SECS));
+    }
+
+    #[test]
+    fn test_mirror_urls() {
+        let mut settings = UpdateSettings::default();
+        let asset = "https://github.com/lbjlaq/Antigravity-Manager/releases/download/v3.4.0/a.dmg";
+        assert_eq!(releases_api_url(&settings), format!("{}{}", GITHUB_API_BASE, RELEASES_PATH));
+        assert_eq!(mirror_download_url(&settings, asset), asset);
+
+        settings.github_api_mirror = Some("https://gh-api.example.com/".to_string());
+        settings.download_mirror = Some(" https://ghproxy.net/ ".to_string());
+        assert_eq!(releases_api_url(&settings), format!("https://gh-api.example.com{}", RELEASES_PATH));
+        assert_eq!(mirror_download_url(&settings, asset), format!("https://ghproxy.net/{}", asset));
+        assert!(validate_mirrors(&settings).is_ok());
+
+        // Blank values count as unset
+        settings.github_api_mirror = Some("  ".to_string());
+        assert_eq!(releases_api_url(&settings), format!("{}{}", GITHUB_API_BASE, RELEASES_PATH));
+
+        settings.download_mirror = Some("ghproxy.net".to_string());
+        assert!(validate_mirrors(&settings).is_err());
+    }
+
     #[test]
     fn test_should_check_for_updates() {
         let mut settings = UpdateSettings::default();
@@ -0,0 +1,500 @@
+// 应用内更新下载与安装
+//
+// 检查到新版本后，下载当前平台对应的安装包 (macOS .dmg、Windows 安装程序、Linux AppImage / .deb)
+// 到数据目录的 updates/，下载过程推送 `update://download-progress` 事件。用户确认后再安装：
+// macOS 打开 dmg，Windows 启动安装程序后退出应用，以 AppImage 运行的 Linux 直接替换 AppImage
+// 文件并重启，.deb 交给系统的软件包安装器。只安装本次下载并校验过大小的文件，不接受前端传入的路径。
+//
+// 下载完成后按发布的 SHA-256 校验 (优先使用 GitHub 为资源提供的 digest，其次是发布中的
+// `<文件名>.sha256` / SHA256SUMS)，没有可用校验和或校验失败时不提供安装。构建时通过
+// `AGM_UPDATE_PUBLIC_KEY` 嵌入 Ed25519 公钥后，还要求 `<文件名>.sig` 签名校验通过；签名对象是
+// 安装包的 SHA-256 摘要 (32 字节原始值)，校验时流式读取文件计算摘要，不把整个安装包读入内存。
+//
+// 安装前把当前版本的安装包 (AppImage 即正在运行的文件，其他平台为上次应用内更新时保留的
+// 安装包) 复制到 updates/previous/，新版本有问题时可以回滚到它；回滚会在更新设置中记录
+// 从哪个版本回滚，并跳过该版本的更新提示。
+
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+
+use crate::modules::logger;
+use crate::modules::update_checker::UpdateAsset;
+use crate::modules::i18n::tr;
+
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "update://download-progress";
+const UPDATES_DIR: &str = "updates";
+/// 当前版本的安装包 (回滚目标)
+const PREVIOUS_DIR: &str = "previous";
+/// 最近一次应用内安装的安装包 (下次更新时成为回滚目标)
+const INSTALLED_DIR: &str = "installed";
+const PACKAGE_RECORD_FILE: &str = "package.json";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DOWNLOAD_TIMEOUT_SECS: u64 = 30 * 60;
+/// 进度事件的最小间隔字节数
+const PROGRESS_STEP_BYTES: u64 = 512 * 1024;
+/// 汇总校验和文件的常见文件名
+const CHECKSUM_FILES: &[&str] = &["SHA256SUMS", "SHA256SUMS.txt", "sha256sums.txt", "checksums.txt"];
+/// 构建时嵌入的更新签名公钥 (base64 编码的 Ed25519 公钥)
+const SIGNING_PUBLIC_KEY: Option<&str> = option_env!("AGM_UPDATE_PUBLIC_KEY");
+
+/// 下载进度
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: u64,
+}
+
+/// 已下载、等待安装的更新
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedUpdate {
+    pub version: String,
+    pub asset_name: String,
+    pub path: String,
+    pub size: u64,
+    /// 已校验的 SHA-256
+    pub sha256: String,
+    /// 已通过签名校验 (未嵌入公钥时为 false)
+    pub signature_verified: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallAction {
+    /// 已启动系统安装程序 / 打开安装包
+    InstallerLaunched,
+    /// 已替换可执行文件 (AppImage)
+    Replaced,
+}
+
+/// 安装结果
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallOutcome {
+    pub action: InstallAction,
+    /// 应用需要退出，让安装程序替换文件 (Windows)
+    pub exit_required: bool,
+    /// 应用需要重启以运行新版本
+    pub restart_required: bool,
+}
+
+/// 保留的某个版本的安装包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeptPackage {
+    pub version: String,
+    pub path: String,
+}
+
+static DOWNLOADED: Lazy<Mutex<Option<DownloadedUpdate>>> = Lazy::new(|| Mutex::new(None));
+
+/// 当前平台按优先级排列的安装包文件名后缀
+fn asset_suffixes(os: &str, arch: &str) -> Vec<String> {
+    let arm = arch == "aarch64";
+    match os {
+        "macos" => vec!["_universal.dmg".to_string(), format!("_{}.dmg", if arm { "aarch64" } else { "x64" })],
+        "windows" => {
+            let arch = if arm { "arm64" } else { "x64" };
+            vec![format!("_{}-setup.exe", arch), format!("_{}_en-US.msi", arch)]
+        }
+        "linux" => vec![
+            format!("_{}.AppImage", if arm { "aarch64" } else { "amd64" }),
+            format!("_{}.deb", if arm { "arm64" } else { "amd64" }),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// 从发布的资源中选出当前平台的安装包
+pub fn select_asset(assets: &[UpdateAsset], os: &str, arch: &str) -> Option<UpdateAsset> {
+    asset_suffixes(os, arch)
+        .iter()
+        .find_map(|suffix| assets.iter().find(|a| a.name.ends_with(suffix.as_str())))
+        .cloned()
+}
+
+/// 为选中的安装包关联发布中的校验和文件与签名文件
+pub fn attach_verification(mut asset: UpdateAsset, all: &[UpdateAsset]) -> UpdateAsset {
+    let per_file = format!("{}.sha256", asset.name);
+    asset.checksums_url = all
+        .iter()
+        .find(|a| a.name == per_file)
+        .or_else(|| all.iter().find(|a| CHECKSUM_FILES.iter().any(|f| a.name.eq_ignore_ascii_case(f))))
+        .map(|a| a.url.clone());
+    let signature = format!("{}.sig", asset.name);
+    asset.signature_url = all.iter().find(|a| a.name == signature).map(|a| a.url.clone());
+    asset
+}
+
+/// 从 sha256sum 格式 (`<hash>  <文件名>`，单文件 .sha256 可以只有哈希) 中取出指定文件的哈希
+fn parse_checksum(content: &str, name: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let matches = match parts.next() {
+            None => true,
+            Some(file) => file.trim_start_matches('*').rsplit('/').next() == Some(name),
+        };
+        matches.then(|| hash.to_ascii_lowercase())
+    })
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, String> {
+    let resp = client.get(url).send().await.map_err(|e| format!("下载校验文件失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(tr("backend.errors.update.checksum_download_failed", &[("status", &resp.status().to_string())]));
+    }
+    resp.text().await.map_err(|e| format!("读取校验文件失败: {}", e))
+}
+
+/// 发布的 SHA-256 (没有任何来源时报错，不安装无法校验的文件)
+async fn expected_sha256(client: &reqwest::Client, asset: &UpdateAsset) -> Result<String, String> {
+    if let Some(hash) = &asset.sha256 {
+        return Ok(hash.clone());
+    }
+    let url = asset
+        .checksums_url
+        .as_deref()
+        .ok_or_else(|| format!("{} 没有发布校验和，无法校验安装包，请前往发布页手动下载", asset.name))?;
+    let content = fetch_text(client, url).await?;
+    parse_checksum(&content, &asset.name).ok_or_else(|| format!("校验和文件中没有 {}", asset.name))
+}
+
+/// 流式计算文件的 SHA-256 (阻塞 I/O)
+fn sha256_file(path: &Path) -> Result<[u8; 32], String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("读取更新文件失败: {}", e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("读取更新文件失败: {}", e))?;
+    Ok(hasher.finalize().into())
+}
+
+/// 嵌入了公钥时校验签名 (签名对象为安装包的 SHA-256 摘要)，返回是否做了签名校验
+async fn verify_signature(client: &reqwest::Client, asset: &UpdateAsset, file: &Path) -> Result<bool, String> {
+    let Some(public_key) = SIGNING_PUBLIC_KEY else { return Ok(false) };
+    let url = asset.signature_url.as_deref().ok_or_else(|| format!("{} 没有发布签名文件", asset.name))?;
+    let signature = fetch_text(client, url).await?;
+    let file = file.to_path_buf();
+    let digest = crate::utils::blocking::run(move || sha256_file(&file)).await?;
+    crate::utils::crypto::verify_ed25519(public_key, &digest, &signature)?;
+    Ok(true)
+}
+
+fn updates_dir() -> Result<PathBuf, String> {
+    let dir = crate::modules::account::get_root_data_dir()?.join(UPDATES_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建更新目录失败: {}", e))?;
+    Ok(dir)
+}
+
+/// 下载最新版本的安装包 (已是最新版本或没有匹配的安装包时报错)
+pub async fn download_update(app: &crate::utils::app::AppHandle) -> Result<DownloadedUpdate, String> {
+    let info = crate::modules::update_checker::check_for_updates().await?;
+    if !info.has_update {
+        return Err(tr("backend.errors.update.up_to_date", &[("version", &info.current_version)]));
+    }
+    let asset = info.asset.ok_or_else(|| format!("版本 {} 没有适用于当前平台的安装包，请前往发布页下载", info.latest_version))?;
+
+    let dir = crate::utils::blocking::run(|| {
+        let dir = updates_dir()?;
+        // 只保留本次下载 (回滚用的安装包在子目录中，不受影响)
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        Ok(dir)
+    })
+    .await?;
+    let path = dir.join(&asset.name);
+    let partial = dir.join(format!("{}.part", asset.name));
+
+    logger::log_info(&format!("开始下载更新 {}: {}", info.latest_version, asset.name));
+    let client = crate::utils::http::client_builder(DOWNLOAD_TIMEOUT_SECS)
+        .user_agent("Antigravity-Manager")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let resp = client.get(&asset.url).send().await.map_err(|e| format!("下载更新失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(tr("backend.errors.update.download_failed", &[("status", &resp.status().to_string())]));
+    }
+    let total = resp.content_length().unwrap_or(asset.size);
+
+    let mut hasher = Sha256::new();
+    let mut file = tokio::fs::File::create(&partial).await.map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut stream = resp.bytes_stream();
+    let (mut downloaded, mut last_emitted) = (0u64, 0u64);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("下载更新失败: {}", e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("写入更新文件失败: {}", e))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        if downloaded - last_emitted >= PROGRESS_STEP_BYTES || downloaded == total {
+            last_emitted = downloaded;
+            crate::utils::app::emit(app, DOWNLOAD_PROGRESS_EVENT, DownloadProgress { downloaded, total });
+        }
+    }
+    file.flush().await.map_err(|e| format!("写入更新文件失败: {}", e))?;
+    drop(file);
+
+    if asset.size > 0 && downloaded != asset.size {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(tr("backend.errors.update.size_mismatch", &[("downloaded", &downloaded.to_string()), ("expected", &asset.size.to_string())]));
+    }
+
+    let sha256 = crate::utils::crypto::hex(&hasher.finalize());
+    let verified = match expected_sha256(&client, &asset).await {
+        Ok(expected) if expected == sha256 => verify_signature(&client, &asset, &partial).await,
+        Ok(expected) => Err(tr("backend.errors.update.sha256_mismatch", &[("expected", &expected), ("actual", &sha256)])),
+        Err(e) => Err(e),
+    };
+    let signature_verified = match verified {
+        Ok(signed) => signed,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            logger::log_error(&format!("更新校验失败: {}", e));
+            return Err(e);
+        }
+    };
+    tokio::fs::rename(&partial, &path).await.map_err(|e| format!("保存更新文件失败: {}", e))?;
+
+    let downloaded = DownloadedUpdate {
+        version: info.latest_version,
+        asset_name: asset.name,
+        path: path.to_string_lossy().to_string(),
+        size: downloaded,
+        sha256,
+        signature_verified,
+    };
+    logger::log_info(&format!("更新已下载并校验: {} (sha256 {})", downloaded.path, downloaded.sha256));
+    *DOWNLOADED.lock().unwrap() = Some(downloaded.clone());
+    Ok(downloaded)
+}
+
+/// 已下载、等待安装的更新
+pub fn downloaded_update() -> Option<DownloadedUpdate> {
+    DOWNLOADED.lock().unwrap().clone()
+}
+
+/// 用新的 AppImage 替换正在运行的 AppImage (先写临时文件再原子重命名)
+#[cfg(target_os = "linux")]
+fn replace_appimage(new_image: &Path, current: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let staging = current.with_extension("AppImage.new");
+    std::fs::copy(new_image, &staging).map_err(|e| format!("复制新版本失败: {}", e))?;
+    std::fs::set_permissions(&staging, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("设置可执行权限失败: {}", e))?;
+    std::fs::rename(&staging, current).map_err(|e| format!("替换 AppImage 失败: {}", e))
+}
+
+fn installer_launched(exit_required: bool) -> InstallOutcome {
+    InstallOutcome { action: InstallAction::InstallerLaunched, exit_required, restart_required: false }
+}
+
+#[cfg(target_os = "macos")]
+fn launch(path: &Path) -> Result<InstallOutcome, String> {
+    std::process::Command::new("open").arg(path).spawn().map_err(|e| format!("打开安装包失败: {}", e))?;
+    Ok(installer_launched(false))
+}
+
+#[cfg(target_os = "windows")]
+fn launch(path: &Path) -> Result<InstallOutcome, String> {
+    let mut cmd = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("msi")) {
+        let mut cmd = std::process::Command::new("msiexec");
+        cmd.arg("/i").arg(path);
+        cmd
+    } else {
+        std::process::Command::new(path)
+    };
+    cmd.spawn().map_err(|e| format!("启动安装程序失败: {}", e))?;
+    // 安装程序需要替换正在运行的文件
+    Ok(installer_launched(true))
+}
+
+#[cfg(target_os = "linux")]
+fn launch(path: &Path) -> Result<InstallOutcome, String> {
+    if path.extension().is_some_and(|ext| ext == "AppImage") {
+        let current = std::env::var_os("APPIMAGE")
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("当前不是以 AppImage 方式运行，请手动安装: {}", path.display()))?;
+        replace_appimage(path, &current)?;
+        return Ok(InstallOutcome { action: InstallAction::Replaced, exit_required: false, restart_required: true });
+    }
+    std::process::Command::new("xdg-open").arg(path).spawn().map_err(|e| format!("打开安装包失败: {}", e))?;
+    Ok(installer_launched(false))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn launch(path: &Path) -> Result<InstallOutcome, String> {
+    Err(tr("backend.errors.update.install_unsupported", &[("path", &path.display().to_string())]))
+}
+
+fn read_package(dir: &Path) -> Option<KeptPackage> {
+    let content = std::fs::read_to_string(dir.join(PACKAGE_RECORD_FILE)).ok()?;
+    let package: KeptPackage = serde_json::from_str(&content).ok()?;
+    Path::new(&package.path).exists().then_some(package)
+}
+
+/// 把安装包复制到 dir (清空原有内容) 并记录版本
+fn keep_package(dir: &Path, source: &Path, version: &str) -> Result<KeptPackage, String> {
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let name = source.file_name().ok_or_else(|| tr("backend.errors.update.invalid_installer_path", &[]))?;
+    let target = dir.join(name);
+    std::fs::copy(source, &target).map_err(|e| format!("保留安装包失败: {}", e))?;
+    let package = KeptPackage { version: version.to_string(), path: target.to_string_lossy().to_string() };
+    let content = serde_json::to_string(&package).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(PACKAGE_RECORD_FILE), content).map_err(|e| format!("保存安装包记录失败: {}", e))?;
+    Ok(package)
+}
+
+/// 当前运行版本的安装包: 以 AppImage 运行时为 AppImage 本身，否则为上次应用内更新保留的安装包
+fn current_version_package(updates: &Path) -> Option<PathBuf> {
+    if let Some(image) = std::env::var_os("APPIMAGE") {
+        return Some(PathBuf::from(image));
+    }
+    read_package(&updates.join(INSTALLED_DIR)).filter(|p| p.version == CURRENT_VERSION).map(|p| PathBuf::from(p.path))
+}
+
+/// 安装前保留当前版本，供回滚 (失败只记录日志，不阻止更新)
+fn keep_current_for_rollback(updates: &Path) {
+    let Some(source) = current_version_package(updates) else {
+        logger::log_warn(&format!("没有当前版本 {} 的安装包，此次更新后无法回滚", CURRENT_VERSION));
+        return;
+    };
+    match keep_package(&updates.join(PREVIOUS_DIR), &source, CURRENT_VERSION) {
+        Ok(package) => logger::log_info(&format!("已保留当前版本 {} 供回滚: {}", package.version, package.path)),
+        Err(e) => logger::log_warn(&format!("保留当前版本失败，此次更新后无法回滚: {}", e)),
+    }
+}
+
+/// 安装已下载的更新 (需用户确认后调用)
+pub fn install_downloaded() -> Result<InstallOutcome, String> {
+    let update = downloaded_update().ok_or_else(|| tr("backend.errors.update.nothing_downloaded", &[]))?;
+    let path = PathBuf::from(&update.path);
+    if !path.exists() {
+        *DOWNLOADED.lock().unwrap() = None;
+        return Err(tr("backend.errors.update.file_missing", &[]));
+    }
+    let updates = updates_dir()?;
+    keep_current_for_rollback(&updates);
+    logger::log_info(&format!("开始安装更新 {}: {}", update.version, update.asset_name));
+    let outcome = launch(&path)?;
+    if let Err(e) = keep_package(&updates.join(INSTALLED_DIR), &path, &update.version) {
+        logger::log_warn(&format!("保留新版本安装包失败: {}", e));
+    }
+    Ok(outcome)
+}
+
+/// 可回滚到的旧版本
+pub fn rollback_target() -> Option<KeptPackage> {
+    read_package(&updates_dir().ok()?.join(PREVIOUS_DIR)).filter(|p| p.version != CURRENT_VERSION)
+}
+
+/// 回滚到更新前的版本，并在更新设置中记录从哪个版本回滚
+pub fn rollback() -> Result<InstallOutcome, String> {
+    let target = rollback_target().ok_or_else(|| tr("backend.errors.update.no_rollback", &[]))?;
+    logger::log_info(&format!("回滚版本: {} -> {}", CURRENT_VERSION, target.version));
+    let outcome = launch(Path::new(&target.path))?;
+    crate::modules::update_checker::record_rollback(CURRENT_VERSION)?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assets(names: &[&str]) -> Vec<UpdateAsset> {
+        names
+            .iter()
+            .map(|n| UpdateAsset {
+                name: n.to_string(),
+                url: format!("https://example.com/{}", n),
+                size: 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_platform_asset() {
+        let all = assets(&[
+            "Antigravity.Tools_3.4.0_aarch64.dmg",
+            "Antigravity.Tools_3.4.0_universal.dmg",
+            "Antigravity.Tools_3.4.0_x64_en-US.msi",
+            "Antigravity.Tools_3.4.0_x64-setup.exe",
+            "Antigravity.Tools_3.4.0_amd64.deb",
+            "Antigravity.Tools_3.4.0_amd64.AppImage",
+            "Antigravity.Tools_3.4.0_aarch64.AppImage",
+            "latest.json",
+        ]);
+        let pick = |os, arch| select_asset(&all, os, arch).map(|a| a.name);
+        assert_eq!(pick("macos", "aarch64").as_deref(), Some("Antigravity.Tools_3.4.0_universal.dmg"));
+        assert_eq!(pick("windows", "x86_64").as_deref(), Some("Antigravity.Tools_3.4.0_x64-setup.exe"));
+        assert_eq!(pick("linux", "x86_64").as_deref(), Some("Antigravity.Tools_3.4.0_amd64.AppImage"));
+        assert_eq!(pick("linux", "aarch64").as_deref(), Some("Antigravity.Tools_3.4.0_aarch64.AppImage"));
+        assert_eq!(pick("windows", "aarch64"), None);
+
+        let msi_only = assets(&["Antigravity.Tools_3.4.0_x64_en-US.msi"]);
+        assert_eq!(select_asset(&msi_only, "windows", "x86_64").map(|a| a.name).as_deref(), Some("Antigravity.Tools_3.4.0_x64_en-US.msi"));
+    }
+
+    #[test]
+    fn test_checksum_sources() {
+        let all = assets(&["app_amd64.AppImage", "app_amd64.AppImage.sig", "SHA256SUMS"]);
+        let asset = attach_verification(all[0].clone(), &all);
+        assert_eq!(asset.checksums_url.as_deref(), Some("https://example.com/SHA256SUMS"));
+        assert_eq!(asset.signature_url.as_deref(), Some("https://example.com/app_amd64.AppImage.sig"));
+
+        let hash = "a".repeat(64);
+        let sums = format!("{}  app_x64-setup.exe\n{} *dist/app_amd64.AppImage\n", "b".repeat(64), hash.to_uppercase());
+        assert_eq!(parse_checksum(&sums, "app_amd64.AppImage"), Some(hash.clone()));
+        assert_eq!(parse_checksum(&sums, "app_amd64.deb"), None);
+        assert_eq!(parse_checksum(&format!("{}\n", hash), "anything"), Some(hash));
+        assert_eq!(parse_checksum("not-a-hash  app_amd64.AppImage", "app_amd64.AppImage"), None);
+    }
+
+    #[test]
+    fn test_signature_covers_streamed_file_digest() {
+        use base64::{engine::general_purpose, Engine as _};
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let path = std::env::temp_dir().join(format!("agm_sig_{}", uuid::Uuid::new_v4().simple()));
+        // 大于 io::copy 的缓冲区，覆盖多次读取
+        let content: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        std::fs::write(&path, &content).unwrap();
+        let digest = sha256_file(&path).unwrap();
+        assert_eq!(digest, <[u8; 32]>::from(Sha256::digest(&content)));
+
+        let signing = SigningKey::from_bytes(&[5u8; 32]);
+        let public = general_purpose::STANDARD.encode(signing.verifying_key().to_bytes());
+        let signature = general_purpose::STANDARD.encode(signing.sign(&digest).to_bytes());
+        assert!(crate::utils::crypto::verify_ed25519(&public, &digest, &signature).is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_keep_package_replaces_previous() {
+        let root = std::env::temp_dir().join(format!("agm_keep_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&root).unwrap();
+        let old = root.join("app_3.4.0_amd64.AppImage");
+        let new = root.join("app_3.4.1_amd64.AppImage");
+        std::fs::write(&old, b"old").unwrap();
+        std::fs::write(&new, b"new").unwrap();
+        let dir = root.join(PREVIOUS_DIR);
+
+        keep_package(&dir, &old, "3.4.0").unwrap();
+        let kept = keep_package(&dir, &new, "3.4.1").unwrap();
+        assert_eq!(read_package(&dir).unwrap().version, "3.4.1");
+        assert_eq!(std::fs::read(&kept.path).unwrap(), b"new");
+        // 只保留一个版本
+        assert!(!dir.join("app_3.4.0_amd64.AppImage").exists());
+
+        std::fs::remove_file(&kept.path).unwrap();
+        assert!(read_package(&dir).is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,264 @@
+// 反代运行时长与可用性统计
+//
+// 记录每次反代服务的启动 / 停止时间，运行期间每分钟采样一次可用性
+// (上游端点是否健康、是否还有未冷却的账号)，按日 (UTC) 汇总运行时长与可用率，
+// 供把反代当作常驻服务使用的用户查看。数据存放在 proxy.db，采样保留 90 天。
+// 异常退出没有停止记录时，以最后一次采样时间作为结束时间。
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use crate::proxy::token_manager::TokenManager;
+use crate::proxy::upstream::client::UpstreamClient;
+
+const DAY_SECS: i64 = 86_400;
+/// 可用性采样间隔
+const SAMPLE_INTERVAL_SECS: u64 = 60;
+/// 采样与启停记录保留天数
+const RETENTION_DAYS: i64 = 90;
+
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS proxy_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            last_seen_at INTEGER NOT NULL,
+            stopped_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_proxy_sessions_started ON proxy_sessions (started_at);
+        CREATE TABLE IF NOT EXISTS availability_samples (
+            timestamp INTEGER NOT NULL,
+            upstream_ok INTEGER NOT NULL,
+            accounts_ok INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_availability_ts ON availability_samples (timestamp);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn open() -> Result<Connection, String> {
+    let conn = Connection::open(crate::modules::proxy_db::get_proxy_db_path()?).map_err(|e| e.to_string())?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// 单日的运行与可用性统计 (UTC)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DailyUptime {
+    /// YYYY-MM-DD
+    pub date: String,
+    /// 当天反代运行的秒数
+    pub uptime_secs: i64,
+    /// 当天已经过的秒数 (今天为截至当前)
+    pub elapsed_secs: i64,
+    pub uptime_ratio: f64,
+    /// 当天启动次数
+    pub starts: u32,
+    pub samples: u32,
+    /// 上游与账号都可用的采样数
+    pub available_samples: u32,
+    /// 运行期间的可用率，没有采样时为 None
+    pub availability: Option<f64>,
+}
+
+/// 运行时长与可用性汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct UptimeSummary {
+    pub days: Vec<DailyUptime>,
+    pub uptime_ratio: f64,
+    pub availability: Option<f64>,
+    /// 当前运行中的会话开始时间 (Unix 秒)
+    pub running_since: Option<i64>,
+}
+
+fn record_start_in(conn: &Connection, now: i64) -> Result<i64, String> {
+    // 上次异常退出未记录停止时间的会话，以最后一次采样时间结束
+    conn.execute("UPDATE proxy_sessions SET stopped_at = last_seen_at WHERE stopped_at IS NULL", [])
+        .map_err(|e| e.to_string())?;
+    conn.execute("INSERT INTO proxy_sessions (started_at, last_seen_at) VALUES (?1, ?1)", params![now])
+        .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn heartbeat_in(conn: &Connection, session_id: i64, now: i64, upstream_ok: bool, accounts_ok: bool) -> Result<(), String> {
+    conn.execute("UPDATE proxy_sessions SET last_seen_at = ?2 WHERE id = ?1 AND stopped_at IS NULL", params![session_id, now])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO availability_samples (timestamp, upstream_ok, accounts_ok) VALUES (?1, ?2, ?3)",
+        params![now, upstream_ok, accounts_ok],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn record_stop_in(conn: &Connection, session_id: i64, now: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE proxy_sessions SET stopped_at = ?2, last_seen_at = ?2 WHERE id = ?1 AND stopped_at IS NULL",
+        params![session_id, now],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+fn cleanup_in(conn: &Connection, now: i64) -> Result<(), String> {
+    let cutoff = now - RETENTION_DAYS * DAY_SECS;
+    conn.execute("DELETE FROM availability_samples WHERE timestamp < ?1", params![cutoff])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM proxy_sessions WHERE stopped_at IS NOT NULL AND stopped_at < ?1", params![cutoff])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn date_of(day_start: i64) -> String {
+    chrono::DateTime::from_timestamp(day_start, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn summarize(conn: &Connection, days: u32, now: i64) -> Result<UptimeSummary, String> {
+    let today = now - now.rem_euclid(DAY_SECS);
+    let since = today - (days.max(1) as i64 - 1) * DAY_SECS;
+
+    // (开始, 结束, 是否运行中)
+    let mut stmt = conn
+        .prepare(
+            "SELECT started_at, COALESCE(stopped_at, last_seen_at), stopped_at IS NULL
+             FROM proxy_sessions
+             WHERE COALESCE(stopped_at, last_seen_at) >= ?1
+             ORDER BY started_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let sessions: Vec<(i64, i64, bool)> = stmt
+        .query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT (timestamp / 86400) * 86400, COUNT(*), SUM(upstream_ok AND accounts_ok)
+             FROM availability_samples
+             WHERE timestamp >= ?1
+             GROUP BY 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let samples: Vec<(i64, u32, u32)> = stmt
+        .query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut result = Vec::new();
+    let mut day_start = since;
+    while day_start <= today {
+        let day_end = (day_start + DAY_SECS).min(now);
+        let uptime_secs: i64 = sessions
+            .iter()
+            .map(|(start, end, _)| ((*end).min(day_end) - (*start).max(day_start)).max(0))
+            .sum();
+        let elapsed_secs = (day_end - day_start).max(0);
+        let (samples, available_samples) = samples
+            .iter()
+            .find(|(day, _, _)| *day == day_start)
+            .map(|(_, total, ok)| (*total, *ok))
+            .unwrap_or((0, 0));
+        result.push(DailyUptime {
+            date: date_of(day_start),
+            uptime_secs,
+            elapsed_secs,
+            uptime_ratio: if elapsed_secs > 0 { (uptime_secs as f64 / elapsed_secs as f64).min(1.0) } else { 0.0 },
+            starts: sessions.iter().filter(|(start, _, _)| *start >= day_start && *start < day_start + DAY_SECS).count() as u32,
+            samples,
+            available_samples,
+            availability: (samples > 0).then(|| available_samples as f64 / samples as f64),
+        });
+        day_start += DAY_SECS;
+    }
+
+    let uptime: i64 = result.iter().map(|d| d.uptime_secs).sum();
+    let elapsed: i64 = result.iter().map(|d| d.elapsed_secs).sum();
+    let total_samples: u32 = result.iter().map(|d| d.samples).sum();
+    let available: u32 = result.iter().map(|d| d.available_samples).sum();
+    Ok(UptimeSummary {
+        days: result,
+        uptime_ratio: if elapsed > 0 { uptime as f64 / elapsed as f64 } else { 0.0 },
+        availability: (total_samples > 0).then(|| available as f64 / total_samples as f64),
+        running_since: sessions.iter().rev().find(|(_, _, running)| *running).map(|(start, _, _)| *start),
+    })
+}
+
+/// 记录反代启动，返回会话 ID
+pub fn record_start() -> Result<i64, String> {
+    let conn = open()?;
+    let now = chrono::Utc::now().timestamp();
+    cleanup_in(&conn, now)?;
+    record_start_in(&conn, now)
+}
+
+/// 记录反代停止
+pub fn record_stop(session_id: i64) -> Result<(), String> {
+    record_stop_in(&open()?, session_id, chrono::Utc::now().timestamp())
+}
+
+/// 最近 `days` 天 (含今天) 的每日运行时长与可用率
+pub fn get_uptime_summary(days: u32) -> Result<UptimeSummary, String> {
+    summarize(&open()?, days, chrono::Utc::now().timestamp())
+}
+
+/// 运行期间每分钟采样一次可用性；反代停止 (TokenManager 被释放) 后退出
+pub fn start_sampler(session_id: i64, token_manager: Weak<TokenManager>, upstream: Arc<UpstreamClient>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SAMPLE_INTERVAL_SECS));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(manager) = token_manager.upgrade() else { break };
+            // 没有任何请求时端点列表为空，视为可用
+            let endpoints = upstream.endpoint_stats();
+            let upstream_ok = endpoints.is_empty() || endpoints.iter().any(|e| e.healthy);
+            let accounts_ok = manager.len() > manager.get_cooling_accounts().len();
+            drop(manager);
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) = open().and_then(|conn| heartbeat_in(&conn, session_id, now, upstream_ok, accounts_ok)) {
+                tracing::warn!("[Uptime] Failed to record availability sample: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_uptime_splits_sessions_across_days() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        let day = DAY_SECS;
+
+        // 第 0 天 12:00 启动，运行到第 1 天 06:00
+        let first = record_start_in(&conn, day / 2).unwrap();
+        heartbeat_in(&conn, first, day / 2 + 60, true, true).unwrap();
+        heartbeat_in(&conn, first, day / 2 + 120, false, true).unwrap();
+        record_stop_in(&conn, first, day + day / 4).unwrap();
+        // 第 1 天 18:00 启动后异常退出，最后一次采样在 19:00
+        let second = record_start_in(&conn, day + day * 3 / 4).unwrap();
+        heartbeat_in(&conn, second, day + day * 3 / 4 + 3600, true, true).unwrap();
+        // 第 2 天重新启动时补记上次的结束时间
+        let third = record_start_in(&conn, 2 * day).unwrap();
+        heartbeat_in(&conn, third, 2 * day + 600, true, false).unwrap();
+
+        let summary = summarize(&conn, 3, 2 * day + 1200).unwrap();
+        let days: Vec<(&str, i64, u32)> = summary.days.iter().map(|d| (d.date.as_str(), d.uptime_secs, d.starts)).collect();
+        assert_eq!(
+            days,
+            [("1970-01-01", day / 2, 1), ("1970-01-02", day / 4 + 3600, 1), ("1970-01-03", 600, 1)]
+        );
+        assert_eq!(summary.days[0].availability, Some(0.5));
+        assert_eq!(summary.days[2].availability, Some(0.0));
+        assert_eq!(summary.days[2].elapsed_secs, 1200);
+        assert_eq!(summary.running_since, Some(2 * day));
+    }
+}
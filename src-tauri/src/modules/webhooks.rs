@@ -0,0 +1,117 @@
+// 运行事件 Webhook
+//
+// 账号被隔离、需要重新授权、配额耗尽、账号池耗尽、反代启停以及告警时，向用户配置的 URL
+// POST 一个 JSON: `{"event": "...", "timestamp": <Unix 毫秒>, "data": {...}}`，每个 Webhook 可按事件过滤。
+// 配置了密钥时附带 HMAC-SHA256 签名，签名内容为 `<timestamp>.<body>`:
+//   X-Antigravity-Timestamp: <timestamp>
+//   X-Antigravity-Signature: sha256=<hex>
+// 发送在后台进行，失败只记日志，不影响触发事件的流程。
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::models::{WebhookConfig, WebhookEvent};
+use crate::modules::{config, logger};
+use crate::utils::crypto::{hex, hmac_sha256};
+use crate::modules::i18n::tr;
+
+/// 单次请求超时 (秒)
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// `sha256=<hex>` 签名
+fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    format!("sha256={}", hex(&hmac_sha256(secret.as_bytes(), &message)))
+}
+
+/// 发送一次，返回 HTTP 状态码
+async fn deliver(client: &reqwest::Client, hook: &WebhookConfig, event: &str, timestamp: i64, body: Vec<u8>) -> Result<u16, String> {
+    let mut request = client
+        .post(hook.url.trim())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Antigravity-Event", event)
+        .header("X-Antigravity-Timestamp", timestamp.to_string());
+    if let Some(secret) = hook.secret.as_deref().filter(|s| !s.is_empty()) {
+        request = request.header("X-Antigravity-Signature", signature(secret, timestamp, &body));
+    }
+    let resp = request.body(body).send().await.map_err(|e| format!("Webhook 请求失败: {}", e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(tr("backend.errors.notify.webhook_status", &[("status", &status.to_string())]));
+    }
+    Ok(status.as_u16())
+}
+
+fn event_name(event: WebhookEvent) -> String {
+    serde_json::to_value(event)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 触发事件，发送给所有订阅了该事件的 Webhook
+pub fn fire(event: WebhookEvent, data: impl Serialize) {
+    let Ok(app_config) = config::load_app_config() else { return };
+    let targets: Vec<WebhookConfig> = app_config.webhooks.into_iter().filter(|h| h.accepts(event)).collect();
+    if targets.is_empty() {
+        return;
+    }
+    let name = event_name(event);
+    let data = serde_json::to_value(data).unwrap_or_default();
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let Ok(body) = serde_json::to_vec(&json!({ "event": name, "timestamp": timestamp, "data": data })) else {
+        return;
+    };
+    crate::utils::app::spawn(async move {
+        let client = crate::utils::http::create_client(WEBHOOK_TIMEOUT_SECS);
+        for hook in targets {
+            if let Err(e) = deliver(&client, &hook, &name, timestamp, body.clone()).await {
+                logger::log_warn(&format!("[Webhook] {} -> {}: {}", name, hook.url, e));
+            }
+        }
+    });
+}
+
+/// 发送测试事件 (设置界面校验 URL 与签名)
+pub async fn send_test(hook: &WebhookConfig) -> Result<u16, String> {
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let body = serde_json::to_vec(&json!({ "event": "test", "timestamp": timestamp, "data": {} }))
+        .map_err(|e| e.to_string())?;
+    let client = crate::utils::http::create_client(WEBHOOK_TIMEOUT_SECS);
+    deliver(&client, hook, "test", timestamp, body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let mut hook = WebhookConfig {
+            enabled: true,
+            url: "https://hooks.example.com/ag".to_string(),
+            events: Vec::new(),
+            secret: None,
+        };
+        assert!(hook.accepts(WebhookEvent::ProxyStarted));
+
+        hook.events = vec![WebhookEvent::AccountQuarantined];
+        assert!(hook.accepts(WebhookEvent::AccountQuarantined));
+        assert!(!hook.accepts(WebhookEvent::ProxyStarted));
+
+        hook.enabled = false;
+        assert!(!hook.accepts(WebhookEvent::AccountQuarantined));
+        assert_eq!(event_name(WebhookEvent::AccountQuarantined), "account_quarantined");
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let sig = signature("secret", 1_700_000_000_000, b"{}");
+        assert!(sig.starts_with("sha256="));
+        assert_eq!(sig.len(), "sha256=".len() + 64);
+        assert_eq!(sig, signature("secret", 1_700_000_000_000, b"{}"));
+        assert_ne!(sig, signature("secret", 1_700_000_000_001, b"{}"));
+        assert_ne!(sig, signature("other", 1_700_000_000_000, b"{}"));
+    }
+}
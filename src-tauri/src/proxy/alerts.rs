@@ -0,0 +1,252 @@
+// 错误率告警
+//
+// 根据每个请求的状态码检查两类阈值：滑动窗口内的错误率、连续 429 次数。
+// 触发时推送 `proxy://alert` 事件，并可选 POST 到用户配置的 Webhook。
+// 同类告警在冷却期内只触发一次，避免持续故障时刷屏。
+// 关闭安全模式后反代在未开启鉴权时监听非本机地址，启动或热更新配置时同样推送告警。
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+
+use crate::proxy::config::AlertConfig;
+
+pub const ALERT_EVENT: &str = "proxy://alert";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    ErrorRate,
+    Consecutive429,
+    /// Token 用量突增 (见 anomaly.rs)
+    TokenSpike,
+    /// 单个 API Key 占据大部分流量
+    KeyDominance,
+    /// 可从本机以外访问且未开启鉴权
+    UnauthenticatedExposure,
+}
+
+/// 告警内容 (事件与 Webhook 共用)
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyAlert {
+    pub kind: AlertKind,
+    /// Unix 毫秒
+    pub timestamp: i64,
+    /// 当前值 (错误率或连续 429 次数)
+    pub value: f64,
+    pub threshold: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct AlertState {
+    /// 窗口内请求 (完成时间毫秒, 是否错误)
+    window: VecDeque<(i64, bool)>,
+    consecutive_429: u32,
+    last_error_rate_alert: Option<i64>,
+    last_429_alert: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct AlertMonitor {
+    config: RwLock<AlertConfig>,
+    state: Mutex<AlertState>,
+}
+
+impl AlertMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_config(&self, config: AlertConfig) {
+        let mut current = self.config.write().unwrap();
+        if *current != config {
+            *current = config;
+            // 阈值变化后重新开始计数
+            *self.state.lock().unwrap() = AlertState::default();
+        }
+    }
+
+    pub fn webhook_url(&self) -> Option<String> {
+        self.config.read().unwrap().webhook_url.clone().filter(|u| !u.trim().is_empty())
+    }
+
+    /// 记录一个已完成请求，返回本次触发的告警
+    pub fn observe(&self, status: u16, now_ms: i64) -> Vec<ProxyAlert> {
+        let config = self.config.read().unwrap().clone();
+        if !config.enabled {
+            return Vec::new();
+        }
+        let cooldown_ms = config.cooldown_secs as i64 * 1000;
+        let cooled = |last: Option<i64>| last.is_none_or(|ts| now_ms - ts >= cooldown_ms);
+
+        let mut state = self.state.lock().unwrap();
+        let mut alerts = Vec::new();
+
+        if status == 429 {
+            state.consecutive_429 += 1;
+        } else {
+            state.consecutive_429 = 0;
+        }
+        if config.consecutive_429 > 0
+            && state.consecutive_429 >= config.consecutive_429
+            && cooled(state.last_429_alert)
+        {
+            state.last_429_alert = Some(now_ms);
+            alerts.push(ProxyAlert {
+                kind: AlertKind::Consecutive429,
+                timestamp: now_ms,
+                value: state.consecutive_429 as f64,
+                threshold: config.consecutive_429 as f64,
+                message: format!("连续 {} 次请求被上游限流 (429)", state.consecutive_429),
+            });
+        }
+
+        let window_ms = config.error_rate_window_secs as i64 * 1000;
+        state.window.push_back((now_ms, status >= 400));
+        while state.window.front().is_some_and(|(ts, _)| *ts <= now_ms - window_ms) {
+            state.window.pop_front();
+        }
+        let total = state.window.len();
+        let errors = state.window.iter().filter(|(_, e)| *e).count();
+        let rate = errors as f64 / total as f64;
+        if config.error_rate_threshold > 0.0
+            && total >= config.min_requests.max(1) as usize
+            && rate > config.error_rate_threshold
+            && cooled(state.last_error_rate_alert)
+        {
+            state.last_error_rate_alert = Some(now_ms);
+            alerts.push(ProxyAlert {
+                kind: AlertKind::ErrorRate,
+                timestamp: now_ms,
+                value: rate,
+                threshold: config.error_rate_threshold,
+                message: format!(
+                    "最近 {} 秒错误率 {:.1}% ({}/{})，超过阈值 {:.1}%",
+                    config.error_rate_window_secs,
+                    rate * 100.0,
+                    errors,
+                    total,
+                    config.error_rate_threshold * 100.0
+                ),
+            });
+        }
+        alerts
+    }
+}
+
+/// 反代可从本机以外访问且无需鉴权时的安全告警 (安全模式开启时不会出现这种情况)
+pub fn exposure_alert(config: &crate::proxy::ProxyConfig, now_ms: i64) -> Option<ProxyAlert> {
+    if !config.exposed_without_auth() || config.require_auth_for_lan {
+        return None;
+    }
+    Some(ProxyAlert {
+        kind: AlertKind::UnauthenticatedExposure,
+        timestamp: now_ms,
+        value: 0.0,
+        threshold: 0.0,
+        message: format!(
+            "反代正在监听 {}:{} 且未开启鉴权，本机以外的设备无需 API Key 即可使用",
+            config.get_bind_address(),
+            config.port
+        ),
+    })
+}
+
+/// 推送告警事件并调用 Webhook (异步，不阻塞请求)
+pub fn notify(alert: ProxyAlert, app_handle: Option<&crate::utils::app::AppHandle>, webhook_url: Option<String>) {
+    tracing::warn!("[Alert] {}", alert.message);
+    crate::modules::webhooks::fire(crate::models::WebhookEvent::Alert, &alert);
+    if let Some(app) = app_handle {
+        crate::utils::app::emit(app, ALERT_EVENT, &alert);
+    }
+    if let Some(url) = webhook_url {
+        tokio::spawn(async move {
+            let client = crate::utils::http::create_client(10);
+            match client.post(&url).json(&alert).send().await {
+                Ok(resp) if !resp.status().is_success() => {
+                    tracing::warn!("[Alert] Webhook returned {}", resp.status());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("[Alert] Webhook failed: {}", e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> AlertMonitor {
+        let monitor = AlertMonitor::new();
+        monitor.set_config(AlertConfig {
+            enabled: true,
+            error_rate_threshold: 0.2,
+            error_rate_window_secs: 60,
+            min_requests: 5,
+            consecutive_429: 3,
+            cooldown_secs: 600,
+            webhook_url: None,
+        });
+        monitor
+    }
+
+    #[test]
+    fn test_consecutive_429_fires_once_per_cooldown() {
+        let m = monitor();
+        assert!(m.observe(429, 0).is_empty());
+        assert!(m.observe(429, 1).is_empty());
+        let alerts = m.observe(429, 2);
+        assert_eq!(alerts.iter().filter(|a| a.kind == AlertKind::Consecutive429).count(), 1);
+        assert!(m.observe(429, 3).iter().all(|a| a.kind != AlertKind::Consecutive429));
+
+        // 冷却期后再次连续 429 才会重新告警
+        m.observe(200, 700_000);
+        m.observe(429, 700_001);
+        m.observe(429, 700_002);
+        assert!(m.observe(429, 700_003).iter().any(|a| a.kind == AlertKind::Consecutive429));
+    }
+
+    #[test]
+    fn test_error_rate_needs_min_requests() {
+        let m = monitor();
+        // 样本不足时即使全部失败也不告警
+        for i in 0..4 {
+            assert!(m.observe(500, i).is_empty());
+        }
+        let alerts = m.observe(200, 4);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::ErrorRate);
+        assert!((alerts[0].value - 0.8).abs() < 1e-9);
+
+        // 窗口滑过之后旧错误不再计入
+        let m = monitor();
+        for i in 0..5 {
+            m.observe(500, i);
+        }
+        for i in 0..5 {
+            assert!(m.observe(200, 700_000 + i).is_empty());
+        }
+
+        let quiet = AlertMonitor::new();
+        for i in 0..10 {
+            assert!(quiet.observe(500, i).is_empty(), "disabled monitor must not alert");
+        }
+    }
+
+    #[test]
+    fn test_exposure_alert_only_without_safe_mode() {
+        use crate::proxy::{ProxyAuthMode, ProxyConfig};
+        let mut config = ProxyConfig { allow_lan_access: true, auth_mode: ProxyAuthMode::Off, ..Default::default() };
+        assert!(exposure_alert(&config, 0).is_none());
+
+        config.require_auth_for_lan = false;
+        let alert = exposure_alert(&config, 0).unwrap();
+        assert_eq!(alert.kind, AlertKind::UnauthenticatedExposure);
+        assert!(alert.message.contains("0.0.0.0:8045"));
+
+        config.auth_mode = ProxyAuthMode::Auto;
+        assert!(exposure_alert(&config, 0).is_none());
+    }
+}
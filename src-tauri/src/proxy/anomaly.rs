@@ -0,0 +1,217 @@
+// 用量异常检测
+//
+// 与错误率告警互补，关注用量本身：最近窗口内的 Token 速率 (折算为每小时) 远超过去
+// 几小时的平均值，或单个 API Key 占据了绝大部分请求 (此前有多个 Key 在用)。
+// 常见于失控的 Agent 循环或泄露的 Key。触发时复用告警的事件与 Webhook，冷却期内不重复。
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Mutex, RwLock};
+
+use crate::proxy::alerts::{AlertKind, ProxyAlert};
+use crate::proxy::config::AnomalyConfig;
+
+const HOUR_MS: i64 = 3_600_000;
+/// 计算基线至少需要的历史小时数
+const MIN_BASELINE_HOURS: usize = 3;
+
+#[derive(Debug)]
+struct HourBucket {
+    start_ms: i64,
+    tokens: u64,
+    keys: HashSet<String>,
+}
+
+#[derive(Debug, Default)]
+struct AnomalyState {
+    /// 窗口内请求 (完成时间毫秒, Key, Token 数)
+    window: VecDeque<(i64, String, u64)>,
+    /// 按小时汇总的历史 (最后一个为当前小时)
+    hours: VecDeque<HourBucket>,
+    last_spike_alert: Option<i64>,
+    last_dominance_alert: Option<i64>,
+}
+
+impl AnomalyState {
+    fn bucket(&mut self, now_ms: i64, max_hours: usize) -> &mut HourBucket {
+        let start_ms = now_ms - now_ms.rem_euclid(HOUR_MS);
+        if self.hours.back().is_none_or(|h| h.start_ms != start_ms) {
+            self.hours.push_back(HourBucket { start_ms, tokens: 0, keys: HashSet::new() });
+        }
+        while self.hours.front().is_some_and(|h| h.start_ms < start_ms - max_hours as i64 * HOUR_MS) {
+            self.hours.pop_front();
+        }
+        self.hours.back_mut().unwrap()
+    }
+
+    /// 过去 (不含当前小时) 有数据的小时的平均 Token 数
+    fn baseline(&self) -> Option<f64> {
+        let past: Vec<u64> = self.hours.iter().rev().skip(1).map(|h| h.tokens).filter(|t| *t > 0).collect();
+        (past.len() >= MIN_BASELINE_HOURS).then(|| past.iter().sum::<u64>() as f64 / past.len() as f64)
+    }
+
+    fn distinct_recent_keys(&self) -> usize {
+        self.hours.iter().flat_map(|h| h.keys.iter()).collect::<HashSet<_>>().len()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AnomalyDetector {
+    config: RwLock<AnomalyConfig>,
+    state: Mutex<AnomalyState>,
+}
+
+impl AnomalyDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_config(&self, config: AnomalyConfig) {
+        let mut current = self.config.write().unwrap();
+        if *current != config {
+            *current = config;
+            // 只重置告警冷却，保留已累计的用量基线
+            let mut state = self.state.lock().unwrap();
+            state.last_spike_alert = None;
+            state.last_dominance_alert = None;
+        }
+    }
+
+    /// 记录一个已完成请求，返回本次触发的异常告警
+    pub fn observe(&self, key_id: &str, tokens: u64, now_ms: i64) -> Vec<ProxyAlert> {
+        let config = self.config.read().unwrap().clone();
+        if !config.enabled {
+            return Vec::new();
+        }
+        let key = if key_id.is_empty() { "anonymous" } else { key_id };
+        let cooldown_ms = config.cooldown_secs as i64 * 1000;
+        let cooled = |last: Option<i64>| last.is_none_or(|ts| now_ms - ts >= cooldown_ms);
+
+        let mut state = self.state.lock().unwrap();
+        let bucket = state.bucket(now_ms, config.baseline_hours.max(MIN_BASELINE_HOURS as u32) as usize);
+        bucket.tokens += tokens;
+        bucket.keys.insert(key.to_string());
+
+        let window_ms = config.window_secs.max(60) as i64 * 1000;
+        state.window.push_back((now_ms, key.to_string(), tokens));
+        while state.window.front().is_some_and(|(ts, _, _)| *ts <= now_ms - window_ms) {
+            state.window.pop_front();
+        }
+
+        let mut alerts = Vec::new();
+
+        // Token 速率突增：窗口内用量折算为每小时后与基线比较
+        let window_tokens: u64 = state.window.iter().map(|(_, _, t)| t).sum();
+        let hourly_rate = window_tokens as f64 * HOUR_MS as f64 / window_ms as f64;
+        if let Some(baseline) = state.baseline() {
+            let factor = hourly_rate / baseline.max(1.0);
+            if config.spike_factor > 0.0
+                && factor >= config.spike_factor
+                && hourly_rate >= config.min_hourly_tokens as f64
+                && cooled(state.last_spike_alert)
+            {
+                state.last_spike_alert = Some(now_ms);
+                alerts.push(ProxyAlert {
+                    kind: AlertKind::TokenSpike,
+                    timestamp: now_ms,
+                    value: factor,
+                    threshold: config.spike_factor,
+                    message: format!(
+                        "最近 {} 秒 Token 用量折合每小时 {:.0}，是平时 ({:.0}/小时) 的 {:.1} 倍",
+                        window_ms / 1000,
+                        hourly_rate,
+                        baseline,
+                        factor
+                    ),
+                });
+            }
+        }
+
+        // 单个 Key 占据绝大部分请求 (此前有多个 Key 在用时才有意义)
+        let total = state.window.len();
+        let key_requests = state.window.iter().filter(|(_, k, _)| k == key).count();
+        let share = key_requests as f64 / total as f64;
+        if config.key_share_threshold > 0.0
+            && total >= config.key_share_min_requests.max(1) as usize
+            && share >= config.key_share_threshold
+            && state.distinct_recent_keys() >= 2
+            && cooled(state.last_dominance_alert)
+        {
+            state.last_dominance_alert = Some(now_ms);
+            let label = if key.len() > 8 { &key[..8] } else { key };
+            alerts.push(ProxyAlert {
+                kind: AlertKind::KeyDominance,
+                timestamp: now_ms,
+                value: share,
+                threshold: config.key_share_threshold,
+                message: format!(
+                    "API Key {} 占最近 {} 秒请求的 {:.0}% ({}/{})",
+                    label,
+                    window_ms / 1000,
+                    share * 100.0,
+                    key_requests,
+                    total
+                ),
+            });
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> AnomalyDetector {
+        let detector = AnomalyDetector::new();
+        detector.set_config(AnomalyConfig {
+            enabled: true,
+            window_secs: 600,
+            spike_factor: 10.0,
+            min_hourly_tokens: 1_000,
+            baseline_hours: 24,
+            key_share_threshold: 0.8,
+            key_share_min_requests: 10,
+            cooldown_secs: 1800,
+        });
+        detector
+    }
+
+    #[test]
+    fn test_token_spike_needs_baseline() {
+        let d = detector();
+        // 3 小时平稳用量：每小时 1000 Token
+        for hour in 0..3 {
+            assert!(d.observe("k1", 1_000, hour * HOUR_MS).is_empty());
+        }
+        // 第 4 小时 10 分钟内用掉 5000 Token，折合 30000/小时 = 30 倍
+        let now = 3 * HOUR_MS;
+        let alerts = d.observe("k1", 5_000, now);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::TokenSpike);
+        assert!((alerts[0].value - 30.0).abs() < 1e-6);
+        // 冷却期内不重复
+        assert!(d.observe("k1", 5_000, now + 1).is_empty());
+
+        // 没有历史基线时不判定突增
+        let fresh = detector();
+        assert!(fresh.observe("k1", 1_000_000, 0).is_empty());
+    }
+
+    #[test]
+    fn test_single_key_dominance_requires_multiple_keys() {
+        let d = detector();
+        for i in 0..20 {
+            assert!(d.observe("only", 0, i).is_empty(), "a single key in use is not an anomaly");
+        }
+
+        let d = detector();
+        d.observe("other", 0, 0);
+        let mut fired = Vec::new();
+        for i in 1..=20 {
+            fired.extend(d.observe("leaked", 0, i));
+        }
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].kind, AlertKind::KeyDominance);
+        assert!(fired[0].value >= 0.8);
+    }
+}
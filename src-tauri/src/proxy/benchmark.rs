@@ -0,0 +1,310 @@
+// 内置压测
+//
+// 以配置的并发数通过本机反代发送合成请求，统计吞吐与延迟分位数，用于验证调参效果
+// (并发上限、连接池、重试等)。可选 mock 上游：在本机启动一个返回固定内容的 v1internal
+// 服务，并在压测期间把反代的生成请求改发到这里，排除上游波动、不消耗配额，只测反代自身的开销。
+// 注意 mock 期间所有经过反代的生成请求都会被改发 (包括其他客户端的请求)。
+
+use axum::{body::Body, http::Uri, response::Response};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::proxy::self_test::{SelfTestProtocol, SELF_TEST_MODEL};
+
+const MAX_CONCURRENCY: u32 = 256;
+const MAX_REQUESTS: u32 = 10_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// 报告中保留的错误样例数
+const MAX_ERROR_SAMPLES: usize = 5;
+
+fn default_concurrency() -> u32 {
+    8
+}
+
+fn default_requests() -> u32 {
+    100
+}
+
+/// 压测参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkOptions {
+    #[serde(default = "default_protocol")]
+    pub protocol: SelfTestProtocol,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default = "default_concurrency")]
+    pub concurrency: u32,
+    #[serde(default = "default_requests")]
+    pub total_requests: u32,
+    /// 使用本机 mock 上游 (不消耗配额)
+    #[serde(default)]
+    pub mock_upstream: bool,
+    /// mock 上游的模拟响应延迟 (毫秒)
+    #[serde(default)]
+    pub mock_latency_ms: u64,
+}
+
+fn default_protocol() -> SelfTestProtocol {
+    SelfTestProtocol::Openai
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            protocol: default_protocol(),
+            model: None,
+            concurrency: default_concurrency(),
+            total_requests: default_requests(),
+            mock_upstream: false,
+            mock_latency_ms: 0,
+        }
+    }
+}
+
+/// 延迟分位数 (毫秒)
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct LatencyPercentiles {
+    pub min: u64,
+    pub avg: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyPercentiles {
+    /// 最近秩法计算分位数
+    fn from_samples(samples: &mut [u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let at = |q: f64| samples[((q * samples.len() as f64).ceil() as usize).clamp(1, samples.len()) - 1];
+        Some(Self {
+            min: samples[0],
+            avg: samples.iter().sum::<u64>() / samples.len() as u64,
+            p50: at(0.50),
+            p90: at(0.90),
+            p99: at(0.99),
+            max: samples[samples.len() - 1],
+        })
+    }
+}
+
+/// 压测报告
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub protocol: SelfTestProtocol,
+    pub model: String,
+    pub concurrency: u32,
+    pub total_requests: u32,
+    pub mock_upstream: bool,
+    pub succeeded: u32,
+    pub failed: u32,
+    pub duration_ms: u64,
+    /// 每秒完成的请求数
+    pub throughput_rps: f64,
+    /// 成功请求的延迟 (读完整个响应)
+    pub latency: Option<LatencyPercentiles>,
+    /// 状态码 -> 请求数 (连接失败记为 0)
+    pub status_counts: BTreeMap<u16, u32>,
+    pub error_samples: Vec<String>,
+}
+
+struct Sample {
+    status: u16,
+    latency_ms: u64,
+    error: Option<String>,
+}
+
+fn summarize(options: &BenchmarkOptions, model: String, samples: Vec<Sample>, elapsed: Duration) -> BenchmarkReport {
+    let mut status_counts = BTreeMap::new();
+    let mut latencies = Vec::new();
+    let mut error_samples = Vec::new();
+    for sample in &samples {
+        *status_counts.entry(sample.status).or_insert(0) += 1;
+        match &sample.error {
+            None => latencies.push(sample.latency_ms),
+            Some(e) if error_samples.len() < MAX_ERROR_SAMPLES && !error_samples.contains(e) => error_samples.push(e.clone()),
+            Some(_) => {}
+        }
+    }
+    let succeeded = latencies.len() as u32;
+    BenchmarkReport {
+        protocol: options.protocol,
+        model,
+        concurrency: options.concurrency,
+        total_requests: samples.len() as u32,
+        mock_upstream: options.mock_upstream,
+        succeeded,
+        failed: samples.len() as u32 - succeeded,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_rps: samples.len() as f64 / elapsed.as_secs_f64().max(0.001),
+        latency: LatencyPercentiles::from_samples(&mut latencies),
+        status_counts,
+        error_samples,
+    }
+}
+
+/// mock 上游：流式方法返回一段 SSE，其余返回一次性 JSON (v1internal 包装格式)
+async fn mock_upstream(uri: Uri, latency_ms: u64) -> Response {
+    if latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+    }
+    let payload = serde_json::json!({
+        "response": {
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "OK" }] },
+                "finishReason": "STOP",
+            }],
+            "usageMetadata": { "promptTokenCount": 8, "candidatesTokenCount": 1, "totalTokenCount": 9 },
+            "modelVersion": "mock",
+        }
+    });
+    let (content_type, body) = if uri.path().ends_with(":streamGenerateContent") {
+        ("text/event-stream", format!("data: {}\n\n", payload))
+    } else {
+        ("application/json", payload.to_string())
+    };
+    Response::builder()
+        .header("content-type", content_type)
+        .body(Body::from(body))
+        .unwrap_or_default()
+}
+
+/// 启动 mock 上游，返回 v1internal 基础地址与停止信号
+async fn start_mock_upstream(latency_ms: u64) -> Result<(String, tokio::sync::oneshot::Sender<()>), String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("启动 mock 上游失败: {}", e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let app = axum::Router::new().fallback(move |uri: Uri| mock_upstream(uri, latency_ms));
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = rx.await;
+            })
+            .await;
+    });
+    Ok((format!("http://{}/v1internal", addr), tx))
+}
+
+/// 对正在运行的反代执行压测；`set_override` 用于在 mock 模式下切换上游地址
+pub async fn run(
+    port: u16,
+    api_key: &str,
+    mut options: BenchmarkOptions,
+    set_override: impl Fn(Option<String>),
+) -> Result<BenchmarkReport, String> {
+    options.concurrency = options.concurrency.clamp(1, MAX_CONCURRENCY);
+    options.total_requests = options.total_requests.clamp(1, MAX_REQUESTS);
+    let model = options
+        .model
+        .clone()
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| SELF_TEST_MODEL.to_string());
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(REQUEST_TIMEOUT)
+        .pool_max_idle_per_host(options.concurrency as usize)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mock = if options.mock_upstream { Some(start_mock_upstream(options.mock_latency_ms).await?) } else { None };
+    if let Some((url, _)) = &mock {
+        set_override(Some(url.clone()));
+    }
+
+    let protocol = options.protocol;
+    let url = format!("http://127.0.0.1:{}{}", port, protocol.path(&model));
+    let body = protocol.body(&model);
+    let started = Instant::now();
+    let samples: Vec<Sample> = futures::stream::iter(0..options.total_requests)
+        .map(|_| {
+            let request = protocol.authorize(client.post(&url), api_key).json(&body);
+            async move {
+                let sent = Instant::now();
+                match request.send().await {
+                    Ok(response) => {
+                        let status = response.status().as_u16();
+                        let text = response.text().await;
+                        let latency_ms = sent.elapsed().as_millis() as u64;
+                        let error = match text {
+                            Ok(_) if (200..300).contains(&status) => None,
+                            Ok(text) => Some(format!("{}: {}", status, text.chars().take(200).collect::<String>())),
+                            Err(e) => Some(format!("读取响应失败: {}", e)),
+                        };
+                        Sample { status, latency_ms, error }
+                    }
+                    Err(e) => Sample { status: 0, latency_ms: sent.elapsed().as_millis() as u64, error: Some(e.to_string()) },
+                }
+            }
+        })
+        .buffer_unordered(options.concurrency as usize)
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    if let Some((_, stop)) = mock {
+        set_override(None);
+        let _ = stop.send(());
+    }
+    Ok(summarize(&options, model, samples, elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_use_nearest_rank() {
+        let mut samples: Vec<u64> = (1..=100).rev().collect();
+        let p = LatencyPercentiles::from_samples(&mut samples).unwrap();
+        assert_eq!((p.min, p.p50, p.p90, p.p99, p.max, p.avg), (1, 50, 90, 99, 100, 50));
+        assert_eq!(LatencyPercentiles::from_samples(&mut []), None);
+        let one = LatencyPercentiles::from_samples(&mut [7]).unwrap();
+        assert_eq!((one.p50, one.p99), (7, 7));
+    }
+
+    #[test]
+    fn test_summary_counts_statuses_and_dedupes_errors() {
+        let sample = |status: u16, latency_ms: u64, error: Option<&str>| Sample { status, latency_ms, error: error.map(str::to_string) };
+        let samples = vec![
+            sample(200, 100, None),
+            sample(200, 300, None),
+            sample(429, 10, Some("429: quota")),
+            sample(429, 12, Some("429: quota")),
+            sample(0, 5, Some("connection refused")),
+        ];
+        let report = summarize(&BenchmarkOptions::default(), "m".into(), samples, Duration::from_secs(2));
+        assert_eq!((report.succeeded, report.failed, report.total_requests), (2, 3, 5));
+        assert_eq!(report.status_counts, BTreeMap::from([(0, 1), (200, 2), (429, 2)]));
+        assert_eq!(report.error_samples, ["429: quota", "connection refused"]);
+        assert!((report.throughput_rps - 2.5).abs() < 1e-9);
+        assert_eq!(report.latency.unwrap().max, 300);
+    }
+
+    #[tokio::test]
+    async fn test_mock_upstream_speaks_v1internal() {
+        let (base, stop) = start_mock_upstream(0).await.unwrap();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let json: serde_json::Value = client
+            .post(format!("{}:generateContent", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(json["response"]["candidates"][0]["content"]["parts"][0]["text"], "OK");
+
+        let sse = client.post(format!("{}:streamGenerateContent?alt=sse", base)).send().await.unwrap();
+        assert_eq!(sse.headers()["content-type"], "text/event-stream");
+        assert!(sse.text().await.unwrap().starts_with("data: {"));
+        let _ = stop.send(());
+    }
+}
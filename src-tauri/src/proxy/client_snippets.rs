@@ -0,0 +1,205 @@
+// 客户端配置片段
+//
+// 根据当前监听地址和选定的 API Key 生成可直接粘贴的客户端配置：Claude Code (环境变量 /
+// settings.json / 管理 MCP)、Cline、Roo Code、Continue 以及 OpenAI SDK (Python / Node.js)，返回给界面复制。
+// Claude Code 走 Anthropic 协议入口，其余客户端走 OpenAI 兼容的 `/v1` 入口。
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// OpenAI 兼容客户端的默认模型
+pub const DEFAULT_OPENAI_MODEL: &str = "gemini-2.5-pro";
+/// Claude Code 的默认模型
+pub const DEFAULT_CLAUDE_MODEL: &str = "claude-sonnet-4-5";
+
+/// 单个客户端的配置片段
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientSnippet {
+    /// 机器可读的客户端标识 (如 `claude_code_env`)
+    pub client: String,
+    pub title: String,
+    /// 代码高亮语言: bash / json / yaml / python / typescript / text
+    pub language: String,
+    /// 片段应放入的文件 (环境变量类片段为 None)
+    pub file: Option<String>,
+    pub content: String,
+}
+
+/// 生成片段所需的参数
+#[derive(Debug, Clone)]
+pub struct SnippetOptions {
+    /// 如 `http://127.0.0.1:8045`，不带结尾 `/`
+    pub base_url: String,
+    pub api_key: String,
+    pub openai_model: String,
+    pub claude_model: String,
+}
+
+/// 一键复制的单个值
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyTarget {
+    /// 反代根地址 (Anthropic 协议入口)
+    BaseUrl,
+    /// OpenAI 兼容入口 (`/v1`)
+    OpenaiBaseUrl,
+    ApiKey,
+    /// 一行 `export ANTHROPIC_BASE_URL=.. ANTHROPIC_AUTH_TOKEN=..`
+    AnthropicEnv,
+}
+
+/// 生成要复制到剪贴板的值
+pub fn copy_value(target: CopyTarget, base_url: &str, api_key: &str) -> String {
+    match target {
+        CopyTarget::BaseUrl => base_url.to_string(),
+        CopyTarget::OpenaiBaseUrl => format!("{}/v1", base_url),
+        CopyTarget::ApiKey => api_key.to_string(),
+        CopyTarget::AnthropicEnv => format!(
+            "export ANTHROPIC_BASE_URL=\"{}\" ANTHROPIC_AUTH_TOKEN=\"{}\"",
+            base_url, api_key
+        ),
+    }
+}
+
+fn snippet(client: &str, title: &str, language: &str, file: Option<&str>, content: String) -> ClientSnippet {
+    ClientSnippet {
+        client: client.to_string(),
+        title: title.to_string(),
+        language: language.to_string(),
+        file: file.map(str::to_string),
+        content,
+    }
+}
+
+/// 生成所有客户端的配置片段
+pub fn generate(options: &SnippetOptions) -> Vec<ClientSnippet> {
+    let SnippetOptions { base_url, api_key, openai_model, claude_model } = options;
+    let openai_base = format!("{}/v1", base_url);
+
+    let claude_settings = json!({
+        "env": {
+            "ANTHROPIC_BASE_URL": base_url,
+            "ANTHROPIC_AUTH_TOKEN": api_key,
+            "ANTHROPIC_MODEL": claude_model,
+        }
+    });
+    let cline_like = |name: &str| {
+        format!(
+            "{} 设置 → API Provider 选择 \"OpenAI Compatible\":\n  Base URL: {}\n  API Key:  {}\n  Model ID: {}",
+            name, openai_base, api_key, openai_model
+        )
+    };
+
+    vec![
+        snippet(
+            "claude_code_env",
+            "Claude Code (环境变量)",
+            "bash",
+            None,
+            format!(
+                "export ANTHROPIC_BASE_URL=\"{}\"\nexport ANTHROPIC_AUTH_TOKEN=\"{}\"\nexport ANTHROPIC_MODEL=\"{}\"\nclaude",
+                base_url, api_key, claude_model
+            ),
+        ),
+        snippet(
+            "claude_code_settings",
+            "Claude Code (settings.json)",
+            "json",
+            Some("~/.claude/settings.json"),
+            serde_json::to_string_pretty(&claude_settings).unwrap_or_default(),
+        ),
+        snippet(
+            "claude_code_mcp",
+            "Claude Code (管理 MCP)",
+            "bash",
+            None,
+            format!(
+                "claude mcp add --transport http antigravity-manager {}/mcp/manager/mcp --header \"Authorization: Bearer {}\"",
+                base_url, api_key
+            ),
+        ),
+        snippet("cline", "Cline", "text", None, cline_like("Cline")),
+        snippet("roo_code", "Roo Code", "text", None, cline_like("Roo Code")),
+        snippet(
+            "continue",
+            "Continue",
+            "yaml",
+            Some("~/.continue/config.yaml"),
+            format!(
+                "models:\n  - name: Antigravity ({model})\n    provider: openai\n    model: {model}\n    apiBase: {base}\n    apiKey: {key}\n    roles:\n      - chat\n      - edit\n      - apply\n",
+                model = openai_model,
+                base = openai_base,
+                key = api_key
+            ),
+        ),
+        snippet(
+            "openai_env",
+            "OpenAI SDK (环境变量)",
+            "bash",
+            None,
+            format!("export OPENAI_BASE_URL=\"{}\"\nexport OPENAI_API_KEY=\"{}\"", openai_base, api_key),
+        ),
+        snippet(
+            "openai_python",
+            "OpenAI SDK (Python)",
+            "python",
+            None,
+            format!(
+                "from openai import OpenAI\n\nclient = OpenAI(base_url=\"{}\", api_key=\"{}\")\nresp = client.chat.completions.create(\n    model=\"{}\",\n    messages=[{{\"role\": \"user\", \"content\": \"Hello\"}}],\n)\nprint(resp.choices[0].message.content)",
+                openai_base, api_key, openai_model
+            ),
+        ),
+        snippet(
+            "openai_node",
+            "OpenAI SDK (Node.js)",
+            "typescript",
+            None,
+            format!(
+                "import OpenAI from \"openai\";\n\nconst client = new OpenAI({{ baseURL: \"{}\", apiKey: \"{}\" }});\nconst resp = await client.chat.completions.create({{\n  model: \"{}\",\n  messages: [{{ role: \"user\", content: \"Hello\" }}],\n}});\nconsole.log(resp.choices[0].message.content);",
+                openai_base, api_key, openai_model
+            ),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets_use_base_url_and_key() {
+        let options = SnippetOptions {
+            base_url: "http://192.168.1.5:8045".to_string(),
+            api_key: "sk-test".to_string(),
+            openai_model: DEFAULT_OPENAI_MODEL.to_string(),
+            claude_model: DEFAULT_CLAUDE_MODEL.to_string(),
+        };
+        let snippets = generate(&options);
+        let clients: Vec<&str> = snippets.iter().map(|s| s.client.as_str()).collect();
+        for expected in ["claude_code_env", "claude_code_settings", "cline", "roo_code", "continue", "openai_python", "openai_node"] {
+            assert!(clients.contains(&expected), "{}", expected);
+        }
+        for s in &snippets {
+            assert!(s.content.contains("sk-test"), "{}", s.client);
+        }
+
+        let claude = snippets.iter().find(|s| s.client == "claude_code_settings").unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&claude.content).unwrap();
+        // Claude Code 使用 Anthropic 入口，不带 /v1
+        assert_eq!(settings["env"]["ANTHROPIC_BASE_URL"], "http://192.168.1.5:8045");
+        let continue_cfg = snippets.iter().find(|s| s.client == "continue").unwrap();
+        assert!(continue_cfg.content.contains("apiBase: http://192.168.1.5:8045/v1"));
+    }
+
+    #[test]
+    fn test_copy_values() {
+        let base = "http://127.0.0.1:8045";
+        assert_eq!(copy_value(CopyTarget::BaseUrl, base, "sk-1"), base);
+        assert_eq!(copy_value(CopyTarget::OpenaiBaseUrl, base, "sk-1"), "http://127.0.0.1:8045/v1");
+        assert_eq!(copy_value(CopyTarget::ApiKey, base, "sk-1"), "sk-1");
+        assert_eq!(
+            copy_value(CopyTarget::AnthropicEnv, base, "sk-1"),
+            "export ANTHROPIC_BASE_URL=\"http://127.0.0.1:8045\" ANTHROPIC_AUTH_TOKEN=\"sk-1\""
+        );
+    }
+}
@@ -2,25 +2,26 @@ use serde::{Deserialize, Serialize};
 // use std::path::PathBuf;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// 上游客户端的配置位于 antigravity-proxy-core
+pub use antigravity_proxy_core::upstream::config::{
+    UpstreamPoolConfig, UpstreamProxyConfig, UpstreamRetryConfig, UpstreamTimeoutConfig, UpstreamTlsConfig,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProxyAuthMode {
+    #[default]
     Off,
     Strict,
     AllExceptHealth,
     Auto,
 }
 
-impl Default for ProxyAuthMode {
-    fn default() -> Self {
-        Self::Off
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ZaiDispatchMode {
     /// Never use z.ai.
+    #[default]
     Off,
     /// Use z.ai for all Anthropic protocol requests.
     Exclusive,
@@ -30,12 +31,6 @@ pub enum ZaiDispatchMode {
     Fallback,
 }
 
-impl Default for ZaiDispatchMode {
-    fn default() -> Self {
-        Self::Off
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZaiModelDefaults {
     /// Default model for "opus" family (when the incoming model is a Claude id).
@@ -59,7 +54,7 @@ impl Default for ZaiModelDefaults {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ZaiMcpConfig {
     #[serde(default)]
     pub enabled: bool,
@@ -71,17 +66,6 @@ pub struct ZaiMcpConfig {
     pub vision_enabled: bool,
 }
 
-impl Default for ZaiMcpConfig {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            web_search_enabled: false,
-            web_reader_enabled: false,
-            vision_enabled: false,
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZaiConfig {
     #[serde(default)]
@@ -135,6 +119,10 @@ pub struct ExperimentalConfig {
     /// 用于解决客户端因 Gemini 上下文过大而错误触发压缩的问题
     #[serde(default = "default_true")]
     pub enable_usage_scaling: bool,
+
+    /// 合并同时到达的相同非流式请求 (只调用一次上游，节省配额)
+    #[serde(default = "default_true")]
+    pub enable_request_coalescing: bool,
 }
 
 impl Default for ExperimentalConfig {
@@ -144,6 +132,7 @@ impl Default for ExperimentalConfig {
             enable_tool_loop_recovery: true,
             enable_cross_model_checks: true,
             enable_usage_scaling: true,
+            enable_request_coalescing: true,
         }
     }
 }
@@ -169,6 +158,10 @@ pub struct ProxyConfig {
     /// - auto: recommended defaults (currently: allow_lan_access => all_except_health, else off)
     #[serde(default)]
     pub auth_mode: ProxyAuthMode,
+
+    /// 安全模式: 未开启鉴权时拒绝监听非本机地址，运行中关闭鉴权后也只接受本机请求 (默认开启)
+    #[serde(default = "default_true")]
+    pub require_auth_for_lan: bool,
     
     /// 监听端口
     pub port: u16,
@@ -196,9 +189,57 @@ pub struct ProxyConfig {
     #[serde(default)]
     pub upstream_proxy: UpstreamProxyConfig,
 
+    /// 上游请求重试策略
+    #[serde(default)]
+    pub upstream_retry: UpstreamRetryConfig,
+
+    /// 上游请求超时 (按流式 / 非流式分别设置)
+    #[serde(default)]
+    pub upstream_timeouts: UpstreamTimeoutConfig,
+
+    /// 上游连接池与 HTTP/2 参数
+    #[serde(default)]
+    pub upstream_pool: UpstreamPoolConfig,
+
+    /// 上游 TLS 校验 (自定义 CA 证书包与证书固定)
+    #[serde(default)]
+    pub upstream_tls: UpstreamTlsConfig,
+
+    /// 错误率 / 连续 429 告警
+    #[serde(default)]
+    pub alerts: AlertConfig,
+
+    /// 用量异常检测 (Token 突增 / 单个 Key 占据大部分流量)
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
+
+    /// 实时请求检查器 (脱敏摘要推送到界面)
+    #[serde(default)]
+    pub inspector: InspectorConfig,
+
+    /// 对话记录归档 (保存到数据目录)
+    #[serde(default)]
+    pub transcripts: TranscriptConfig,
+
+    /// 请求日志、对话归档与缓存的内存 / 磁盘上限
+    #[serde(default)]
+    pub memory_limits: MemoryLimitsConfig,
+
+    /// 请求钩子脚本 (收到请求 / 请求完成时运行外部命令)
+    #[serde(default)]
+    pub hooks: RequestHookConfig,
+
+    /// 远程访问隧道 (SSH 反向隧道 / Cloudflare Quick Tunnel)
+    #[serde(default)]
+    pub tunnel: TunnelConfig,
+
     /// z.ai provider configuration (Anthropic-compatible).
     #[serde(default)]
     pub zai: ZaiConfig,
+
+    /// 账号之外的其他上游后端 (Vertex AI 服务账号、Gemini API Key、OpenAI 兼容上游)
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
     
     /// 账号调度配置 (粘性会话/限流重试)
     #[serde(default)]
@@ -207,15 +248,401 @@ pub struct ProxyConfig {
     /// 实验性功能配置
     #[serde(default)]
     pub experimental: ExperimentalConfig,
+
+    /// 账号池: 绑定到池的 API Key 只会使用池内账号
+    #[serde(default)]
+    pub account_pools: Vec<AccountPool>,
+
+    /// 未绑定账号池的请求 (主 API Key 等) 使用的账号池，为空时使用全部账号
+    #[serde(default)]
+    pub default_pool: Option<String>,
+}
+
+/// 账号池配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AccountPool {
+    /// 池名称 (唯一)
+    pub name: String,
+    /// 池内账号 (账号 ID、邮箱或 "tag:<标签>")
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// 绑定到此池的 API Key
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+}
+
+/// 错误率告警配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 窗口内错误率超过该比例时告警 (0.2 = 20%，0 表示不检查)
+    #[serde(default = "default_alert_error_rate")]
+    pub error_rate_threshold: f64,
+    /// 错误率统计窗口 (秒)
+    #[serde(default = "default_alert_window_secs")]
+    pub error_rate_window_secs: u64,
+    /// 窗口内请求数少于该值时不计算错误率，避免样本过少误报
+    #[serde(default = "default_alert_min_requests")]
+    pub min_requests: u32,
+    /// 连续 N 次 429 时告警 (0 表示不检查)
+    #[serde(default = "default_alert_consecutive_429")]
+    pub consecutive_429: u32,
+    /// 同类告警的最短间隔 (秒)
+    #[serde(default = "default_alert_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// 可选的 Webhook 地址 (POST JSON)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            error_rate_threshold: default_alert_error_rate(),
+            error_rate_window_secs: default_alert_window_secs(),
+            min_requests: default_alert_min_requests(),
+            consecutive_429: default_alert_consecutive_429(),
+            cooldown_secs: default_alert_cooldown_secs(),
+            webhook_url: None,
+        }
+    }
+}
+
+fn default_alert_error_rate() -> f64 {
+    0.2
+}
+
+fn default_alert_window_secs() -> u64 {
+    300
+}
+
+fn default_alert_min_requests() -> u32 {
+    10
+}
+
+fn default_alert_consecutive_429() -> u32 {
+    5
+}
+
+fn default_alert_cooldown_secs() -> u64 {
+    600
+}
+
+/// 用量异常检测配置 (告警复用 alerts 的 Webhook)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnomalyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 统计最近用量的窗口 (秒)
+    #[serde(default = "default_anomaly_window_secs")]
+    pub window_secs: u64,
+    /// 折算后的每小时 Token 数达到平时平均值的多少倍时告警 (0 表示不检查)
+    #[serde(default = "default_anomaly_spike_factor")]
+    pub spike_factor: f64,
+    /// 折算后的每小时 Token 数低于该值时不判定突增，避免低用量时误报
+    #[serde(default = "default_anomaly_min_hourly_tokens")]
+    pub min_hourly_tokens: u64,
+    /// 计算平时用量的历史小时数
+    #[serde(default = "default_anomaly_baseline_hours")]
+    pub baseline_hours: u32,
+    /// 单个 Key 占窗口内请求的比例达到该值时告警 (0 表示不检查)
+    #[serde(default = "default_anomaly_key_share")]
+    pub key_share_threshold: f64,
+    /// 窗口内请求数少于该值时不检查 Key 占比
+    #[serde(default = "default_anomaly_key_share_min_requests")]
+    pub key_share_min_requests: u32,
+    /// 同类告警的最短间隔 (秒)
+    #[serde(default = "default_anomaly_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: default_anomaly_window_secs(),
+            spike_factor: default_anomaly_spike_factor(),
+            min_hourly_tokens: default_anomaly_min_hourly_tokens(),
+            baseline_hours: default_anomaly_baseline_hours(),
+            key_share_threshold: default_anomaly_key_share(),
+            key_share_min_requests: default_anomaly_key_share_min_requests(),
+            cooldown_secs: default_anomaly_cooldown_secs(),
+        }
+    }
+}
+
+fn default_anomaly_window_secs() -> u64 {
+    600
+}
+
+fn default_anomaly_spike_factor() -> f64 {
+    10.0
+}
+
+fn default_anomaly_min_hourly_tokens() -> u64 {
+    100_000
+}
+
+fn default_anomaly_baseline_hours() -> u32 {
+    24
+}
+
+fn default_anomaly_key_share() -> f64 {
+    0.8
+}
+
+fn default_anomaly_key_share_min_requests() -> u32 {
+    50
+}
+
+fn default_anomaly_cooldown_secs() -> u64 {
+    1800
+}
+
+/// 实时请求检查器配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InspectorConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 预览内容最多保留的字符数
+    #[serde(default = "default_inspector_max_chars")]
+    pub max_content_chars: usize,
+    /// 额外的脱敏正则 (匹配内容替换为 [REDACTED])
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+}
+
+impl Default for InspectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_content_chars: default_inspector_max_chars(),
+            redact_patterns: Vec::new(),
+        }
+    }
+}
+
+fn default_inspector_max_chars() -> usize {
+    200
 }
 
-/// 上游代理配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct UpstreamProxyConfig {
-    /// 是否启用
+/// 对话记录的保存格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Json,
+    Markdown,
+    Both,
+}
+
+/// 对话记录归档配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptConfig {
+    #[serde(default)]
     pub enabled: bool,
-    /// 代理地址 (http://, https://, socks5://)
-    pub url: String,
+    #[serde(default)]
+    pub format: TranscriptFormat,
+}
+
+/// 内存 / 磁盘上限 (见 proxy::memory_limits)，0 表示不限制
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoryLimitsConfig {
+    /// 内存中请求日志 (含请求 / 响应体) 的上限 (MB)
+    #[serde(default = "default_log_buffer_mb")]
+    pub log_buffer_mb: u64,
+    /// 请求日志库 (proxy_logs.db) 的上限 (MB)
+    #[serde(default = "default_log_db_mb")]
+    pub log_db_mb: u64,
+    /// 对话归档目录的上限 (MB)
+    #[serde(default = "default_transcripts_mb")]
+    pub transcripts_mb: u64,
+    /// 签名缓存的条目上限 (会话层；工具层与模型族层分别为其 1/2 与 1/5)
+    #[serde(default = "default_signature_cache_entries")]
+    pub signature_cache_entries: usize,
+    /// 延迟统计中 (模型, 账号) 序列的上限
+    #[serde(default = "default_latency_series")]
+    pub latency_series: usize,
+}
+
+impl Default for MemoryLimitsConfig {
+    fn default() -> Self {
+        Self {
+            log_buffer_mb: default_log_buffer_mb(),
+            log_db_mb: default_log_db_mb(),
+            transcripts_mb: default_transcripts_mb(),
+            signature_cache_entries: default_signature_cache_entries(),
+            latency_series: default_latency_series(),
+        }
+    }
+}
+
+fn default_log_buffer_mb() -> u64 {
+    64
+}
+
+fn default_log_db_mb() -> u64 {
+    512
+}
+
+fn default_transcripts_mb() -> u64 {
+    1024
+}
+
+fn default_signature_cache_entries() -> usize {
+    1000
+}
+
+fn default_latency_series() -> usize {
+    500
+}
+
+/// 请求钩子配置
+///
+/// 命令通过系统 shell 执行 (`sh -c` / `cmd /C`)，请求摘要以 JSON 写入标准输入
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestHookConfig {
+    /// 收到请求时运行的命令
+    #[serde(default)]
+    pub on_request: Option<String>,
+    /// 请求完成时运行的命令
+    #[serde(default)]
+    pub on_complete: Option<String>,
+    /// 等待 on_request 执行完毕并使用其输出替换请求体；退出码非 0 时拒绝请求
+    #[serde(default)]
+    pub transform: bool,
+    /// 单次执行超时 (秒)
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    5
+}
+
+impl Default for RequestHookConfig {
+    fn default() -> Self {
+        Self {
+            on_request: None,
+            on_complete: None,
+            transform: false,
+            timeout_secs: default_hook_timeout_secs(),
+        }
+    }
+}
+
+/// 上游后端
+///
+/// 请求的模型以 `<name>/` 开头 (前缀会被去掉) 或匹配 `models` 中的模式 (支持 `*` 通配) 时，
+/// 由该后端处理，不再使用账号池
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProviderConfig {
+    /// 名称 (唯一，也用作模型前缀)
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 路由到此后端的模型
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(flatten)]
+    pub kind: ProviderKind,
+}
+
+/// 后端类型与认证信息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// OpenAI 兼容上游 (base_url 包含版本路径，如 https://api.example.com/v1)
+    OpenaiCompatible { base_url: String, api_key: String },
+    /// Gemini API (AI Studio) Key
+    GeminiApiKey {
+        api_key: String,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    /// Vertex AI 服务账号 (service_account 为 JSON 文件路径或 JSON 内容)
+    VertexServiceAccount {
+        service_account: String,
+        /// 为空时使用服务账号所属项目
+        #[serde(default)]
+        project_id: Option<String>,
+        #[serde(default = "default_vertex_location")]
+        location: String,
+    },
+}
+
+fn default_vertex_location() -> String {
+    "us-central1".to_string()
+}
+
+/// 隧道类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelProvider {
+    /// `ssh -R` 把本机端口转发到自己的服务器
+    #[default]
+    Ssh,
+    /// `cloudflared tunnel --url`，无需服务器，分配随机 trycloudflare.com 地址
+    Cloudflared,
+}
+
+/// 远程访问隧道配置
+///
+/// 隧道开启期间反代强制要求 API Key (即使认证模式为自动且未开启局域网访问)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TunnelConfig {
+    /// 反代启动时自动建立隧道
+    #[serde(default)]
+    pub auto_start: bool,
+    #[serde(default)]
+    pub provider: TunnelProvider,
+    /// SSH 目标 (user@host)
+    #[serde(default)]
+    pub ssh_target: String,
+    /// SSH 端口
+    #[serde(default = "default_ssh_port")]
+    pub ssh_port: u16,
+    /// SSH 私钥路径 (为空时使用 ssh 默认配置)
+    #[serde(default)]
+    pub ssh_identity_file: Option<String>,
+    /// 服务器上监听的端口
+    #[serde(default = "default_tunnel_remote_port")]
+    pub remote_port: u16,
+    /// 服务器上监听的地址，为空时只监听服务器本机 (需要服务器开启 GatewayPorts 才能对外监听)
+    #[serde(default)]
+    pub remote_bind_address: String,
+    /// 对外访问地址 (如服务器前面的 HTTPS 反代)，为空时按 http://<host>:<remote_port> 显示
+    #[serde(default)]
+    pub public_url: Option<String>,
+    /// cloudflared 可执行文件路径 (为空时从 PATH 查找)
+    #[serde(default)]
+    pub cloudflared_path: Option<String>,
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+fn default_tunnel_remote_port() -> u16 {
+    8045
+}
+
+impl Default for TunnelConfig {
+    fn default() -> Self {
+        Self {
+            auto_start: false,
+            provider: TunnelProvider::default(),
+            ssh_target: String::new(),
+            ssh_port: default_ssh_port(),
+            ssh_identity_file: None,
+            remote_port: default_tunnel_remote_port(),
+            remote_bind_address: String::new(),
+            public_url: None,
+            cloudflared_path: None,
+        }
+    }
 }
 
 impl Default for ProxyConfig {
@@ -224,6 +651,7 @@ impl Default for ProxyConfig {
             enabled: false,
             allow_lan_access: false, // 默认仅本机访问，隐私优先
             auth_mode: ProxyAuthMode::default(),
+            require_auth_for_lan: true,
             port: 8045,
             api_key: format!("sk-{}", uuid::Uuid::new_v4().simple()),
             auto_start: false,
@@ -231,9 +659,23 @@ impl Default for ProxyConfig {
             request_timeout: default_request_timeout(),
             enable_logging: false, // 默认关闭，节省性能
             upstream_proxy: UpstreamProxyConfig::default(),
+            upstream_retry: UpstreamRetryConfig::default(),
+            upstream_timeouts: UpstreamTimeoutConfig::default(),
+            upstream_pool: UpstreamPoolConfig::default(),
+            upstream_tls: UpstreamTlsConfig::default(),
+            alerts: AlertConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            inspector: InspectorConfig::default(),
+            transcripts: TranscriptConfig::default(),
+            memory_limits: MemoryLimitsConfig::default(),
+            hooks: RequestHookConfig::default(),
+            tunnel: TunnelConfig::default(),
             zai: ZaiConfig::default(),
+            providers: Vec::new(),
             scheduling: crate::proxy::sticky_config::StickySessionConfig::default(),
             experimental: ExperimentalConfig::default(),
+            account_pools: Vec::new(),
+            default_pool: None,
         }
     }
 }
@@ -269,4 +711,54 @@ impl ProxyConfig {
             "127.0.0.1"
         }
     }
+
+    /// 允许局域网访问但关闭了鉴权 (本机以外的设备无需 API Key 即可访问)
+    pub fn exposed_without_auth(&self) -> bool {
+        self.allow_lan_access && matches!(self.auth_mode, ProxyAuthMode::Off)
+    }
+
+    /// 安全模式下拒绝在未开启鉴权时监听非本机地址 (启动服务前调用)
+    pub fn check_bind_safety(&self) -> Result<(), String> {
+        if self.require_auth_for_lan && self.exposed_without_auth() {
+            return Err(crate::modules::i18n::tr("backend.errors.lan_requires_auth", &[]));
+        }
+        Ok(())
+    }
+
+    /// 与运行中的配置相比，变更后需要重启服务才能生效的字段 (监听端口与地址)
+    pub fn restart_required_fields(&self, running: &ProxyConfig) -> Vec<String> {
+        let mut fields = Vec::new();
+        if self.port != running.port {
+            fields.push("port".to_string());
+        }
+        if self.allow_lan_access != running.allow_lan_access {
+            fields.push("allow_lan_access".to_string());
+        }
+        fields
+    }
+
+    /// 热重载后实际生效的配置：监听相关字段保持运行中的值
+    pub fn hot_reloadable(&self, running: &ProxyConfig) -> ProxyConfig {
+        ProxyConfig { port: running.port, allow_lan_access: running.allow_lan_access, ..self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hot_reload_keeps_listener_fields() {
+        let running = ProxyConfig::default();
+        let mut edited = running.clone();
+        edited.port = running.port + 1;
+        edited.allow_lan_access = !running.allow_lan_access;
+        edited.request_timeout = running.request_timeout + 30;
+
+        assert_eq!(edited.restart_required_fields(&running), ["port", "allow_lan_access"]);
+        let effective = edited.hot_reloadable(&running);
+        assert_eq!((effective.port, effective.allow_lan_access), (running.port, running.allow_lan_access));
+        assert_eq!(effective.request_timeout, edited.request_timeout);
+        assert!(running.restart_required_fields(&running).is_empty());
+    }
 }
@@ -0,0 +1,130 @@
+// 配置变更预览 (dry-run)
+//
+// 应用配置前 (界面保存或外部修改的配置文件) 先对比当前生效的配置，列出每个会变化的字段
+// 以及哪些变化需要重启监听才能生效，供界面在端口、监听地址等破坏性变更前让用户确认。
+// 对象逐键比较 (模型映射按别名列出)，数组整体比较；凭证类字段只报告有变化，不返回值。
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::proxy::ProxyConfig;
+use crate::utils::redact::{is_secret_field, REDACTED};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// 单个字段的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    /// 点分隔的字段路径 (如 `custom_mapping.gpt-4o`)
+    pub field: String,
+    pub kind: ChangeKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    /// 需要重启服务才能生效
+    pub restart_required: bool,
+}
+
+/// 变更预览
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiff {
+    /// 对比的是运行中服务的配置 (否则是已保存的配置)
+    pub against_running: bool,
+    pub changes: Vec<ConfigChange>,
+    /// 需要重启服务才能生效的顶层字段
+    pub restart_required: Vec<String>,
+}
+
+fn redact(value: Value, secret: bool) -> Value {
+    if secret { Value::String(REDACTED.to_string()) } else { value }
+}
+
+fn diff_values(path: &str, secret: bool, before: Option<&Value>, after: Option<&Value>, out: &mut Vec<ConfigChange>) {
+    let change = |kind, before: Option<&Value>, after: Option<&Value>| ConfigChange {
+        field: path.to_string(),
+        kind,
+        before: before.cloned().map(|v| redact(v, secret)),
+        after: after.cloned().map(|v| redact(v, secret)),
+        restart_required: false,
+    };
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => diff_objects(path, secret, a, b, out),
+        (Some(a), Some(b)) if a != b => out.push(change(ChangeKind::Modified, Some(a), Some(b))),
+        (None, Some(b)) => out.push(change(ChangeKind::Added, None, Some(b))),
+        (Some(a), None) => out.push(change(ChangeKind::Removed, Some(a), None)),
+        _ => {}
+    }
+}
+
+fn diff_objects(path: &str, secret: bool, a: &Map<String, Value>, b: &Map<String, Value>, out: &mut Vec<ConfigChange>) {
+    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        diff_values(&field, secret || is_secret_field(key), a.get(key), b.get(key), out);
+    }
+}
+
+/// 对比两份配置
+pub fn diff(current: &ProxyConfig, proposed: &ProxyConfig, against_running: bool) -> ConfigDiff {
+    let before = serde_json::to_value(current).unwrap_or_default();
+    let after = serde_json::to_value(proposed).unwrap_or_default();
+    let mut changes = Vec::new();
+    diff_values("", false, Some(&before), Some(&after), &mut changes);
+
+    // 服务未运行时下次启动直接使用新配置，不存在重启问题
+    let restart_required = if against_running { proposed.restart_required_fields(current) } else { Vec::new() };
+    for change in &mut changes {
+        let top = change.field.split('.').next().unwrap_or_default();
+        change.restart_required = restart_required.iter().any(|f| f == top);
+    }
+    ConfigDiff { against_running, changes, restart_required }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lists_nested_changes_and_restart_fields() {
+        let current = ProxyConfig::default();
+        let mut proposed = current.clone();
+        proposed.port = current.port + 1;
+        proposed.api_key = "sk-new".to_string();
+        proposed.custom_mapping.insert("gpt-4o".to_string(), "gemini-2.5-pro".to_string());
+
+        let report = diff(&current, &proposed, true);
+        assert_eq!(report.restart_required, vec!["port".to_string()]);
+        let fields: Vec<(&str, ChangeKind, bool)> =
+            report.changes.iter().map(|c| (c.field.as_str(), c.kind, c.restart_required)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("api_key", ChangeKind::Modified, false),
+                ("custom_mapping.gpt-4o", ChangeKind::Added, false),
+                ("port", ChangeKind::Modified, true),
+            ]
+        );
+        // 凭证不出现在预览中
+        let key = &report.changes[0];
+        assert_eq!(key.after, Some(Value::String(REDACTED.to_string())));
+    }
+
+    #[test]
+    fn test_identical_or_stopped_service() {
+        let current = ProxyConfig::default();
+        assert!(diff(&current, &current, true).changes.is_empty());
+
+        let mut proposed = current.clone();
+        proposed.allow_lan_access = !current.allow_lan_access;
+        let report = diff(&current, &proposed, false);
+        assert_eq!(report.changes.len(), 1);
+        assert!(report.restart_required.is_empty() && !report.changes[0].restart_required);
+    }
+}
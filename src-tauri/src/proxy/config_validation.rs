@@ -0,0 +1,312 @@
+// 配置校验
+//
+// 检查反代配置中的冲突与无效项 (重复的模型别名、无法监听的地址、无效的正则、未知的目标模型、
+// 账号池冲突等)，返回结构化的错误 / 警告供界面展示，而不是等到请求时才失败。
+// 错误表示该项在运行时一定不能按预期工作；警告表示可能不是用户的本意。
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::proxy::common::model_mapping::is_known_model;
+use crate::proxy::{ProxyAuthMode, ProxyConfig, ZaiDispatchMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/// 单条校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// 点分隔的字段路径 (如 `custom_mapping.gpt-4*`)
+    pub field: String,
+    /// 机器可读的问题类型
+    pub code: String,
+    pub message: String,
+}
+
+/// 校验报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigValidationReport {
+    /// 没有错误 (可以有警告)
+    pub valid: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// 校验所需的外部信息
+#[derive(Debug, Default)]
+pub struct ValidationContext {
+    /// 本地账号的 ID 与邮箱 (小写)
+    pub accounts: HashSet<String>,
+    pub tags: HashSet<String>,
+    /// 是否检测端口占用 (服务已在该端口运行时应关闭)
+    pub probe_port: bool,
+}
+
+struct Issues(Vec<ConfigIssue>);
+
+impl Issues {
+    fn push(&mut self, severity: IssueSeverity, field: impl Into<String>, code: &str, message: impl Into<String>) {
+        self.0.push(ConfigIssue { severity, field: field.into(), code: code.to_string(), message: message.into() });
+    }
+
+    fn error(&mut self, field: impl Into<String>, code: &str, message: impl Into<String>) {
+        self.push(IssueSeverity::Error, field, code, message);
+    }
+
+    fn warn(&mut self, field: impl Into<String>, code: &str, message: impl Into<String>) {
+        self.push(IssueSeverity::Warning, field, code, message);
+    }
+}
+
+fn check_listener(config: &ProxyConfig, ctx: &ValidationContext, issues: &mut Issues) {
+    if config.port == 0 {
+        issues.error("port", "invalid_port", "端口不能为 0");
+        return;
+    }
+    if config.port < 1024 {
+        issues.warn("port", "privileged_port", format!("端口 {} 小于 1024，在 macOS / Linux 上需要管理员权限", config.port));
+    }
+    if ctx.probe_port {
+        if let Err(e) = std::net::TcpListener::bind((config.get_bind_address(), config.port)) {
+            issues.error("port", "bind_failed", format!("无法监听 {}:{}: {}", config.get_bind_address(), config.port, e));
+        }
+    }
+    if config.request_timeout == 0 {
+        issues.error("request_timeout", "invalid_timeout", "请求超时不能为 0");
+    }
+}
+
+fn check_auth(config: &ProxyConfig, issues: &mut Issues) {
+    let requires_key = match config.auth_mode {
+        ProxyAuthMode::Off => false,
+        ProxyAuthMode::Strict | ProxyAuthMode::AllExceptHealth => true,
+        ProxyAuthMode::Auto => config.allow_lan_access,
+    };
+    if requires_key && config.api_key.trim().is_empty() {
+        issues.error("api_key", "missing_api_key", "已开启鉴权但 API Key 为空，所有请求都会被拒绝");
+    }
+    if config.exposed_without_auth() {
+        if config.require_auth_for_lan {
+            issues.error("auth_mode", "lan_without_auth", "安全模式下未开启鉴权时不能允许局域网访问，服务将拒绝启动");
+        } else {
+            issues.warn("auth_mode", "lan_without_auth", "已允许局域网访问但未开启鉴权，局域网内任何人都可以使用反代");
+        }
+    }
+}
+
+/// 单个 `*` 的通配符拆成 (前缀, 后缀)
+fn split_wildcard(pattern: &str) -> Option<(&str, &str)> {
+    pattern.find('*').map(|i| (&pattern[..i], &pattern[i + 1..]))
+}
+
+fn check_mapping(config: &ProxyConfig, issues: &mut Issues) {
+    let mut keys: Vec<&String> = config.custom_mapping.keys().collect();
+    keys.sort();
+
+    let mut normalized: HashMap<String, &str> = HashMap::new();
+    for key in &keys {
+        let target = &config.custom_mapping[*key];
+        let field = format!("custom_mapping.{}", key);
+        if key.trim().is_empty() {
+            issues.error(&field, "empty_alias", "模型别名不能为空");
+            continue;
+        }
+        if target.trim().is_empty() {
+            issues.error(&field, "empty_target", format!("{} 的目标模型为空", key));
+        } else if target.contains('*') {
+            issues.error(&field, "wildcard_target", format!("目标模型 {} 不能包含通配符", target));
+        } else if !is_known_model(target.trim()) && !config.custom_mapping.contains_key(target) {
+            issues.warn(&field, "unknown_model", format!("目标模型 {} 不在已知模型列表中，上游可能拒绝", target));
+        }
+        if config.custom_mapping.contains_key(target) && *target != **key {
+            issues.warn(&field, "chained_mapping", format!("映射不会级联: {} -> {} 不会继续按 {} 的规则映射", key, target, target));
+        }
+        if key.matches('*').count() > 1 {
+            issues.warn(&field, "multiple_wildcards", "只支持一个 *，之后的 * 按普通字符匹配");
+        }
+        // 大小写或首尾空白不同的别名，客户端很可能认为是同一个
+        if let Some(previous) = normalized.insert(key.trim().to_ascii_lowercase(), key.as_str()) {
+            issues.warn(&field, "duplicate_alias", format!("别名 {:?} 与 {:?} 仅大小写或空白不同", key, previous));
+        }
+    }
+
+    // 通配符规则按 HashMap 顺序匹配，两条规则能匹配同一模型时结果不确定
+    let wildcards: Vec<(&String, (&str, &str))> = keys.iter().filter_map(|k| split_wildcard(k).map(|w| (*k, w))).collect();
+    for (i, (a, (pa, sa))) in wildcards.iter().enumerate() {
+        for (b, (pb, sb)) in &wildcards[i + 1..] {
+            let prefix_overlap = pa.starts_with(pb) || pb.starts_with(pa);
+            let suffix_overlap = sa.ends_with(sb) || sb.ends_with(sa);
+            if prefix_overlap && suffix_overlap && config.custom_mapping[*a] != config.custom_mapping[*b] {
+                issues.warn(
+                    format!("custom_mapping.{}", b),
+                    "overlapping_wildcards",
+                    format!("通配符 {} 与 {} 可能匹配同一模型，生效的规则不确定", a, b),
+                );
+            }
+        }
+    }
+}
+
+fn check_pools(config: &ProxyConfig, ctx: &ValidationContext, issues: &mut Issues) {
+    let mut names = HashSet::new();
+    let mut key_owner: HashMap<&str, &str> = HashMap::new();
+    // 没有账号信息时不检查成员
+    let check_members = !(ctx.accounts.is_empty() && ctx.tags.is_empty());
+    for (i, pool) in config.account_pools.iter().enumerate() {
+        let field = format!("account_pools.{}", i);
+        if pool.name.trim().is_empty() {
+            issues.error(&field, "empty_pool_name", "账号池名称不能为空");
+        } else if !names.insert(pool.name.as_str()) {
+            issues.error(&field, "duplicate_pool", format!("账号池名称重复: {}", pool.name));
+        }
+        if pool.accounts.is_empty() {
+            issues.warn(&field, "empty_pool", format!("账号池 {} 没有账号，绑定的 Key 将无法使用", pool.name));
+        }
+        for member in &pool.accounts {
+            let known = match member.strip_prefix("tag:") {
+                Some(tag) => ctx.tags.contains(tag),
+                None => ctx.accounts.contains(&member.to_lowercase()),
+            };
+            if check_members && !known {
+                issues.warn(&field, "unknown_pool_member", format!("账号池 {} 中的 {} 没有对应的账号", pool.name, member));
+            }
+        }
+        for key in pool.api_keys.iter().filter(|k| !k.is_empty()) {
+            if *key == config.api_key {
+                issues.error(&field, "pool_key_is_main_key", format!("账号池 {} 绑定了主 API Key", pool.name));
+            }
+            if let Some(owner) = key_owner.insert(key.as_str(), pool.name.as_str()) {
+                if owner != pool.name {
+                    issues.error(&field, "key_in_multiple_pools", format!("同一个 Key 同时绑定到账号池 {} 和 {}", owner, pool.name));
+                }
+            }
+        }
+    }
+    if let Some(default_pool) = config.default_pool.as_deref().filter(|p| !p.is_empty()) {
+        if !names.contains(default_pool) {
+            issues.error("default_pool", "unknown_default_pool", format!("默认账号池 {} 不存在", default_pool));
+        }
+    }
+}
+
+fn check_upstream(config: &ProxyConfig, issues: &mut Issues) {
+    if let Err(e) = config.upstream_proxy.validate() {
+        issues.error("upstream_proxy.url", "invalid_upstream_proxy", e);
+    }
+    let zai_active = !matches!(config.zai.dispatch_mode, ZaiDispatchMode::Off);
+    if config.zai.enabled && zai_active && config.zai.api_key.trim().is_empty() {
+        issues.error("zai.api_key", "missing_zai_key", "已启用 z.ai 但 API Key 为空");
+    }
+    if config.zai.enabled && url::Url::parse(config.zai.base_url.trim()).is_err() {
+        issues.error("zai.base_url", "invalid_url", format!("无效的 z.ai 地址: {}", config.zai.base_url));
+    }
+    if let Err(e) = crate::proxy::upstream::tls::validate(&config.upstream_tls) {
+        issues.error("upstream_tls", "invalid_upstream_tls", format!("{}，上游请求将全部失败", e));
+    }
+    if config.upstream_tls.ca_bundle_only && config.upstream_tls.ca_bundle_path.as_deref().unwrap_or_default().trim().is_empty() {
+        issues.warn("upstream_tls.ca_bundle_only", "missing_ca_bundle", "未设置 CA 证书包，仍使用系统根证书");
+    }
+}
+
+fn check_patterns(config: &ProxyConfig, issues: &mut Issues) {
+    for (i, pattern) in config.inspector.redact_patterns.iter().enumerate() {
+        if let Err(e) = regex::Regex::new(pattern) {
+            issues.error(format!("inspector.redact_patterns.{}", i), "invalid_regex", format!("无效的正则 {:?}: {}", pattern, e));
+        }
+    }
+}
+
+fn check_secret_refs(config: &ProxyConfig, issues: &mut Issues) {
+    for u in crate::proxy::secret_refs::unresolved_refs(config) {
+        issues.error(u.field, "unresolved_secret_ref", format!("凭证引用 {} 无法解析: {}", u.reference, u.reason));
+    }
+}
+
+/// 校验配置
+pub fn validate(config: &ProxyConfig, ctx: &ValidationContext) -> ConfigValidationReport {
+    let mut issues = Issues(Vec::new());
+    check_listener(config, ctx, &mut issues);
+    check_auth(config, &mut issues);
+    check_mapping(config, &mut issues);
+    check_pools(config, ctx, &mut issues);
+    check_upstream(config, &mut issues);
+    check_patterns(config, &mut issues);
+    check_secret_refs(config, &mut issues);
+
+    let issues = issues.0;
+    let errors = issues.iter().filter(|i| i.severity == IssueSeverity::Error).count();
+    ConfigValidationReport { valid: errors == 0, errors, warnings: issues.len() - errors, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::config::AccountPool;
+
+    fn codes(report: &ConfigValidationReport) -> Vec<(&str, IssueSeverity)> {
+        let mut codes: Vec<_> = report.issues.iter().map(|i| (i.code.as_str(), i.severity)).collect();
+        codes.sort_by_key(|(code, _)| *code);
+        codes
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let report = validate(&ProxyConfig::default(), &ValidationContext::default());
+        assert!(report.valid, "{:?}", report.issues);
+        assert_eq!(report.warnings, 0);
+    }
+
+    #[test]
+    fn test_detects_mapping_and_pool_conflicts() {
+        let mut config = ProxyConfig {
+            custom_mapping: HashMap::from([
+                ("gpt-4*".to_string(), "gemini-2.5-pro".to_string()),
+                ("gpt-4o*".to_string(), "gemini-2.5-flash".to_string()),
+                ("GPT-4o-mini".to_string(), "gemini-2.5-flash".to_string()),
+                ("gpt-4o-mini".to_string(), "my-model".to_string()),
+            ]),
+            ..Default::default()
+        };
+        config.inspector.redact_patterns = vec!["(unclosed".to_string()];
+        config.account_pools = vec![
+            AccountPool { name: "work".into(), accounts: vec!["a@example.com".into()], api_keys: vec!["sk-1".into()] },
+            AccountPool { name: "home".into(), accounts: vec!["tag:missing".into()], api_keys: vec!["sk-1".into()] },
+        ];
+        let ctx = ValidationContext { accounts: HashSet::from(["a@example.com".to_string()]), ..Default::default() };
+
+        let report = validate(&config, &ctx);
+        assert_eq!(
+            codes(&report),
+            [
+                ("duplicate_alias", IssueSeverity::Warning),
+                ("invalid_regex", IssueSeverity::Error),
+                ("key_in_multiple_pools", IssueSeverity::Error),
+                ("overlapping_wildcards", IssueSeverity::Warning),
+                ("unknown_model", IssueSeverity::Warning),
+                ("unknown_pool_member", IssueSeverity::Warning),
+            ]
+        );
+        assert_eq!((report.valid, report.errors, report.warnings), (false, 2, 4));
+    }
+
+    #[test]
+    fn test_lan_access_requires_key() {
+        let mut config =
+            ProxyConfig { allow_lan_access: true, auth_mode: ProxyAuthMode::Auto, api_key: String::new(), ..Default::default() };
+        assert_eq!(codes(&validate(&config, &ValidationContext::default())), [("missing_api_key", IssueSeverity::Error)]);
+
+        config.auth_mode = ProxyAuthMode::Off;
+        assert_eq!(codes(&validate(&config, &ValidationContext::default())), [("lan_without_auth", IssueSeverity::Error)]);
+        assert!(config.check_bind_safety().is_err());
+
+        config.require_auth_for_lan = false;
+        assert_eq!(codes(&validate(&config, &ValidationContext::default())), [("lan_without_auth", IssueSeverity::Warning)]);
+        assert!(config.check_bind_safety().is_ok());
+    }
+}
@@ -0,0 +1,151 @@
+// 环境变量覆盖反代配置
+//
+// 无界面 / Docker 部署时编辑 GUI 管理的 JSON 不方便，这里允许用环境变量覆盖任意
+// ProxyConfig 字段：`AGM_PROXY_<字段名>`，嵌套字段用双下划线分隔，例如
+// `AGM_PROXY_PORT=8080`、`AGM_PROXY_UPSTREAM_PROXY__URL=http://10.0.0.1:7890`。
+// 值按 JSON 解析 (数字、布尔、数组、对象)，解析失败则作为字符串。另有简写
+// `AGM_BIND=0.0.0.0|127.0.0.1` 对应 allow_lan_access。
+// 覆盖只作用于运行中的服务，不写回配置文件；无效的覆盖会被忽略并记录警告。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::proxy::ProxyConfig;
+
+const PREFIX: &str = "AGM_PROXY_";
+const BIND_VAR: &str = "AGM_BIND";
+
+/// 一条生效的覆盖
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EnvOverride {
+    pub var: String,
+    /// 点分隔的字段路径
+    pub field: String,
+    /// 覆盖值 (凭证类字段已隐藏)
+    pub value: String,
+}
+
+fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// 环境变量名 -> 字段路径；不是覆盖变量时返回 None
+fn field_path(var: &str, raw: &str) -> Option<(Vec<String>, Value)> {
+    if var == BIND_VAR {
+        let lan = match raw.trim() {
+            "0.0.0.0" | "lan" => true,
+            "127.0.0.1" | "localhost" | "local" => false,
+            other => {
+                tracing::warn!("[EnvOverride] 忽略 {}={}: 只支持 0.0.0.0 或 127.0.0.1", BIND_VAR, other);
+                return None;
+            }
+        };
+        return Some((vec!["allow_lan_access".to_string()], Value::Bool(lan)));
+    }
+    let rest = var.strip_prefix(PREFIX)?;
+    let path: Vec<String> = rest.split("__").map(|s| s.to_ascii_lowercase()).collect();
+    if path.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+    Some((path, parse_value(raw)))
+}
+
+/// 把环境变量应用到配置，返回生效的覆盖 (按变量名排序)
+fn apply<I: IntoIterator<Item = (String, String)>>(config: &ProxyConfig, vars: I) -> (ProxyConfig, Vec<EnvOverride>) {
+    let mut vars: Vec<(String, String)> = vars.into_iter().collect();
+    vars.sort();
+    let mut current = config.clone();
+    let mut applied = Vec::new();
+    for (var, raw) in vars {
+        let Some((path, value)) = field_path(&var, &raw) else { continue };
+        let Ok(mut json) = serde_json::to_value(&current) else { break };
+
+        // 父级必须存在，最后一级可以是映射类字段的新键
+        let (last, parents) = path.split_last().expect("path is not empty");
+        let parent = parents.iter().try_fold(&mut json, |node, key| node.get_mut(key.as_str()));
+        let Some(Value::Object(parent)) = parent else {
+            tracing::warn!("[EnvOverride] 忽略 {}: 配置中没有字段 {}", var, path.join("."));
+            continue;
+        };
+        if parents.is_empty() && !parent.contains_key(last.as_str()) {
+            tracing::warn!("[EnvOverride] 忽略 {}: 配置中没有字段 {}", var, last);
+            continue;
+        }
+        parent.insert(last.clone(), value);
+
+        match serde_json::from_value::<ProxyConfig>(json) {
+            Ok(updated) => {
+                let secret = path.iter().any(|s| crate::utils::redact::is_secret_field(s));
+                applied.push(EnvOverride {
+                    var,
+                    field: path.join("."),
+                    value: if secret { crate::utils::redact::REDACTED.to_string() } else { raw },
+                });
+                current = updated;
+            }
+            Err(e) => tracing::warn!("[EnvOverride] 忽略 {}: 值无效 ({})", var, e),
+        }
+    }
+    (current, applied)
+}
+
+/// 当前环境中生效的覆盖
+pub fn active_overrides(config: &ProxyConfig) -> Vec<EnvOverride> {
+    apply(config, std::env::vars()).1
+}
+
+impl ProxyConfig {
+    /// 应用环境变量覆盖后的配置
+    pub fn with_env_overrides(&self) -> ProxyConfig {
+        apply(self, std::env::vars()).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_overrides_nested_fields_and_aliases() {
+        let base = ProxyConfig::default();
+        let (config, applied) = apply(
+            &base,
+            vars(&[
+                ("AGM_PROXY_PORT", "9090"),
+                ("AGM_BIND", "0.0.0.0"),
+                ("AGM_PROXY_API_KEY", "sk-docker"),
+                ("AGM_PROXY_UPSTREAM_PROXY__URL", "http://10.0.0.1:7890"),
+                ("AGM_PROXY_CUSTOM_MAPPING__GPT-4O", "gemini-2.5-pro"),
+                ("PATH", "/usr/bin"),
+            ]),
+        );
+        assert_eq!(config.port, 9090);
+        assert!(config.allow_lan_access);
+        assert_eq!(config.api_key, "sk-docker");
+        assert_eq!(config.upstream_proxy.url, "http://10.0.0.1:7890");
+        assert_eq!(config.custom_mapping.get("gpt-4o").map(String::as_str), Some("gemini-2.5-pro"));
+        let key = applied.iter().find(|o| o.var == "AGM_PROXY_API_KEY").unwrap();
+        assert_eq!((key.field.as_str(), key.value.as_str()), ("api_key", "[REDACTED]"));
+        assert_eq!(applied.len(), 5);
+    }
+
+    #[test]
+    fn test_invalid_overrides_are_ignored() {
+        let base = ProxyConfig::default();
+        let (config, applied) = apply(
+            &base,
+            vars(&[
+                ("AGM_PROXY_PORT", "not-a-port"),
+                ("AGM_PROXY_NO_SUCH_FIELD", "1"),
+                ("AGM_PROXY_MISSING__CHILD", "1"),
+                ("AGM_BIND", "10.0.0.5"),
+            ]),
+        );
+        assert!(applied.is_empty());
+        assert_eq!((config.port, config.allow_lan_access), (base.port, base.allow_lan_access));
+    }
+}
@@ -2,7 +2,7 @@ use axum::{
     extract::{Multipart, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde_json::{json, Value};
 use tracing::{debug, info};
@@ -11,13 +11,16 @@ use uuid::Uuid;
 use crate::proxy::{
     audio::AudioProcessor,
     server::AppState,
+    AccountPoolScope,
 };
 
 /// 处理音频转录请求 (OpenAI Whisper API 兼容)
 pub async fn handle_audio_transcription(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     mut multipart: Multipart,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     let mut audio_data: Option<Vec<u8>> = None;
     let mut filename: Option<String> = None;
     let mut model = "gemini-2.0-flash-exp".to_string();
@@ -100,10 +103,10 @@ pub async fn handle_audio_transcription(
 
     // 6. 获取 Token 和上游客户端
     let token_manager = state.token_manager;
-    let (access_token, project_id, email) = token_manager
-        .get_token("text", false, None, &model)
+    let (access_token, project_id, email, _lease) = token_manager
+        .get_token_in_pool("text", false, None, &model, pool.as_deref())
         .await
-        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))?;
+        .map_err(|e| (super::common::token_error_status(&e), e))?;
 
     info!("使用账号: {}", email);
 
@@ -119,8 +122,9 @@ pub async fn handle_audio_transcription(
 
     // 8. 发送请求到 Gemini
     let upstream = state.upstream.clone();
+    let egress_proxy = token_manager.egress_proxy_for(&email);
     let response = upstream
-        .call_v1_internal("generateContent", &access_token, wrapped_body, None)
+        .call_v1_internal_via("generateContent", &access_token, wrapped_body, None, egress_proxy.as_deref())
         .await
         .map_err(|e| (StatusCode::BAD_GATEWAY, format!("上游请求失败: {}", e)))?;
 
@@ -5,6 +5,7 @@ use axum::{
     extract::{Json, State},
     http::{header, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
 use bytes::Bytes;
 use futures::StreamExt;
@@ -17,6 +18,7 @@ use crate::proxy::mappers::claude::{
     close_tool_loop_for_thinking,
 };
 use crate::proxy::server::AppState;
+use crate::proxy::AccountPoolScope;
 use axum::http::HeaderMap;
 use std::sync::atomic::Ordering;
 
@@ -45,7 +47,7 @@ fn has_valid_signature(block: &ContentBlock) -> bool {
                 return true;
             }
             // 有内容 + 足够长度的 signature = 有效
-            signature.as_ref().map_or(false, |s| s.len() >= MIN_SIGNATURE_LENGTH)
+            signature.as_ref().is_some_and(|s| s.len() >= MIN_SIGNATURE_LENGTH)
         }
         _ => true  // 非 thinking 块默认有效
     }
@@ -67,7 +69,7 @@ fn sanitize_thinking_block(block: ContentBlock) -> ContentBlock {
 }
 
 /// 过滤消息中的无效 thinking 块
-fn filter_invalid_thinking_blocks(messages: &mut Vec<Message>) {
+fn filter_invalid_thinking_blocks(messages: &mut [Message]) {
     let mut total_filtered = 0;
     
     for msg in messages.iter_mut() {
@@ -306,9 +308,11 @@ fn should_rotate_account(status_code: u16) -> bool {
 /// 处理 Chat 消息请求流程
 pub async fn handle_messages(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     headers: HeaderMap,
     Json(body): Json<Value>,
 ) -> Response {
+    let pool = pool.map(|Extension(scope)| scope.0);
     tracing::debug!("handle_messages called. Body JSON len: {}", body.to_string().len());
     
     // 生成随机 Trace ID 用户追踪
@@ -531,9 +535,21 @@ pub async fn handle_messages(
         let session_id = Some(session_id_str.as_str());
 
         let force_rotate_token = attempt > 0;
-        let (access_token, project_id, email) = match token_manager.get_token(&config.request_type, force_rotate_token, session_id, &config.final_model).await {
+        let (access_token, project_id, email, lease) = match token_manager.get_token_in_pool(&config.request_type, force_rotate_token, session_id, &config.final_model, pool.as_deref()).await {
             Ok(t) => t,
             Err(e) => {
+                if crate::proxy::token_manager::is_pool_exhausted(&e) {
+                    return (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        Json(json!({
+                            "type": "error",
+                            "error": {
+                                "type": crate::proxy::token_manager::POOL_EXHAUSTED_ERROR,
+                                "message": e
+                            }
+                        }))
+                    ).into_response();
+                }
                 let safe_message = if e.contains("invalid_grant") {
                     "OAuth refresh failed (invalid_grant): refresh_token likely revoked/expired; reauthorize account(s) to restore service.".to_string()
                 } else {
@@ -651,11 +667,15 @@ pub async fn handle_messages(
     let method = if actual_stream { "streamGenerateContent" } else { "generateContent" };
     let query = if actual_stream { Some("alt=sse") } else { None };
 
-    let response = match upstream.call_v1_internal(
+    let egress_proxy = token_manager.egress_proxy_for(&email);
+    // 保留请求体用于流式中断后的续传
+    let resume_body = actual_stream.then(|| gemini_body.clone());
+    let response = match upstream.call_v1_internal_via(
         method,
         &access_token,
         gemini_body,
-        query
+        query,
+        egress_proxy.as_deref(),
     ).await {
             Ok(r) => r,
             Err(e) => {
@@ -677,8 +697,13 @@ pub async fn handle_messages(
 
             // 处理流式响应
             if actual_stream {
-                let stream = response.bytes_stream();
-                let gemini_stream = Box::pin(stream);
+                let gemini_stream = crate::proxy::upstream::resume::resumable_stream(
+                    upstream.clone(),
+                    response,
+                    access_token.clone(),
+                    resume_body.unwrap_or_default(),
+                    egress_proxy.clone(),
+                );
                 // [v3.3.17] Pass session_id for signature caching
                 let mut claude_stream = create_claude_sse_stream(
                     gemini_stream, 
@@ -721,7 +746,7 @@ pub async fn handle_messages(
                                 .header(header::CONNECTION, "keep-alive")
                                 .header("X-Account-Email", &email)
                                 .header("X-Mapped-Model", &request_with_mapped.model)
-                                .body(Body::from_stream(combined_stream))
+                                .body(Body::from_stream(lease.hold_during(combined_stream)))
                                 .unwrap();
                         } else {
                             // 客户端要非 Stream，需要收集完整响应并转换为 JSON
@@ -818,6 +843,11 @@ pub async fn handle_messages(
         let error_text = response.text().await.unwrap_or_else(|_| format!("HTTP {}", status));
         last_error = format!("HTTP {}: {}", status_code, error_text);
         debug!("[{}] Upstream Error Response: {}", trace_id, error_text);
+
+        // 账号被封禁/停用: 自动隔离并换号重试
+        if token_manager.quarantine_if_suspended(&email, status_code, &error_text).await {
+            continue;
+        }
         
         // 3. 标记限流状态(用于 UI 显示) - 使用异步版本以支持实时配额刷新
         // 🆕 传入实际使用的模型,实现模型级别限流,避免不同模型配额互相影响
@@ -1227,7 +1257,7 @@ fn create_warmup_response(request: &ClaudeRequest, is_stream: bool) -> Response
     
     if is_stream {
         // 流式响应：发送标准的 SSE 事件序列
-        let events = vec![
+        let events = [
             // message_start
             format!(
                 "event: message_start\ndata: {{\"type\":\"message_start\",\"message\":{{\"id\":\"{}\",\"type\":\"message\",\"role\":\"assistant\",\"content\":[],\"model\":\"{}\",\"stop_reason\":null,\"stop_sequence\":null,\"usage\":{{\"input_tokens\":1,\"output_tokens\":0}}}}}}\n\n",
@@ -2,6 +2,17 @@ use axum::{extract::State, extract::Json, http::StatusCode, response::IntoRespon
 use serde_json::{json, Value};
 use crate::proxy::server::AppState;
 
+/// 获取 Token 失败时返回给客户端的状态码
+///
+/// 账号池耗尽返回 429 (错误信息以 `pool_exhausted` 开头)，其余情况返回 503
+pub fn token_error_status(error: &str) -> StatusCode {
+    if crate::proxy::token_manager::is_pool_exhausted(error) {
+        StatusCode::TOO_MANY_REQUESTS
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
 /// Detects model capabilities and configuration
 /// POST /v1/models/detect
 pub async fn handle_detect_model(
@@ -0,0 +1,120 @@
+// 内置 Web 管理面板
+//
+// `GET /dashboard` 返回内嵌的单页 (不含任何数据，开启认证时也无需 Key 即可打开)，
+// 页面用输入的主 API Key 轮询 `GET /dashboard/api/overview` 获取服务状态、账号、
+// 各 Key 用量与实时统计，无界面模式 / VPS 部署时无需桌面程序即可查看。
+// 数据接口与管理 MCP 一样只对主 API Key (或未开启认证) 开放。
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::proxy::server::AppState;
+use crate::proxy::AdminAccess;
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+/// 最近请求的条数
+const RECENT_REQUESTS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct OverviewQuery {
+    pub window_hours: Option<u32>,
+}
+
+pub async fn handle_dashboard() -> Response {
+    (
+        [(header::CACHE_CONTROL, "no-store")],
+        Html(DASHBOARD_HTML),
+    )
+        .into_response()
+}
+
+async fn key_usage(window_hours: u32) -> Result<Value, String> {
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours as i64 * 3600;
+    let keys = tokio::task::spawn_blocking(move || {
+        let prices = crate::modules::config::load_app_config()
+            .map(|c| c.model_prices)
+            .unwrap_or_default();
+        crate::modules::token_stats::get_key_usage(since, now + 1, None, &prices)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    serde_json::to_value(keys).map_err(|e| e.to_string())
+}
+
+async fn overview(state: &AppState, window_hours: u32) -> Result<Value, String> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let monitor = &state.monitor;
+    let load = crate::proxy::load::LoadSnapshot::collect(monitor.in_flight.get(), Some(&state.token_manager));
+    // 面板只展示摘要，不返回请求 / 响应内容
+    let recent: Vec<Value> = monitor
+        .get_logs(RECENT_REQUESTS)
+        .await
+        .into_iter()
+        .map(|log| {
+            json!({
+                "id": log.id,
+                "timestamp": log.timestamp,
+                "method": log.method,
+                "url": log.url,
+                "status": log.status,
+                "duration": log.duration,
+                "model": log.mapped_model.or(log.model),
+                "account_email": log.account_email,
+                "input_tokens": log.input_tokens,
+                "output_tokens": log.output_tokens,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "timestamp": now_ms,
+        "status": {
+            "logging": monitor.is_enabled(),
+            "active_accounts": state.token_manager.len(),
+            "stats": monitor.get_stats().await,
+        },
+        "live": {
+            "rps": monitor.live.rps(now_ms),
+            "recent_errors": monitor.live.recent_errors(now_ms),
+            "load": load,
+        },
+        "accounts": crate::proxy::manager_mcp::list_accounts(state).await?,
+        "usage": crate::proxy::manager_mcp::usage_stats(&json!({ "window_hours": window_hours })).await?,
+        "keys": key_usage(window_hours).await?,
+        "recent": recent,
+    }))
+}
+
+pub async fn handle_overview(
+    State(state): State<AppState>,
+    admin: Option<Extension<AdminAccess>>,
+    Query(query): Query<OverviewQuery>,
+) -> Response {
+    if admin.is_none() {
+        return (StatusCode::FORBIDDEN, Json(json!({ "error": "Dashboard requires the main API key" }))).into_response();
+    }
+    let window_hours = query.window_hours.unwrap_or(24).max(1);
+    match overview(&state, window_hours).await {
+        Ok(body) => Json(body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboard_page_uses_overview_endpoint() {
+        assert!(DASHBOARD_HTML.contains("/dashboard/api/overview"));
+        assert!(DASHBOARD_HTML.contains("Authorization"));
+    }
+}
@@ -1,10 +1,12 @@
 // Gemini Handler
-use axum::{extract::State, extract::{Json, Path}, http::StatusCode, response::IntoResponse};
+use axum::{extract::State, extract::{Json, Path}, http::StatusCode, response::IntoResponse, Extension};
 use serde_json::{json, Value};
 use tracing::{debug, error, info};
 
-use crate::proxy::mappers::gemini::{wrap_request, unwrap_response};
+use crate::proxy::mappers::gemini::{response_span, wrap_request};
 use crate::proxy::server::AppState;
+use crate::proxy::providers::InboundProtocol;
+use crate::proxy::AccountPoolScope;
 use crate::proxy::session_manager::SessionManager;
  
 const MAX_RETRY_ATTEMPTS: usize = 3;
@@ -13,9 +15,11 @@ const MAX_RETRY_ATTEMPTS: usize = 3;
 /// 路径参数: model_name, method (e.g. "gemini-pro", "generateContent")
 pub async fn handle_generate(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     Path(model_action): Path<String>,
     Json(body): Json<Value>
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     // 解析 model:method
     let (model_name, method) = if let Some((m, action)) = model_action.rsplit_once(':') {
         (m.to_string(), action.to_string())
@@ -31,6 +35,11 @@ pub async fn handle_generate(
     }
     let is_stream = method == "streamGenerateContent";
 
+    // 命中其他上游后端 (Provider) 的模型直接转发，不经过账号池
+    if let Some(response) = crate::proxy::providers::dispatch(&state, InboundProtocol::Gemini, &model_name, is_stream, &body).await {
+        return Ok(response);
+    }
+
     // 2. 获取 UpstreamClient 和 TokenManager
     let upstream = state.upstream.clone();
     let token_manager = state.token_manager;
@@ -66,10 +75,10 @@ pub async fn handle_generate(
         let session_id = SessionManager::extract_gemini_session_id(&body, &model_name);
 
         // 关键：在重试尝试 (attempt > 0) 时强制轮换账号
-        let (access_token, project_id, email) = match token_manager.get_token(&config.request_type, attempt > 0, Some(&session_id), &config.final_model).await {
+        let (access_token, project_id, email, lease) = match token_manager.get_token_in_pool(&config.request_type, attempt > 0, Some(&session_id), &config.final_model, pool.as_deref()).await {
             Ok(t) => t,
             Err(e) => {
-                return Err((StatusCode::SERVICE_UNAVAILABLE, format!("Token error: {}", e)));
+                return Err((super::common::token_error_status(&e), format!("Token error: {}", e)));
             }
         };
 
@@ -83,8 +92,10 @@ pub async fn handle_generate(
         let query_string = if is_stream { Some("alt=sse") } else { None };
         let upstream_method = if is_stream { "streamGenerateContent" } else { "generateContent" };
 
+        let egress_proxy = token_manager.egress_proxy_for(&email);
+        let resume_body = is_stream.then(|| wrapped_body.clone());
         let response = match upstream
-            .call_v1_internal(upstream_method, &access_token, wrapped_body, query_string)
+            .call_v1_internal_via(upstream_method, &access_token, wrapped_body, query_string, egress_proxy.as_deref())
             .await {
                 Ok(r) => r,
                 Err(e) => {
@@ -103,7 +114,13 @@ pub async fn handle_generate(
                 use bytes::{Bytes, BytesMut};
                 use futures::StreamExt;
                 
-                let mut response_stream = response.bytes_stream();
+                let mut response_stream = crate::proxy::upstream::resume::resumable_stream(
+                    upstream.clone(),
+                    response,
+                    access_token.clone(),
+                    resume_body.unwrap_or_default(),
+                    egress_proxy.clone(),
+                );
                 let mut buffer = BytesMut::new();
 
                 let stream = async_stream::stream! {
@@ -111,45 +128,50 @@ pub async fn handle_generate(
                         match item {
                             Ok(bytes) => {
                                 debug!("[Gemini-SSE] Received chunk: {} bytes", bytes.len());
-                                buffer.extend_from_slice(&bytes);
-                                while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
-                                    let line_raw = buffer.split_to(pos + 1);
-                                    if let Ok(line_str) = std::str::from_utf8(&line_raw) {
-                                        let line = line_str.trim();
-                                        if line.is_empty() { continue; }
-                                        
-                                        if line.starts_with("data: ") {
-                                            let json_part = line.trim_start_matches("data: ").trim();
-                                            if json_part == "[DONE]" {
-                                                yield Ok::<Bytes, String>(Bytes::from("data: [DONE]\n\n"));
-                                                continue;
-                                            }
-                                            
-                                            match serde_json::from_str::<Value>(json_part) {
-                                                Ok(mut json) => {
-                                                    // Unwrap v1internal response wrapper
-                                                    if let Some(inner) = json.get_mut("response").map(|v| v.take()) {
-                                                        let new_line = format!("data: {}\n\n", serde_json::to_string(&inner).unwrap_or_default());
-                                                        yield Ok::<Bytes, String>(Bytes::from(new_line));
-                                                    } else {
-                                                        yield Ok::<Bytes, String>(Bytes::from(format!("data: {}\n\n", serde_json::to_string(&json).unwrap_or_default())));
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    debug!("[Gemini-SSE] JSON parse error: {}, passing raw line", e);
-                                                    yield Ok::<Bytes, String>(Bytes::from(format!("{}\n\n", line)));
-                                                }
-                                            }
-                                        } else {
-                                            // Non-data lines (comments, etc.)
-                                            yield Ok::<Bytes, String>(Bytes::from(format!("{}\n\n", line)));
-                                        }
-                                    } else {
-                                        // Non-UTF8 data? Just pass it through or skip
+                                // 没有残留的半行时直接在上游分片上切行，避免拷贝
+                                let mut chunk = if buffer.is_empty() {
+                                    bytes
+                                } else {
+                                    buffer.extend_from_slice(&bytes);
+                                    buffer.split().freeze()
+                                };
+                                while let Some(pos) = chunk.iter().position(|&b| b == b'\n') {
+                                    let line_raw = chunk.split_to(pos + 1);
+                                    let Ok(line_str) = std::str::from_utf8(&line_raw) else {
+                                        // Non-UTF8 data? Just pass it through
                                         debug!("[Gemini-SSE] Non-UTF8 line encountered");
-                                        yield Ok::<Bytes, String>(line_raw.freeze());
+                                        yield Ok::<Bytes, String>(line_raw.clone());
+                                        continue;
+                                    };
+                                    let line = line_str.trim();
+                                    if line.is_empty() { continue; }
+
+                                    let Some(payload) = line.strip_prefix("data:").map(str::trim_start) else {
+                                        // Non-data lines (comments, etc.)
+                                        yield Ok::<Bytes, String>(Bytes::from(format!("{}\n\n", line)));
+                                        continue;
+                                    };
+                                    if payload == "[DONE]" {
+                                        yield Ok::<Bytes, String>(Bytes::from_static(b"data: [DONE]\n\n"));
+                                        continue;
                                     }
+
+                                    // 原生透传: 只切出 v1internal 包装里的 response 字段转发上游原始字节，
+                                    // 不逐块解析 / 重新序列化 JSON
+                                    let offset = payload.as_ptr() as usize - line_raw.as_ptr() as usize;
+                                    let data = match response_span(payload.as_bytes()) {
+                                        Ok(Some(span)) => line_raw.slice(offset + span.start..offset + span.end),
+                                        Ok(None) => line_raw.slice(offset..offset + payload.len()),
+                                        Err(e) => {
+                                            debug!("[Gemini-SSE] JSON parse error: {}, passing raw line", e);
+                                            line_raw.slice(offset..offset + payload.len())
+                                        }
+                                    };
+                                    yield Ok::<Bytes, String>(Bytes::from_static(b"data: "));
+                                    yield Ok::<Bytes, String>(data);
+                                    yield Ok::<Bytes, String>(Bytes::from_static(b"\n\n"));
                                 }
+                                buffer.extend_from_slice(&chunk);
                             }
                             Err(e) => {
                                 error!("[Gemini-SSE] Connection error: {}", e);
@@ -159,7 +181,7 @@ pub async fn handle_generate(
                     }
                 };
                 
-                let body = Body::from_stream(stream);
+                let body = Body::from_stream(lease.hold_during(stream));
                 return Ok(Response::builder()
                     .header("Content-Type", "text/event-stream")
                     .header("Cache-Control", "no-cache")
@@ -171,13 +193,26 @@ pub async fn handle_generate(
                     .into_response());
             }
 
-            let gemini_resp: Value = response
-                .json()
+            // 非流式同样按字节切出 response 字段，不构建完整的 JSON 树
+            let raw = response
+                .bytes()
                 .await
-                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Parse error: {}", e)))?;
-
-            let unwrapped = unwrap_response(&gemini_resp);
-            return Ok((StatusCode::OK, [("X-Account-Email", email.as_str()), ("X-Mapped-Model", mapped_model.as_str())], Json(unwrapped)).into_response());
+                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Read error: {}", e)))?;
+            let body = match response_span(&raw) {
+                Ok(Some(span)) => raw.slice(span),
+                Ok(None) => raw,
+                Err(e) => return Err((StatusCode::BAD_GATEWAY, format!("Parse error: {}", e))),
+            };
+            return Ok((
+                StatusCode::OK,
+                [
+                    ("Content-Type", "application/json"),
+                    ("X-Account-Email", email.as_str()),
+                    ("X-Mapped-Model", mapped_model.as_str()),
+                ],
+                body,
+            )
+                .into_response());
         }
 
         // 处理错误并重试
@@ -185,6 +220,11 @@ pub async fn handle_generate(
         let retry_after = response.headers().get("Retry-After").and_then(|h| h.to_str().ok()).map(|s| s.to_string());
         let error_text = response.text().await.unwrap_or_else(|_| format!("HTTP {}", status_code));
         last_error = format!("HTTP {}: {}", status_code, error_text);
+
+        // 账号被封禁/停用: 自动隔离并换号重试
+        if token_manager.quarantine_if_suspended(&email, status_code, &error_text).await {
+            continue;
+        }
  
         // 只有 429 (限流), 529 (过载), 503, 403 (权限) 和 401 (认证失效) 触发账号轮换
         if status_code == 429 || status_code == 529 || status_code == 503 || status_code == 500 || status_code == 403 || status_code == 401 {
@@ -247,10 +287,11 @@ pub async fn handle_get_model(Path(model_name): Path<String>) -> impl IntoRespon
     }))
 }
 
-pub async fn handle_count_tokens(State(state): State<AppState>, Path(_model_name): Path<String>, Json(_body): Json<Value>) -> Result<impl IntoResponse, (StatusCode, String)> {
+pub async fn handle_count_tokens(State(state): State<AppState>, pool: Option<Extension<AccountPoolScope>>, Path(_model_name): Path<String>, Json(_body): Json<Value>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     let model_group = "gemini";
-    let (_access_token, _project_id, _) = state.token_manager.get_token(model_group, false, None, "gemini").await
-        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, format!("Token error: {}", e)))?;
+    let (_access_token, _project_id, _, _lease) = state.token_manager.get_token_in_pool(model_group, false, None, "gemini", pool.as_deref()).await
+        .map_err(|e| (super::common::token_error_status(&e), format!("Token error: {}", e)))?;
     
     Ok(Json(json!({"totalTokens": 0})))
 }
@@ -3,6 +3,7 @@ use axum::{
     extract::State,
     http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
 use bytes::Bytes;
 use futures::StreamExt;
@@ -10,23 +11,9 @@ use serde_json::{json, Value};
 use tokio::time::Duration;
 use tokio_stream::wrappers::IntervalStream;
 
+use crate::proxy::providers::build_client;
 use crate::proxy::server::AppState;
-
-fn build_client(
-    upstream_proxy: crate::proxy::config::UpstreamProxyConfig,
-    timeout_secs: u64,
-) -> Result<reqwest::Client, String> {
-    let mut builder = reqwest::Client::builder()
-        .timeout(Duration::from_secs(timeout_secs.max(5)));
-
-    if upstream_proxy.enabled && !upstream_proxy.url.is_empty() {
-        let proxy = reqwest::Proxy::all(&upstream_proxy.url)
-            .map_err(|e| format!("Invalid upstream proxy url: {}", e))?;
-        builder = builder.proxy(proxy);
-    }
-
-    builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
-}
+use crate::proxy::AdminAccess;
 
 fn copy_passthrough_headers(incoming: &HeaderMap) -> HeaderMap {
     let mut out = HeaderMap::new();
@@ -59,7 +46,7 @@ async fn forward_mcp(
     }
 
     let upstream_proxy = state.upstream_proxy.read().await.clone();
-    let client = match build_client(upstream_proxy, state.request_timeout) {
+    let client = match build_client(Some(upstream_proxy), state.request_timeout) {
         Ok(c) => c,
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
     };
@@ -395,3 +382,95 @@ pub async fn handle_zai_mcp_server(
         _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
     }
 }
+
+/// 管理 MCP (无会话的 Streamable HTTP: 只处理 POST，不提供 SSE 推送)
+pub async fn handle_manager_mcp(
+    State(state): State<AppState>,
+    admin: Option<Extension<AdminAccess>>,
+    method: Method,
+    body: Body,
+) -> Response {
+    if admin.is_none() {
+        return (StatusCode::FORBIDDEN, "Management MCP requires the main API key").into_response();
+    }
+    if method != Method::POST {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+
+    let collected = match to_bytes(body, 1024 * 1024).await {
+        Ok(b) => b,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, format!("Failed to read request body: {}", e)).into_response();
+        }
+    };
+    let request_json: Value = match serde_json::from_slice(&collected) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::Json(jsonrpc_error(Value::Null, -32700, format!("Parse error: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    let id = request_json.get("id").cloned().unwrap_or(Value::Null);
+    let method = request_json.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    if method.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            axum::Json(jsonrpc_error(id, -32600, "Invalid Request: missing method")),
+        )
+            .into_response();
+    }
+    // Notifications (no id) should not produce a response.
+    if request_json.get("id").is_none() || request_json.get("id") == Some(&Value::Null) {
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let result = match method {
+        "initialize" => {
+            let requested_protocol = request_json
+                .get("params")
+                .and_then(|p| p.get("protocolVersion"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("2024-11-05");
+            json!({
+                "protocolVersion": requested_protocol,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "antigravity-manager",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }
+            })
+        }
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": crate::proxy::manager_mcp::tool_specs() }),
+        "tools/call" => {
+            let params = request_json.get("params").cloned().unwrap_or(Value::Null);
+            let Some(tool_name) = params.get("name").and_then(|v| v.as_str()) else {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    axum::Json(jsonrpc_error(id, -32602, "Missing params.name")),
+                )
+                    .into_response();
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(Value::Object(Default::default()));
+            match crate::proxy::manager_mcp::call_tool(&state, tool_name, &arguments).await {
+                Ok(tool_result) => tool_result,
+                Err(e) => json!({
+                    "content": [ { "type": "text", "text": format!("Error: {}", e) } ],
+                    "isError": true
+                }),
+            }
+        }
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::Json(jsonrpc_error(id, -32601, format!("Method not found: {}", method))),
+            )
+                .into_response();
+        }
+    };
+    (StatusCode::OK, axum::Json(jsonrpc_result(id, result))).into_response()
+}
@@ -0,0 +1,42 @@
+// Prometheus 指标端点
+//
+// 暴露延迟直方图、上游连接复用计数、各端点请求数、当前负载与日志 / 缓存的淘汰次数，供外部监控系统抓取。
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+use crate::proxy::server::AppState;
+
+pub async fn handle_metrics(State(state): State<AppState>) -> Response {
+    let mut body = state.monitor.latency.render_prometheus();
+
+    let connections = state.upstream.connection_stats();
+    for (name, help, value) in [
+        ("antigravity_upstream_requests_total", "HTTP requests sent upstream", connections.requests),
+        ("antigravity_upstream_new_connections_total", "New upstream connections (TCP + TLS handshakes)", connections.new_connections),
+        ("antigravity_upstream_reused_connections_total", "Upstream requests served by pooled connections", connections.reused_connections),
+    ] {
+        body.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", name, help, name, name, value));
+    }
+
+    let endpoints = state.upstream.endpoint_stats();
+    for (name, help) in [
+        ("antigravity_upstream_endpoint_requests_total", "Requests sent to each upstream endpoint"),
+        ("antigravity_upstream_endpoint_errors_total", "Failed requests (network, 408, 429, 5xx) per upstream endpoint"),
+    ] {
+        body.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n", name, help, name));
+        for endpoint in &endpoints {
+            let value = if name.ends_with("errors_total") { endpoint.errors } else { endpoint.requests };
+            body.push_str(&format!("{}{{endpoint=\"{}\"}} {}\n", name, endpoint.base_url, value));
+        }
+    }
+
+    let load = crate::proxy::load::LoadSnapshot::collect(state.monitor.in_flight.get(), Some(&state.token_manager));
+    body.push_str(&load.render_prometheus());
+    body.push_str(&crate::proxy::memory_limits::render_prometheus(state.monitor.log_buffer_bytes()));
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+}
@@ -8,4 +8,8 @@ pub mod mcp;
 pub mod common;
 pub mod audio;  // 音频转录处理器 (PR #311)
 pub mod warmup; // 预热处理器
+pub mod metrics; // Prometheus 指标
+pub mod usage; // 按 API Key 的用量查询
+pub mod dashboard; // 内置 Web 管理面板
+pub mod openapi; // OpenAPI 规范与 Swagger UI
 
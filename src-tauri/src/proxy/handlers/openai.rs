@@ -1,5 +1,5 @@
 // OpenAI Handler
-use axum::{extract::Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{extract::Json, extract::State, http::StatusCode, response::IntoResponse, Extension};
 use base64::Engine as _; 
 use bytes::Bytes;
 use serde_json::{json, Value};
@@ -10,14 +10,24 @@ use crate::proxy::mappers::openai::{
 };
 // use crate::proxy::upstream::client::UpstreamClient; // 通过 state 获取
 use crate::proxy::server::AppState;
+use crate::proxy::providers::InboundProtocol;
+use crate::proxy::AccountPoolScope;
 
 const MAX_RETRY_ATTEMPTS: usize = 3;
 use crate::proxy::session_manager::SessionManager;
 
 pub async fn handle_chat_completions(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     Json(body): Json<Value>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
+    // 命中其他上游后端 (Provider) 的模型直接转发，不经过账号池
+    let model = body.get("model").and_then(|m| m.as_str()).unwrap_or_default();
+    let stream = body.get("stream").and_then(|s| s.as_bool()).unwrap_or(false);
+    if let Some(response) = crate::proxy::providers::dispatch(&state, InboundProtocol::OpenAiChat, model, stream, &body).await {
+        return Ok(response);
+    }
     let mut openai_req: OpenAIRequest = serde_json::from_value(body)
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid request: {}", e)))?;
 
@@ -59,7 +69,7 @@ pub async fn handle_chat_completions(
         let tools_val: Option<Vec<Value>> = openai_req
             .tools
             .as_ref()
-            .map(|list| list.iter().cloned().collect());
+            .map(|list| list.to_vec());
         let config = crate::proxy::mappers::common_utils::resolve_request_config(
             &openai_req.model,
             &mapped_model,
@@ -71,14 +81,14 @@ pub async fn handle_chat_completions(
 
         // 4. 获取 Token (使用准确的 request_type)
         // 关键：在重试尝试 (attempt > 0) 时强制轮换账号
-        let (access_token, project_id, email) = match token_manager
-            .get_token(&config.request_type, attempt > 0, Some(&session_id), &config.final_model)
+        let (access_token, project_id, email, lease) = match token_manager
+            .get_token_in_pool(&config.request_type, attempt > 0, Some(&session_id), &config.final_model, pool.as_deref())
             .await
         {
             Ok(t) => t,
             Err(e) => {
                 return Err((
-                    StatusCode::SERVICE_UNAVAILABLE,
+                    super::common::token_error_status(&e),
                     format!("Token error: {}", e),
                 ));
             }
@@ -112,8 +122,10 @@ pub async fn handle_chat_completions(
         };
         let query_string = if actual_stream { Some("alt=sse") } else { None };
 
+        let egress_proxy = token_manager.egress_proxy_for(&email);
+        let resume_body = actual_stream.then(|| gemini_body.clone());
         let response = match upstream
-            .call_v1_internal(method, &access_token, gemini_body, query_string)
+            .call_v1_internal_via(method, &access_token, gemini_body, query_string, egress_proxy.as_deref())
             .await
         {
             Ok(r) => r,
@@ -137,14 +149,19 @@ pub async fn handle_chat_completions(
                 use axum::body::Body;
                 use axum::response::Response;
 
-                let gemini_stream = response.bytes_stream();
-                let openai_stream =
-                    create_openai_sse_stream(Box::pin(gemini_stream), openai_req.model.clone());
+                let gemini_stream = crate::proxy::upstream::resume::resumable_stream(
+                    upstream.clone(),
+                    response,
+                    access_token.clone(),
+                    resume_body.unwrap_or_default(),
+                    egress_proxy.clone(),
+                );
+                let openai_stream = create_openai_sse_stream(gemini_stream, openai_req.model.clone());
                 
                 // 判断客户端期望的格式
                 if client_wants_stream {
                     // 客户端本就要 Stream，直接返回 SSE
-                    let body = Body::from_stream(openai_stream);
+                    let body = Body::from_stream(lease.hold_during(openai_stream));
                     return Ok(Response::builder()
                         .header("Content-Type", "text/event-stream")
                         .header("Cache-Control", "no-cache")
@@ -163,7 +180,7 @@ pub async fn handle_chat_completions(
                     let sse_stream = openai_stream.map(|result| -> Result<Bytes, std::io::Error> {
                         match result {
                             Ok(bytes) => Ok(bytes),
-                            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
+                            Err(e) => Err(std::io::Error::other(e)),
                         }
                     });
                     
@@ -201,6 +218,11 @@ pub async fn handle_chat_completions(
             error_text
         );
 
+        // 账号被封禁/停用: 自动隔离并换号重试
+        if token_manager.quarantine_if_suspended(&email, status_code, &error_text).await {
+            continue;
+        }
+
         // 429/529/503 智能处理
         if status_code == 429 || status_code == 529 || status_code == 503 || status_code == 500 {
             // 记录限流信息 (全局同步)
@@ -282,8 +304,10 @@ pub async fn handle_chat_completions(
 /// 将 Prompt 转换为 Chat Message 格式，复用 handle_chat_completions
 pub async fn handle_completions(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     Json(mut body): Json<Value>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     info!(
         "Received /v1/completions or /v1/responses payload: {:?}",
         body
@@ -574,19 +598,19 @@ pub async fn handle_completions(
         let tools_val: Option<Vec<Value>> = openai_req
             .tools
             .as_ref()
-            .map(|list| list.iter().cloned().collect());
+            .map(|list| list.to_vec());
         let config = crate::proxy::mappers::common_utils::resolve_request_config(
             &openai_req.model,
             &mapped_model,
             &tools_val,
         );
 
-        let (access_token, project_id, email) =
-            match token_manager.get_token(&config.request_type, false, None, &config.final_model).await {
+        let (access_token, project_id, email, lease) =
+            match token_manager.get_token_in_pool(&config.request_type, false, None, &config.final_model, pool.as_deref()).await {
                 Ok(t) => t,
                 Err(e) => {
                     return Err((
-                        StatusCode::SERVICE_UNAVAILABLE,
+                        super::common::token_error_status(&e),
                         format!("Token error: {}", e),
                     ))
                 }
@@ -609,8 +633,10 @@ pub async fn handle_completions(
         };
         let query_string = if list_response { Some("alt=sse") } else { None };
 
+        let egress_proxy = token_manager.egress_proxy_for(&email);
+        let resume_body = list_response.then(|| gemini_body.clone());
         let response = match upstream
-            .call_v1_internal(method, &access_token, gemini_body, query_string)
+            .call_v1_internal_via(method, &access_token, gemini_body, query_string, egress_proxy.as_deref())
             .await
         {
             Ok(r) => r,
@@ -626,17 +652,21 @@ pub async fn handle_completions(
                 use axum::body::Body;
                 use axum::response::Response;
 
-                let gemini_stream = response.bytes_stream();
+                let gemini_stream = crate::proxy::upstream::resume::resumable_stream(
+                    upstream.clone(),
+                    response,
+                    access_token.clone(),
+                    resume_body.unwrap_or_default(),
+                    egress_proxy.clone(),
+                );
                 let body = if is_codex_style {
                     use crate::proxy::mappers::openai::streaming::create_codex_sse_stream;
-                    let s =
-                        create_codex_sse_stream(Box::pin(gemini_stream), openai_req.model.clone());
-                    Body::from_stream(s)
+                    let s = create_codex_sse_stream(gemini_stream, openai_req.model.clone());
+                    Body::from_stream(lease.hold_during(s))
                 } else {
                     use crate::proxy::mappers::openai::streaming::create_legacy_sse_stream;
-                    let s =
-                        create_legacy_sse_stream(Box::pin(gemini_stream), openai_req.model.clone());
-                    Body::from_stream(s)
+                    let s = create_legacy_sse_stream(gemini_stream, openai_req.model.clone());
+                    Body::from_stream(lease.hold_during(s))
                 };
 
                 return Ok(Response::builder()
@@ -686,6 +716,11 @@ pub async fn handle_completions(
         let error_text = response.text().await.unwrap_or_default();
         last_error = format!("HTTP {}: {}", status_code, error_text);
 
+        // 账号被封禁/停用: 自动隔离并换号重试
+        if token_manager.quarantine_if_suspended(&email, status_code, &error_text).await {
+            continue;
+        }
+
         if status_code == 429 || status_code == 403 || status_code == 401 {
             continue;
         }
@@ -724,8 +759,10 @@ pub async fn handle_list_models(State(state): State<AppState>) -> impl IntoRespo
 /// 处理图像生成请求，转换为 Gemini API 格式
 pub async fn handle_images_generations(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     Json(body): Json<Value>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     // 1. 解析请求参数
     let prompt = body.get("prompt").and_then(|v| v.as_str()).ok_or((
         StatusCode::BAD_REQUEST,
@@ -793,12 +830,12 @@ pub async fn handle_images_generations(
     let upstream = state.upstream.clone();
     let token_manager = state.token_manager;
 
-    let (access_token, project_id, email) = match token_manager.get_token("image_gen", false, None, "dall-e-3").await
+    let (access_token, project_id, email, _lease) = match token_manager.get_token_in_pool("image_gen", false, None, "dall-e-3", pool.as_deref()).await
     {
         Ok(t) => t,
         Err(e) => {
             return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
+                super::common::token_error_status(&e),
                 format!("Token error: {}", e),
             ))
         }
@@ -807,11 +844,13 @@ pub async fn handle_images_generations(
     info!("✓ Using account: {} for image generation", email);
 
     // 4. 并发发送请求 (解决 candidateCount > 1 不支持的问题)
+    let egress_proxy = token_manager.egress_proxy_for(&email);
     let mut tasks = Vec::new();
 
     for _ in 0..n {
         let upstream = upstream.clone();
         let access_token = access_token.clone();
+        let egress_proxy = egress_proxy.clone();
         let project_id = project_id.clone();
         let final_prompt = final_prompt.clone();
         let aspect_ratio = aspect_ratio.to_string();
@@ -846,7 +885,7 @@ pub async fn handle_images_generations(
             });
 
             match upstream
-                .call_v1_internal("generateContent", &access_token, gemini_body, None)
+                .call_v1_internal_via("generateContent", &access_token, gemini_body, None, egress_proxy.as_deref())
                 .await
             {
                 Ok(response) => {
@@ -958,8 +997,10 @@ pub async fn handle_images_generations(
 
 pub async fn handle_images_edits(
     State(state): State<AppState>,
+    pool: Option<Extension<AccountPoolScope>>,
     mut multipart: axum::extract::Multipart,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = pool.map(|Extension(scope)| scope.0);
     tracing::info!("[Images] Received edit request");
 
     let mut image_data = None;
@@ -1047,12 +1088,12 @@ pub async fn handle_images_edits(
     let upstream = state.upstream.clone();
     let token_manager = state.token_manager;
     // Fix: Proper get_token call with correct signature and unwrap (using image_gen quota)
-    let (access_token, project_id, email) = match token_manager.get_token("image_gen", false, None, "dall-e-3").await
+    let (access_token, project_id, email, _lease) = match token_manager.get_token_in_pool("image_gen", false, None, "dall-e-3", pool.as_deref()).await
     {
         Ok(t) => t,
         Err(e) => {
             return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
+                super::common::token_error_status(&e),
                 format!("Token error: {}", e),
             ))
         }
@@ -1113,15 +1154,17 @@ pub async fn handle_images_edits(
         }
     });
 
+    let egress_proxy = token_manager.egress_proxy_for(&email);
     let mut tasks = Vec::new();
     for _ in 0..n {
         let upstream = upstream.clone();
         let access_token = access_token.clone();
+        let egress_proxy = egress_proxy.clone();
         let body = gemini_body.clone();
 
         tasks.push(tokio::spawn(async move {
             match upstream
-                .call_v1_internal("generateContent", &access_token, body, None)
+                .call_v1_internal_via("generateContent", &access_token, body, None, egress_proxy.as_deref())
                 .await
             {
                 Ok(response) => {
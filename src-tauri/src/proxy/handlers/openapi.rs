@@ -0,0 +1,277 @@
+// OpenAPI 规范与 Swagger UI
+//
+// `GET /openapi.json` 描述本代理实际实现的端点、认证方式与错误格式，`GET /docs` 用 Swagger UI 浏览。
+// 端点列表来自 `proxy::routes::api_routes()`，与 server.rs 注册的路由是同一张表。
+// Swagger UI 的脚本与样式 (swagger-ui-dist 5.33.1，Apache-2.0，见 swagger-ui/LICENSE) 编译进程序，
+// 由 `/docs/` 下的路由提供，不从外部 CDN 加载。这些页面都不含敏感数据，开启认证时也可直接访问。
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
+
+/// 端点认证要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RouteAuth {
+    /// 无需 API Key
+    Public,
+    /// 任意有效 API Key (主 Key、账号池 Key、临时 Key)
+    ApiKey,
+    /// 仅主 API Key
+    Admin,
+}
+
+/// 请求 / 响应格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Body {
+    None,
+    Json,
+    Multipart,
+    /// 支持 `stream` 的 JSON 请求，流式时返回 SSE
+    JsonStream,
+    /// MCP Streamable HTTP (JSON-RPC)
+    Mcp,
+}
+
+/// 错误响应格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorShape {
+    /// 纯文本错误信息 (OpenAI / Gemini 协议端点)
+    Text,
+    /// Anthropic 格式 `{"type": "error", "error": {...}}`
+    Anthropic,
+    /// `{"error": "..."}` (管理类端点)
+    Json,
+}
+
+pub(crate) struct RouteDoc {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub tag: &'static str,
+    pub summary: &'static str,
+    pub auth: RouteAuth,
+    pub body: Body,
+    pub errors: ErrorShape,
+}
+
+pub(crate) const fn route(
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    auth: RouteAuth,
+    body: Body,
+    errors: ErrorShape,
+) -> RouteDoc {
+    RouteDoc { method, path, tag, summary, auth, body, errors }
+}
+
+/// `/v1beta/models/:model` -> (`/v1beta/models/{model}`, ["model"])
+fn openapi_path(path: &str) -> (String, Vec<&str>) {
+    let mut params = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => {
+                params.push(name);
+                format!("{{{}}}", name)
+            }
+            None => segment.to_string(),
+        })
+        .collect();
+    (segments.join("/"), params)
+}
+
+fn error_response(shape: ErrorShape, description: &str) -> Value {
+    match shape {
+        ErrorShape::Text => json!({ "description": description, "content": { "text/plain": { "schema": { "type": "string" } } } }),
+        ErrorShape::Anthropic => json!({
+            "description": description,
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/AnthropicError" } } }
+        }),
+        ErrorShape::Json => json!({
+            "description": description,
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+        }),
+    }
+}
+
+fn operation(route: &RouteDoc) -> Value {
+    let (_, params) = openapi_path(route.path);
+    let mut op = Map::new();
+    op.insert("tags".into(), json!([route.tag]));
+    op.insert("summary".into(), json!(route.summary));
+    if !params.is_empty() {
+        let parameters: Vec<Value> = params
+            .iter()
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .collect();
+        op.insert("parameters".into(), json!(parameters));
+    }
+
+    let json_body = json!({ "required": true, "content": { "application/json": { "schema": { "type": "object" } } } });
+    match route.body {
+        Body::Json | Body::JsonStream | Body::Mcp => {
+            op.insert("requestBody".into(), json_body);
+        }
+        Body::Multipart => {
+            op.insert(
+                "requestBody".into(),
+                json!({ "required": true, "content": { "multipart/form-data": { "schema": { "type": "object" } } } }),
+            );
+        }
+        Body::None => {}
+    }
+
+    let ok = match route.body {
+        Body::JsonStream => json!({
+            "description": "JSON response, or an SSE stream when the request asks for streaming",
+            "content": { "application/json": {}, "text/event-stream": {} }
+        }),
+        Body::Mcp => json!({
+            "description": "JSON-RPC response (MCP Streamable HTTP)",
+            "content": { "application/json": {}, "text/event-stream": {} }
+        }),
+        _ => json!({ "description": "OK" }),
+    };
+    let mut responses = Map::new();
+    responses.insert("200".into(), ok);
+    if route.body != Body::None {
+        responses.insert("400".into(), error_response(route.errors, "Invalid request"));
+    }
+    match route.auth {
+        RouteAuth::Public => {
+            op.insert("security".into(), json!([]));
+        }
+        RouteAuth::ApiKey => {
+            responses.insert("401".into(), json!({ "description": "Missing or invalid API key (empty body)" }));
+        }
+        RouteAuth::Admin => {
+            responses.insert("401".into(), json!({ "description": "Missing or invalid API key (empty body)" }));
+            responses.insert("403".into(), error_response(route.errors, "Requires the main API key"));
+        }
+    }
+    if route.body == Body::JsonStream {
+        responses.insert("429".into(), error_response(route.errors, "All accounts are rate limited or the pool is exhausted"));
+        responses.insert("503".into(), error_response(route.errors, "No usable account"));
+    }
+    op.insert("responses".into(), Value::Object(responses));
+    Value::Object(op)
+}
+
+/// 生成 OpenAPI 3.0 文档
+pub fn spec() -> Value {
+    let mut paths = Map::new();
+    for route in crate::proxy::routes::api_routes() {
+        let (path, _) = openapi_path(route.doc.path);
+        let item = paths.entry(path).or_insert_with(|| json!({}));
+        item[route.doc.method] = operation(&route.doc);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Antigravity Tools API Proxy",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "OpenAI, Anthropic and Gemini compatible endpoints served by Antigravity Tools. \
+                Authentication is only enforced when the proxy's auth mode requires it.",
+        },
+        "servers": [{ "url": "/" }],
+        "security": [{ "bearerAuth": [] }, { "apiKeyHeader": [] }],
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+                "apiKeyHeader": { "type": "apiKey", "in": "header", "name": "x-api-key" },
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": { "error": { "type": "string" } },
+                    "required": ["error"],
+                },
+                "AnthropicError": {
+                    "type": "object",
+                    "properties": {
+                        "type": { "type": "string", "enum": ["error"] },
+                        "error": {
+                            "type": "object",
+                            "properties": {
+                                "type": { "type": "string", "example": "invalid_request_error" },
+                                "message": { "type": "string" },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// 文档内容在运行期间不变，只生成一次
+static SPEC: Lazy<Value> = Lazy::new(spec);
+
+pub async fn handle_openapi() -> Response {
+    Json(SPEC.clone()).into_response()
+}
+
+const SWAGGER_UI_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Antigravity Tools API</title>
+<link rel="stylesheet" href="/docs/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="/docs/swagger-ui-bundle.js"></script>
+<script>
+  window.ui = SwaggerUIBundle({ url: '/openapi.json', dom_id: '#swagger-ui' });
+</script>
+</body>
+</html>
+"#;
+
+pub async fn handle_docs() -> Response {
+    ([(header::CACHE_CONTROL, "no-store")], Html(SWAGGER_UI_HTML)).into_response()
+}
+
+const SWAGGER_UI_CSS: &str = include_str!("swagger-ui/swagger-ui.css");
+const SWAGGER_UI_JS: &str = include_str!("swagger-ui/swagger-ui-bundle.js");
+
+/// Swagger UI 的静态资源 (随程序版本更新，可长期缓存)
+pub async fn handle_docs_asset(Path(asset): Path<String>) -> Response {
+    let (content_type, body) = match asset.as_str() {
+        "swagger-ui.css" => ("text/css; charset=utf-8", SWAGGER_UI_CSS),
+        "swagger-ui-bundle.js" => ("application/javascript; charset=utf-8", SWAGGER_UI_JS),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    (
+        [(header::CONTENT_TYPE, content_type), (header::CACHE_CONTROL, "public, max-age=86400")],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_paths() {
+        let spec = spec();
+        let op = &spec["paths"]["/v1beta/models/{model}"]["post"];
+        assert_eq!(op["parameters"][0]["name"], "model");
+        assert!(op["responses"]["200"]["content"]["text/event-stream"].is_object());
+        assert_eq!(spec["paths"]["/healthz"]["get"]["security"], json!([]));
+        assert_eq!(
+            spec["paths"]["/v1/messages"]["post"]["responses"]["400"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/AnthropicError"
+        );
+        assert!(spec["paths"]["/mcp/manager/mcp"]["post"]["responses"]["403"].is_object());
+    }
+}
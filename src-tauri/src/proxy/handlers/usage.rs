@@ -0,0 +1,46 @@
+// 按 API Key 的用量查询端点
+//
+// `GET /v1/usage/keys?window_hours=24`：主 API Key (或未开启认证时) 返回所有 Key 的用量，
+// 其他 Key 只能看到自己的用量。Key 只以指纹与末 4 位出现。
+
+use axum::{
+    extract::Query,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::modules::token_stats::KeyId;
+use crate::proxy::AdminAccess;
+
+#[derive(Debug, Deserialize)]
+pub struct KeyUsageQuery {
+    pub window_hours: Option<u32>,
+}
+
+pub async fn handle_key_usage(
+    admin: Option<Extension<AdminAccess>>,
+    headers: HeaderMap,
+    Query(query): Query<KeyUsageQuery>,
+) -> Response {
+    let window_hours = query.window_hours.unwrap_or(24);
+    let key_filter = if admin.is_some() { None } else { Some(KeyId::from_request(&headers, None).id) };
+
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours as i64 * 3600;
+    let result = tokio::task::spawn_blocking(move || {
+        let prices = crate::modules::config::load_app_config()
+            .map(|c| c.model_prices)
+            .unwrap_or_default();
+        crate::modules::token_stats::get_key_usage(since, now + 1, key_filter.as_deref(), &prices)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(keys)) => Json(json!({ "window_hours": window_hours, "keys": keys })).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
@@ -0,0 +1,321 @@
+// 请求钩子脚本
+//
+// 收到请求 (`on_request`) 与请求完成 (`on_complete`) 时运行用户配置的外部命令，
+// 请求摘要以一行 JSON 写入命令的标准输入，用于自定义日志、告警等，无需修改本程序。
+// 默认在后台执行，不等待结果也不影响请求；开启 `transform` 后 on_request 会收到请求体，
+// 程序等待其执行完毕: 标准输出为 JSON 时替换请求体，退出码非 0 时拒绝请求 (标准错误作为原因)，
+// 超时 (结束进程并记录警告) 或启动失败时按原请求继续，避免钩子故障导致反代不可用。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::modules::token_stats::KeyId;
+use crate::proxy::config::RequestHookConfig;
+use crate::proxy::monitor::ProxyRequestLog;
+
+/// 摘要中错误信息的最大字符数
+const MAX_ERROR_CHARS: usize = 2000;
+
+/// 收到请求时的摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestSummary {
+    pub event: &'static str,
+    pub id: String,
+    /// Unix 毫秒
+    pub timestamp: i64,
+    pub method: String,
+    pub url: String,
+    pub model: Option<String>,
+    /// API Key 指纹 (未携带 Key 时为空)
+    pub key_id: String,
+    /// 请求体 (仅 transform 模式)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl RequestSummary {
+    pub fn new(id: &str, method: &str, url: &str, model: Option<&str>, key_id: &KeyId) -> Self {
+        Self {
+            event: "request",
+            id: id.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            method: method.to_string(),
+            url: url.to_string(),
+            model: model.map(str::to_string),
+            key_id: key_id.id.clone(),
+            body: None,
+        }
+    }
+}
+
+/// 请求完成时的摘要 (不含请求 / 响应内容)
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionSummary {
+    pub event: &'static str,
+    pub id: String,
+    pub timestamp: i64,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// 拿到响应头的耗时 (流式响应即首字节时间)
+    pub duration_ms: u64,
+    /// 完整响应耗时
+    pub total_ms: u64,
+    pub model: Option<String>,
+    pub mapped_model: Option<String>,
+    pub account_email: Option<String>,
+    pub project_id: Option<String>,
+    pub error: Option<String>,
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+    pub key_id: String,
+}
+
+impl CompletionSummary {
+    pub fn from_log(log: &ProxyRequestLog, total_ms: u64, key_id: &KeyId) -> Self {
+        Self {
+            event: "complete",
+            id: log.id.clone(),
+            timestamp: log.timestamp,
+            method: log.method.clone(),
+            url: log.url.clone(),
+            status: log.status,
+            duration_ms: log.duration,
+            total_ms,
+            model: log.model.clone(),
+            mapped_model: log.mapped_model.clone(),
+            account_email: log.account_email.clone(),
+            project_id: log.project_id.clone(),
+            error: log.error.as_ref().map(|e| e.chars().take(MAX_ERROR_CHARS).collect()),
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+            key_id: key_id.id.clone(),
+        }
+    }
+}
+
+/// 命令执行结果
+struct HookOutput {
+    success: bool,
+    stdout: Vec<u8>,
+    stderr: String,
+}
+
+fn shell_command(command: &str) -> tokio::process::Command {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    cmd
+}
+
+/// 运行命令并把 `input` 写入标准输入，超时后结束进程
+///
+/// 标准输入在单独的任务中写入，同时读取输出: 命令边读边输出 (如 cat、sed) 时，
+/// 先写完再读会在管道缓冲区写满后互相等待
+async fn run_hook(command: &str, input: Vec<u8>, timeout_secs: u64) -> Result<HookOutput, String> {
+    let mut child = shell_command(command)
+        .spawn()
+        .map_err(|e| format!("启动钩子命令失败: {}", e))?;
+    let writer = child.stdin.take().map(|mut stdin| {
+        tokio::spawn(async move {
+            // 命令不读取标准输入时写入会失败，忽略
+            if let Err(e) = async {
+                stdin.write_all(&input).await?;
+                stdin.write_all(b"\n").await
+            }
+            .await
+            {
+                tracing::debug!("[Hooks] 写入钩子标准输入失败: {}", e);
+            }
+        })
+    });
+    let result = tokio::time::timeout(Duration::from_secs(timeout_secs.max(1)), child.wait_with_output()).await;
+    if let Some(writer) = writer {
+        writer.abort();
+    }
+    let output = result
+        .map_err(|_| format!("钩子命令执行超时 ({} 秒)，已结束进程", timeout_secs.max(1)))?
+        .map_err(|e| format!("钩子命令执行失败: {}", e))?;
+    Ok(HookOutput {
+        success: output.status.success(),
+        stdout: output.stdout,
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+/// 在后台运行命令，失败只记日志
+fn spawn_hook(name: &'static str, command: String, input: Vec<u8>, timeout_secs: u64) {
+    tokio::spawn(async move {
+        match run_hook(&command, input, timeout_secs).await {
+            Ok(output) if !output.success => {
+                tracing::warn!("[Hooks] {} 钩子退出码非 0: {}", name, output.stderr);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("[Hooks] {} 钩子: {}", name, e),
+        }
+    });
+}
+
+/// 解析 transform 钩子的输出: 非 0 退出码拒绝请求，JSON 输出替换请求体，空输出保持原样
+fn transform_result(output: HookOutput) -> Result<Option<Vec<u8>>, String> {
+    if !output.success {
+        return Err(if output.stderr.is_empty() { "request hook exited with non-zero status".to_string() } else { output.stderr });
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str::<Value>(stdout) {
+        Ok(body) => Ok(serde_json::to_vec(&body).ok()),
+        Err(e) => {
+            tracing::warn!("[Hooks] on_request 输出不是有效的 JSON，保持原请求: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+fn configured(command: &Option<String>) -> Option<String> {
+    command.as_deref().map(str::trim).filter(|c| !c.is_empty()).map(str::to_string)
+}
+
+#[derive(Debug, Default)]
+pub struct RequestHooks {
+    config: RwLock<RequestHookConfig>,
+}
+
+impl RequestHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_config(&self, config: RequestHookConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn has_request_hook(&self) -> bool {
+        configured(&self.config.read().unwrap().on_request).is_some()
+    }
+
+    /// on_request 需要请求体并等待其结果
+    pub fn transforms(&self) -> bool {
+        let config = self.config.read().unwrap();
+        config.transform && configured(&config.on_request).is_some()
+    }
+
+    /// 运行 on_request 钩子；transform 模式下返回替换后的请求体，Err 表示钩子拒绝了请求
+    pub async fn on_request(&self, mut summary: RequestSummary, body: Option<&[u8]>) -> Result<Option<Vec<u8>>, String> {
+        let (command, transform, timeout_secs) = {
+            let config = self.config.read().unwrap();
+            let Some(command) = configured(&config.on_request) else { return Ok(None) };
+            (command, config.transform, config.timeout_secs)
+        };
+        let body = body
+            .filter(|_| transform)
+            .and_then(|b| serde_json::from_slice::<Value>(b).ok());
+        if body.is_none() {
+            if let Ok(input) = serde_json::to_vec(&summary) {
+                spawn_hook("on_request", command, input, timeout_secs);
+            }
+            return Ok(None);
+        }
+
+        summary.body = body;
+        let input = serde_json::to_vec(&summary).map_err(|e| e.to_string())?;
+        match run_hook(&command, input, timeout_secs).await {
+            Ok(output) => transform_result(output),
+            Err(e) => {
+                tracing::warn!("[Hooks] on_request 钩子 (请求 {}): {}，未应用请求转换，按原请求继续", summary.id, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// 在后台运行 on_complete 钩子
+    pub fn on_complete(&self, log: &ProxyRequestLog, total_ms: u64, key_id: &KeyId) {
+        let (command, timeout_secs) = {
+            let config = self.config.read().unwrap();
+            let Some(command) = configured(&config.on_complete) else { return };
+            (command, config.timeout_secs)
+        };
+        if let Ok(input) = serde_json::to_vec(&CompletionSummary::from_log(log, total_ms, key_id)) {
+            spawn_hook("on_complete", command, input, timeout_secs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(success: bool, stdout: &str, stderr: &str) -> HookOutput {
+        HookOutput { success, stdout: stdout.as_bytes().to_vec(), stderr: stderr.to_string() }
+    }
+
+    #[test]
+    fn test_transform_result() {
+        assert_eq!(transform_result(output(true, "", "")).unwrap(), None);
+        assert_eq!(transform_result(output(true, "not json", "")).unwrap(), None);
+        assert_eq!(
+            transform_result(output(true, " {\"model\": \"gemini-2.5-pro\"}\n", "")).unwrap(),
+            Some(br#"{"model":"gemini-2.5-pro"}"#.to_vec())
+        );
+        assert_eq!(transform_result(output(false, "{}", "blocked")).unwrap_err(), "blocked");
+        assert!(transform_result(output(false, "", "")).is_err());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_run_hook_streams_large_input_and_times_out() {
+        // 输入远大于管道缓冲区，cat 边读边写
+        let input = vec![b'x'; 1024 * 1024];
+        let output = run_hook("cat", input.clone(), 10).await.unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout.len(), input.len() + 1);
+
+        let err = run_hook("sleep 5", Vec::new(), 1).await.err().unwrap();
+        assert!(err.contains("超时"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_transform_hook_rewrites_body() {
+        let hooks = RequestHooks::new();
+        hooks.set_config(RequestHookConfig {
+            on_request: Some("sed 's/\"claude-sonnet\"/\"gemini-2.5-pro\"/g' | grep -o '\"body\":{[^}]*}' | cut -c8-".to_string()),
+            transform: true,
+            ..Default::default()
+        });
+        assert!(hooks.transforms());
+
+        let summary = RequestSummary::new("req-1", "POST", "/v1/messages", Some("claude-sonnet"), &KeyId::default());
+        let body = hooks.on_request(summary, Some(br#"{"model":"claude-sonnet"}"#)).await.unwrap();
+        assert_eq!(body, Some(br#"{"model":"gemini-2.5-pro"}"#.to_vec()));
+
+        hooks.set_config(RequestHookConfig {
+            on_request: Some("echo denied >&2; exit 1".to_string()),
+            transform: true,
+            ..Default::default()
+        });
+        let summary = RequestSummary::new("req-2", "POST", "/v1/messages", None, &KeyId::default());
+        assert_eq!(hooks.on_request(summary, Some(b"{}")).await.unwrap_err(), "denied");
+    }
+}
@@ -0,0 +1,235 @@
+// 实时请求检查器 (可选)
+//
+// 开启后每个请求结束时推送一条脱敏摘要到前端 (`proxy://inspector`)：模型、消息数、
+// 最后一条用户消息与响应内容的截断预览、Token 用量。预览在截断前先做脱敏，
+// 内置规则见 utils::redact (API Key、OAuth Token、refresh_token 等)，并可追加自定义正则。
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::RwLock;
+
+use crate::proxy::config::InspectorConfig;
+use crate::proxy::monitor::ProxyRequestLog;
+use crate::utils::redact::REDACTED;
+
+pub const INSPECTOR_EVENT: &str = "proxy://inspector";
+
+/// 推送给前端的请求摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectorEvent {
+    pub id: String,
+    pub timestamp: i64,
+    pub method: String,
+    /// 请求路径 (不含查询参数)
+    pub path: String,
+    pub status: u16,
+    pub duration: u64,
+    pub model: Option<String>,
+    pub mapped_model: Option<String>,
+    pub account_email: Option<String>,
+    pub stream: bool,
+    pub message_count: usize,
+    /// 最后一条用户消息 (脱敏并截断)
+    pub prompt_preview: Option<String>,
+    /// 响应文本 (脱敏并截断)
+    pub response_preview: Option<String>,
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct Inspector {
+    config: RwLock<InspectorConfig>,
+    patterns: RwLock<Vec<Regex>>,
+}
+
+/// 提取消息内容中的文本 (字符串或 Claude / OpenAI 内容块数组)
+pub(crate) fn content_text(content: &Value) -> Option<String> {
+    match content {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(blocks) => {
+            let texts: Vec<&str> = blocks
+                .iter()
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect();
+            (!texts.is_empty()).then(|| texts.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+/// 请求摘要：(消息数, 最后一条用户消息, 是否流式)，兼容 OpenAI / Claude / Gemini 格式
+fn request_summary(body: &Value) -> (usize, Option<String>, bool) {
+    let stream = body.get("stream").and_then(|v| v.as_bool()).unwrap_or(false);
+    if let Some(messages) = body.get("messages").and_then(|m| m.as_array()) {
+        let last_user = messages
+            .iter()
+            .rev()
+            .find(|m| m["role"] == "user")
+            .and_then(|m| content_text(&m["content"]));
+        return (messages.len(), last_user, stream);
+    }
+    if let Some(contents) = body.get("contents").and_then(|c| c.as_array()) {
+        let last_user = contents
+            .iter()
+            .rev()
+            .find(|c| c.get("role").is_none_or(|r| r == "user"))
+            .and_then(|c| content_text(&c["parts"]));
+        return (contents.len(), last_user, stream);
+    }
+    // OpenAI Responses / Completions
+    let input = body.get("input").or_else(|| body.get("prompt"));
+    match input {
+        Some(Value::Array(items)) => (items.len(), items.last().and_then(|i| content_text(&i["content"])), stream),
+        Some(value) => (1, content_text(value), stream),
+        None => (0, None, stream),
+    }
+}
+
+/// 非流式响应中的文本，兼容 OpenAI / Claude / Gemini 格式
+pub fn response_text(json: &Value) -> Option<String> {
+    if let Some(text) = json["choices"][0]["message"]["content"].as_str() {
+        return Some(text.to_string());
+    }
+    if let Some(content) = json.get("content").filter(|c| c.is_array()) {
+        return content_text(content);
+    }
+    let candidate = json.get("response").unwrap_or(json);
+    content_text(&candidate["candidates"][0]["content"]["parts"])
+}
+
+/// 流式响应中一行 SSE 携带的文本增量
+pub fn stream_delta_text(line: &str) -> Option<String> {
+    let data = line.trim().strip_prefix("data:")?.trim();
+    let json: Value = serde_json::from_str(data).ok()?;
+    if let Some(text) = json["choices"][0]["delta"]["content"].as_str() {
+        return Some(text.to_string());
+    }
+    if let Some(text) = json["delta"]["text"].as_str() {
+        return Some(text.to_string());
+    }
+    let candidate = json.get("response").unwrap_or(&json);
+    content_text(&candidate["candidates"][0]["content"]["parts"])
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 更新配置；无效的自定义正则会被跳过并记录警告
+    pub fn set_config(&self, config: InspectorConfig) {
+        let patterns = config
+            .redact_patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    tracing::warn!("[Inspector] Ignoring invalid redact pattern {:?}: {}", p, e);
+                    None
+                }
+            })
+            .collect();
+        *self.patterns.write().unwrap() = patterns;
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.read().unwrap().enabled
+    }
+
+    pub fn max_content_chars(&self) -> usize {
+        self.config.read().unwrap().max_content_chars
+    }
+
+    /// 按内置与自定义规则替换文本中的密钥
+    pub fn redact(&self, text: &str) -> String {
+        let patterns = self.patterns.read().unwrap();
+        let mut out = crate::utils::redact::redact(text).into_owned();
+        for re in patterns.iter() {
+            out = re.replace_all(&out, REDACTED).into_owned();
+        }
+        out
+    }
+
+    fn preview(&self, text: &str, max_chars: usize) -> String {
+        truncate(&self.redact(text), max_chars)
+    }
+
+    /// 生成请求摘要；`response` 为已提取的响应文本
+    pub fn build_event(&self, log: &ProxyRequestLog, response: Option<&str>) -> InspectorEvent {
+        let max_chars = self.max_content_chars();
+        let body = log.request_body.as_deref().and_then(|b| serde_json::from_str::<Value>(b).ok());
+        let (message_count, prompt, stream) = body.as_ref().map(request_summary).unwrap_or((0, None, false));
+        InspectorEvent {
+            id: log.id.clone(),
+            timestamp: log.timestamp,
+            method: log.method.clone(),
+            path: log.url.split('?').next().unwrap_or_default().to_string(),
+            status: log.status,
+            duration: log.duration,
+            model: log.model.clone(),
+            mapped_model: log.mapped_model.clone(),
+            account_email: log.account_email.clone(),
+            stream,
+            message_count,
+            prompt_preview: prompt.map(|p| self.preview(&p, max_chars)),
+            response_preview: response
+                .or(log.error.as_deref())
+                .filter(|r| !r.is_empty())
+                .map(|r| self.preview(r, max_chars)),
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn inspector(patterns: Vec<String>) -> Inspector {
+        let inspector = Inspector::new();
+        inspector.set_config(InspectorConfig { enabled: true, max_content_chars: 40, redact_patterns: patterns });
+        inspector
+    }
+
+    #[test]
+    fn test_redacts_secrets_before_truncating() {
+        let i = inspector(vec![r"\bINTERNAL-\d+\b".to_string(), "(".to_string()]);
+        let text = "use sk-abcdefghijklmnopqrstuvwx and api_key=hunter2 for INTERNAL-42";
+        let redacted = i.redact(text);
+        assert!(!redacted.contains("sk-abc"));
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("INTERNAL-42"));
+        assert_eq!(i.preview(&"x".repeat(100), 40).chars().count(), 41);
+        assert_eq!(i.redact("Authorization: Bearer ya29.a0AfH6SM"), "Authorization: [REDACTED]");
+    }
+
+    #[test]
+    fn test_summaries_cover_all_protocols() {
+        let openai = json!({ "stream": true, "messages": [
+            { "role": "system", "content": "be brief" },
+            { "role": "user", "content": [{ "type": "text", "text": "hello" }] }
+        ] });
+        assert_eq!(request_summary(&openai), (2, Some("hello".to_string()), true));
+
+        let gemini = json!({ "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }] });
+        assert_eq!(request_summary(&gemini), (1, Some("hi".to_string()), false));
+
+        assert_eq!(response_text(&json!({ "choices": [{ "message": { "content": "ok" } }] })).as_deref(), Some("ok"));
+        assert_eq!(response_text(&json!({ "content": [{ "type": "text", "text": "claude" }] })).as_deref(), Some("claude"));
+        assert_eq!(stream_delta_text(r#"data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"Hi"}}"#).as_deref(), Some("Hi"));
+        assert_eq!(stream_delta_text(r#"data: {"choices":[{"delta":{"content":"o"}}]}"#).as_deref(), Some("o"));
+        assert!(stream_delta_text("data: [DONE]").is_none());
+    }
+}
@@ -0,0 +1,245 @@
+// 延迟直方图
+//
+// 按 (模型, 账号) 记录首字节时间 (TTFB) 与总耗时的固定分桶直方图，
+// 用于定位慢账号 / 慢区域。数据只保存在内存中，服务重启后重新累计；
+// 序列数超过上限 (`memory_limits.latency_series`) 时淘汰最久没有新请求的序列。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::proxy::memory_limits::{self, Subsystem};
+
+/// 分桶上界 (毫秒)，最后还有一个 +Inf 桶
+pub const BUCKET_BOUNDS_MS: [u64; 12] = [100, 250, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 30_000, 60_000, 120_000, 300_000];
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// 各桶计数 (非累积)，长度为 BUCKET_BOUNDS_MS.len() + 1
+    buckets: Vec<u64>,
+    count: u64,
+    sum_ms: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, ms: u64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; BUCKET_BOUNDS_MS.len() + 1];
+        }
+        let idx = BUCKET_BOUNDS_MS.iter().position(|&bound| ms <= bound).unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.sum_ms += ms;
+    }
+
+    /// 按分桶估算分位数 (返回所在桶的上界；落在 +Inf 桶时返回最后一个上界)
+    fn quantile(&self, q: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(*BUCKET_BOUNDS_MS.get(idx).unwrap_or(&BUCKET_BOUNDS_MS[BUCKET_BOUNDS_MS.len() - 1]));
+            }
+        }
+        None
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self.buckets.clone(),
+            count: self.count,
+            sum_ms: self.sum_ms,
+            avg_ms: self.sum_ms.checked_div(self.count).unwrap_or(0),
+            p50_ms: self.quantile(0.5),
+            p95_ms: self.quantile(0.95),
+        }
+    }
+}
+
+/// 直方图快照
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramSnapshot {
+    /// 各桶计数 (非累积)，与 `bounds_ms` 对应，最后一个为 +Inf 桶
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_ms: u64,
+    pub avg_ms: u64,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+}
+
+/// 单个 (模型, 账号) 的延迟统计
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySeries {
+    pub model: String,
+    pub account: String,
+    pub ttfb: HistogramSnapshot,
+    pub total: HistogramSnapshot,
+}
+
+/// 延迟统计结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub bounds_ms: Vec<u64>,
+    pub series: Vec<LatencySeries>,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    ttfb: Histogram,
+    total: Histogram,
+    /// 最近一次记录的逻辑时钟 (LRU 淘汰)
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct LatencyRegistry {
+    series: DashMap<(String, String), Series>,
+    clock: AtomicU64,
+    /// 序列数上限，0 表示不限制
+    max_series: AtomicUsize,
+}
+
+impl LatencyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_max_series(&self, max_series: usize) {
+        self.max_series.store(max_series, Ordering::Relaxed);
+        self.evict_lru();
+    }
+
+    pub fn record(&self, model: &str, account: &str, ttfb_ms: u64, total_ms: u64) {
+        let key = (model.to_string(), account.to_string());
+        let is_new = !self.series.contains_key(&key);
+        {
+            let mut entry = self.series.entry(key).or_default();
+            entry.ttfb.observe(ttfb_ms);
+            entry.total.observe(total_ms.max(ttfb_ms));
+            entry.last_used = self.clock.fetch_add(1, Ordering::Relaxed);
+        }
+        if is_new {
+            self.evict_lru();
+        }
+    }
+
+    /// 超出上限时淘汰最久未更新的序列
+    fn evict_lru(&self) {
+        let max = self.max_series.load(Ordering::Relaxed);
+        if max == 0 || self.series.len() <= max {
+            return;
+        }
+        let mut by_use: Vec<(u64, (String, String))> =
+            self.series.iter().map(|entry| (entry.last_used, entry.key().clone())).collect();
+        by_use.sort_unstable();
+        let excess = by_use.len().saturating_sub(max);
+        for (_, key) in by_use.into_iter().take(excess) {
+            self.series.remove(&key);
+        }
+        memory_limits::record_evictions(Subsystem::LatencySeries, excess);
+    }
+
+    /// 按平均总耗时从慢到快排序
+    pub fn report(&self) -> LatencyReport {
+        let mut series: Vec<LatencySeries> = self
+            .series
+            .iter()
+            .map(|entry| {
+                let ((model, account), series) = entry.pair();
+                LatencySeries {
+                    model: model.clone(),
+                    account: account.clone(),
+                    ttfb: series.ttfb.snapshot(),
+                    total: series.total.snapshot(),
+                }
+            })
+            .collect();
+        series.sort_by(|a, b| b.total.avg_ms.cmp(&a.total.avg_ms).then_with(|| a.model.cmp(&b.model)));
+        LatencyReport { bounds_ms: BUCKET_BOUNDS_MS.to_vec(), series }
+    }
+
+    pub fn clear(&self) {
+        self.series.clear();
+    }
+
+    /// Prometheus 文本格式 (秒为单位的累积直方图)
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let report = self.report();
+        for (name, help, pick) in [
+            (
+                "antigravity_upstream_ttfb_seconds",
+                "Time to first byte of proxied requests",
+                (|s: &LatencySeries| &s.ttfb) as fn(&LatencySeries) -> &HistogramSnapshot,
+            ),
+            (
+                "antigravity_request_duration_seconds",
+                "Total duration of proxied requests",
+                |s: &LatencySeries| &s.total,
+            ),
+        ] {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} histogram\n", name, help, name));
+            for series in &report.series {
+                let hist = pick(series);
+                let labels = format!(
+                    "model=\"{}\",account=\"{}\"",
+                    escape_label(&series.model),
+                    escape_label(&series.account)
+                );
+                let mut cumulative = 0;
+                for (idx, count) in hist.buckets.iter().enumerate() {
+                    cumulative += count;
+                    let le = BUCKET_BOUNDS_MS
+                        .get(idx)
+                        .map(|ms| format!("{}", *ms as f64 / 1000.0))
+                        .unwrap_or_else(|| "+Inf".to_string());
+                    out.push_str(&format!("{}_bucket{{{},le=\"{}\"}} {}\n", name, labels, le, cumulative));
+                }
+                out.push_str(&format!("{}_sum{{{}}} {}\n", name, labels, hist.sum_ms as f64 / 1000.0));
+                out.push_str(&format!("{}_count{{{}}} {}\n", name, labels, hist.count));
+            }
+        }
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histograms_are_split_by_model_and_account() {
+        let registry = LatencyRegistry::new();
+        registry.record("gemini-2.5-flash", "a@example.com", 80, 900);
+        registry.record("gemini-2.5-flash", "a@example.com", 300, 4_000);
+        registry.record("gemini-2.5-flash", "b@example.com", 2_500, 400_000);
+
+        let report = registry.report();
+        assert_eq!(report.series.len(), 2);
+        let slow = &report.series[0];
+        assert_eq!(slow.account, "b@example.com");
+        assert_eq!(slow.total.buckets.last(), Some(&1));
+        assert_eq!(slow.total.p95_ms, Some(300_000));
+
+        let fast = &report.series[1];
+        assert_eq!(fast.ttfb.count, 2);
+        assert_eq!(fast.ttfb.p50_ms, Some(100));
+        assert_eq!(fast.ttfb.p95_ms, Some(500));
+        assert_eq!(fast.total.avg_ms, 2_450);
+
+        let text = registry.render_prometheus();
+        assert!(text.contains(
+            "antigravity_request_duration_seconds_bucket{model=\"gemini-2.5-flash\",account=\"a@example.com\",le=\"+Inf\"} 2"
+        ));
+        assert!(text.contains("antigravity_upstream_ttfb_seconds_count{model=\"gemini-2.5-flash\",account=\"b@example.com\"} 1"));
+    }
+}
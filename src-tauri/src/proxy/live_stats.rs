@@ -0,0 +1,226 @@
+// 实时统计事件
+//
+// 每个请求结束时先累积到内存中，再由后台任务按固定节奏 (默认 1 秒) 合并推送
+// `proxy://live-stats` 事件 (本批完成的请求、Token 数与当前 RPS)，
+// 前端仪表盘直接订阅即可，无需轮询日志库。不受请求日志开关影响。
+// 另外保留最近 5 分钟的错误时间，供托盘显示错误角标。
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Mutex, Weak};
+use std::time::Duration;
+
+use crate::proxy::monitor::ProxyRequestLog;
+
+pub const LIVE_STATS_EVENT: &str = "proxy://live-stats";
+const EMIT_INTERVAL_MS: u64 = 1_000;
+/// RPS 滑动窗口
+const RPS_WINDOW_MS: i64 = 10_000;
+/// 单个事件最多携带的请求明细 (超出部分只计入汇总)
+const MAX_RECENT: usize = 50;
+/// 托盘错误角标的统计窗口
+const RECENT_ERRORS_WINDOW_MS: i64 = 5 * 60_000;
+
+/// 单个已完成请求的摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct FinishedRequest {
+    pub id: String,
+    pub timestamp: i64,
+    pub model: Option<String>,
+    pub account_email: Option<String>,
+    pub status: u16,
+    pub duration: u64,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// 一次推送的内容 (自上次推送以来的增量)
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveStatsEvent {
+    /// 推送时间 (毫秒)
+    pub timestamp: i64,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 最近 10 秒的平均每秒请求数
+    pub rps: f64,
+    /// 本批完成的请求 (最多 50 条，新的在后)
+    pub recent: Vec<FinishedRequest>,
+}
+
+#[derive(Debug, Default)]
+struct Pending {
+    requests: u64,
+    errors: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    recent: Vec<FinishedRequest>,
+    /// 窗口内请求的完成时间 (毫秒)
+    window: VecDeque<i64>,
+    /// 上一次推送时仍有流量，用于空闲后补发一次 RPS 归零
+    was_active: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct LiveStats {
+    pending: Mutex<Pending>,
+    /// 最近错误的完成时间 (毫秒)，不随推送清空
+    errors: Mutex<VecDeque<i64>>,
+}
+
+fn prune(window: &mut VecDeque<i64>, cutoff: i64) {
+    while window.front().is_some_and(|&ts| ts <= cutoff) {
+        window.pop_front();
+    }
+}
+
+impl LiveStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&self, log: &ProxyRequestLog, finished_at_ms: i64) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.requests += 1;
+        if log.status >= 400 {
+            pending.errors += 1;
+            let mut errors = self.errors.lock().unwrap();
+            prune(&mut errors, finished_at_ms - RECENT_ERRORS_WINDOW_MS);
+            errors.push_back(finished_at_ms);
+        }
+        pending.input_tokens += log.input_tokens.unwrap_or(0) as u64;
+        pending.output_tokens += log.output_tokens.unwrap_or(0) as u64;
+        pending.window.push_back(finished_at_ms);
+        if pending.recent.len() >= MAX_RECENT {
+            pending.recent.remove(0);
+        }
+        pending.recent.push(FinishedRequest {
+            id: log.id.clone(),
+            timestamp: log.timestamp,
+            model: log.mapped_model.clone().or_else(|| log.model.clone()),
+            account_email: log.account_email.clone(),
+            status: log.status,
+            duration: log.duration,
+            input_tokens: log.input_tokens.unwrap_or(0),
+            output_tokens: log.output_tokens.unwrap_or(0),
+        });
+    }
+
+    /// 最近 5 分钟的错误请求数
+    pub fn recent_errors(&self, now_ms: i64) -> usize {
+        let mut errors = self.errors.lock().unwrap();
+        prune(&mut errors, now_ms - RECENT_ERRORS_WINDOW_MS);
+        errors.len()
+    }
+
+    /// 最近 10 秒的每秒请求数 (不影响推送的增量)
+    pub fn rps(&self, now_ms: i64) -> f64 {
+        let mut pending = self.pending.lock().unwrap();
+        prune(&mut pending.window, now_ms - RPS_WINDOW_MS);
+        pending.window.len() as f64 / (RPS_WINDOW_MS as f64 / 1000.0)
+    }
+
+    /// 取出自上次以来的增量；没有新请求且 RPS 已归零时返回 None (不推送)
+    fn drain(&self, now_ms: i64) -> Option<LiveStatsEvent> {
+        let mut pending = self.pending.lock().unwrap();
+        prune(&mut pending.window, now_ms - RPS_WINDOW_MS);
+        let active = pending.requests > 0 || !pending.window.is_empty();
+        if !active && !pending.was_active {
+            return None;
+        }
+        pending.was_active = active;
+
+        let rps = pending.window.len() as f64 / (RPS_WINDOW_MS as f64 / 1000.0);
+        let event = LiveStatsEvent {
+            timestamp: now_ms,
+            requests: pending.requests,
+            errors: pending.errors,
+            input_tokens: pending.input_tokens,
+            output_tokens: pending.output_tokens,
+            rps,
+            recent: std::mem::take(&mut pending.recent),
+        };
+        pending.requests = 0;
+        pending.errors = 0;
+        pending.input_tokens = 0;
+        pending.output_tokens = 0;
+        Some(event)
+    }
+}
+
+/// 启动推送任务；反代服务停止 (统计对象被释放) 后自动退出
+pub fn start_emitter(stats: Weak<LiveStats>, app_handle: crate::utils::app::AppHandle) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(EMIT_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            let Some(stats) = stats.upgrade() else { break };
+            if let Some(event) = stats.drain(chrono::Utc::now().timestamp_millis()) {
+                crate::utils::app::emit(&app_handle, LIVE_STATS_EVENT, &event);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(status: u16, input: u32, output: u32) -> ProxyRequestLog {
+        ProxyRequestLog {
+            id: "id".to_string(),
+            timestamp: 0,
+            method: "POST".to_string(),
+            url: "/v1/messages".to_string(),
+            status,
+            duration: 10,
+            model: Some("claude-sonnet-4-5".to_string()),
+            mapped_model: Some("gemini-2.5-pro".to_string()),
+            account_email: Some("a@example.com".to_string()),
+            project_id: None,
+            error: None,
+            request_body: None,
+            response_body: None,
+            input_tokens: Some(input),
+            output_tokens: Some(output),
+        }
+    }
+
+    #[test]
+    fn test_drain_returns_increments_and_goes_quiet_when_idle() {
+        let stats = LiveStats::new();
+        assert!(stats.drain(1_000).is_none());
+
+        stats.observe(&log(200, 100, 20), 1_000);
+        stats.observe(&log(429, 0, 0), 1_500);
+        assert!((stats.rps(2_000) - 0.2).abs() < 1e-9);
+        let event = stats.drain(2_000).unwrap();
+        assert_eq!((event.requests, event.errors, event.input_tokens, event.output_tokens), (2, 1, 100, 20));
+        assert_eq!(event.recent.len(), 2);
+        assert_eq!(event.recent[0].model.as_deref(), Some("gemini-2.5-pro"));
+        assert!((event.rps - 0.2).abs() < 1e-9);
+
+        // 窗口内仍有流量：继续推送 RPS，但增量为 0
+        let event = stats.drain(3_000).unwrap();
+        assert_eq!(event.requests, 0);
+        assert!(event.recent.is_empty());
+
+        // 窗口过期后补发一次归零，之后不再推送
+        assert_eq!(stats.drain(20_000).unwrap().rps, 0.0);
+        assert!(stats.drain(21_000).is_none());
+    }
+
+    #[test]
+    fn test_recent_errors_window() {
+        let stats = LiveStats::new();
+        stats.observe(&log(500, 0, 0), 1_000);
+        stats.observe(&log(200, 0, 0), 2_000);
+        stats.observe(&log(429, 0, 0), 60_000);
+        // 推送不会清空错误窗口
+        stats.drain(61_000);
+        assert_eq!(stats.recent_errors(61_000), 2);
+        assert_eq!(stats.recent_errors(1_000 + RECENT_ERRORS_WINDOW_MS), 1);
+        assert_eq!(stats.recent_errors(60_000 + RECENT_ERRORS_WINDOW_MS), 0);
+    }
+}
@@ -0,0 +1,135 @@
+// 负载仪表 (进行中请求 / 排队数 / 各账号并发)
+//
+// 进行中请求数由监控中间件计数 (流式响应直到流结束才释放)；排队数与各账号并发来自
+// 账号池 (TokenPool) 的并发槽位。通过 `/metrics` 暴露为 gauge，并在数值变化时推送
+// `proxy://load` 事件，过载在变成失败之前就能在界面上看到。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use crate::proxy::token_manager::TokenManager;
+
+pub use antigravity_proxy_core::token_pool::AccountLoad;
+
+pub const LOAD_EVENT: &str = "proxy://load";
+const EMIT_INTERVAL_MS: u64 = 1_000;
+
+/// 进行中请求计数，drop 时减一
+pub struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 进行中请求计数器 (clone 后共享同一计数)
+#[derive(Debug, Default, Clone)]
+pub struct InFlightCounter(Arc<AtomicUsize>);
+
+impl InFlightCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter(&self) -> InFlightGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self.0.clone())
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 当前负载快照
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LoadSnapshot {
+    /// 生成时间 (毫秒)
+    pub timestamp: i64,
+    /// 反代正在处理的请求 (含流式响应)
+    pub in_flight: usize,
+    /// 所有账号满载、正在排队等待槽位的请求
+    pub queued: usize,
+    /// 有请求在途的账号 (按并发数从多到少)
+    pub accounts: Vec<AccountLoad>,
+}
+
+impl LoadSnapshot {
+    pub fn collect(in_flight: usize, token_manager: Option<&TokenManager>) -> Self {
+        let (queued, accounts) = token_manager.map(|m| m.load_snapshot()).unwrap_or_default();
+        Self { timestamp: chrono::Utc::now().timestamp_millis(), in_flight, queued, accounts }
+    }
+
+    fn same_load(&self, other: &LoadSnapshot) -> bool {
+        self.in_flight == other.in_flight && self.queued == other.queued && self.accounts == other.accounts
+    }
+
+    /// Prometheus 文本格式
+    pub fn render_prometheus(&self) -> String {
+        let mut body = String::new();
+        for (name, help, value) in [
+            ("antigravity_requests_in_flight", "Proxy requests currently being served", self.in_flight),
+            ("antigravity_requests_queued", "Requests waiting for a free account slot", self.queued),
+        ] {
+            body.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+        }
+        body.push_str("# HELP antigravity_account_in_flight Upstream requests in flight per account\n");
+        body.push_str("# TYPE antigravity_account_in_flight gauge\n");
+        for account in &self.accounts {
+            body.push_str(&format!(
+                "antigravity_account_in_flight{{account=\"{}\"}} {}\n",
+                account.email.replace('\\', "\\\\").replace('"', "\\\""),
+                account.in_flight
+            ));
+        }
+        body
+    }
+}
+
+/// 启动推送任务 (只在负载变化时推送)；反代服务停止 (TokenManager 被释放) 后自动退出
+pub fn start_emitter(token_manager: Weak<TokenManager>, in_flight: InFlightCounter, app_handle: crate::utils::app::AppHandle) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(EMIT_INTERVAL_MS));
+        let mut last: Option<LoadSnapshot> = None;
+        loop {
+            interval.tick().await;
+            let Some(manager) = token_manager.upgrade() else { break };
+            let snapshot = LoadSnapshot::collect(in_flight.get(), Some(&manager));
+            if last.as_ref().is_none_or(|prev| !prev.same_load(&snapshot)) {
+                crate::utils::app::emit(&app_handle, LOAD_EVENT, &snapshot);
+                last = Some(snapshot);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_tracks_in_flight_and_renders_gauges() {
+        let counter = InFlightCounter::new();
+        let a = counter.enter();
+        let b = counter.enter();
+        assert_eq!(counter.get(), 2);
+        drop(a);
+        assert_eq!(counter.get(), 1);
+        drop(b);
+
+        let snapshot = LoadSnapshot {
+            timestamp: 0,
+            in_flight: 3,
+            queued: 1,
+            accounts: vec![AccountLoad { account_id: "id".into(), email: "a@example.com".into(), in_flight: 2 }],
+        };
+        let body = snapshot.render_prometheus();
+        assert!(body.contains("antigravity_requests_in_flight 3\n"));
+        assert!(body.contains("antigravity_requests_queued 1\n"));
+        assert!(body.contains("antigravity_account_in_flight{account=\"a@example.com\"} 2\n"));
+        assert!(snapshot.same_load(&LoadSnapshot { timestamp: 99, ..snapshot.clone() }));
+    }
+}
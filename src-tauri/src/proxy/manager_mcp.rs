@@ -0,0 +1,202 @@
+// 管理用 MCP 工具
+//
+// 通过 `/mcp/manager/mcp` 暴露给 MCP 客户端 (如编程 Agent)，让其查看和管理自己正在使用的反代:
+// 账号列表、账号健康检查、用量统计、切换默认账号池。只有主 API Key (或未开启认证) 可以调用。
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::models::Account;
+use crate::proxy::config::AccountPool;
+use crate::proxy::server::AppState;
+
+/// 账号列表中的单个账号 (不含 Token)
+#[derive(Debug, Serialize)]
+struct AccountEntry {
+    id: String,
+    email: String,
+    tier: Option<String>,
+    disabled: bool,
+    proxy_disabled: bool,
+    needs_reauth: bool,
+    quarantined: bool,
+    priority: i32,
+    tags: Vec<String>,
+    /// 模型 -> 剩余配额百分比
+    quota: Vec<(String, i32)>,
+}
+
+impl From<&Account> for AccountEntry {
+    fn from(account: &Account) -> Self {
+        Self {
+            id: account.id.clone(),
+            email: account.email.clone(),
+            tier: account.quota.as_ref().and_then(|q| q.subscription_tier.clone()),
+            disabled: account.disabled,
+            proxy_disabled: account.proxy_disabled,
+            needs_reauth: account.needs_reauth,
+            quarantined: account.quarantined,
+            priority: account.priority,
+            tags: account.tags.clone(),
+            quota: account
+                .quota
+                .as_ref()
+                .map(|q| q.models.iter().map(|m| (m.name.clone(), m.percentage)).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+pub fn tool_specs() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "list_accounts",
+            "description": "List proxy accounts with their status, tier, tags and remaining quota per model, plus the configured account pools.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        json!({
+            "name": "account_health",
+            "description": "Run a live health check (token refresh + test request) for one account, or for every enabled account when `account` is omitted.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "account": { "type": "string", "description": "Account ID or email" }
+                }
+            }
+        }),
+        json!({
+            "name": "usage_stats",
+            "description": "Token usage per API key, model and account over a recent window, with estimated cost.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "window_hours": { "type": "integer", "minimum": 1, "description": "Look-back window in hours (default 24)" }
+                }
+            }
+        }),
+        json!({
+            "name": "switch_pool",
+            "description": "Switch the account pool used by requests whose API key is not bound to a pool. Pass an empty or null `pool` to use all accounts again.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "pool": { "type": ["string", "null"], "description": "Account pool name" }
+                },
+                "required": ["pool"]
+            }
+        }),
+    ]
+}
+
+/// 校验要切换到的账号池 (空字符串视为清除)
+fn resolve_pool(pools: &[AccountPool], requested: Option<&str>) -> Result<Option<String>, String> {
+    match requested.map(str::trim).filter(|p| !p.is_empty()) {
+        None => Ok(None),
+        Some(name) if pools.iter().any(|p| p.name == name) => Ok(Some(name.to_string())),
+        Some(name) => Err(format!(
+            "Account pool '{}' not found (available: {})",
+            name,
+            pools.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| e.to_string())?
+}
+
+pub(crate) async fn list_accounts(state: &AppState) -> Result<Value, String> {
+    let accounts = blocking(crate::modules::account::list_accounts).await?;
+    let config = blocking(crate::modules::config::load_app_config).await?;
+    let default_pool = state.security.read().await.default_pool.clone();
+    Ok(json!({
+        "accounts": accounts.iter().map(AccountEntry::from).collect::<Vec<_>>(),
+        "pools": config.proxy.account_pools.iter().map(|p| json!({ "name": p.name, "accounts": p.accounts })).collect::<Vec<_>>(),
+        "default_pool": default_pool,
+    }))
+}
+
+async fn account_health(arguments: &Value) -> Result<Value, String> {
+    let Some(target) = arguments.get("account").and_then(|v| v.as_str()).filter(|s| !s.is_empty()) else {
+        return serde_json::to_value(crate::modules::health::check_all_accounts().await?).map_err(|e| e.to_string());
+    };
+    let accounts = blocking(crate::modules::account::list_accounts).await?;
+    let account = accounts
+        .into_iter()
+        .find(|a| a.id == target || a.email.eq_ignore_ascii_case(target))
+        .ok_or_else(|| format!("Account '{}' not found", target))?;
+    serde_json::to_value(crate::modules::health::check_account(account).await).map_err(|e| e.to_string())
+}
+
+pub(crate) async fn usage_stats(arguments: &Value) -> Result<Value, String> {
+    let window_hours = arguments.get("window_hours").and_then(|v| v.as_u64()).unwrap_or(24).max(1);
+    let now = chrono::Utc::now().timestamp();
+    let since = now - window_hours as i64 * 3600;
+    let rows = blocking(move || {
+        let prices = crate::modules::config::load_app_config().map(|c| c.model_prices).unwrap_or_default();
+        crate::modules::token_stats::get_summary(since, now + 1, &prices)
+    })
+    .await?;
+    let total_requests: u64 = rows.iter().map(|r| r.requests).sum();
+    let total_input: u64 = rows.iter().map(|r| r.input_tokens).sum();
+    let total_output: u64 = rows.iter().map(|r| r.output_tokens).sum();
+    Ok(json!({
+        "window_hours": window_hours,
+        "total_requests": total_requests,
+        "total_input_tokens": total_input,
+        "total_output_tokens": total_output,
+        "rows": rows,
+    }))
+}
+
+async fn switch_pool(state: &AppState, arguments: &Value) -> Result<Value, String> {
+    let requested = arguments.get("pool").and_then(|v| v.as_str()).map(str::to_string);
+    let pool = blocking(move || {
+        let mut config = crate::modules::config::load_app_config()?;
+        let pool = resolve_pool(&config.proxy.account_pools, requested.as_deref())?;
+        config.proxy.default_pool = pool.clone();
+        crate::modules::config::save_app_config(&config)?;
+        Ok(pool)
+    })
+    .await?;
+    // 立即作用于运行中的服务 (界面的配置监听随后会同步完整配置)
+    state.security.write().await.default_pool = pool.clone();
+    tracing::info!("[ManagerMcp] 默认账号池已切换为 {:?}", pool);
+    Ok(json!({ "default_pool": pool }))
+}
+
+/// 执行工具，返回 MCP `tools/call` 结果
+pub async fn call_tool(state: &AppState, tool_name: &str, arguments: &Value) -> Result<Value, String> {
+    let result = match tool_name {
+        "list_accounts" => list_accounts(state).await?,
+        "account_health" => account_health(arguments).await?,
+        "usage_stats" => usage_stats(arguments).await?,
+        "switch_pool" => switch_pool(state, arguments).await?,
+        other => return Err(format!("Unknown tool: {}", other)),
+    };
+    let text = serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?;
+    Ok(json!({
+        "content": [ { "type": "text", "text": text } ],
+        "structuredContent": result,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_pool() {
+        let pools = vec![AccountPool { name: "work".to_string(), ..Default::default() }];
+        assert_eq!(resolve_pool(&pools, Some(" work ")).unwrap(), Some("work".to_string()));
+        assert_eq!(resolve_pool(&pools, Some("")).unwrap(), None);
+        assert_eq!(resolve_pool(&pools, None).unwrap(), None);
+        assert!(resolve_pool(&pools, Some("missing")).unwrap_err().contains("work"));
+    }
+
+    #[test]
+    fn test_tool_specs_cover_handlers() {
+        let names: Vec<_> = tool_specs().iter().filter_map(|t| t["name"].as_str().map(str::to_string)).collect();
+        assert_eq!(names, ["list_accounts", "account_health", "usage_stats", "switch_pool"]);
+    }
+}
@@ -0,0 +1,130 @@
+// 调试功能的内存 / 磁盘上限
+//
+// 开启请求日志、对话归档等调试功能后，内存中的日志缓冲、日志库、归档目录、签名缓存与
+// 延迟统计会随流量持续增长。这里按 `MemoryLimitsConfig` 给各子系统设置上限，
+// 超出时淘汰最旧 (日志与归档) 或最久未使用 (缓存与统计序列) 的条目，
+// 淘汰次数通过 `/metrics` 的 `antigravity_evictions_total` 暴露。
+//
+// 与需求中各项的对应关系: 反代不缓存响应 (相同请求合并只保留进行中的请求)，唯一随流量增长的
+// 缓存是思维签名缓存，由 signature_cache_entries 限制；capture 模式即请求监控的日志缓冲、
+// 日志库与对话归档，分别由 log_buffer_mb、log_db_mb 与 transcripts_mb 限制；
+// 统计缓冲为延迟统计序列，由 latency_series 限制。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::proxy::SignatureCache;
+
+/// 有上限的子系统 (签名缓存的计数在 proxy-core 中)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// 内存中的请求日志
+    LogBuffer,
+    /// 请求日志库
+    LogDb,
+    /// 对话归档
+    Transcripts,
+    /// 延迟统计序列
+    LatencySeries,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 4] = [Self::LogBuffer, Self::LogDb, Self::Transcripts, Self::LatencySeries];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::LogBuffer => "log_buffer",
+            Self::LogDb => "log_db",
+            Self::Transcripts => "transcripts",
+            Self::LatencySeries => "latency_series",
+        }
+    }
+}
+
+static EVICTIONS: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// 累加淘汰次数
+pub fn record_evictions(subsystem: Subsystem, count: usize) {
+    if count > 0 {
+        EVICTIONS[subsystem as usize].fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+pub fn evictions(subsystem: Subsystem) -> u64 {
+    EVICTIONS[subsystem as usize].load(Ordering::Relaxed)
+}
+
+/// MB 转字节，0 表示不限制
+pub fn mb_to_bytes(mb: u64) -> u64 {
+    if mb == 0 {
+        u64::MAX
+    } else {
+        mb.saturating_mul(1024 * 1024)
+    }
+}
+
+/// 按条数与字节数限制队列 (新条目在队首)，从队尾淘汰最旧的条目并更新字节数，
+/// 至少保留最新的一条；返回淘汰条数
+pub fn trim_oldest<T>(
+    queue: &mut VecDeque<T>,
+    bytes: &mut u64,
+    max_len: usize,
+    max_bytes: u64,
+    size: impl Fn(&T) -> u64,
+) -> usize {
+    let mut evicted = 0;
+    while queue.len() > 1 && (queue.len() > max_len || *bytes > max_bytes) {
+        let Some(oldest) = queue.pop_back() else { break };
+        *bytes = bytes.saturating_sub(size(&oldest));
+        evicted += 1;
+    }
+    evicted
+}
+
+/// Prometheus 文本格式 (淘汰次数与内存日志缓冲的当前大小)
+pub fn render_prometheus(log_buffer_bytes: u64) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP antigravity_evictions_total Entries evicted to stay within memory / disk limits\n");
+    out.push_str("# TYPE antigravity_evictions_total counter\n");
+    for subsystem in Subsystem::ALL {
+        out.push_str(&format!("antigravity_evictions_total{{subsystem=\"{}\"}} {}\n", subsystem.as_str(), evictions(subsystem)));
+    }
+    out.push_str(&format!(
+        "antigravity_evictions_total{{subsystem=\"signature_cache\"}} {}\n",
+        SignatureCache::global().evictions()
+    ));
+    out.push_str("# HELP antigravity_log_buffer_bytes Size of the in-memory request log buffer\n");
+    out.push_str("# TYPE antigravity_log_buffer_bytes gauge\n");
+    out.push_str(&format!("antigravity_log_buffer_bytes {}\n", log_buffer_bytes));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_oldest_by_count_and_bytes() {
+        let mut queue: VecDeque<u64> = VecDeque::new();
+        let mut bytes = 0;
+        for size in [10, 20, 30, 40] {
+            queue.push_front(size);
+            bytes += size;
+        }
+        // 字节数超限: 淘汰最旧的 10 与 20
+        assert_eq!(trim_oldest(&mut queue, &mut bytes, 10, 70, |s| *s), 2);
+        assert_eq!(queue, [40, 30]);
+        assert_eq!(bytes, 70);
+
+        // 条数超限
+        assert_eq!(trim_oldest(&mut queue, &mut bytes, 1, u64::MAX, |s| *s), 1);
+        assert_eq!(queue, [40]);
+
+        // 单条超出字节上限时仍保留最新的一条
+        assert_eq!(trim_oldest(&mut queue, &mut bytes, 10, 1, |s| *s), 0);
+        assert_eq!(bytes, 40);
+
+        assert_eq!(mb_to_bytes(0), u64::MAX);
+        assert_eq!(mb_to_bytes(2), 2 * 1024 * 1024);
+    }
+}
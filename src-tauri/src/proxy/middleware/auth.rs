@@ -6,19 +6,36 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::proxy::{ProxyAuthMode, ProxySecurityConfig};
+use crate::modules::audit_log::{self, AuditEvent};
+use crate::proxy::{AccountPoolScope, AdminAccess, ProxyAuthMode, ProxySecurityConfig};
+
+/// 仅主 API Key 可用的管理接口 (会修改状态的调用与未授权的调用记入审计日志)
+fn is_admin_path(path: &str) -> bool {
+    path.starts_with("/dashboard/api/") || path == "/mcp/manager/mcp"
+}
+
+/// 只读请求 (管理面板的轮询等) 不记入审计日志
+fn is_read_only(method: &axum::http::Method) -> bool {
+    matches!(*method, axum::http::Method::GET | axum::http::Method::HEAD)
+}
 
 /// API Key 认证中间件
 pub async fn auth_middleware(
     State(security): State<Arc<RwLock<ProxySecurityConfig>>>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let method = request.method().clone();
     let path = request.uri().path().to_string();
+    let client_addr = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    let client_ip = client_addr.map(|ip| ip.to_string());
 
     // 过滤心跳和健康检查请求,避免日志噪音
     if !path.contains("event_logging") && path != "/healthz" {
@@ -33,16 +50,12 @@ pub async fn auth_middleware(
     }
 
     let security = security.read().await.clone();
-    let effective_mode = security.effective_auth_mode();
-
-    if matches!(effective_mode, ProxyAuthMode::Off) {
-        return Ok(next.run(request).await);
+    let mut effective_mode = security.effective_auth_mode();
+    // 经远程访问隧道转发的请求来自本机，隧道运行期间始终要求 API Key
+    if matches!(effective_mode, ProxyAuthMode::Off) && crate::modules::tunnel::is_active() {
+        effective_mode = ProxyAuthMode::AllExceptHealth;
     }
 
-    if matches!(effective_mode, ProxyAuthMode::AllExceptHealth) && path == "/healthz" {
-        return Ok(next.run(request).await);
-    }
-    
     // 从 header 中提取 API key
     let api_key = request
         .headers()
@@ -54,24 +67,92 @@ pub async fn auth_middleware(
                 .headers()
                 .get("x-api-key")
                 .and_then(|h| h.to_str().ok())
-        });
+        })
+        .map(|s| s.to_string());
+
+    // 绑定到账号池的 Key: 无论是否开启认证都限定可用账号范围 (其他请求使用默认账号池)
+    let pool = api_key
+        .as_deref()
+        .and_then(|k| security.pool_for_key(k))
+        .map(|p| p.to_string());
+    if let Some(scope) = security.scope_for_key(api_key.as_deref()) {
+        request.extensions_mut().insert(AccountPoolScope(scope.to_string()));
+    }
+    let is_master_key = api_key
+        .as_deref()
+        .is_some_and(|k| !security.api_key.is_empty() && k == security.api_key);
+    // 安全模式: 未开启鉴权时只接受本机请求 (监听局域网期间把鉴权改为 off 也不会暴露)
+    let remote_without_auth = matches!(effective_mode, ProxyAuthMode::Off)
+        && security.require_auth_for_lan
+        && client_addr.is_some_and(|ip| !ip.is_loopback());
+    let is_admin = is_master_key || (matches!(effective_mode, ProxyAuthMode::Off) && !remote_without_auth);
+    if is_admin {
+        request.extensions_mut().insert(AdminAccess);
+    }
+    if is_admin_path(&path) && !(is_admin && is_read_only(&method)) {
+        let event = AuditEvent::AdminCall {
+            ip: client_ip.clone(),
+            method: method.to_string(),
+            path: path.clone(),
+            authorized: is_admin,
+        };
+        if is_admin {
+            audit_log::record(event);
+        } else {
+            audit_log::record_client_event(client_ip.as_deref(), event);
+        }
+    }
+    let auth_failed = |reason: &'static str| {
+        audit_log::record_client_event(
+            client_ip.as_deref(),
+            AuditEvent::AuthFailed {
+                ip: client_ip.clone(),
+                method: method.to_string(),
+                path: path.clone(),
+                reason,
+            },
+        );
+        StatusCode::UNAUTHORIZED
+    };
+
+    if remote_without_auth {
+        return Err(auth_failed("remote_without_auth"));
+    }
+    if matches!(effective_mode, ProxyAuthMode::Off) {
+        return Ok(next.run(request).await);
+    }
+
+    if matches!(effective_mode, ProxyAuthMode::AllExceptHealth) && path == "/healthz" {
+        return Ok(next.run(request).await);
+    }
+
+    // 管理面板页面本身不含数据，Key 在页面中输入后用于请求数据接口；接口文档同样公开
+    if matches!(path.as_str(), "/dashboard" | "/openapi.json" | "/docs") || path.starts_with("/docs/") {
+        return Ok(next.run(request).await);
+    }
+
+    // 扫码配置签发的临时 Key (仅转发请求，不具备管理权限)
+    let is_temp_key = api_key
+        .as_deref()
+        .is_some_and(|k| crate::proxy::temp_keys::is_valid(k, chrono::Utc::now().timestamp()));
 
-    if security.api_key.is_empty() {
+    if security.api_key.is_empty() && security.pool_keys.is_empty() && !is_temp_key {
         tracing::error!("Proxy auth is enabled but api_key is empty; denying request");
-        return Err(StatusCode::UNAUTHORIZED);
+        return Err(auth_failed("no_key_configured"));
     }
 
     // Constant-time compare is unnecessary here, but keep strict equality and avoid leaking values.
-    let authorized = api_key.map(|k| k == security.api_key).unwrap_or(false);
+    let authorized = pool.is_some() || is_master_key || is_temp_key;
 
     if authorized {
         Ok(next.run(request).await)
     } else {
-        Err(StatusCode::UNAUTHORIZED)
+        Err(auth_failed(if api_key.is_some() { "invalid_key" } else { "missing_key" }))
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     // 移除未使用的 use super::*;
 
@@ -0,0 +1,146 @@
+// 相同请求合并 (In-flight Coalescing)
+//
+// 同时到达的完全相同的非流式请求 (相同路径、API Key 与请求体) 只向上游发起一次，
+// 结果复制给所有等待者，避免爱重试的客户端重复消耗配额。
+// 合并出来的响应带有 `X-Coalesced` 头，监控中间件据此不重复计入账号用量。
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::proxy::server::AppState;
+
+pub const COALESCED_HEADER: &str = "X-Coalesced";
+const MAX_BODY_SIZE: usize = 100 * 1024 * 1024;
+
+/// 首个请求完成后的响应快照
+struct CapturedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+type SharedResponse = Shared<BoxFuture<'static, Option<Arc<CapturedResponse>>>>;
+
+/// 请求摘要 -> 进行中的上游调用
+static IN_FLIGHT: Lazy<DashMap<String, SharedResponse>> = Lazy::new(DashMap::new);
+
+/// 可合并的生成接口 (Gemini 仅非流式 generateContent)
+fn is_generation_path(path: &str) -> bool {
+    matches!(path, "/v1/chat/completions" | "/v1/completions" | "/v1/responses" | "/v1/messages")
+        || (path.starts_with("/v1beta/models/") && path.ends_with(":generateContent"))
+}
+
+/// 计算合并键；流式请求或非生成接口返回 None
+fn coalesce_key(path: &str, api_key: &str, body: &[u8]) -> Option<String> {
+    if !is_generation_path(path) {
+        return None;
+    }
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    if json.get("stream").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    for part in [path.as_bytes(), api_key.as_bytes(), body] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn request_api_key(headers: &HeaderMap) -> String {
+    ["authorization", "x-api-key", "x-goog-api-key"]
+        .iter()
+        .find_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+        .unwrap_or("")
+        .to_string()
+}
+
+fn replay(captured: &CapturedResponse, coalesced: bool) -> Response {
+    let mut response = Response::new(Body::from(captured.body.clone()));
+    *response.status_mut() = captured.status;
+    *response.headers_mut() = captured.headers.clone();
+    if coalesced {
+        response.headers_mut().insert(COALESCED_HEADER, HeaderValue::from_static("1"));
+    }
+    response
+}
+
+pub async fn coalesce_middleware(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() != axum::http::Method::POST
+        || !is_generation_path(request.uri().path())
+        || !state.experimental.read().await.enable_request_coalescing
+    {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Failed to read body: {}", e)).into_response(),
+    };
+    let Some(key) = coalesce_key(parts.uri.path(), &request_api_key(&parts.headers), &bytes) else {
+        return next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    };
+
+    let (shared, leader) = match IN_FLIGHT.entry(key.clone()) {
+        dashmap::mapref::entry::Entry::Occupied(entry) => (entry.get().clone(), false),
+        dashmap::mapref::entry::Entry::Vacant(entry) => {
+            let request = Request::from_parts(parts, Body::from(bytes));
+            let cleanup_key = key.clone();
+            // 清理放在共享 future 内部：即使首个客户端提前断开，其余等待者完成后也会移除条目
+            let fut = async move {
+                let response = next.run(request).await;
+                let (parts, body) = response.into_parts();
+                let captured = axum::body::to_bytes(body, MAX_BODY_SIZE).await.ok().map(|body| {
+                    Arc::new(CapturedResponse { status: parts.status, headers: parts.headers, body })
+                });
+                IN_FLIGHT.remove(&cleanup_key);
+                captured
+            }
+            .boxed()
+            .shared();
+            entry.insert(fut.clone());
+            (fut, true)
+        }
+    };
+
+    if !leader {
+        tracing::info!("[Coalesce] Joined in-flight identical request {}", &key[..12]);
+    }
+    match shared.await {
+        Some(captured) => replay(&captured, !leader),
+        None => (StatusCode::BAD_GATEWAY, "Failed to read upstream response").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coalesce_key_only_for_identical_non_stream_generation() {
+        let body = br#"{"model":"gemini-2.5-flash","messages":[{"role":"user","content":"hi"}]}"#;
+        let key = coalesce_key("/v1/chat/completions", "Bearer sk-a", body).unwrap();
+        assert_eq!(coalesce_key("/v1/chat/completions", "Bearer sk-a", body), Some(key.clone()));
+        assert_ne!(coalesce_key("/v1/chat/completions", "Bearer sk-b", body), Some(key.clone()));
+        assert_ne!(coalesce_key("/v1/messages", "Bearer sk-a", body), Some(key));
+
+        let stream = br#"{"model":"m","stream":true}"#;
+        assert!(coalesce_key("/v1/messages", "k", stream).is_none());
+        assert!(coalesce_key("/v1/models/detect", "k", body).is_none());
+        assert!(coalesce_key("/v1beta/models/gemini-2.5-flash:streamGenerateContent", "k", body).is_none());
+        assert!(coalesce_key("/v1beta/models/gemini-2.5-flash:generateContent", "k", body).is_some());
+        assert!(coalesce_key("/v1/messages", "k", b"not json").is_none());
+    }
+}
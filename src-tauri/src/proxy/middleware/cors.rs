@@ -21,6 +21,7 @@ pub fn cors_layer() -> CorsLayer {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
 
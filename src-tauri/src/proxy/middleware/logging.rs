@@ -2,6 +2,7 @@
 // 直接使用 tower_http::trace::TraceLayer::new_for_http() 在路由中
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     #[test]
     fn test_logging_middleware() {
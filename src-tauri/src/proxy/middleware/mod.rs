@@ -1,6 +1,7 @@
 // Middleware 模块 - Axum 中间件
 
 pub mod auth;
+pub mod coalesce;
 pub mod cors;
 pub mod logging;
 pub mod monitor;
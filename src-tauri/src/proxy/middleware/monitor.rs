@@ -1,14 +1,15 @@
 use axum::{
     extract::{Request, State},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
     body::Body,
 };
 use std::time::Instant;
 use crate::proxy::server::AppState;
-use crate::proxy::monitor::ProxyRequestLog;
+use crate::proxy::monitor::{ProxyMonitor, ProxyRequestLog};
 use serde_json::Value;
 use futures::StreamExt;
+use tracing::Instrument;
 
 const MAX_REQUEST_LOG_SIZE: usize = 100 * 1024 * 1024; // 100MB
 const MAX_RESPONSE_LOG_SIZE: usize = 100 * 1024 * 1024; // 100MB for image responses
@@ -18,17 +19,24 @@ pub async fn monitor_middleware(
     request: Request,
     next: Next,
 ) -> Response {
-    if !state.monitor.is_enabled() {
-        return next.run(request).await;
-    }
+    // 请求日志可关闭，但账号用量统计始终记录
+    let logging = state.monitor.is_enabled();
+    // 检查器与对话归档需要请求 / 响应内容
+    let inspecting = state.monitor.inspector.is_enabled();
+    let archiving = state.monitor.transcripts.is_enabled();
+    // 请求钩子的 transform 模式需要请求体
+    let transforming = state.monitor.hooks.transforms();
 
     let start = Instant::now();
+    let key_id = crate::modules::token_stats::KeyId::from_request(request.headers(), request.uri().query());
     let method = request.method().to_string();
     let uri = request.uri().to_string();
     
-    if uri.contains("event_logging") {
+    if uri.contains("event_logging") || uri == "/metrics" || uri.starts_with("/v1/usage/") || uri.starts_with("/dashboard") || uri == "/openapi.json" || uri == "/docs" || uri.starts_with("/docs/") {
         return next.run(request).await;
     }
+    // 进行中请求计数，流式响应在流结束后才释放
+    let in_flight = state.monitor.in_flight.enter();
     
     let mut model = if uri.contains("/v1beta/models/") {
         uri.split("/v1beta/models/")
@@ -40,7 +48,8 @@ pub async fn monitor_middleware(
     };
 
     let request_body_str;
-    let request = if method == "POST" {
+    let mut request_bytes = None;
+    let request = if (logging || inspecting || archiving || transforming) && method == "POST" {
         let (parts, body) = request.into_parts();
         match axum::body::to_bytes(body, MAX_REQUEST_LOG_SIZE).await {
             Ok(bytes) => {
@@ -54,6 +63,7 @@ pub async fn monitor_middleware(
                 } else {
                     Some("[Binary Request Data]".to_string())
                 };
+                request_bytes = Some(bytes.clone());
                 Request::from_parts(parts, Body::from(bytes))
             }
             Err(_) => {
@@ -65,11 +75,38 @@ pub async fn monitor_middleware(
         request_body_str = None;
         request
     };
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let request = if state.monitor.hooks.has_request_hook() {
+        let summary = crate::proxy::hooks::RequestSummary::new(&request_id, &method, &uri, model.as_deref(), &key_id);
+        match state.monitor.hooks.on_request(summary, request_bytes.as_deref()).await {
+            Ok(Some(body)) => {
+                let (mut parts, _) = request.into_parts();
+                parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(body))
+            }
+            Ok(None) => request,
+            Err(reason) => {
+                tracing::info!("[Hooks] 请求被 on_request 钩子拒绝: {}", reason);
+                return (axum::http::StatusCode::FORBIDDEN, format!("Rejected by request hook: {}", reason)).into_response();
+            }
+        }
+    } else {
+        request
+    };
     
-    let response = next.run(request).await;
+    let span = tracing::info_span!(
+        "proxy.request",
+        otel.kind = "server",
+        http.method = %method,
+        http.target = %request.uri().path(),
+        http.status_code = tracing::field::Empty,
+    );
+    let response = next.run(request).instrument(span.clone()).await;
     
     let duration = start.elapsed().as_millis() as u64;
     let status = response.status().as_u16();
+    span.record("http.status_code", status);
     
     let content_type = response.headers().get("content-type")
         .and_then(|v| v.to_str().ok())
@@ -90,9 +127,18 @@ pub async fn monitor_middleware(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
 
+    // 合并请求复用了其他请求的上游结果，不重复计入账号用量
+    let count_usage = !response
+        .headers()
+        .contains_key(crate::proxy::middleware::coalesce::COALESCED_HEADER);
+
+    let project_id = account_email
+        .as_deref()
+        .and_then(|email| state.token_manager.last_project_for(email));
+
     let monitor = state.monitor.clone();
     let mut log = ProxyRequestLog {
-        id: uuid::Uuid::new_v4().to_string(),
+        id: request_id,
         timestamp: chrono::Utc::now().timestamp_millis(),
         method,
         url: uri,
@@ -101,6 +147,7 @@ pub async fn monitor_middleware(
         model,
         mapped_model,
         account_email,
+        project_id,
         error: None,
         request_body: request_body_str,
         response_body: None,
@@ -112,12 +159,34 @@ pub async fn monitor_middleware(
         log.response_body = Some("[Stream Data]".to_string());
         let (parts, body) = response.into_parts();
         let mut stream = body.into_data_stream();
-        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        // 有界转发: 客户端读取缓慢时暂停读取上游，避免长时间生成的数据在内存中堆积
+        let (tx, rx) = crate::proxy::upstream::backpressure::default_channel::<axum::Error>();
         
         tokio::spawn(async move {
+            let _in_flight = in_flight;
             let mut last_few_bytes = Vec::new();
+            // 累积响应文本增量：检查器只需要预览长度，对话归档需要完整输出
+            let text_limit = if archiving {
+                crate::proxy::transcripts::MAX_OUTPUT_CHARS
+            } else if inspecting {
+                monitor.inspector.max_content_chars()
+            } else {
+                0
+            };
+            let mut output = String::new();
+            let mut output_chars = 0;
+            let mut line_buf = Vec::new();
             while let Some(chunk_res) = stream.next().await {
                 if let Ok(chunk) = chunk_res {
+                    if output_chars < text_limit {
+                        line_buf.extend_from_slice(&chunk);
+                        for line in take_lines(&mut line_buf) {
+                            if let Some(text) = crate::proxy::inspector::stream_delta_text(&line) {
+                                output_chars += text.chars().count();
+                                output.push_str(&text);
+                            }
+                        }
+                    }
                     if chunk.len() > 8192 {
                         last_few_bytes = chunk.slice(chunk.len()-8192..).to_vec();
                     } else {
@@ -126,7 +195,7 @@ pub async fn monitor_middleware(
                             last_few_bytes.drain(0..last_few_bytes.len()-8192);
                         }
                     }
-                    let _ = tx.send(Ok::<_, axum::Error>(chunk)).await;
+                    let _ = tx.send(Ok(chunk)).await;
                 } else if let Err(e) = chunk_res {
                     let _ = tx.send(Err(axum::Error::new(e))).await;
                 }
@@ -166,16 +235,22 @@ pub async fn monitor_middleware(
             if log.status >= 400 {
                 log.error = Some("Stream Error or Failed".to_string());
             }
-            monitor.log_request(log).await;
+            monitor.inspect(&log, Some(&output));
+            monitor.transcripts.archive(&log, Some(&output), &key_id);
+            finish(&monitor, log, logging, count_usage, start.elapsed().as_millis() as u64, &key_id).await;
         });
 
-        Response::from_parts(parts, Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)))
+        Response::from_parts(parts, Body::from_stream(rx))
     } else if content_type.contains("application/json") || content_type.contains("text/") {
         let (parts, body) = response.into_parts();
         match axum::body::to_bytes(body, MAX_RESPONSE_LOG_SIZE).await {
             Ok(bytes) => {
+                let mut response_text = None;
                 if let Ok(s) = std::str::from_utf8(&bytes) {
-                    if let Ok(json) = serde_json::from_str::<Value>(&s) {
+                    if let Ok(json) = serde_json::from_str::<Value>(s) {
+                        if inspecting || archiving {
+                            response_text = crate::proxy::inspector::response_text(&json);
+                        }
                         // 支持 OpenAI "usage" 或 Gemini "usageMetadata"
                         if let Some(usage) = json.get("usage").or(json.get("usageMetadata")) {
                             log.input_tokens = usage.get("prompt_tokens")
@@ -197,26 +272,88 @@ pub async fn monitor_middleware(
                             }
                         }
                     }
-                    log.response_body = Some(s.to_string());
+                    if logging {
+                        log.response_body = Some(s.to_string());
+                    }
                 } else {
                     log.response_body = Some("[Binary Response Data]".to_string());
                 }
                 
                 if log.status >= 400 {
-                    log.error = log.response_body.clone();
+                    log.error = std::str::from_utf8(&bytes).ok().map(|s| s.to_string());
                 }
-                monitor.log_request(log).await;
+                monitor.inspect(&log, response_text.as_deref());
+                monitor.transcripts.archive(&log, response_text.as_deref(), &key_id);
+                finish(&monitor, log, logging, count_usage, start.elapsed().as_millis() as u64, &key_id).await;
                 Response::from_parts(parts, Body::from(bytes))
             }
             Err(_) => {
                 log.response_body = Some("[Response too large (>100MB)]".to_string());
-                monitor.log_request(log).await;
+                finish(&monitor, log, logging, count_usage, duration, &key_id).await;
                 Response::from_parts(parts, Body::empty())
             }
         }
     } else {
         log.response_body = Some(format!("[{}]", content_type));
-        monitor.log_request(log).await;
+        monitor.inspect(&log, None);
+        finish(&monitor, log, logging, count_usage, duration, &key_id).await;
         response
     }
 }
+
+/// 取出缓冲区中的完整行，未结束的行留在缓冲区
+///
+/// 按字节切分后再解码，跨分块的多字节字符不会被拆成替换字符
+fn take_lines(buf: &mut Vec<u8>) -> Vec<String> {
+    let Some(last) = buf.iter().rposition(|&b| b == b'\n') else { return Vec::new() };
+    let complete: Vec<u8> = buf.drain(..=last).collect();
+    complete
+        .split_inclusive(|&b| b == b'\n')
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect()
+}
+
+/// `total_ms` 为完整响应耗时；`log.duration` 是拿到响应头的时间 (流式响应即首字节时间)
+async fn finish(
+    monitor: &ProxyMonitor,
+    log: ProxyRequestLog,
+    logging: bool,
+    count_usage: bool,
+    total_ms: u64,
+    key_id: &crate::modules::token_stats::KeyId,
+) {
+    if count_usage {
+        monitor.record_account_usage(&log);
+        monitor.record_token_usage(&log, key_id);
+        monitor.check_anomalies(&log, key_id);
+    }
+    monitor.live.observe(&log, chrono::Utc::now().timestamp_millis());
+    monitor.check_alerts(log.status);
+    crate::modules::statsd::record_request(&log, total_ms);
+    monitor.hooks.on_complete(&log, total_ms, key_id);
+    if let Some(account) = log.account_email.as_deref() {
+        let model = log.mapped_model.as_deref().or(log.model.as_deref()).unwrap_or("unknown");
+        monitor.latency.record(model, account, log.duration, total_ms);
+    }
+    if logging {
+        monitor.log_request(log).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_lines_keeps_multibyte_chars_split_across_chunks() {
+        let data = "data: {\"text\":\"你好\"}\n\ndata: [DONE]\n".as_bytes();
+        // 在 "你" 的 UTF-8 编码中间切开
+        let split = data.iter().position(|&b| b >= 0x80).unwrap() + 1;
+        let mut buf = data[..split].to_vec();
+        assert!(take_lines(&mut buf).is_empty());
+        buf.extend_from_slice(&data[split..]);
+        let lines = take_lines(&mut buf);
+        assert_eq!(lines, ["data: {\"text\":\"你好\"}\n", "\n", "data: [DONE]\n"]);
+        assert!(buf.is_empty());
+    }
+}
@@ -2,26 +2,42 @@
 
 // 现有模块 (保留)
 pub mod config;
+pub mod env_overrides;     // 环境变量覆盖配置
+pub mod secret_refs;       // 配置中的凭证引用 (env: / keychain:)
+pub mod config_validation; // 配置校验
+pub mod config_diff;       // 配置变更预览
+pub mod client_snippets;   // 客户端配置片段
 pub mod token_manager;
 pub mod project_resolver;
 pub mod server;
+pub mod routes;            // 路由表 (路由注册与 OpenAPI 文档共用)
 pub mod security;
+pub mod temp_keys;         // 扫码配置用的临时 API Key
 
 // 新架构模块
-pub mod mappers;           // 协议转换器
 pub mod handlers;          // API 端点处理器
 pub mod middleware;        // Axum 中间件
-pub mod upstream;          // 上游客户端
-pub mod common;            // 公共工具
 pub mod providers;         // Extra upstream providers (z.ai, etc.)
 pub mod zai_vision_mcp;    // Built-in Vision MCP server state
 pub mod zai_vision_tools;  // Built-in Vision MCP tools (z.ai vision API)
+pub mod manager_mcp;       // 管理用 MCP 工具 (账号、健康检查、用量、账号池)
 pub mod monitor;           // 监控
-pub mod rate_limit;        // 限流跟踪
-pub mod sticky_config;     // 粘性调度配置
-pub mod session_manager;   // 会话指纹管理
+pub mod latency;           // 延迟直方图
+pub mod live_stats;        // 实时统计事件
+pub mod load;              // 负载仪表 (进行中 / 排队)
+pub mod alerts;            // 错误率告警
+pub mod anomaly;           // 用量异常检测
+pub mod inspector;         // 实时请求检查器
+pub mod transcripts;       // 对话记录归档
+pub mod memory_limits;     // 日志 / 归档 / 缓存的内存与磁盘上限
+pub mod hooks;             // 请求钩子脚本
+pub mod self_test;         // 端到端自检
+pub mod benchmark;         // 内置压测
 pub mod audio;             // 音频处理模块 (PR #311)
-pub mod signature_cache;   // Signature Cache (v3.3.16)
+
+// 协议转换、模型映射、会话指纹、签名缓存、限流跟踪、调度配置与上游客户端位于 antigravity-proxy-core，
+// 原路径保持可用
+pub use antigravity_proxy_core::{common, mappers, rate_limit, session_manager, signature_cache, sticky_config, upstream};
 
 
 pub use config::ProxyConfig;
@@ -30,7 +46,7 @@ pub use config::ZaiConfig;
 pub use config::ZaiDispatchMode;
 pub use token_manager::TokenManager;
 pub use server::AxumServer;
-pub use security::ProxySecurityConfig;
+pub use security::{AccountPoolScope, AdminAccess, ProxySecurityConfig};
 pub use signature_cache::SignatureCache;
 
 #[cfg(test)]
@@ -1,8 +1,12 @@
 use serde::{Serialize, Deserialize};
 use std::collections::VecDeque;
 use tokio::sync::RwLock;
-use tauri::Emitter;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::proxy::memory_limits::{self, Subsystem};
+
+/// 每保存多少条日志检查一次日志库容量 (首次保存时也会检查)
+const LOG_DB_TRIM_INTERVAL: u64 = 100;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyRequestLog {
@@ -15,6 +19,8 @@ pub struct ProxyRequestLog {
     pub model: Option<String>,        // 客户端请求的模型名
     pub mapped_model: Option<String>, // 实际路由后使用的模型名
     pub account_email: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,   // 本次请求使用的项目
     pub error: Option<String>,
     pub request_body: Option<String>,
     pub response_body: Option<String>,
@@ -22,6 +28,14 @@ pub struct ProxyRequestLog {
     pub output_tokens: Option<u32>,
 }
 
+impl ProxyRequestLog {
+    /// 在内存中大致占用的字节数
+    fn memory_size(&self) -> u64 {
+        let text: usize = [&self.request_body, &self.response_body, &self.error].into_iter().flatten().map(String::len).sum();
+        (std::mem::size_of::<Self>() + self.id.len() + self.url.len() + text) as u64
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProxyStats {
     pub total_requests: u64,
@@ -32,17 +46,44 @@ pub struct ProxyStats {
 pub struct ProxyMonitor {
     pub logs: RwLock<VecDeque<ProxyRequestLog>>,
     pub stats: RwLock<ProxyStats>,
+    /// 按模型 / 账号的延迟直方图
+    pub latency: crate::proxy::latency::LatencyRegistry,
+    /// 实时统计 (节流后推送给前端)
+    pub live: std::sync::Arc<crate::proxy::live_stats::LiveStats>,
+    /// 进行中请求数 (负载仪表)
+    pub in_flight: crate::proxy::load::InFlightCounter,
+    /// 错误率 / 连续 429 告警
+    pub alerts: crate::proxy::alerts::AlertMonitor,
+    /// 用量异常检测
+    pub anomaly: crate::proxy::anomaly::AnomalyDetector,
+    /// 实时请求检查器 (默认关闭)
+    pub inspector: crate::proxy::inspector::Inspector,
+    /// 对话记录归档
+    pub transcripts: crate::proxy::transcripts::TranscriptArchive,
+    /// 请求钩子脚本
+    pub hooks: crate::proxy::hooks::RequestHooks,
     pub max_logs: usize,
     pub enabled: AtomicBool,
-    app_handle: Option<tauri::AppHandle>,
+    /// 内存日志的当前字节数 (在 logs 写锁内更新)
+    log_bytes: AtomicU64,
+    /// 内存日志 / 日志库的字节上限 (见 memory_limits)
+    log_buffer_limit: AtomicU64,
+    log_db_limit: AtomicU64,
+    saved_logs: AtomicU64,
+    app_handle: Option<crate::utils::app::AppHandle>,
+    /// 请求摘要广播 (无界面场景下的事件订阅，如 JSON-RPC 模式)
+    request_events: tokio::sync::broadcast::Sender<ProxyRequestLog>,
 }
 
 impl ProxyMonitor {
-    pub fn new(max_logs: usize, app_handle: Option<tauri::AppHandle>) -> Self {
+    pub fn new(max_logs: usize, app_handle: Option<crate::utils::app::AppHandle>) -> Self {
         // Initialize DB
         if let Err(e) = crate::modules::proxy_db::init_db() {
             tracing::error!("Failed to initialize proxy DB: {}", e);
         }
+        if let Err(e) = crate::modules::token_stats::init_db() {
+            tracing::error!("Failed to initialize token stats DB: {}", e);
+        }
 
         // Auto cleanup old logs (keep last 30 days)
         tokio::spawn(async {
@@ -58,12 +99,30 @@ impl ProxyMonitor {
             }
         });
 
+        let live = std::sync::Arc::new(crate::proxy::live_stats::LiveStats::new());
+        if let Some(app) = &app_handle {
+            crate::proxy::live_stats::start_emitter(std::sync::Arc::downgrade(&live), app.clone());
+        }
+
         Self {
             logs: RwLock::new(VecDeque::with_capacity(max_logs)),
             stats: RwLock::new(ProxyStats::default()),
+            latency: crate::proxy::latency::LatencyRegistry::new(),
+            live,
+            in_flight: crate::proxy::load::InFlightCounter::new(),
+            alerts: crate::proxy::alerts::AlertMonitor::new(),
+            anomaly: crate::proxy::anomaly::AnomalyDetector::new(),
+            inspector: crate::proxy::inspector::Inspector::new(),
+            transcripts: crate::proxy::transcripts::TranscriptArchive::new(),
+            hooks: crate::proxy::hooks::RequestHooks::new(),
             max_logs,
             enabled: AtomicBool::new(false), // Default to disabled
+            log_bytes: AtomicU64::new(0),
+            log_buffer_limit: AtomicU64::new(u64::MAX),
+            log_db_limit: AtomicU64::new(u64::MAX),
+            saved_logs: AtomicU64::new(0),
             app_handle,
+            request_events: tokio::sync::broadcast::channel(256).0,
         }
     }
 
@@ -75,10 +134,103 @@ impl ProxyMonitor {
         self.enabled.load(Ordering::Relaxed)
     }
 
-    pub async fn log_request(&self, log: ProxyRequestLog) {
+    /// 应用内存 / 磁盘上限 (日志、对话归档、延迟统计与签名缓存)
+    pub fn set_memory_limits(&self, limits: &crate::proxy::config::MemoryLimitsConfig) {
+        self.log_buffer_limit.store(memory_limits::mb_to_bytes(limits.log_buffer_mb), Ordering::Relaxed);
+        self.log_db_limit.store(memory_limits::mb_to_bytes(limits.log_db_mb), Ordering::Relaxed);
+        self.transcripts.set_max_bytes(memory_limits::mb_to_bytes(limits.transcripts_mb));
+        self.latency.set_max_series(limits.latency_series);
+        crate::proxy::SignatureCache::global().set_max_entries(limits.signature_cache_entries);
+    }
+
+    /// 内存日志的当前字节数
+    pub fn log_buffer_bytes(&self) -> u64 {
+        self.log_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 持久化单次请求的 Token 用量 (按 Key / 模型 / 账号)
+    pub fn record_token_usage(&self, log: &ProxyRequestLog, key: &crate::modules::token_stats::KeyId) {
+        let Some(email) = log.account_email.clone() else { return };
+        let record = crate::modules::token_stats::TokenUsageRecord {
+            timestamp: log.timestamp / 1000,
+            key_id: key.id.clone(),
+            key_hint: key.hint.clone(),
+            model: log.mapped_model.clone().or_else(|| log.model.clone()).unwrap_or_else(|| "unknown".to_string()),
+            requested_model: log.model.clone().unwrap_or_default(),
+            account_email: email,
+            input_tokens: log.input_tokens.unwrap_or(0),
+            output_tokens: log.output_tokens.unwrap_or(0),
+            is_error: log.status >= 400,
+        };
+        crate::utils::blocking::spawn(move || {
+            if let Err(e) = crate::modules::token_stats::record(&record) {
+                tracing::error!("Failed to record token usage: {}", e);
+            }
+        });
+    }
+
+    /// 检查器开启时推送脱敏后的请求摘要
+    pub fn inspect(&self, log: &ProxyRequestLog, response_text: Option<&str>) {
+        if !self.inspector.is_enabled() {
+            return;
+        }
+        if let Some(app) = &self.app_handle {
+            let event = self.inspector.build_event(log, response_text);
+            crate::utils::app::emit(app, crate::proxy::inspector::INSPECTOR_EVENT, &event);
+        }
+    }
+
+    /// 按告警阈值检查请求结果，触发时推送事件 / Webhook
+    pub fn check_alerts(&self, status: u16) {
+        for alert in self.alerts.observe(status, chrono::Utc::now().timestamp_millis()) {
+            crate::proxy::alerts::notify(alert, self.app_handle.as_ref(), self.alerts.webhook_url());
+        }
+    }
+
+    /// 反代可从本机以外访问且无需鉴权时推送安全告警并弹出系统通知
+    pub fn warn_if_exposed(&self, config: &crate::proxy::ProxyConfig) {
+        let Some(alert) = crate::proxy::alerts::exposure_alert(config, chrono::Utc::now().timestamp_millis()) else { return };
+        crate::proxy::alerts::notify(alert, self.app_handle.as_ref(), self.alerts.webhook_url());
+        crate::modules::notifications::unauthenticated_exposure(&format!("{}:{}", config.get_bind_address(), config.port));
+    }
+
+    /// 检查用量异常 (Token 突增 / 单个 Key 占据大部分流量)
+    pub fn check_anomalies(&self, log: &ProxyRequestLog, key: &crate::modules::token_stats::KeyId) {
+        let tokens = log.input_tokens.unwrap_or(0) as u64 + log.output_tokens.unwrap_or(0) as u64;
+        for alert in self.anomaly.observe(&key.id, tokens, chrono::Utc::now().timestamp_millis()) {
+            crate::proxy::alerts::notify(alert, self.app_handle.as_ref(), self.alerts.webhook_url());
+        }
+    }
+
+    /// 累加账号用量 (不受日志开关影响)
+    pub fn record_account_usage(&self, log: &ProxyRequestLog) {
+        let Some(email) = log.account_email.clone() else { return };
+        let is_error = log.status >= 400;
+        let timestamp = log.timestamp / 1000;
+        let input_tokens = log.input_tokens.unwrap_or(0);
+        let output_tokens = log.output_tokens.unwrap_or(0);
+        let error_class = crate::modules::proxy_db::ErrorClass::classify(log.status, log.error.as_deref());
+        crate::utils::blocking::spawn(move || {
+            if let Err(e) = crate::modules::proxy_db::record_account_usage(&email, timestamp, is_error, input_tokens, output_tokens) {
+                tracing::error!("Failed to record account usage: {}", e);
+            }
+            if let Some(class) = error_class {
+                if let Err(e) = crate::modules::proxy_db::record_account_error(&email, timestamp, class) {
+                    tracing::error!("Failed to record account error: {}", e);
+                }
+            }
+        });
+    }
+
+    pub async fn log_request(&self, mut log: ProxyRequestLog) {
         if !self.is_enabled() {
             return;
         }
+        // 记录会写入内存、数据库并推送到前端，先去掉其中的凭证
+        crate::utils::redact::redact_in_place(&mut log.url);
+        crate::utils::redact::redact_option(&mut log.error);
+        crate::utils::redact::redact_option(&mut log.request_body);
+        crate::utils::redact::redact_option(&mut log.response_body);
         tracing::info!("[Monitor] Logging request: {} {}", log.method, log.url);
         // Update stats
         {
@@ -91,25 +243,40 @@ impl ProxyMonitor {
             }
         }
 
-        // Add log to memory
+        // Add log to memory (超出条数或字节上限时淘汰最旧的日志)
         {
             let mut logs = self.logs.write().await;
-            if logs.len() >= self.max_logs {
-                logs.pop_back();
-            }
+            let mut bytes = self.log_bytes.load(Ordering::Relaxed) + log.memory_size();
             logs.push_front(log.clone());
+            let evicted = memory_limits::trim_oldest(
+                &mut logs,
+                &mut bytes,
+                self.max_logs,
+                self.log_buffer_limit.load(Ordering::Relaxed),
+                ProxyRequestLog::memory_size,
+            );
+            self.log_bytes.store(bytes, Ordering::Relaxed);
+            memory_limits::record_evictions(Subsystem::LogBuffer, evicted);
         }
 
-        // Save to DB
+        // Save to DB (定期按容量上限清理最旧的日志)，在阻塞线程池中执行
         let log_to_save = log.clone();
-        tokio::spawn(async move {
+        let trim_db = self.saved_logs.fetch_add(1, Ordering::Relaxed).is_multiple_of(LOG_DB_TRIM_INTERVAL);
+        let db_limit = self.log_db_limit.load(Ordering::Relaxed);
+        crate::utils::blocking::spawn(move || {
             if let Err(e) = crate::modules::proxy_db::save_log(&log_to_save) {
                 tracing::error!("Failed to save proxy log to DB: {}", e);
             }
+            if trim_db && db_limit != u64::MAX {
+                match crate::modules::proxy_db::trim_logs_to_size(db_limit) {
+                    Ok(deleted) => memory_limits::record_evictions(Subsystem::LogDb, deleted),
+                    Err(e) => tracing::error!("Failed to trim proxy log DB: {}", e),
+                }
+            }
         });
 
         // Emit event (send summary only, without body to reduce memory)
-        if let Some(app) = &self.app_handle {
+        if self.app_handle.is_some() || self.request_events.receiver_count() > 0 {
             let log_summary = ProxyRequestLog {
                 id: log.id.clone(),
                 timestamp: log.timestamp,
@@ -120,16 +287,25 @@ impl ProxyMonitor {
                 model: log.model.clone(),
                 mapped_model: log.mapped_model.clone(),
                 account_email: log.account_email.clone(),
+                project_id: log.project_id.clone(),
                 error: log.error.clone(),
                 request_body: None,  // Don't send body in event
                 response_body: None, // Don't send body in event
                 input_tokens: log.input_tokens,
                 output_tokens: log.output_tokens,
             };
-            let _ = app.emit("proxy://request", &log_summary);
+            if let Some(app) = &self.app_handle {
+                crate::utils::app::emit(app, "proxy://request", &log_summary);
+            }
+            let _ = self.request_events.send(log_summary);
         }
     }
 
+    /// 订阅请求摘要 (不含请求 / 响应正文)
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<ProxyRequestLog> {
+        self.request_events.subscribe()
+    }
+
     pub async fn get_logs(&self, limit: usize) -> Vec<ProxyRequestLog> {
         // Try to get from DB first for true history
         match crate::modules::proxy_db::get_logs(limit) {
@@ -156,8 +332,10 @@ impl ProxyMonitor {
     pub async fn clear(&self) {
         let mut logs = self.logs.write().await;
         logs.clear();
+        self.log_bytes.store(0, Ordering::Relaxed);
         let mut stats = self.stats.write().await;
         *stats = ProxyStats::default();
+        self.latency.clear();
 
         if let Err(e) = crate::modules::proxy_db::clear_logs() {
             tracing::error!("Failed to clear logs in DB: {}", e);
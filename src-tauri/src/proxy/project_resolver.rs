@@ -1,48 +1,262 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use serde_json::Value;
 
-/// 使用 Antigravity 的 loadCodeAssist API 获取 project_id
-/// 这是获取 cloudaicompanionProject 的正确方式
-pub async fn fetch_project_id(access_token: &str) -> Result<String, String> {
-    let url = "https://cloudcode-pa.googleapis.com/v1internal:loadCodeAssist";
-    
-    let request_body = serde_json::json!({
-        "metadata": {
-            "ideType": "ANTIGRAVITY"
+use crate::modules::i18n::tr;
+
+/// 已解析 project_id 的有效期 (秒)，过期后在下次请求时重新解析
+const PROJECT_CACHE_TTL_SECS: i64 = 6 * 3600;
+
+struct CachedProject {
+    project_id: String,
+    resolved_at: i64,
+}
+
+/// account_id -> 已解析的 project_id
+static PROJECT_CACHE: Lazy<DashMap<String, CachedProject>> = Lazy::new(DashMap::new);
+
+fn lookup_at(account_id: &str, now: i64) -> Option<String> {
+    PROJECT_CACHE
+        .get(account_id)
+        .filter(|c| now - c.resolved_at < PROJECT_CACHE_TTL_SECS)
+        .map(|c| c.project_id.clone())
+}
+
+fn remember_at(account_id: &str, project_id: &str, now: i64) {
+    PROJECT_CACHE.insert(
+        account_id.to_string(),
+        CachedProject { project_id: project_id.to_string(), resolved_at: now },
+    );
+}
+
+/// 记录账号的 project_id (视为刚刚解析)
+pub fn remember_project_id(account_id: &str, project_id: &str) {
+    remember_at(account_id, project_id, chrono::Utc::now().timestamp());
+}
+
+/// 使账号的 project_id 缓存失效，下次请求时重新解析
+pub fn invalidate_project_id(account_id: &str) {
+    PROJECT_CACHE.remove(account_id);
+}
+
+/// 解析账号的 project_id
+///
+/// - 缓存未过期: 直接返回，不发起请求
+/// - 尚无缓存但账号已保存 project_id (如刚启动): 直接采用并开始计时
+/// - 缓存已过期或无 project_id: 重新调用 loadCodeAssist；失败时回退到已保存的值
+///
+/// 返回 (project_id, 是否与已保存的值不同)
+pub async fn resolve_project_id(
+    account_id: &str,
+    access_token: &str,
+    stored: Option<&str>,
+) -> Result<(String, bool), String> {
+    let now = chrono::Utc::now().timestamp();
+    if let Some(pid) = lookup_at(account_id, now) {
+        return Ok((pid, false));
+    }
+    if let Some(pid) = stored.filter(|_| !PROJECT_CACHE.contains_key(account_id)) {
+        remember_at(account_id, pid, now);
+        return Ok((pid.to_string(), false));
+    }
+
+    match fetch_project_id(access_token).await {
+        Ok((pid, outcome)) => {
+            record_outcome(account_id, &pid, &outcome);
+            remember_at(account_id, &pid, now);
+            let changed = stored != Some(pid.as_str());
+            Ok((pid, changed))
         }
-    });
-    
-    let client = crate::utils::http::create_client(30);
+        Err(e) => match stored {
+            Some(pid) => {
+                tracing::warn!("重新解析 project_id 失败，继续使用已保存的值 {}: {}", pid, e);
+                Ok((pid.to_string(), false))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+const LOAD_CODE_ASSIST_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:loadCodeAssist";
+const ONBOARD_USER_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:onboardUser";
+/// onboardUser 为长时操作，轮询次数与间隔
+const ONBOARD_MAX_POLLS: usize = 5;
+const ONBOARD_POLL_INTERVAL_SECS: u64 = 2;
+
+/// project_id 的解析结果来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectOutcome {
+    /// loadCodeAssist 直接返回了项目
+    Existing,
+    /// 账号原本没有项目，已通过 onboardUser 自动创建
+    Created,
+    /// 自动创建失败，使用随机生成的 project_id 兜底
+    CreateFailed(String),
+    /// 未启用自动创建，使用随机生成的 project_id 兜底
+    Fallback,
+}
+
+/// 将自动创建项目的结果记录到账号事件
+pub fn record_outcome(account_id: &str, project_id: &str, outcome: &ProjectOutcome) {
+    let (kind, detail) = match outcome {
+        ProjectOutcome::Created => ("project_created", project_id.to_string()),
+        ProjectOutcome::CreateFailed(e) => ("project_create_failed", e.clone()),
+        _ => return,
+    };
+    if let Ok(data_dir) = crate::modules::account::get_data_dir() {
+        crate::modules::account_store::record_event(&data_dir, account_id, kind, Some(&detail));
+    }
+}
+
+async fn post_code_assist(url: &str, access_token: &str, body: &Value) -> Result<Value, String> {
+    let client = crate::utils::http::create_upstream_client(30);
     let response = client
         .post(url)
         .bearer_auth(access_token)
         .header("Host", "cloudcode-pa.googleapis.com")
         .header("User-Agent", "antigravity/1.11.9 windows/amd64")
         .header("Content-Type", "application/json")
-        .json(&request_body)
+        .json(body)
         .send()
         .await
-        .map_err(|e| format!("loadCodeAssist 请求失败: {}", e))?;
-    
+        .map_err(|e| format!("{} 请求失败: {}", url.rsplit(':').next().unwrap_or(url), e))?;
+
     if !response.status().is_success() {
         let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("loadCodeAssist 返回错误 {}: {}", status, body));
+        let text = response.text().await.unwrap_or_default();
+        return Err(tr("backend.errors.project.api_error", &[("method", url.rsplit(':').next().unwrap_or(url)), ("status", &status.to_string()), ("body", &text)]));
     }
-    
-    let data: Value = response.json()
-        .await
-        .map_err(|e| format!("解析响应失败: {}", e))?;
-    
+
+    response.json().await.map_err(|e| format!("解析响应失败: {}", e))
+}
+
+/// loadCodeAssist 返回的默认订阅层级 (onboardUser 所需的 tierId)
+///
+/// 需要用户自行提供 GCP 项目的层级无法自动创建，返回错误
+fn default_tier_id(load_response: &Value) -> Result<String, String> {
+    let tiers = load_response.get("allowedTiers").and_then(|t| t.as_array());
+    let tier = tiers
+        .and_then(|t| t.iter().find(|tier| tier.get("isDefault").and_then(|d| d.as_bool()).unwrap_or(false)))
+        .or_else(|| tiers.and_then(|t| t.first()));
+    let Some(tier) = tier else {
+        return Ok("free-tier".to_string());
+    };
+    if tier.get("userDefinedCloudaicompanionProject").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Err(tr("backend.errors.project.manual_required", &[]));
+    }
+    tier.get("id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| "loadCodeAssist 未返回订阅层级 ID".to_string())
+}
+
+/// 从 onboardUser 的长时操作结果中提取 project_id (未完成时返回 None)
+fn onboarded_project_id(operation: &Value) -> Option<String> {
+    if !operation.get("done").and_then(|d| d.as_bool()).unwrap_or(false) {
+        return None;
+    }
+    let project = operation.get("response")?.get("cloudaicompanionProject")?;
+    project
+        .as_str()
+        .or_else(|| project.get("id").and_then(|v| v.as_str()))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
+/// 通过 onboardUser 为账号初始化项目
+async fn onboard_project(access_token: &str, load_response: &Value) -> Result<String, String> {
+    let request_body = serde_json::json!({
+        "tierId": default_tier_id(load_response)?,
+        "metadata": {
+            "ideType": "ANTIGRAVITY"
+        }
+    });
+
+    for attempt in 0..ONBOARD_MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(std::time::Duration::from_secs(ONBOARD_POLL_INTERVAL_SECS)).await;
+        }
+        let operation = post_code_assist(ONBOARD_USER_URL, access_token, &request_body).await?;
+        if let Some(project_id) = onboarded_project_id(&operation) {
+            return Ok(project_id);
+        }
+    }
+    Err(tr("backend.errors.project.onboard_timeout", &[]))
+}
+
+/// 使用 Antigravity 的 loadCodeAssist API 获取 project_id 及其来源
+/// 这是获取 cloudaicompanionProject 的正确方式
+///
+/// 账号没有项目时，若启用了 `auto_create_project` 则尝试通过 onboardUser 自动创建，
+/// 否则 (或创建失败时) 使用随机生成的 project_id 兜底
+pub async fn fetch_project_id(access_token: &str) -> Result<(String, ProjectOutcome), String> {
+    let request_body = serde_json::json!({
+        "metadata": {
+            "ideType": "ANTIGRAVITY"
+        }
+    });
+    let data = post_code_assist(LOAD_CODE_ASSIST_URL, access_token, &request_body).await?;
+
     // 提取 cloudaicompanionProject
     if let Some(project_id) = data.get("cloudaicompanionProject")
         .and_then(|v| v.as_str()) {
-        return Ok(project_id.to_string());
+        return Ok((project_id.to_string(), ProjectOutcome::Existing));
     }
-    
+
+    let auto_create = crate::modules::config::load_app_config_async()
+        .await
+        .map(|c| c.auto_create_project)
+        .unwrap_or(false);
+    let outcome = if auto_create {
+        match onboard_project(access_token, &data).await {
+            Ok(project_id) => {
+                tracing::info!("账号没有可用项目，已自动创建: {}", project_id);
+                return Ok((project_id, ProjectOutcome::Created));
+            }
+            Err(e) => {
+                tracing::warn!("自动创建项目失败: {}", e);
+                ProjectOutcome::CreateFailed(e)
+            }
+        }
+    } else {
+        ProjectOutcome::Fallback
+    };
+
     // 如果没有返回 project_id，说明账号无资格，使用内置随机生成逻辑作为兜底
     let mock_id = generate_mock_project_id();
     tracing::warn!("账号无资格获取官方 cloudaicompanionProject，将使用随机生成的 Project ID 作为兜底: {}", mock_id);
-    Ok(mock_id)
+    Ok((mock_id, outcome))
+}
+
+/// 列出账号可访问的全部 GCP 项目 (Cloud Resource Manager)
+pub async fn list_projects(access_token: &str) -> Result<Vec<String>, String> {
+    let client = crate::utils::http::create_upstream_client(30);
+    let response = client
+        .get("https://cloudresourcemanager.googleapis.com/v1/projects")
+        .bearer_auth(access_token)
+        .query(&[("filter", "lifecycleState:ACTIVE")])
+        .send()
+        .await
+        .map_err(|e| format!("列出项目请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(tr("backend.errors.project.list_failed", &[("status", &status.to_string()), ("body", &text)]));
+    }
+
+    let data: Value = response.json().await.map_err(|e| format!("解析响应失败: {}", e))?;
+    Ok(data
+        .get("projects")
+        .and_then(|p| p.as_array())
+        .map(|projects| {
+            projects
+                .iter()
+                .filter_map(|p| p.get("projectId").and_then(|v| v.as_str()))
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default())
 }
 
 /// 生成随机 project_id（当无法从 API 获取时使用）
@@ -68,3 +282,55 @@ pub fn generate_mock_project_id() -> String {
     
     format!("{}-{}-{}", adj, noun, random_num)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_cache_expires_after_ttl() {
+        let now = 1_000_000;
+        remember_at("ttl-account", "project-a", now);
+        assert_eq!(lookup_at("ttl-account", now + 60).as_deref(), Some("project-a"));
+        assert_eq!(lookup_at("ttl-account", now + PROJECT_CACHE_TTL_SECS), None);
+
+        remember_at("ttl-account", "project-b", now);
+        invalidate_project_id("ttl-account");
+        assert_eq!(lookup_at("ttl-account", now), None);
+    }
+
+    #[test]
+    fn test_onboarding_tier_and_result_parsing() {
+        let load = serde_json::json!({
+            "allowedTiers": [
+                {"id": "legacy-tier", "isDefault": false},
+                {"id": "free-tier", "isDefault": true}
+            ]
+        });
+        assert_eq!(default_tier_id(&load).unwrap(), "free-tier");
+        assert_eq!(default_tier_id(&serde_json::json!({})).unwrap(), "free-tier");
+        let byo = serde_json::json!({
+            "allowedTiers": [{"id": "standard-tier", "isDefault": true, "userDefinedCloudaicompanionProject": true}]
+        });
+        assert!(default_tier_id(&byo).is_err());
+
+        assert_eq!(onboarded_project_id(&serde_json::json!({"done": false})), None);
+        let done = serde_json::json!({
+            "done": true,
+            "response": {"cloudaicompanionProject": {"id": "new-project-1", "name": "new"}}
+        });
+        assert_eq!(onboarded_project_id(&done).as_deref(), Some("new-project-1"));
+    }
+
+    #[tokio::test]
+    async fn test_stored_project_id_seeds_cache_without_request() {
+        invalidate_project_id("seed-account");
+        let (pid, changed) = resolve_project_id("seed-account", "unused-token", Some("stored-project"))
+            .await
+            .unwrap();
+        assert_eq!(pid, "stored-project");
+        assert!(!changed);
+        assert!(lookup_at("seed-account", chrono::Utc::now().timestamp()).is_some());
+        invalidate_project_id("seed-account");
+    }
+}
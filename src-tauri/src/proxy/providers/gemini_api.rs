@@ -0,0 +1,108 @@
+// Gemini API Key 上游 (Google AI Studio)
+//
+// - Gemini 原生请求: 转发到 `/v1beta/models/{model}:{method}`，流式使用 `alt=sse`
+// - OpenAI Chat 请求: 转发到官方 OpenAI 兼容端点 `/v1beta/openai/chat/completions`
+// 两者都不需要协议转换，只替换模型名与认证方式。
+
+use futures::future::BoxFuture;
+
+use super::{InboundProtocol, InboundRequest, UpstreamProvider, UpstreamRequest};
+use crate::modules::i18n::tr;
+
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+/// Gemini 原生请求的上游方法
+pub(crate) fn gemini_method(stream: bool) -> &'static str {
+    if stream {
+        "streamGenerateContent?alt=sse"
+    } else {
+        "generateContent"
+    }
+}
+
+pub struct GeminiApiKeyProvider {
+    api_key: String,
+    base_url: String,
+}
+
+impl GeminiApiKeyProvider {
+    pub fn new(api_key: &str, base_url: Option<&str>) -> Result<Self, String> {
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return Err(tr("backend.errors.provider.missing_api_key", &[]));
+        }
+        let base_url = base_url
+            .map(|u| u.trim().trim_end_matches('/'))
+            .filter(|u| !u.is_empty())
+            .unwrap_or(DEFAULT_BASE_URL);
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            return Err(tr("backend.errors.provider.invalid_base_url", &[("url", base_url)]));
+        }
+        Ok(Self { api_key: api_key.to_string(), base_url: base_url.to_string() })
+    }
+}
+
+impl UpstreamProvider for GeminiApiKeyProvider {
+    fn supports(&self, _protocol: InboundProtocol) -> bool {
+        true
+    }
+
+    fn convert(&self, request: &InboundRequest) -> Result<UpstreamRequest, String> {
+        match request.protocol {
+            InboundProtocol::Gemini => Ok(UpstreamRequest {
+                url: format!("{}/v1beta/models/{}:{}", self.base_url, request.model, gemini_method(request.stream)),
+                body: request.body.clone(),
+            }),
+            InboundProtocol::OpenAiChat => {
+                let mut body = request.body.clone();
+                body["model"] = request.model.clone().into();
+                Ok(UpstreamRequest { url: format!("{}/v1beta/openai/chat/completions", self.base_url), body })
+            }
+        }
+    }
+
+    fn authorize<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        protocol: InboundProtocol,
+        request: reqwest::RequestBuilder,
+    ) -> BoxFuture<'a, Result<reqwest::RequestBuilder, String>> {
+        Box::pin(async move {
+            Ok(match protocol {
+                InboundProtocol::Gemini => request.header("x-goog-api-key", &self.api_key),
+                InboundProtocol::OpenAiChat => request.bearer_auth(&self.api_key),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_convert_urls() {
+        let provider = GeminiApiKeyProvider::new("AIza", None).unwrap();
+        let mut request = InboundRequest {
+            protocol: InboundProtocol::Gemini,
+            model: "gemini-2.0-flash".to_string(),
+            stream: true,
+            body: json!({ "contents": [] }),
+        };
+        let upstream = provider.convert(&request).unwrap();
+        assert_eq!(
+            upstream.url,
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(upstream.body, request.body);
+
+        request.protocol = InboundProtocol::OpenAiChat;
+        request.body = json!({ "model": "aistudio/gemini-2.0-flash", "messages": [] });
+        let upstream = provider.convert(&request).unwrap();
+        assert_eq!(upstream.url, "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions");
+        assert_eq!(upstream.body["model"], "gemini-2.0-flash");
+
+        assert!(GeminiApiKeyProvider::new(" ", None).is_err());
+    }
+}
@@ -1,2 +1,264 @@
+// 上游后端 (Provider)
+//
+// 账号池 (Google OAuth 账号 + v1internal) 之外的其他上游，每种后端实现 `UpstreamProvider`:
+// - `convert`: 把入站请求 (OpenAI Chat / Gemini 原生) 转换为该后端的地址与请求体
+// - `authorize`: 为上游请求附加认证 (API Key、服务账号 Access Token 等)
+// 后端由配置中的 `providers` 注册 (见 ProviderRegistry)，请求的模型命中某个后端时直接转发，
+// 响应 (含 SSE 流) 原样返回。z.ai (Anthropic 协议) 仍由 zai_anthropic 按分发模式处理。
+
+pub mod gemini_api;
+pub mod openai_compat;
+pub mod vertex;
 pub mod zai_anthropic;
 
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::proxy::common::model_mapping::wildcard_match;
+use crate::proxy::config::{ProviderConfig, ProviderKind, UpstreamProxyConfig};
+use crate::proxy::server::AppState;
+
+pub(crate) fn build_client(
+    upstream_proxy: Option<UpstreamProxyConfig>,
+    timeout_secs: u64,
+) -> Result<reqwest::Client, String> {
+    crate::utils::http::upstream_builder(timeout_secs.max(5), upstream_proxy.as_ref())
+        .map_err(|e| format!("Invalid upstream proxy url: {}", e))?
+        .tcp_nodelay(true) // [FIX #307] Disable Nagle's algorithm to improve latency for small requests
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+/// 入站协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundProtocol {
+    /// OpenAI `/v1/chat/completions`
+    OpenAiChat,
+    /// Gemini 原生 `generateContent` / `streamGenerateContent`
+    Gemini,
+}
+
+/// 入站请求
+#[derive(Debug, Clone)]
+pub struct InboundRequest {
+    pub protocol: InboundProtocol,
+    /// 发送给后端的模型名 (已去掉 `<name>/` 前缀)
+    pub model: String,
+    pub stream: bool,
+    pub body: Value,
+}
+
+/// 转换后的上游请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpstreamRequest {
+    pub url: String,
+    pub body: Value,
+}
+
+/// 可插拔的上游后端
+pub trait UpstreamProvider: Send + Sync {
+    /// 是否支持该入站协议
+    fn supports(&self, protocol: InboundProtocol) -> bool;
+
+    /// 把入站请求转换为该后端的请求
+    fn convert(&self, request: &InboundRequest) -> Result<UpstreamRequest, String>;
+
+    /// 为上游请求附加认证 (不同入站协议可能对应不同的上游端点)
+    fn authorize<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        protocol: InboundProtocol,
+        request: reqwest::RequestBuilder,
+    ) -> BoxFuture<'a, Result<reqwest::RequestBuilder, String>>;
+}
+
+/// 根据配置创建后端
+pub fn create_provider(kind: &ProviderKind) -> Result<Arc<dyn UpstreamProvider>, String> {
+    Ok(match kind {
+        ProviderKind::OpenaiCompatible { base_url, api_key } => {
+            Arc::new(openai_compat::OpenAiCompatProvider::new(base_url, api_key)?)
+        }
+        ProviderKind::GeminiApiKey { api_key, base_url } => {
+            Arc::new(gemini_api::GeminiApiKeyProvider::new(api_key, base_url.as_deref())?)
+        }
+        ProviderKind::VertexServiceAccount { service_account, project_id, location } => {
+            Arc::new(vertex::VertexProvider::new(service_account, project_id.as_deref(), location)?)
+        }
+    })
+}
+
+struct RegisteredProvider {
+    name: String,
+    models: Vec<String>,
+    provider: Arc<dyn UpstreamProvider>,
+}
+
+/// 已注册的后端 (按配置顺序匹配)
+#[derive(Default)]
+pub struct ProviderRegistry {
+    providers: Vec<RegisteredProvider>,
+}
+
+impl ProviderRegistry {
+    /// 从配置创建，无效的后端记录日志后跳过
+    pub fn from_config(configs: &[ProviderConfig]) -> Self {
+        let mut providers = Vec::new();
+        for config in configs.iter().filter(|c| c.enabled) {
+            let name = config.name.trim();
+            if name.is_empty() {
+                tracing::warn!("[Provider] 后端名称为空，已跳过");
+                continue;
+            }
+            match create_provider(&config.kind) {
+                Ok(provider) => providers.push(RegisteredProvider {
+                    name: name.to_string(),
+                    models: config.models.iter().map(|m| m.trim().to_string()).filter(|m| !m.is_empty()).collect(),
+                    provider,
+                }),
+                Err(e) => tracing::warn!("[Provider] 后端 {} 配置无效，已跳过: {}", name, e),
+            }
+        }
+        Self { providers }
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// 选择处理该模型的后端，返回 (后端名称, 后端, 发送给后端的模型名)
+    ///
+    /// `<name>/<model>` 显式指定后端优先，其次按 `models` 模式匹配
+    pub fn route(&self, protocol: InboundProtocol, model: &str) -> Option<(String, Arc<dyn UpstreamProvider>, String)> {
+        let supported = self.providers.iter().filter(|p| p.provider.supports(protocol));
+        let prefixed = supported.clone().find_map(|p| {
+            model
+                .strip_prefix(p.name.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+                .filter(|rest| !rest.is_empty())
+                .map(|rest| (p, rest.to_string()))
+        });
+        let (entry, model) = prefixed.or_else(|| {
+            supported
+                .clone()
+                .find(|p| p.models.iter().any(|pattern| wildcard_match(pattern, model)))
+                .map(|p| (p, model.to_string()))
+        })?;
+        Some((entry.name.clone(), entry.provider.clone(), model))
+    }
+}
+
+/// 把请求转发到后端，响应 (含 SSE) 原样返回
+pub async fn forward(
+    name: &str,
+    provider: &dyn UpstreamProvider,
+    request: InboundRequest,
+    upstream_proxy: UpstreamProxyConfig,
+    timeout_secs: u64,
+) -> Response {
+    let upstream = match provider.convert(&request) {
+        Ok(upstream) => upstream,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let client = match build_client(Some(upstream_proxy), timeout_secs) {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    };
+
+    let body_bytes = serde_json::to_vec(&upstream.body).unwrap_or_default();
+    let builder = client
+        .post(&upstream.url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body_bytes);
+    let builder = match provider.authorize(&client, request.protocol, builder).await {
+        Ok(b) => b,
+        Err(e) => {
+            return (StatusCode::BAD_GATEWAY, format!("Provider {} authorization failed: {}", name, e)).into_response();
+        }
+    };
+
+    tracing::debug!("[Provider] {} -> {} (model: {})", name, upstream.url, request.model);
+    let resp = match builder.send().await {
+        Ok(r) => r,
+        Err(e) => {
+            return (StatusCode::BAD_GATEWAY, format!("Upstream request failed: {}", e.without_url())).into_response();
+        }
+    };
+
+    let status = StatusCode::from_u16(resp.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut out = Response::builder().status(status);
+    if let Some(ct) = resp.headers().get(header::CONTENT_TYPE) {
+        out = out.header(header::CONTENT_TYPE, ct.clone());
+    }
+    // 监控日志中的 "账号" 与 "映射模型" 列
+    if let Ok(v) = HeaderValue::from_str(&format!("provider:{}", name)) {
+        out = out.header("X-Account-Email", v);
+    }
+    if let Ok(v) = HeaderValue::from_str(&request.model) {
+        out = out.header("X-Mapped-Model", v);
+    }
+
+    let stream = resp.bytes_stream().map(|chunk| match chunk {
+        Ok(b) => Ok::<Bytes, std::io::Error>(b),
+        Err(e) => Ok(Bytes::from(format!("Upstream stream error: {}", e))),
+    });
+    out.body(Body::from_stream(stream)).unwrap_or_else(|_| {
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response()
+    })
+}
+
+/// 模型命中已注册的后端时转发并返回响应，否则返回 None (继续使用账号池)
+pub async fn dispatch(state: &AppState, protocol: InboundProtocol, model: &str, stream: bool, body: &Value) -> Option<Response> {
+    let (name, provider, model) = state.providers.read().await.route(protocol, model)?;
+    let request = InboundRequest { protocol, model, stream, body: body.clone() };
+    let upstream_proxy = state.upstream_proxy.read().await.clone();
+    Some(forward(&name, provider.as_ref(), request, upstream_proxy, state.request_timeout).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, models: &[&str], kind: ProviderKind) -> ProviderConfig {
+        ProviderConfig {
+            name: name.to_string(),
+            enabled: true,
+            models: models.iter().map(|m| m.to_string()).collect(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_registry_routing() {
+        let registry = ProviderRegistry::from_config(&[
+            config(
+                "deepseek",
+                &["deepseek-*"],
+                ProviderKind::OpenaiCompatible { base_url: "https://api.deepseek.com/v1".to_string(), api_key: "sk-1".to_string() },
+            ),
+            config("aistudio", &["gemini-2.0-*"], ProviderKind::GeminiApiKey { api_key: "AIza".to_string(), base_url: None }),
+            config("broken", &["*"], ProviderKind::OpenaiCompatible { base_url: String::new(), api_key: String::new() }),
+        ]);
+        assert_eq!(registry.len(), 2);
+
+        let (name, _, model) = registry.route(InboundProtocol::OpenAiChat, "deepseek-chat").unwrap();
+        assert_eq!((name.as_str(), model.as_str()), ("deepseek", "deepseek-chat"));
+
+        // 显式前缀优先，且去掉前缀
+        let (name, _, model) = registry.route(InboundProtocol::OpenAiChat, "aistudio/gemini-2.5-pro").unwrap();
+        assert_eq!((name.as_str(), model.as_str()), ("aistudio", "gemini-2.5-pro"));
+
+        // OpenAI 兼容上游不支持 Gemini 原生协议
+        assert!(registry.route(InboundProtocol::Gemini, "deepseek-chat").is_none());
+        assert!(registry.route(InboundProtocol::Gemini, "gemini-2.0-flash").is_some());
+        assert!(registry.route(InboundProtocol::OpenAiChat, "gemini-2.5-pro").is_none());
+        assert!(registry.route(InboundProtocol::OpenAiChat, "aistudio/").is_none());
+    }
+}
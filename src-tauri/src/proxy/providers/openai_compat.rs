@@ -0,0 +1,76 @@
+// OpenAI 兼容上游 (DeepSeek、OpenRouter、自建 vLLM 等)
+//
+// 只接受 OpenAI Chat 请求，请求体原样转发 (仅替换模型名)，使用 Bearer API Key 认证。
+
+use futures::future::BoxFuture;
+
+use super::{InboundProtocol, InboundRequest, UpstreamProvider, UpstreamRequest};
+use crate::modules::i18n::tr;
+
+pub struct OpenAiCompatProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl OpenAiCompatProvider {
+    pub fn new(base_url: &str, api_key: &str) -> Result<Self, String> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            return Err(tr("backend.errors.provider.invalid_base_url", &[("url", base_url)]));
+        }
+        Ok(Self { base_url: base_url.to_string(), api_key: api_key.trim().to_string() })
+    }
+}
+
+impl UpstreamProvider for OpenAiCompatProvider {
+    fn supports(&self, protocol: InboundProtocol) -> bool {
+        protocol == InboundProtocol::OpenAiChat
+    }
+
+    fn convert(&self, request: &InboundRequest) -> Result<UpstreamRequest, String> {
+        if !self.supports(request.protocol) {
+            return Err(tr("backend.errors.provider.chat_only", &[]));
+        }
+        let mut body = request.body.clone();
+        body["model"] = request.model.clone().into();
+        Ok(UpstreamRequest { url: format!("{}/chat/completions", self.base_url), body })
+    }
+
+    fn authorize<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        _protocol: InboundProtocol,
+        request: reqwest::RequestBuilder,
+    ) -> BoxFuture<'a, Result<reqwest::RequestBuilder, String>> {
+        Box::pin(async move {
+            // 本地 vLLM / Ollama 等可以不配置 Key
+            if self.api_key.is_empty() {
+                return Ok(request);
+            }
+            Ok(request.bearer_auth(&self.api_key))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_convert_replaces_model() {
+        let provider = OpenAiCompatProvider::new("https://api.deepseek.com/v1/", "sk-1").unwrap();
+        let request = InboundRequest {
+            protocol: InboundProtocol::OpenAiChat,
+            model: "deepseek-chat".to_string(),
+            stream: true,
+            body: json!({ "model": "deepseek/deepseek-chat", "stream": true, "messages": [] }),
+        };
+        let upstream = provider.convert(&request).unwrap();
+        assert_eq!(upstream.url, "https://api.deepseek.com/v1/chat/completions");
+        assert_eq!(upstream.body["model"], "deepseek-chat");
+        assert_eq!(upstream.body["stream"], true);
+
+        assert!(OpenAiCompatProvider::new("api.deepseek.com", "").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+// Vertex AI 服务账号上游
+//
+// 用服务账号私钥签发 JWT (RS256) 换取 Access Token (缓存到过期前 1 分钟)，再调用 Vertex AI:
+// - Gemini 原生请求: `publishers/google/models/{model}:{method}`
+// - OpenAI Chat 请求: Vertex 的 OpenAI 兼容端点 `endpoints/openapi/chat/completions`
+//   (模型名需要带 `google/` 前缀)
+
+use futures::future::BoxFuture;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::gemini_api::gemini_method;
+use super::{InboundProtocol, InboundRequest, UpstreamProvider, UpstreamRequest};
+use crate::modules::i18n::tr;
+
+const SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+/// Access Token 提前刷新的秒数
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 60;
+
+#[derive(Debug, Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default)]
+    project_id: Option<String>,
+    #[serde(default)]
+    token_uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+/// 服务账号可以填 JSON 文件路径或直接粘贴 JSON 内容
+fn load_service_account(value: &str) -> Result<ServiceAccountKey, String> {
+    let value = value.trim();
+    let content = if value.starts_with('{') {
+        value.to_string()
+    } else {
+        std::fs::read_to_string(value).map_err(|e| format!("读取服务账号文件 {} 失败: {}", value, e))?
+    };
+    serde_json::from_str(&content).map_err(|e| format!("解析服务账号 JSON 失败: {}", e))
+}
+
+pub struct VertexProvider {
+    client_email: String,
+    signing_key: EncodingKey,
+    token_uri: String,
+    project_id: String,
+    location: String,
+    /// (access_token, 过期时间戳)
+    token: Mutex<Option<(String, i64)>>,
+}
+
+impl VertexProvider {
+    pub fn new(service_account: &str, project_id: Option<&str>, location: &str) -> Result<Self, String> {
+        let key = load_service_account(service_account)?;
+        let signing_key = EncodingKey::from_rsa_pem(key.private_key.as_bytes())
+            .map_err(|e| format!("无效的服务账号私钥: {}", e))?;
+        let project_id = project_id
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .or(key.project_id)
+            .ok_or_else(|| tr("backend.errors.provider.missing_project_id", &[]))?;
+        let location = match location.trim() {
+            "" => "us-central1".to_string(),
+            other => other.to_string(),
+        };
+        Ok(Self {
+            client_email: key.client_email,
+            signing_key,
+            token_uri: key.token_uri.unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string()),
+            project_id,
+            location,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// `global` 区域使用不带区域前缀的域名
+    fn base_url(&self) -> String {
+        let host = if self.location == "global" {
+            "aiplatform.googleapis.com".to_string()
+        } else {
+            format!("{}-aiplatform.googleapis.com", self.location)
+        };
+        format!("https://{}/v1/projects/{}/locations/{}", host, self.project_id, self.location)
+    }
+
+    async fn access_token(&self, client: &reqwest::Client) -> Result<String, String> {
+        let mut cached = self.token.lock().await;
+        let now = chrono::Utc::now().timestamp();
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if *expires_at - TOKEN_REFRESH_MARGIN_SECS > now {
+                return Ok(token.clone());
+            }
+        }
+
+        let claims = Claims { iss: &self.client_email, scope: SCOPE, aud: &self.token_uri, iat: now, exp: now + 3600 };
+        let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.signing_key)
+            .map_err(|e| format!("签发 JWT 失败: {}", e))?;
+        let resp = client
+            .post(&self.token_uri)
+            .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", assertion.as_str())])
+            .send()
+            .await
+            .map_err(|e| format!("获取 Access Token 失败: {}", e.without_url()))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(tr("backend.errors.provider.token_failed", &[("status", &status.to_string()), ("body", &text.chars().take(200).collect::<String>())]));
+        }
+        let token: TokenResponse = resp.json().await.map_err(|e| format!("解析 Access Token 响应失败: {}", e))?;
+        let expires_at = now + token.expires_in.unwrap_or(3600);
+        *cached = Some((token.access_token.clone(), expires_at));
+        Ok(token.access_token)
+    }
+}
+
+impl UpstreamProvider for VertexProvider {
+    fn supports(&self, _protocol: InboundProtocol) -> bool {
+        true
+    }
+
+    fn convert(&self, request: &InboundRequest) -> Result<UpstreamRequest, String> {
+        match request.protocol {
+            InboundProtocol::Gemini => Ok(UpstreamRequest {
+                url: format!(
+                    "{}/publishers/google/models/{}:{}",
+                    self.base_url(),
+                    request.model,
+                    gemini_method(request.stream)
+                ),
+                body: request.body.clone(),
+            }),
+            InboundProtocol::OpenAiChat => {
+                let model = if request.model.contains('/') {
+                    request.model.clone()
+                } else {
+                    format!("google/{}", request.model)
+                };
+                let mut body = request.body.clone();
+                body["model"] = model.into();
+                Ok(UpstreamRequest { url: format!("{}/endpoints/openapi/chat/completions", self.base_url()), body })
+            }
+        }
+    }
+
+    fn authorize<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        _protocol: InboundProtocol,
+        request: reqwest::RequestBuilder,
+    ) -> BoxFuture<'a, Result<reqwest::RequestBuilder, String>> {
+        Box::pin(async move {
+            let token = self.access_token(client).await?;
+            Ok(request.bearer_auth(token))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn provider(location: &str) -> VertexProvider {
+        VertexProvider {
+            client_email: "sa@demo.iam.gserviceaccount.com".to_string(),
+            signing_key: EncodingKey::from_secret(b"test"),
+            token_uri: DEFAULT_TOKEN_URI.to_string(),
+            project_id: "demo".to_string(),
+            location: location.to_string(),
+            token: Mutex::new(None),
+        }
+    }
+
+    #[test]
+    fn test_convert_urls() {
+        let mut request = InboundRequest {
+            protocol: InboundProtocol::Gemini,
+            model: "gemini-2.5-pro".to_string(),
+            stream: false,
+            body: json!({ "contents": [] }),
+        };
+        let upstream = provider("us-central1").convert(&request).unwrap();
+        assert_eq!(
+            upstream.url,
+            "https://us-central1-aiplatform.googleapis.com/v1/projects/demo/locations/us-central1/publishers/google/models/gemini-2.5-pro:generateContent"
+        );
+
+        request.protocol = InboundProtocol::OpenAiChat;
+        request.body = json!({ "model": "vertex/gemini-2.5-pro", "messages": [] });
+        let upstream = provider("global").convert(&request).unwrap();
+        assert_eq!(
+            upstream.url,
+            "https://aiplatform.googleapis.com/v1/projects/demo/locations/global/endpoints/openapi/chat/completions"
+        );
+        assert_eq!(upstream.body["model"], "google/gemini-2.5-pro");
+    }
+
+    #[test]
+    fn test_invalid_service_account() {
+        assert!(VertexProvider::new(r#"{"client_email": "a", "private_key": "not a key"}"#, Some("p"), "").is_err());
+        assert!(VertexProvider::new("/nonexistent/sa.json", None, "").is_err());
+    }
+}
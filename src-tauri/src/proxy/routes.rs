@@ -0,0 +1,204 @@
+// 反代 HTTP 路由表
+//
+// server.rs 注册的路由与 `/openapi.json` 文档都由 `api_routes()` 生成: 每个端点的处理函数
+// 与文档描述 (认证、请求体、错误格式) 写在同一项里，新增端点只需要在这里添加一行，不会出现文档遗漏。
+// 同一路径的多个方法分别列出，注册时由 axum 合并。
+
+use axum::handler::Handler;
+use axum::routing::{any, get, post, MethodRouter};
+
+use crate::proxy::handlers;
+use crate::proxy::handlers::openapi::{route, Body as B, ErrorShape as E, RouteAuth as A, RouteDoc};
+use crate::proxy::server::{self, AppState};
+
+pub(crate) struct ApiRoute {
+    pub doc: RouteDoc,
+    pub handler: MethodRouter<AppState>,
+}
+
+/// 按文档中的方法注册处理函数
+fn api<H, T>(doc: RouteDoc, handler: H) -> ApiRoute
+where
+    H: Handler<T, AppState>,
+    T: 'static,
+{
+    let handler = match doc.method {
+        "get" => get(handler),
+        "post" => post(handler),
+        other => panic!("unsupported method {} for {}", other, doc.path),
+    };
+    ApiRoute { doc, handler }
+}
+
+/// 接受任意方法的端点 (MCP Streamable HTTP)，文档中按实际使用的方法列出
+fn api_any<H, T>(doc: RouteDoc, handler: H) -> ApiRoute
+where
+    H: Handler<T, AppState>,
+    T: 'static,
+{
+    ApiRoute { doc, handler: any(handler) }
+}
+
+/// 全部端点 (路径使用 axum 语法)
+pub(crate) fn api_routes() -> Vec<ApiRoute> {
+    vec![
+        // OpenAI
+        api(
+            route("get", "/v1/models", "OpenAI", "List available models", A::ApiKey, B::None, E::Text),
+            handlers::openai::handle_list_models,
+        ),
+        api(
+            route("post", "/v1/chat/completions", "OpenAI", "Chat completions", A::ApiKey, B::JsonStream, E::Text),
+            handlers::openai::handle_chat_completions,
+        ),
+        api(
+            route("post", "/v1/completions", "OpenAI", "Legacy completions", A::ApiKey, B::JsonStream, E::Text),
+            handlers::openai::handle_completions,
+        ),
+        api(
+            route("post", "/v1/responses", "OpenAI", "Responses API (Codex CLI compatible)", A::ApiKey, B::JsonStream, E::Text),
+            handlers::openai::handle_completions,
+        ),
+        api(
+            route("post", "/v1/images/generations", "OpenAI", "Generate images", A::ApiKey, B::Json, E::Text),
+            handlers::openai::handle_images_generations,
+        ),
+        api(
+            route("post", "/v1/images/edits", "OpenAI", "Edit images", A::ApiKey, B::Multipart, E::Text),
+            handlers::openai::handle_images_edits,
+        ),
+        api(
+            route("post", "/v1/audio/transcriptions", "OpenAI", "Transcribe audio", A::ApiKey, B::Multipart, E::Text),
+            handlers::audio::handle_audio_transcription,
+        ),
+        // Claude
+        api(
+            route("post", "/v1/messages", "Claude", "Create a message", A::ApiKey, B::JsonStream, E::Anthropic),
+            handlers::claude::handle_messages,
+        ),
+        api(
+            route("post", "/v1/messages/count_tokens", "Claude", "Count message tokens", A::ApiKey, B::Json, E::Anthropic),
+            handlers::claude::handle_count_tokens,
+        ),
+        api(
+            route("get", "/v1/models/claude", "Claude", "List Claude models", A::ApiKey, B::None, E::Text),
+            handlers::claude::handle_list_models,
+        ),
+        // z.ai MCP (可选反代) 与管理 MCP (仅主 API Key)
+        api_any(
+            route("post", "/mcp/web_search_prime/mcp", "MCP", "z.ai web search MCP", A::ApiKey, B::Mcp, E::Text),
+            handlers::mcp::handle_web_search_prime,
+        ),
+        api_any(
+            route("post", "/mcp/web_reader/mcp", "MCP", "z.ai web reader MCP", A::ApiKey, B::Mcp, E::Text),
+            handlers::mcp::handle_web_reader,
+        ),
+        api_any(
+            route("post", "/mcp/zai-mcp-server/mcp", "MCP", "z.ai vision MCP", A::ApiKey, B::Mcp, E::Text),
+            handlers::mcp::handle_zai_mcp_server,
+        ),
+        api_any(
+            route("post", "/mcp/manager/mcp", "MCP", "Manager MCP (accounts, health, usage, pools)", A::Admin, B::Mcp, E::Text),
+            handlers::mcp::handle_manager_mcp,
+        ),
+        // Gemini (原生协议)
+        api(
+            route("get", "/v1beta/models", "Gemini", "List models", A::ApiKey, B::None, E::Text),
+            handlers::gemini::handle_list_models,
+        ),
+        api(
+            route("get", "/v1beta/models/:model", "Gemini", "Get model info", A::ApiKey, B::None, E::Text),
+            handlers::gemini::handle_get_model,
+        ),
+        api(
+            route(
+                "post",
+                "/v1beta/models/:model",
+                "Gemini",
+                "generateContent / streamGenerateContent (`{model}` is `<name>:<method>`)",
+                A::ApiKey,
+                B::JsonStream,
+                E::Text,
+            ),
+            handlers::gemini::handle_generate,
+        ),
+        api(
+            route("post", "/v1beta/models/:model/countTokens", "Gemini", "Count tokens", A::ApiKey, B::Json, E::Text),
+            handlers::gemini::handle_count_tokens,
+        ),
+        // 其他
+        api(
+            route("post", "/v1/models/detect", "Utility", "Detect model capabilities and routing", A::ApiKey, B::Json, E::Text),
+            handlers::common::handle_detect_model,
+        ),
+        api(
+            route("post", "/internal/warmup", "Utility", "Warm up an account", A::ApiKey, B::Json, E::Json),
+            handlers::warmup::handle_warmup,
+        ),
+        api(
+            route("post", "/v1/api/event_logging/batch", "Utility", "Swallow client telemetry", A::ApiKey, B::None, E::Text),
+            server::silent_ok_handler,
+        ),
+        api(
+            route("post", "/v1/api/event_logging", "Utility", "Swallow client telemetry", A::ApiKey, B::None, E::Text),
+            server::silent_ok_handler,
+        ),
+        api(
+            route("get", "/healthz", "Status", "Health check", A::Public, B::None, E::Text),
+            server::health_check_handler,
+        ),
+        api(
+            route("get", "/metrics", "Status", "Prometheus metrics", A::ApiKey, B::None, E::Text),
+            handlers::metrics::handle_metrics,
+        ),
+        api(
+            route("get", "/v1/usage/keys", "Status", "Usage per API key (`window_hours`, default 24)", A::ApiKey, B::None, E::Json),
+            handlers::usage::handle_key_usage,
+        ),
+        // 内置 Web 管理面板 (数据接口仅主 API Key 可用)
+        api(
+            route("get", "/dashboard", "Dashboard", "Web dashboard page", A::Public, B::None, E::Text),
+            handlers::dashboard::handle_dashboard,
+        ),
+        api(
+            route("get", "/dashboard/api/overview", "Dashboard", "Dashboard data (`window_hours`, default 24)", A::Admin, B::None, E::Json),
+            handlers::dashboard::handle_overview,
+        ),
+        // OpenAPI 规范与 Swagger UI
+        api(
+            route("get", "/openapi.json", "Status", "This OpenAPI document", A::Public, B::None, E::Text),
+            handlers::openapi::handle_openapi,
+        ),
+        api(
+            route("get", "/docs", "Status", "Swagger UI", A::Public, B::None, E::Text),
+            handlers::openapi::handle_docs,
+        ),
+        api(
+            route("get", "/docs/:asset", "Status", "Swagger UI assets (embedded)", A::Public, B::None, E::Text),
+            handlers::openapi::handle_docs_asset,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_route_table_builds_router_and_spec() {
+        let routes = api_routes();
+        // 路由冲突 (同一路径重复注册同一方法) 时 axum 会 panic
+        let _router = routes
+            .into_iter()
+            .fold(axum::Router::<AppState>::new(), |router, r| router.route(r.doc.path, r.handler));
+
+        let docs: Vec<(&str, &str)> = api_routes().iter().map(|r| (r.doc.method, r.doc.path)).collect();
+        let unique: HashSet<_> = docs.iter().collect();
+        assert_eq!(unique.len(), docs.len(), "duplicate route in table");
+
+        let spec = handlers::openapi::spec();
+        let documented: usize = spec["paths"].as_object().unwrap().values().map(|item| item.as_object().unwrap().len()).sum();
+        assert_eq!(documented, docs.len());
+    }
+}
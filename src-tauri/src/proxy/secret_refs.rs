@@ -0,0 +1,174 @@
+// 配置中的凭证引用
+//
+// 凭证类字段 (API Key、上游代理密码等，沿用诊断包的判定规则) 可以写成 `env:VAR` 读取环境变量，
+// 或 `keychain:NAME` 读取系统钥匙串中本应用服务下的 NAME 条目。配置文件、配置方案和配置包里
+// 只保存引用本身，分享出去的配置不含明文凭证。引用在服务启动 / 热应用时解析，不写回配置文件。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::utils::redact::is_secret_field;
+use crate::proxy::ProxyConfig;
+use crate::modules::i18n::tr;
+
+const ENV_PREFIX: &str = "env:";
+const KEYCHAIN_PREFIX: &str = "keychain:";
+
+/// 无法解析的引用
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UnresolvedSecretRef {
+    /// 点分隔的字段路径
+    pub field: String,
+    pub reference: String,
+    pub reason: String,
+}
+
+/// 值是否为凭证引用 (而不是明文凭证)
+pub fn is_secret_ref(value: &str) -> bool {
+    value.strip_prefix(ENV_PREFIX).or_else(|| value.strip_prefix(KEYCHAIN_PREFIX)).is_some_and(|name| !name.is_empty())
+}
+
+struct Resolver<E, K> {
+    env: E,
+    keychain: K,
+    unresolved: Vec<UnresolvedSecretRef>,
+}
+
+impl<E: Fn(&str) -> Option<String>, K: Fn(&str) -> Result<String, String>> Resolver<E, K> {
+    fn resolve_string(&mut self, field: &str, s: &mut String) {
+        if !is_secret_ref(s) {
+            return;
+        }
+        let result = if let Some(var) = s.strip_prefix(ENV_PREFIX) {
+            (self.env)(var).ok_or_else(|| format!("环境变量 {} 未设置", var))
+        } else {
+            (self.keychain)(&s[KEYCHAIN_PREFIX.len()..])
+        };
+        match result {
+            Ok(value) => *s = value,
+            Err(reason) => self.unresolved.push(UnresolvedSecretRef { field: field.to_string(), reference: s.clone(), reason }),
+        }
+    }
+
+    fn walk(&mut self, value: &mut Value, path: &str) {
+        match value {
+            Value::Object(map) => {
+                for (name, field) in map.iter_mut() {
+                    let path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+                    match field {
+                        Value::String(s) if is_secret_field(name) => self.resolve_string(&path, s),
+                        Value::Array(items) if is_secret_field(name) => {
+                            for (i, item) in items.iter_mut().enumerate() {
+                                if let Value::String(s) = item {
+                                    self.resolve_string(&format!("{}.{}", path, i), s);
+                                }
+                            }
+                        }
+                        _ => self.walk(field, &path),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    self.walk(item, &format!("{}.{}", path, i));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 解析所有引用，无法解析的保持原样并返回
+fn resolve_with(
+    config: &ProxyConfig,
+    env: impl Fn(&str) -> Option<String>,
+    keychain: impl Fn(&str) -> Result<String, String>,
+) -> (ProxyConfig, Vec<UnresolvedSecretRef>) {
+    let Ok(mut json) = serde_json::to_value(config) else { return (config.clone(), Vec::new()) };
+    let mut resolver = Resolver { env, keychain, unresolved: Vec::new() };
+    resolver.walk(&mut json, "");
+    let resolved = serde_json::from_value(json).unwrap_or_else(|_| config.clone());
+    (resolved, resolver.unresolved)
+}
+
+fn resolve(config: &ProxyConfig) -> (ProxyConfig, Vec<UnresolvedSecretRef>) {
+    resolve_with(config, |var| std::env::var(var).ok(), crate::modules::token_vault::read_keychain_entry)
+}
+
+/// 解析单个值 (不是引用时原样返回)
+pub fn resolve_value(value: &str) -> Result<String, String> {
+    let mut resolved = value.to_string();
+    let mut resolver = Resolver {
+        env: |var: &str| std::env::var(var).ok(),
+        keychain: crate::modules::token_vault::read_keychain_entry,
+        unresolved: Vec::new(),
+    };
+    resolver.resolve_string("", &mut resolved);
+    match resolver.unresolved.pop() {
+        Some(u) => Err(tr("backend.errors.secret_ref.unresolved", &[("reference", &u.reference), ("reason", &u.reason)])),
+        None => Ok(resolved),
+    }
+}
+
+/// 当前环境中无法解析的引用
+pub fn unresolved_refs(config: &ProxyConfig) -> Vec<UnresolvedSecretRef> {
+    resolve(config).1
+}
+
+impl ProxyConfig {
+    /// 解析凭证引用，任一引用无法解析时报错
+    pub fn resolve_secret_refs(&self) -> Result<ProxyConfig, String> {
+        let (resolved, unresolved) = resolve(self);
+        if unresolved.is_empty() {
+            return Ok(resolved);
+        }
+        let details: Vec<String> = unresolved.iter().map(|u| format!("{} ({})", u.field, u.reason)).collect();
+        Err(tr("backend.errors.secret_ref.unresolved_config", &[("details", &details.join("; "))]))
+    }
+
+    /// 运行时生效的配置：环境变量覆盖后再解析凭证引用 (无法解析的引用保持原样并记录警告)
+    pub fn effective(&self) -> ProxyConfig {
+        let (resolved, unresolved) = resolve(&self.with_env_overrides());
+        for u in unresolved {
+            tracing::warn!("[SecretRef] {} = {} 无法解析: {}", u.field, u.reference, u.reason);
+        }
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_env_and_keychain_refs() {
+        let mut config = ProxyConfig { api_key: "env:AGM_TEST_KEY".to_string(), ..Default::default() };
+        config.upstream_proxy.password = "keychain:corp-proxy".to_string();
+        config.upstream_proxy.username = "env:NOT_A_SECRET_FIELD".to_string();
+        let (resolved, unresolved) = resolve_with(
+            &config,
+            |var| (var == "AGM_TEST_KEY").then(|| "sk-from-env".to_string()),
+            |name| if name == "corp-proxy" { Ok("hunter2".to_string()) } else { Err("missing".to_string()) },
+        );
+        assert!(unresolved.is_empty());
+        assert_eq!(resolved.api_key, "sk-from-env");
+        assert_eq!(resolved.upstream_proxy.password, "hunter2");
+        // 只解析凭证类字段
+        assert_eq!(resolved.upstream_proxy.username, "env:NOT_A_SECRET_FIELD");
+    }
+
+    #[test]
+    fn test_unresolved_refs_are_reported_and_kept() {
+        let mut config = ProxyConfig { api_key: "env:AGM_MISSING".to_string(), ..Default::default() };
+        config.upstream_proxy.password = "plain-password".to_string();
+        let (resolved, unresolved) = resolve_with(&config, |_| None, |_| Err("no keychain".to_string()));
+        assert_eq!(resolved.api_key, "env:AGM_MISSING");
+        assert_eq!(resolved.upstream_proxy.password, "plain-password");
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!((unresolved[0].field.as_str(), unresolved[0].reference.as_str()), ("api_key", "env:AGM_MISSING"));
+
+        assert!(is_secret_ref("keychain:x"));
+        assert!(!is_secret_ref("env:"));
+        assert!(!is_secret_ref("sk-123"));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::proxy::config::{ProxyAuthMode, ProxyConfig};
 
 #[derive(Debug, Clone)]
@@ -5,17 +7,53 @@ pub struct ProxySecurityConfig {
     pub auth_mode: ProxyAuthMode,
     pub api_key: String,
     pub allow_lan_access: bool,
+    /// 未开启鉴权时只接受本机请求 (见 ProxyConfig::require_auth_for_lan)
+    pub require_auth_for_lan: bool,
+    /// 绑定到账号池的 API Key (key -> 池名称)
+    pub pool_keys: HashMap<String, String>,
+    /// 未绑定账号池的请求使用的账号池
+    pub default_pool: Option<String>,
 }
 
+/// 请求所属的账号池 (由认证中间件根据 API Key 写入请求扩展)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountPoolScope(pub String);
+
+/// 使用主 API Key (或未开启认证) 的请求，可查看所有 Key 的用量等管理信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdminAccess;
+
 impl ProxySecurityConfig {
     pub fn from_proxy_config(config: &ProxyConfig) -> Self {
         Self {
             auth_mode: config.auth_mode.clone(),
             api_key: config.api_key.clone(),
             allow_lan_access: config.allow_lan_access,
+            require_auth_for_lan: config.require_auth_for_lan,
+            pool_keys: config
+                .account_pools
+                .iter()
+                .flat_map(|pool| {
+                    pool.api_keys
+                        .iter()
+                        .filter(|k| !k.is_empty())
+                        .map(move |k| (k.clone(), pool.name.clone()))
+                })
+                .collect(),
+            default_pool: config.default_pool.clone().filter(|p| !p.is_empty()),
         }
     }
 
+    /// API Key 绑定的账号池
+    pub fn pool_for_key(&self, key: &str) -> Option<&str> {
+        self.pool_keys.get(key).map(|s| s.as_str())
+    }
+
+    /// 请求实际使用的账号池: Key 绑定的池优先，其次为默认账号池
+    pub fn scope_for_key(&self, key: Option<&str>) -> Option<&str> {
+        key.and_then(|k| self.pool_for_key(k)).or(self.default_pool.as_deref())
+    }
+
     pub fn effective_auth_mode(&self) -> ProxyAuthMode {
         match self.auth_mode {
             ProxyAuthMode::Auto => {
@@ -40,6 +78,9 @@ mod tests {
             auth_mode: ProxyAuthMode::Auto,
             api_key: "sk-test".to_string(),
             allow_lan_access: false,
+            require_auth_for_lan: true,
+            pool_keys: HashMap::new(),
+            default_pool: None,
         };
         assert!(matches!(s.effective_auth_mode(), ProxyAuthMode::Off));
     }
@@ -50,11 +91,48 @@ mod tests {
             auth_mode: ProxyAuthMode::Auto,
             api_key: "sk-test".to_string(),
             allow_lan_access: true,
+            require_auth_for_lan: true,
+            pool_keys: HashMap::new(),
+            default_pool: None,
         };
         assert!(matches!(
             s.effective_auth_mode(),
             ProxyAuthMode::AllExceptHealth
         ));
     }
-}
 
+    #[test]
+    fn pool_keys_map_to_their_pool() {
+        let config = ProxyConfig {
+            account_pools: vec![crate::proxy::config::AccountPool {
+                name: "work".to_string(),
+                accounts: vec!["a@example.com".to_string()],
+                api_keys: vec!["sk-work".to_string(), String::new()],
+            }],
+            ..Default::default()
+        };
+        let s = ProxySecurityConfig::from_proxy_config(&config);
+        assert_eq!(s.pool_for_key("sk-work"), Some("work"));
+        assert_eq!(s.pool_for_key(""), None);
+        assert_eq!(s.pool_for_key(&config.api_key), None);
+    }
+
+    #[test]
+    fn default_pool_applies_to_unbound_keys() {
+        let config = ProxyConfig {
+            account_pools: vec![crate::proxy::config::AccountPool {
+                name: "work".to_string(),
+                accounts: vec!["a@example.com".to_string()],
+                api_keys: vec!["sk-work".to_string()],
+            }],
+            default_pool: Some("shared".to_string()),
+            ..Default::default()
+        };
+        let s = ProxySecurityConfig::from_proxy_config(&config);
+        assert_eq!(s.scope_for_key(Some("sk-work")), Some("work"));
+        assert_eq!(s.scope_for_key(Some(&config.api_key)), Some("shared"));
+        assert_eq!(s.scope_for_key(None), Some("shared"));
+        // 默认账号池只决定使用范围，不授予访问权限
+        assert_eq!(s.pool_for_key(&config.api_key), None);
+    }
+}
@@ -0,0 +1,274 @@
+// 端到端自检
+//
+// 通过本机反代对每种协议入口 (OpenAI / Claude / Gemini) 各发送一个极小的请求，
+// 按阶段报告结果：鉴权 -> 账号选择 -> 上游请求 -> 协议转换。与账号健康检查不同，
+// 这里走完整的反代链路，用于把 "用不了" 定位到具体环节。失败的阶段之后不再继续判断。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+use crate::proxy::ProxyConfig;
+use crate::modules::i18n::tr;
+
+/// 自检默认使用的模型 (轻量且所有订阅类型可用)
+pub const SELF_TEST_MODEL: &str = "gemini-2.5-flash";
+const SELF_TEST_PROMPT: &str = "Reply with the single word OK.";
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// 错误详情保留的最大字符数
+const MAX_DETAIL_CHARS: usize = 500;
+
+/// 自检阶段
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStage {
+    Auth,
+    AccountSelection,
+    Upstream,
+    Conversion,
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestStageResult {
+    pub stage: SelfTestStage,
+    pub ok: bool,
+    pub detail: Option<String>,
+}
+
+/// 自检的协议入口
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestProtocol {
+    Openai,
+    Claude,
+    Gemini,
+}
+
+impl SelfTestProtocol {
+    pub const ALL: [SelfTestProtocol; 3] = [Self::Openai, Self::Claude, Self::Gemini];
+
+    pub(crate) fn path(&self, model: &str) -> String {
+        match self {
+            Self::Openai => "/v1/chat/completions".to_string(),
+            Self::Claude => "/v1/messages".to_string(),
+            Self::Gemini => format!("/v1beta/models/{}:generateContent", model),
+        }
+    }
+
+    pub(crate) fn body(&self, model: &str) -> Value {
+        let user = json!([{ "role": "user", "content": SELF_TEST_PROMPT }]);
+        match self {
+            Self::Openai => json!({ "model": model, "messages": user, "max_tokens": 16, "stream": false }),
+            Self::Claude => json!({ "model": model, "messages": user, "max_tokens": 16 }),
+            Self::Gemini => json!({
+                "contents": [{ "role": "user", "parts": [{ "text": SELF_TEST_PROMPT }] }],
+                "generationConfig": { "maxOutputTokens": 16 },
+            }),
+        }
+    }
+
+    /// 每种协议使用客户端惯用的鉴权头
+    pub(crate) fn authorize(&self, request: reqwest::RequestBuilder, api_key: &str) -> reqwest::RequestBuilder {
+        match self {
+            Self::Openai => request.bearer_auth(api_key),
+            Self::Claude => request.header("x-api-key", api_key).header("anthropic-version", "2023-06-01"),
+            Self::Gemini => request.header("x-goog-api-key", api_key),
+        }
+    }
+
+    /// 响应是否为该协议的格式
+    fn check_response(&self, body: &Value) -> Result<(), String> {
+        let ok = match self {
+            Self::Openai => body["choices"][0]["message"].is_object(),
+            Self::Claude => body["type"] == "message" && body["content"].is_array(),
+            Self::Gemini => body["candidates"][0]["content"].is_object(),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(tr("backend.errors.self_test.wrong_format", &[("format", &format!("{:?}", self)), ("body", &truncate(&body.to_string()))]))
+        }
+    }
+}
+
+/// 单个协议入口的自检结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolSelfTest {
+    pub protocol: SelfTestProtocol,
+    pub endpoint: String,
+    pub passed: bool,
+    pub status: Option<u16>,
+    pub account_email: Option<String>,
+    pub mapped_model: Option<String>,
+    pub latency_ms: u64,
+    /// 已执行的各阶段结果 (按执行顺序，失败的阶段之后不再继续)
+    pub stages: Vec<SelfTestStageResult>,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub base_url: String,
+    pub model: String,
+    /// 为自检临时启动了反代服务 (结束后已停止)
+    pub started_proxy: bool,
+    pub passed: bool,
+    pub protocols: Vec<ProtocolSelfTest>,
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_DETAIL_CHARS {
+        text.to_string()
+    } else {
+        format!("{}...", text.chars().take(MAX_DETAIL_CHARS).collect::<String>())
+    }
+}
+
+/// 根据反代的响应判断各阶段结果
+fn evaluate(protocol: SelfTestProtocol, status: u16, account_email: Option<&str>, body: &str) -> Vec<SelfTestStageResult> {
+    let mut stages = Vec::new();
+    let mut push = |stage: SelfTestStage, result: Result<Option<String>, String>| {
+        let ok = result.is_ok();
+        stages.push(SelfTestStageResult { stage, ok, detail: result.unwrap_or_else(Some) });
+        ok
+    };
+
+    if !push(
+        SelfTestStage::Auth,
+        if status == 401 { Err(tr("backend.errors.self_test.unauthorized", &[])) } else { Ok(None) },
+    ) {
+        return stages;
+    }
+
+    // 选中账号后处理器会带上 X-Account-Email；没有该头且失败表示没拿到账号
+    let account = match account_email {
+        Some(email) => Ok(Some(email.to_string())),
+        None if status == 429 || status == 503 => Err(tr("backend.errors.self_test.no_accounts", &[("status", &status.to_string()), ("body", &truncate(body))])),
+        None => Ok(None),
+    };
+    if !push(SelfTestStage::AccountSelection, account) {
+        return stages;
+    }
+
+    if !push(
+        SelfTestStage::Upstream,
+        if (200..300).contains(&status) { Ok(None) } else { Err(tr("backend.errors.self_test.upstream_error", &[("status", &status.to_string()), ("body", &truncate(body))])) },
+    ) {
+        return stages;
+    }
+
+    let conversion = serde_json::from_str::<Value>(body)
+        .map_err(|e| format!("响应不是有效的 JSON: {}", e))
+        .and_then(|json| protocol.check_response(&json))
+        .map(|_| None);
+    push(SelfTestStage::Conversion, conversion);
+    stages
+}
+
+async fn run_protocol(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    protocol: SelfTestProtocol,
+) -> ProtocolSelfTest {
+    let endpoint = protocol.path(model);
+    let started = Instant::now();
+    let request = protocol.authorize(client.post(format!("{}{}", base_url, endpoint)), api_key).json(&protocol.body(model));
+
+    let mut result = ProtocolSelfTest {
+        protocol,
+        endpoint,
+        passed: false,
+        status: None,
+        account_email: None,
+        mapped_model: None,
+        latency_ms: 0,
+        stages: Vec::new(),
+    };
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+            result.account_email = header("X-Account-Email");
+            result.mapped_model = header("X-Mapped-Model");
+            result.status = Some(status);
+            let body = response.text().await.unwrap_or_default();
+            result.stages = evaluate(protocol, status, result.account_email.as_deref(), &body);
+        }
+        Err(e) => {
+            // 连不上本机反代，鉴权之前就失败了
+            result.stages.push(SelfTestStageResult {
+                stage: SelfTestStage::Auth,
+                ok: false,
+                detail: Some(format!("无法连接反代服务: {}", e)),
+            });
+        }
+    }
+    result.latency_ms = started.elapsed().as_millis() as u64;
+    result.passed = result.stages.len() == 4 && result.stages.iter().all(|s| s.ok);
+    result
+}
+
+/// 对正在运行的反代执行自检
+pub async fn run(config: &ProxyConfig, model: Option<String>, started_proxy: bool) -> Result<SelfTestReport, String> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(SELF_TEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let base_url = format!("http://127.0.0.1:{}", config.port);
+    let model = model.filter(|m| !m.trim().is_empty()).unwrap_or_else(|| SELF_TEST_MODEL.to_string());
+
+    let protocols = futures::future::join_all(
+        SelfTestProtocol::ALL
+            .iter()
+            .map(|protocol| run_protocol(&client, &base_url, &config.api_key, &model, *protocol)),
+    )
+    .await;
+
+    Ok(SelfTestReport {
+        base_url,
+        model,
+        started_proxy,
+        passed: protocols.iter().all(|p| p.passed),
+        protocols,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stages(results: &[SelfTestStageResult]) -> Vec<(SelfTestStage, bool)> {
+        results.iter().map(|s| (s.stage, s.ok)).collect()
+    }
+
+    #[test]
+    fn test_stops_at_first_failed_stage() {
+        let auth = evaluate(SelfTestProtocol::Openai, 401, None, "Unauthorized");
+        assert_eq!(stages(&auth), [(SelfTestStage::Auth, false)]);
+
+        let no_account = evaluate(SelfTestProtocol::Claude, 503, None, r#"{"error":"No available accounts"}"#);
+        assert_eq!(stages(&no_account), [(SelfTestStage::Auth, true), (SelfTestStage::AccountSelection, false)]);
+
+        let upstream = evaluate(SelfTestProtocol::Gemini, 429, Some("a@example.com"), "quota exhausted");
+        assert_eq!(stages(&upstream).last(), Some(&(SelfTestStage::Upstream, false)));
+        assert_eq!(upstream[1].detail.as_deref(), Some("a@example.com"));
+    }
+
+    #[test]
+    fn test_conversion_checks_protocol_shape() {
+        let openai = r#"{"choices":[{"message":{"role":"assistant","content":"OK"}}]}"#;
+        let passed = evaluate(SelfTestProtocol::Openai, 200, Some("a@example.com"), openai);
+        assert!(passed.iter().all(|s| s.ok) && passed.len() == 4);
+
+        // Claude 入口返回了 OpenAI 格式
+        let wrong = evaluate(SelfTestProtocol::Claude, 200, Some("a@example.com"), openai);
+        assert_eq!(stages(&wrong).last(), Some(&(SelfTestStage::Conversion, false)));
+
+        let gemini = r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"OK"}]}}]}"#;
+        assert!(evaluate(SelfTestProtocol::Gemini, 200, None, gemini).iter().all(|s| s.ok));
+    }
+}
@@ -3,7 +3,6 @@ use axum::{
     extract::DefaultBodyLimit,
     http::StatusCode,
     response::{IntoResponse, Json, Response},
-    routing::{any, get, post},
     Router,
 };
 use std::sync::Arc;
@@ -30,6 +29,8 @@ pub struct AppState {
     pub zai_vision_mcp: Arc<crate::proxy::zai_vision_mcp::ZaiVisionMcpState>,
     pub monitor: Arc<crate::proxy::monitor::ProxyMonitor>,
     pub experimental: Arc<RwLock<crate::proxy::config::ExperimentalConfig>>,
+    pub security: Arc<RwLock<crate::proxy::ProxySecurityConfig>>, // 管理 MCP 切换默认账号池时即时生效
+    pub providers: Arc<RwLock<crate::proxy::providers::ProviderRegistry>>, // 账号池之外的上游后端
 }
 
 /// Axum 服务器实例
@@ -40,6 +41,8 @@ pub struct AxumServer {
     security_state: Arc<RwLock<crate::proxy::ProxySecurityConfig>>,
     zai_state: Arc<RwLock<crate::proxy::ZaiConfig>>,
     experimental: Arc<RwLock<crate::proxy::config::ExperimentalConfig>>,
+    upstream: Arc<crate::proxy::upstream::client::UpstreamClient>,
+    providers: Arc<RwLock<crate::proxy::providers::ProviderRegistry>>,
 }
 
 impl AxumServer {
@@ -53,6 +56,7 @@ impl AxumServer {
 
     /// 更新代理配置
     pub async fn update_proxy(&self, new_config: crate::proxy::config::UpstreamProxyConfig) {
+        self.upstream.set_proxy_config(new_config.clone());
         let mut proxy = self.proxy_state.write().await;
         *proxy = new_config;
         tracing::info!("上游代理配置已热更新");
@@ -75,7 +79,39 @@ impl AxumServer {
         *exp = config.experimental.clone();
         tracing::info!("实验性配置已热更新");
     }
+
+    pub fn update_upstream_client(&self, config: &crate::proxy::config::ProxyConfig) {
+        self.upstream.set_retry_config(config.upstream_retry.clone());
+        self.upstream.set_timeout_config(config.upstream_timeouts.clone());
+        self.upstream.set_pool_config(config.upstream_pool.clone());
+        self.upstream.set_tls_config(config.upstream_tls.clone());
+        tracing::info!("上游重试策略、超时、连接池与 TLS 配置已热更新");
+    }
+
+    pub async fn update_providers(&self, config: &crate::proxy::config::ProxyConfig) {
+        let registry = crate::proxy::providers::ProviderRegistry::from_config(&config.providers);
+        let count = registry.len();
+        *self.providers.write().await = registry;
+        tracing::info!("上游后端配置已热更新 ({} 个启用)", count);
+    }
+
+    /// 上游连接复用统计
+    pub fn upstream_connection_stats(&self) -> crate::proxy::upstream::client::ConnectionStats {
+        self.upstream.connection_stats()
+    }
+
+    /// 上游客户端 (压测时临时切换 mock 上游)
+    pub fn upstream_client(&self) -> Arc<crate::proxy::upstream::client::UpstreamClient> {
+        self.upstream.clone()
+    }
+
+    /// 各上游端点的延迟与错误率
+    pub fn upstream_endpoint_stats(&self) -> Vec<crate::proxy::upstream::endpoint_stats::EndpointSnapshot> {
+        self.upstream.endpoint_stats()
+    }
+
     /// 启动 Axum 服务器
+    #[allow(clippy::too_many_arguments)]
     pub async fn start(
         host: String,
         port: u16,
@@ -97,6 +133,10 @@ impl AxumServer {
 	        let zai_vision_mcp_state =
 	            Arc::new(crate::proxy::zai_vision_mcp::ZaiVisionMcpState::new());
 	        let experimental_state = Arc::new(RwLock::new(experimental_config));
+	        let upstream = Arc::new(crate::proxy::upstream::client::UpstreamClient::new(Some(
+	            upstream_proxy.clone(),
+	        )));
+	        let providers_state = Arc::new(RwLock::new(crate::proxy::providers::ProviderRegistry::default()));
 
 	        let state = AppState {
 	            token_manager: token_manager.clone(),
@@ -106,84 +146,23 @@ impl AxumServer {
                 std::collections::HashMap::new(),
             )),
             upstream_proxy: proxy_state.clone(),
-            upstream: Arc::new(crate::proxy::upstream::client::UpstreamClient::new(Some(
-                upstream_proxy.clone(),
-            ))),
+            upstream: upstream.clone(),
             zai: zai_state.clone(),
             provider_rr: provider_rr.clone(),
             zai_vision_mcp: zai_vision_mcp_state,
             monitor: monitor.clone(),
             experimental: experimental_state.clone(),
+            security: security_state.clone(),
+            providers: providers_state.clone(),
         };
 
 
-        // 构建路由 - 使用新架构的 handlers！
-        use crate::proxy::handlers;
-        // 构建路由
-        let app = Router::new()
-            // OpenAI Protocol
-            .route("/v1/models", get(handlers::openai::handle_list_models))
-            .route(
-                "/v1/chat/completions",
-                post(handlers::openai::handle_chat_completions),
-            )
-            .route(
-                "/v1/completions",
-                post(handlers::openai::handle_completions),
-            )
-            .route("/v1/responses", post(handlers::openai::handle_completions)) // 兼容 Codex CLI
-            .route(
-                "/v1/images/generations",
-                post(handlers::openai::handle_images_generations),
-            ) // 图像生成 API
-            .route(
-                "/v1/images/edits",
-                post(handlers::openai::handle_images_edits),
-            ) // 图像编辑 API
-            .route(
-                "/v1/audio/transcriptions",
-                post(handlers::audio::handle_audio_transcription),
-            ) // 音频转录 API (PR #311)
-            // Claude Protocol
-            .route("/v1/messages", post(handlers::claude::handle_messages))
-            .route(
-                "/v1/messages/count_tokens",
-                post(handlers::claude::handle_count_tokens),
-            )
-            .route(
-                "/v1/models/claude",
-                get(handlers::claude::handle_list_models),
-            )
-            // z.ai MCP (optional reverse-proxy)
-            .route(
-                "/mcp/web_search_prime/mcp",
-                any(handlers::mcp::handle_web_search_prime),
-            )
-	            .route(
-	                "/mcp/web_reader/mcp",
-	                any(handlers::mcp::handle_web_reader),
-	            )
-	            .route(
-	                "/mcp/zai-mcp-server/mcp",
-	                any(handlers::mcp::handle_zai_mcp_server),
-	            )
-	            // Gemini Protocol (Native)
-	            .route("/v1beta/models", get(handlers::gemini::handle_list_models))
-            // Handle both GET (get info) and POST (generateContent with colon) at the same route
-            .route(
-                "/v1beta/models/:model",
-                get(handlers::gemini::handle_get_model).post(handlers::gemini::handle_generate),
-            )
-            .route(
-                "/v1beta/models/:model/countTokens",
-                post(handlers::gemini::handle_count_tokens),
-            ) // Specific route priority
-            .route("/v1/models/detect", post(handlers::common::handle_detect_model))
-            .route("/internal/warmup", post(handlers::warmup::handle_warmup)) // 内部预热端点
-            .route("/v1/api/event_logging/batch", post(silent_ok_handler))
-            .route("/v1/api/event_logging", post(silent_ok_handler))
-            .route("/healthz", get(health_check_handler))
+        // 构建路由 (路由与 OpenAPI 文档共用 routes::api_routes 路由表)
+        let app = crate::proxy::routes::api_routes()
+            .into_iter()
+            .fold(Router::new(), |router, route| router.route(route.doc.path, route.handler))
             .layer(DefaultBodyLimit::max(100 * 1024 * 1024))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), crate::proxy::middleware::coalesce::coalesce_middleware))
             .layer(axum::middleware::from_fn_with_state(state.clone(), crate::proxy::middleware::monitor::monitor_middleware))
             .layer(TraceLayer::new_for_http())
             .layer(axum::middleware::from_fn_with_state(
@@ -197,7 +176,12 @@ impl AxumServer {
         let addr = format!("{}:{}", host, port);
         let listener = tokio::net::TcpListener::bind(&addr)
             .await
-            .map_err(|e| format!("地址 {} 绑定失败: {}", addr, e))?;
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AddrInUse => {
+                    crate::modules::i18n::tr("backend.errors.port_in_use", &[("port", &port.to_string())])
+                }
+                _ => crate::modules::i18n::tr("backend.errors.bind_failed", &[("addr", &addr), ("error", &e.to_string())]),
+            })?;
 
         tracing::info!("反代服务器启动在 http://{}", addr);
 
@@ -211,6 +195,8 @@ impl AxumServer {
             security_state,
             zai_state,
             experimental: experimental_state.clone(),
+            upstream,
+            providers: providers_state,
         };
 
         // 在新任务中启动服务器
@@ -223,9 +209,16 @@ impl AxumServer {
                 tokio::select! {
                     res = listener.accept() => {
                         match res {
-                            Ok((stream, _)) => {
+                            Ok((stream, remote_addr)) => {
+                                use tower::ServiceExt;
                                 let io = TokioIo::new(stream);
-                                let service = TowerToHyperService::new(app.clone());
+                                // 来源地址写入请求扩展，供认证中间件记录审计日志
+                                let service = TowerToHyperService::new(app.clone().map_request(
+                                    move |mut request: hyper::Request<hyper::body::Incoming>| {
+                                        request.extensions_mut().insert(axum::extract::ConnectInfo(remote_addr));
+                                        request
+                                    },
+                                ));
 
                                 tokio::task::spawn(async move {
                                     if let Err(err) = http1::Builder::new()
@@ -264,7 +257,7 @@ impl AxumServer {
 // ===== API 处理器 (旧代码已移除，由 src/proxy/handlers/* 接管) =====
 
 /// 健康检查处理器
-async fn health_check_handler() -> Response {
+pub(crate) async fn health_check_handler() -> Response {
     Json(serde_json::json!({
         "status": "ok"
     }))
@@ -272,6 +265,6 @@ async fn health_check_handler() -> Response {
 }
 
 /// 静默成功处理器 (用于拦截遥测日志等)
-async fn silent_ok_handler() -> Response {
+pub(crate) async fn silent_ok_handler() -> Response {
     StatusCode::OK.into_response()
 }
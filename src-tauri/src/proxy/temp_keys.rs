@@ -0,0 +1,58 @@
+// 临时 API Key
+//
+// 供扫码配置的移动端使用: 只在内存中保存，过期或应用退出后失效，不写入配置文件，
+// 也不具备主 Key 的管理权限。
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 默认有效期 (分钟)
+pub const DEFAULT_TTL_MINUTES: u32 = 60 * 24;
+/// 最长有效期 (分钟)
+pub const MAX_TTL_MINUTES: u32 = 60 * 24 * 30;
+const TEMP_KEY_PREFIX: &str = "sk-tmp-";
+
+/// key -> 过期时间 (unix 秒)
+static TEMP_KEYS: Lazy<Mutex<HashMap<String, i64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 签发一个临时 Key，返回 (key, 过期时间)
+pub fn issue(ttl_minutes: u32, now: i64) -> (String, i64) {
+    let ttl = ttl_minutes.clamp(1, MAX_TTL_MINUTES) as i64;
+    let key = format!("{}{}", TEMP_KEY_PREFIX, uuid::Uuid::new_v4().simple());
+    let expires_at = now + ttl * 60;
+    let mut keys = TEMP_KEYS.lock().unwrap();
+    keys.retain(|_, exp| *exp > now);
+    keys.insert(key.clone(), expires_at);
+    (key, expires_at)
+}
+
+/// Key 是否为未过期的临时 Key
+pub fn is_valid(key: &str, now: i64) -> bool {
+    key.starts_with(TEMP_KEY_PREFIX)
+        && TEMP_KEYS.lock().unwrap().get(key).is_some_and(|exp| *exp > now)
+}
+
+/// 吊销所有临时 Key，返回吊销数量
+pub fn revoke_all() -> usize {
+    let mut keys = TEMP_KEYS.lock().unwrap();
+    let count = keys.len();
+    keys.clear();
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_key_expires() {
+        let now = 1_700_000_000;
+        let (key, expires_at) = issue(10, now);
+        assert!(key.starts_with(TEMP_KEY_PREFIX));
+        assert_eq!(expires_at, now + 600);
+        assert!(is_valid(&key, now + 599));
+        assert!(!is_valid(&key, now + 600));
+        assert!(!is_valid("sk-tmp-unknown", now));
+    }
+}
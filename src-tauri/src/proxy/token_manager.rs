@@ -1,111 +1,368 @@
-// 移除冗余的顶层导入，因为这些在代码中已由 full path 或局部导入处理
+// 账号令牌管理
+//
+// 选择账号、Token 刷新、限流冷却与并发槽位由 antigravity-proxy-core 的 `TokenPool` 负责，
+// 这里在其之上提供账号存储: 从 SQLite 加载账号 (含配额保护)、热重载、隔离封禁账号、
+// 按配额刷新时间精确锁定限流账号，以及向前端 / Webhook 推送账号池事件。
 use dashmap::DashMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-
-use crate::proxy::rate_limit::RateLimitTracker;
-use crate::proxy::sticky_config::StickySessionConfig;
-
-#[derive(Debug, Clone)]
-pub struct ProxyToken {
-    pub account_id: String,
-    pub access_token: String,
-    pub refresh_token: String,
-    pub expires_in: i64,
-    pub timestamp: i64,
-    pub email: String,
-    pub account_path: PathBuf,  // 账号文件路径，用于更新
-    pub project_id: Option<String>,
-    pub subscription_tier: Option<String>, // "FREE" | "PRO" | "ULTRA"
-    pub remaining_quota: Option<i32>, // [FIX #563] Remaining quota for priority sorting
-    pub protected_models: HashSet<String>, // [NEW #621]
+use std::sync::{Arc, OnceLock};
+
+use futures::future::BoxFuture;
+
+use crate::models::UsageWindow;
+use crate::modules::i18n::tr;
+
+pub use antigravity_proxy_core::rotation::{
+    detect_account_suspension, is_pool_exhausted, AccountLease, ProxyToken, POOL_EXHAUSTED_ERROR,
+};
+pub use antigravity_proxy_core::token_pool::{AccountCooldownEvent, AccountStore, PoolExhaustedEvent, RefreshedToken, TokenPool};
+
+/// 账号存储外部修改检查间隔 (秒)
+const STORE_WATCH_INTERVAL_SECS: u64 = 3;
+/// 账号存储被外部修改并重新同步后推送的事件
+pub const STORE_CHANGED_EVENT: &str = "accounts://store-changed";
+/// 全部账号都在冷却或已隔离时推送的事件 (仅在进入耗尽状态时推送一次)
+pub const POOL_EXHAUSTED_EVENT: &str = "proxy://pool-exhausted";
+/// 账号冷却状态变化时推送的事件
+const ACCOUNT_COOLDOWN_EVENT: &str = "proxy://account-cooldown";
+
+/// 账号池的存储实现: 读写应用数据目录中的账号存储，并推送账号事件
+struct AppAccountStore {
+    data_dir: PathBuf,
+    app_handle: OnceLock<crate::utils::app::AppHandle>, // 用于推送账号冷却状态变化等事件
+}
+
+impl AppAccountStore {
+    /// 在阻塞线程池中访问账号存储 (SQLite 为同步接口，不占用异步工作线程)
+    async fn with_store<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&std::path::Path) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        let data_dir = self.data_dir.clone();
+        crate::utils::blocking::run(move || f(&data_dir)).await
+    }
+
+    async fn write_account(&self, account: &serde_json::Value) -> Result<(), String> {
+        let account = account.clone();
+        self.with_store(move |dir| crate::modules::account_store::write_account(dir, &account)).await
+    }
+
+    async fn update_account<F>(&self, account_id: &str, f: F) -> Result<serde_json::Value, String>
+    where
+        F: FnOnce(&mut serde_json::Value) + Send + 'static,
+    {
+        let account_id = account_id.to_string();
+        self.with_store(move |dir| crate::modules::account_store::update_account(dir, &account_id, f)).await
+    }
+
+    fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(app) = self.app_handle.get() {
+            crate::utils::app::emit(app, event, payload);
+        }
+    }
 }
 
+impl AccountStore for AppAccountStore {
+    fn refresh_token<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<RefreshedToken, String>> {
+        Box::pin(async move {
+            crate::modules::token_status::mark_refresh_started(&token.email);
+            let result = crate::modules::oauth::refresh_access_token(&token.refresh_token).await;
+            crate::modules::token_status::mark_refresh_finished(
+                &token.email,
+                result.as_ref().map(|_| ()).map_err(|e| e.as_str()),
+            );
+            result.map(|response| RefreshedToken {
+                access_token: response.access_token,
+                expires_in: response.expires_in,
+            })
+        })
+    }
 
+    /// 保存刷新后的 token 到账号存储
+    fn save_refreshed_token<'a>(
+        &'a self,
+        account_id: &'a str,
+        refreshed: &'a RefreshedToken,
+    ) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let now = chrono::Utc::now().timestamp();
+
+            let access_token = crate::modules::token_vault::encrypt_secret(&refreshed.access_token)
+                .unwrap_or_else(|_| refreshed.access_token.clone());
+            let expires_in = refreshed.expires_in;
+            self.update_account(account_id, move |content| {
+                content["token"]["access_token"] = serde_json::Value::String(access_token);
+                content["token"]["expires_in"] = serde_json::Value::Number(expires_in.into());
+                content["token"]["expiry_timestamp"] = serde_json::Value::Number((now + expires_in).into());
+            }).await?;
+            self.record_event(account_id, "refreshed", Some("proxy".to_string()));
+
+            tracing::debug!("已保存刷新后的 token 到账号 {}", account_id);
+            Ok(())
+        })
+    }
+
+    /// 按 TTL 缓存解析 project_id，过期后重新解析
+    fn resolve_project_id<'a>(&'a self, token: &'a ProxyToken) -> BoxFuture<'a, Result<(String, bool), String>> {
+        Box::pin(crate::proxy::project_resolver::resolve_project_id(
+            &token.account_id,
+            &token.access_token,
+            token.project_id.as_deref(),
+        ))
+    }
+
+    /// 保存 project_id 到账号存储
+    fn save_project_id<'a>(&'a self, account_id: &'a str, project_id: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let project_id = project_id.to_string();
+            self.update_account(account_id, move |content| {
+                content["token"]["project_id"] = serde_json::Value::String(project_id);
+            }).await?;
+
+            tracing::debug!("已保存 project_id 到账号 {}", account_id);
+            Ok(())
+        })
+    }
+
+    fn disable_account<'a>(&'a self, account_id: &'a str, reason: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let needs_reauth = reason.contains("invalid_grant");
+            let truncated = truncate_reason(reason, 800);
+            let disabled_reason = truncated.clone();
+            let content = self.update_account(account_id, move |content| {
+                let now = chrono::Utc::now().timestamp();
+                content["disabled"] = serde_json::Value::Bool(true);
+                content["disabled_at"] = serde_json::Value::Number(now.into());
+                content["disabled_reason"] = serde_json::Value::String(disabled_reason);
+                if needs_reauth {
+                    content["needs_reauth"] = serde_json::Value::Bool(true);
+                }
+            }).await?;
+            self.record_event(account_id, "disabled", Some(truncated.clone()));
+
+            if needs_reauth {
+                let event = crate::modules::account::ReauthRequiredEvent {
+                    account_id: account_id.to_string(),
+                    email: content["email"].as_str().unwrap_or_default().to_string(),
+                    reason: truncated,
+                };
+                crate::modules::webhooks::fire(crate::models::WebhookEvent::ReauthRequired, &event);
+                self.emit(crate::modules::account::REAUTH_REQUIRED_EVENT, event);
+            }
+
+            tracing::warn!("Account disabled: {} ({})", account_id, content["email"].as_str().unwrap_or_default());
+            Ok(())
+        })
+    }
+
+    /// 存储中已隔离的账号数 (隔离的账号不会加载到内存中)
+    fn count_quarantined(&self) -> BoxFuture<'_, usize> {
+        Box::pin(async move {
+            self.with_store(crate::modules::account_store::list_accounts)
+                .await
+                .map(|accounts| {
+                    accounts
+                        .iter()
+                        .filter(|a| a.get("quarantined").and_then(|v| v.as_bool()).unwrap_or(false))
+                        .count()
+                })
+                .unwrap_or(0)
+        })
+    }
+
+    /// 记录账号事件 (在运行时中转到阻塞线程池执行，不等待写入完成)
+    fn record_event(&self, account_id: &str, kind: &'static str, detail: Option<String>) {
+        let data_dir = self.data_dir.clone();
+        let account_id = account_id.to_string();
+        crate::utils::blocking::spawn(move || {
+            crate::modules::account_store::record_event(&data_dir, &account_id, kind, detail.as_deref())
+        });
+    }
+
+    fn cooldown_changed(&self, event: &AccountCooldownEvent) {
+        self.emit(ACCOUNT_COOLDOWN_EVENT, event);
+    }
+
+    fn pool_exhausted(&self, event: &PoolExhaustedEvent) {
+        crate::modules::webhooks::fire(crate::models::WebhookEvent::PoolExhausted, event);
+        self.emit(POOL_EXHAUSTED_EVENT, event);
+        crate::modules::notifications::pool_exhausted();
+    }
+}
+
+/// 反代使用的账号令牌管理器
+///
+/// 通过 `Deref` 直接提供 [`TokenPool`] 的选择、限流与会话接口
 pub struct TokenManager {
-    tokens: Arc<DashMap<String, ProxyToken>>,  // account_id -> ProxyToken
-    current_index: Arc<AtomicUsize>,
-    last_used_account: Arc<tokio::sync::Mutex<Option<(String, std::time::Instant)>>>,
-    data_dir: PathBuf,
-    rate_limit_tracker: Arc<RateLimitTracker>,  // 新增: 限流跟踪器
-    sticky_config: Arc<tokio::sync::RwLock<StickySessionConfig>>, // 新增：调度配置
-    session_accounts: Arc<DashMap<String, String>>, // 新增：会话与账号映射 (SessionID -> AccountID)
+    pool: Arc<TokenPool>,
+    store: Arc<AppAccountStore>,
+    store_fingerprints: DashMap<String, u64>, // 最近一次同步时各账号的存储内容指纹 (热重载)
+}
+
+impl std::ops::Deref for TokenManager {
+    type Target = TokenPool;
+
+    fn deref(&self) -> &TokenPool {
+        &self.pool
+    }
 }
 
 impl TokenManager {
     /// 创建新的 TokenManager
     pub fn new(data_dir: PathBuf) -> Self {
+        let store = Arc::new(AppAccountStore { data_dir, app_handle: OnceLock::new() });
         Self {
-            tokens: Arc::new(DashMap::new()),
-            current_index: Arc::new(AtomicUsize::new(0)),
-            last_used_account: Arc::new(tokio::sync::Mutex::new(None)),
-            data_dir,
-            rate_limit_tracker: Arc::new(RateLimitTracker::new()),
-            sticky_config: Arc::new(tokio::sync::RwLock::new(StickySessionConfig::default())),
-            session_accounts: Arc::new(DashMap::new()),
+            pool: Arc::new(TokenPool::new(store.clone())),
+            store,
+            store_fingerprints: DashMap::new(),
         }
     }
-    
-    /// 从主应用账号目录加载所有账号
-    pub async fn load_accounts(&self) -> Result<usize, String> {
-        let accounts_dir = self.data_dir.join("accounts");
-        
-        if !accounts_dir.exists() {
-            return Err(format!("账号目录不存在: {:?}", accounts_dir));
-        }
 
-        // Reload should reflect current on-disk state (accounts can be added/removed/disabled).
-        self.tokens.clear();
-        self.current_index.store(0, Ordering::SeqCst);
-        {
-            let mut last_used = self.last_used_account.lock().await;
-            *last_used = None;
-        }
-        
-        let entries = std::fs::read_dir(&accounts_dir)
-            .map_err(|e| format!("读取账号目录失败: {}", e))?;
-        
-        let mut count = 0;
-        
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-            let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+    /// 绑定 AppHandle,用于向前端推送账号冷却/恢复事件
+    pub fn with_app_handle(self, app_handle: crate::utils::app::AppHandle) -> Self {
+        let _ = self.store.app_handle.set(app_handle);
+        self
+    }
+
+    async fn with_store<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&std::path::Path) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.store.with_store(f).await
+    }
+
+    /// 启动冷却监视任务 (见 [`TokenPool::start_cooldown_watcher`])
+    pub fn start_cooldown_watcher(&self) {
+        self.pool.start_cooldown_watcher();
+    }
+
+    /// 在后台刷新即将过期的 Token (见 [`TokenPool::start_startup_refresh`])
+    pub fn start_startup_refresh(&self) {
+        self.pool.start_startup_refresh();
+    }
+
+    /// 启动账号存储监视任务: 数据库文件变化时 (脚本或另一实例修改) 增量同步账号池
+    ///
+    /// 与冷却监视任务一样只持有 Weak 引用
+    pub fn start_store_watcher(self: &Arc<Self>) {
+        let weak = Arc::downgrade(self);
+        let data_dir = self.store.data_dir.clone();
+        tokio::spawn(async move {
+            let mut last_modified = crate::modules::account_store::modified_at(&data_dir);
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(STORE_WATCH_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let Some(manager) = weak.upgrade() else { break };
+                let modified = crate::modules::account_store::modified_at(&data_dir);
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+                match manager.sync_from_store().await {
+                    Ok(changed) if !changed.is_empty() => {
+                        tracing::info!("账号存储已变更，已重新同步 {} 个账号", changed.len());
+                        manager.store.emit(STORE_CHANGED_EVENT, &changed);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::debug!("Failed to sync accounts from store: {}", e),
+                }
+            }
+        });
+    }
+
+    /// 与账号存储对比指纹，只重新加载新增/变更的账号并移除已删除的账号
+    ///
+    /// 不影响其余账号的轮换位置与会话绑定，返回发生变化的 account_id 列表
+    pub async fn sync_from_store(&self) -> Result<Vec<String>, String> {
+        let current = self.with_store(crate::modules::account_store::fingerprints).await?;
+        let mut changed = Vec::new();
+
+        for (account_id, fingerprint) in &current {
+            if self.store_fingerprints.get(account_id).map(|f| *f) == Some(*fingerprint) {
                 continue;
             }
-            
+            changed.push(account_id.clone());
+            let id = account_id.clone();
+            let stored = self.with_store(move |dir| crate::modules::account_store::read_account(dir, &id)).await?;
+            let loaded = match stored {
+                Some(account) => self.load_single_account(account).await.unwrap_or(None),
+                None => None,
+            };
+            match loaded {
+                Some(token) => self.insert_token(token),
+                None => self.remove_account(account_id).await,
+            }
+        }
+
+        let removed: Vec<String> = self.store_fingerprints.iter()
+            .map(|e| e.key().clone())
+            .filter(|id| !current.contains_key(id))
+            .collect();
+        for account_id in removed {
+            self.remove_account(&account_id).await;
+            changed.push(account_id);
+        }
+
+        self.store_fingerprints.clear();
+        for (account_id, fingerprint) in current {
+            self.store_fingerprints.insert(account_id, fingerprint);
+        }
+        Ok(changed)
+    }
+
+    /// 从主应用账号存储加载所有账号
+    pub async fn load_accounts(&self) -> Result<usize, String> {
+        let (accounts, fingerprints) = self
+            .with_store(|dir| {
+                Ok((
+                    crate::modules::account_store::list_accounts(dir)?,
+                    crate::modules::account_store::fingerprints(dir)?,
+                ))
+            })
+            .await?;
+
+        let mut tokens = Vec::new();
+
+        for account in accounts {
+            let id = account.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
             // 尝试加载账号
-            match self.load_single_account(&path).await {
-                Ok(Some(token)) => {
-                    let account_id = token.account_id.clone();
-                    self.tokens.insert(account_id, token);
-                    count += 1;
-                },
+            match self.load_single_account(account).await {
+                Ok(Some(token)) => tokens.push(token),
                 Ok(None) => {
                     // 跳过无效账号
                 },
                 Err(e) => {
-                    tracing::debug!("加载账号失败 {:?}: {}", path, e);
+                    tracing::debug!("加载账号失败 {}: {}", id, e);
                 }
             }
         }
+
+        // Reload should reflect current stored state (accounts can be added/removed/disabled).
+        let count = tokens.len();
+        self.pool.replace_tokens(tokens).await;
+
+        self.store_fingerprints.clear();
+        for (account_id, fingerprint) in fingerprints {
+            self.store_fingerprints.insert(account_id, fingerprint);
+        }
         
         Ok(count)
     }
 
     /// 重新加载指定账号（用于配额更新后的实时同步）
     pub async fn reload_account(&self, account_id: &str) -> Result<(), String> {
-        let path = self.data_dir.join("accounts").join(format!("{}.json", account_id));
-        if !path.exists() {
-            return Err(format!("账号文件不存在: {:?}", path));
-        }
+        let id = account_id.to_string();
+        let account = self
+            .with_store(move |dir| crate::modules::account_store::read_account(dir, &id))
+            .await?
+            .ok_or_else(|| format!("账号不存在: {}", account_id))?;
 
-        match self.load_single_account(&path).await {
+        match self.load_single_account(account).await {
             Ok(Some(token)) => {
-                self.tokens.insert(account_id.to_string(), token);
+                self.insert_token(token);
                 Ok(())
             }
             Ok(None) => Err("账号加载失败".to_string()),
@@ -113,18 +370,14 @@ impl TokenManager {
         }
     }
 
+
     /// 重新加载所有账号
     pub async fn reload_all_accounts(&self) -> Result<usize, String> {
         self.load_accounts().await
     }
     
     /// 加载单个账号
-    async fn load_single_account(&self, path: &PathBuf) -> Result<Option<ProxyToken>, String> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| format!("读取文件失败: {}", e))?;
-        
-        let mut account: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| format!("解析 JSON 失败: {}", e))?;
+    async fn load_single_account(&self, mut account: serde_json::Value) -> Result<Option<ProxyToken>, String> {
 
         if account
             .get("disabled")
@@ -132,8 +385,22 @@ impl TokenManager {
             .unwrap_or(false)
         {
             tracing::debug!(
-                "Skipping disabled account file: {:?} (email={})",
-                path,
+                "Skipping disabled account: {} (email={})",
+                account.get("id").and_then(|v| v.as_str()).unwrap_or("<unknown>"),
+                account.get("email").and_then(|v| v.as_str()).unwrap_or("<unknown>")
+            );
+            return Ok(None);
+        }
+
+        // 被上游封禁/停用而隔离的账号
+        if account
+            .get("quarantined")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            tracing::debug!(
+                "Skipping quarantined account: {} (email={})",
+                account.get("id").and_then(|v| v.as_str()).unwrap_or("<unknown>"),
                 account.get("email").and_then(|v| v.as_str()).unwrap_or("<unknown>")
             );
             return Ok(None);
@@ -141,10 +408,10 @@ impl TokenManager {
 
         // 【新增】配额保护检查 - 在检查 proxy_disabled 之前执行
         // 这样可以在加载时自动恢复配额已恢复的账号
-        if self.check_and_protect_quota(&mut account, path).await {
+        if self.check_and_protect_quota(&mut account).await {
             tracing::debug!(
-                "Account skipped due to quota protection: {:?} (email={})",
-                path,
+                "Account skipped due to quota protection: {} (email={})",
+                account.get("id").and_then(|v| v.as_str()).unwrap_or("<unknown>"),
                 account.get("email").and_then(|v| v.as_str()).unwrap_or("<unknown>")
             );
             return Ok(None);
@@ -157,8 +424,8 @@ impl TokenManager {
             .unwrap_or(false)
         {
             tracing::debug!(
-                "Skipping proxy-disabled account file: {:?} (email={})",
-                path,
+                "Skipping proxy-disabled account: {} (email={})",
+                account.get("id").and_then(|v| v.as_str()).unwrap_or("<unknown>"),
                 account.get("email").and_then(|v| v.as_str()).unwrap_or("<unknown>")
             );
             return Ok(None);
@@ -175,13 +442,13 @@ impl TokenManager {
         let token_obj = account["token"].as_object()
             .ok_or("缺少 token 字段")?;
         
-        let access_token = token_obj["access_token"].as_str()
-            .ok_or("缺少 access_token")?
-            .to_string();
+        let access_token = crate::modules::token_vault::decrypt_secret(
+            token_obj["access_token"].as_str().ok_or_else(|| tr("backend.errors.missing_field", &[("field", "access_token")]))?
+        )?;
         
-        let refresh_token = token_obj["refresh_token"].as_str()
-            .ok_or("缺少 refresh_token")?
-            .to_string();
+        let refresh_token = crate::modules::token_vault::decrypt_secret(
+            token_obj["refresh_token"].as_str().ok_or_else(|| tr("backend.errors.missing_field", &[("field", "refresh_token")]))?
+        )?;
         
         let expires_in = token_obj["expires_in"].as_i64()
             .ok_or("缺少 expires_in")?;
@@ -216,6 +483,44 @@ impl TokenManager {
                     .collect()
             })
             .unwrap_or_default();
+
+        let priority = account.get("priority")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0) as i32;
+
+        let usage_windows: Vec<UsageWindow> = account.get("usage_windows")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let egress_proxy = account.get("egress_proxy")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        let project_id_override = account.get("project_id_override")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        let projects: Vec<String> = account.get("projects")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let tags: Vec<String> = account.get("tags")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        // 账号权益: 配额接口返回的模型即该账号可访问的模型
+        let available_models: HashSet<String> = account.get("quota")
+            .and_then(|q| q.get("models"))
+            .and_then(|m| m.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
         
         Ok(Some(ProxyToken {
             account_id,
@@ -224,20 +529,26 @@ impl TokenManager {
             expires_in,
             timestamp,
             email,
-            account_path: path.clone(),
             project_id,
             subscription_tier,
             remaining_quota,
             protected_models,
+            available_models,
+            usage_windows,
+            egress_proxy,
+            project_id_override,
+            projects,
+            tags,
+            priority,
         }))
     }
 
     
     /// 检查账号是否应该被配额保护
     /// 如果配额低于阈值，自动禁用账号并返回 true
-    async fn check_and_protect_quota(&self, account_json: &mut serde_json::Value, account_path: &PathBuf) -> bool {
+    async fn check_and_protect_quota(&self, account_json: &mut serde_json::Value) -> bool {
         // 1. 加载配额保护配置
-        let config = match crate::modules::config::load_app_config() {
+        let config = match crate::modules::config::load_app_config_async().await {
             Ok(cfg) => cfg.quota_protection,
             Err(_) => return false, // 配置加载失败，跳过保护
         };
@@ -265,7 +576,7 @@ impl TokenManager {
         if is_proxy_disabled {
             if reason == "quota_protection" {
                 // [兼容性 #621] 如果是被旧版账号级保护禁用的，尝试恢复并转为模型级
-                return self.check_and_restore_quota(account_json, account_path, &quota, &config).await;
+                return self.check_and_restore_quota(account_json, &quota, &config).await;
             }
             return true; // 其他原因禁用，跳过加载
         }
@@ -293,20 +604,18 @@ impl TokenManager {
 
             if percentage <= threshold {
                 // 触发保护 (Issue #621 改为模型级)
-                if self.trigger_quota_protection(account_json, &account_id, account_path, percentage, threshold, name).await.unwrap_or(false) {
+                if self.trigger_quota_protection(account_json, &account_id, percentage, threshold, name).await.unwrap_or(false) {
                     changed = true;
                 }
             } else {
                 // 尝试恢复 (如果之前受限)
                 let protected_models = account_json.get("protected_models").and_then(|v| v.as_array());
-                let is_protected = protected_models.map_or(false, |arr| {
+                let is_protected = protected_models.is_some_and(|arr| {
                     arr.iter().any(|m| m.as_str() == Some(name))
                 });
 
-                if is_protected {
-                    if self.restore_quota_protection(account_json, &account_id, account_path, name).await.unwrap_or(false) {
-                        changed = true;
-                    }
+                if is_protected && self.restore_quota_protection(account_json, &account_id, name).await.unwrap_or(false) {
+                    changed = true;
                 }
             }
         }
@@ -321,10 +630,7 @@ impl TokenManager {
     /// 计算账号的最大剩余配额百分比（用于排序）
     /// 返回值: Option<i32> (max_percentage)
     fn calculate_quota_stats(&self, quota: &serde_json::Value) -> Option<i32> {
-        let models = match quota.get("models").and_then(|m| m.as_array()) {
-            Some(m) => m,
-            None => return None,
-        };
+        let models = quota.get("models").and_then(|m| m.as_array())?;
         
         let mut max_percentage = 0;
         let mut has_data = false;
@@ -352,7 +658,6 @@ impl TokenManager {
         &self,
         account_json: &mut serde_json::Value,
         account_id: &str,
-        account_path: &PathBuf,
         current_val: i32,
         threshold: i32,
         model_name: &str,
@@ -373,9 +678,8 @@ impl TokenManager {
                 account_id, model_name, current_val, threshold
             );
             
-            // 3. 写入磁盘
-            std::fs::write(account_path, serde_json::to_string_pretty(account_json).unwrap())
-                .map_err(|e| format!("写入文件失败: {}", e))?;
+            // 3. 写入账号存储
+            self.store.write_account(account_json).await?;
             
             return Ok(true);
         }
@@ -387,7 +691,6 @@ impl TokenManager {
     async fn check_and_restore_quota(
         &self,
         account_json: &mut serde_json::Value,
-        account_path: &PathBuf,
         quota: &serde_json::Value,
         config: &crate::models::QuotaProtectionConfig,
     ) -> bool {
@@ -419,7 +722,7 @@ impl TokenManager {
         
         account_json["protected_models"] = serde_json::Value::Array(protected_list);
         
-        let _ = std::fs::write(account_path, serde_json::to_string_pretty(account_json).unwrap());
+        let _ = self.store.write_account(account_json).await;
         
         false // 返回 false 表示现在已可以尝试加载该账号（模型级过滤会在 get_token 时发生）
     }
@@ -430,7 +733,6 @@ impl TokenManager {
         &self,
         account_json: &mut serde_json::Value,
         account_id: &str,
-        account_path: &PathBuf,
         model_name: &str,
     ) -> Result<bool, String> {
         if let Some(arr) = account_json.get_mut("protected_models").and_then(|v| v.as_array_mut()) {
@@ -439,8 +741,7 @@ impl TokenManager {
             
             if arr.len() < original_len {
                 tracing::info!("账号 {} 的模型 {} 配额已恢复，移出保护列表", account_id, model_name);
-                std::fs::write(account_path, serde_json::to_string_pretty(account_json).unwrap())
-                    .map_err(|e| format!("写入文件失败: {}", e))?;
+                self.store.write_account(account_json).await?;
                 return Ok(true);
             }
         }
@@ -448,639 +749,80 @@ impl TokenManager {
         Ok(false)
     }
 
-    
-    /// 获取当前可用的 Token（支持粘性会话与智能调度）
-    /// 参数 `quota_group` 用于区分 "claude" vs "gemini" 组
-    /// 参数 `force_rotate` 为 true 时将忽略锁定，强制切换账号
-    /// 参数 `session_id` 用于跨请求维持会话粘性
-    /// 参数 `target_model` 用于检查配额保护 (Issue #621)
-    pub async fn get_token(
-        &self, 
-        quota_group: &str, 
-        force_rotate: bool, 
-        session_id: Option<&str>,
-        target_model: &str,
-    ) -> Result<(String, String, String), String> {
-        // 【优化 Issue #284】添加 5 秒超时，防止死锁
-        let timeout_duration = std::time::Duration::from_secs(5);
-        match tokio::time::timeout(timeout_duration, self.get_token_internal(quota_group, force_rotate, session_id, target_model)).await {
-            Ok(result) => result,
-            Err(_) => Err("Token acquisition timeout (5s) - system too busy or deadlock detected".to_string()),
-        }
-    }
-
-    /// 内部实现：获取 Token 的核心逻辑
-    async fn get_token_internal(
-        &self, 
-        quota_group: &str, 
-        force_rotate: bool, 
-        session_id: Option<&str>,
-        target_model: &str,
-    ) -> Result<(String, String, String), String> {
-        let mut tokens_snapshot: Vec<ProxyToken> = self.tokens.iter().map(|e| e.value().clone()).collect();
-        let total = tokens_snapshot.len();
-        if total == 0 {
-            return Err("Token pool is empty".to_string());
-        }
-
-        // ===== 【优化】根据订阅等级和剩余配额排序 =====
-        // [FIX #563] 优先级: ULTRA > PRO > FREE, 同tier内优先高配额账号
-        // 理由: ULTRA/PRO 重置快，优先消耗；FREE 重置慢，用于兜底
-        //       高配額账号优先使用，避免低配额账号被用光
-        tokens_snapshot.sort_by(|a, b| {
-            let tier_priority = |tier: &Option<String>| match tier.as_deref() {
-                Some("ULTRA") => 0,
-                Some("PRO") => 1,
-                Some("FREE") => 2,
-                _ => 3,
-            };
-            
-            // First: compare by subscription tier
-            let tier_cmp = tier_priority(&a.subscription_tier)
-                .cmp(&tier_priority(&b.subscription_tier));
-            
-            if tier_cmp != std::cmp::Ordering::Equal {
-                return tier_cmp;
-            }
-            
-            // [FIX #563] Second: compare by remaining quota percentage (higher is better)
-            // Accounts with unknown/zero percentage go last within their tier
-            let quota_a = a.remaining_quota.unwrap_or(0);
-            let quota_b = b.remaining_quota.unwrap_or(0);
-            quota_b.cmp(&quota_a)  // Descending: higher percentage first
-        });
-
-
-        // 0. 读取当前调度配置
-        let scheduling = self.sticky_config.read().await.clone();
-        use crate::proxy::sticky_config::SchedulingMode;
-
-        // 【优化 Issue #284】将锁操作移到循环外，避免重复获取锁
-        // 预先获取 last_used_account 的快照，避免在循环中多次加锁
-        let last_used_account_id = if quota_group != "image_gen" {
-            let last_used = self.last_used_account.lock().await;
-            last_used.clone()
-        } else {
-            None
+    /// 检查上游错误是否表明账号已被封禁/停用，是则隔离该账号 (参数可为 email 或 account_id)
+    ///
+    /// 返回 true 表示账号已被隔离，调用方应换号重试
+    pub async fn quarantine_if_suspended(&self, key: &str, status: u16, error_body: &str) -> bool {
+        let Some(signature) = detect_account_suspension(status, error_body) else {
+            return false;
         };
+        let account_id = self.email_to_account_id(key).unwrap_or_else(|| key.to_string());
+        let reason = truncate_reason(&format!("[{}] HTTP {}: {}", signature, status, error_body), 800);
 
-        let mut attempted: HashSet<String> = HashSet::new();
-        let mut last_error: Option<String> = None;
-        let mut need_update_last_used: Option<(String, std::time::Instant)> = None;
-
-        for attempt in 0..total {
-            let rotate = force_rotate || attempt > 0;
-
-            // ===== 【核心】粘性会话与智能调度逻辑 =====
-            let mut target_token: Option<ProxyToken> = None;
-            
-            // 模式 A: 粘性会话处理 (CacheFirst 或 Balance 且有 session_id)
-            if !rotate && session_id.is_some() && scheduling.mode != SchedulingMode::PerformanceFirst {
-                let sid = session_id.unwrap();
-                
-                // 1. 检查会话是否已绑定账号
-                if let Some(bound_id) = self.session_accounts.get(sid).map(|v| v.clone()) {
-                    // 【修复】先通过 account_id 找到对应的账号，获取其 email
-                    // 2. 转换 email -> account_id 检查绑定的账号是否限流
-                    if let Some(bound_token) = tokens_snapshot.iter().find(|t| t.account_id == bound_id) {
-                        let key = self.email_to_account_id(&bound_token.email).unwrap_or_else(|| bound_token.account_id.clone());
-                        let reset_sec = self.rate_limit_tracker.get_remaining_wait(&key);
-                        if reset_sec > 0 {
-                            // 【修复 Issue #284】立即解绑并切换账号，不再阻塞等待
-                            // 原因：阻塞等待会导致并发请求时客户端 socket 超时 (UND_ERR_SOCKET)
-                            tracing::debug!(
-                                "Sticky Session: Bound account {} is rate-limited ({}s), unbinding and switching.",
-                                bound_token.email, reset_sec
-                            );
-                            self.session_accounts.remove(sid);
-                        } else if !attempted.contains(&bound_id) && !bound_token.protected_models.contains(target_model) {
-                            // 3. 账号可用且未被标记为尝试失败，优先复用
-                            tracing::debug!("Sticky Session: Successfully reusing bound account {} for session {}", bound_token.email, sid);
-                            target_token = Some(bound_token.clone());
-                        } else if bound_token.protected_models.contains(target_model) {
-                            tracing::debug!("Sticky Session: Bound account {} is quota-protected for model {}, unbinding and switching.", bound_token.email, target_model);
-                            self.session_accounts.remove(sid);
-                        }
-                    } else {
-                        // 绑定的账号已不存在（可能被删除），解绑
-                        tracing::debug!("Sticky Session: Bound account not found for session {}, unbinding", sid);
-                        self.session_accounts.remove(sid);
-                    }
-                }
-            }
-
-            // 模式 B: 原子化 60s 全局锁定 (针对无 session_id 情况的默认保护)
-            if target_token.is_none() && !rotate && quota_group != "image_gen" {
-                // 【优化】使用预先获取的快照，不再在循环内加锁
-                if let Some((account_id, last_time)) = &last_used_account_id {
-                    // [FIX #3] 60s 锁定逻辑应检查 `attempted` 集合，避免重复尝试失败的账号
-                    if last_time.elapsed().as_secs() < 60 && !attempted.contains(account_id) {
-                        if let Some(found) = tokens_snapshot.iter().find(|t| &t.account_id == account_id) {
-                            // 【修复】检查限流状态和配额保护，避免复用已被锁定的账号
-                            if !self.is_rate_limited_by_account_id(&found.account_id) && !found.protected_models.contains(target_model) { // Changed to account_id
-                                tracing::debug!("60s Window: Force reusing last account: {}", found.email);
-                                target_token = Some(found.clone());
-                            } else {
-                                if self.is_rate_limited_by_account_id(&found.account_id) { // Changed to account_id
-                                    tracing::debug!("60s Window: Last account {} is rate-limited, skipping", found.email);
-                                } else {
-                                    tracing::debug!("60s Window: Last account {} is quota-protected for model {}, skipping", found.email, target_model);
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // 若无锁定，则轮询选择新账号
-                if target_token.is_none() {
-                    let start_idx = self.current_index.fetch_add(1, Ordering::SeqCst) % total;
-                    for offset in 0..total {
-                        let idx = (start_idx + offset) % total;
-                        let candidate = &tokens_snapshot[idx];
-                        if attempted.contains(&candidate.account_id) {
-                            continue;
-                        }
-
-                        // 【新增 #621】模型级限流检查
-                        if candidate.protected_models.contains(target_model) {
-                            tracing::debug!("Account {} is quota-protected for model {}, skipping", candidate.email, target_model);
-                            continue;
-                        }
-
-                        // 【新增】主动避开限流或 5xx 锁定的账号 (来自 PR #28 的高可用思路)
-                        if self.is_rate_limited_by_account_id(&candidate.account_id) { // Changed to account_id
-                            continue;
-                        }
-
-                        target_token = Some(candidate.clone());
-                        // 【优化】标记需要更新，稍后统一写回
-                        need_update_last_used = Some((candidate.account_id.clone(), std::time::Instant::now()));
-                        
-                        // 如果是会话首次分配且需要粘性，在此建立绑定
-                        if let Some(sid) = session_id {
-                            if scheduling.mode != SchedulingMode::PerformanceFirst {
-                                self.session_accounts.insert(sid.to_string(), candidate.account_id.clone());
-                                tracing::debug!("Sticky Session: Bound new account {} to session {}", candidate.email, sid);
-                            }
-                        }
-                        break;
-                    }
-                }
-            } else if target_token.is_none() {
-                // 模式 C: 纯轮询模式 (Round-robin) 或强制轮换
-                let start_idx = self.current_index.fetch_add(1, Ordering::SeqCst) % total;
-                for offset in 0..total {
-                    let idx = (start_idx + offset) % total;
-                    let candidate = &tokens_snapshot[idx];
-                    if attempted.contains(&candidate.account_id) {
-                        continue;
-                    }
-
-                    // 【新增 #621】模型级限流检查
-                    if candidate.protected_models.contains(target_model) {
-                        continue;
-                    }
-
-                    // 【新增】主动避开限流或 5xx 锁定的账号
-                    if self.is_rate_limited_by_account_id(&candidate.account_id) { // Changed to account_id
-                        continue;
-                    }
-
-                    target_token = Some(candidate.clone());
-                    
-                    if rotate {
-                        tracing::debug!("Force Rotation: Switched to account: {}", candidate.email);
-                    }
-                    break;
-                }
-            }
-            
-            let mut token = match target_token {
-                Some(t) => t,
-                None => {
-                    // 乐观重置策略: 双层防护机制
-                    // 当所有账号都无法选择时,可能是时序竞争导致的状态不同步
-                    
-                    // 计算最短等待时间
-                    let min_wait = tokens_snapshot.iter()
-                        .filter_map(|t| self.rate_limit_tracker.get_reset_seconds(&t.account_id))
-                        .min();
-                    
-                    // Layer 1: 如果最短等待时间 <= 2秒,执行缓冲延迟
-                    if let Some(wait_sec) = min_wait {
-                        if wait_sec <= 2 {
-                            tracing::warn!(
-                                "All accounts rate-limited but shortest wait is {}s. Applying 500ms buffer for state sync...",
-                                wait_sec
-                            );
-                            
-                            // 缓冲延迟 500ms
-                            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                            
-                            // 重新尝试选择账号
-                            let retry_token = tokens_snapshot.iter()
-                                .find(|t| !attempted.contains(&t.account_id) && !self.is_rate_limited_by_account_id(&t.account_id)); // Changed to account_id
-                            
-                            if let Some(t) = retry_token {
-                                tracing::info!("✅ Buffer delay successful! Found available account: {}", t.email);
-                                t.clone()
-                            } else {
-                                // Layer 2: 缓冲后仍无可用账号,执行乐观重置
-                                tracing::warn!(
-                                    "Buffer delay failed. Executing optimistic reset for all {} accounts...",
-                                    tokens_snapshot.len()
-                                );
-                                
-                                // 清除所有限流记录
-                                self.rate_limit_tracker.clear_all();
-                                
-                                // 再次尝试选择账号
-                                let final_token = tokens_snapshot.iter()
-                                    .find(|t| !attempted.contains(&t.account_id));
-                                
-                                if let Some(t) = final_token {
-                                    tracing::info!("✅ Optimistic reset successful! Using account: {}", t.email);
-                                    t.clone()
-                                } else {
-                                    // 所有策略都失败,返回错误
-                                    return Err(
-                                        "All accounts failed after optimistic reset. Please check account health.".to_string()
-                                    );
-                                }
-                            }
-                        } else {
-                            // 等待时间 > 2秒,正常返回错误
-                            return Err(format!("All accounts are currently limited. Please wait {}s.", wait_sec));
-                        }
-                    } else {
-                        // 无限流记录但仍无可用账号,可能是其他问题
-                        return Err("All accounts failed or unhealthy.".to_string());
-                    }
-                }
-            };
-
-        
-            // 3. 检查 token 是否过期（提前5分钟刷新）
-            let now = chrono::Utc::now().timestamp();
-            if now >= token.timestamp - 300 {
-                tracing::debug!("账号 {} 的 token 即将过期，正在刷新...", token.email);
-
-                // 调用 OAuth 刷新 token
-                match crate::modules::oauth::refresh_access_token(&token.refresh_token).await {
-                    Ok(token_response) => {
-                        tracing::debug!("Token 刷新成功！");
-
-                        // 更新本地内存对象供后续使用
-                        token.access_token = token_response.access_token.clone();
-                        token.expires_in = token_response.expires_in;
-                        token.timestamp = now + token_response.expires_in;
-
-                        // 同步更新跨线程共享的 DashMap
-                        if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
-                            entry.access_token = token.access_token.clone();
-                            entry.expires_in = token.expires_in;
-                            entry.timestamp = token.timestamp;
-                        }
-
-                        // 同步落盘（避免重启后继续使用过期 timestamp 导致频繁刷新）
-                        if let Err(e) = self.save_refreshed_token(&token.account_id, &token_response).await {
-                            tracing::debug!("保存刷新后的 token 失败 ({}): {}", token.email, e);
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Token 刷新失败 ({}): {}，尝试下一个账号", token.email, e);
-                        if e.contains("\"invalid_grant\"") || e.contains("invalid_grant") {
-                            tracing::error!(
-                                "Disabling account due to invalid_grant ({}): refresh_token likely revoked/expired",
-                                token.email
-                            );
-                            let _ = self
-                                .disable_account(&token.account_id, &format!("invalid_grant: {}", e))
-                                .await;
-                            self.tokens.remove(&token.account_id);
-                        }
-                        // Avoid leaking account emails to API clients; details are still in logs.
-                        last_error = Some(format!("Token refresh failed: {}", e));
-                        attempted.insert(token.account_id.clone());
-
-                        // 【优化】标记需要清除锁定，避免在循环内加锁
-                        if quota_group != "image_gen" {
-                            if matches!(&last_used_account_id, Some((id, _)) if id == &token.account_id) {
-                                need_update_last_used = Some((String::new(), std::time::Instant::now())); // 空字符串表示需要清除
-                            }
-                        }
-                        continue;
-                    }
-                }
-            }
-
-            // 4. 确保有 project_id
-            let project_id = if let Some(pid) = &token.project_id {
-                pid.clone()
-            } else {
-                tracing::debug!("账号 {} 缺少 project_id，尝试获取...", token.email);
-                match crate::proxy::project_resolver::fetch_project_id(&token.access_token).await {
-                    Ok(pid) => {
-                        if let Some(mut entry) = self.tokens.get_mut(&token.account_id) {
-                            entry.project_id = Some(pid.clone());
-                        }
-                        let _ = self.save_project_id(&token.account_id, &pid).await;
-                        pid
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to fetch project_id for {}: {}", token.email, e);
-                        last_error = Some(format!("Failed to fetch project_id for {}: {}", token.email, e));
-                        attempted.insert(token.account_id.clone());
-
-                        // 【优化】标记需要清除锁定，避免在循环内加锁
-                        if quota_group != "image_gen" {
-                            if matches!(&last_used_account_id, Some((id, _)) if id == &token.account_id) {
-                                need_update_last_used = Some((String::new(), std::time::Instant::now())); // 空字符串表示需要清除
-                            }
-                        }
-                        continue;
-                    }
-                }
-            };
-
-            // 【优化】在成功返回前，统一更新 last_used_account（如果需要）
-            if let Some((new_account_id, new_time)) = need_update_last_used {
-                if quota_group != "image_gen" {
-                    let mut last_used = self.last_used_account.lock().await;
-                    if new_account_id.is_empty() {
-                        // 空字符串表示需要清除锁定
-                        *last_used = None;
-                    } else {
-                        *last_used = Some((new_account_id, new_time));
-                    }
-                }
+        match self.quarantine_account(&account_id, &reason).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::error!("Failed to quarantine account {}: {}", account_id, e);
+                false
             }
-
-            return Ok((token.access_token, project_id, token.email));
         }
-
-        Err(last_error.unwrap_or_else(|| "All accounts failed".to_string()))
     }
 
-    async fn disable_account(&self, account_id: &str, reason: &str) -> Result<(), String> {
-        let path = if let Some(entry) = self.tokens.get(account_id) {
-            entry.account_path.clone()
-        } else {
-            self.data_dir
-                .join("accounts")
-                .join(format!("{}.json", account_id))
+    async fn quarantine_account(&self, account_id: &str, reason: &str) -> Result<(), String> {
+        let quarantine_reason = reason.to_string();
+        let content = self.store.update_account(account_id, move |content| {
+            let now = chrono::Utc::now().timestamp();
+            content["quarantined"] = serde_json::Value::Bool(true);
+            content["quarantined_at"] = serde_json::Value::Number(now.into());
+            content["quarantine_reason"] = serde_json::Value::String(quarantine_reason);
+        }).await?;
+        self.store.record_event(account_id, "quarantined", Some(reason.to_string()));
+
+        self.remove_account(account_id).await;
+
+        let email = content["email"].as_str().unwrap_or_default().to_string();
+        tracing::warn!("Account quarantined: {} ({})", email, reason);
+        let event = crate::modules::account::QuarantinedEvent {
+            account_id: account_id.to_string(),
+            email,
+            reason: reason.to_string(),
         };
-
-        let mut content: serde_json::Value = serde_json::from_str(
-            &std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?,
-        )
-        .map_err(|e| format!("解析 JSON 失败: {}", e))?;
-
-        let now = chrono::Utc::now().timestamp();
-        content["disabled"] = serde_json::Value::Bool(true);
-        content["disabled_at"] = serde_json::Value::Number(now.into());
-        content["disabled_reason"] = serde_json::Value::String(truncate_reason(reason, 800));
-
-        std::fs::write(&path, serde_json::to_string_pretty(&content).unwrap())
-            .map_err(|e| format!("写入文件失败: {}", e))?;
-        
-        // 【修复 Issue #3】从内存中移除禁用的账号，防止被60s锁定逻辑继续使用
-        self.tokens.remove(account_id);
-
-        tracing::warn!("Account disabled: {} ({:?})", account_id, path);
-        Ok(())
-    }
-
-    /// 保存 project_id 到账号文件
-    async fn save_project_id(&self, account_id: &str, project_id: &str) -> Result<(), String> {
-        let entry = self.tokens.get(account_id)
-            .ok_or("账号不存在")?;
-        
-        let path = &entry.account_path;
-        
-        let mut content: serde_json::Value = serde_json::from_str(
-            &std::fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?
-        ).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-        
-        content["token"]["project_id"] = serde_json::Value::String(project_id.to_string());
-        
-        std::fs::write(path, serde_json::to_string_pretty(&content).unwrap())
-            .map_err(|e| format!("写入文件失败: {}", e))?;
-        
-        tracing::debug!("已保存 project_id 到账号 {}", account_id);
-        Ok(())
-    }
-    
-    /// 保存刷新后的 token 到账号文件
-    async fn save_refreshed_token(&self, account_id: &str, token_response: &crate::modules::oauth::TokenResponse) -> Result<(), String> {
-        let entry = self.tokens.get(account_id)
-            .ok_or("账号不存在")?;
-        
-        let path = &entry.account_path;
-        
-        let mut content: serde_json::Value = serde_json::from_str(
-            &std::fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?
-        ).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-        
-        let now = chrono::Utc::now().timestamp();
-        
-        content["token"]["access_token"] = serde_json::Value::String(token_response.access_token.clone());
-        content["token"]["expires_in"] = serde_json::Value::Number(token_response.expires_in.into());
-        content["token"]["expiry_timestamp"] = serde_json::Value::Number((now + token_response.expires_in).into());
-        
-        std::fs::write(path, serde_json::to_string_pretty(&content).unwrap())
-            .map_err(|e| format!("写入文件失败: {}", e))?;
-        
-        tracing::debug!("已保存刷新后的 token 到账号 {}", account_id);
+        crate::modules::webhooks::fire(crate::models::WebhookEvent::AccountQuarantined, &event);
+        self.store.emit(crate::modules::account::QUARANTINED_EVENT, event);
         Ok(())
     }
-    
-    pub fn len(&self) -> usize {
-        self.tokens.len()
-    }
-
-    /// 通过 email 获取指定账号的 Token（用于预热等需要指定账号的场景）
-    /// 此方法会自动刷新过期的 token
-    pub async fn get_token_by_email(&self, email: &str) -> Result<(String, String, String), String> {
-        // 查找账号信息
-        let token_info = {
-            let mut found = None;
-            for entry in self.tokens.iter() {
-                let token = entry.value();
-                if token.email == email {
-                    found = Some((
-                        token.account_id.clone(),
-                        token.access_token.clone(),
-                        token.refresh_token.clone(),
-                        token.timestamp,
-                        token.expires_in,
-                        chrono::Utc::now().timestamp(),
-                        token.project_id.clone(),
-                    ));
-                    break;
-                }
-            }
-            found
-        };
-
-        let (
-            account_id,
-            current_access_token,
-            refresh_token,
-            timestamp,
-            expires_in,
-            now,
-            project_id_opt,
-        ) = match token_info {
-            Some(info) => info,
-            None => return Err(format!("未找到账号: {}", email)),
-        };
-
-        let project_id = project_id_opt.unwrap_or_else(|| "bamboo-precept-lgxtn".to_string());
-        
-        // 检查是否过期 (提前5分钟)
-        if now < timestamp + expires_in - 300 {
-            return Ok((current_access_token, project_id, email.to_string()));
-        }
-
-        tracing::info!("[Warmup] Token for {} is expiring, refreshing...", email);
 
-        // 调用 OAuth 刷新 token
-        match crate::modules::oauth::refresh_access_token(&refresh_token).await {
-            Ok(token_response) => {
-                tracing::info!("[Warmup] Token refresh successful for {}", email);
-                let new_now = chrono::Utc::now().timestamp();
-                
-                // 更新缓存
-                if let Some(mut entry) = self.tokens.get_mut(&account_id) {
-                    entry.access_token = token_response.access_token.clone();
-                    entry.expires_in = token_response.expires_in;
-                    entry.timestamp = new_now;
-                }
-
-                // 保存到磁盘
-                let _ = self.save_refreshed_token(&account_id, &token_response).await;
-
-                Ok((token_response.access_token, project_id, email.to_string()))
-            }
-            Err(e) => Err(format!("[Warmup] Token refresh failed for {}: {}", email, e)),
-        }
-    }
-    
     // ===== 限流管理方法 =====
-    
-    /// 标记账号限流(从外部调用,通常在 handler 中)
-    /// 参数为 email，内部会自动转换为 account_id
-    pub fn mark_rate_limited(
-        &self,
-        email: &str,
-        status: u16,
-        retry_after_header: Option<&str>,
-        error_body: &str,
-    ) {
-        // 【替代方案】转换 email -> account_id
-        let key = self.email_to_account_id(email).unwrap_or_else(|| email.to_string());
-        self.rate_limit_tracker.parse_from_error(
-            &key,
-            status,
-            retry_after_header,
-            error_body,
-            None,
-        );
-    }
-    
-    /// 检查账号是否在限流中
-    /// 参数为 email，内部会自动转换为 account_id
-    pub fn is_rate_limited(&self, email: &str) -> bool {
-        // 【替代方案】转换 email -> account_id
-        if let Some(account_id) = self.email_to_account_id(email) {
-            self.rate_limit_tracker.is_rate_limited(&account_id)
-        } else {
-            // Fallback: 如果找不到，直接用email查询(兼容旧数据)
-            self.rate_limit_tracker.is_rate_limited(email)
-        }
-    }
 
-    /// 检查账号是否在限流中 (直接使用 account_id)
-    pub fn is_rate_limited_by_account_id(&self, account_id: &str) -> bool {
-        self.rate_limit_tracker.is_rate_limited(account_id)
-    }
-    
-    /// 获取距离限流重置还有多少秒
-    #[allow(dead_code)]
-    pub fn get_rate_limit_reset_seconds(&self, account_id: &str) -> Option<u64> {
-        self.rate_limit_tracker.get_reset_seconds(account_id)
+    /// 更新账号池配置
+    pub async fn update_account_pools(&self, pools: &[crate::proxy::config::AccountPool]) {
+        self.pool
+            .set_account_pools(pools.iter().map(|p| (p.name.clone(), p.accounts.iter().cloned().collect())).collect())
+            .await;
     }
-    
-    /// 清除过期的限流记录
-    #[allow(dead_code)]
-    pub fn clean_expired_rate_limits(&self) {
-        self.rate_limit_tracker.cleanup_expired();
-    }
-    
-    /// 【替代方案】通过 email 查找对应的 account_id
-    /// 用于将 handlers 传入的 email 转换为 tracker 使用的 account_id
-    fn email_to_account_id(&self, email: &str) -> Option<String> {
-        self.tokens.iter()
-            .find(|entry| entry.value().email == email)
-            .map(|entry| entry.value().account_id.clone())
-    }
-    
-    /// 清除指定账号的限流记录
-    #[allow(dead_code)]
-    pub fn clear_rate_limit(&self, account_id: &str) -> bool {
-        self.rate_limit_tracker.clear(account_id)
-    }
-    
-    /// 标记账号请求成功，重置连续失败计数
-    /// 
-    /// 在请求成功完成后调用，将该账号的失败计数归零，
-    /// 下次失败时从最短的锁定时间开始（智能限流）。
-    pub fn mark_account_success(&self, account_id: &str) {
-        self.rate_limit_tracker.mark_success(account_id);
-    }
-    
-    /// 从账号文件获取配额刷新时间
+
+    /// 从账号存储获取配额刷新时间
     /// 
     /// 返回该账号最近的配额刷新时间字符串（ISO 8601 格式）
-    pub fn get_quota_reset_time(&self, email: &str) -> Option<String> {
-        // 尝试从账号文件读取配额信息
-        let accounts_dir = self.data_dir.join("accounts");
-        
-        // 遍历账号文件查找对应的 email
-        if let Ok(entries) = std::fs::read_dir(&accounts_dir) {
-            for entry in entries.flatten() {
-                if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                    if let Ok(account) = serde_json::from_str::<serde_json::Value>(&content) {
-                        // 检查 email 是否匹配
-                        if account.get("email").and_then(|e| e.as_str()) == Some(email) {
-                            // 获取 quota.models 中最早的 reset_time
-                            if let Some(models) = account
-                                .get("quota")
-                                .and_then(|q| q.get("models"))
-                                .and_then(|m| m.as_array()) 
-                            {
-                                // 找到最早的 reset_time（最保守的锁定策略）
-                                let mut earliest_reset: Option<&str> = None;
-                                for model in models {
-                                    if let Some(reset_time) = model.get("reset_time").and_then(|r| r.as_str()) {
-                                        if !reset_time.is_empty() {
-                                            if earliest_reset.is_none() || reset_time < earliest_reset.unwrap() {
-                                                earliest_reset = Some(reset_time);
-                                            }
-                                        }
-                                    }
-                                }
-                                if let Some(reset) = earliest_reset {
-                                    return Some(reset.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        None
+    pub async fn get_quota_reset_time(&self, email: &str) -> Option<String> {
+        // 从账号存储读取配额信息
+        let account = self
+            .with_store(crate::modules::account_store::list_accounts)
+            .await
+            .ok()?
+            .into_iter()
+            .find(|a| a.get("email").and_then(|e| e.as_str()) == Some(email))?;
+
+        // 找到 quota.models 中最早的 reset_time（最保守的锁定策略）
+        account
+            .get("quota")
+            .and_then(|q| q.get("models"))
+            .and_then(|m| m.as_array())?
+            .iter()
+            .filter_map(|model| model.get("reset_time").and_then(|r| r.as_str()))
+            .filter(|reset_time| !reset_time.is_empty())
+            .min()
+            .map(|reset| reset.to_string())
     }
     
     /// 使用配额刷新时间精确锁定账号
@@ -1089,10 +831,11 @@ impl TokenManager {
     /// 
     /// # 参数
     /// - `model`: 可选的模型名称,用于模型级别限流
-    pub fn set_precise_lockout(&self, email: &str, reason: crate::proxy::rate_limit::RateLimitReason, model: Option<String>) -> bool {
-        if let Some(reset_time_str) = self.get_quota_reset_time(email) {
+    pub async fn set_precise_lockout(&self, email: &str, reason: crate::proxy::rate_limit::RateLimitReason, model: Option<String>) -> bool {
+        if let Some(reset_time_str) = self.get_quota_reset_time(email).await {
             tracing::info!("找到账号 {} 的配额刷新时间: {}", email, reset_time_str);
-            self.rate_limit_tracker.set_lockout_until_iso(email, &reset_time_str, reason, model)
+            let key = self.email_to_account_id(email).unwrap_or_else(|| email.to_string());
+            self.rate_limit_tracker().set_lockout_until_iso(&key, &reset_time_str, reason, model)
         } else {
             tracing::debug!("未找到账号 {} 的配额刷新时间,将使用默认退避策略", email);
             false
@@ -1115,18 +858,7 @@ impl TokenManager {
         model: Option<String>,
     ) -> bool {
         // 1. 从 tokens 中获取该账号的 access_token
-        let access_token = {
-            let mut found_token: Option<String> = None;
-            for entry in self.tokens.iter() {
-                if entry.value().email == email {
-                    found_token = Some(entry.value().access_token.clone());
-                    break;
-                }
-            }
-            found_token
-        };
-        
-        let access_token = match access_token {
+        let access_token = match self.token_by_email(email).map(|t| t.access_token) {
             Some(t) => t,
             None => {
                 tracing::warn!("无法找到账号 {} 的 access_token,无法实时刷新配额", email);
@@ -1154,7 +886,8 @@ impl TokenManager {
                         "账号 {} 实时配额刷新成功,reset_time: {}",
                         email, reset_time_str
                     );
-                    self.rate_limit_tracker.set_lockout_until_iso(email, reset_time_str, reason, model)
+                    let key = self.email_to_account_id(email).unwrap_or_else(|| email.to_string());
+                    self.rate_limit_tracker().set_lockout_until_iso(&key, reset_time_str, reason, model)
                 } else {
                     tracing::warn!("账号 {} 配额刷新成功但未找到 reset_time", email);
                     false
@@ -1185,6 +918,25 @@ impl TokenManager {
         error_body: &str,
         model: Option<&str>,  // 🆕 新增模型参数
     ) {
+        self.record_account_error(account_id);
+        self.lock_rate_limited_account(account_id, status, retry_after_header, error_body, model).await;
+
+        let key = self.email_to_account_id(account_id).unwrap_or_else(|| account_id.to_string());
+        self.notify_cooling(&key);
+    }
+
+    async fn lock_rate_limited_account(
+        &self,
+        account_id: &str,
+        status: u16,
+        retry_after_header: Option<&str>,
+        error_body: &str,
+        model: Option<&str>,
+    ) {
+        // handlers 传入的是 email,tracker 统一使用 account_id 作为 key,
+        // 否则 get_token 中的 is_rate_limited_by_account_id 无法识别冷却状态
+        let key = self.email_to_account_id(account_id).unwrap_or_else(|| account_id.to_string());
+
         // 检查 API 是否返回了精确的重试时间
         let has_explicit_retry_time = retry_after_header.is_some() || 
             error_body.contains("quotaResetDelay");
@@ -1196,8 +948,8 @@ impl TokenManager {
             } else {
                 tracing::debug!("账号 {} 的 429 响应包含 quotaResetDelay,直接使用 API 返回的时间", account_id);
             }
-            self.rate_limit_tracker.parse_from_error(
-                account_id,
+            self.rate_limit_tracker().parse_from_error(
+                &key,
                 status,
                 retry_after_header,
                 error_body,
@@ -1228,46 +980,21 @@ impl TokenManager {
         }
         
         // 实时刷新失败,尝试使用本地缓存的配额刷新时间
-        if self.set_precise_lockout(account_id, reason, model.map(|s| s.to_string())) {
+        if self.set_precise_lockout(account_id, reason, model.map(|s| s.to_string())).await {
             tracing::info!("账号 {} 已使用本地缓存配额锁定", account_id);
             return;
         }
         
         // 都失败了,回退到指数退避策略
         tracing::warn!("账号 {} 无法获取配额刷新时间,使用指数退避策略", account_id);
-        self.rate_limit_tracker.parse_from_error(
-            account_id,
+        self.rate_limit_tracker().parse_from_error(
+            &key,
             status,
             retry_after_header,
             error_body,
             model.map(|s| s.to_string()),
         );
     }
-
-    // ===== 调度配置相关方法 =====
-
-    /// 获取当前调度配置
-    pub async fn get_sticky_config(&self) -> StickySessionConfig {
-        self.sticky_config.read().await.clone()
-    }
-
-    /// 更新调度配置
-    pub async fn update_sticky_config(&self, new_config: StickySessionConfig) {
-        let mut config = self.sticky_config.write().await;
-        *config = new_config;
-        tracing::debug!("Scheduling configuration updated: {:?}", *config);
-    }
-
-    /// 清除特定会话的粘性映射
-    #[allow(dead_code)]
-    pub fn clear_session_binding(&self, session_id: &str) {
-        self.session_accounts.remove(session_id);
-    }
-
-    /// 清除所有会话的粘性映射
-    pub fn clear_all_sessions(&self) {
-        self.session_accounts.clear();
-    }
 }
 
 fn truncate_reason(reason: &str, max_len: usize) -> String {
@@ -1278,3 +1005,91 @@ fn truncate_reason(reason: &str, max_len: usize) -> String {
     s.push('…');
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_token(id: &str) -> ProxyToken {
+        ProxyToken {
+            account_id: id.to_string(),
+            access_token: format!("at-{}", id),
+            refresh_token: format!("rt-{}", id),
+            expires_in: 3600,
+            // 远未过期，避免测试中触发网络刷新
+            timestamp: chrono::Utc::now().timestamp() + 3600,
+            email: format!("{}@example.com", id),
+            project_id: Some(format!("project-{}", id)),
+            subscription_tier: Some("PRO".to_string()),
+            remaining_quota: Some(80),
+            protected_models: HashSet::new(),
+            available_models: HashSet::new(),
+            usage_windows: Vec::new(),
+            egress_proxy: None,
+            project_id_override: None,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            priority: 0,
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_sync_from_store_applies_external_changes() {
+        let dir = std::env::temp_dir().join(format!("ag-token-manager-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stored = |id: &str| serde_json::json!({
+            "id": id,
+            "email": format!("{}@example.com", id),
+            "token": {"access_token": "at", "refresh_token": "rt", "expires_in": 3600, "expiry_timestamp": 4102444800i64},
+            "created_at": 1,
+            "last_used": 1,
+        });
+        for id in ["a", "b"] {
+            crate::modules::account_store::write_account(&dir, &stored(id)).unwrap();
+        }
+
+        let manager = TokenManager::new(dir.clone());
+        assert_eq!(manager.load_accounts().await.unwrap(), 2);
+        manager.bind_session("s", "b");
+        assert!(manager.sync_from_store().await.unwrap().is_empty());
+
+        // 模拟外部修改: 禁用 a、更新 b 的 Token、新增 c
+        crate::modules::account_store::update_account(&dir, "a", |a| a["disabled"] = serde_json::Value::Bool(true)).unwrap();
+        crate::modules::account_store::update_account(&dir, "b", |b| b["token"]["access_token"] = "at-new".into()).unwrap();
+        crate::modules::account_store::write_account(&dir, &stored("c")).unwrap();
+
+        let mut changed = manager.sync_from_store().await.unwrap();
+        changed.sort();
+        assert_eq!(changed, vec!["a", "b", "c"]);
+        assert!(manager.token("a").is_none());
+        assert_eq!(manager.token("b").unwrap().access_token, "at-new");
+        assert!(manager.token("c").is_some());
+        // 未删除的账号保留会话绑定
+        assert_eq!(manager.session_account("s").as_deref(), Some("b"));
+
+        crate::modules::account_store::archive_accounts(&dir, &["c".to_string()]).unwrap();
+        assert_eq!(manager.sync_from_store().await.unwrap(), vec!["c"]);
+        assert!(manager.token("c").is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rate_limit_event_recorded_once_per_cooldown() {
+        let dir = std::env::temp_dir().join(format!("ag-token-manager-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manager = TokenManager::new(dir.clone());
+        manager.insert_token(test_token("a"));
+
+        manager.mark_rate_limited("a@example.com", 429, Some("60"), "");
+        manager.mark_rate_limited("a@example.com", 429, Some("60"), "");
+
+        let events = crate::modules::account_store::list_events(&dir, Some("a"), 10).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, "rate_limited");
+        assert!(events[0].detail.as_deref().unwrap().starts_with("HTTP 429"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,368 @@
+// 对话记录归档 (可选)
+//
+// 开启后把成功完成的对话 (请求中的消息 + 最终助手输出) 保存到数据目录的
+// `transcripts/<日期>/<Key 指纹>/` 下，格式为 JSON、Markdown 或两者，仅供个人留档。
+// 只保存在本机，消息与输出中的凭证 (API Key、Bearer Token 等) 与请求日志一样脱敏；未携带 API Key 的请求归入 `anonymous` 目录。
+// 目录超出容量上限 (`memory_limits.transcripts_mb`) 时从最旧的记录开始删除。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+use crate::modules::token_stats::KeyId;
+use crate::proxy::config::{TranscriptConfig, TranscriptFormat};
+use crate::proxy::inspector::content_text;
+use crate::proxy::memory_limits::{self, Subsystem};
+use crate::proxy::monitor::ProxyRequestLog;
+use crate::modules::i18n::tr;
+
+/// 单个对话最多保存的输出字符数 (流式响应累积时的上限)
+pub const MAX_OUTPUT_CHARS: usize = 2_000_000;
+/// 每归档多少次检查一次目录容量 (首次归档时也会检查)
+const LIMIT_CHECK_INTERVAL: u64 = 20;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TranscriptMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub id: String,
+    /// Unix 毫秒
+    pub timestamp: i64,
+    pub key_id: String,
+    pub model: Option<String>,
+    pub mapped_model: Option<String>,
+    pub account_email: Option<String>,
+    pub messages: Vec<TranscriptMessage>,
+    pub output: String,
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+}
+
+/// 提取请求中的消息，兼容 OpenAI / Claude / Gemini 格式 (系统提示词作为 system 消息)
+fn request_messages(body: &Value) -> Vec<TranscriptMessage> {
+    let mut messages = Vec::new();
+    let mut push = |role: &str, content: Option<String>| {
+        if let Some(content) = content.filter(|c| !c.is_empty()) {
+            messages.push(TranscriptMessage { role: role.to_string(), content });
+        }
+    };
+
+    if let Some(system) = body.get("system") {
+        push("system", content_text(system));
+    }
+    if let Some(system) = body.get("systemInstruction").or_else(|| body.get("system_instruction")) {
+        push("system", content_text(&system["parts"]));
+    }
+    if let Some(list) = body.get("messages").and_then(|m| m.as_array()) {
+        for m in list {
+            push(m["role"].as_str().unwrap_or("user"), content_text(&m["content"]));
+        }
+    } else if let Some(list) = body.get("contents").and_then(|c| c.as_array()) {
+        for c in list {
+            let role = match c["role"].as_str() {
+                Some("model") => "assistant",
+                Some(role) => role,
+                None => "user",
+            };
+            push(role, content_text(&c["parts"]));
+        }
+    } else if let Some(input) = body.get("input").or_else(|| body.get("prompt")) {
+        match input {
+            Value::Array(items) => {
+                for item in items {
+                    push(item["role"].as_str().unwrap_or("user"), content_text(&item["content"]));
+                }
+            }
+            other => push("user", content_text(other)),
+        }
+    }
+    messages
+}
+
+impl Transcript {
+    fn to_markdown(&self) -> String {
+        let time = chrono::DateTime::from_timestamp_millis(self.timestamp)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let mut md = format!("# {} · {}\n\n", self.model.as_deref().unwrap_or("unknown"), time);
+        for message in &self.messages {
+            md.push_str(&format!("## {}\n\n{}\n\n", message.role, message.content));
+        }
+        md.push_str(&format!("## assistant\n\n{}\n", self.output));
+        md
+    }
+
+    fn file_stem(&self) -> String {
+        let time = chrono::DateTime::from_timestamp_millis(self.timestamp)
+            .map(|t| t.with_timezone(&chrono::Local).format("%H%M%S").to_string())
+            .unwrap_or_default();
+        format!("{}-{}", time, self.id.chars().take(8).collect::<String>())
+    }
+
+    fn dir(&self, root: &Path) -> PathBuf {
+        let date = chrono::DateTime::from_timestamp_millis(self.timestamp)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let key = if self.key_id.is_empty() { "anonymous" } else { &self.key_id };
+        root.join(date).join(key)
+    }
+
+    /// 按格式写入文件，返回写入的路径
+    fn write(&self, root: &Path, format: TranscriptFormat) -> Result<Vec<PathBuf>, String> {
+        let dir = self.dir(root);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建对话归档目录失败: {}", e))?;
+        let stem = self.file_stem();
+        let mut written = Vec::new();
+        if matches!(format, TranscriptFormat::Json | TranscriptFormat::Both) {
+            let path = dir.join(format!("{}.json", stem));
+            let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+            std::fs::write(&path, content).map_err(|e| format!("写入对话归档失败: {}", e))?;
+            written.push(path);
+        }
+        if matches!(format, TranscriptFormat::Markdown | TranscriptFormat::Both) {
+            let path = dir.join(format!("{}.md", stem));
+            std::fs::write(&path, self.to_markdown()).map_err(|e| format!("写入对话归档失败: {}", e))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+fn transcripts_dir() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join("transcripts"))
+}
+
+/// 递归收集归档文件: (修改时间, 路径, 大小)
+fn collect_files(dir: &Path, out: &mut Vec<(SystemTime, PathBuf, u64)>) -> Result<(), String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(tr("backend.errors.transcripts_read_failed", &[("error", &e.to_string())])),
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.is_dir() {
+            collect_files(&entry.path(), out)?;
+        } else {
+            out.push((meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), entry.path(), meta.len()));
+        }
+    }
+    Ok(())
+}
+
+/// 目录超出上限时从最旧的文件开始删除 (并移除删空的目录)，返回删除的文件数
+fn enforce_limit(root: &Path, max_bytes: u64) -> Result<usize, String> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files)?;
+    let mut total: u64 = files.iter().map(|(_, _, len)| len).sum();
+    if total <= max_bytes {
+        return Ok(0);
+    }
+    files.sort();
+    let mut deleted = 0;
+    for (_, path, len) in files {
+        if total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(&path).map_err(|e| format!("删除对话归档失败: {}", e))?;
+        total = total.saturating_sub(len);
+        deleted += 1;
+        // <日期>/<Key 指纹>/ 删空后一并移除 (非空目录删除失败，忽略即可)
+        for dir in path.ancestors().skip(1).take_while(|dir| *dir != root) {
+            if std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(deleted)
+}
+
+#[derive(Debug, Default)]
+pub struct TranscriptArchive {
+    config: RwLock<TranscriptConfig>,
+    /// 目录容量上限 (字节)，0 表示未设置
+    max_bytes: AtomicU64,
+    archived: AtomicU64,
+}
+
+impl TranscriptArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_config(&self, config: TranscriptConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.read().unwrap().enabled
+    }
+
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+    }
+
+    /// 由请求日志与最终输出生成对话记录；失败的请求或不是对话的请求返回 None
+    ///
+    /// 归档在请求日志脱敏之前进行，消息与输出在这里单独脱敏
+    fn build(log: &ProxyRequestLog, output: &str, key_id: &KeyId) -> Option<Transcript> {
+        if log.status >= 400 {
+            return None;
+        }
+        let body: Value = serde_json::from_str(log.request_body.as_deref()?).ok()?;
+        let mut messages = request_messages(&body);
+        if messages.is_empty() {
+            return None;
+        }
+        for message in &mut messages {
+            crate::utils::redact::redact_in_place(&mut message.content);
+        }
+        Some(Transcript {
+            id: log.id.clone(),
+            timestamp: log.timestamp,
+            key_id: key_id.id.clone(),
+            model: log.model.clone(),
+            mapped_model: log.mapped_model.clone(),
+            account_email: log.account_email.clone(),
+            messages,
+            output: crate::utils::redact::redact(output).into_owned(),
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+        })
+    }
+
+    /// 归档一次已完成的对话 (后台写入，不阻塞请求)
+    pub fn archive(&self, log: &ProxyRequestLog, output: Option<&str>, key_id: &KeyId) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(transcript) = Self::build(log, output.unwrap_or(""), key_id) else { return };
+        let format = self.config.read().unwrap().format;
+        let max_bytes = self.max_bytes.load(Ordering::Relaxed);
+        let check_limit = self.archived.fetch_add(1, Ordering::Relaxed).is_multiple_of(LIMIT_CHECK_INTERVAL)
+            && max_bytes != 0
+            && max_bytes != u64::MAX;
+        tokio::task::spawn_blocking(move || {
+            let root = match transcripts_dir() {
+                Ok(root) => root,
+                Err(e) => {
+                    tracing::warn!("[Transcripts] Failed to archive {}: {}", transcript.id, e);
+                    return;
+                }
+            };
+            if let Err(e) = transcript.write(&root, format) {
+                tracing::warn!("[Transcripts] Failed to archive {}: {}", transcript.id, e);
+            }
+            if check_limit {
+                match enforce_limit(&root, max_bytes) {
+                    Ok(deleted) => memory_limits::record_evictions(Subsystem::Transcripts, deleted),
+                    Err(e) => tracing::warn!("[Transcripts] Failed to enforce size limit: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(body: &str, status: u16) -> ProxyRequestLog {
+        ProxyRequestLog {
+            id: "0123456789abcdef".to_string(),
+            timestamp: 1_717_200_000_000,
+            method: "POST".to_string(),
+            url: "/v1/messages".to_string(),
+            status,
+            duration: 10,
+            model: Some("claude-sonnet-4-5".to_string()),
+            mapped_model: Some("gemini-2.5-pro".to_string()),
+            account_email: None,
+            project_id: None,
+            error: None,
+            request_body: Some(body.to_string()),
+            response_body: None,
+            input_tokens: Some(12),
+            output_tokens: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_builds_transcripts_for_each_protocol() {
+        let claude = r#"{"system":"be brief","messages":[{"role":"user","content":[{"type":"text","text":"hi"}]}]}"#;
+        let transcript = TranscriptArchive::build(&log(claude, 200), "hello", &KeyId::from_key(Some("sk-test"))).unwrap();
+        assert_eq!(transcript.messages.len(), 2);
+        assert_eq!(transcript.messages[0], TranscriptMessage { role: "system".into(), content: "be brief".into() });
+        assert_eq!(transcript.key_id.len(), 16);
+
+        let gemini = r#"{"contents":[{"role":"user","parts":[{"text":"q"}]},{"role":"model","parts":[{"text":"a"}]}]}"#;
+        let transcript = TranscriptArchive::build(&log(gemini, 200), "", &KeyId::default()).unwrap();
+        assert_eq!(transcript.messages[1].role, "assistant");
+
+        assert!(TranscriptArchive::build(&log(claude, 500), "", &KeyId::default()).is_none());
+        assert!(TranscriptArchive::build(&log("{}", 200), "", &KeyId::default()).is_none());
+    }
+
+    #[test]
+    fn test_writes_json_and_markdown_by_date_and_key() {
+        let root = std::env::temp_dir().join(format!("ag-transcripts-{}", uuid::Uuid::new_v4()));
+        let body = r#"{"messages":[{"role":"user","content":"ping"}]}"#;
+        let transcript = TranscriptArchive::build(&log(body, 200), "pong", &KeyId::default()).unwrap();
+        let written = transcript.write(&root, TranscriptFormat::Both).unwrap();
+
+        assert_eq!(written.len(), 2);
+        assert!(written.iter().all(|p| p.starts_with(&root)));
+        assert!(written[0].parent().unwrap().ends_with("anonymous"));
+        let md = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(md.contains("## user\n\nping") && md.contains("## assistant\n\npong"));
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(json["output"], "pong");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_archived_transcripts_are_redacted() {
+        let root = std::env::temp_dir().join(format!("ag-transcripts-{}", uuid::Uuid::new_v4()));
+        let body = r#"{"messages":[{"role":"user","content":"my key is sk-abcdefghijklmnopqrstuvwx"}]}"#;
+        let transcript =
+            TranscriptArchive::build(&log(body, 200), "use Bearer ya29.secret-token", &KeyId::default()).unwrap();
+        let written = transcript.write(&root, TranscriptFormat::Both).unwrap();
+
+        for path in &written {
+            let text = std::fs::read_to_string(path).unwrap();
+            assert!(!text.contains("sk-abcdefghijklmnopqrstuvwx") && !text.contains("ya29.secret-token"));
+            assert!(text.contains("my key is [REDACTED]"));
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_enforce_limit_deletes_oldest_first() {
+        let root = std::env::temp_dir().join(format!("ag-transcripts-{}", uuid::Uuid::new_v4()));
+        let body = r#"{"messages":[{"role":"user","content":"ping"}]}"#;
+        let mut written = Vec::new();
+        for day in 0..3 {
+            let mut entry = log(body, 200);
+            entry.timestamp += day * 86_400_000;
+            let transcript = TranscriptArchive::build(&entry, "pong", &KeyId::default()).unwrap();
+            written.extend(transcript.write(&root, TranscriptFormat::Json).unwrap());
+        }
+        let newest = std::fs::metadata(&written[2]).unwrap().len();
+
+        assert_eq!(enforce_limit(&root, u64::MAX).unwrap(), 0);
+        assert_eq!(enforce_limit(&root, newest).unwrap(), 2);
+        assert!(!written[0].exists() && !written[1].exists() && written[2].exists());
+        // 删空的日期目录一并移除
+        assert!(!written[0].parent().unwrap().parent().unwrap().exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,25 +1,12 @@
 use base64::Engine;
 use serde_json::{json, Value};
-use tokio::time::Duration;
 
 use crate::proxy::config::UpstreamProxyConfig;
+use crate::proxy::providers::build_client;
 use crate::proxy::ZaiConfig;
 
 const ZAI_PAAZ_CHAT_COMPLETIONS_URL: &str = "https://api.z.ai/api/paas/v4/chat/completions";
 
-fn build_client(upstream_proxy: UpstreamProxyConfig, timeout_secs: u64) -> Result<reqwest::Client, String> {
-    let mut builder = reqwest::Client::builder()
-        .timeout(Duration::from_secs(timeout_secs.max(5)));
-
-    if upstream_proxy.enabled && !upstream_proxy.url.is_empty() {
-        let proxy = reqwest::Proxy::all(&upstream_proxy.url)
-            .map_err(|e| format!("Invalid upstream proxy url: {}", e))?;
-        builder = builder.proxy(proxy);
-    }
-
-    builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
-}
-
 fn is_http_url(value: &str) -> bool {
     let v = value.trim();
     v.starts_with("http://") || v.starts_with("https://")
@@ -282,7 +269,7 @@ pub async fn call_tool(
         return Err("z.ai api_key is missing".to_string());
     }
 
-    let client = build_client(upstream_proxy, timeout_secs)?;
+    let client = build_client(Some(upstream_proxy), timeout_secs)?;
 
     let tool_result = match tool_name {
         "ui_to_artifact" => {
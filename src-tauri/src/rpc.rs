@@ -0,0 +1,437 @@
+// JSON-RPC 自动化模式
+//
+// `antigravity_tools rpc [--config <path>] [--port <port>] [--log-level <filter>]`
+// 从 stdin 逐行读取 JSON-RPC 2.0 请求，向 stdout 逐行输出响应与事件通知，
+// 用于编写集成测试脚本或由外部程序编排。日志改写到 stderr，stdout 只输出协议消息。
+// 请求按顺序处理；不带 id 的通知照常执行但不输出响应。
+//
+// 方法:
+// - `proxy.start {port?}`          启动反代服务 (同无界面模式，不启动定时任务)
+// - `proxy.stop`                   停止反代服务
+// - `proxy.status`                 反代服务状态
+// - `accounts.list`                账号列表 (不含 Token)
+// - `accounts.add {refresh_token}` 用 refresh_token 添加账号，反代运行中时立即重新加载账号池
+// - `health.check {account_id?}`   账号端到端健康检查，省略 account_id 时检查全部账号
+// - `stats.get`                    请求统计 (反代运行中时附带进行中请求数与延迟直方图)
+// - `shutdown`                     停止反代服务并退出
+//
+// 事件以通知形式输出: `{"jsonrpc":"2.0","method":"event","params":{"type":...,"data":...}}`，
+// 类型为 ready、proxy.started、proxy.stopped、proxy.request (需开启请求日志)、account.added。
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+
+use crate::headless::{self, RunningProxy};
+use crate::models::{Account, TokenData};
+use crate::modules;
+
+/// 进入 JSON-RPC 模式的子命令
+pub const RPC_COMMAND: &str = "rpc";
+
+const USAGE: &str = "用法: antigravity_tools rpc [选项]
+
+从 stdin 逐行读取 JSON-RPC 2.0 请求，向 stdout 逐行输出响应与事件。
+
+选项:
+  --config <path>      使用指定的配置文件 (.json / .toml / .yaml)，默认为数据目录下的 gui_config
+  --port <port>        proxy.start 未指定端口时使用的反代端口
+  --log-level <filter> 日志过滤规则 (日志输出到 stderr)
+  -h, --help           显示帮助";
+
+// JSON-RPC 2.0 标准错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// 方法执行失败 (实现自定义的服务端错误区间)
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// 缺省 (或为 null) 时为通知，不输出响应
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn server(message: impl Into<String>) -> Self {
+        Self::new(SERVER_ERROR, message)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StartParams {
+    port: Option<u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AddAccountParams {
+    refresh_token: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct HealthParams {
+    account_id: Option<String>,
+}
+
+/// 解析一行输入，失败时返回应输出的错误响应
+fn parse_request(line: &str) -> Result<Request, Value> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| error_response(Value::Null, PARSE_ERROR, &format!("无法解析 JSON: {}", e)))?;
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request: Request = serde_json::from_value(value)
+        .map_err(|e| error_response(id.clone(), INVALID_REQUEST, &format!("无效的请求: {}", e)))?;
+    if request.jsonrpc != "2.0" {
+        return Err(error_response(id, INVALID_REQUEST, "jsonrpc 必须为 \"2.0\""));
+    }
+    Ok(request)
+}
+
+/// 省略 params (或为 null) 时使用默认值
+fn parse_params<T: DeserializeOwned + Default>(params: Value) -> Result<T, RpcError> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, format!("无效的参数: {}", e)))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e.code, &e.message),
+    }
+}
+
+fn event(kind: &str, data: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "event", "params": { "type": kind, "data": data } })
+}
+
+/// 对外输出的账号摘要 (不含 Token)
+fn account_summary(account: &Account) -> Value {
+    json!({
+        "id": account.id,
+        "email": account.email,
+        "name": account.name,
+        "disabled": account.disabled,
+        "needs_reauth": account.needs_reauth,
+        "quarantined": account.quarantined,
+        "proxy_disabled": account.proxy_disabled,
+    })
+}
+
+/// 单个 stdio 会话的状态
+struct Session {
+    /// 命令行指定的默认端口
+    port: Option<u16>,
+    proxy: Option<RunningProxy>,
+    /// 转发 proxy.request 事件的任务
+    forwarder: Option<tokio::task::JoinHandle<()>>,
+    output: mpsc::UnboundedSender<Value>,
+}
+
+impl Session {
+    fn new(port: Option<u16>, output: mpsc::UnboundedSender<Value>) -> Self {
+        Self { port, proxy: None, forwarder: None, output }
+    }
+
+    fn emit(&self, kind: &str, data: Value) {
+        let _ = self.output.send(event(kind, data));
+    }
+
+    async fn handle(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "proxy.start" => self.start_proxy(parse_params(params)?).await,
+            "proxy.stop" => self.stop_proxy().await,
+            "proxy.status" => Ok(self.status()),
+            "accounts.list" => {
+                let accounts = modules::account::list_accounts().map_err(RpcError::server)?;
+                Ok(Value::Array(accounts.iter().map(account_summary).collect()))
+            }
+            "accounts.add" => self.add_account(parse_params(params)?).await,
+            "health.check" => {
+                let params: HealthParams = parse_params(params)?;
+                let result = match params.account_id {
+                    Some(id) => serde_json::to_value(modules::health::check_account_by_id(&id).await.map_err(RpcError::server)?),
+                    None => serde_json::to_value(modules::health::check_all_accounts().await.map_err(RpcError::server)?),
+                };
+                result.map_err(|e| RpcError::server(e.to_string()))
+            }
+            "stats.get" => self.stats(),
+            "shutdown" => {
+                if self.proxy.is_some() {
+                    self.stop_proxy().await?;
+                }
+                Ok(json!({}))
+            }
+            other => Err(RpcError::new(METHOD_NOT_FOUND, format!("未知方法: {}", other))),
+        }
+    }
+
+    async fn start_proxy(&mut self, params: StartParams) -> Result<Value, RpcError> {
+        if self.proxy.is_some() {
+            return Err(RpcError::server("反代服务已在运行"));
+        }
+        let config = headless::load_proxy_config(params.port.or(self.port)).map_err(RpcError::server)?;
+        let proxy = RunningProxy::start(&config).await.map_err(RpcError::server)?;
+        info!("[RPC] 反代服务已启动: http://{}:{}", proxy.bind_address, proxy.port);
+
+        let mut requests = proxy.monitor.subscribe();
+        let output = self.output.clone();
+        self.forwarder = Some(tokio::spawn(async move {
+            loop {
+                match requests.recv().await {
+                    Ok(log) => {
+                        let data = serde_json::to_value(&log).unwrap_or_default();
+                        if output.send(event("proxy.request", data)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("[RPC] 输出过慢，丢弃了 {} 条请求事件", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }));
+
+        let data = json!({
+            "bind_address": proxy.bind_address,
+            "port": proxy.port,
+            "active_accounts": proxy.active_accounts,
+        });
+        self.proxy = Some(proxy);
+        self.emit("proxy.started", data.clone());
+        Ok(data)
+    }
+
+    async fn stop_proxy(&mut self) -> Result<Value, RpcError> {
+        let proxy = self.proxy.take().ok_or_else(|| RpcError::server("反代服务未运行"))?;
+        if let Some(forwarder) = self.forwarder.take() {
+            forwarder.abort();
+        }
+        let data = json!({ "port": proxy.port });
+        proxy.stop().await;
+        info!("[RPC] 反代服务已停止");
+        self.emit("proxy.stopped", data.clone());
+        Ok(data)
+    }
+
+    fn status(&self) -> Value {
+        match &self.proxy {
+            Some(proxy) => json!({
+                "running": true,
+                "bind_address": proxy.bind_address,
+                "port": proxy.port,
+                "active_accounts": proxy.token_manager.len(),
+            }),
+            None => json!({ "running": false }),
+        }
+    }
+
+    async fn add_account(&mut self, params: AddAccountParams) -> Result<Value, RpcError> {
+        let refresh_token = params.refresh_token.trim().to_string();
+        if refresh_token.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "缺少 refresh_token"));
+        }
+        let token_res = modules::oauth::refresh_access_token(&refresh_token).await.map_err(RpcError::server)?;
+        let user_info = modules::oauth::get_user_info(&token_res.access_token).await.map_err(RpcError::server)?;
+        let token = TokenData::new(
+            token_res.access_token,
+            refresh_token,
+            token_res.expires_in,
+            Some(user_info.email.clone()),
+            None,
+            None,
+        );
+        let account = modules::upsert_account(user_info.email.clone(), user_info.get_display_name(), token)
+            .map_err(RpcError::server)?;
+        info!("[RPC] 添加账号成功: {}", account.email);
+
+        if let Some(proxy) = &self.proxy {
+            if let Err(e) = proxy.token_manager.reload_all_accounts().await {
+                warn!("[RPC] 重新加载账号池失败: {}", e);
+            }
+        }
+        let summary = account_summary(&account);
+        self.emit("account.added", summary.clone());
+        Ok(summary)
+    }
+
+    fn stats(&self) -> Result<Value, RpcError> {
+        let requests = modules::proxy_db::get_stats().map_err(RpcError::server)?;
+        let mut stats = json!({ "running": self.proxy.is_some(), "requests": requests });
+        if let Some(proxy) = &self.proxy {
+            stats["in_flight"] = proxy.monitor.in_flight.get().into();
+            stats["latency"] = serde_json::to_value(proxy.monitor.latency.report()).unwrap_or_default();
+        }
+        Ok(stats)
+    }
+}
+
+/// JSON-RPC 模式入口，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let options = match headless::parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return 0;
+    }
+    modules::logger::console_to_stderr();
+    if let Err(e) = headless::init_environment(&options) {
+        eprintln!("{}", e);
+        return 2;
+    }
+    if let Err(e) = modules::proxy_db::init_db() {
+        warn!("初始化请求日志数据库失败: {}", e);
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建异步运行时失败: {}", e);
+            return 1;
+        }
+    };
+    runtime.block_on(serve(options.port));
+    // stdin 读取在阻塞线程中进行，不等待其结束
+    runtime.shutdown_timeout(std::time::Duration::from_secs(1));
+    0
+}
+
+/// 处理请求直到 stdin 关闭或收到 shutdown
+async fn serve(port: Option<u16>) {
+    let (output, mut messages) = mpsc::unbounded_channel::<Value>();
+    // 响应与事件共用同一个写入任务，保证每行是一条完整的消息
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = messages.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = Session::new(port, output.clone());
+    session.emit("ready", json!({ "version": env!("CARGO_PKG_VERSION") }));
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("[RPC] 读取 stdin 失败: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = match parse_request(&line) {
+            Ok(request) => request,
+            Err(error) => {
+                let _ = output.send(error);
+                continue;
+            }
+        };
+        let shutdown = request.method == "shutdown";
+        let result = session.handle(&request.method, request.params).await;
+        if let Some(id) = request.id {
+            let _ = output.send(response(id, result));
+        }
+        if shutdown {
+            break;
+        }
+    }
+
+    if session.proxy.is_some() {
+        let _ = session.stop_proxy().await;
+    }
+    drop(session);
+    drop(output);
+    let _ = writer.await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(r#"{"jsonrpc":"2.0","id":1,"method":"proxy.start","params":{"port":9000}}"#).unwrap();
+        assert_eq!(request.id, Some(json!(1)));
+        assert_eq!(request.method, "proxy.start");
+        let params: StartParams = parse_params(request.params).unwrap();
+        assert_eq!(params.port, Some(9000));
+
+        let notification = parse_request(r#"{"jsonrpc":"2.0","method":"proxy.status"}"#).unwrap();
+        assert_eq!(notification.id, None);
+        assert!(notification.params.is_null());
+
+        let error = parse_request("{not json").unwrap_err();
+        assert_eq!(error["error"]["code"], PARSE_ERROR);
+        assert_eq!(error["id"], Value::Null);
+
+        let error = parse_request(r#"{"jsonrpc":"1.0","id":"a","method":"x"}"#).unwrap_err();
+        assert_eq!(error["error"]["code"], INVALID_REQUEST);
+        assert_eq!(error["id"], "a");
+        let error = parse_request(r#"{"jsonrpc":"2.0","id":2}"#).unwrap_err();
+        assert_eq!(error["error"]["code"], INVALID_REQUEST);
+
+        let error = parse_params::<StartParams>(json!({ "port": "abc" })).unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert!(parse_params::<StartParams>(json!({ "unknown": 1 })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_errors() {
+        let (output, mut messages) = mpsc::unbounded_channel();
+        let mut session = Session::new(None, output);
+
+        let error = session.handle("proxy.restart", Value::Null).await.unwrap_err();
+        assert_eq!(error.code, METHOD_NOT_FOUND);
+        let error = session.handle("proxy.stop", Value::Null).await.unwrap_err();
+        assert_eq!(error.code, SERVER_ERROR);
+        let error = session.handle("accounts.add", json!({ "refresh_token": " " })).await.unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert_eq!(session.handle("proxy.status", Value::Null).await.unwrap(), json!({ "running": false }));
+        assert_eq!(session.handle("shutdown", Value::Null).await.unwrap(), json!({}));
+
+        let reply = response(json!(7), Err(error));
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+        assert!(messages.try_recv().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+// 桌面界面句柄
+//
+// 监控器、Token 管理器、告警、更新检查等通过 `AppHandle` 向前端推送事件。
+// 桌面版 (gui 特性) 中即 `tauri::AppHandle`；未启用 gui 时不链接 tauri，`AppHandle` 是不会被构造的占位类型，
+// 持有 `Option<AppHandle>` 的组件始终为 None，事件推送随之跳过。
+
+use std::future::Future;
+
+#[cfg(feature = "gui")]
+pub use tauri::AppHandle;
+
+/// 无界面构建中的占位类型 (模块外无法构造)
+#[cfg(not(feature = "gui"))]
+#[derive(Debug, Clone)]
+pub struct AppHandle {
+    _private: (),
+}
+
+/// 向前端推送事件 (失败时忽略)
+pub fn emit<S: serde::Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    #[cfg(feature = "gui")]
+    {
+        use tauri::Emitter;
+        let _ = app.emit(event, payload);
+    }
+    #[cfg(not(feature = "gui"))]
+    let _ = (app, event, payload);
+}
+
+/// 启动后台任务
+///
+/// 桌面版使用 tauri 的全局运行时 (setup 阶段调用时不在 tokio 运行时中)，
+/// 无界面模式使用当前的 tokio 运行时。
+pub fn spawn<F>(future: F)
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(feature = "gui")]
+    tauri::async_runtime::spawn(future);
+    #[cfg(not(feature = "gui"))]
+    tokio::spawn(future);
+}
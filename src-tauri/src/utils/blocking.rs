@@ -0,0 +1,56 @@
+// 阻塞 I/O 的异步封装
+//
+// 设置文件与账号存储 (std::fs / SQLite) 的读写在慢速或网络磁盘上可能耗时较长，
+// 异步上下文 (反代请求路径、异步命令) 中通过这里放到 tokio 的阻塞线程池执行，
+// 避免拖住执行器工作线程上的其他请求。
+
+/// 在阻塞线程池中执行并等待结果
+pub async fn run<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("后台 I/O 任务异常: {}", e))?
+}
+
+/// 在阻塞线程池中执行且不等待结果 (不在 tokio 运行时中时直接执行)
+pub fn spawn<F>(f: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn_blocking(f);
+        }
+        Err(_) => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_run_and_spawn() {
+        assert_eq!(run(|| Ok(42)).await, Ok(42));
+        assert_eq!(run::<(), _>(|| Err("boom".to_string())).await, Err("boom".to_string()));
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        spawn(move || {
+            let _ = tx.send(());
+        });
+        assert!(rx.await.is_ok());
+    }
+
+    #[test]
+    fn test_spawn_without_runtime_runs_inline() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let inner = flag.clone();
+        spawn(move || inner.store(true, Ordering::SeqCst));
+        assert!(flag.load(Ordering::SeqCst));
+    }
+}
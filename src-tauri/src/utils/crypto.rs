@@ -0,0 +1,159 @@
+// 对称加密工具: AES-256-GCM + Argon2id 口令派生
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use crate::modules::i18n::tr;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 加密后的数据 (字段均为 base64)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedBlob {
+    /// 密钥派生算法，口令加密为 "argon2id"，直接使用密钥时为 "none"
+    pub kdf: String,
+    #[serde(default)]
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// 使用 Argon2id 从口令派生 256 位密钥
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("密钥派生失败: {}", e))?;
+    Ok(key)
+}
+
+/// 生成随机 256 位密钥
+pub fn generate_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+}
+
+/// 使用密钥加密
+pub fn encrypt_with_key(key: &[u8; 32], plaintext: &[u8]) -> Result<EncryptedBlob, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("初始化加密器失败: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "加密失败".to_string())?;
+    Ok(EncryptedBlob {
+        kdf: "none".to_string(),
+        salt: String::new(),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+/// 使用密钥解密
+pub fn decrypt_with_key(key: &[u8; 32], blob: &EncryptedBlob) -> Result<Vec<u8>, String> {
+    let nonce = general_purpose::STANDARD.decode(&blob.nonce).map_err(|e| format!("nonce 格式错误: {}", e))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(tr("backend.errors.crypto.invalid_nonce", &[]));
+    }
+    let ciphertext = general_purpose::STANDARD.decode(&blob.ciphertext).map_err(|e| format!("密文格式错误: {}", e))?;
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("初始化解密器失败: {}", e))?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "解密失败: 密钥或口令错误，或数据已损坏".to_string())
+}
+
+/// 使用口令加密
+pub fn encrypt_with_passphrase(passphrase: &str, plaintext: &[u8]) -> Result<EncryptedBlob, String> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let mut blob = encrypt_with_key(&key, plaintext)?;
+    blob.kdf = "argon2id".to_string();
+    blob.salt = general_purpose::STANDARD.encode(salt);
+    Ok(blob)
+}
+
+/// 使用口令解密
+pub fn decrypt_with_passphrase(passphrase: &str, blob: &EncryptedBlob) -> Result<Vec<u8>, String> {
+    if blob.kdf != "argon2id" {
+        return Err(tr("backend.errors.crypto.unsupported_kdf", &[("kdf", &blob.kdf)]));
+    }
+    let salt = general_purpose::STANDARD.decode(&blob.salt).map_err(|e| format!("salt 格式错误: {}", e))?;
+    let key = derive_key(passphrase, &salt)?;
+    decrypt_with_key(&key, blob)
+}
+
+/// 小写十六进制编码
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// HMAC-SHA256 (S3 请求签名、Webhook 签名)
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new().chain_update(block.map(|b| b ^ 0x36)).chain_update(data).finalize();
+    Sha256::new().chain_update(block.map(|b| b ^ 0x5c)).chain_update(inner).finalize().into()
+}
+
+/// 校验 Ed25519 签名 (公钥和签名均为 base64)
+pub fn verify_ed25519(public_key_b64: &str, message: &[u8], signature_b64: &str) -> Result<(), String> {
+    use ed25519_dalek::{Signature, VerifyingKey};
+    let key_bytes: [u8; 32] = general_purpose::STANDARD
+        .decode(public_key_b64.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| tr("backend.errors.crypto.invalid_public_key", &[]))?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| format!("签名公钥无效: {}", e))?;
+    let sig_bytes = general_purpose::STANDARD.decode(signature_b64.trim()).map_err(|_| "签名格式无效")?;
+    let signature = Signature::from_slice(&sig_bytes).map_err(|_| "签名格式无效")?;
+    key.verify_strict(message, &signature).map_err(|_| "签名校验失败".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_roundtrip() {
+        let blob = encrypt_with_passphrase("correct horse", b"secret tokens").unwrap();
+        assert_eq!(decrypt_with_passphrase("correct horse", &blob).unwrap(), b"secret tokens");
+        assert!(decrypt_with_passphrase("wrong", &blob).is_err());
+    }
+
+    #[test]
+    fn test_key_roundtrip() {
+        let key = [7u8; 32];
+        let blob = encrypt_with_key(&key, b"refresh").unwrap();
+        assert_eq!(blob.kdf, "none");
+        assert_eq!(decrypt_with_key(&key, &blob).unwrap(), b"refresh");
+        assert!(decrypt_with_key(&[8u8; 32], &blob).is_err());
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex(&mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn test_verify_ed25519() {
+        use ed25519_dalek::{Signer, SigningKey};
+        let signing = SigningKey::from_bytes(&[9u8; 32]);
+        let public = general_purpose::STANDARD.encode(signing.verifying_key().to_bytes());
+        let signature = general_purpose::STANDARD.encode(signing.sign(b"installer").to_bytes());
+        assert!(verify_ed25519(&public, b"installer", &signature).is_ok());
+        assert!(verify_ed25519(&public, b"tampered", &signature).is_err());
+        assert!(verify_ed25519("not-a-key", b"installer", &signature).is_err());
+    }
+}
@@ -1,11 +1,11 @@
-use reqwest::{Client, Proxy};
+use reqwest::{Client, ClientBuilder};
 use crate::modules::config::load_app_config;
 
 /// 创建统一配置的 HTTP 客户端
 /// 自动加载全局配置并应用代理
 pub fn create_client(timeout_secs: u64) -> Client {
     if let Ok(config) = load_app_config() {
-        create_client_with_proxy(timeout_secs, Some(config.proxy.upstream_proxy))
+        create_client_with_proxy(timeout_secs, Some(config.proxy.effective().upstream_proxy))
     } else {
         create_client_with_proxy(timeout_secs, None)
     }
@@ -16,22 +16,60 @@ pub fn create_client_with_proxy(
     timeout_secs: u64, 
     proxy_config: Option<crate::proxy::config::UpstreamProxyConfig>
 ) -> Client {
-    let mut builder = Client::builder()
+    let builder = Client::builder()
         .timeout(std::time::Duration::from_secs(timeout_secs));
 
+    with_proxy(builder, proxy_config).build().unwrap_or_else(|_| Client::new())
+}
+
+/// 访问 Google 接口 (OAuth、配额、项目) 的 HTTP 客户端
+/// 在全局代理之外应用上游 TLS 校验 (自定义 CA 与证书固定)
+pub fn create_upstream_client(timeout_secs: u64) -> Client {
+    crate::proxy::upstream::tls::apply(client_builder(timeout_secs), &upstream_tls(), true)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// 上游客户端 (第三方模型服务、z.ai MCP) 的 ClientBuilder: 应用指定的上游代理与当前的上游 TLS 校验
+pub fn upstream_builder(
+    timeout_secs: u64,
+    proxy_config: Option<&crate::proxy::config::UpstreamProxyConfig>,
+) -> Result<ClientBuilder, String> {
+    let mut builder = Client::builder().timeout(std::time::Duration::from_secs(timeout_secs));
+    if let Some(config) = proxy_config {
+        builder = config.apply(builder)?;
+    }
+    Ok(crate::proxy::upstream::tls::apply(builder, &upstream_tls(), true))
+}
+
+/// 反代运行时使用其生效的 TLS 配置，否则读取应用配置
+fn upstream_tls() -> crate::proxy::config::UpstreamTlsConfig {
+    crate::proxy::upstream::tls::current().unwrap_or_else(|| {
+        load_app_config()
+            .map(|config| config.proxy.effective().upstream_tls)
+            .unwrap_or_default()
+    })
+}
+
+/// 应用全局代理配置的 ClientBuilder (调用方可继续设置 User-Agent 等)
+pub fn client_builder(timeout_secs: u64) -> ClientBuilder {
+    let builder = Client::builder().timeout(std::time::Duration::from_secs(timeout_secs));
+    let proxy_config = load_app_config().ok().map(|config| config.proxy.effective().upstream_proxy);
+    with_proxy(builder, proxy_config)
+}
+
+fn with_proxy(mut builder: ClientBuilder, proxy_config: Option<crate::proxy::config::UpstreamProxyConfig>) -> ClientBuilder {
     if let Some(config) = proxy_config {
-        if config.enabled && !config.url.is_empty() {
-            match Proxy::all(&config.url) {
-                Ok(proxy) => {
-                    builder = builder.proxy(proxy);
-                    tracing::info!("HTTP 客户端已启用上游代理: {}", config.url);
-                }
-                Err(e) => {
-                    tracing::error!("无效的代理地址: {}, 错误: {}", config.url, e);
-                }
+        match config.reqwest_proxy() {
+            Ok(Some(proxy)) => {
+                builder = builder.no_proxy().proxy(proxy);
+                tracing::info!("HTTP 客户端已启用上游代理: {}", config.display_url());
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("无效的代理地址: {}, 错误: {}", config.display_url(), e);
             }
         }
     }
-
-    builder.build().unwrap_or_else(|_| Client::new())
+    builder
 }
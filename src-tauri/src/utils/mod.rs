@@ -1,2 +1,7 @@
 pub mod http;
 pub mod protobuf;
+pub mod crypto;
+pub mod qr;
+pub mod blocking;
+pub mod app;
+pub mod redact;
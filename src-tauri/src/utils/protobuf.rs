@@ -106,6 +106,30 @@ pub fn find_field(data: &[u8], target_field: u32) -> Result<Option<Vec<u8>>, Str
     Ok(None)
 }
 
+/// 查找指定的 Protobuf Varint 字段
+pub fn find_varint_field(data: &[u8], target_field: u32) -> Result<Option<u64>, String> {
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let (tag, new_offset) = match read_varint(data, offset) {
+            Ok(v) => v,
+            Err(_) => break,
+        };
+
+        let wire_type = (tag & 7) as u8;
+        let field_num = (tag >> 3) as u32;
+
+        if field_num == target_field && wire_type == 0 {
+            let (value, _) = read_varint(data, new_offset)?;
+            return Ok(Some(value));
+        }
+
+        offset = skip_field(data, new_offset, wire_type)?;
+    }
+
+    Ok(None)
+}
+
 /// 创建 OAuthTokenInfo (Field 6)
 /// 
 /// 结构：
@@ -146,7 +170,7 @@ pub fn create_oauth_field(access_token: &str, refresh_token: &str, expiry: i64)
 
     // Field 4: expiry (嵌套的 Timestamp 消息, wire_type = 2)
     // Timestamp 消息包含: Field 1: seconds (int64, wire_type = 0)
-    let timestamp_tag = (1 << 3) | 0;  // Field 1, varint
+    let timestamp_tag = 1 << 3;  // Field 1, varint
     let timestamp_msg = {
         let mut m = encode_varint(timestamp_tag);
         m.extend(encode_varint(expiry as u64));
@@ -0,0 +1,455 @@
+// 二维码生成 (字节模式，纠错等级 M，版本 1-40 自动选择)，输出 SVG
+//
+// 用于在界面上展示账号分享串与移动端扫码配置，按 ISO/IEC 18004 实现编码、Reed-Solomon 纠错与掩码选择。
+
+/// 纠错等级 M 每块纠错码字数 (下标为版本号)
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+/// 纠错等级 M 的纠错块数 (下标为版本号)
+const NUM_ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31,
+    33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+/// 格式信息中纠错等级 M 的编码
+const ECL_M_FORMAT_BITS: u32 = 0;
+/// SVG 四周留白 (模块数)
+const QUIET_ZONE: usize = 4;
+
+/// 二维码模块矩阵
+pub struct QrCode {
+    size: usize,
+    modules: Vec<Vec<bool>>,
+    is_function: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    /// 以字节模式编码文本
+    pub fn encode(text: &str) -> Result<Self, String> {
+        let data = text.as_bytes();
+        let version = (1..=40)
+            .find(|&v| 4 + char_count_bits(v) + data.len() * 8 <= num_data_codewords(v) * 8)
+            .ok_or_else(|| "内容过长，无法生成二维码".to_string())?;
+
+        let mut bits: Vec<bool> = Vec::new();
+        append_bits(&mut bits, 0b0100, 4);
+        append_bits(&mut bits, data.len() as u32, char_count_bits(version));
+        for &b in data {
+            append_bits(&mut bits, b as u32, 8);
+        }
+        let capacity = num_data_codewords(version) * 8;
+        let terminator = (capacity - bits.len()).min(4);
+        append_bits(&mut bits, 0, terminator);
+        let align = (8 - bits.len() % 8) % 8;
+        append_bits(&mut bits, 0, align);
+        for pad in [0xEC, 0x11].iter().cycle() {
+            if bits.len() >= capacity {
+                break;
+            }
+            append_bits(&mut bits, *pad, 8);
+        }
+
+        let codewords: Vec<u8> = bits
+            .chunks(8)
+            .map(|chunk| chunk.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+            .collect();
+
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&add_ecc_and_interleave(&codewords, version));
+
+        let best_mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(mask);
+                let penalty = qr.penalty_score();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(best_mask);
+        qr.draw_format_bits(best_mask);
+        Ok(qr)
+    }
+
+    /// 渲染为 SVG (含留白)
+    pub fn to_svg(&self) -> String {
+        let total = self.size + QUIET_ZONE * 2;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.modules[y][x] {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+                }
+            }
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {0} {0}\" shape-rendering=\"crispEdges\">\
+             <rect width=\"100%\" height=\"100%\" fill=\"#fff\"/><path d=\"{1}\" fill=\"#000\"/></svg>",
+            total, path
+        )
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder(x, y);
+        }
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // 与定位图形重叠的三个角跳过
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                self.draw_alignment(x, y);
+            }
+        }
+
+        // 先占位格式信息，掩码选定后再写入
+        self.draw_format_bits(0);
+        self.draw_version(version);
+    }
+
+    fn draw_finder(&mut self, cx: usize, cy: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                if x < 0 || y < 0 || x >= self.size as i32 || y >= self.size as i32 {
+                    continue;
+                }
+                let dist = dx.abs().max(dy.abs());
+                self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, cx: usize, cy: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((cx as i32 + dx) as usize, (cy as i32 + dy) as usize, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let bits = format_bits(mask);
+        let size = self.size;
+        for i in 0..=5 {
+            self.set_function(8, i, get_bit(bits, i));
+        }
+        self.set_function(8, 7, get_bit(bits, 6));
+        self.set_function(8, 8, get_bit(bits, 7));
+        self.set_function(7, 8, get_bit(bits, 8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, get_bit(bits, i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, get_bit(bits, i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, get_bit(bits, i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let bits = version_bits(version);
+        for i in 0..18 {
+            let bit = get_bit(bits, i);
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, bit);
+            self.set_function(b, a, bit);
+        }
+    }
+
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let total_bits = data.len() * 8;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.is_function[y][x] && i < total_bits {
+                        self.modules[y][x] = get_bit(data[i >> 3] as u32, 7 - (i & 7));
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    fn penalty_score(&self) -> usize {
+        let size = self.size;
+        let row = |y: usize| -> Vec<bool> { self.modules[y].clone() };
+        let col = |x: usize| -> Vec<bool> { (0..size).map(|y| self.modules[y][x]).collect() };
+        let lines: Vec<Vec<bool>> = (0..size).map(row).chain((0..size).map(col)).collect();
+
+        let mut score = 0;
+        const FINDER_LIKE: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+        for line in &lines {
+            // 连续同色模块
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                } else {
+                    if run >= 5 {
+                        score += run - 2;
+                    }
+                    run = 1;
+                }
+            }
+            // 类定位图形
+            for window in line.windows(11) {
+                if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                    score += 40;
+                }
+            }
+        }
+
+        // 2x2 同色块
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.modules[y][x];
+                if c == self.modules[y][x + 1] && c == self.modules[y + 1][x] && c == self.modules[y + 1][x + 1] {
+                    score += 3;
+                }
+            }
+        }
+
+        // 深浅比例
+        let total = size * size;
+        let dark = self.modules.iter().flatten().filter(|&&m| m).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        score + deviation.div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+fn append_bits(bits: &mut Vec<bool>, value: u32, len: usize) {
+    for i in (0..len).rev() {
+        bits.push((value >> i) & 1 != 0);
+    }
+}
+
+fn get_bit(value: u32, i: usize) -> bool {
+    (value >> i) & 1 != 0
+}
+
+fn char_count_bits(version: usize) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+/// 15 位格式信息 (纠错等级 M + 掩码，含 BCH 校验)
+fn format_bits(mask: u32) -> u32 {
+    let data = (ECL_M_FORMAT_BITS << 3) | mask;
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    ((data << 10) | rem) ^ 0x5412
+}
+
+/// 18 位版本信息 (版本 7 及以上)
+fn version_bits(version: usize) -> u32 {
+    let mut rem = version as u32;
+    for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+    }
+    ((version as u32) << 12) | rem
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let size = version * 4 + 17;
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let count = version / 7 + 2;
+        result -= (25 * count - 10) * count - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize) -> usize {
+    num_raw_data_modules(version) / 8 - ECC_CODEWORDS_PER_BLOCK[version] * NUM_ERROR_CORRECTION_BLOCKS[version]
+}
+
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(num_blocks);
+    let mut offset = 0;
+    for i in 0..num_blocks {
+        let data_len = short_block_len - ecc_len + usize::from(i >= num_short_blocks);
+        let mut block = data[offset..offset + data_len].to_vec();
+        offset += data_len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        if i < num_short_blocks {
+            block.push(0);
+        }
+        block.extend_from_slice(&ecc);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            // 短块中的占位字节不输出
+            if i != short_block_len - ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_multiply(d, factor);
+        }
+    }
+    result
+}
+
+/// GF(2^8) 乘法 (本原多项式 0x11D)
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_version_bits_match_spec() {
+        // ISO/IEC 18004 附录中的参考值
+        assert_eq!(format_bits(0), 0b101010000010010);
+        assert_eq!(version_bits(7), 0x07C94);
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(num_data_codewords(1), 16);
+        assert_eq!(num_data_codewords(40), 2334);
+    }
+
+    #[test]
+    fn test_reed_solomon_matches_reference_codewords() {
+        // 常见的 1-M "HELLO WORLD" 示例
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+        let ecc = reed_solomon_remainder(&data, &reed_solomon_divisor(10));
+        assert_eq!(ecc, vec![196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn test_encode_picks_smallest_version_and_draws_finders() {
+        let qr = QrCode::encode("hello").unwrap();
+        assert_eq!(qr.size, 21);
+        // 左上角定位图形: 外框深色、内圈浅色、中心深色
+        let dark = |x: usize, y: usize| qr.modules[y][x];
+        assert!(dark(0, 0) && dark(6, 6) && dark(3, 3));
+        assert!(!dark(1, 1) && !dark(7, 7));
+        assert!(dark(8, qr.size - 8));
+
+        let long = "x".repeat(300);
+        assert!(QrCode::encode(&long).unwrap().size > 21);
+        assert!(QrCode::encode(&"x".repeat(3000)).is_err());
+        assert!(qr.to_svg().starts_with("<svg"));
+    }
+}
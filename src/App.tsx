@@ -7,6 +7,8 @@ import Settings from './pages/Settings';
 import ApiProxy from './pages/ApiProxy';
 import Monitor from './pages/Monitor';
 import ThemeManager from './components/common/ThemeManager';
+import CloseConfirmDialog from './components/common/CloseConfirmDialog';
+import DeepLinkHandler from './components/common/DeepLinkHandler';
 import { UpdateNotification } from './components/UpdateNotification';
 import { useEffect, useState } from 'react';
 import { useConfigStore } from './stores/useConfigStore';
@@ -14,6 +16,8 @@ import { useAccountStore } from './stores/useAccountStore';
 import { useTranslation } from 'react-i18next';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
+import { showToast } from './components/common/ToastContainer';
+import { AccountHealthReport, PoolExhaustedEvent } from './types/account';
 
 const router = createBrowserRouter([
   {
@@ -47,7 +51,7 @@ const router = createBrowserRouter([
 function App() {
   const { config, loadConfig } = useConfigStore();
   const { fetchCurrentAccount, fetchAccounts } = useAccountStore();
-  const { i18n } = useTranslation();
+  const { t, i18n } = useTranslation();
 
   useEffect(() => {
     loadConfig();
@@ -82,13 +86,63 @@ function App() {
       })
     );
 
+    // IDE 刷新的 Token 已同步回管理器
+    unlistenPromises.push(
+      listen('account://ide-token-synced', () => {
+        fetchAccounts();
+      })
+    );
+
+    // 监听账号存储被外部修改 (脚本或另一实例)
+    unlistenPromises.push(
+      listen('accounts://store-changed', () => {
+        fetchAccounts();
+      })
+    );
+
+    // 切换配置档案后重新加载配置与账号
+    unlistenPromises.push(
+      listen('profile://switched', () => {
+        loadConfig();
+        fetchCurrentAccount();
+        fetchAccounts();
+      })
+    );
+
+    // 监听账号池耗尽 (全部账号冷却或隔离)
+    unlistenPromises.push(
+      listen<PoolExhaustedEvent>('proxy://pool-exhausted', (event) => {
+        const { cooling, quarantined } = event.payload;
+        showToast(t('proxy.pool_exhausted', { cooling, quarantined }), 'error', 10000);
+        fetchAccounts();
+      })
+    );
+
+    // 新添加账号的端到端验证结果
+    unlistenPromises.push(
+      listen<AccountHealthReport>('account://verified', (event) => {
+        const report = event.payload;
+        const failed = report.stages.find(s => !s.ok);
+        if (failed) {
+          showToast(t('accounts.verification.verification_failed', {
+            email: report.email,
+            stage: t(`accounts.verification.stages.${failed.stage}`),
+            error: failed.error ?? '',
+          }), 'error', 10000);
+        } else {
+          showToast(t('accounts.verification.verified', { email: report.email }), 'success');
+        }
+        fetchAccounts();
+      })
+    );
+
     // Cleanup
     return () => {
       Promise.all(unlistenPromises).then(unlisteners => {
         unlisteners.forEach(unlisten => unlisten());
       });
     };
-  }, [fetchCurrentAccount, fetchAccounts]);
+  }, [fetchCurrentAccount, fetchAccounts, loadConfig, t]);
 
   // Update notification state
   const [showUpdateNotification, setShowUpdateNotification] = useState(false);
@@ -115,12 +169,23 @@ function App() {
 
     // Delay check to avoid blocking initial render
     const timer = setTimeout(checkUpdates, 2000);
-    return () => clearTimeout(timer);
+
+    // 后台检查发现新版本 (同时已弹出系统通知)
+    const unlistenUpdate = listen('update://available', () => {
+      setShowUpdateNotification(true);
+    });
+
+    return () => {
+      clearTimeout(timer);
+      unlistenUpdate.then(unlisten => unlisten());
+    };
   }, []);
 
   return (
     <>
       <ThemeManager />
+      <CloseConfirmDialog />
+      <DeepLinkHandler />
       {showUpdateNotification && (
         <UpdateNotification onClose={() => setShowUpdateNotification(false)} />
       )}
@@ -8,6 +8,7 @@ interface UpdateInfo {
   latest_version: string;
   current_version: string;
   download_url: string;
+  skipped?: boolean;
 }
 
 interface UpdateNotificationProps {
@@ -27,7 +28,7 @@ export const UpdateNotification: React.FC<UpdateNotificationProps> = ({ onClose
   const checkForUpdates = async () => {
     try {
       const info = await invoke<UpdateInfo>('check_for_updates');
-      if (info.has_update) {
+      if (info.has_update && !info.skipped) {
         setUpdateInfo(info);
         // Small delay to ensure smooth entry animation
         setTimeout(() => setIsVisible(true), 100);
@@ -47,6 +48,17 @@ export const UpdateNotification: React.FC<UpdateNotificationProps> = ({ onClose
     }
   };
 
+  const handleSkip = async () => {
+    if (updateInfo) {
+      try {
+        await invoke('skip_update_version', { version: updateInfo.latest_version });
+      } catch (error) {
+        console.error('Failed to skip version:', error);
+      }
+    }
+    handleClose();
+  };
+
   const handleClose = () => {
     setIsClosing(true);
     setIsVisible(false);
@@ -141,6 +153,13 @@ export const UpdateNotification: React.FC<UpdateNotificationProps> = ({ onClose
             {/* Shimmer effect */}
             <div className="absolute inset-0 -translate-x-full group-hover/btn:animate-[shimmer_1.5s_infinite] bg-gradient-to-r from-transparent via-white/20 to-transparent z-20 pointer-events-none" />
           </button>
+
+          <button
+            onClick={handleSkip}
+            className="w-full mt-2 py-1.5 text-xs text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200 transition-colors duration-200"
+          >
+            {t('update_notification.skip')}
+          </button>
         </div>
       </div>
     </div>
@@ -287,6 +287,16 @@ function AccountRowContent({
                             </span>
                         )}
 
+                        {account.quarantined && (
+                            <span
+                                className="px-2 py-0.5 rounded-md bg-red-100 dark:bg-red-900/50 text-red-700 dark:text-red-300 text-[10px] font-bold flex items-center gap-1 shadow-sm border border-red-200/50"
+                                title={account.quarantine_reason || t('accounts.quarantined_tooltip')}
+                            >
+                                <Ban className="w-2.5 h-2.5" />
+                                <span>{t('accounts.quarantined')}</span>
+                            </span>
+                        )}
+
                         {account.quota?.is_forbidden && (
                             <span className="px-2 py-0.5 rounded-md bg-red-100 dark:bg-red-900/50 text-red-600 dark:text-red-400 text-[10px] font-bold flex items-center gap-1 shadow-sm border border-red-200/50" title={t('accounts.forbidden_tooltip')}>
                                 <Lock className="w-2.5 h-2.5" />
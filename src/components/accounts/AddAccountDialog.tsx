@@ -6,6 +6,8 @@ import { useTranslation } from 'react-i18next';
 import { listen } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-dialog';
 import { request as invoke } from '../../utils/request';
+import { detectIdeCredentials } from '../../services/accountService';
+import { IdeCredentialStatus } from '../../types/account';
 
 interface AddAccountDialogProps {
     onAdd: (email: string, refreshToken: string) => Promise<void>;
@@ -24,8 +26,9 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
     // UI State
     const [status, setStatus] = useState<Status>('idle');
     const [message, setMessage] = useState('');
+    const [ideStatus, setIdeStatus] = useState<IdeCredentialStatus | null>(null);
 
-    const { startOAuthLogin, completeOAuthLogin, cancelOAuthLogin, importFromDb, importV1Accounts, importFromCustomDb } = useAccountStore();
+    const { startOAuthLogin, completeOAuthLogin, cancelOAuthLogin, importFromDb, importV1Accounts, importFromCustomDb, importGeminiCliCredentials, importToolCredentials } = useAccountStore();
 
     const oauthUrlRef = useRef(oauthUrl);
     const statusRef = useRef(status);
@@ -46,6 +49,17 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
         }
     }, [isOpen, activeTab]);
 
+    // 切换到导入页时探测本机 IDE 登录状态
+    useEffect(() => {
+        if (!isOpen || activeTab !== 'import') return;
+        detectIdeCredentials()
+            .then(setIdeStatus)
+            .catch(error => {
+                console.error('Failed to detect IDE credentials:', error);
+                setIdeStatus(null);
+            });
+    }, [isOpen, activeTab]);
+
     // Listen for OAuth URL
     useEffect(() => {
         let unlisten: (() => void) | undefined;
@@ -298,6 +312,10 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
         handleAction(t('accounts.add.import.btn_v1'), importV1Accounts);
     };
 
+    const handleImportGeminiCli = () => {
+        handleAction(t('accounts.add.import.btn_gemini_cli'), importGeminiCliCredentials);
+    };
+
     const handleImportCustomDb = async () => {
         try {
             const selected = await open({
@@ -319,6 +337,18 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
         }
     };
 
+    // CLIProxyAPI / gcli2api 的凭据目录 (每个账号一个 JSON 文件)
+    const handleImportToolCredentials = async () => {
+        try {
+            const selected = await open({ directory: true, multiple: false });
+            if (selected && typeof selected === 'string') {
+                handleAction(t('accounts.add.import.btn_tool_credentials'), () => importToolCredentials(selected));
+            }
+        } catch (err) {
+            console.error('Failed to open dialog:', err);
+        }
+    };
+
     // 状态提示组件
     const StatusAlert = () => {
         if (status === 'idle' || !message) return null;
@@ -490,6 +520,20 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
                                         <p className="text-xs text-gray-500 dark:text-gray-400">
                                             {t('accounts.add.import.scheme_a_desc')}
                                         </p>
+                                        {ideStatus && (
+                                            <div
+                                                className={`text-xs px-3 py-2 rounded-lg ${ideStatus.logged_in && !ideStatus.imported_account_id
+                                                    ? 'bg-blue-50 dark:bg-blue-900/20 text-blue-600 dark:text-blue-400'
+                                                    : 'bg-gray-50 dark:bg-base-200 text-gray-500 dark:text-gray-400'}`}
+                                                title={ideStatus.db_path || undefined}
+                                            >
+                                                {!ideStatus.logged_in
+                                                    ? t('accounts.add.import.detected_none')
+                                                    : ideStatus.imported_account_id
+                                                        ? t('accounts.add.import.detected_imported', { email: ideStatus.imported_email })
+                                                        : t('accounts.add.import.detected_login')}
+                                            </div>
+                                        )}
                                         <button
                                             className="w-full px-4 py-3 bg-gray-50 dark:bg-base-200 text-gray-700 dark:text-gray-300 font-medium rounded-xl border border-gray-200 dark:border-base-300 hover:bg-blue-50 dark:hover:bg-blue-900/20 hover:border-blue-200 dark:hover:border-blue-800 hover:text-blue-600 dark:hover:text-blue-400 transition-all flex items-center justify-center gap-2 disabled:opacity-50 disabled:cursor-not-allowed mb-2 shadow-sm"
                                             onClick={handleImportDb}
@@ -506,6 +550,24 @@ function AddAccountDialog({ onAdd }: AddAccountDialogProps) {
                                             <Database className="w-4 h-4" />
                                             {t('accounts.add.import.btn_custom_db') || 'Custom DB (state.vscdb)'}
                                         </button>
+                                        <button
+                                            className="w-full px-4 py-3 bg-gray-50 dark:bg-base-200 text-gray-700 dark:text-gray-300 font-medium rounded-xl border border-gray-200 dark:border-base-300 hover:bg-indigo-50 dark:hover:bg-indigo-900/20 hover:border-indigo-200 dark:hover:border-indigo-800 hover:text-indigo-600 dark:hover:text-indigo-400 transition-all flex items-center justify-center gap-2 disabled:opacity-50 disabled:cursor-not-allowed shadow-sm"
+                                            onClick={handleImportGeminiCli}
+                                            disabled={status === 'loading' || status === 'success'}
+                                            title="~/.gemini/oauth_creds.json"
+                                        >
+                                            <FileClock className="w-4 h-4" />
+                                            {t('accounts.add.import.btn_gemini_cli')}
+                                        </button>
+                                        <button
+                                            className="w-full px-4 py-3 bg-gray-50 dark:bg-base-200 text-gray-700 dark:text-gray-300 font-medium rounded-xl border border-gray-200 dark:border-base-300 hover:bg-indigo-50 dark:hover:bg-indigo-900/20 hover:border-indigo-200 dark:hover:border-indigo-800 hover:text-indigo-600 dark:hover:text-indigo-400 transition-all flex items-center justify-center gap-2 disabled:opacity-50 disabled:cursor-not-allowed shadow-sm"
+                                            onClick={handleImportToolCredentials}
+                                            disabled={status === 'loading' || status === 'success'}
+                                            title={t('accounts.add.import.btn_tool_credentials_desc')}
+                                        >
+                                            <Database className="w-4 h-4" />
+                                            {t('accounts.add.import.btn_tool_credentials')}
+                                        </button>
                                     </div>
 
                                     <div className="divider text-xs text-gray-300 dark:text-gray-600">{t('accounts.add.import.or')}</div>
@@ -1,6 +1,7 @@
 import { useEffect, useRef } from 'react';
 import { useConfigStore } from '../../stores/useConfigStore';
 import { useAccountStore } from '../../stores/useAccountStore';
+import { getPowerStatus } from '../../services/configService';
 
 function BackgroundTaskRunner() {
     const { config } = useConfigStore();
@@ -26,7 +27,13 @@ function BackgroundTaskRunner() {
 
         if (auto_refresh && refresh_interval > 0) {
             console.log(`[BackgroundTask] Starting auto-refresh quota timer: ${refresh_interval} mins`);
-            intervalId = setInterval(() => {
+            intervalId = setInterval(async () => {
+                // 电池供电 / 计费网络 / 离线时跳过，条件解除后的下一轮自动恢复
+                const power = await getPowerStatus().catch(() => null);
+                if (power?.paused) {
+                    console.log(`[BackgroundTask] Auto-refresh paused (${power.reason})`);
+                    return;
+                }
                 console.log('[BackgroundTask] Auto-refreshing all quotas...');
                 refreshAllQuotas();
             }, refresh_interval * 60 * 1000);
@@ -0,0 +1,84 @@
+import { useEffect, useState } from 'react';
+import { createPortal } from 'react-dom';
+import { useTranslation } from 'react-i18next';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import { LogOut, MinusSquare } from 'lucide-react';
+import { useConfigStore } from '../../stores/useConfigStore';
+
+type CloseChoice = 'tray' | 'quit';
+
+// 关闭方式为「每次询问」时，点击关闭按钮弹出的确认框
+export default function CloseConfirmDialog() {
+    const { t } = useTranslation();
+    const { loadConfig } = useConfigStore();
+    const [isOpen, setIsOpen] = useState(false);
+    const [remember, setRemember] = useState(false);
+
+    useEffect(() => {
+        const unlisten = listen('window://close-requested', () => {
+            setRemember(false);
+            setIsOpen(true);
+        });
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, []);
+
+    if (!isOpen) return null;
+
+    const choose = async (action: CloseChoice) => {
+        setIsOpen(false);
+        try {
+            await invoke('confirm_window_close', { action, remember });
+            if (remember) {
+                await loadConfig();
+            }
+        } catch (error) {
+            console.error('Failed to close window:', error);
+        }
+    };
+
+    return createPortal(
+        <div className="modal modal-open z-[100]">
+            {/* Draggable Top Region */}
+            <div data-tauri-drag-region className="fixed top-0 left-0 right-0 h-8 z-[110]" />
+
+            <div className="modal-box relative max-w-sm bg-white dark:bg-base-100 shadow-2xl rounded-2xl p-0 overflow-hidden transform transition-all animate-in fade-in zoom-in-95 duration-200">
+                <div className="flex flex-col items-center text-center p-6 pt-8">
+                    <h3 className="text-xl font-bold text-gray-900 dark:text-base-content mb-2">{t('close_confirm.title')}</h3>
+                    <p className="text-gray-500 dark:text-gray-400 text-sm mb-6 leading-relaxed px-4">{t('close_confirm.message')}</p>
+
+                    <label className="flex items-center gap-2 mb-6 text-sm text-gray-600 dark:text-gray-400 cursor-pointer">
+                        <input
+                            type="checkbox"
+                            className="checkbox checkbox-sm"
+                            checked={remember}
+                            onChange={(e) => setRemember(e.target.checked)}
+                        />
+                        {t('close_confirm.remember')}
+                    </label>
+
+                    <div className="flex gap-3 w-full">
+                        <button
+                            className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 bg-gray-100 dark:bg-base-200 text-gray-700 dark:text-gray-300 font-medium rounded-xl hover:bg-gray-200 dark:hover:bg-base-300 transition-colors focus:outline-none focus:ring-2 focus:ring-gray-200 dark:focus:ring-base-300"
+                            onClick={() => choose('quit')}
+                        >
+                            <LogOut className="w-4 h-4" />
+                            {t('close_confirm.quit')}
+                        </button>
+                        <button
+                            className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 text-white font-medium rounded-xl shadow-md transition-all focus:outline-none focus:ring-2 focus:ring-offset-2 bg-blue-500 hover:bg-blue-600 focus:ring-blue-500 shadow-blue-100"
+                            onClick={() => choose('tray')}
+                        >
+                            <MinusSquare className="w-4 h-4" />
+                            {t('close_confirm.tray')}
+                        </button>
+                    </div>
+                </div>
+            </div>
+            <div className="modal-backdrop bg-black/40 backdrop-blur-sm fixed inset-0 z-[-1]" onClick={() => setIsOpen(false)}></div>
+        </div>,
+        document.body
+    );
+}
@@ -0,0 +1,138 @@
+import { useEffect, useState } from 'react';
+import { createPortal } from 'react-dom';
+import { useTranslation } from 'react-i18next';
+import { listen } from '@tauri-apps/api/event';
+import { Copy, Link } from 'lucide-react';
+import { ClientSnippet, DeepLinkAction } from '../../types/config';
+import { getClientSnippets, takePendingDeepLink } from '../../services/configService';
+import { importAccountShare } from '../../services/accountService';
+import { useAccountStore } from '../../stores/useAccountStore';
+import { showToast } from './ToastContainer';
+
+// 处理 antigravity-manager:// 深度链接: 导入账号分享串 (需输入口令) 或生成指向指定地址的客户端配置
+export default function DeepLinkHandler() {
+    const { t } = useTranslation();
+    const { fetchAccounts } = useAccountStore();
+    const [action, setAction] = useState<DeepLinkAction | null>(null);
+    const [passphrase, setPassphrase] = useState('');
+    const [snippets, setSnippets] = useState<ClientSnippet[]>([]);
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        const take = async () => {
+            try {
+                const pending = await takePendingDeepLink();
+                if (!pending) return;
+                setPassphrase('');
+                setSnippets([]);
+                setAction(pending);
+                if (pending.action === 'client_setup') {
+                    setSnippets(await getClientSnippets({ baseUrl: pending.base_url, apiKey: pending.api_key ?? undefined }));
+                }
+            } catch (error) {
+                showToast(`${t('common.error')}: ${error}`, 'error');
+            }
+        };
+
+        // 应用由链接启动时，链接在前端加载前已到达
+        take();
+        const unlisten = listen('deep-link://received', take);
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, [t]);
+
+    if (!action) return null;
+
+    const close = () => setAction(null);
+
+    const handleImport = async () => {
+        if (action.action !== 'import_account' || !passphrase) return;
+        setBusy(true);
+        try {
+            const account = await importAccountShare(action.share, passphrase);
+            showToast(t('deep_link.imported', { email: account.email }), 'success');
+            fetchAccounts();
+            close();
+        } catch (error) {
+            showToast(`${t('common.error')}: ${error}`, 'error');
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const copy = (text: string) => {
+        navigator.clipboard.writeText(text).then(() => showToast(t('deep_link.copied'), 'success'));
+    };
+
+    return createPortal(
+        <div className="modal modal-open z-[100]">
+            {/* Draggable Top Region */}
+            <div data-tauri-drag-region className="fixed top-0 left-0 right-0 h-8 z-[110]" />
+
+            <div className="modal-box relative max-w-2xl bg-white dark:bg-base-100 shadow-2xl rounded-2xl p-6">
+                <h3 className="flex items-center gap-2 text-xl font-bold text-gray-900 dark:text-base-content mb-2">
+                    <Link className="w-5 h-5 text-blue-500" />
+                    {t(`deep_link.${action.action}.title`)}
+                </h3>
+
+                {action.action === 'import_account' ? (
+                    <>
+                        <p className="text-gray-500 dark:text-gray-400 text-sm mb-4">{t('deep_link.import_account.desc')}</p>
+                        <input
+                            type="password"
+                            autoFocus
+                            className="w-full px-4 py-2 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 text-gray-900 dark:text-base-content bg-white dark:bg-base-100 mb-6"
+                            placeholder={t('deep_link.import_account.passphrase')}
+                            value={passphrase}
+                            onChange={(e) => setPassphrase(e.target.value)}
+                            onKeyDown={(e) => e.key === 'Enter' && handleImport()}
+                        />
+                    </>
+                ) : (
+                    <>
+                        <p className="text-gray-500 dark:text-gray-400 text-sm mb-4">
+                            {t('deep_link.client_setup.desc', { url: action.base_url })}
+                        </p>
+                        <div className="space-y-3 max-h-[50vh] overflow-y-auto mb-6">
+                            {snippets.map(snippet => (
+                                <div key={snippet.client} className="border border-gray-100 dark:border-base-300 rounded-lg">
+                                    <div className="flex items-center justify-between px-3 py-2 bg-gray-50 dark:bg-base-200 rounded-t-lg">
+                                        <span className="text-sm font-medium text-gray-900 dark:text-base-content">
+                                            {snippet.title}
+                                            {snippet.file && <span className="ml-2 text-xs text-gray-500 font-mono">{snippet.file}</span>}
+                                        </span>
+                                        <button className="btn btn-ghost btn-xs" onClick={() => copy(snippet.content)}>
+                                            <Copy className="w-3.5 h-3.5" />
+                                        </button>
+                                    </div>
+                                    <pre className="px-3 py-2 text-xs font-mono whitespace-pre-wrap break-all text-gray-700 dark:text-gray-300">{snippet.content}</pre>
+                                </div>
+                            ))}
+                        </div>
+                    </>
+                )}
+
+                <div className="flex justify-end gap-3">
+                    <button
+                        className="px-4 py-2.5 bg-gray-100 dark:bg-base-200 text-gray-700 dark:text-gray-300 font-medium rounded-xl hover:bg-gray-200 dark:hover:bg-base-300 transition-colors"
+                        onClick={close}
+                    >
+                        {t(action.action === 'import_account' ? 'common.cancel' : 'common.close')}
+                    </button>
+                    {action.action === 'import_account' && (
+                        <button
+                            className="px-4 py-2.5 text-white font-medium rounded-xl shadow-md bg-blue-500 hover:bg-blue-600 disabled:opacity-50"
+                            disabled={busy || !passphrase}
+                            onClick={handleImport}
+                        >
+                            {t('deep_link.import_account.confirm')}
+                        </button>
+                    )}
+                </div>
+            </div>
+            <div className="modal-backdrop bg-black/40 backdrop-blur-sm fixed inset-0 z-[-1]" onClick={close}></div>
+        </div>,
+        document.body
+    );
+}
@@ -2,6 +2,7 @@
 import { useEffect } from 'react';
 import { useConfigStore } from '../../stores/useConfigStore';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { invoke } from '@tauri-apps/api/core';
 
 export default function ThemeManager() {
     const { config, loadConfig } = useConfigStore();
@@ -10,6 +11,9 @@ export default function ThemeManager() {
     useEffect(() => {
         const init = async () => {
             await loadConfig();
+            // 开机自启 (--minimized) 或隐藏启动 (--hidden) 时只保留托盘，不弹出窗口
+            const minimized = await invoke<boolean>('is_launched_minimized').catch(() => false);
+            if (minimized) return;
             // Show window after a short delay to ensure React has painted
             setTimeout(async () => {
                 await getCurrentWindow().show();
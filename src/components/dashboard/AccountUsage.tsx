@@ -0,0 +1,81 @@
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { BarChart3 } from 'lucide-react';
+import { request as invoke } from '../../utils/request';
+import { AccountUsageStats } from '../../types/account';
+import { formatCompactNumber, formatRelativeTime } from '../../utils/format';
+
+// 超过 7 天未使用的账号视为闲置
+const IDLE_SECONDS = 7 * 24 * 3600;
+
+function AccountUsage() {
+    const { t, i18n } = useTranslation();
+    const [stats, setStats] = useState<AccountUsageStats[]>([]);
+
+    useEffect(() => {
+        invoke<AccountUsageStats[]>('get_account_usage_stats', { windowHours: 24 })
+            .then(setStats)
+            .catch(error => console.error('[Dashboard] Failed to load usage stats:', error));
+    }, []);
+
+    if (stats.length === 0) return null;
+
+    const now = Date.now() / 1000;
+    const isIdle = (s: AccountUsageStats) => !s.last_request_at || now - s.last_request_at > IDLE_SECONDS;
+    const errorRate = (s: AccountUsageStats) =>
+        s.total_requests > 0 ? Math.round((s.total_errors / s.total_requests) * 100) : 0;
+
+    return (
+        <div className="bg-white dark:bg-base-100 rounded-xl p-4 shadow-sm border border-gray-100 dark:border-base-200">
+            <h2 className="text-base font-semibold text-gray-900 dark:text-base-content mb-3 flex items-center gap-2">
+                <BarChart3 className="w-4 h-4 text-blue-500 dark:text-blue-400" />
+                {t('dashboard.usage.title')}
+            </h2>
+
+            <div className="overflow-x-auto">
+                <table className="w-full text-xs">
+                    <thead>
+                        <tr className="text-left text-gray-500 dark:text-gray-400 border-b border-gray-100 dark:border-base-200">
+                            <th className="py-1.5 font-medium">{t('dashboard.usage.account')}</th>
+                            <th className="py-1.5 font-medium text-right">{t('dashboard.usage.requests_24h')}</th>
+                            <th className="py-1.5 font-medium text-right">{t('dashboard.usage.total_requests')}</th>
+                            <th className="py-1.5 font-medium text-right">{t('dashboard.usage.tokens')}</th>
+                            <th className="py-1.5 font-medium text-right">{t('dashboard.usage.error_rate')}</th>
+                            <th className="py-1.5 font-medium text-right">{t('dashboard.usage.last_used')}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {stats.map(s => (
+                            <tr
+                                key={s.account_id}
+                                className={`border-b border-gray-50 dark:border-base-200 ${isIdle(s) ? 'text-gray-400 dark:text-gray-500' : 'text-gray-700 dark:text-gray-300'}`}
+                            >
+                                <td className="py-1.5 truncate max-w-[200px]">
+                                    {s.account_email}
+                                    {isIdle(s) && (
+                                        <span className="ml-1.5 px-1.5 py-0.5 rounded bg-gray-100 dark:bg-base-200 text-[10px]">
+                                            {t('dashboard.usage.idle')}
+                                        </span>
+                                    )}
+                                </td>
+                                <td className="py-1.5 text-right">{formatCompactNumber(s.window_requests)}</td>
+                                <td className="py-1.5 text-right">{formatCompactNumber(s.total_requests)}</td>
+                                <td className="py-1.5 text-right">
+                                    {formatCompactNumber(s.total_input_tokens + s.total_output_tokens)}
+                                </td>
+                                <td className={`py-1.5 text-right ${errorRate(s) >= 20 ? 'text-red-500' : ''}`}>
+                                    {errorRate(s)}%
+                                </td>
+                                <td className="py-1.5 text-right">
+                                    {s.last_request_at ? formatRelativeTime(s.last_request_at, i18n.language) : t('dashboard.usage.never')}
+                                </td>
+                            </tr>
+                        ))}
+                    </tbody>
+                </table>
+            </div>
+        </div>
+    );
+}
+
+export default AccountUsage;
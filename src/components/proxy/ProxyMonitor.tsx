@@ -23,6 +23,7 @@ interface ProxyRequestLog {
     input_tokens?: number;
     output_tokens?: number;
     account_email?: string;
+    project_id?: string;
 }
 
 interface ProxyStats {
@@ -452,6 +453,12 @@ export const ProxyMonitor: React.FC<ProxyMonitorProps> = ({ className }) => {
                                         <span className="font-mono font-semibold text-gray-900 dark:text-white text-xs">{selectedLog.account_email}</span>
                                     </div>
                                 )}
+                                {selectedLog.project_id && (
+                                    <div className="mt-5 pt-5 border-t border-gray-200 dark:border-slate-700">
+                                        <span className="block text-gray-500 dark:text-slate-400 uppercase font-black text-[10px] tracking-widest mb-2">{t('monitor.details.project')}</span>
+                                        <span className="font-mono font-semibold text-gray-900 dark:text-white text-xs">{selectedLog.project_id}</span>
+                                    </div>
+                                )}
                             </div>
 
                             {/* Payloads */}
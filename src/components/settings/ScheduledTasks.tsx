@@ -0,0 +1,145 @@
+import React, { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { CalendarClock, Play } from 'lucide-react';
+import { ScheduledTaskKind, ScheduledTaskRun, ScheduledTaskStatus } from '../../types/config';
+import { getScheduledTaskHistory, listScheduledTasks, runScheduledTask, updateScheduledTask } from '../../services/configService';
+import { showToast } from '../common/ToastContainer';
+
+const formatTime = (secs?: number | null) => (secs ? new Date(secs * 1000).toLocaleString() : '-');
+
+const ScheduledTasks: React.FC = () => {
+    const { t } = useTranslation();
+    const [tasks, setTasks] = useState<ScheduledTaskStatus[]>([]);
+    const [history, setHistory] = useState<ScheduledTaskRun[]>([]);
+    // 正在编辑的计划 (失焦时保存)
+    const [drafts, setDrafts] = useState<Partial<Record<ScheduledTaskKind, string>>>({});
+
+    const refresh = async () => {
+        try {
+            const [list, runs] = await Promise.all([listScheduledTasks(), getScheduledTaskHistory(undefined, 10)]);
+            setTasks(list);
+            setHistory(runs);
+        } catch (error) {
+            showToast(String(error), 'error');
+        }
+    };
+
+    useEffect(() => {
+        refresh();
+    }, []);
+
+    const save = async (task: ScheduledTaskStatus, enabled: boolean, schedule: string) => {
+        try {
+            await updateScheduledTask(task.kind, enabled, schedule);
+            setDrafts(prev => {
+                const next = { ...prev };
+                delete next[task.kind];
+                return next;
+            });
+            await refresh();
+        } catch (error) {
+            showToast(`${t('settings.tasks.save_failed')}: ${error}`, 'error');
+        }
+    };
+
+    const runNow = async (task: ScheduledTaskStatus) => {
+        setTasks(tasks.map(item => (item.kind === task.kind ? { ...item, running: true } : item)));
+        try {
+            const run = await runScheduledTask(task.kind);
+            showToast(run.message, run.success ? 'success' : 'error');
+        } catch (error) {
+            showToast(String(error), 'error');
+        }
+        await refresh();
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="flex items-center gap-2">
+                <CalendarClock size={18} className="text-blue-500" />
+                <h3 className="font-medium text-gray-900 dark:text-base-content">{t('settings.tasks.title')}</h3>
+            </div>
+            <p className="text-sm text-gray-500 dark:text-gray-400">{t('settings.tasks.desc')}</p>
+
+            <div className="overflow-x-auto">
+                <table className="table table-sm">
+                    <thead>
+                        <tr>
+                            <th>{t('settings.tasks.task')}</th>
+                            <th>{t('settings.tasks.schedule')}</th>
+                            <th>{t('settings.tasks.next_run')}</th>
+                            <th>{t('settings.tasks.last_result')}</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {tasks.map(task => (
+                            <tr key={task.kind}>
+                                <td>
+                                    <label className="flex items-center gap-2 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            className="toggle toggle-sm toggle-primary"
+                                            checked={task.enabled}
+                                            onChange={(e) => save(task, e.target.checked, drafts[task.kind] ?? task.schedule)}
+                                        />
+                                        <span>{t(`settings.tasks.kinds.${task.kind}`)}</span>
+                                    </label>
+                                </td>
+                                <td>
+                                    <input
+                                        type="text"
+                                        className="input input-bordered input-sm w-36 font-mono"
+                                        value={drafts[task.kind] ?? task.schedule}
+                                        onChange={(e) => setDrafts({ ...drafts, [task.kind]: e.target.value })}
+                                        onBlur={() => {
+                                            const draft = drafts[task.kind];
+                                            if (draft !== undefined && draft !== task.schedule) {
+                                                save(task, task.enabled, draft);
+                                            }
+                                        }}
+                                    />
+                                </td>
+                                <td className="text-xs">{formatTime(task.next_run_at)}</td>
+                                <td className="text-xs max-w-xs truncate" title={task.last_result?.message}>
+                                    {task.last_result ? (
+                                        <span className={task.last_result.success ? 'text-green-600' : 'text-red-500'}>
+                                            {formatTime(task.last_result.started_at)} · {task.last_result.message}
+                                        </span>
+                                    ) : '-'}
+                                </td>
+                                <td>
+                                    <button
+                                        className="btn btn-ghost btn-xs"
+                                        disabled={task.running}
+                                        onClick={() => runNow(task)}
+                                        title={t('settings.tasks.run_now')}
+                                    >
+                                        <Play size={14} className={task.running ? 'animate-pulse' : ''} />
+                                    </button>
+                                </td>
+                            </tr>
+                        ))}
+                    </tbody>
+                </table>
+            </div>
+            <p className="text-xs text-gray-400">{t('settings.tasks.schedule_hint')}</p>
+
+            {history.length > 0 && (
+                <details className="text-xs">
+                    <summary className="cursor-pointer text-gray-500">{t('settings.tasks.history')}</summary>
+                    <ul className="mt-2 space-y-1">
+                        {history.map(run => (
+                            <li key={`${run.kind}-${run.started_at}`} className={run.success ? '' : 'text-red-500'}>
+                                {formatTime(run.started_at)} · {t(`settings.tasks.kinds.${run.kind}`)}
+                                {run.manual ? ` (${t('settings.tasks.manual')})` : ''} · {run.duration_ms} ms · {run.message}
+                            </li>
+                        ))}
+                    </ul>
+                </details>
+            )}
+        </div>
+    );
+};
+
+export default ScheduledTasks;
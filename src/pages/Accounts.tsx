@@ -2,7 +2,8 @@ import { useState, useEffect, useMemo, useRef } from 'react';
 import { save } from '@tauri-apps/plugin-dialog';
 import { request as invoke } from '../utils/request';
 import { join } from '@tauri-apps/api/path';
-import { Search, RefreshCw, Download, Trash2, LayoutGrid, List, ToggleLeft, ToggleRight, Sparkles } from 'lucide-react';
+import { listen } from '@tauri-apps/api/event';
+import { Search, RefreshCw, Download, Trash2, LayoutGrid, List, ToggleLeft, ToggleRight, Sparkles, KeyRound } from 'lucide-react';
 import { useAccountStore } from '../stores/useAccountStore';
 import { useConfigStore } from '../stores/useConfigStore';
 import AccountTable from '../components/accounts/AccountTable';
@@ -13,7 +14,8 @@ import AddAccountDialog from '../components/accounts/AddAccountDialog';
 import ModalDialog from '../components/common/ModalDialog';
 import Pagination from '../components/common/Pagination';
 import { showToast } from '../components/common/ToastContainer';
-import { Account } from '../types/account';
+import { Account, TokenRefreshProgress } from '../types/account';
+import { refreshAllTokens } from '../services/accountService';
 import { cn } from '../utils/cn';
 
 // ... (省略中间代码)
@@ -386,6 +388,30 @@ function Accounts() {
 
     const [isRefreshing, setIsRefreshing] = useState(false);
     const [isRefreshConfirmOpen, setIsRefreshConfirmOpen] = useState(false);
+    const [tokenRefreshProgress, setTokenRefreshProgress] = useState<{ completed: number; total: number } | null>(null);
+
+    const handleRefreshTokens = async () => {
+        if (tokenRefreshProgress) return;
+        setTokenRefreshProgress({ completed: 0, total: 0 });
+        const unlisten = await listen<TokenRefreshProgress>('account://token-refresh-progress', (event) => {
+            setTokenRefreshProgress({ completed: event.payload.completed, total: event.payload.total });
+        });
+        try {
+            const report = await refreshAllTokens();
+            await fetchAccounts();
+            if (report.needs_attention.length === 0) {
+                showToast(t('accounts.refresh_tokens_done', { count: report.refreshed }), 'success');
+            } else {
+                showToast(t('accounts.refresh_tokens_attention', { count: report.refreshed, attention: report.needs_attention.length }), 'warning');
+                console.warn('Accounts needing attention:', report.needs_attention);
+            }
+        } catch (error) {
+            showToast(`${t('common.error')}: ${error}`, 'error');
+        } finally {
+            unlisten();
+            setTokenRefreshProgress(null);
+        }
+    };
 
     const handleRefreshClick = () => {
         setIsRefreshConfirmOpen(true);
@@ -696,6 +722,20 @@ function Accounts() {
                         </span>
                     </button>
 
+                    <button
+                        className={`px-2.5 py-2 border border-gray-200 dark:border-base-300 text-gray-700 dark:text-gray-300 text-xs font-medium rounded-lg hover:bg-gray-50 dark:hover:bg-base-200 transition-colors flex items-center gap-1.5 ${tokenRefreshProgress ? 'opacity-70 cursor-not-allowed' : ''}`}
+                        onClick={handleRefreshTokens}
+                        disabled={!!tokenRefreshProgress}
+                        title={t('accounts.refresh_tokens')}
+                    >
+                        <KeyRound className={`w-3.5 h-3.5 ${tokenRefreshProgress ? 'animate-pulse' : ''}`} />
+                        <span className="hidden xl:inline">
+                            {tokenRefreshProgress
+                                ? `${tokenRefreshProgress.completed}/${tokenRefreshProgress.total || '…'}`
+                                : t('accounts.refresh_tokens')}
+                        </span>
+                    </button>
+
                     <button
                         className={`px-2.5 py-2 bg-orange-500 text-white text-xs font-medium rounded-lg hover:bg-orange-600 transition-colors flex items-center gap-1.5 shadow-sm ${isWarmuping ? 'opacity-70 cursor-not-allowed' : ''}`}
                         onClick={() => setIsWarmupConfirmOpen(true)}
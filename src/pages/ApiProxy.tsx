@@ -22,12 +22,16 @@ import {
     Activity,
     Check,
     X,
-    Edit2
+    Edit2,
+    Wand2,
+    Undo2,
+    Globe
 } from 'lucide-react';
-import { AppConfig, ProxyConfig, StickySessionConfig, ExperimentalConfig } from '../types/config';
+import { AppConfig, ClaudeCodeStatus, ProxyConfig, StickySessionConfig, ExperimentalConfig, TunnelConfig, TunnelStatus } from '../types/config';
 import HelpTooltip from '../components/common/HelpTooltip';
 import ModalDialog from '../components/common/ModalDialog';
 import { showToast } from '../components/common/ToastContainer';
+import { configureClaudeCode, copyClientValue, getClaudeCodeStatus, getTunnelStatus, startTunnel, stopTunnel, undoClaudeCodeConfig } from '../services/configService';
 import { cn } from '../utils/cn';
 import { useProxyModels } from '../hooks/useProxyModels';
 import GroupedSelect, { SelectOption } from '../components/common/GroupedSelect';
@@ -144,6 +148,9 @@ export default function ApiProxy() {
     const [appConfig, setAppConfig] = useState<AppConfig | null>(null);
     const [loading, setLoading] = useState(false);
     const [copied, setCopied] = useState<string | null>(null);
+    const [claudeCode, setClaudeCode] = useState<ClaudeCodeStatus | null>(null);
+    const [tunnel, setTunnel] = useState<TunnelStatus | null>(null);
+    const [tunnelLoading, setTunnelLoading] = useState(false);
     const [selectedProtocol, setSelectedProtocol] = useState<'openai' | 'anthropic' | 'gemini'>('openai');
     const [selectedModelId, setSelectedModelId] = useState('gemini-3-flash');
     const [zaiAvailableModels, setZaiAvailableModels] = useState<string[]>([]);
@@ -187,6 +194,7 @@ export default function ApiProxy() {
     useEffect(() => {
         loadConfig();
         loadStatus();
+        getClaudeCodeStatus().then(setClaudeCode).catch(() => setClaudeCode(null));
         const interval = setInterval(loadStatus, 3000);
         return () => clearInterval(interval);
     }, []);
@@ -204,6 +212,7 @@ export default function ApiProxy() {
         try {
             const s = await invoke<ProxyStatus>('get_proxy_status');
             setStatus(s);
+            setTunnel(await getTunnelStatus());
         } catch (error) {
             console.error('获取状态失败:', error);
         }
@@ -497,6 +506,62 @@ export default function ApiProxy() {
         });
     };
 
+    // 复制一行 Claude Code 环境变量 (ANTHROPIC_BASE_URL + ANTHROPIC_AUTH_TOKEN)
+    const copyClientEnv = async () => {
+        try {
+            await copyClientValue('anthropic_env');
+            setCopied('anthropic-env');
+            setTimeout(() => setCopied(null), 2000);
+        } catch (error: any) {
+            showToast(t('proxy.dialog.operate_failed', { error: error.toString() }), 'error');
+        }
+    };
+
+    // 一键配置 Claude Code (写入 ~/.claude/settings.json)，可撤销
+    const applyClaudeCodeConfig = async () => {
+        try {
+            const result = await configureClaudeCode();
+            setClaudeCode(result);
+            showToast(t('proxy.multi_protocol.claude_code_configured', { path: result.settings_path }), 'success');
+        } catch (error: any) {
+            showToast(t('proxy.dialog.operate_failed', { error: error.toString() }), 'error');
+        }
+    };
+
+    const revertClaudeCodeConfig = async () => {
+        try {
+            setClaudeCode(await undoClaudeCodeConfig());
+            showToast(t('proxy.multi_protocol.claude_code_restored'), 'success');
+        } catch (error: any) {
+            showToast(t('proxy.dialog.operate_failed', { error: error.toString() }), 'error');
+        }
+    };
+
+    // 远程访问隧道
+    const toggleTunnel = async () => {
+        setTunnelLoading(true);
+        try {
+            setTunnel(tunnel?.running ? await stopTunnel() : await startTunnel());
+        } catch (error: any) {
+            showToast(t('proxy.dialog.operate_failed', { error: error.toString() }), 'error');
+        } finally {
+            setTunnelLoading(false);
+        }
+    };
+
+    const updateTunnelConfig = (updates: Partial<TunnelConfig>) => {
+        if (!appConfig) return;
+        const current: TunnelConfig = appConfig.proxy.tunnel || {
+            auto_start: false,
+            provider: 'ssh',
+            ssh_target: '',
+            ssh_port: 22,
+            remote_port: 8045,
+            remote_bind_address: '',
+        };
+        updateProxyConfig({ tunnel: { ...current, ...updates } });
+    };
+
     // API Key editing functions
     const validateApiKey = (key: string): boolean => {
         // Must start with 'sk-' and be at least 10 characters long
@@ -852,6 +917,80 @@ print(response.text)`;
                                 </div>
                             </div>
 
+                            {/* 远程访问隧道 */}
+                            <div className="border-t border-gray-200 dark:border-base-300 pt-3 space-y-2">
+                                <div className="flex items-center justify-between">
+                                    <span className="text-xs font-medium text-gray-700 dark:text-gray-300 inline-flex items-center gap-1">
+                                        <Globe size={14} />
+                                        {t('proxy.config.tunnel.title')}
+                                        <HelpTooltip
+                                            text={t('proxy.config.tunnel.tooltip')}
+                                            ariaLabel={t('proxy.config.tunnel.title')}
+                                            placement="right"
+                                        />
+                                    </span>
+                                    <div className="flex items-center gap-3">
+                                        <label className="flex items-center cursor-pointer gap-2">
+                                            <span className="text-[11px] text-gray-600 dark:text-gray-400">{t('proxy.config.tunnel.auto_start')}</span>
+                                            <input
+                                                type="checkbox"
+                                                className="toggle toggle-sm bg-gray-200 dark:bg-gray-700 border-gray-300 dark:border-gray-600 checked:bg-blue-500 checked:border-blue-500"
+                                                checked={appConfig.proxy.tunnel?.auto_start || false}
+                                                onChange={(e) => updateTunnelConfig({ auto_start: e.target.checked })}
+                                            />
+                                        </label>
+                                        <button
+                                            onClick={toggleTunnel}
+                                            disabled={tunnelLoading || !status.running}
+                                            className="px-3 py-1 rounded-lg text-xs font-medium border bg-white dark:bg-base-200 text-gray-600 dark:text-gray-300 border-gray-200 dark:border-base-300 hover:bg-gray-50 dark:hover:bg-base-300 disabled:opacity-50 disabled:cursor-not-allowed"
+                                        >
+                                            {tunnelLoading
+                                                ? t('proxy.status.processing')
+                                                : (tunnel?.running ? t('proxy.config.tunnel.stop') : t('proxy.config.tunnel.start'))}
+                                        </button>
+                                    </div>
+                                </div>
+                                <div className="grid grid-cols-1 md:grid-cols-3 gap-3">
+                                    <select
+                                        value={appConfig.proxy.tunnel?.provider || 'ssh'}
+                                        onChange={(e) => updateTunnelConfig({ provider: e.target.value as TunnelConfig['provider'] })}
+                                        className="w-full px-2.5 py-1.5 border border-gray-300 dark:border-base-200 rounded-lg bg-white dark:bg-base-200 text-xs text-gray-900 dark:text-base-content"
+                                    >
+                                        <option value="ssh">{t('proxy.config.tunnel.providers.ssh')}</option>
+                                        <option value="cloudflared">{t('proxy.config.tunnel.providers.cloudflared')}</option>
+                                    </select>
+                                    {(appConfig.proxy.tunnel?.provider || 'ssh') === 'ssh' && (
+                                        <>
+                                            <input
+                                                type="text"
+                                                value={appConfig.proxy.tunnel?.ssh_target || ''}
+                                                onChange={(e) => updateTunnelConfig({ ssh_target: e.target.value })}
+                                                placeholder="user@vps.example.com"
+                                                className="w-full px-2.5 py-1.5 border border-gray-300 dark:border-base-200 rounded-lg bg-white dark:bg-base-200 text-xs font-mono text-gray-900 dark:text-base-content"
+                                            />
+                                            <input
+                                                type="number"
+                                                value={appConfig.proxy.tunnel?.remote_port || 8045}
+                                                onChange={(e) => updateTunnelConfig({ remote_port: parseInt(e.target.value) || 8045 })}
+                                                min={1}
+                                                max={65535}
+                                                title={t('proxy.config.tunnel.remote_port')}
+                                                className="w-full px-2.5 py-1.5 border border-gray-300 dark:border-base-200 rounded-lg bg-white dark:bg-base-200 text-xs text-gray-900 dark:text-base-content"
+                                            />
+                                        </>
+                                    )}
+                                </div>
+                                {tunnel?.running && tunnel.public_url && (
+                                    <p className="text-[10px] text-green-600 dark:text-green-400 font-mono">
+                                        {t('proxy.config.tunnel.public_url', { url: tunnel.public_url })}
+                                    </p>
+                                )}
+                                {tunnel?.last_error && (
+                                    <p className="text-[10px] text-red-600 dark:text-red-400">{tunnel.last_error}</p>
+                                )}
+                                <p className="text-[10px] text-amber-600 dark:text-amber-500">{t('proxy.config.tunnel.auth_hint')}</p>
+                            </div>
+
                             {/* API 密钥 */}
                             <div>
                                 <label className="block text-xs font-medium text-gray-700 dark:text-gray-300 mb-1">
@@ -1292,6 +1431,29 @@ print(response.text)`;
                                             <div className="w-11 h-6 bg-gray-200 dark:bg-base-300 peer-focus:outline-none rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-purple-500 shadow-inner"></div>
                                         </label>
                                     </div>
+
+                                    <div className="flex items-center justify-between p-4 bg-gray-50 dark:bg-base-200 rounded-xl border border-gray-100 dark:border-base-300">
+                                        <div className="space-y-1">
+                                            <div className="flex items-center gap-2">
+                                                <span className="text-sm font-bold text-gray-900 dark:text-base-content">
+                                                    {t('proxy.config.experimental.enable_request_coalescing')}
+                                                </span>
+                                                <HelpTooltip text={t('proxy.config.experimental.enable_request_coalescing_tooltip')} />
+                                            </div>
+                                            <p className="text-[10px] text-gray-500 dark:text-gray-400 max-w-lg">
+                                                {t('proxy.config.experimental.enable_request_coalescing_tooltip')}
+                                            </p>
+                                        </div>
+                                        <label className="relative inline-flex items-center cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                className="sr-only peer"
+                                                checked={appConfig.proxy.experimental?.enable_request_coalescing ?? true}
+                                                onChange={(e) => updateExperimentalConfig({ enable_request_coalescing: e.target.checked })}
+                                            />
+                                            <div className="w-11 h-6 bg-gray-200 dark:bg-base-300 peer-focus:outline-none rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-purple-500 shadow-inner"></div>
+                                        </label>
+                                    </div>
                                 </div>
                             </CollapsibleCard>
                         </div>
@@ -1535,9 +1697,34 @@ print(response.text)`;
                                     >
                                         <div className="flex items-center justify-between mb-2">
                                             <span className="text-xs font-bold text-purple-600">{t('proxy.multi_protocol.anthropic_label')}</span>
-                                            <button onClick={(e) => { e.stopPropagation(); copyToClipboard(`${status.base_url}/v1/messages`, 'anthropic'); }} className="btn btn-ghost btn-xs">
-                                                {copied === 'anthropic' ? <CheckCircle size={14} /> : <Copy size={14} />}
-                                            </button>
+                                            <div className="flex items-center">
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); copyClientEnv(); }}
+                                                    className="btn btn-ghost btn-xs"
+                                                    title={t('proxy.multi_protocol.copy_anthropic_env')}
+                                                >
+                                                    {copied === 'anthropic-env' ? <CheckCircle size={14} /> : <Terminal size={14} />}
+                                                </button>
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); applyClaudeCodeConfig(); }}
+                                                    className="btn btn-ghost btn-xs"
+                                                    title={t('proxy.multi_protocol.configure_claude_code')}
+                                                >
+                                                    <Wand2 size={14} />
+                                                </button>
+                                                {claudeCode?.has_backup && (
+                                                    <button
+                                                        onClick={(e) => { e.stopPropagation(); revertClaudeCodeConfig(); }}
+                                                        className="btn btn-ghost btn-xs"
+                                                        title={t('proxy.multi_protocol.undo_claude_code')}
+                                                    >
+                                                        <Undo2 size={14} />
+                                                    </button>
+                                                )}
+                                                <button onClick={(e) => { e.stopPropagation(); copyToClipboard(`${status.base_url}/v1/messages`, 'anthropic'); }} className="btn btn-ghost btn-xs">
+                                                    {copied === 'anthropic' ? <CheckCircle size={14} /> : <Copy size={14} />}
+                                                </button>
+                                            </div>
                                         </div>
                                         <code className="text-[10px] block truncate bg-black/5 dark:bg-white/5 p-1 rounded">/v1/messages</code>
                                     </div>
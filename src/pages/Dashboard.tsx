@@ -5,6 +5,7 @@ import { Users, Sparkles, Bot, AlertTriangle, ArrowRight, Download, RefreshCw }
 import { useAccountStore } from '../stores/useAccountStore';
 import CurrentAccount from '../components/dashboard/CurrentAccount';
 import BestAccounts from '../components/dashboard/BestAccounts';
+import AccountUsage from '../components/dashboard/AccountUsage';
 import AddAccountDialog from '../components/accounts/AddAccountDialog';
 import { save } from '@tauri-apps/plugin-dialog';
 import { request as invoke } from '../utils/request';
@@ -261,6 +262,9 @@ function Dashboard() {
                     />
                 </div>
 
+                {/* 账号用量统计 */}
+                <AccountUsage />
+
                 {/* 快速链接 */}
                 <div className="grid grid-cols-2 gap-3">
                     <button
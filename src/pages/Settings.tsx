@@ -3,15 +3,25 @@ import { Save, Github, User, MessageCircle, ExternalLink, RefreshCw, Sparkles, H
 import { request as invoke } from '../utils/request';
 import { open } from '@tauri-apps/plugin-dialog';
 import { useConfigStore } from '../stores/useConfigStore';
-import { AppConfig } from '../types/config';
+import { AppConfig, NotificationConfig, UpdateSettings } from '../types/config';
 import ModalDialog from '../components/common/ModalDialog';
 import { showToast } from '../components/common/ToastContainer';
 import QuotaProtection from '../components/settings/QuotaProtection';
 import SmartWarmup from '../components/settings/SmartWarmup';
+import ScheduledTasks from '../components/settings/ScheduledTasks';
 
 import { useTranslation } from 'react-i18next';
 
 
+// 与后端 NotificationConfig 默认值一致 (旧配置中可能不存在该字段)
+const DEFAULT_NOTIFICATIONS: NotificationConfig = {
+    pool_exhausted: true,
+    reauth_required: true,
+    proxy_restarted: true,
+    quota_threshold: 10,
+    update_available: true,
+};
+
 function Settings() {
     const { t } = useTranslation();
     const { config, loadConfig, saveConfig } = useConfigStore();
@@ -52,6 +62,9 @@ function Settings() {
     const [isSupportModalOpen, setIsSupportModalOpen] = useState(false);
     const [dataDirPath, setDataDirPath] = useState<string>('~/.antigravity_tools/');
 
+    // Update settings (kept whole so saving one field does not reset the others)
+    const [updateSettings, setUpdateSettings] = useState<UpdateSettings | null>(null);
+
     // Update check state
     const [isCheckingUpdate, setIsCheckingUpdate] = useState(false);
     const [updateInfo, setUpdateInfo] = useState<{
@@ -70,12 +83,14 @@ function Settings() {
             .catch(err => console.error('Failed to get data dir:', err));
 
         // 加载更新设置
-        invoke<{ auto_check: boolean; last_check_time: number; check_interval_hours: number }>('get_update_settings')
+        invoke<UpdateSettings>('get_update_settings')
             .then(settings => {
+                setUpdateSettings(settings);
                 setFormData(prev => ({
                     ...prev,
                     auto_check_update: settings.auto_check,
-                    update_check_interval: settings.check_interval_hours
+                    update_check_interval: settings.check_interval_hours,
+                    update_channel: settings.channel
                 }));
             })
             .catch(err => console.error('Failed to load update settings:', err));
@@ -88,6 +103,19 @@ function Settings() {
             .catch(err => console.error('Failed to get auto launch status:', err));
     }, [loadConfig]);
 
+    const saveUpdateSettings = async (patch: Partial<UpdateSettings>) => {
+        const settings: UpdateSettings = {
+            auto_check: formData.auto_check_update ?? true,
+            check_interval_hours: formData.update_check_interval ?? 24,
+            channel: formData.update_channel ?? 'stable',
+            ...updateSettings,
+            ...patch,
+            last_check_time: 0
+        };
+        await invoke('save_update_settings', { settings });
+        setUpdateSettings(settings);
+    };
+
     useEffect(() => {
         if (config) {
             setFormData(config);
@@ -318,6 +346,157 @@ function Settings() {
                                 <p className="text-sm text-gray-500 dark:text-gray-400 mt-2">{t('settings.general.auto_launch_desc')}</p>
                             </div>
 
+                            {/* 隐藏启动 (只有托盘，作为后台服务运行) */}
+                            <div className="flex items-center justify-between p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300">
+                                <div>
+                                    <div className="font-medium text-gray-900 dark:text-base-content">{t('settings.general.start_hidden')}</div>
+                                    <p className="text-sm text-gray-600 dark:text-gray-400 mt-1">{t('settings.general.start_hidden_desc')}</p>
+                                </div>
+                                <label className="relative inline-flex items-center cursor-pointer">
+                                    <input
+                                        type="checkbox"
+                                        className="sr-only peer"
+                                        checked={formData.start_hidden ?? false}
+                                        onChange={async (e) => {
+                                            if (!config) return;
+                                            const start_hidden = e.target.checked;
+                                            try {
+                                                await saveConfig({ ...config, start_hidden });
+                                                setFormData({ ...formData, start_hidden });
+                                            } catch (error) {
+                                                showToast(`${t('common.error')}: ${error}`, 'error');
+                                            }
+                                        }}
+                                    />
+                                    <div className="w-11 h-6 bg-gray-200 dark:bg-base-300 peer-focus:outline-none peer-focus:ring-4 peer-focus:ring-blue-300 dark:peer-focus:ring-blue-800 rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-blue-500"></div>
+                                </label>
+                            </div>
+
+                            {/* 启动时自动开启反代服务 */}
+                            <div className="flex items-center justify-between p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300">
+                                <div>
+                                    <div className="font-medium text-gray-900 dark:text-base-content">{t('proxy.config.auto_start')}</div>
+                                    <p className="text-sm text-gray-600 dark:text-gray-400 mt-1">{t('proxy.config.auto_start_tooltip')}</p>
+                                </div>
+                                <label className="relative inline-flex items-center cursor-pointer">
+                                    <input
+                                        type="checkbox"
+                                        className="sr-only peer"
+                                        checked={formData.proxy?.auto_start ?? false}
+                                        onChange={async (e) => {
+                                            if (!config) return;
+                                            const auto_start = e.target.checked;
+                                            try {
+                                                await saveConfig({ ...config, proxy: { ...config.proxy, auto_start } });
+                                                setFormData({ ...formData, proxy: { ...formData.proxy, auto_start } });
+                                            } catch (error) {
+                                                showToast(`${t('common.error')}: ${error}`, 'error');
+                                            }
+                                        }}
+                                    />
+                                    <div className="w-11 h-6 bg-gray-200 dark:bg-base-300 peer-focus:outline-none peer-focus:ring-4 peer-focus:ring-blue-300 dark:peer-focus:ring-blue-800 rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-blue-500"></div>
+                                </label>
+                            </div>
+
+                            {/* 关闭窗口时的行为 */}
+                            <div>
+                                <label className="block text-sm font-medium text-gray-900 dark:text-base-content mb-2">{t('settings.general.close_action')}</label>
+                                <select
+                                    className="w-full px-4 py-4 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-900 dark:text-base-content bg-gray-50 dark:bg-base-200"
+                                    value={formData.close_action ?? 'tray'}
+                                    onChange={async (e) => {
+                                        if (!config) return;
+                                        const close_action = e.target.value as AppConfig['close_action'];
+                                        try {
+                                            await saveConfig({ ...config, close_action });
+                                            setFormData({ ...formData, close_action });
+                                        } catch (error) {
+                                            showToast(`${t('common.error')}: ${error}`, 'error');
+                                        }
+                                    }}
+                                >
+                                    <option value="tray">{t('settings.general.close_action_tray')}</option>
+                                    <option value="ask">{t('settings.general.close_action_ask')}</option>
+                                    <option value="quit">{t('settings.general.close_action_quit')}</option>
+                                </select>
+                                <p className="text-sm text-gray-500 dark:text-gray-400 mt-2">{t('settings.general.close_action_desc')}</p>
+                            </div>
+
+                            {/* 切换反代服务的全局快捷键 */}
+                            <div>
+                                <label className="block text-sm font-medium text-gray-900 dark:text-base-content mb-2">{t('settings.general.proxy_toggle_shortcut')}</label>
+                                <input
+                                    type="text"
+                                    className="w-full px-4 py-4 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-900 dark:text-base-content bg-gray-50 dark:bg-base-200"
+                                    placeholder="CmdOrCtrl+Shift+P"
+                                    value={formData.proxy_toggle_shortcut ?? ''}
+                                    onChange={(e) => setFormData({ ...formData, proxy_toggle_shortcut: e.target.value })}
+                                    onBlur={async (e) => {
+                                        if (!config) return;
+                                        const proxy_toggle_shortcut = e.target.value.trim() || null;
+                                        if (proxy_toggle_shortcut === (config.proxy_toggle_shortcut ?? null)) return;
+                                        try {
+                                            await saveConfig({ ...config, proxy_toggle_shortcut });
+                                            showToast(t('settings.general.proxy_toggle_shortcut_saved'), 'success');
+                                        } catch (error) {
+                                            showToast(`${t('common.error')}: ${error}`, 'error');
+                                        }
+                                    }}
+                                />
+                                <p className="text-sm text-gray-500 dark:text-gray-400 mt-2">{t('settings.general.proxy_toggle_shortcut_desc')}</p>
+                            </div>
+
+                            {/* 系统通知 */}
+                            <div className="p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300">
+                                <div className="font-medium text-gray-900 dark:text-base-content">{t('settings.general.notifications')}</div>
+                                <p className="text-sm text-gray-600 dark:text-gray-400 mt-1 mb-3">{t('settings.general.notifications_desc')}</p>
+                                <div className="space-y-2">
+                                    {(['pool_exhausted', 'reauth_required', 'proxy_restarted', 'update_available'] as const).map(key => (
+                                        <label key={key} className="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                className="checkbox checkbox-sm"
+                                                checked={formData.notifications?.[key] ?? true}
+                                                onChange={async (e) => {
+                                                    if (!config) return;
+                                                    const notifications = { ...DEFAULT_NOTIFICATIONS, ...config.notifications, [key]: e.target.checked };
+                                                    try {
+                                                        await saveConfig({ ...config, notifications });
+                                                        setFormData({ ...formData, notifications });
+                                                    } catch (error) {
+                                                        showToast(`${t('common.error')}: ${error}`, 'error');
+                                                    }
+                                                }}
+                                            />
+                                            {t(`settings.general.notify_${key}`)}
+                                        </label>
+                                    ))}
+                                    <label className="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                                        {t('settings.general.notify_quota_threshold')}
+                                        <input
+                                            type="number"
+                                            className="w-20 px-2 py-1 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 text-gray-900 dark:text-base-content bg-white dark:bg-base-100"
+                                            min="0"
+                                            max="100"
+                                            value={formData.notifications?.quota_threshold ?? 10}
+                                            onChange={(e) => setFormData({
+                                                ...formData,
+                                                notifications: { ...DEFAULT_NOTIFICATIONS, ...formData.notifications, quota_threshold: Math.min(100, Math.max(0, parseInt(e.target.value) || 0)) },
+                                            })}
+                                            onBlur={async () => {
+                                                if (!config || !formData.notifications) return;
+                                                try {
+                                                    await saveConfig({ ...config, notifications: formData.notifications });
+                                                } catch (error) {
+                                                    showToast(`${t('common.error')}: ${error}`, 'error');
+                                                }
+                                            }}
+                                        />
+                                        %
+                                    </label>
+                                </div>
+                            </div>
+
                             {/* 自动检查更新 */}
                             <div className="flex items-center justify-between p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300">
                                 <div>
@@ -332,13 +511,7 @@ function Settings() {
                                         onChange={async (e) => {
                                             const enabled = e.target.checked;
                                             try {
-                                                await invoke('save_update_settings', {
-                                                    settings: {
-                                                        auto_check: enabled,
-                                                        last_check_time: 0,
-                                                        check_interval_hours: formData.update_check_interval ?? 24
-                                                    }
-                                                });
+                                                await saveUpdateSettings({ auto_check: enabled });
                                                 setFormData({ ...formData, auto_check_update: enabled });
                                                 showToast(enabled ? t('settings.general.auto_check_update_enabled') : t('settings.general.auto_check_update_disabled'), 'success');
                                             } catch (error) {
@@ -363,13 +536,7 @@ function Settings() {
                                         onChange={(e) => setFormData({ ...formData, update_check_interval: parseInt(e.target.value) })}
                                         onBlur={async () => {
                                             try {
-                                                await invoke('save_update_settings', {
-                                                    settings: {
-                                                        auto_check: formData.auto_check_update ?? true,
-                                                        last_check_time: 0,
-                                                        check_interval_hours: formData.update_check_interval ?? 24
-                                                    }
-                                                });
+                                                await saveUpdateSettings({ check_interval_hours: formData.update_check_interval ?? 24 });
                                                 showToast(t('settings.general.update_check_interval_saved'), 'success');
                                             } catch (error) {
                                                 showToast(`${t('common.error')}: ${error}`, 'error');
@@ -379,6 +546,61 @@ function Settings() {
                                     <p className="text-sm text-gray-500 dark:text-gray-400 mt-2">{t('settings.general.update_check_interval_desc')}</p>
                                 </div>
                             )}
+
+                            {/* 更新通道 */}
+                            <div className="flex items-center justify-between p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300">
+                                <div>
+                                    <div className="font-medium text-gray-900 dark:text-base-content">{t('settings.general.update_channel')}</div>
+                                    <p className="text-sm text-gray-600 dark:text-gray-400 mt-1">{t('settings.general.update_channel_desc')}</p>
+                                </div>
+                                <select
+                                    className="px-4 py-2 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 text-gray-900 dark:text-base-content bg-white dark:bg-base-100"
+                                    value={formData.update_channel ?? 'stable'}
+                                    onChange={async (e) => {
+                                        const channel = e.target.value as 'stable' | 'beta';
+                                        try {
+                                            await saveUpdateSettings({ channel });
+                                            setFormData({ ...formData, update_channel: channel });
+                                        } catch (error) {
+                                            showToast(`${t('common.error')}: ${error}`, 'error');
+                                        }
+                                    }}
+                                >
+                                    <option value="stable">{t('settings.general.update_channel_stable')}</option>
+                                    <option value="beta">{t('settings.general.update_channel_beta')}</option>
+                                </select>
+                            </div>
+
+                            {/* GitHub 镜像 */}
+                            <div className="p-4 bg-gray-50 dark:bg-base-200 rounded-lg border border-gray-100 dark:border-base-300 space-y-3">
+                                <div>
+                                    <div className="font-medium text-gray-900 dark:text-base-content">{t('settings.general.update_mirror')}</div>
+                                    <p className="text-sm text-gray-600 dark:text-gray-400 mt-1">{t('settings.general.update_mirror_desc')}</p>
+                                </div>
+                                {([
+                                    ['github_api_mirror', 'https://api.github.com'],
+                                    ['download_mirror', 'https://ghproxy.net']
+                                ] as const).map(([field, placeholder]) => (
+                                    <div key={field}>
+                                        <label className="block text-sm font-medium text-gray-900 dark:text-base-content mb-1">{t(`settings.general.${field}`)}</label>
+                                        <input
+                                            type="text"
+                                            className="w-full px-4 py-2 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 text-gray-900 dark:text-base-content bg-white dark:bg-base-100"
+                                            placeholder={placeholder}
+                                            value={updateSettings?.[field] ?? ''}
+                                            onChange={(e) => updateSettings && setUpdateSettings({ ...updateSettings, [field]: e.target.value })}
+                                            onBlur={async (e) => {
+                                                try {
+                                                    await saveUpdateSettings({ [field]: e.target.value.trim() || null } as Partial<UpdateSettings>);
+                                                    showToast(t('settings.general.update_mirror_saved'), 'success');
+                                                } catch (error) {
+                                                    showToast(`${t('common.error')}: ${error}`, 'error');
+                                                }
+                                            }}
+                                        />
+                                    </div>
+                                ))}
+                            </div>
                         </div>
                     )}
 
@@ -608,6 +830,10 @@ function Settings() {
                                 </p>
                             </div>
 
+                            <div className="border-t border-gray-200 dark:border-base-200 pt-4">
+                                <ScheduledTasks />
+                            </div>
+
                             <div className="border-t border-gray-200 dark:border-base-200 pt-4">
                                 <h3 className="font-medium text-gray-900 dark:text-base-content mb-3">{t('settings.advanced.logs_title')}</h3>
                                 <div className="bg-gray-50 dark:bg-base-200 border border-gray-200 dark:border-base-300 rounded-lg p-3 mb-3">
@@ -694,6 +920,51 @@ function Settings() {
                                             {t('proxy.config.upstream_proxy.tip')}
                                         </p>
                                     </div>
+
+                                    <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+                                        <div>
+                                            <label className="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
+                                                {t('proxy.config.upstream_proxy.username')}
+                                            </label>
+                                            <input
+                                                type="text"
+                                                autoComplete="off"
+                                                value={formData.proxy?.upstream_proxy?.username || ''}
+                                                onChange={(e) => setFormData({
+                                                    ...formData,
+                                                    proxy: {
+                                                        ...formData.proxy,
+                                                        upstream_proxy: {
+                                                            ...formData.proxy.upstream_proxy,
+                                                            username: e.target.value
+                                                        }
+                                                    }
+                                                })}
+                                                className="w-full px-4 py-3 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-900 dark:text-base-content bg-gray-50 dark:bg-base-200"
+                                            />
+                                        </div>
+                                        <div>
+                                            <label className="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
+                                                {t('proxy.config.upstream_proxy.password')}
+                                            </label>
+                                            <input
+                                                type="password"
+                                                autoComplete="off"
+                                                value={formData.proxy?.upstream_proxy?.password || ''}
+                                                onChange={(e) => setFormData({
+                                                    ...formData,
+                                                    proxy: {
+                                                        ...formData.proxy,
+                                                        upstream_proxy: {
+                                                            ...formData.proxy.upstream_proxy,
+                                                            password: e.target.value
+                                                        }
+                                                    }
+                                                })}
+                                                className="w-full px-4 py-3 border border-gray-200 dark:border-base-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-900 dark:text-base-content bg-gray-50 dark:bg-base-200"
+                                            />
+                                        </div>
+                                    </div>
                                 </div>
                             </div>
                         </div>
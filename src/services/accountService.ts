@@ -1,6 +1,6 @@
 import i18n from '../i18n';
 import { request as invoke } from '../utils/request';
-import { Account, QuotaData, DeviceProfile, DeviceProfileVersion } from '../types/account';
+import { Account, QuotaData, DeviceProfile, DeviceProfileVersion, IdeCredentialStatus, IdeInstallation, IdeBackup, StorageBackup, DeviceReset, BatchImportReport, BulkRefreshReport, AccountEvent, AccountShare, ProfileInfo, ArchivedAccount, ProjectQuotaStatus, TokenUsageSummary, UsageReportPeriod, UsageReportRow, AccountErrorBreakdown, KeyUsageSummary, ModelMappingUsage } from '../types/account';
 
 // 检查 Tauri 环境
 function ensureTauriEnvironment() {
@@ -31,6 +31,19 @@ export async function deleteAccounts(accountIds: string[]): Promise<void> {
     return await invoke('delete_accounts', { accountIds });
 }
 
+// 删除的账号会移入归档，可恢复或永久删除
+export async function listArchivedAccounts(): Promise<ArchivedAccount[]> {
+    return await invoke('list_archived_accounts');
+}
+
+export async function restoreArchivedAccount(accountId: string): Promise<Account> {
+    return await invoke('restore_archived_account', { accountId });
+}
+
+export async function purgeArchivedAccounts(accountIds: string[]): Promise<number> {
+    return await invoke('purge_archived_accounts', { accountIds });
+}
+
 export async function switchAccount(accountId: string): Promise<void> {
     return await invoke('switch_account', { accountId });
 }
@@ -50,6 +63,42 @@ export async function refreshAllQuotas(): Promise<RefreshStats> {
     return await invoke('refresh_all_quotas');
 }
 
+// 批量刷新 Token，进度通过 account://token-refresh-progress 事件推送
+export async function refreshAllTokens(): Promise<BulkRefreshReport> {
+    return await invoke('refresh_all_tokens');
+}
+
+// 配置档案 (每个档案拥有独立的账号与配置)
+export async function listProfiles(): Promise<ProfileInfo[]> {
+    return await invoke('list_profiles');
+}
+
+export async function createProfile(name: string): Promise<void> {
+    return await invoke('create_profile', { name });
+}
+
+export async function deleteProfile(name: string): Promise<void> {
+    return await invoke('delete_profile', { name });
+}
+
+// 切换后推送 profile://switched 事件
+export async function switchProfile(name: string): Promise<void> {
+    return await invoke('switch_profile', { name });
+}
+
+// 单账号加密分享串 (附二维码 SVG)
+export async function exportAccountShare(accountId: string, passphrase: string): Promise<AccountShare> {
+    return await invoke('export_account_share', { accountId, passphrase });
+}
+
+export async function importAccountShare(share: string, passphrase: string): Promise<Account> {
+    return await invoke('import_account_share', { share, passphrase });
+}
+
+export async function listAccountEvents(accountId?: string, limit?: number): Promise<AccountEvent[]> {
+    return await invoke('list_account_events', { accountId, limit });
+}
+
 // OAuth
 export async function startOAuthLogin(): Promise<Account> {
     ensureTauriEnvironment();
@@ -95,6 +144,45 @@ export async function importV1Accounts(): Promise<Account[]> {
     return await invoke('import_v1_accounts');
 }
 
+export async function detectIdeCredentials(): Promise<IdeCredentialStatus> {
+    return await invoke('detect_ide_credentials');
+}
+
+export async function detectIdeInstallation(): Promise<IdeInstallation> {
+    return await invoke('detect_ide_installation');
+}
+
+// 修改 IDE 数据的命令在 IDE 运行中且未传 closeIde 时返回以 IDE_RUNNING 开头的错误，界面确认后带 closeIde: true 重试
+export function isIdeRunningError(error: unknown): boolean {
+    return String(error).startsWith('IDE_RUNNING');
+}
+
+// 正常关闭 IDE，返回 IDE 原本是否在运行
+export async function closeIde(): Promise<boolean> {
+    return await invoke('close_ide');
+}
+
+export async function launchIde(): Promise<void> {
+    return await invoke('launch_ide');
+}
+
+export async function listIdeBackups(): Promise<IdeBackup[]> {
+    return await invoke('list_ide_backups');
+}
+
+export async function createIdeBackup(): Promise<IdeBackup> {
+    return await invoke('create_ide_backup');
+}
+
+// 恢复前会自动备份当前配置，IDE 运行中时需 closeIde: true
+export async function restoreIdeBackup(id: string, closeIde?: boolean): Promise<IdeBackup> {
+    return await invoke('restore_ide_backup', { id, closeIde });
+}
+
+export async function deleteIdeBackup(id: string): Promise<void> {
+    return await invoke('delete_ide_backup', { id });
+}
+
 export async function importFromDb(): Promise<Account> {
     return await invoke('import_from_db');
 }
@@ -103,6 +191,21 @@ export async function importFromCustomDb(path: string): Promise<Account> {
     return await invoke('import_custom_db', { path });
 }
 
+// 导入 gemini-cli 的 oauth_creds.json，不传路径时读取 ~/.gemini/oauth_creds.json
+export async function importGeminiCliCredentials(path?: string): Promise<BatchImportReport> {
+    return await invoke('import_gemini_cli_credentials', { path: path ?? null });
+}
+
+// 从 CLIProxyAPI / gcli2api 的凭据文件或凭据目录导入账号
+export async function importToolCredentials(path: string): Promise<BatchImportReport> {
+    return await invoke('import_tool_credentials', { path });
+}
+
+// 按 CLIProxyAPI / gcli2api 的格式导出账号凭据到目录 (每个账号一个文件)，不传 accountIds 时导出全部，返回导出数量
+export async function exportToolCredentials(dir: string, format: 'cli_proxy_api' | 'gcli2api', accountIds?: string[]): Promise<number> {
+    return await invoke('export_tool_credentials', { dir, format, accountIds: accountIds ?? null });
+}
+
 export async function syncAccountFromDb(): Promise<Account | null> {
     return await invoke('sync_account_from_db');
 }
@@ -111,6 +214,30 @@ export async function toggleProxyStatus(accountId: string, enable: boolean, reas
     return await invoke('toggle_proxy_status', { accountId, enable, reason });
 }
 
+export async function releaseAccountQuarantine(accountId: string): Promise<Account> {
+    return await invoke('release_account_quarantine', { accountId });
+}
+
+// 设置账号固定使用的 project_id，传 null 恢复自动解析
+export async function setAccountProjectOverride(accountId: string, projectId: string | null): Promise<Account> {
+    return await invoke('set_account_project_override', { accountId, projectId });
+}
+
+// 查询各账号当前项目的配额状态 (最接近上限的在前)
+export async function getProjectQuotas(threshold?: number): Promise<ProjectQuotaStatus[]> {
+    return await invoke('get_project_quotas', { threshold });
+}
+
+// 发现账号可访问的全部项目
+export async function discoverAccountProjects(accountId: string): Promise<string[]> {
+    return await invoke('discover_account_projects', { accountId });
+}
+
+// 清除 project_id 缓存并重新解析，返回新的 project_id
+export async function resolveAccountProjectId(accountId: string): Promise<string> {
+    return await invoke('resolve_account_project_id', { accountId });
+}
+
 /**
  * 重新排序账号列表
  * @param accountIds 按新顺序排列的账号ID数组
@@ -138,6 +265,23 @@ export async function restoreOriginalDevice(): Promise<string> {
     return await invoke('restore_original_device');
 }
 
+// 备份 storage.json 后为 IDE 生成全新的设备标识
+export async function resetDeviceIdentity(closeIde?: boolean): Promise<DeviceReset> {
+    return await invoke('reset_device_identity', { closeIde });
+}
+
+export async function listDeviceBackups(): Promise<StorageBackup[]> {
+    return await invoke('list_device_backups');
+}
+
+export async function restoreDeviceBackup(fileName: string, closeIde?: boolean): Promise<DeviceProfile> {
+    return await invoke('restore_device_backup', { fileName, closeIde });
+}
+
+export async function applyDeviceProfile(accountId: string, closeIde?: boolean): Promise<DeviceProfile> {
+    return await invoke('apply_device_profile', { accountId, closeIde });
+}
+
 export async function listDeviceVersions(accountId: string): Promise<DeviceProfilesResponse> {
     return await invoke('list_device_versions', { accountId });
 }
@@ -171,3 +315,32 @@ export async function warmUpAccount(accountId: string): Promise<string> {
     return await invoke('warm_up_account', { accountId });
 }
 
+export async function getTokenUsageSummary(windowHours?: number): Promise<TokenUsageSummary[]> {
+    return await invoke('get_token_usage_summary', { windowHours });
+}
+
+export async function getUsageReport(period: UsageReportPeriod, windowDays?: number): Promise<UsageReportRow[]> {
+    return await invoke('get_usage_report', { period, windowDays });
+}
+
+// 导出 CSV 报表，返回导出的行数
+export async function exportUsageReportCsv(path: string, period: UsageReportPeriod, windowDays?: number): Promise<number> {
+    return await invoke('export_usage_report_csv', { path, period, windowDays });
+}
+
+export async function getAccountErrorBreakdown(windowHours?: number): Promise<AccountErrorBreakdown[]> {
+    return await invoke('get_account_error_breakdown', { windowHours });
+}
+
+// 导出时间范围内 (Unix 毫秒) 记录的请求为 HAR 文件 (已去除凭证)，返回导出的请求数
+export async function exportProxyLogsHar(path: string, since: number, until?: number): Promise<number> {
+    return await invoke('export_proxy_logs_har', { path, since, until });
+}
+
+export async function getKeyUsageStats(windowHours?: number): Promise<KeyUsageSummary[]> {
+    return await invoke('get_key_usage_stats', { windowHours });
+}
+
+export async function getModelMappingStats(windowHours?: number): Promise<ModelMappingUsage[]> {
+    return await invoke('get_model_mapping_stats', { windowHours });
+}
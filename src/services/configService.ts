@@ -1,5 +1,5 @@
 import { request as invoke } from '../utils/request';
-import { AppConfig } from '../types/config';
+import { AppConfig, AppLogPage, AppLogQuery, BenchmarkOptions, BenchmarkReport, ClaudeCodeStatus, ClaudeModelMapping, ClientCopyTarget, ClientSnippet, ConfigDiff, ConfigExportSummary, ConfigHistoryItem, ConfigImportReport, ConfigPresetInfo, ConfigReloadResult, ConfigSyncReport, ConfigSyncSettings, ConfigValidationReport, ConnectionStats, CrashReport, DeepLinkAction, DiagnosticsSummary, EnvOverride, LatencyReport, MobileClientQr, NotificationChannel, PowerStatus, PresetSwitchResult, ProxyConfig, ProxyLoad, ScheduledTaskKind, ScheduledTaskRun, ScheduledTaskStatus, SelfTestReport, SyncBackend, TunnelStatus, UpstreamEndpointStats, UptimeSummary, WebhookConfig } from '../types/config';
 
 export async function loadConfig(): Promise<AppConfig> {
     return await invoke('load_config');
@@ -8,3 +8,222 @@ export async function loadConfig(): Promise<AppConfig> {
 export async function saveConfig(config: AppConfig): Promise<void> {
     return await invoke('save_config', { config });
 }
+
+export async function getUpstreamConnectionStats(): Promise<ConnectionStats> {
+    return await invoke('get_upstream_connection_stats');
+}
+
+export async function getUpstreamEndpointStats(): Promise<UpstreamEndpointStats[]> {
+    return await invoke('get_upstream_endpoint_stats');
+}
+
+export async function getProxyLoad(): Promise<ProxyLoad> {
+    return await invoke('get_proxy_load');
+}
+
+// 校验反代配置 (不传时校验当前生效的配置)，可用于保存前检查表单
+export async function validateProxyConfig(config?: ProxyConfig): Promise<ConfigValidationReport> {
+    return await invoke('validate_proxy_config', { config });
+}
+
+// 预览配置变更 (不应用)，列出变化的字段与需要重启服务的项；不传时预览配置文件中的配置
+export async function previewProxyConfigChange(config?: ProxyConfig): Promise<ConfigDiff> {
+    return await invoke('preview_proxy_config_change', { config });
+}
+
+// 生成客户端配置片段；host 默认 127.0.0.1 (其他设备使用时传入本机局域网地址)，baseUrl 指向其他机器上的反代，apiKey 默认为当前配置的 Key
+export async function getClientSnippets(options: { host?: string; baseUrl?: string; apiKey?: string; openaiModel?: string; claudeModel?: string } = {}): Promise<ClientSnippet[]> {
+    return await invoke('get_client_snippets', options);
+}
+
+// 复制客户端接入用的单个值到剪贴板；apiKey 须为主 Key 或账号池 Key，默认主 Key
+export async function copyClientValue(target: ClientCopyTarget, options: { host?: string; apiKey?: string } = {}): Promise<void> {
+    return await invoke('copy_client_value', { target, ...options });
+}
+
+// 把反代地址与 API Key 写入 Claude Code 的 settings.json (首次写入前自动备份)
+export async function configureClaudeCode(options: { host?: string; apiKey?: string; models?: ClaudeModelMapping } = {}): Promise<ClaudeCodeStatus> {
+    return await invoke('configure_claude_code', options);
+}
+
+// 恢复配置前的 Claude Code settings.json
+export async function undoClaudeCodeConfig(): Promise<ClaudeCodeStatus> {
+    return await invoke('undo_claude_code_config');
+}
+
+export async function getClaudeCodeStatus(): Promise<ClaudeCodeStatus> {
+    return await invoke('get_claude_code_status');
+}
+
+// 建立远程访问隧道 (反代需已启动)
+export async function startTunnel(): Promise<TunnelStatus> {
+    return await invoke('start_tunnel');
+}
+
+export async function stopTunnel(): Promise<TunnelStatus> {
+    return await invoke('stop_tunnel');
+}
+
+export async function getTunnelStatus(): Promise<TunnelStatus> {
+    return await invoke('get_tunnel_status');
+}
+
+// 移动端扫码配置二维码；host 默认自动探测局域网地址，temporary 默认签发临时 Key (ttlMinutes 默认 1 天)
+export async function generateMobileClientQr(options: { host?: string; temporary?: boolean; ttlMinutes?: number } = {}): Promise<MobileClientQr> {
+    return await invoke('generate_mobile_client_qr', options);
+}
+
+// 吊销所有扫码签发的临时 Key，返回吊销数量
+export async function revokeTemporaryApiKeys(): Promise<number> {
+    return await invoke('revoke_temporary_api_keys');
+}
+
+// 取出待处理的深度链接操作 (取出后清空)
+export async function takePendingDeepLink(): Promise<DeepLinkAction | null> {
+    return await invoke('take_pending_deep_link');
+}
+
+// 当前生效的环境变量覆盖 (只作用于运行中的服务，不写回配置文件)
+export async function getEnvOverrides(): Promise<EnvOverride[]> {
+    return await invoke('get_env_overrides');
+}
+
+// 从配置文件重新加载并热应用到运行中的反代 (不中断进行中的请求)
+export async function reloadProxyConfig(): Promise<ConfigReloadResult> {
+    return await invoke('reload_proxy_config');
+}
+
+export async function listConfigPresets(): Promise<ConfigPresetInfo[]> {
+    return await invoke('list_config_presets');
+}
+
+// 将当前反代配置保存为方案 (同名覆盖)，返回去除首尾空白后的名称
+export async function saveConfigPreset(name: string): Promise<string> {
+    return await invoke('save_config_preset', { name });
+}
+
+export async function deleteConfigPreset(name: string): Promise<void> {
+    return await invoke('delete_config_preset', { name });
+}
+
+// 切换配置方案；端口或监听地址变化时会重启反代服务
+export async function switchConfigPreset(name: string): Promise<PresetSwitchResult> {
+    return await invoke('switch_config_preset', { name });
+}
+
+// 最近 windowDays 天 (默认 7) 的每日运行时长与可用率
+export async function getUptimeSummary(windowDays?: number): Promise<UptimeSummary> {
+    return await invoke('get_uptime_summary', { windowDays });
+}
+
+// 导出诊断包 (zip，已去除凭证)，用于附在问题反馈中
+export async function exportDiagnosticsBundle(path: string): Promise<DiagnosticsSummary> {
+    return await invoke('export_diagnostics_bundle', { path });
+}
+
+// 导出完整配置 (含配置方案)；提供口令时凭证加密导出，否则不含凭证
+export async function exportConfigBundle(path: string, passphrase?: string): Promise<ConfigExportSummary> {
+    return await invoke('export_config_bundle', { path, passphrase });
+}
+
+// 导入配置包并立即生效；未带凭证的字段保留本机现有值
+export async function importConfigBundle(path: string, passphrase?: string): Promise<ConfigImportReport> {
+    return await invoke('import_config_bundle', { path, passphrase });
+}
+
+// 配置变更历史 (新版本在前)
+export async function listConfigHistory(): Promise<ConfigHistoryItem[]> {
+    return await invoke('list_config_history');
+}
+
+// 回滚到历史版本并立即生效 (监听端口与地址变化需重启服务)
+export async function rollbackConfig(id: string): Promise<ConfigReloadResult> {
+    return await invoke('rollback_config', { id });
+}
+
+export async function getConfigSyncSettings(): Promise<ConfigSyncSettings> {
+    return await invoke('get_config_sync_settings');
+}
+
+// 设置远程同步后端 (传 null 关闭同步)，会重置同步基线
+export async function setConfigSyncBackend(backend: SyncBackend | null): Promise<ConfigSyncSettings> {
+    return await invoke('set_config_sync_backend', { backend });
+}
+
+// 与远程后端同步；两边都有修改时返回 conflict，再用 force 选择保留哪一边
+export async function syncConfig(force?: 'push' | 'pull'): Promise<ConfigSyncReport> {
+    return await invoke('sync_config', { force });
+}
+
+// 通过反代对 OpenAI / Claude / Gemini 入口各发送一个小请求，按阶段报告结果
+export async function runProxySelfTest(model?: string): Promise<SelfTestReport> {
+    return await invoke('run_proxy_self_test', { model });
+}
+
+// 压测反代 (需先启动服务)，返回吞吐与延迟分位数
+export async function runProxyBenchmark(options?: BenchmarkOptions): Promise<BenchmarkReport> {
+    return await invoke('run_proxy_benchmark', { options });
+}
+
+// 列出崩溃报告；pendingOnly 时只返回尚未查看的 (启动时提示)
+export async function listCrashReports(pendingOnly?: boolean): Promise<CrashReport[]> {
+    return await invoke('list_crash_reports', { pendingOnly });
+}
+
+export async function acknowledgeCrashReport(id: string): Promise<void> {
+    return await invoke('acknowledge_crash_report', { id });
+}
+
+// 预填崩溃信息的 GitHub Issue 链接
+export async function getCrashReportIssueUrl(id: string): Promise<string> {
+    return await invoke('get_crash_report_issue_url', { id });
+}
+
+export async function getLatencyStats(): Promise<LatencyReport> {
+    return await invoke('get_latency_stats');
+}
+
+export async function getLogFilter(): Promise<string> {
+    return await invoke('get_log_filter');
+}
+
+// EnvFilter 语法 (如 "info,proxy::mappers=debug")，空字符串恢复默认；仅本次运行生效
+export async function setLogFilter(filter: string): Promise<string> {
+    return await invoke('set_log_filter', { filter });
+}
+
+export async function queryAppLogs(query: AppLogQuery): Promise<AppLogPage> {
+    return await invoke('query_app_logs', { query });
+}
+
+// 电池供电 / 计费网络 / 离线时后台任务 (含自动刷新配额) 暂停
+export async function getPowerStatus(): Promise<PowerStatus> {
+    return await invoke('get_power_status');
+}
+
+// 发送测试事件，返回 HTTP 状态码
+export async function testWebhook(webhook: WebhookConfig): Promise<number> {
+    return await invoke('test_webhook', { webhook });
+}
+
+// 向通知渠道发送一条测试消息
+export async function testNotificationChannel(channel: NotificationChannel): Promise<void> {
+    return await invoke('test_notification_channel', { channel });
+}
+
+export async function listScheduledTasks(): Promise<ScheduledTaskStatus[]> {
+    return await invoke('list_scheduled_tasks');
+}
+
+export async function updateScheduledTask(kind: ScheduledTaskKind, enabled: boolean, schedule: string): Promise<void> {
+    await invoke('update_scheduled_task', { kind, enabled, schedule });
+}
+
+// 立即运行一次 (不影响计划)
+export async function runScheduledTask(kind: ScheduledTaskKind): Promise<ScheduledTaskRun> {
+    return await invoke('run_scheduled_task', { kind });
+}
+
+export async function getScheduledTaskHistory(kind?: ScheduledTaskKind, limit?: number): Promise<ScheduledTaskRun[]> {
+    return await invoke('get_scheduled_task_history', { kind, limit });
+}
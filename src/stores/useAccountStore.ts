@@ -26,6 +26,8 @@ interface AccountState {
     importV1Accounts: () => Promise<void>;
     importFromDb: () => Promise<void>;
     importFromCustomDb: (path: string) => Promise<void>;
+    importGeminiCliCredentials: () => Promise<void>;
+    importToolCredentials: (path: string) => Promise<void>;
     syncAccountFromDb: () => Promise<void>;
     toggleProxyStatus: (accountId: string, enable: boolean, reason?: string) => Promise<void>;
     warmUpAccounts: () => Promise<string>;
@@ -216,6 +218,22 @@ export const useAccountStore = create<AccountState>((set, get) => ({
         }
     },
 
+    importGeminiCliCredentials: async () => {
+        set({ loading: true, error: null });
+        try {
+            const report = await accountService.importGeminiCliCredentials();
+            const rejected = report.rows.find(r => r.status === 'failed' || r.status === 'invalid');
+            if (rejected) {
+                throw new Error(rejected.message || rejected.status);
+            }
+            await get().fetchAccounts();
+            set({ loading: false });
+        } catch (error) {
+            set({ error: String(error), loading: false });
+            throw error;
+        }
+    },
+
     importFromDb: async () => {
         set({ loading: true, error: null });
         try {
@@ -231,6 +249,22 @@ export const useAccountStore = create<AccountState>((set, get) => ({
         }
     },
 
+    importToolCredentials: async (path: string) => {
+        set({ loading: true, error: null });
+        try {
+            const report = await accountService.importToolCredentials(path);
+            const rejected = report.rows.find(r => r.status === 'failed' || r.status === 'invalid');
+            if (rejected && report.imported + report.updated === 0) {
+                throw new Error(rejected.message || rejected.status);
+            }
+            await get().fetchAccounts();
+            set({ loading: false });
+        } catch (error) {
+            set({ error: String(error), loading: false });
+            throw error;
+        }
+    },
+
     importFromCustomDb: async (path: string) => {
         set({ loading: true, error: null });
         try {
@@ -9,13 +9,29 @@ export interface Account {
     disabled?: boolean;
     disabled_reason?: string;
     disabled_at?: number;
+    needs_reauth?: boolean;
+    quarantined?: boolean;
+    quarantine_reason?: string;
+    quarantined_at?: number;
     proxy_disabled?: boolean;
     proxy_disabled_reason?: string;
     proxy_disabled_at?: number;
+    priority?: number; // 反代调度优先级，数值越大越优先
+    usage_windows?: UsageWindow[]; // 反代可用时段 (本地时间)，为空表示全天可用
+    egress_proxy?: string; // 独立出口代理 (http/https/socks5)，为空时使用全局上游代理
+    project_id_override?: string; // 固定使用的 project_id，设置后跳过自动解析
+    projects?: string[]; // 账号可访问的全部项目 (多项目选择策略)
+    tags?: string[]; // 自定义标签，账号池可用 "tag:<标签>" 引用
     created_at: number;
     last_used: number;
 }
 
+// "HH:MM"，end 早于 start 表示跨越午夜
+export interface UsageWindow {
+    start: string;
+    end: string;
+}
+
 export interface TokenData {
     access_token: string;
     refresh_token: string;
@@ -38,6 +54,311 @@ export interface ModelQuota {
     reset_time: string;
 }
 
+export interface AccountEvent {
+    id: number;
+    account_id: string;
+    kind:
+        | 'created'
+        | 'merged'
+        | 'refreshed'
+        | 'rate_limited'
+        | 'disabled'
+        | 'proxy_disabled'
+        | 'quarantined'
+        | 'reenabled'
+        | 'archived'
+        | 'restored'
+        | 'deleted'
+        | 'verified'
+        | 'verification_failed'
+        | 'project_created'
+        | 'project_create_failed'
+        | string;
+    detail?: string | null;
+    created_at: number;
+}
+
+// 已删除 (归档) 的账号，Token 仍保留
+export interface ArchivedAccount {
+    id: string;
+    email: string;
+    name?: string | null;
+    archived_at: number;
+}
+
+export interface AccountCooldown {
+    account_id: string;
+    email: string;
+    state: 'cooling' | 'active';
+    reason?: string | null;
+    model?: string | null;
+    remaining_seconds?: number | null;
+    usable_at?: number | null; // 预计恢复可用的时间 (Unix 秒)
+}
+
+// proxy://pool-exhausted 事件载荷
+export interface PoolExhaustedEvent {
+    cooling: number;
+    quarantined: number;
+    retry_after_seconds?: number | null;
+}
+
+export interface AccountUsage {
+    account_email: string;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+    last_request_at: number;
+}
+
+// 按 API Key / 模型 / 账号汇总的 Token 用量 (key_hint 为 Key 末 4 位)
+export interface TokenUsageSummary {
+    key_id: string;
+    key_hint: string;
+    model: string;
+    account_email: string;
+    requests: number;
+    input_tokens: number;
+    output_tokens: number;
+    estimated_cost_usd?: number | null; // 按单价表估算，无匹配单价时为空
+}
+
+// proxy://live-stats 事件中的单个已完成请求
+export interface FinishedRequest {
+    id: string;
+    timestamp: number;
+    model?: string | null;
+    account_email?: string | null;
+    status: number;
+    duration: number;
+    input_tokens: number;
+    output_tokens: number;
+}
+
+// proxy://live-stats 事件载荷 (约每秒一次，为自上次推送以来的增量)
+export interface LiveStatsEvent {
+    timestamp: number;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+    rps: number; // 最近 10 秒平均
+    recent: FinishedRequest[];
+}
+
+export type UsageReportPeriod = 'day' | 'week' | 'month';
+
+// 按周期 (UTC) 聚合的用量报表行
+export interface UsageReportRow {
+    period: string; // 2024-06-01 / 2024-W22 / 2024-06
+    key_id: string;
+    key_hint: string;
+    model: string;
+    account_email: string;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+    estimated_cost_usd?: number | null;
+}
+
+// 按 API Key 汇总的用量 (使用方视角)
+export interface KeyModelUsage {
+    model: string;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+}
+
+export interface KeyUsageSummary {
+    key_id: string; // SHA-256 指纹前 16 位，未携带 Key 时为空
+    key_hint: string; // Key 末 4 位
+    requests: number;
+    errors: number;
+    error_rate: number;
+    input_tokens: number;
+    output_tokens: number;
+    estimated_cost_usd?: number | null;
+    last_used_at: number; // Unix 秒
+    models: KeyModelUsage[];
+}
+
+export interface ModelRoute {
+    upstream_model: string;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+}
+
+// 客户端请求的模型名 -> 映射 / 降级后实际使用的上游模型
+export interface ModelMappingUsage {
+    requested_model: string;
+    requests: number;
+    errors: number;
+    input_tokens: number;
+    output_tokens: number;
+    remapped: boolean;
+    routes: ModelRoute[];
+}
+
+// 单个账号在统计窗口内的错误分类统计
+export interface AccountErrorBreakdown {
+    account_email: string;
+    requests: number;
+    errors: number;
+    rate_limit: number; // 429 / 配额耗尽
+    auth: number; // 401 / 403 / Token 失效
+    safety: number; // 内容安全拦截
+    network: number; // 网络 / 网关 / 超时
+    server: number; // 其他 5xx
+    client: number; // 其他 4xx
+    last_error_at?: number | null;
+}
+
+export interface AccountUsageStats {
+    account_id: string;
+    account_email: string;
+    window_requests: number;
+    window_errors: number;
+    window_input_tokens: number;
+    window_output_tokens: number;
+    total_requests: number;
+    total_errors: number;
+    total_input_tokens: number;
+    total_output_tokens: number;
+    first_request_at?: number | null;
+    last_request_at?: number | null;
+    last_error_at?: number | null;
+}
+
+export type HealthErrorKind =
+    | 'invalid_grant'
+    | 'forbidden'
+    | 'rate_limited'
+    | 'quota_exhausted'
+    | 'server_error'
+    | 'network'
+    | 'unknown';
+
+export type HealthStage = 'token_refresh' | 'project_resolution' | 'generation';
+
+export interface StageResult {
+    stage: HealthStage;
+    ok: boolean;
+    latency_ms?: number | null;
+    error?: string | null;
+}
+
+export interface AccountHealthReport {
+    account_id: string;
+    email: string;
+    healthy: boolean;
+    tier?: string | null;
+    refresh_latency_ms?: number | null;
+    request_latency_ms?: number | null;
+    status?: number | null;
+    error_kind?: HealthErrorKind | null;
+    error?: string | null;
+    stages: StageResult[];
+    summary?: string | null; // 按界面语言生成的结果说明
+}
+
+// 账号当前项目的配额状态
+export interface ProjectQuotaStatus {
+    account_id: string;
+    email: string;
+    project_id?: string | null;
+    forbidden: boolean;
+    models: ModelQuota[];
+    lowest_model?: string | null;
+    lowest_percentage?: number | null;
+    near_limit: boolean;
+    error?: string | null;
+}
+
+export type ImportRowStatus = 'imported' | 'updated' | 'duplicate' | 'invalid' | 'failed';
+
+export interface ImportRowResult {
+    row: number;
+    email?: string | null;
+    status: ImportRowStatus;
+    message?: string | null;
+}
+
+export interface BatchImportReport {
+    total: number;
+    imported: number;
+    updated: number;
+    skipped: number;
+    failed: number;
+    rows: ImportRowResult[];
+}
+
+export interface RestoreReport {
+    restored: number;
+    updated: number;
+    errors: string[];
+}
+
+export interface ProfileInfo {
+    name: string;
+    active: boolean;
+    account_count: number;
+}
+
+export interface AccountShare {
+    email: string;
+    share: string; // "agshare1." 开头的口令加密分享串
+    qr_svg: string;
+    link: string; // antigravity-manager://import-account 深度链接
+}
+
+// 本机 Antigravity IDE 登录状态
+export interface IdeCredentialStatus {
+    db_path?: string | null;
+    found: boolean;
+    logged_in: boolean;
+    imported_account_id?: string | null;
+    imported_email?: string | null;
+}
+
+// storage.json 备份
+export interface StorageBackup {
+    file_name: string;
+    created_at: number; // unix 秒
+    profile?: DeviceProfile | null;
+}
+
+export interface DeviceReset {
+    backup: string; // 重置前的备份文件名
+    profile: DeviceProfile;
+}
+
+// IDE 配置 (settings.json / storage.json / state.vscdb) 备份
+export interface IdeBackup {
+    id: string;
+    created_at: number; // unix 秒
+    reason: 'manual' | 'scheduled' | 'before_switch' | 'before_restore';
+    files: string[]; // 相对 IDE User 目录
+    size_bytes: number;
+}
+
+// Antigravity IDE 安装与数据目录
+export interface IdeInstallation {
+    installed: boolean;
+    executable?: string | null;
+    version?: string | null;
+    running: boolean;
+    portable: boolean; // 便携模式 (数据在可执行文件旁的 data/user-data)
+    user_data_dir?: string | null; // --user-data-dir 指定的数据目录
+    storage_path?: string | null; // storage.json (设备指纹)
+    state_db_path?: string | null; // state.vscdb (登录凭据)
+    state_db_exists: boolean;
+}
+
 export interface DeviceProfile {
     machine_id: string;
     mac_machine_id: string;
@@ -53,3 +374,41 @@ export interface DeviceProfileVersion {
     is_current?: boolean;
 }
 
+
+export type TokenRefreshState = 'valid' | 'expiring' | 'expired' | 'refreshing' | 'refresh_failed';
+
+// account://token-status 事件载荷 (数组)
+export interface TokenStatus {
+    account_id: string;
+    email: string;
+    expires_at: number;
+    seconds_remaining: number;
+    state: TokenRefreshState;
+    last_refresh_at?: number | null;
+    last_error?: string | null;
+}
+
+// account://token-refresh-progress 事件载荷
+export interface TokenRefreshProgress {
+    completed: number;
+    total: number;
+    account_id: string;
+    email: string;
+    success: boolean;
+    error?: string | null;
+}
+
+export interface AttentionItem {
+    account_id: string;
+    email: string;
+    needs_reauth: boolean;
+    reason: string;
+}
+
+export interface BulkRefreshReport {
+    total: number;
+    refreshed: number;
+    failed: number;
+    skipped: number;
+    needs_attention: AttentionItem[];
+}
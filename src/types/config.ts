@@ -1,12 +1,473 @@
 export interface UpstreamProxyConfig {
     enabled: boolean;
     url: string;
+    username?: string; // 代理认证 (可选)
+    password?: string;
+}
+
+// 上游请求重试 (网络错误与 5xx，指数退避 + 随机抖动)
+export interface UpstreamRetryConfig {
+    enabled: boolean;
+    max_attempts: number; // 含首次请求
+    base_delay_ms: number;
+    max_delay_ms: number;
+    max_stream_resumes?: number; // 流式响应中途断开后的续传次数
+}
+
+export interface UpstreamTimeoutConfig {
+    connect_secs: number;
+    non_stream_secs: number;
+    stream_first_byte_secs: number; // 流式请求等待响应头
+    stream_secs: number;
+}
+
+// 错误率告警，触发时推送 proxy://alert 事件 (ProxyAlert)
+export interface AlertConfig {
+    enabled: boolean;
+    error_rate_threshold: number; // 0.2 = 20%，0 表示不检查
+    error_rate_window_secs: number;
+    min_requests: number; // 样本少于该值时不计算错误率
+    consecutive_429: number; // 0 表示不检查
+    cooldown_secs: number; // 同类告警最短间隔
+    webhook_url?: string | null; // 可选，POST JSON
+}
+
+// 用量异常检测，告警同样通过 proxy://alert 推送，复用 alerts.webhook_url
+export interface AnomalyConfig {
+    enabled: boolean;
+    window_secs: number;
+    spike_factor: number; // 每小时 Token 速率达到平时的多少倍时告警，0 表示不检查
+    min_hourly_tokens: number;
+    baseline_hours: number;
+    key_share_threshold: number; // 单个 Key 占请求比例，0 表示不检查
+    key_share_min_requests: number;
+    cooldown_secs: number;
+}
+
+export interface ProxyAlert {
+    kind: 'error_rate' | 'consecutive429' | 'token_spike' | 'key_dominance' | 'unauthenticated_exposure';
+    timestamp: number;
+    value: number;
+    threshold: number;
+    message: string;
+}
+
+// 实时请求检查器，开启后每个请求推送 proxy://inspector 事件 (InspectorEvent)
+export interface InspectorConfig {
+    enabled: boolean;
+    max_content_chars: number;
+    redact_patterns: string[]; // 额外的脱敏正则
+}
+
+// 对话记录归档，保存到数据目录 transcripts/<日期>/<Key 指纹>/
+export interface TranscriptConfig {
+    enabled: boolean;
+    format: 'json' | 'markdown' | 'both';
+}
+
+// 内存 / 磁盘上限，超出时淘汰最旧或最久未使用的条目 (0 = 不限制)
+export interface MemoryLimitsConfig {
+    log_buffer_mb: number; // 内存中的请求日志
+    log_db_mb: number; // 请求日志库 (proxy_logs.db)
+    transcripts_mb: number; // 对话归档目录
+    signature_cache_entries: number; // 签名缓存 (会话层，工具层 / 模型族层为其 1/2、1/5)
+    latency_series: number; // 延迟统计的 (模型, 账号) 序列
+}
+
+// 请求钩子: 通过系统 shell 运行命令，请求摘要以 JSON 写入标准输入
+export interface RequestHookConfig {
+    on_request?: string;
+    on_complete?: string;
+    transform: boolean; // 等待 on_request 并用其 JSON 输出替换请求体，退出码非 0 时拒绝请求
+    timeout_secs: number;
+}
+
+// 远程访问隧道: SSH 反向隧道或 Cloudflare Quick Tunnel，隧道运行期间强制要求 API Key
+export interface TunnelConfig {
+    auto_start: boolean; // 反代启动时自动建立
+    provider: 'ssh' | 'cloudflared';
+    ssh_target: string; // user@host
+    ssh_port: number;
+    ssh_identity_file?: string | null;
+    remote_port: number; // 服务器上监听的端口
+    remote_bind_address: string; // 为空时只监听服务器本机
+    public_url?: string | null; // 对外访问地址 (为空时按 http://<host>:<remote_port> 显示)
+    cloudflared_path?: string | null;
+}
+
+export interface TunnelStatus {
+    running: boolean;
+    provider?: 'ssh' | 'cloudflared' | null;
+    public_url?: string | null;
+    started_at?: number | null;
+    last_error?: string | null;
+}
+
+// 账号池之外的上游后端，模型命中 models 模式或使用 `<name>/<model>` 时直接转发
+export type ProviderKind =
+    | { type: 'openai_compatible'; base_url: string; api_key: string }
+    | { type: 'gemini_api_key'; api_key: string; base_url?: string | null }
+    | { type: 'vertex_service_account'; service_account: string; project_id?: string | null; location: string }; // service_account: JSON 文件路径或内容
+
+export type ProviderConfig = ProviderKind & {
+    name: string;
+    enabled: boolean;
+    models: string[]; // 支持 * 通配
+};
+
+export interface InspectorEvent {
+    id: string;
+    timestamp: number;
+    method: string;
+    path: string;
+    status: number;
+    duration: number;
+    model?: string | null;
+    mapped_model?: string | null;
+    account_email?: string | null;
+    stream: boolean;
+    message_count: number;
+    prompt_preview?: string | null; // 已脱敏并截断
+    response_preview?: string | null;
+    input_tokens?: number | null;
+    output_tokens?: number | null;
+}
+
+export interface UpstreamPoolConfig {
+    max_idle_per_host: number;
+    idle_timeout_secs: number;
+    http2: boolean; // ALPN 协商 HTTP/2，不支持时回退 HTTP/1.1
+    http2_keep_alive_secs: number; // 0 = 关闭
+    http2_keep_alive_timeout_secs: number;
+    http2_adaptive_window: boolean;
+}
+
+// 上游 TLS 校验 (自定义 CA 证书包与证书固定)，配置无效时拒绝所有上游连接
+export interface UpstreamTlsConfig {
+    ca_bundle_path?: string | null; // PEM 证书包路径
+    ca_bundle_only: boolean; // 只信任证书包中的 CA
+    pinned_sha256: string[]; // 叶子证书 SHA-256 指纹 (十六进制)
+}
+
+// 上游连接复用统计
+export interface ConnectionStats {
+    requests: number;
+    new_connections: number;
+    reused_connections: number;
+    reuse_rate: number;
+}
+
+// 单个上游端点的延迟与错误率 (window_* 为最近 10 分钟)
+export interface UpstreamEndpointStats {
+    base_url: string;
+    requests: number;
+    errors: number;
+    window_requests: number;
+    window_errors: number;
+    error_rate: number;
+    avg_latency_ms?: number | null;
+    p50_latency_ms?: number | null;
+    p95_latency_ms?: number | null;
+    ewma_latency_ms?: number | null;
+    last_status?: number | null;
+    last_error?: string | null;
+    last_error_at?: number | null;
+    healthy: boolean;
+}
+
+export type SelfTestStage = 'auth' | 'account_selection' | 'upstream' | 'conversion';
+export type SelfTestProtocol = 'openai' | 'claude' | 'gemini';
+
+export interface SelfTestStageResult {
+    stage: SelfTestStage;
+    ok: boolean;
+    detail?: string | null;
+}
+
+export interface ProtocolSelfTest {
+    protocol: SelfTestProtocol;
+    endpoint: string;
+    passed: boolean;
+    status?: number | null;
+    account_email?: string | null;
+    mapped_model?: string | null;
+    latency_ms: number;
+    stages: SelfTestStageResult[]; // 失败的阶段之后不再继续
+}
+
+// 端到端自检报告
+export interface SelfTestReport {
+    base_url: string;
+    model: string;
+    started_proxy: boolean; // 为自检临时启动了反代 (结束后已停止)
+    passed: boolean;
+    protocols: ProtocolSelfTest[];
+}
+
+export interface BenchmarkOptions {
+    protocol?: SelfTestProtocol;
+    model?: string | null;
+    concurrency?: number; // 1-256，默认 8
+    total_requests?: number; // 1-10000，默认 100
+    mock_upstream?: boolean; // 使用本机 mock 上游 (期间所有生成请求都改发到 mock)
+    mock_latency_ms?: number;
+}
+
+export interface LatencyPercentiles {
+    min: number;
+    avg: number;
+    p50: number;
+    p90: number;
+    p99: number;
+    max: number;
+}
+
+// 压测报告
+export interface BenchmarkReport {
+    protocol: SelfTestProtocol;
+    model: string;
+    concurrency: number;
+    total_requests: number;
+    mock_upstream: boolean;
+    succeeded: number;
+    failed: number;
+    duration_ms: number;
+    throughput_rps: number;
+    latency?: LatencyPercentiles | null;
+    status_counts: Record<string, number>; // 状态码 -> 请求数 (连接失败为 0)
+    error_samples: string[];
+}
+
+// 本地崩溃报告
+export interface CrashReport {
+    id: string;
+    timestamp: number; // Unix 毫秒
+    app_version: string;
+    os: string;
+    arch: string;
+    thread: string;
+    message: string;
+    location?: string | null;
+    backtrace: string;
+    log_tail: string;
+    acknowledged: boolean;
+}
+
+// 诊断包导出结果
+export interface DiagnosticsSummary {
+    path: string;
+    files: string[]; // 打包的文件名
+    size_bytes: number;
+}
+
+// 配置包导出结果
+export interface ConfigExportSummary {
+    path: string;
+    secret_fields: number;
+    secrets_included: boolean; // 凭证已加密导出 (未设置口令时不导出)
+    presets: number;
+}
+
+export interface ConfigImportReport {
+    secrets_restored: number; // 从配置包解密恢复的凭证字段数
+    secrets_kept_local: number; // 配置包未带凭证、保留本机值的字段数
+    presets: number;
+}
+
+// 远程配置同步 (不含凭证与账号 Token)
+export type SyncBackend =
+    | { kind: 'gist'; gist_id?: string; token: string } // gist_id 为空时首次上传自动创建私有 Gist
+    | { kind: 'webdav'; url: string; username?: string; password?: string }
+    | { kind: 's3'; endpoint: string; region?: string; bucket: string; object_key?: string; access_key_id: string; secret_access_key: string };
+
+export interface ConfigSyncSettings {
+    backend: SyncBackend | null;
+    last_synced_fingerprint: string | null;
+    last_synced_at: number | null; // Unix 秒
+}
+
+export interface ConfigSyncReport {
+    outcome: 'up_to_date' | 'pushed' | 'pulled' | 'conflict';
+    local_changed: boolean;
+    remote_changed: boolean;
+    synced_at: number | null;
+}
+
+// 配置变更历史
+export interface ConfigHistoryItem {
+    id: string;
+    timestamp: number; // Unix 毫秒
+    source: 'app' | 'external' | 'rollback';
+    changed_fields: string[]; // 相对上一版本变化的字段，如 proxy.port
+    current: boolean; // 与当前配置相同
+}
+
+export interface AccountLoad {
+    account_id: string;
+    email: string;
+    in_flight: number;
+}
+
+// 当前负载 (`proxy://load` 事件载荷)
+export interface ProxyLoad {
+    timestamp: number;
+    in_flight: number;
+    queued: number;
+    accounts: AccountLoad[];
+}
+
+// 配置热重载结果 (`proxy://config-reloaded` 事件载荷)
+export interface ConfigReloadResult {
+    applied: boolean; // 服务正在运行且已应用
+    restart_required: string[]; // 需要重启服务才能生效的字段
+}
+
+// 配置校验
+export interface ConfigIssue {
+    severity: 'error' | 'warning';
+    field: string; // 点分隔的字段路径
+    code: string; // 如 duplicate_alias / invalid_regex / bind_failed
+    message: string;
+}
+
+export interface ConfigValidationReport {
+    valid: boolean; // 没有错误 (可以有警告)
+    errors: number;
+    warnings: number;
+    issues: ConfigIssue[];
+}
+
+// 配置变更预览
+export interface ConfigChange {
+    field: string; // 点分隔的字段路径
+    kind: 'added' | 'removed' | 'modified';
+    before?: unknown; // 凭证类字段为 [REDACTED]
+    after?: unknown;
+    restart_required: boolean;
+}
+
+export interface ConfigDiff {
+    against_running: boolean; // 对比的是运行中服务的配置 (否则是已保存的配置)
+    changes: ConfigChange[];
+    restart_required: string[];
+}
+
+// 客户端配置片段
+export interface ClientSnippet {
+    client: string; // claude_code_env / claude_code_settings / cline / roo_code / continue / openai_env / openai_python / openai_node
+    title: string;
+    language: 'bash' | 'json' | 'yaml' | 'python' | 'typescript' | 'text';
+    file?: string | null; // 片段应放入的文件
+    content: string;
+}
+
+// Claude Code 模型映射 (为空的项不写入)
+export interface ClaudeModelMapping {
+    model?: string; // ANTHROPIC_MODEL，默认 claude-sonnet-4-5
+    opus_model?: string;
+    sonnet_model?: string;
+    haiku_model?: string;
+}
+
+// Claude Code settings.json 的当前状态
+export interface ClaudeCodeStatus {
+    settings_path: string;
+    exists: boolean;
+    base_url?: string | null; // 当前的 ANTHROPIC_BASE_URL
+    model?: string | null;
+    has_backup: boolean; // 是否可以撤销
+    backup_created_at?: number | null;
+}
+
+// 一键复制到剪贴板的值 (anthropic_env 为一行 export ANTHROPIC_BASE_URL=.. ANTHROPIC_AUTH_TOKEN=..)
+export type ClientCopyTarget = 'base_url' | 'openai_base_url' | 'api_key' | 'anthropic_env';
+
+// 移动端扫码配置 (局域网地址 + API Key)
+export interface MobileClientQr {
+    base_url: string;
+    openai_base_url: string;
+    api_key: string;
+    expires_at?: number | null; // 临时 Key 的过期时间 (unix 秒)，使用主 Key 时为空
+    payload: string; // 二维码编码的 JSON
+    qr_svg: string;
+}
+
+// antigravity-manager:// 深度链接预填的操作
+export type DeepLinkAction =
+    | { action: 'import_account'; share: string }
+    | { action: 'client_setup'; base_url: string; api_key?: string | null };
+
+// 生效的环境变量覆盖 (AGM_PROXY_<字段>，嵌套用 __ 分隔；AGM_BIND)
+export interface EnvOverride {
+    var: string;
+    field: string; // 点分隔的字段路径
+    value: string; // 凭证类字段为 [REDACTED]
+}
+
+// 反代配置方案 (同一档案内的多套反代配置)
+export interface ConfigPresetInfo {
+    name: string;
+    active: boolean; // 最近一次切换到的方案
+    matches_current: boolean; // 与当前反代配置一致
+    port: number;
+    allow_lan_access: boolean;
+    mapping_count: number;
+    pool_count: number;
+    updated_at: number; // Unix 秒
+}
+
+export interface PresetSwitchResult {
+    name: string;
+    applied: boolean;
+    restarted: boolean; // 端口或监听地址变化，已重启服务
+}
+
+// 单日运行时长与可用率 (UTC)
+export interface DailyUptime {
+    date: string; // YYYY-MM-DD
+    uptime_secs: number;
+    elapsed_secs: number; // 当天已经过的秒数 (今天为截至当前)
+    uptime_ratio: number;
+    starts: number;
+    samples: number;
+    available_samples: number;
+    availability: number | null; // 运行期间上游与账号都可用的采样比例
+}
+
+export interface UptimeSummary {
+    days: DailyUptime[];
+    uptime_ratio: number;
+    availability: number | null;
+    running_since: number | null; // Unix 秒
+}
+
+// 延迟直方图 (buckets 与 bounds_ms 对应，最后一个为 +Inf 桶)
+export interface HistogramSnapshot {
+    buckets: number[];
+    count: number;
+    sum_ms: number;
+    avg_ms: number;
+    p50_ms: number | null;
+    p95_ms: number | null;
+}
+
+export interface LatencySeries {
+    model: string;
+    account: string;
+    ttfb: HistogramSnapshot;
+    total: HistogramSnapshot;
+}
+
+export interface LatencyReport {
+    bounds_ms: number[];
+    series: LatencySeries[];
 }
 
 export interface ProxyConfig {
     enabled: boolean;
     allow_lan_access?: boolean;
     auth_mode?: 'off' | 'strict' | 'all_except_health' | 'auto';
+    require_auth_for_lan?: boolean; // 安全模式: 未开启鉴权时拒绝监听非本机地址 (默认开启)
     port: number;
     api_key: string;
     auto_start: boolean;
@@ -14,16 +475,45 @@ export interface ProxyConfig {
     request_timeout: number;
     enable_logging: boolean;
     upstream_proxy: UpstreamProxyConfig;
+    upstream_retry?: UpstreamRetryConfig;
+    upstream_timeouts?: UpstreamTimeoutConfig;
+    upstream_pool?: UpstreamPoolConfig;
+    upstream_tls?: UpstreamTlsConfig;
+    alerts?: AlertConfig; // 错误率 / 连续 429 告警
+    anomaly?: AnomalyConfig; // 用量异常检测
+    inspector?: InspectorConfig; // 实时请求检查器
+    transcripts?: TranscriptConfig; // 对话记录归档
+    memory_limits?: MemoryLimitsConfig; // 日志 / 归档 / 缓存的内存与磁盘上限
+    hooks?: RequestHookConfig; // 请求钩子脚本
+    tunnel?: TunnelConfig; // 远程访问隧道
+    providers?: ProviderConfig[]; // 其他上游后端 (Vertex AI、Gemini API Key、OpenAI 兼容)
     zai?: ZaiConfig;
     scheduling?: StickySessionConfig;
     experimental?: ExperimentalConfig;
+    account_pools?: AccountPool[];
+    default_pool?: string | null; // 未绑定账号池的请求使用的账号池 (管理 MCP 的 switch_pool 可切换)
+}
+
+export interface AccountPool {
+    name: string;
+    accounts: string[]; // 账号 ID、邮箱或 "tag:<标签>"
+    api_keys: string[];
 }
 
 export type SchedulingMode = 'CacheFirst' | 'Balance' | 'PerformanceFirst';
 
+export type SelectionStrategy = 'RoundRobin' | 'LeastRecentlyUsed' | 'LeastErrors' | 'MostRemainingQuota';
+
+export type ProjectStrategy = 'First' | 'Preference' | 'Rotate';
+
 export interface StickySessionConfig {
     mode: SchedulingMode;
     max_wait_seconds: number;
+    strategy?: SelectionStrategy;
+    pinned_account_id?: string | null;
+    max_concurrent_per_account?: number; // 单账号最大并发请求数，0 表示不限制
+    project_strategy?: ProjectStrategy; // 多项目账号的项目选择策略
+    project_preference?: string[]; // Preference 策略的项目优先列表
 }
 
 export type ZaiDispatchMode = 'off' | 'exclusive' | 'pooled' | 'fallback';
@@ -62,8 +552,105 @@ export interface QuotaProtectionConfig {
     monitored_models: string[];
 }
 
+export interface KeepWarmConfig {
+    enabled: boolean;
+    idle_minutes: number; // 账号空闲超过该时长才会被保活
+    max_pings_per_hour: number; // 所有账号合计的每小时保活上限
+}
+
+export type WebhookEvent =
+    | 'account_quarantined'
+    | 'reauth_required'
+    | 'quota_exhausted'
+    | 'pool_exhausted'
+    | 'proxy_started'
+    | 'proxy_stopped'
+    | 'alert';
+
+export interface WebhookConfig {
+    enabled: boolean;
+    url: string;
+    events: WebhookEvent[]; // 为空表示全部事件
+    secret?: string | null; // HMAC-SHA256 签名密钥
+}
+
+export interface PowerSavingConfig {
+    enabled: boolean;
+    pause_on_battery: boolean;
+    pause_on_metered: boolean;
+}
+
+export interface PowerStatus {
+    on_battery: boolean;
+    metered: boolean;
+    online: boolean;
+    paused: boolean; // 后台任务当前是否暂停
+    reason?: 'battery' | 'metered' | 'offline' | null;
+}
+
+export interface NotificationConfig {
+    pool_exhausted: boolean;
+    reauth_required: boolean;
+    proxy_restarted: boolean; // 反代服务意外退出并自动重启
+    quota_threshold: number; // 模型剩余配额降到该百分比及以下时通知，0 表示关闭
+    update_available?: boolean; // 发现新版本
+    channels?: NotificationChannel[]; // 外部通知渠道 (Telegram / Discord / 邮件)
+}
+
+// 外部通知渠道，与系统通知使用相同的开关与冷却期
+export type NotificationChannel = { enabled?: boolean } & (
+    | { type: 'telegram'; bot_token: string; chat_id: string }
+    | { type: 'discord'; webhook_url: string }
+    | {
+        type: 'email';
+        smtp_host: string;
+        smtp_port?: number; // 默认 587
+        security?: 'starttls' | 'tls' | 'none';
+        username?: string;
+        password?: string;
+        from: string;
+        to: string[];
+    }
+);
+
+export interface IdeBackupConfig {
+    enabled: boolean; // 定时备份 (手动与切换前备份不受影响)
+    interval_hours: number;
+    keep: number; // 最多保留的备份数，0 表示不限制
+}
+
 export interface ExperimentalConfig {
     enable_usage_scaling: boolean;
+    enable_request_coalescing?: boolean; // 合并同时到达的相同非流式请求
+}
+
+// 模型单价 (美元 / 百万 Token)，model 以 * 结尾表示前缀匹配
+export interface ModelPrice {
+    model: string;
+    input_per_million: number;
+    output_per_million: number;
+}
+
+// OTLP/HTTP JSON 链路导出
+export interface OtelConfig {
+    enabled: boolean;
+    endpoint: string; // 如 http://127.0.0.1:4318/v1/traces
+    service_name: string;
+    headers?: Record<string, string>;
+}
+
+export interface StatsdConfig {
+    enabled: boolean;
+    address: string; // UDP host:port，如 127.0.0.1:8125
+    prefix: string;
+    dogstatsd: boolean; // 以 tag 发送模型与状态码
+    flush_interval_secs: number;
+}
+
+export interface LogRotationConfig {
+    max_file_size_mb: number;
+    max_files: number; // 含当前 app.log
+    retention_days: number; // 0 = 只按文件数清理
 }
 
 export interface AppConfig {
@@ -77,11 +664,80 @@ export interface AppConfig {
     antigravity_executable?: string; // [NEW] 手动指定的反重力程序路径
     antigravity_args?: string[]; // [NEW] Antigravity 启动参数
     auto_launch?: boolean; // 开机自动启动
+    start_hidden?: boolean; // 启动时不显示窗口 (只有托盘) 并自动启动反代服务
     auto_check_update?: boolean; // 自动检查更新
     update_check_interval?: number; // 更新检查间隔（小时）
+    update_channel?: 'stable' | 'beta'; // 更新通道
     accounts_page_size?: number; // 账号列表每页显示数量,默认 0 表示自动计算
     scheduled_warmup: ScheduledWarmupConfig;
     quota_protection: QuotaProtectionConfig; // [NEW] 配额保护配置
+    keep_warm?: KeepWarmConfig; // 空闲账号保活
+    archive_purge_days?: number; // 已删除 (归档) 账号保留天数，0 表示永久保留
+    auto_create_project?: boolean; // 账号没有可用项目时自动创建
+    model_prices?: ModelPrice[]; // 估算成本用的模型单价表
+    log_rotation?: LogRotationConfig; // 日志滚动策略 (重启后生效)
+    otel?: OtelConfig; // OpenTelemetry 链路导出
+    statsd?: StatsdConfig; // StatsD / DogStatsD 指标推送
+    crash_reports?: boolean; // 崩溃时写入本地崩溃报告 (重启后生效)
+    close_action?: 'tray' | 'ask' | 'quit'; // 关闭主窗口时的行为
+    proxy_toggle_shortcut?: string | null; // 启动 / 停止反代服务的全局快捷键
+    ide_backup?: IdeBackupConfig; // IDE 配置定时备份
+    ide_token_sync?: boolean; // IDE 刷新同一账号的 Token 时自动写回管理器 (默认开启)
+    notifications?: NotificationConfig; // 系统通知 (逐项开关)
+    power_saving?: PowerSavingConfig; // 电池供电 / 计费网络 / 离线时暂停后台任务
+    webhooks?: WebhookConfig[]; // 运行事件 Webhook
     proxy: ProxyConfig;
 }
 
+// 应用日志查看器
+export interface AppLogEntry {
+    timestamp: string;
+    level: 'TRACE' | 'DEBUG' | 'INFO' | 'WARN' | 'ERROR';
+    target: string;
+    message: string;
+}
+
+export interface AppLogQuery {
+    level?: string; // 最低级别
+    module?: string; // target 包含该字符串
+    text?: string; // 不区分大小写
+    offset?: number;
+    limit?: number; // 默认 200，最大 1000
+}
+
+export interface AppLogPage {
+    entries: AppLogEntry[]; // 新的在前
+    total: number;
+    has_more: boolean;
+}
+
+export interface UpdateSettings {
+    auto_check: boolean;
+    last_check_time: number;
+    check_interval_hours: number;
+    channel: 'stable' | 'beta';
+    github_api_mirror?: string | null; // 替代 https://api.github.com 的地址
+    download_mirror?: string | null; // 下载地址前缀 (gh-proxy 形式)
+}
+
+// 定时任务: schedule 为 5 段 cron 表达式 (分 时 日 月 周) 或 @hourly / @daily / @every 30m
+export type ScheduledTaskKind = 'token_refresh' | 'health_check' | 'usage_report' | 'ide_backup' | 'update_check' | 'archive_purge';
+
+export interface ScheduledTaskRun {
+    kind: ScheduledTaskKind;
+    started_at: number; // Unix 秒
+    duration_ms: number;
+    success: boolean;
+    message: string;
+    manual: boolean;
+}
+
+export interface ScheduledTaskStatus {
+    kind: ScheduledTaskKind;
+    enabled: boolean;
+    schedule: string;
+    last_run_at?: number | null;
+    next_run_at?: number | null;
+    running: boolean;
+    last_result?: ScheduledTaskRun | null;
+}